@@ -32,6 +32,12 @@ interface LoginResponse {
   token: string;
 }
 
+// the template a workspace starts from, if connecting creates it
+interface WorkspaceTemplateRequest {
+  id: string;
+  values?: Record<string, unknown>;
+}
+
 export const useAuthStore = defineStore("auth", {
   state: () => ({
     tokens: {} as Record<string, string>,
@@ -74,10 +80,18 @@ export const useAuthStore = defineStore("auth", {
 
     // exchanges a code from the auth portal/api to auth with sdf
     // and initializes workspace/user if necessary
-    async AUTH_CONNECT(payload: { code: string; onDemandAssets: boolean }) {
+    async AUTH_CONNECT(payload: {
+      code: string;
+      onDemandAssets: boolean;
+      workspaceTemplate?: WorkspaceTemplateRequest;
+    }) {
       return new ApiRequest<
         LoginResponse,
-        { code: string; onDemandAssets: boolean }
+        {
+          code: string;
+          onDemandAssets: boolean;
+          workspaceTemplate?: WorkspaceTemplateRequest;
+        }
       >({
         method: "post",
         url: "/session/connect",
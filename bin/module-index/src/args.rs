@@ -115,6 +115,10 @@ pub(crate) struct Args {
     #[arg(long, env)]
     pub(crate) s3_path_prefix: Option<String>,
 
+    /// Stores modules in this local directory instead of s3
+    #[arg(long, env)]
+    pub(crate) storage_path: Option<PathBuf>,
+
     /// The path to the JWT public signing key
     #[arg(long, env)]
     pub(crate) jwt_public_key: Option<String>,
@@ -169,6 +173,10 @@ impl TryFrom<Args> for Config {
             if let Some(s3_path_prefix) = args.s3_path_prefix {
                 config_map.set("s3.path_prefix", s3_path_prefix);
             }
+            if let Some(storage_path) = args.storage_path {
+                config_map.set("storage.backend", "fileSystem");
+                config_map.set("storage.path", storage_path.display().to_string());
+            }
             if let Some(jwt_public_key) = args.jwt_public_key {
                 config_map.set("jwt_signing_public_key_path", jwt_public_key.to_string());
             }
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+const NAME: &str = "si-pkg";
+
+/// Parse, validate, and return the CLI arguments as a typed struct.
+pub(crate) fn parse() -> Args {
+    Args::parse()
+}
+
+/// Inspect, validate and convert System Initiative packages offline
+///
+/// A package can be given either as a `.sipkg` file or as a source directory written by
+/// `si-pkg unpack`, holding a `pkg.json` spec and a `funcs/` directory of `.ts` files.
+#[derive(Parser, Debug)]
+#[command(name = NAME, max_term_width = 100)]
+pub(crate) struct Args {
+    #[command(subcommand)]
+    pub(crate) command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// Prints the root hash of a package
+    Hash {
+        /// The package file or source directory
+        path: PathBuf,
+    },
+    /// Prints the metadata of a package
+    Info {
+        /// The package file or source directory
+        path: PathBuf,
+    },
+    /// Builds a package file from a source directory
+    Pack {
+        /// The source directory to read
+        source_dir: PathBuf,
+        /// The package file to write
+        output: PathBuf,
+    },
+    /// Prints the node tree of a package
+    Tree {
+        /// The package file or source directory
+        path: PathBuf,
+    },
+    /// Writes a package file out as an editable source directory
+    Unpack {
+        /// The package file to read
+        pkg: PathBuf,
+        /// The source directory to write
+        output_dir: PathBuf,
+    },
+    /// Checks a package for dangling func references, bad socket connection annotations and
+    /// default values that do not match their prop kinds
+    Validate {
+        /// The package file or source directory
+        path: PathBuf,
+    },
+}
@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use color_eyre::{eyre::eyre, Result};
+use object_tree::NameStr;
+use si_pkg::{PkgSourceDir, PkgSpec, SiPkg};
+
+use crate::args::Command;
+
+mod args;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    color_eyre::install()?;
+    let args = args::parse();
+
+    match args.command {
+        Command::Hash { path } => {
+            let (pkg, _) = load(&path).await?;
+            println!("{}", pkg.hash()?);
+        }
+        Command::Info { path } => info(&path).await?,
+        Command::Pack { source_dir, output } => {
+            let spec = PkgSourceDir::read(&source_dir).await?;
+            let pkg = SiPkg::load_from_spec(spec)?;
+            tokio::fs::write(&output, pkg.write_to_bytes()?).await?;
+            println!("{}", pkg.hash()?);
+        }
+        Command::Tree { path } => {
+            let (pkg, _) = load(&path).await?;
+            print_tree(&pkg);
+        }
+        Command::Unpack { pkg, output_dir } => {
+            let pkg = SiPkg::load_from_file(&pkg).await?;
+            PkgSourceDir::write(&pkg.to_spec().await?, &output_dir).await?;
+        }
+        Command::Validate { path } => {
+            let (_, spec) = load(&path).await?;
+            let errors = spec.validate();
+            if !errors.is_empty() {
+                for error in &errors {
+                    eprintln!("{error}");
+                }
+                return Err(eyre!("package has {} problem(s)", errors.len()));
+            }
+            println!("ok");
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads a package from a `.sipkg` file or a source directory, along with its spec.
+async fn load(path: &Path) -> Result<(SiPkg, PkgSpec)> {
+    if path.is_dir() {
+        let spec = PkgSourceDir::read(path).await?;
+        let pkg = SiPkg::load_from_spec(spec.clone())?;
+        Ok((pkg, spec))
+    } else {
+        let pkg = SiPkg::load_from_file(path).await?;
+        let spec = pkg.to_spec().await?;
+        Ok((pkg, spec))
+    }
+}
+
+async fn info(path: &Path) -> Result<()> {
+    let (pkg, spec) = load(path).await?;
+    let metadata = pkg.metadata()?;
+
+    println!("name:        {}", metadata.name());
+    println!("version:     {}", metadata.version());
+    println!("kind:        {}", metadata.kind());
+    println!("description: {}", metadata.description());
+    println!("created at:  {}", metadata.created_at());
+    println!("created by:  {}", metadata.created_by());
+    println!("hash:        {}", metadata.hash());
+    println!("schemas:     {}", spec.schemas.len());
+    for schema in &spec.schemas {
+        println!("  {} ({} variant(s))", schema.name, schema.variants.len());
+    }
+    println!("funcs:       {}", spec.funcs.len());
+    println!("depends on:  {}", spec.dependencies.len());
+    for dependency in &spec.dependencies {
+        println!("  {} {}", dependency.name, dependency.version_constraint);
+    }
+
+    Ok(())
+}
+
+fn print_tree(pkg: &SiPkg) {
+    let (graph, root_idx) = pkg.as_petgraph();
+
+    let mut stack = vec![(root_idx, 0)];
+    while let Some((node_idx, depth)) = stack.pop() {
+        let node = &graph[node_idx];
+        println!(
+            "{:indent$}{} {} ({})",
+            "",
+            node.inner().node_kind_str(),
+            node.name(),
+            node.hash(),
+            indent = depth * 2
+        );
+
+        // Neighbors are walked most recently added first, so popping them off the stack visits
+        // the children in the order they were added
+        stack.extend(
+            graph
+                .neighbors(node_idx)
+                .map(|child_idx| (child_idx, depth + 1)),
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+const NAME: &str = "si-snapshot";
+
+/// Parse, validate, and return the CLI arguments as a typed struct.
+pub(crate) fn parse() -> Args {
+    Args::parse()
+}
+
+/// Work on System Initiative workspace snapshots offline
+///
+/// A snapshot is read either as the raw bytes stored in the layer cache, or as the base64 text
+/// returned by the admin `get_snapshot` route. Snapshots are written back in the same form they
+/// were read in.
+#[derive(Parser, Debug)]
+#[command(name = NAME, max_term_width = 100)]
+pub(crate) struct Args {
+    #[command(subcommand)]
+    pub(crate) command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// Checks a snapshot for broken graph invariants
+    ///
+    /// Content hashes are not checked, since there is no content store to look them up in.
+    Fsck {
+        /// The snapshot file
+        path: PathBuf,
+        /// Fixes the violations that can be fixed without losing information
+        #[arg(long)]
+        repair: bool,
+        /// Where to write the repaired snapshot [default: the snapshot file]
+        #[arg(long, short, requires = "repair")]
+        output: Option<PathBuf>,
+        /// Prints the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Migrates a snapshot to the current graph version
+    ///
+    /// Only migrations that do not need to read or write content can run offline, so the snapshot
+    /// is migrated as far as it can be and sdf finishes the job.
+    Migrate {
+        /// The snapshot file
+        path: PathBuf,
+        /// Where to write the migrated snapshot [default: the snapshot file]
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Prints node and edge counts by kind, and the size of each component
+    Stats {
+        /// The snapshot file
+        path: PathBuf,
+        /// Prints the stats as JSON
+        #[arg(long)]
+        json: bool,
+        /// How many of the largest components to print, ignored for JSON
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Prints the part of a snapshot around a single node: everything above it, up to the root,
+    /// and everything below it
+    Subgraph {
+        /// The snapshot file
+        path: PathBuf,
+        /// The id of the node to print the subgraph of
+        #[arg(long)]
+        root: String,
+        #[arg(long, value_enum, default_value_t = SubgraphFormat::Dot)]
+        format: SubgraphFormat,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub(crate) enum SubgraphFormat {
+    /// Graphviz DOT, for `dot -Tsvg`
+    Dot,
+    Json,
+}
@@ -0,0 +1,367 @@
+use std::path::Path;
+
+use base64::prelude::*;
+use color_eyre::{eyre::eyre, Result};
+use dal::workspace_snapshot::graph::{
+    FsckReport, WorkspaceSnapshotGraphDiscriminants, WorkspaceSnapshotGraphStats,
+    WorkspaceSnapshotGraphV4,
+};
+use dal::workspace_snapshot::migrator::SnapshotGraphMigrator;
+use dal::workspace_snapshot::node_weight::NodeWeight;
+use dal::{
+    EdgeWeight, EdgeWeightKindDiscriminants, NodeWeightDiscriminants, Ulid, WorkspaceSnapshotGraph,
+};
+use serde::Serialize;
+
+use crate::args::{Command, SubgraphFormat};
+
+mod args;
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let args = args::parse();
+
+    match args.command {
+        Command::Fsck {
+            path,
+            repair,
+            output,
+            json,
+        } => {
+            let (mut graph, encoding) = read(&path)?;
+            let repaired = if repair { graph.fsck_repair()? } else { vec![] };
+            let report = FsckReport {
+                violations: graph.fsck()?,
+                repaired,
+            };
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                for violation in &report.repaired {
+                    println!("repaired: {violation}");
+                }
+                for violation in &report.violations {
+                    println!("{violation}");
+                }
+            }
+
+            if !report.repaired.is_empty() {
+                write(
+                    output.as_deref().unwrap_or(&path),
+                    WorkspaceSnapshotGraph::V4(graph),
+                    encoding,
+                )?;
+            }
+            if !report.is_clean() {
+                return Err(eyre!("snapshot has {} problem(s)", report.violations.len()));
+            }
+        }
+        Command::Migrate { path, output } => {
+            let (from, to) = migrate(&path, output.as_deref())?;
+
+            if from == to {
+                println!("snapshot is a {from:?} graph, nothing to migrate offline");
+            } else {
+                println!("migrated snapshot from a {from:?} graph to a {to:?} graph");
+            }
+            if to != WorkspaceSnapshotGraph::current_discriminant() {
+                println!(
+                    "migrating a {to:?} graph any further needs the content store, so sdf has to finish it"
+                );
+            }
+        }
+        Command::Stats { path, json, top } => {
+            let (graph, _) = read(&path)?;
+            let stats = graph.stats();
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else {
+                print_stats(&stats, top);
+            }
+        }
+        Command::Subgraph { path, root, format } => {
+            let (graph, _) = read(&path)?;
+            let root_id = Ulid::from_string(&root)?;
+            let subgraph = graph
+                .subgraph(graph.get_node_index_by_id(root_id)?)
+                .ok_or(eyre!("could not build the subgraph around {root_id}"))?;
+
+            match format {
+                SubgraphFormat::Dot => println!("{}", subgraph.tiny_dot()),
+                SubgraphFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&SubgraphJson::new(root_id, &subgraph))?
+                ),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Migrates the snapshot as far as it can be without a content store, writing it to `output` (or
+/// back to `path`) when anything changed. Returns the graph versions before and after.
+fn migrate(
+    path: &Path,
+    output: Option<&Path>,
+) -> Result<(
+    WorkspaceSnapshotGraphDiscriminants,
+    WorkspaceSnapshotGraphDiscriminants,
+)> {
+    let (graph, encoding) = read_any_version(path)?;
+    let from = WorkspaceSnapshotGraphDiscriminants::from(&graph);
+    let graph = SnapshotGraphMigrator::new().migrate_offline(graph)?;
+    let to = WorkspaceSnapshotGraphDiscriminants::from(&graph);
+
+    if from != to {
+        write(output.unwrap_or(path), graph, encoding)?;
+    }
+
+    Ok((from, to))
+}
+
+/// How a snapshot file was encoded, so that it can be written back the same way.
+#[derive(Clone, Copy)]
+enum Encoding {
+    Base64,
+    Raw,
+}
+
+fn read(path: &Path) -> Result<(WorkspaceSnapshotGraphV4, Encoding)> {
+    match read_any_version(path)? {
+        (WorkspaceSnapshotGraph::V4(graph), encoding) => Ok((graph, encoding)),
+        (graph, _) => Err(eyre!(
+            "snapshot is a {:?} graph, which needs to be migrated first",
+            WorkspaceSnapshotGraphDiscriminants::from(graph)
+        )),
+    }
+}
+
+fn read_any_version(path: &Path) -> Result<(WorkspaceSnapshotGraph, Encoding)> {
+    let contents = std::fs::read(path)?;
+    let (bytes, encoding) = match BASE64_STANDARD.decode(contents.trim_ascii()) {
+        Ok(bytes) => (bytes, Encoding::Base64),
+        Err(_) => (contents, Encoding::Raw),
+    };
+
+    Ok((si_layer_cache::db::serialize::from_bytes(&bytes)?, encoding))
+}
+
+fn write(path: &Path, graph: WorkspaceSnapshotGraph, encoding: Encoding) -> Result<()> {
+    let bytes = si_layer_cache::db::serialize::to_vec(&graph)?;
+    match encoding {
+        Encoding::Base64 => std::fs::write(path, BASE64_STANDARD.encode(bytes))?,
+        Encoding::Raw => std::fs::write(path, bytes)?,
+    }
+
+    Ok(())
+}
+
+fn print_stats(stats: &WorkspaceSnapshotGraphStats, top: usize) {
+    println!("nodes: {}", stats.node_count);
+    for (kind, count) in &stats.nodes_by_kind {
+        println!("  {kind:<32} {count:>8}");
+    }
+    println!("edges: {}", stats.edge_count);
+    for (kind, count) in &stats.edges_by_kind {
+        println!("  {:<32} {count:>8}", format!("{kind:?}"));
+    }
+    println!(
+        "components: {} (largest {}, nodes and edges)",
+        stats.components.len(),
+        top.min(stats.components.len())
+    );
+    for component in stats.components.iter().take(top) {
+        println!(
+            "  {:<32} {:>8} {:>8}",
+            component.component_id, component.node_count, component.edge_count
+        );
+    }
+}
+
+/// A subgraph as JSON. Ids are repeated as strings outside of the weights, where they are
+/// serialized as bytes.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SubgraphJson<'a> {
+    root_id: String,
+    nodes: Vec<SubgraphJsonNode<'a>>,
+    edges: Vec<SubgraphJsonEdge<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SubgraphJsonNode<'a> {
+    id: String,
+    lineage_id: String,
+    kind: NodeWeightDiscriminants,
+    merkle_tree_hash: String,
+    weight: &'a NodeWeight,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SubgraphJsonEdge<'a> {
+    source: String,
+    target: String,
+    kind: EdgeWeightKindDiscriminants,
+    weight: &'a EdgeWeight,
+}
+
+impl<'a> SubgraphJson<'a> {
+    fn new(root_id: Ulid, graph: &'a WorkspaceSnapshotGraphV4) -> Self {
+        let id = |node_index| {
+            graph
+                .node_index_to_id(node_index)
+                .map(|id| id.to_string())
+                .unwrap_or_default()
+        };
+
+        Self {
+            root_id: root_id.to_string(),
+            nodes: graph
+                .nodes()
+                .map(|(node, _)| SubgraphJsonNode {
+                    id: node.id().to_string(),
+                    lineage_id: node.lineage_id().to_string(),
+                    kind: node.into(),
+                    merkle_tree_hash: node.merkle_tree_hash().to_string(),
+                    weight: node,
+                })
+                .collect(),
+            edges: graph
+                .edges()
+                .map(|(edge, source, target)| SubgraphJsonEdge {
+                    source: id(source),
+                    target: id(target),
+                    kind: edge.kind().into(),
+                    weight: edge,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use dal::workspace_snapshot::content_address::ContentAddress;
+    use dal::workspace_snapshot::graph::deprecated::v1::{
+        DeprecatedCategoryNodeWeightV1, DeprecatedContentNodeWeightV1, DeprecatedEdgeWeightV1,
+        DeprecatedNodeWeightV1,
+    };
+    use dal::workspace_snapshot::graph::deprecated::DeprecatedWorkspaceSnapshotGraphV1;
+    use dal::workspace_snapshot::node_weight::category_node_weight::CategoryNodeKind;
+    use dal::workspace_snapshot::vector_clock::VectorClock;
+    use dal::EdgeWeightKind;
+    use petgraph::prelude::*;
+    use si_events::merkle_tree_hash::MerkleTreeHash;
+
+    use super::*;
+
+    /// A V1 graph with a root and a component category under it.
+    fn v1_graph(root_id: Ulid, category_id: Ulid) -> DeprecatedWorkspaceSnapshotGraphV1 {
+        let mut graph = StableDiGraph::new();
+        let root_index = graph.add_node(DeprecatedNodeWeightV1::Content(
+            DeprecatedContentNodeWeightV1 {
+                id: root_id,
+                lineage_id: root_id,
+                content_address: ContentAddress::Root,
+                merkle_tree_hash: MerkleTreeHash::nil(),
+                vector_clock_first_seen: VectorClock::empty(),
+                vector_clock_recently_seen: VectorClock::empty(),
+                vector_clock_write: VectorClock::empty(),
+                to_delete: false,
+            },
+        ));
+        let category_index = graph.add_node(DeprecatedNodeWeightV1::Category(
+            DeprecatedCategoryNodeWeightV1 {
+                id: category_id,
+                lineage_id: category_id,
+                kind: CategoryNodeKind::Component,
+                merkle_tree_hash: MerkleTreeHash::nil(),
+                vector_clock_first_seen: VectorClock::empty(),
+                vector_clock_recently_seen: VectorClock::empty(),
+                vector_clock_write: VectorClock::empty(),
+            },
+        ));
+        graph.add_edge(
+            root_index,
+            category_index,
+            DeprecatedEdgeWeightV1 {
+                kind: EdgeWeightKind::new_use(),
+                vector_clock_first_seen: VectorClock::empty(),
+                vector_clock_recently_seen: VectorClock::empty(),
+                vector_clock_write: VectorClock::empty(),
+            },
+        );
+
+        DeprecatedWorkspaceSnapshotGraphV1 {
+            graph,
+            node_index_by_id: HashMap::from([(root_id, root_index), (category_id, category_index)]),
+            node_indices_by_lineage_id: HashMap::from([
+                (root_id, HashSet::from([root_index])),
+                (category_id, HashSet::from([category_index])),
+            ]),
+            root_index,
+        }
+    }
+
+    #[test]
+    fn migrates_a_v1_snapshot_and_writes_it_back() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("snapshot.b64");
+        let (root_id, category_id) = (Ulid::new(), Ulid::new());
+        write(
+            &path,
+            WorkspaceSnapshotGraph::V1(v1_graph(root_id, category_id)),
+            Encoding::Base64,
+        )?;
+
+        assert_eq!(
+            (
+                WorkspaceSnapshotGraphDiscriminants::V1,
+                WorkspaceSnapshotGraphDiscriminants::V2
+            ),
+            migrate(&path, None)?
+        );
+
+        let (graph, encoding) = read_any_version(&path)?;
+        assert!(matches!(encoding, Encoding::Base64));
+        let WorkspaceSnapshotGraph::V2(graph) = graph else {
+            return Err(eyre!("snapshot was not written back as a V2 graph"));
+        };
+        let root = graph.get_node_weight(graph.root())?;
+        assert_eq!(root_id, root.id());
+        let edges: Vec<_> = graph
+            .edges()
+            .map(|(edge, source, target)| -> Result<_> {
+                Ok((
+                    graph.get_node_weight(source)?.id(),
+                    edge.kind().clone(),
+                    graph.get_node_weight(target)?.id(),
+                ))
+            })
+            .collect::<Result<_>>()?;
+        assert_eq!(
+            vec![(root_id, EdgeWeightKind::new_use(), category_id)],
+            edges
+        );
+
+        // Nothing more can be done offline, so the file is left alone
+        let written = std::fs::read(&path)?;
+        assert_eq!(
+            (
+                WorkspaceSnapshotGraphDiscriminants::V2,
+                WorkspaceSnapshotGraphDiscriminants::V2
+            ),
+            migrate(&path, None)?
+        );
+        assert_eq!(written, std::fs::read(&path)?);
+
+        Ok(())
+    }
+}
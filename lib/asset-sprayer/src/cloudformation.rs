@@ -0,0 +1,571 @@
+//! Generates asset schemas from CloudFormation resource provider schemas, without any AI
+//! involved: the same resource schema always generates the same asset function.
+//!
+//! Properties become props of the matching [`PropKind`], `required` properties get a
+//! `.required()` validation format, `readOnlyProperties` become resource value props and each
+//! `primaryIdentifier` gets an output socket.
+
+use std::collections::HashSet;
+
+use dal::schema::variant::authoring::asset_code::{
+    js_string, AssetCodeProp, ValidationFormat, INDENT,
+};
+use dal::PropKind;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{AssetSprayerError, AssetSprayerResult};
+
+const DEFINITIONS_REF_PREFIX: &str = "#/definitions/";
+const PROPERTIES_POINTER_PREFIX: &str = "/properties/";
+
+/// A CloudFormation resource provider schema, as published in the CloudFormation registry.
+/// Only the parts needed to generate an asset are deserialized.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CfnResourceSchema {
+    pub type_name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub properties: IndexMap<String, CfnProperty>,
+    #[serde(default)]
+    pub definitions: IndexMap<String, CfnProperty>,
+    #[serde(default)]
+    pub required: Vec<String>,
+    #[serde(default)]
+    pub read_only_properties: Vec<String>,
+    #[serde(default)]
+    pub primary_identifier: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CfnProperty {
+    #[serde(rename = "type", default)]
+    pub kind: Option<CfnType>,
+    #[serde(rename = "$ref", default)]
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub properties: IndexMap<String, CfnProperty>,
+    #[serde(default)]
+    pub required: Vec<String>,
+    #[serde(default)]
+    pub items: Option<Box<CfnProperty>>,
+    #[serde(default)]
+    pub pattern_properties: IndexMap<String, CfnProperty>,
+    #[serde(rename = "enum", default)]
+    pub allowed_values: Option<Vec<Value>>,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub min_length: Option<u64>,
+    #[serde(default)]
+    pub max_length: Option<u64>,
+    #[serde(default)]
+    pub minimum: Option<serde_json::Number>,
+    #[serde(default)]
+    pub maximum: Option<serde_json::Number>,
+}
+
+/// JSON schema allows a single type or a list of them.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum CfnType {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+/// A prop of the generated asset.
+pub type GeneratedProp = AssetCodeProp;
+
+/// An output socket of the generated asset, taking its value from a prop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedSocket {
+    pub name: String,
+    pub prop_path: Vec<String>,
+}
+
+/// The asset generated from a [`CfnResourceSchema`], ready to be rendered into the code of an
+/// asset function with [`Self::render`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedAsset {
+    pub type_name: String,
+    pub domain_props: Vec<GeneratedProp>,
+    pub resource_props: Vec<GeneratedProp>,
+    pub output_sockets: Vec<GeneratedSocket>,
+}
+
+/// Generates the code of an asset function from the JSON of a CloudFormation resource schema.
+pub fn cloudformation_asset_schema(resource_schema_json: &str) -> AssetSprayerResult<String> {
+    let resource_schema: CfnResourceSchema = serde_json::from_str(resource_schema_json)?;
+    Ok(resource_schema.generate_asset()?.render())
+}
+
+impl CfnResourceSchema {
+    pub fn generate_asset(&self) -> AssetSprayerResult<GeneratedAsset> {
+        let read_only: HashSet<&str> = self
+            .read_only_properties
+            .iter()
+            .filter_map(|pointer| top_level_property(pointer))
+            .collect();
+
+        let mut domain_props = vec![];
+        let mut resource_props = vec![];
+        for (name, property) in &self.properties {
+            let mut refs_in_progress = vec![];
+            if read_only.contains(name.as_str()) {
+                // The resource is the source of truth for read only properties, so they don't
+                // get validations
+                let mut prop = self.generate_prop(name, property, false, &mut refs_in_progress)?;
+                clear_validation_formats(&mut prop);
+                resource_props.push(prop);
+            } else {
+                let required = self.required.contains(name);
+                domain_props.push(self.generate_prop(
+                    name,
+                    property,
+                    required,
+                    &mut refs_in_progress,
+                )?);
+            }
+        }
+
+        let output_sockets = self
+            .primary_identifier
+            .iter()
+            .filter_map(|pointer| top_level_property(pointer))
+            .filter(|name| self.properties.contains_key(*name))
+            .map(|name| {
+                let parent = if read_only.contains(name) {
+                    "resource_value"
+                } else {
+                    "domain"
+                };
+                GeneratedSocket {
+                    name: name.to_owned(),
+                    prop_path: vec!["root".to_owned(), parent.to_owned(), name.to_owned()],
+                }
+            })
+            .collect();
+
+        Ok(GeneratedAsset {
+            type_name: self.type_name.to_owned(),
+            domain_props,
+            resource_props,
+            output_sockets,
+        })
+    }
+
+    fn generate_prop(
+        &self,
+        name: &str,
+        property: &CfnProperty,
+        required: bool,
+        refs_in_progress: &mut Vec<String>,
+    ) -> AssetSprayerResult<GeneratedProp> {
+        if let Some(reference) = &property.reference {
+            let definition_name =
+                reference
+                    .strip_prefix(DEFINITIONS_REF_PREFIX)
+                    .ok_or_else(|| {
+                        AssetSprayerError::CloudFormationRefNotFound(reference.to_owned())
+                    })?;
+            let definition = self.definitions.get(definition_name).ok_or_else(|| {
+                AssetSprayerError::CloudFormationRefNotFound(reference.to_owned())
+            })?;
+
+            // Props can't be recursive, so a definition that contains itself is cut off at the
+            // point it repeats and kept as a JSON string from there on
+            if refs_in_progress.iter().any(|r| r == definition_name) {
+                return Ok(json_string_prop(name, definition, required));
+            }
+
+            let mut definition = definition.clone();
+            if definition.description.is_none() {
+                definition.description = property.description.clone();
+            }
+            refs_in_progress.push(definition_name.to_owned());
+            let prop = self.generate_prop(name, &definition, required, refs_in_progress);
+            refs_in_progress.pop();
+            return prop;
+        }
+
+        let documentation = property.description.clone();
+        let prop = match property.json_type() {
+            Some("string") => {
+                let mut prop = GeneratedProp::new(name, PropKind::String, documentation);
+                prop.validation_format = string_validation_format(property, required);
+                prop
+            }
+            Some(kind @ ("integer" | "number")) => {
+                let (prop_kind, format) = ValidationFormat::number(kind == "integer");
+                let mut prop = GeneratedProp::new(name, prop_kind, documentation);
+                prop.validation_format = number_validation_format(format, property, required);
+                prop
+            }
+            Some("boolean") => {
+                let mut prop = GeneratedProp::new(name, PropKind::Boolean, documentation);
+                prop.validation_format = ValidationFormat::new("Joi.boolean()").finish(required);
+                prop
+            }
+            Some("array") => {
+                let items = property.items.as_deref().cloned().unwrap_or_default();
+                let entry =
+                    self.generate_prop(&format!("{name}Item"), &items, false, refs_in_progress)?;
+                let mut prop = GeneratedProp::new(name, PropKind::Array, documentation);
+                prop.validation_format = ValidationFormat::new("Joi.array()").finish(required);
+                prop.entry = Some(Box::new(entry));
+                prop
+            }
+            Some("object") if !property.properties.is_empty() => {
+                let mut prop = GeneratedProp::new(name, PropKind::Object, documentation);
+                for (child_name, child) in &property.properties {
+                    prop.children.push(self.generate_prop(
+                        child_name,
+                        child,
+                        property.required.contains(child_name),
+                        refs_in_progress,
+                    )?);
+                }
+                prop.validation_format = ValidationFormat::new("Joi.object()").finish(required);
+                prop
+            }
+            Some("object") if !property.pattern_properties.is_empty() => {
+                let mut prop = GeneratedProp::new(name, PropKind::Map, documentation);
+                if let Some((_, value)) = property.pattern_properties.first() {
+                    prop.entry = Some(Box::new(self.generate_prop(
+                        &format!("{name}Item"),
+                        value,
+                        false,
+                        refs_in_progress,
+                    )?));
+                }
+                prop.validation_format = ValidationFormat::new("Joi.object()").finish(required);
+                prop
+            }
+            // Free form objects (policy documents and the like) and properties that can be one
+            // of several types
+            _ => json_string_prop(name, property, required),
+        };
+
+        Ok(prop)
+    }
+}
+
+impl CfnProperty {
+    /// The JSON type of the property, if it has exactly one.
+    fn json_type(&self) -> Option<&str> {
+        match &self.kind {
+            Some(CfnType::Single(kind)) => Some(kind.as_str()),
+            Some(CfnType::Multiple(kinds)) if kinds.len() == 1 => kinds.first().map(String::as_str),
+            _ => None,
+        }
+    }
+}
+
+impl GeneratedAsset {
+    /// Renders the code of the asset function. The AWS credential, the region socket and the
+    /// CloudFormation type name are added the same way for every resource.
+    pub fn render(&self) -> String {
+        let mut code = String::new();
+        code.push_str("function main() {\n");
+        code.push_str(&format!("{INDENT}const asset = new AssetBuilder();\n"));
+
+        for prop in &self.domain_props {
+            code.push('\n');
+            code.push_str(&format!(
+                "{INDENT}asset.addProp(\n{}{INDENT});\n",
+                prop.render(2)
+            ));
+        }
+
+        for prop in &self.resource_props {
+            code.push('\n');
+            code.push_str(&format!(
+                "{INDENT}asset.addResourceProp(\n{}{INDENT});\n",
+                prop.render(2)
+            ));
+        }
+
+        code.push_str(&format!(
+            r#"
+  const credentialProp = new SecretPropBuilder()
+    .setName("credential")
+    .setSecretKind("AWS Credential")
+    .build();
+  asset.addSecretProp(credentialProp);
+
+  const regionSocket = new SocketDefinitionBuilder()
+    .setArity("one")
+    .setName("Region")
+    .build();
+  asset.addInputSocket(regionSocket);
+
+  const extraProp = new PropBuilder()
+    .setKind("object")
+    .setName("extra")
+    .addChild(
+      new PropBuilder()
+        .setKind("string")
+        .setName("Region")
+        .setValueFrom(
+          new ValueFromBuilder()
+            .setKind("inputSocket")
+            .setSocketName("Region")
+            .build(),
+        )
+        .build(),
+    )
+    .addChild(
+      new PropBuilder()
+        .setKind("string")
+        .setName("AwsResourceType")
+        .setDefaultValue({})
+        .setHidden(true)
+        .build(),
+    )
+    .build();
+  asset.addProp(extraProp);
+"#,
+            js_string(&self.type_name)
+        ));
+
+        for socket in &self.output_sockets {
+            let prop_path = socket
+                .prop_path
+                .iter()
+                .map(|part| js_string(part))
+                .collect::<Vec<_>>()
+                .join(", ");
+            code.push_str(&format!(
+                r#"
+  asset.addOutputSocket(
+    new SocketDefinitionBuilder()
+      .setArity("many")
+      .setName({})
+      .setValueFrom(
+        new ValueFromBuilder()
+          .setKind("prop")
+          .setPropPath([{prop_path}])
+          .build(),
+      )
+      .build(),
+  );
+"#,
+                js_string(&socket.name)
+            ));
+        }
+
+        code.push_str(&format!("\n{INDENT}return asset.build();\n}}\n"));
+        code
+    }
+}
+
+fn json_string_prop(name: &str, property: &CfnProperty, required: bool) -> GeneratedProp {
+    let mut prop = GeneratedProp::new(name, PropKind::String, property.description.clone());
+    prop.validation_format = ValidationFormat::new("Joi.string()").finish(required);
+    prop
+}
+
+fn string_validation_format(property: &CfnProperty, required: bool) -> Option<String> {
+    let mut format = ValidationFormat::new("Joi.string()");
+
+    if let Some(allowed_values) = &property.allowed_values {
+        format.valid(allowed_values);
+    }
+    if let Some(pattern) = &property.pattern {
+        format.pattern(pattern);
+    }
+    if let Some(min_length) = property.min_length {
+        format.constrain("min", min_length);
+    }
+    if let Some(max_length) = property.max_length {
+        format.constrain("max", max_length);
+    }
+
+    format.finish(required)
+}
+
+fn number_validation_format(
+    mut format: ValidationFormat,
+    property: &CfnProperty,
+    required: bool,
+) -> Option<String> {
+    if let Some(minimum) = &property.minimum {
+        format.constrain("min", minimum);
+    }
+    if let Some(maximum) = &property.maximum {
+        format.constrain("max", maximum);
+    }
+
+    format.finish(required)
+}
+
+fn clear_validation_formats(prop: &mut GeneratedProp) {
+    prop.validation_format = None;
+    for child in &mut prop.children {
+        clear_validation_formats(child);
+    }
+    if let Some(entry) = &mut prop.entry {
+        clear_validation_formats(entry);
+    }
+}
+
+/// The property a `/properties/<name>` pointer refers to, if it is a top level one.
+fn top_level_property(pointer: &str) -> Option<&str> {
+    pointer
+        .strip_prefix(PROPERTIES_POINTER_PREFIX)
+        .filter(|name| !name.contains('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUCKET_SCHEMA: &str = r##"{
+        "typeName": "AWS::Test::Bucket",
+        "description": "A test bucket",
+        "definitions": {
+            "Tag": {
+                "type": "object",
+                "properties": {
+                    "Key": { "type": "string", "minLength": 1, "maxLength": 128 },
+                    "Value": { "type": "string" }
+                },
+                "required": ["Key"]
+            },
+            "Rule": {
+                "type": "object",
+                "properties": {
+                    "Id": { "type": "string" },
+                    "Rules": { "type": "array", "items": { "$ref": "#/definitions/Rule" } }
+                }
+            }
+        },
+        "properties": {
+            "BucketName": { "type": "string", "pattern": "^[a-z0-9.-]+$" },
+            "StorageClass": { "type": "string", "enum": ["STANDARD", "GLACIER"] },
+            "Versioned": { "type": "boolean" },
+            "RetentionDays": { "type": "integer", "minimum": 1 },
+            "Ratio": { "type": "number" },
+            "Tags": { "type": "array", "items": { "$ref": "#/definitions/Tag" } },
+            "Labels": { "type": "object", "patternProperties": { ".*": { "type": "string" } } },
+            "Policy": { "type": "object" },
+            "Rule": { "$ref": "#/definitions/Rule" },
+            "Arn": { "type": "string" }
+        },
+        "required": ["BucketName", "Versioned"],
+        "readOnlyProperties": ["/properties/Arn"],
+        "primaryIdentifier": ["/properties/BucketName"]
+    }"##;
+
+    fn find<'a>(props: &'a [GeneratedProp], name: &str) -> &'a GeneratedProp {
+        props
+            .iter()
+            .find(|prop| prop.name == name)
+            .expect("prop should be generated")
+    }
+
+    #[test]
+    fn generates_props_from_cloudformation_schema() -> AssetSprayerResult<()> {
+        let schema: CfnResourceSchema = serde_json::from_str(BUCKET_SCHEMA)?;
+        let asset = schema.generate_asset()?;
+
+        let bucket_name = find(&asset.domain_props, "BucketName");
+        assert_eq!(PropKind::String, bucket_name.kind);
+        assert_eq!(
+            Some(r#"Joi.string().pattern(new RegExp("^[a-z0-9.-]+$")).required()"#),
+            bucket_name.validation_format.as_deref()
+        );
+        assert_eq!(
+            Some(r#"Joi.string().valid("STANDARD", "GLACIER")"#),
+            find(&asset.domain_props, "StorageClass")
+                .validation_format
+                .as_deref()
+        );
+        assert_eq!(
+            Some("Joi.boolean().required()"),
+            find(&asset.domain_props, "Versioned")
+                .validation_format
+                .as_deref()
+        );
+        assert_eq!(
+            Some("Joi.number().integer().min(1)"),
+            find(&asset.domain_props, "RetentionDays")
+                .validation_format
+                .as_deref()
+        );
+        let ratio = find(&asset.domain_props, "Ratio");
+        assert_eq!(PropKind::String, ratio.kind);
+        assert_eq!(Some("Joi.number()"), ratio.validation_format.as_deref());
+
+        let tags = find(&asset.domain_props, "Tags");
+        assert_eq!(PropKind::Array, tags.kind);
+        let tag = tags.entry.as_deref().expect("tags should have an entry");
+        assert_eq!(PropKind::Object, tag.kind);
+        assert_eq!(
+            Some("Joi.string().min(1).max(128).required()"),
+            find(&tag.children, "Key").validation_format.as_deref()
+        );
+
+        assert_eq!(PropKind::Map, find(&asset.domain_props, "Labels").kind);
+        assert_eq!(PropKind::String, find(&asset.domain_props, "Policy").kind);
+
+        // The recursive definition is cut off where it repeats
+        let rule = find(&asset.domain_props, "Rule");
+        let nested_rule = find(&rule.children, "Rules")
+            .entry
+            .as_deref()
+            .expect("rules should have an entry");
+        assert_eq!(PropKind::String, nested_rule.kind);
+
+        assert!(asset.domain_props.iter().all(|prop| prop.name != "Arn"));
+        assert_eq!(1, asset.resource_props.len());
+        assert_eq!("Arn", asset.resource_props[0].name);
+
+        assert_eq!(
+            vec![GeneratedSocket {
+                name: "BucketName".to_owned(),
+                prop_path: vec![
+                    "root".to_owned(),
+                    "domain".to_owned(),
+                    "BucketName".to_owned()
+                ],
+            }],
+            asset.output_sockets
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn renders_the_same_code_every_time() -> AssetSprayerResult<()> {
+        let code = cloudformation_asset_schema(BUCKET_SCHEMA)?;
+        assert_eq!(code, cloudformation_asset_schema(BUCKET_SCHEMA)?);
+
+        assert!(code.starts_with("function main() {\n  const asset = new AssetBuilder();\n"));
+        assert!(
+            code.contains("asset.addResourceProp(\n    new PropBuilder()\n      .setName(\"Arn\")")
+        );
+        assert!(code.contains(".setDefaultValue(\"AWS::Test::Bucket\")"));
+        assert!(code.contains(".setPropPath([\"root\", \"domain\", \"BucketName\"])"));
+        assert!(code.ends_with("  return asset.build();\n}\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn missing_definitions_are_an_error() {
+        let result = cloudformation_asset_schema(
+            r##"{ "typeName": "AWS::Test::Thing", "properties": { "A": { "$ref": "#/definitions/B" } } }"##,
+        );
+        assert!(matches!(
+            result,
+            Err(AssetSprayerError::CloudFormationRefNotFound(reference)) if reference == "#/definitions/B"
+        ));
+    }
+}
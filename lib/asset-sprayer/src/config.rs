@@ -22,6 +22,21 @@ pub struct SIOpenAIConfig {
 pub struct AssetSprayerConfig {
     #[serde_as(as = "NoneAsEmptyString")]
     pub prompts_dir: Option<String>,
+    /// Directory to cache the documents fetched for prompts in. They are fetched every time
+    /// when unset.
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub fetch_cache_dir: Option<String>,
+    /// Send prompts to a local model server instead of OpenAI.
+    #[serde(default)]
+    pub local_model: Option<LocalModelConfig>,
+}
+
+/// A model server with an Ollama compatible API.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LocalModelConfig {
+    pub base_url: String,
+    pub model: String,
 }
 
 impl SIOpenAIConfig {
@@ -1,4 +1,5 @@
-//! This create provides centralized support for using AI to generate assets.
+//! This create provides centralized support for using AI to generate assets, along with
+//! deterministic generators for sources that describe resources precisely enough not to need it.
 
 #![warn(
     bad_style,
@@ -25,18 +26,24 @@
     while_true
 )]
 
-use async_openai::config::OpenAIConfig;
+use std::sync::Arc;
+
 use config::AssetSprayerConfig;
 use prompts::{Prompt, Prompts};
+use provider::AssetSprayerProvider;
 use telemetry::prelude::*;
 use thiserror::Error;
 
+pub mod cloudformation;
 pub mod config;
 pub mod prompts;
+pub mod provider;
 
 #[remain::sorted]
 #[derive(Debug, Error)]
 pub enum AssetSprayerError {
+    #[error("CloudFormation schema reference not found: {0}")]
+    CloudFormationRefNotFound(String),
     #[error("Empty choice returned from AI.")]
     EmptyChoice,
     #[error("I/O error: {0}")]
@@ -45,10 +52,14 @@ pub enum AssetSprayerError {
     MissingEndFetch(Prompt),
     #[error("No choices were returned from the AI.")]
     NoChoices,
+    #[error("No fixture response matches the prompt.")]
+    NoFixtureResponse,
     #[error("OpenAI error: {0}")]
     OpenAI(#[from] async_openai::error::OpenAIError),
     #[error("Reqwest error: {0}")]
     Reqwest(#[from] reqwest::Error),
+    #[error("SerdeJson error: {0}")]
+    SerdeJson(#[from] serde_json::Error),
     #[error("SerdeYaml error: {0}")]
     SerdeYaml(#[from] serde_yaml::Error),
     #[error("Unreachable")]
@@ -59,18 +70,16 @@ pub type AssetSprayerResult<T> = Result<T, AssetSprayerError>;
 
 #[derive(Debug, Clone)]
 pub struct AssetSprayer {
-    openai_client: async_openai::Client<OpenAIConfig>,
+    provider: Arc<dyn AssetSprayerProvider>,
     prompts: Prompts,
 }
 
 impl AssetSprayer {
-    pub fn new(
-        openai_client: async_openai::Client<OpenAIConfig>,
-        config: AssetSprayerConfig,
-    ) -> Self {
+    pub fn new(provider: Arc<dyn AssetSprayerProvider>, config: AssetSprayerConfig) -> Self {
         Self {
-            openai_client,
-            prompts: Prompts::new(config.prompts_dir.map(Into::into)),
+            provider,
+            prompts: Prompts::new(config.prompts_dir.map(Into::into))
+                .with_fetch_cache_dir(config.fetch_cache_dir.map(Into::into)),
         }
     }
 
@@ -94,26 +103,18 @@ impl AssetSprayer {
     }
 
     async fn run(&self, prompt: Prompt, replace: &[(&str, &str)]) -> AssetSprayerResult<String> {
-        let request = self.prompts.create_request(prompt, replace).await?;
-        let response = self.openai_client.chat().create(request).await?;
-        let choice = response
-            .choices
-            .into_iter()
-            .next()
-            .ok_or(AssetSprayerError::NoChoices)?;
-        let text = choice
-            .message
-            .content
-            .ok_or(AssetSprayerError::EmptyChoice)?;
-        Ok(text)
+        let rendered = self.prompts.render(prompt, replace).await?;
+        self.provider.complete(rendered).await
     }
 }
 
 #[ignore = "You must have OPENAI_API_KEY set to run this test"]
 #[tokio::test]
 async fn test_do_ai() -> AssetSprayerResult<()> {
-    let asset_sprayer =
-        AssetSprayer::new(async_openai::Client::new(), AssetSprayerConfig::default());
+    let asset_sprayer = AssetSprayer::new(
+        Arc::new(provider::OpenAIProvider::new(async_openai::Client::new())),
+        AssetSprayerConfig::default(),
+    );
     println!(
         "Done: {}",
         asset_sprayer
@@ -122,3 +123,66 @@ async fn test_do_ai() -> AssetSprayerResult<()> {
     );
     Ok(())
 }
+
+#[tokio::test]
+async fn aws_asset_schema_with_fixture_provider() -> AssetSprayerResult<()> {
+    use prompts::{fetch_cache_file_name, PromptRole};
+    use provider::{AssetSprayerProvider as _, FixtureProvider};
+
+    // Everything the prompt fetches is already cached, so nothing goes over the network
+    let fetch_cache_dir = tempfile::tempdir()?;
+    for (url, text) in [
+        (
+            "https://raw.githubusercontent.com/systeminit/si/refs/heads/main/app/docs/src/reference/asset/schema.md",
+            "SCHEMA DOCS",
+        ),
+        (
+            "https://raw.githubusercontent.com/systeminit/si/refs/heads/main/app/docs/src/reference/asset/function.md",
+            "FUNCTION DOCS",
+        ),
+        (
+            "https://docs.aws.amazon.com/cli/latest/reference/sqs/create-queue.html",
+            "CREATE QUEUE DOCS",
+        ),
+    ] {
+        tokio::fs::write(fetch_cache_dir.path().join(fetch_cache_file_name(url)), text).await?;
+    }
+
+    let provider = FixtureProvider::new().with_response("CREATE QUEUE DOCS", "function main() {}");
+    let asset_sprayer = AssetSprayer::new(
+        Arc::new(provider.clone()),
+        AssetSprayerConfig {
+            fetch_cache_dir: Some(fetch_cache_dir.path().to_string_lossy().to_string()),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(
+        "function main() {}",
+        asset_sprayer
+            .aws_asset_schema("sqs", "create-queue")
+            .await?
+    );
+
+    let prompts = provider.prompts().await;
+    assert_eq!(1, prompts.len());
+    assert_eq!(PromptRole::System, prompts[0].messages[0].role);
+    let user_text: Vec<&str> = prompts[0]
+        .messages
+        .iter()
+        .filter(|message| message.role == PromptRole::User)
+        .map(|message| message.content.as_str())
+        .collect();
+    assert!(user_text.iter().any(|text| text.contains("SCHEMA DOCS")));
+    assert!(user_text.iter().any(|text| text.contains("FUNCTION DOCS")));
+    assert!(prompts[0]
+        .messages
+        .iter()
+        .all(|message| !message.content.contains("{FETCH}")));
+
+    // Prompts nothing was set up for get no answer
+    let result = FixtureProvider::new().complete(prompts[0].clone()).await;
+    assert!(matches!(result, Err(AssetSprayerError::NoFixtureResponse)));
+
+    Ok(())
+}
@@ -1,9 +1,7 @@
+use std::io::ErrorKind;
 use std::path::PathBuf;
 
-use async_openai::types::{
-    ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageContent,
-    ChatCompletionRequestUserMessageContent, CreateChatCompletionRequest,
-};
+use serde::{Deserialize, Serialize};
 use telemetry::prelude::*;
 
 use crate::{AssetSprayerError, AssetSprayerResult};
@@ -11,6 +9,7 @@ use crate::{AssetSprayerError, AssetSprayerResult};
 #[derive(Debug, Clone)]
 pub struct Prompts {
     prompts_dir: Option<PathBuf>,
+    fetch_cache_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Copy, strum::Display)]
@@ -32,23 +31,60 @@ impl Prompt {
     }
 }
 
+/// A prompt with everything filled in, ready to be sent to a
+/// [`provider`](crate::provider::AssetSprayerProvider). It is not tied to any particular API.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RenderedPrompt {
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    pub messages: Vec<PromptMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PromptMessage {
+    pub role: PromptRole,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptRole {
+    Assistant,
+    System,
+    User,
+}
+
 impl Prompts {
     pub fn new(prompts_dir: Option<PathBuf>) -> Self {
         Self {
             prompts_dir: prompts_dir.map(Into::into),
+            fetch_cache_dir: None,
         }
     }
 
-    pub async fn create_request(
+    /// Keeps the documents fetched for `{FETCH}` in the given directory, and reads them from
+    /// there instead of fetching them again.
+    pub fn with_fetch_cache_dir(mut self, fetch_cache_dir: Option<PathBuf>) -> Self {
+        self.fetch_cache_dir = fetch_cache_dir;
+        self
+    }
+
+    pub async fn render(
         &self,
         prompt: Prompt,
         replace: &[(&str, &str)],
-    ) -> AssetSprayerResult<CreateChatCompletionRequest> {
-        let request = self.raw_request(prompt).await?;
-        Self::replace_prompt_request(request, replace, prompt).await
+    ) -> AssetSprayerResult<RenderedPrompt> {
+        let mut rendered = self.raw_prompt(prompt).await?;
+        for message in rendered.messages.iter_mut() {
+            message.content = self
+                .replace_prompt_text(message.content.clone(), replace, prompt)
+                .await?;
+        }
+        Ok(rendered)
     }
 
-    async fn raw_request(&self, prompt: Prompt) -> AssetSprayerResult<CreateChatCompletionRequest> {
+    async fn raw_prompt(&self, prompt: Prompt) -> AssetSprayerResult<RenderedPrompt> {
         Ok(serde_yaml::from_str(&self.yaml(prompt).await?)?)
     }
 
@@ -64,43 +100,8 @@ impl Prompts {
         }
     }
 
-    async fn replace_prompt_request(
-        request: CreateChatCompletionRequest,
-        replace: &[(&str, &str)],
-        prompt: Prompt,
-    ) -> AssetSprayerResult<CreateChatCompletionRequest> {
-        let mut request = request;
-        for message in request.messages.iter_mut() {
-            *message =
-                Self::replace_prompt_request_message(message.clone(), replace, prompt).await?;
-        }
-        Ok(request)
-    }
-
-    async fn replace_prompt_request_message(
-        message: ChatCompletionRequestMessage,
-        replace: &[(&str, &str)],
-        prompt: Prompt,
-    ) -> AssetSprayerResult<ChatCompletionRequestMessage> {
-        let mut message = message;
-        match &mut message {
-            ChatCompletionRequestMessage::User(message) => {
-                if let ChatCompletionRequestUserMessageContent::Text(text) = &mut message.content {
-                    *text = Self::replace_prompt_text(text.clone(), replace, prompt).await?;
-                }
-            }
-            ChatCompletionRequestMessage::System(message) => {
-                if let ChatCompletionRequestSystemMessageContent::Text(text) = &mut message.content
-                {
-                    *text = Self::replace_prompt_text(text.clone(), replace, prompt).await?;
-                }
-            }
-            _ => (),
-        }
-        Ok(message)
-    }
-
     async fn replace_prompt_text(
+        &self,
         text: String,
         replace: &[(&str, &str)],
         prompt: Prompt,
@@ -112,10 +113,10 @@ impl Prompts {
             text = text.replace(from, to);
         }
 
-        Self::fetch_prompt_text(&text, prompt).await
+        self.fetch_prompt_text(&text, prompt).await
     }
 
-    async fn fetch_prompt_text(text: &str, prompt: Prompt) -> AssetSprayerResult<String> {
+    async fn fetch_prompt_text(&self, text: &str, prompt: Prompt) -> AssetSprayerResult<String> {
         // Fetch things between {FETCH} and {/FETCH}
         let mut result = String::new();
         let mut text = text;
@@ -126,7 +127,7 @@ impl Prompts {
 
             if let Some(url_end) = text.find("{/FETCH}") {
                 // Fetch the URL between {FETCH}...{/FETCH}
-                result.push_str(&Self::get(&text[..url_end]).await?);
+                result.push_str(&self.fetch(&text[..url_end]).await?);
                 text = &text[(url_end + "{/FETCH}".len())..];
             } else {
                 return Err(AssetSprayerError::MissingEndFetch(prompt));
@@ -139,6 +140,27 @@ impl Prompts {
         Ok(result)
     }
 
+    async fn fetch(&self, url: &str) -> AssetSprayerResult<String> {
+        let Some(ref fetch_cache_dir) = self.fetch_cache_dir else {
+            return Ok(Self::get(url).await?);
+        };
+
+        let path = fetch_cache_dir.join(fetch_cache_file_name(url));
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => {
+                debug!("Using cached fetch of {} from {:?}", url, path);
+                return Ok(text);
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let text = Self::get(url).await?;
+        tokio::fs::create_dir_all(fetch_cache_dir).await?;
+        tokio::fs::write(&path, &text).await?;
+        Ok(text)
+    }
+
     async fn get(url: &str) -> reqwest::Result<String> {
         info!("Fetching: {}", url);
         let client = reqwest::ClientBuilder::new()
@@ -148,3 +170,17 @@ impl Prompts {
         response.error_for_status()?.text().await
     }
 }
+
+/// The name of the file a fetched URL is cached in: the URL, with everything but letters,
+/// digits, `-` and `.` replaced by `_`.
+pub fn fetch_cache_file_name(url: &str) -> String {
+    url.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
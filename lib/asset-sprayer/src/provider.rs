@@ -0,0 +1,183 @@
+//! The models an [`AssetSprayer`](crate::AssetSprayer) can send its prompts to.
+//!
+//! [`OpenAIProvider`] talks to OpenAI or any endpoint compatible with its API,
+//! [`LocalModelProvider`] to a model server running next to us (with an Ollama compatible API),
+//! so that nothing leaves our infrastructure, and [`FixtureProvider`] answers with canned
+//! responses for tests.
+
+use std::fmt;
+use std::sync::Arc;
+
+use async_openai::{config::OpenAIConfig, types::CreateChatCompletionRequest};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+use tokio::sync::Mutex;
+
+use crate::config::LocalModelConfig;
+use crate::prompts::{PromptMessage, RenderedPrompt};
+use crate::{AssetSprayerError, AssetSprayerResult};
+
+/// Something that can answer a [`RenderedPrompt`].
+#[async_trait]
+pub trait AssetSprayerProvider: fmt::Debug + Send + Sync {
+    /// Sends the prompt to the model and returns the text of its answer.
+    async fn complete(&self, prompt: RenderedPrompt) -> AssetSprayerResult<String>;
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenAIProvider {
+    client: async_openai::Client<OpenAIConfig>,
+}
+
+impl OpenAIProvider {
+    pub fn new(client: async_openai::Client<OpenAIConfig>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl AssetSprayerProvider for OpenAIProvider {
+    async fn complete(&self, prompt: RenderedPrompt) -> AssetSprayerResult<String> {
+        // Rendered prompts have the same shape as OpenAI chat completion requests
+        let request: CreateChatCompletionRequest =
+            serde_json::from_value(serde_json::to_value(prompt)?)?;
+        let response = self.client.chat().create(request).await?;
+        let choice = response
+            .choices
+            .into_iter()
+            .next()
+            .ok_or(AssetSprayerError::NoChoices)?;
+        let text = choice
+            .message
+            .content
+            .ok_or(AssetSprayerError::EmptyChoice)?;
+        Ok(text)
+    }
+}
+
+/// Sends prompts to the `/api/chat` endpoint of a local model server. The model named in the
+/// prompt is replaced with the configured one, since prompts are written for hosted models.
+#[derive(Debug, Clone)]
+pub struct LocalModelProvider {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+}
+
+#[derive(Debug, Serialize)]
+struct LocalChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [PromptMessage],
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<LocalChatOptions>,
+}
+
+#[derive(Debug, Serialize)]
+struct LocalChatOptions {
+    temperature: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalChatResponse {
+    message: PromptMessage,
+}
+
+impl LocalModelProvider {
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into(),
+            model: model.into(),
+        }
+    }
+}
+
+impl From<LocalModelConfig> for LocalModelProvider {
+    fn from(config: LocalModelConfig) -> Self {
+        Self::new(config.base_url, config.model)
+    }
+}
+
+#[async_trait]
+impl AssetSprayerProvider for LocalModelProvider {
+    async fn complete(&self, prompt: RenderedPrompt) -> AssetSprayerResult<String> {
+        let url = format!("{}/api/chat", self.base_url.trim_end_matches('/'));
+        debug!("Sending prompt to local model {} at {}", self.model, url);
+
+        let request = LocalChatRequest {
+            model: &self.model,
+            messages: &prompt.messages,
+            stream: false,
+            options: prompt
+                .temperature
+                .map(|temperature| LocalChatOptions { temperature }),
+        };
+        let response: LocalChatResponse = self
+            .client
+            .post(url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if response.message.content.is_empty() {
+            return Err(AssetSprayerError::EmptyChoice);
+        }
+        Ok(response.message.content)
+    }
+}
+
+/// Answers prompts with canned responses and remembers every prompt it was sent. The first
+/// response whose key appears in one of the prompt's messages is used, falling back to the
+/// default response.
+#[derive(Debug, Clone, Default)]
+pub struct FixtureProvider {
+    responses: Vec<(String, String)>,
+    default_response: Option<String>,
+    prompts: Arc<Mutex<Vec<RenderedPrompt>>>,
+}
+
+impl FixtureProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_response(mut self, key: impl Into<String>, response: impl Into<String>) -> Self {
+        self.responses.push((key.into(), response.into()));
+        self
+    }
+
+    pub fn with_default_response(mut self, response: impl Into<String>) -> Self {
+        self.default_response = Some(response.into());
+        self
+    }
+
+    /// The prompts sent so far, in order.
+    pub async fn prompts(&self) -> Vec<RenderedPrompt> {
+        self.prompts.lock().await.clone()
+    }
+}
+
+#[async_trait]
+impl AssetSprayerProvider for FixtureProvider {
+    async fn complete(&self, prompt: RenderedPrompt) -> AssetSprayerResult<String> {
+        let response = self
+            .responses
+            .iter()
+            .find(|(key, _)| {
+                prompt
+                    .messages
+                    .iter()
+                    .any(|message| message.content.contains(key.as_str()))
+            })
+            .map(|(_, response)| response.to_owned())
+            .or_else(|| self.default_response.clone());
+
+        self.prompts.lock().await.push(prompt);
+        response.ok_or(AssetSprayerError::NoFixtureResponse)
+    }
+}
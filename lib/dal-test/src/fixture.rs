@@ -0,0 +1,414 @@
+//! Declarative fixtures for setting up and checking test scenarios without long sequences of
+//! helper calls.
+//!
+//! A [`Fixture`] describes the secrets, components and connections to create, by name:
+//!
+//! ```yaml
+//! secrets:
+//!   - name: todd
+//!     definition: dummy
+//!     value: { value: todd }
+//! components:
+//!   - name: vault
+//!     schema: fallout
+//!     type: configurationFrameDown
+//!   - name: source
+//!     schema: dummy-secret
+//!     secrets:
+//!       dummy: todd
+//!   - name: destination
+//!     schema: fallout
+//!     parent: vault
+//!     props:
+//!       /root/domain/special: boom
+//! connections:
+//!   - from: source
+//!     output: dummy
+//!     to: destination
+//!     input: dummy
+//! ```
+//!
+//! [`Fixture::load`] creates all of it in the change set of the context in one go, and an
+//! [`ExpectedState`] written the same way can then be asserted against the [`LoadedFixture`]:
+//!
+//! ```yaml
+//! components:
+//!   destination:
+//!     parent: vault
+//!     props:
+//!       /root/domain/name: destination
+//!     secrets:
+//!       dummy: todd
+//! connections:
+//!   - from: source
+//!     output: dummy
+//!     to: destination
+//!     input: dummy
+//! ```
+//!
+//! Like the rest of [`expected`](crate::expected), everything here panics instead of returning
+//! errors.
+
+#![allow(missing_docs)]
+#![allow(clippy::expect_used, clippy::panic)]
+
+use std::collections::{BTreeMap, HashMap};
+
+use dal::prop::PropPath;
+use dal::{
+    Component, ComponentType, DalContext, InputSocket, OutputSocket, PublicKey, SchemaVariant,
+    Secret, SecretId,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::expected::{commit_and_update_snapshot_to_visibility, ExpectComponent, ExpectSchema};
+use crate::helpers::encrypt_message;
+
+/// Secrets, components and connections to create in a change set.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Fixture {
+    #[serde(default)]
+    pub secrets: Vec<FixtureSecret>,
+    #[serde(default)]
+    pub components: Vec<FixtureComponent>,
+    #[serde(default)]
+    pub connections: Vec<FixtureConnection>,
+}
+
+/// A secret, encrypted with the current key pair of the workspace.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FixtureSecret {
+    pub name: String,
+    /// The name of the secret definition, like `dummy`
+    pub definition: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The message that gets encrypted
+    pub value: Value,
+}
+
+/// A component of the default variant of a schema.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FixtureComponent {
+    pub name: String,
+    pub schema: String,
+    #[serde(default, rename = "type")]
+    pub component_type: Option<ComponentType>,
+    /// The name of the frame the component is in
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Values by prop path (e.g. `/root/domain/region`), set in path order
+    #[serde(default)]
+    pub props: BTreeMap<String, Value>,
+    /// The names of the secrets to use, by secret definition name
+    #[serde(default)]
+    pub secrets: BTreeMap<String, String>,
+}
+
+/// A connection from an output socket of one component to an input socket of another.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FixtureConnection {
+    pub from: String,
+    pub output: String,
+    pub to: String,
+    pub input: String,
+}
+
+/// What [`Fixture::load`] created, by name.
+#[derive(Clone, Debug, Default)]
+pub struct LoadedFixture {
+    components: HashMap<String, ExpectComponent>,
+    secrets: HashMap<String, SecretId>,
+}
+
+impl LoadedFixture {
+    pub fn component(&self, name: impl AsRef<str>) -> ExpectComponent {
+        let name = name.as_ref();
+        *self
+            .components
+            .get(name)
+            .unwrap_or_else(|| panic!("no component named {name:?} in the fixture"))
+    }
+
+    pub fn secret(&self, name: impl AsRef<str>) -> SecretId {
+        let name = name.as_ref();
+        *self
+            .secrets
+            .get(name)
+            .unwrap_or_else(|| panic!("no secret named {name:?} in the fixture"))
+    }
+
+    fn component_name(&self, component: ExpectComponent) -> Option<&str> {
+        self.components
+            .iter()
+            .find(|(_, &loaded)| loaded == component)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+impl Fixture {
+    pub fn from_yaml(yaml: &str) -> Fixture {
+        serde_yaml::from_str(yaml).expect("parse fixture yaml")
+    }
+
+    pub fn from_json(json: &str) -> Fixture {
+        serde_json::from_str(json).expect("parse fixture json")
+    }
+
+    /// Creates everything in the fixture, then commits so that dependent values are updated.
+    pub async fn load(&self, ctx: &mut DalContext) -> LoadedFixture {
+        let mut loaded = LoadedFixture::default();
+
+        if !self.secrets.is_empty() {
+            let key_pair_pk = *PublicKey::get_current(ctx)
+                .await
+                .expect("get current key pair")
+                .pk();
+            for secret in &self.secrets {
+                let crypted = encrypt_message(ctx, key_pair_pk, &secret.value)
+                    .await
+                    .expect("encrypt secret");
+                let created = Secret::new(
+                    ctx,
+                    &secret.name,
+                    &secret.definition,
+                    secret.description.clone(),
+                    &crypted,
+                    key_pair_pk,
+                    Default::default(),
+                    Default::default(),
+                )
+                .await
+                .expect("create secret");
+                loaded.secrets.insert(secret.name.clone(), created.id());
+            }
+        }
+
+        // Everything is created before anything refers to it, and frames get their types before
+        // anything is put in them
+        for component in &self.components {
+            let created = ExpectSchema::find(ctx, &component.schema)
+                .await
+                .create_named_component(ctx, &component.name)
+                .await;
+            if loaded
+                .components
+                .insert(component.name.clone(), created)
+                .is_some()
+            {
+                panic!("more than one component named {:?}", component.name);
+            }
+        }
+        for component in &self.components {
+            if let Some(component_type) = component.component_type {
+                loaded
+                    .component(&component.name)
+                    .set_type(ctx, component_type)
+                    .await;
+            }
+        }
+        for component in &self.components {
+            let created = loaded.component(&component.name);
+            if let Some(parent) = &component.parent {
+                created.upsert_parent(ctx, loaded.component(parent)).await;
+            }
+            for (path, value) in &component.props {
+                created
+                    .prop(ctx, prop_path(path))
+                    .await
+                    .set(ctx, value.clone())
+                    .await;
+            }
+            for (definition, secret) in &component.secrets {
+                let attribute_value = created
+                    .prop(ctx, PropPath::new(["root", "secrets", definition.as_str()]))
+                    .await
+                    .attribute_value(ctx)
+                    .await;
+                Secret::attach_for_attribute_value(
+                    ctx,
+                    attribute_value.id(),
+                    Some(loaded.secret(secret)),
+                )
+                .await
+                .expect("attach secret");
+            }
+        }
+
+        for connection in &self.connections {
+            loaded
+                .component(&connection.from)
+                .connect(
+                    ctx,
+                    connection.output.as_str(),
+                    loaded.component(&connection.to),
+                    connection.input.as_str(),
+                )
+                .await;
+        }
+
+        commit_and_update_snapshot_to_visibility(ctx).await;
+
+        loaded
+    }
+}
+
+/// What the components of a [`LoadedFixture`] should look like. Only what is written down is
+/// checked.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExpectedState {
+    #[serde(default)]
+    pub components: BTreeMap<String, ExpectedComponent>,
+    /// Every connection between components of the fixture, if they should be checked
+    #[serde(default)]
+    pub connections: Option<Vec<FixtureConnection>>,
+}
+
+/// What a single component should look like.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExpectedComponent {
+    #[serde(default)]
+    pub schema: Option<String>,
+    #[serde(default, rename = "type")]
+    pub component_type: Option<ComponentType>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Values by prop path. `null` expects no value.
+    #[serde(default)]
+    pub props: BTreeMap<String, Value>,
+    /// The names of the secrets in use, by secret definition name
+    #[serde(default)]
+    pub secrets: BTreeMap<String, String>,
+}
+
+impl ExpectedState {
+    pub fn from_yaml(yaml: &str) -> ExpectedState {
+        serde_yaml::from_str(yaml).expect("parse expected state yaml")
+    }
+
+    pub fn from_json(json: &str) -> ExpectedState {
+        serde_json::from_str(json).expect("parse expected state json")
+    }
+
+    pub async fn assert(&self, ctx: &DalContext, loaded: &LoadedFixture) {
+        for (name, expected) in &self.components {
+            expected.assert(ctx, loaded, name).await;
+        }
+
+        if let Some(expected) = &self.connections {
+            let mut expected = expected.clone();
+            expected.sort();
+            assert_eq!(
+                expected,
+                connections(ctx, loaded).await,
+                "connections between fixture components"
+            );
+        }
+    }
+}
+
+impl ExpectedComponent {
+    async fn assert(&self, ctx: &DalContext, loaded: &LoadedFixture, name: &str) {
+        let component = loaded.component(name);
+
+        if let Some(schema) = &self.schema {
+            let schema_variant_id = component.schema_variant(ctx).await.id();
+            let actual = SchemaVariant::schema_for_schema_variant_id(ctx, schema_variant_id)
+                .await
+                .expect("get schema for schema variant");
+            assert_eq!(schema, actual.name(), "schema of {name:?}");
+        }
+
+        if let Some(component_type) = self.component_type {
+            assert_eq!(
+                component_type,
+                component.get_type(ctx).await,
+                "type of {name:?}"
+            );
+        }
+
+        if let Some(parent) = &self.parent {
+            let actual = Component::get_parent_by_id(ctx, component.id())
+                .await
+                .expect("get parent")
+                .map(|parent_id| {
+                    loaded
+                        .component_name(parent_id.into())
+                        .map(ToOwned::to_owned)
+                        .unwrap_or_else(|| parent_id.to_string())
+                });
+            assert_eq!(Some(parent), actual.as_ref(), "parent of {name:?}");
+        }
+
+        for (path, expected) in &self.props {
+            let actual = component
+                .prop(ctx, prop_path(path))
+                .await
+                .view(ctx)
+                .await
+                .unwrap_or(Value::Null);
+            assert_eq!(expected, &actual, "{path} of {name:?}");
+        }
+
+        for (definition, secret) in &self.secrets {
+            let expected = Secret::get_by_id_or_error(ctx, loaded.secret(secret))
+                .await
+                .expect("get secret")
+                .encrypted_secret_key();
+            let actual = component
+                .prop(ctx, PropPath::new(["root", "secrets", definition.as_str()]))
+                .await
+                .view(ctx)
+                .await
+                .map(|value| {
+                    Secret::key_from_value_in_attribute_value(value).expect("read secret key")
+                });
+            assert_eq!(
+                Some(expected),
+                actual,
+                "secret {definition:?} of {name:?} should be {secret:?}"
+            );
+        }
+    }
+}
+
+/// Turns a path like `/root/domain/region` into a [`PropPath`].
+fn prop_path(path: &str) -> PropPath {
+    PropPath::new(path.trim_start_matches('/').split('/'))
+}
+
+/// The connections between components of the fixture, in order.
+async fn connections(ctx: &DalContext, loaded: &LoadedFixture) -> Vec<FixtureConnection> {
+    let mut connections = Vec::new();
+    for (to, component) in &loaded.components {
+        for incoming in Component::incoming_connections_for_id(ctx, component.id())
+            .await
+            .expect("list incoming connections")
+        {
+            let Some(from) = loaded.component_name(incoming.from_component_id.into()) else {
+                continue;
+            };
+            let output = OutputSocket::get_by_id(ctx, incoming.from_output_socket_id)
+                .await
+                .expect("get output socket");
+            let input = InputSocket::get_by_id(ctx, incoming.to_input_socket_id)
+                .await
+                .expect("get input socket");
+            connections.push(FixtureConnection {
+                from: from.to_owned(),
+                output: output.name().to_owned(),
+                to: to.clone(),
+                input: input.name().to_owned(),
+            });
+        }
+    }
+    connections.sort();
+    connections
+}
@@ -68,7 +68,7 @@ impl ChangeSetTestHelpers {
 
     /// Force Apply Changeset To base Approvals
     pub async fn force_apply_change_set_to_base_approvals(ctx: &mut DalContext) -> Result<()> {
-        ChangeSet::prepare_for_force_apply(ctx).await?;
+        ChangeSet::prepare_for_force_apply(ctx, false).await?;
 
         Self::commit_and_update_snapshot_to_visibility(ctx).await?;
 
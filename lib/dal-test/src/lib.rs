@@ -44,12 +44,14 @@ use dal::{
 use derive_builder::Builder;
 use jwt_simple::prelude::RS256KeyPair;
 use lazy_static::lazy_static;
+use permissions::{EmbeddedPermissionsBackend, PermissionsBackendKind, PermissionsClient};
 use si_crypto::{
     SymmetricCryptoService, SymmetricCryptoServiceConfig, SymmetricCryptoServiceConfigFile,
     VeritechEncryptionKey,
 };
 use si_data_nats::{NatsClient, NatsConfig};
 use si_data_pg::{PgPool, PgPoolConfig};
+use si_data_spicedb::{SpiceDbClient, SpiceDbConfig};
 use si_layer_cache::hybrid_cache::CacheConfig;
 use si_runtime::DedicatedExecutor;
 use si_std::ResultExt;
@@ -65,7 +67,9 @@ use uuid::Uuid;
 pub mod expand_helpers;
 
 pub mod expected;
+pub mod fixture;
 pub mod helpers;
+pub mod veritech_recording;
 
 mod signup;
 mod test_exclusive_schemas;
@@ -78,6 +82,7 @@ pub use si_test_macros::{dal_test as test, sdf_test};
 pub use signup::WorkspaceSignup;
 pub use telemetry;
 pub use tracing_subscriber;
+pub use veritech_recording::{TestVeritech, VeritechMode};
 
 const DEFAULT_TEST_PG_USER: &str = "si_test";
 const DEFAULT_TEST_PG_PORT_STR: &str = "6432";
@@ -91,6 +96,10 @@ const ENV_VAR_LAYER_CACHE_PG_DBNAME: &str = "SI_TEST_LAYER_CACHE_PG_DBNAME";
 const ENV_VAR_PG_USER: &str = "SI_TEST_PG_USER";
 const ENV_VAR_PG_PORT: &str = "SI_TEST_PG_PORT";
 const ENV_VAR_KEEP_OLD_DBS: &str = "SI_TEST_KEEP_OLD_DBS";
+const ENV_VAR_PERMISSIONS_BACKEND: &str = "SI_TEST_PERMISSIONS_BACKEND";
+const ENV_VAR_SPICEDB_URL: &str = "SI_TEST_SPICEDB_URL";
+const ENV_VAR_VERITECH_MODE: &str = "SI_TEST_VERITECH_MODE";
+const ENV_VAR_VERITECH_RECORDINGS: &str = "SI_TEST_VERITECH_RECORDINGS";
 
 #[allow(missing_docs)]
 pub static COLOR_EYRE_INIT: Once = Once::new();
@@ -166,6 +175,14 @@ pub struct Config {
     #[allow(dead_code)]
     #[builder(default = "si_layer_cache::default_pg_pool_config()")]
     layer_cache_pg_pool: PgPoolConfig,
+    #[builder(default = "PermissionsBackendKind::Embedded")]
+    permissions_backend: PermissionsBackendKind,
+    #[builder(default)]
+    spicedb: SpiceDbConfig,
+    #[builder(default)]
+    veritech_mode: VeritechMode,
+    #[builder(default)]
+    veritech_recordings_path: Option<PathBuf>,
 }
 
 impl Config {
@@ -217,6 +234,21 @@ impl Config {
             config.module_index_url = value;
         }
 
+        if let Ok(value) = env::var(ENV_VAR_PERMISSIONS_BACKEND) {
+            config.permissions_backend = value
+                .parse()
+                .wrap_err_with(|| format!("invalid {ENV_VAR_PERMISSIONS_BACKEND}: {value}"))?;
+        }
+        if let Ok(value) = env::var(ENV_VAR_SPICEDB_URL) {
+            config.spicedb.endpoint = value.parse()?;
+        }
+        if let Ok(value) = env::var(ENV_VAR_VERITECH_MODE) {
+            config.veritech_mode = value.parse()?;
+        }
+        if let Ok(value) = env::var(ENV_VAR_VERITECH_RECORDINGS) {
+            config.veritech_recordings_path = Some(value.into());
+        }
+
         debug!(?config, "test config");
 
         Ok(config)
@@ -566,6 +598,25 @@ pub async fn jwt_public_signing_key() -> Result<JwtPublicSigningKey> {
     Ok(key)
 }
 
+/// Returns a client for the permissions backend selected by `SI_TEST_PERMISSIONS_BACKEND`.
+///
+/// The default is a fresh, in-memory [`EmbeddedPermissionsBackend`] so that tests exercising
+/// permission checks do not need a running SpiceDB.
+pub async fn permissions_client() -> Result<PermissionsClient> {
+    let (backend, spicedb_config) = {
+        let context_builder = TEST_CONTEXT_BUILDER.lock().await;
+        let config = context_builder.config()?;
+        (config.permissions_backend, config.spicedb.clone())
+    };
+
+    let client = match backend {
+        PermissionsBackendKind::Embedded => EmbeddedPermissionsBackend::in_memory()?.into(),
+        PermissionsBackendKind::SpiceDb => SpiceDbClient::new(&spicedb_config).await?.into(),
+    };
+
+    Ok(client)
+}
+
 /// Returns a JWT private signing key, which is used to sign claims.
 #[allow(clippy::expect_used, clippy::panic)]
 pub async fn jwt_private_signing_key() -> Result<RS256KeyPair> {
@@ -646,16 +697,7 @@ pub async fn veritech_server_for_uds_cyclone(
     nats_config: NatsConfig,
     token: CancellationToken,
 ) -> Result<veritech_server::Server> {
-    let config: veritech_server::Config = {
-        let mut config_file = veritech_server::ConfigFile::default_local_uds();
-        config_file.nats = nats_config;
-        config_file.cyclone.set_pool_size(4);
-        veritech_server::detect_and_configure_development(&mut config_file)
-            .wrap_err("failed to detect and configure Veritech ConfigFile")?;
-        config_file
-            .try_into()
-            .wrap_err("failed to build Veritech server config")?
-    };
+    let config = veritech_server_config(nats_config)?;
 
     let server = veritech_server::Server::from_config(config, token)
         .await
@@ -664,6 +706,27 @@ pub async fn veritech_server_for_uds_cyclone(
     Ok(server)
 }
 
+fn veritech_server_config(nats_config: NatsConfig) -> Result<veritech_server::Config> {
+    let mut config_file = veritech_server::ConfigFile::default_local_uds();
+    config_file.nats = nats_config;
+    config_file.cyclone.set_pool_size(4);
+    veritech_server::detect_and_configure_development(&mut config_file)
+        .wrap_err("failed to detect and configure Veritech ConfigFile")?;
+    config_file
+        .try_into()
+        .wrap_err("failed to build Veritech server config")
+}
+
+/// Configures and builds the veritech for a test, which either executes functions with cyclone
+/// or records or replays their results, depending on `SI_TEST_VERITECH_MODE` (see
+/// [`veritech_recording`]).
+pub async fn veritech_for_tests(
+    test_context: &TestContext,
+    token: CancellationToken,
+) -> Result<TestVeritech> {
+    TestVeritech::new(&test_context.config, test_context.nats_conn.clone(), token).await
+}
+
 /// Configures and builds a [`forklift_server::Server`] suitable for running alongside DAL
 /// object-related tests.
 pub async fn forklift_server(
@@ -786,9 +849,8 @@ async fn global_setup(test_context_builer: TestContextBuilder) -> Result<()> {
 
     // Start up a Veritech server as a task exclusively to allow the migrations to run
     info!("starting Veritech server for initial migrations");
-    let veritech_server =
-        veritech_server_for_uds_cyclone(test_context.config.nats.clone(), token.clone()).await?;
-    tracker.spawn(veritech_server.run());
+    let veritech = veritech_for_tests(&test_context, token.clone()).await?;
+    tracker.spawn(veritech.run());
 
     tracker.close();
 
@@ -0,0 +1,746 @@
+//! Recording and replaying function executions, so that tests can run without veritech and
+//! cyclone executing every function.
+//!
+//! The mode is picked with `SI_TEST_VERITECH_MODE`:
+//!
+//! - `live` (the default): a [`veritech_server::Server`] executes everything with cyclone
+//! - `record`: everything is executed by a live veritech as usual, and every result is written
+//!   to the recordings directory
+//! - `replay`: results are served from the recordings directory without executing anything.
+//!   Requests that were never recorded fail with an error saying so.
+//! - `replay-or-live`: like `replay`, but requests that were never recorded are passed through to
+//!   a live veritech
+//!
+//! The recordings directory is set with `SI_TEST_VERITECH_RECORDINGS`. Each recording is its own
+//! file at `<kind>/<code hash>/<args hash>.json`, so that tests running at the same time can
+//! record without stepping on each other. The args hash covers everything in the request except
+//! the execution id and the code, with secrets decrypted, so requests only match when the
+//! functions see the same inputs. Ids (of components and views, for example) differ every time a
+//! test runs, so they are numbered in the order they appear in before hashing, and the values of
+//! fields like `last_synced` are left out. Secrets are redacted from the args that are written
+//! down.
+//!
+//! When recording or replaying, requests are taken off the veritech work queue of the test by a
+//! fake veritech. A live veritech, if one is needed, is run under its own NATS subject prefix and
+//! only sees what the fake one passes on to it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use dal::ContentHash;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use si_crypto::{SensitiveStrings, VeritechDecryptionKey};
+use si_data_nats::{async_nats, jetstream, HeaderMap, NatsClient, NatsConfig, Subject};
+use telemetry::prelude::*;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use ulid::Ulid;
+use veritech_client::{FunctionResult, FunctionResultFailure, OutputStream};
+use veritech_core::{
+    decrypt_value_tree, incoming_subject, reply_mailbox_for_output, reply_mailbox_for_result,
+    veritech_work_queue, VeritechRequest, FINAL_MESSAGE_HEADER_KEY, REPLY_INBOX_HEADER_NAME,
+};
+
+use crate::{veritech_server_config, veritech_server_for_uds_cyclone, Config};
+
+const CONSUMER_NAME: &str = "veritech-recorder";
+const REDACTED_ARGS_FIELDS: &[&str] = &["executionId", "codeBase64"];
+/// Fields whose values change every time a test runs, like the time a resource was last synced.
+const VOLATILE_ARGS_FIELDS: &[&str] = &["last_synced"];
+
+/// How function executions are handled in tests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VeritechMode {
+    /// Execute everything with cyclone
+    #[default]
+    Live,
+    /// Execute everything with cyclone and record the results
+    Record,
+    /// Serve recorded results, failing requests that were never recorded
+    Replay,
+    /// Serve recorded results, executing requests that were never recorded with cyclone
+    ReplayOrLive,
+}
+
+impl VeritechMode {
+    fn needs_live(self) -> bool {
+        matches!(self, Self::Live | Self::Record | Self::ReplayOrLive)
+    }
+}
+
+impl FromStr for VeritechMode {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "live" => Ok(Self::Live),
+            "record" => Ok(Self::Record),
+            "replay" => Ok(Self::Replay),
+            "replay-or-live" => Ok(Self::ReplayOrLive),
+            _ => Err(eyre!(
+                "unknown veritech mode {s:?}, expected live, record, replay or replay-or-live"
+            )),
+        }
+    }
+}
+
+/// The veritech a test runs against. See the [module docs](self) for the modes.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum TestVeritech {
+    /// A veritech server executing everything with cyclone
+    Live(veritech_server::Server),
+    /// A fake veritech serving recordings, with a live one behind it if it needs one
+    #[allow(missing_docs)]
+    Recording {
+        recorder: VeritechRecorder,
+        live: Option<veritech_server::Server>,
+    },
+}
+
+impl TestVeritech {
+    pub(crate) async fn new(
+        config: &Config,
+        nats: NatsClient,
+        token: CancellationToken,
+    ) -> Result<Self> {
+        let mode = config.veritech_mode;
+        if mode == VeritechMode::Live {
+            return Ok(Self::Live(
+                veritech_server_for_uds_cyclone(config.nats.clone(), token).await?,
+            ));
+        }
+
+        let recordings_path = config.veritech_recordings_path.clone().ok_or_else(|| {
+            eyre!("SI_TEST_VERITECH_RECORDINGS must be set for veritech mode {mode:?}")
+        })?;
+        let decryption_key = VeritechDecryptionKey::from_config(
+            veritech_server_config(config.nats.clone())?
+                .crypto()
+                .clone(),
+        )
+        .await
+        .wrap_err("failed to load veritech decryption key")?;
+        let recordings = FuncRecordings {
+            path: recordings_path,
+            decryption_key,
+        };
+
+        let (live, live_client) = if mode.needs_live() {
+            let live_nats_config = live_nats_config(&config.nats);
+            let live_client = veritech_client::Client::new(
+                NatsClient::new(&live_nats_config)
+                    .await
+                    .wrap_err("failed to create NatsClient for live veritech")?,
+            );
+            let live = veritech_server_for_uds_cyclone(live_nats_config, token.clone()).await?;
+            (Some(live), Some(live_client))
+        } else {
+            (None, None)
+        };
+
+        Ok(Self::Recording {
+            recorder: VeritechRecorder {
+                mode,
+                nats,
+                live: live_client,
+                recordings: Arc::new(recordings),
+                token,
+            },
+            live,
+        })
+    }
+
+    /// Runs until the cancellation token the veritech was built with is cancelled.
+    pub async fn run(self) {
+        match self {
+            Self::Live(server) => server.run().await,
+            Self::Recording { recorder, live } => {
+                if let Some(live) = live {
+                    tokio::spawn(live.run());
+                }
+                if let Err(err) = recorder.run().await {
+                    error!(si.error.message = ?err, "veritech recorder failed");
+                }
+            }
+        }
+    }
+}
+
+/// The same NATS config, under a subject prefix of its own.
+fn live_nats_config(nats_config: &NatsConfig) -> NatsConfig {
+    let mut live_nats_config = nats_config.clone();
+    live_nats_config.subject_prefix = Some(match &nats_config.subject_prefix {
+        Some(prefix) => format!("{prefix}_live"),
+        None => "live".to_owned(),
+    });
+    live_nats_config
+}
+
+/// Serves the veritech work queue of a test from recordings, and from a live veritech when
+/// recording or when a recording is missing.
+#[derive(Debug)]
+pub struct VeritechRecorder {
+    mode: VeritechMode,
+    nats: NatsClient,
+    live: Option<veritech_client::Client>,
+    recordings: Arc<FuncRecordings>,
+    token: CancellationToken,
+}
+
+impl VeritechRecorder {
+    async fn run(self) -> Result<()> {
+        let prefix = self.nats.metadata().subject_prefix().map(ToOwned::to_owned);
+        let context = jetstream::new(self.nats.clone());
+        let mut messages = veritech_work_queue(&context, prefix.as_deref())
+            .await?
+            .create_consumer(async_nats::jetstream::consumer::pull::Config {
+                durable_name: Some(CONSUMER_NAME.to_owned()),
+                filter_subject: incoming_subject(prefix.as_deref()).to_string(),
+                ..Default::default()
+            })
+            .await?
+            .messages()
+            .await?;
+
+        let recorder = Arc::new(self);
+        loop {
+            tokio::select! {
+                _ = recorder.token.cancelled() => break,
+                message = messages.next() => match message {
+                    Some(Ok(message)) => {
+                        if let Err(err) = message.ack().await {
+                            warn!(si.error.message = ?err, "failed to ack veritech request");
+                        }
+                        let recorder = recorder.clone();
+                        tokio::spawn(async move {
+                            if let Err(err) = recorder
+                                .process(
+                                    &message.subject,
+                                    message.headers.as_ref(),
+                                    &message.payload,
+                                )
+                                .await
+                            {
+                                error!(si.error.message = ?err, "failed to process veritech request");
+                            }
+                        });
+                    }
+                    Some(Err(err)) => {
+                        warn!(si.error.message = ?err, "failed to receive veritech request");
+                    }
+                    None => break,
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replies to the request, with a [`FunctionResultFailure`] if it could not be served, so
+    /// that the caller does not wait for a result that never comes.
+    async fn process(
+        &self,
+        subject: &Subject,
+        headers: Option<&HeaderMap>,
+        payload: &[u8],
+    ) -> Result<()> {
+        let reply_mailbox = headers
+            .and_then(|headers| headers.get(REPLY_INBOX_HEADER_NAME))
+            .map(|value| value.to_string())
+            .ok_or_else(|| eyre!("no reply inbox for veritech request on {subject}"))?;
+        let execution_id = serde_json::from_slice::<Value>(payload)
+            .ok()
+            .and_then(|args| {
+                args.get("executionId")
+                    .and_then(Value::as_str)
+                    .map(ToOwned::to_owned)
+            })
+            .unwrap_or_default();
+
+        let result = match self.result_for(subject, &reply_mailbox, payload).await {
+            Ok(result) => with_execution_id(result, &execution_id),
+            Err(err) => {
+                error!(si.error.message = ?err, "failed to process veritech request");
+                serde_json::to_value(FunctionResult::<()>::Failure(
+                    FunctionResultFailure::new_for_veritech_server_error(
+                        execution_id,
+                        format!("{err:#}"),
+                        0,
+                    ),
+                ))?
+            }
+        };
+
+        self.publish_final_output(&reply_mailbox).await?;
+        self.nats
+            .publish_with_headers(
+                reply_mailbox_for_result(&reply_mailbox),
+                HeaderMap::new(),
+                serde_json::to_vec(&result)?.into(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// The result for the request, recorded or from the live veritech depending on the mode.
+    async fn result_for(
+        &self,
+        subject: &Subject,
+        reply_mailbox: &str,
+        payload: &[u8],
+    ) -> Result<Value> {
+        // Subjects end with the workspace id, change set id and kind of request
+        let mut parts = subject.as_str().rsplit('.');
+        let (Some(kind), Some(change_set_id), Some(workspace_id)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(eyre!("unexpected veritech request subject {subject}"));
+        };
+
+        let request = VeritechRequest::from_subject_and_payload(kind, payload)?;
+        let recording = self.recordings.recording_for(kind, payload)?;
+
+        let recorded = match self.mode {
+            VeritechMode::Replay => return self.recordings.replay(&recording).await,
+            VeritechMode::ReplayOrLive => self.recordings.get(&recording).await?,
+            VeritechMode::Live | VeritechMode::Record => None,
+        };
+        if let Some(result) = recorded {
+            return Ok(result);
+        }
+
+        let live = self
+            .live
+            .as_ref()
+            .ok_or_else(|| eyre!("no live veritech for veritech mode {:?}", self.mode))?;
+        let result = self
+            .execute_live(live, request, reply_mailbox, workspace_id, change_set_id)
+            .await?;
+        if self.mode == VeritechMode::Record {
+            self.recordings
+                .put(&FuncRecording {
+                    result: result.clone(),
+                    ..recording
+                })
+                .await?;
+        }
+
+        Ok(result)
+    }
+
+    /// Executes the request with the live veritech, forwarding its output as it comes.
+    async fn execute_live(
+        &self,
+        live: &veritech_client::Client,
+        request: VeritechRequest,
+        reply_mailbox: &str,
+        workspace_id: &str,
+        change_set_id: &str,
+    ) -> Result<Value> {
+        let (output_tx, mut output_rx) = mpsc::channel::<OutputStream>(64);
+        let nats = self.nats.clone();
+        let output_subject = reply_mailbox_for_output(reply_mailbox);
+        let forward_output = tokio::spawn(async move {
+            while let Some(output) = output_rx.recv().await {
+                let payload = match serde_json::to_vec(&output) {
+                    Ok(payload) => payload,
+                    Err(err) => {
+                        warn!(si.error.message = ?err, "failed to serialize function output");
+                        continue;
+                    }
+                };
+                if let Err(err) = nats
+                    .publish_with_headers(output_subject.clone(), HeaderMap::new(), payload.into())
+                    .await
+                {
+                    warn!(si.error.message = ?err, "failed to forward function output");
+                }
+            }
+        });
+
+        let result = match request {
+            VeritechRequest::ActionRun(request) => serde_json::to_value(
+                live.execute_action_run(output_tx, &request, workspace_id, change_set_id)
+                    .await?,
+            )?,
+            VeritechRequest::Mangement(request) => serde_json::to_value(
+                live.execute_management(output_tx, &request, workspace_id, change_set_id)
+                    .await?,
+            )?,
+            VeritechRequest::Resolver(request) => serde_json::to_value(
+                live.execute_resolver_function(output_tx, &request, workspace_id, change_set_id)
+                    .await?,
+            )?,
+            VeritechRequest::SchemaVariantDefinition(request) => serde_json::to_value(
+                live.execute_schema_variant_definition(
+                    output_tx,
+                    &request,
+                    workspace_id,
+                    change_set_id,
+                )
+                .await?,
+            )?,
+            VeritechRequest::Validation(request) => serde_json::to_value(
+                live.execute_validation(output_tx, &request, workspace_id, change_set_id)
+                    .await?,
+            )?,
+            // Kill requests are not sent to the work queue
+            VeritechRequest::KillExecution(_) => {
+                return Err(eyre!("unexpected kill request on the veritech work queue"))
+            }
+        };
+        forward_output.await?;
+
+        Ok(result)
+    }
+
+    async fn publish_final_output(&self, reply_mailbox: &str) -> Result<()> {
+        let mut headers = HeaderMap::new();
+        headers.insert(FINAL_MESSAGE_HEADER_KEY, "true");
+        self.nats
+            .publish_with_headers(
+                reply_mailbox_for_output(reply_mailbox),
+                headers,
+                Vec::new().into(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// A request and the result a live veritech returned for it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FuncRecording {
+    kind: String,
+    handler: String,
+    code_hash: String,
+    args_hash: String,
+    /// The rest of the request, with secrets redacted
+    args: Value,
+    result: Value,
+}
+
+#[derive(Debug)]
+struct FuncRecordings {
+    path: PathBuf,
+    decryption_key: VeritechDecryptionKey,
+}
+
+impl FuncRecordings {
+    /// A recording for the request, without a result yet.
+    fn recording_for(&self, kind: &str, payload: &[u8]) -> Result<FuncRecording> {
+        let mut args: Value = serde_json::from_slice(payload)?;
+        let handler = args
+            .get("handler")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned();
+        let code = args
+            .get("codeBase64")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let code_hash = ContentHash::new(format!("{handler}\n{code}").as_bytes()).to_string();
+
+        if let Value::Object(fields) = &mut args {
+            for field in REDACTED_ARGS_FIELDS {
+                fields.remove(*field);
+            }
+        }
+        let mut sensitive_strings = SensitiveStrings::default();
+        decrypt_value_tree(&mut args, &mut sensitive_strings, &self.decryption_key)?;
+        let args = normalized(sorted(args), &mut HashMap::new());
+        let serialized_args = serde_json::to_string(&args)?;
+        let args_hash = ContentHash::new(serialized_args.as_bytes()).to_string();
+
+        Ok(FuncRecording {
+            kind: kind.to_owned(),
+            handler,
+            code_hash,
+            args_hash,
+            args: serde_json::from_str(&sensitive_strings.redact(&serialized_args))?,
+            result: Value::Null,
+        })
+    }
+
+    fn path_for(&self, recording: &FuncRecording) -> PathBuf {
+        self.path
+            .join(&recording.kind)
+            .join(&recording.code_hash)
+            .join(format!("{}.json", recording.args_hash))
+    }
+
+    async fn get(&self, recording: &FuncRecording) -> Result<Option<Value>> {
+        let path = self.path_for(recording);
+        match tokio::fs::read(&path).await {
+            Ok(contents) => {
+                let recorded: FuncRecording = serde_json::from_slice(&contents)
+                    .wrap_err_with(|| format!("invalid recording at {}", path.display()))?;
+                debug!(
+                    kind = %recording.kind,
+                    code_hash = %recording.code_hash,
+                    args_hash = %recording.args_hash,
+                    "replaying recorded function result"
+                );
+                Ok(Some(recorded.result))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// The recorded result for the request, failing if it was never recorded.
+    async fn replay(&self, recording: &FuncRecording) -> Result<Value> {
+        self.get(recording).await?.ok_or_else(|| {
+            eyre!(
+                "no recorded result for {} request to {} (code hash {}, args hash {}); \
+                 run the test with SI_TEST_VERITECH_MODE=record to record it",
+                recording.kind,
+                recording.handler,
+                recording.code_hash,
+                recording.args_hash,
+            )
+        })
+    }
+
+    /// Writes the recording to a file of its own, replacing it in one go so that a test reading
+    /// it at the same time never sees half of it.
+    async fn put(&self, recording: &FuncRecording) -> Result<()> {
+        let path = self.path_for(recording);
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        tokio::fs::create_dir_all(dir).await?;
+        let partial_path = dir.join(format!(
+            ".{}.{}",
+            recording.args_hash,
+            crate::random_identifier_string()
+        ));
+        tokio::fs::write(&partial_path, serde_json::to_vec_pretty(recording)?).await?;
+        tokio::fs::rename(&partial_path, &path).await?;
+
+        Ok(())
+    }
+}
+
+/// The value with the keys of all objects in it in order, so that the same args always hash the
+/// same.
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Array(values) => Value::Array(values.into_iter().map(sorted).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, sorted(value)))
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// The value with the ids in it numbered in the order they first appear in, and the values of
+/// [`VOLATILE_ARGS_FIELDS`] left out, so that the same request hashes the same every time a test
+/// runs. `ids` holds the numbers given out so far.
+fn normalized(value: Value, ids: &mut HashMap<String, String>) -> Value {
+    match value {
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|value| normalized(value, ids))
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = if VOLATILE_ARGS_FIELDS.contains(&key.as_str()) {
+                        Value::Null
+                    } else {
+                        normalized(value, ids)
+                    };
+                    (normalized_id(key, ids), value)
+                })
+                .collect(),
+        ),
+        Value::String(string) => Value::String(normalized_id(string, ids)),
+        value => value,
+    }
+}
+
+fn normalized_id(string: String, ids: &mut HashMap<String, String>) -> String {
+    if Ulid::from_string(&string).is_err() {
+        return string;
+    }
+    let next = ids.len();
+    ids.entry(string)
+        .or_insert_with(|| format!("<id {next}>"))
+        .clone()
+}
+
+/// A recorded [`FunctionResult`] as if it was for the execution.
+fn with_execution_id(mut result: Value, execution_id: &str) -> Value {
+    if let Some(Value::Object(fields)) = result
+        .as_object_mut()
+        .and_then(|variants| variants.values_mut().next())
+    {
+        for key in ["executionId", "execution_id"] {
+            if fields.contains_key(key) {
+                fields.insert(key.to_owned(), Value::String(execution_id.to_owned()));
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use si_crypto::VeritechEncryptionKey;
+    use sodiumoxide::crypto::box_;
+    use tempfile::TempDir;
+    use veritech_core::encrypt_value_tree;
+
+    use super::*;
+
+    const KIND: &str = "resolverfunction";
+
+    fn recordings() -> Result<(TempDir, FuncRecordings, VeritechEncryptionKey)> {
+        sodiumoxide::init().map_err(|()| eyre!("failed to init sodiumoxide"))?;
+        let dir = tempfile::tempdir()?;
+        let (public_key, secret_key) = box_::gen_keypair();
+        let recordings = FuncRecordings {
+            path: dir.path().to_owned(),
+            decryption_key: VeritechDecryptionKey::from(secret_key),
+        };
+
+        Ok((dir, recordings, VeritechEncryptionKey::from(public_key)))
+    }
+
+    fn payload(execution_id: &str, args: Value) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&json!({
+            "executionId": execution_id,
+            "handler": "main",
+            "codeBase64": "ZnVuY3Rpb24gbWFpbigpIHt9",
+            "args": args,
+        }))?)
+    }
+
+    fn encrypted(value: &str, encryption_key: &VeritechEncryptionKey) -> Result<Value> {
+        let mut value = json!({ "value": value });
+        encrypt_value_tree(&mut value, encryption_key)?;
+        Ok(value)
+    }
+
+    #[tokio::test]
+    async fn replays_what_was_recorded() -> Result<()> {
+        let (_dir, recordings, _) = recordings()?;
+        let result = json!({ "Success": { "executionId": "recorded", "data": "hello" } });
+
+        let recording = recordings.recording_for(KIND, &payload("recorded", json!("hi"))?)?;
+        recordings
+            .put(&FuncRecording {
+                result: result.clone(),
+                ..recording
+            })
+            .await?;
+
+        let recording = recordings.recording_for(KIND, &payload("replayed", json!("hi"))?)?;
+        let replayed = recordings.replay(&recording).await?;
+        assert_eq!(result, replayed);
+        assert_eq!(
+            json!({ "Success": { "executionId": "replayed", "data": "hello" } }),
+            with_execution_id(replayed, "replayed")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn replay_fails_for_what_was_never_recorded() -> Result<()> {
+        let (_dir, recordings, _) = recordings()?;
+
+        let recording = recordings.recording_for(KIND, &payload("missing", json!("hi"))?)?;
+        assert_eq!(None, recordings.get(&recording).await?);
+        let Err(err) = recordings.replay(&recording).await else {
+            return Err(eyre!("replaying what was never recorded should fail"));
+        };
+        assert!(err.to_string().contains("SI_TEST_VERITECH_MODE=record"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn args_hash_is_stable_with_secrets() -> Result<()> {
+        let (_dir, recordings, encryption_key) = recordings()?;
+
+        // The same secret is encrypted differently every time
+        let first = recordings.recording_for(
+            KIND,
+            &payload("first", encrypted("hunter2", &encryption_key)?)?,
+        )?;
+        let second = recordings.recording_for(
+            KIND,
+            &payload("second", encrypted("hunter2", &encryption_key)?)?,
+        )?;
+        let other = recordings.recording_for(
+            KIND,
+            &payload("other", encrypted("correct horse", &encryption_key)?)?,
+        )?;
+
+        assert_eq!(first.args_hash, second.args_hash);
+        assert_ne!(first.args_hash, other.args_hash);
+        assert!(!serde_json::to_string(&first.args)?.contains("hunter2"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn args_hash_is_stable_with_new_ids() -> Result<()> {
+        let (_dir, recordings, _) = recordings()?;
+        // Two ids as if made one after the other by a test run at the given time
+        let ids = |ms: u64| (Ulid::from_parts(ms, 1), Ulid::from_parts(ms, 2));
+        let components = |this: Ulid, other: Ulid, last_synced: &str| {
+            json!({
+                "thisComponent": { "properties": { "si": { "name": "this" } } },
+                "components": {
+                    this.to_string(): { "properties": { "si": { "name": "this" } } },
+                    other.to_string(): {
+                        "properties": {
+                            "si": { "name": "other", "parentId": this.to_string() },
+                            "resource": { "last_synced": last_synced },
+                        },
+                    },
+                },
+            })
+        };
+
+        let (this, other) = ids(1_727_740_800_000);
+        let first = recordings.recording_for(
+            KIND,
+            &payload("first", components(this, other, "2024-10-01T00:00:00Z"))?,
+        )?;
+        let (this, other) = ids(1_727_827_200_000);
+        let second = recordings.recording_for(
+            KIND,
+            &payload("second", components(this, other, "2024-10-02T00:00:00Z"))?,
+        )?;
+        // Ids are numbered in the order they were made in, so the same components made in the
+        // other order are different args
+        let swapped = recordings.recording_for(
+            KIND,
+            &payload("swapped", components(other, this, "2024-10-02T00:00:00Z"))?,
+        )?;
+
+        assert_eq!(first.args_hash, second.args_hash);
+        assert_ne!(first.args_hash, swapped.args_hash);
+
+        Ok(())
+    }
+}
@@ -54,6 +54,9 @@ use thiserror::Error;
 use tokio::sync::{RwLock, TryLockError};
 
 pub use dependent_value_graph::DependentValueGraph;
+pub use dependent_value_index::{
+    DependentValueIndex, DependentValueIndexError, DependentValueIndexScope,
+};
 
 use crate::attribute::prototype::{AttributePrototypeError, AttributePrototypeSource};
 use crate::change_set::ChangeSetError;
@@ -89,7 +92,9 @@ pub use is_for::ValueIsFor;
 
 pub mod debug;
 pub mod dependent_value_graph;
+pub mod dependent_value_index;
 pub mod is_for;
+pub mod provenance;
 
 #[remain::sorted]
 #[derive(Debug, Error)]
@@ -126,6 +131,8 @@ pub enum AttributeValueError {
     ChangeSet(#[from] ChangeSetError),
     #[error("component error: {0}")]
     Component(#[from] Box<ComponentError>),
+    #[error("dependent value index error: {0}")]
+    DependentValueIndex(#[from] DependentValueIndexError),
     #[error("duplicate key or index {key_or_index} for attribute values {child1} and {child2}")]
     DuplicateKeyOrIndex {
         key_or_index: KeyOrIndex,
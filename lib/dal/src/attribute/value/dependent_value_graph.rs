@@ -20,12 +20,16 @@ use crate::{
 };
 use crate::{ComponentError, ComponentId, Prop, PropKind};
 
+use super::dependent_value_index::{DependentValueIndex, InferredStep, ValueStep};
 use super::{AttributeValue, AttributeValueError, AttributeValueId, AttributeValueResult};
 
 #[derive(Debug, Clone)]
 pub struct DependentValueGraph {
     inner: DependencyGraph<AttributeValueId>,
     values_that_need_to_execute_from_prototype_function: HashSet<AttributeValueId>,
+    initial_value_ids: HashSet<AttributeValueId>,
+    /// The value each value was first discovered through
+    causes: HashMap<AttributeValueId, AttributeValueId>,
 }
 
 // We specifically need to track if the value is one of the child values we
@@ -58,12 +62,21 @@ impl DependentValueGraph {
         let mut dependent_value_graph = Self {
             inner: DependencyGraph::new(),
             values_that_need_to_execute_from_prototype_function: HashSet::new(),
+            initial_value_ids: HashSet::new(),
+            causes: HashMap::new(),
         };
 
         let values = dependent_value_graph.parse_initial_ids(ctx, roots).await?;
         dependent_value_graph
-            .populate_for_values(ctx, values)
+            .initial_value_ids
+            .extend(values.iter().map(WorkQueueValue::id));
+
+        let mut index = DependentValueIndex::for_context(ctx).await?;
+        dependent_value_graph
+            .populate_for_values(ctx, values, &mut index)
             .await?;
+        index.keep(ctx).await?;
+
         Ok(dependent_value_graph)
     }
 
@@ -144,13 +157,15 @@ impl DependentValueGraph {
     /// inferred dependencies based on parentage, for example if a component gets its inputs from a
     /// parent frame, and that frame's output sockets change, we add those downstream input sockets
     /// to the graph.
+    ///
+    /// What was found for each value is taken from the [`DependentValueIndex`] if it is there,
+    /// and put there if not.
     async fn populate_for_values(
         &mut self,
         ctx: &DalContext,
         values: Vec<WorkQueueValue>,
+        index: &mut DependentValueIndex,
     ) -> AttributeValueResult<()> {
-        let workspace_snapshot = ctx.workspace_snapshot()?;
-
         let mut controlling_funcs_for_component = HashMap::new();
         let mut work_queue = VecDeque::from_iter(values);
         let mut seen_list = HashSet::new();
@@ -163,196 +178,78 @@ impl DependentValueGraph {
             }
             seen_list.insert(current_attribute_value.id());
 
-            let current_component_id =
-                AttributeValue::component_id(ctx, current_attribute_value.id()).await?;
-
-            // We need to be sure to only construct the graph out of
-            // "controlling" values. However, controlled values can still be
-            // inputs to functions, so we need to find the prototypes that
-            // depend on them!
-            let current_attribute_value_controlling_value_id =
-                Self::get_controlling_attribute_value_id(
-                    ctx,
-                    current_component_id,
-                    current_attribute_value.id(),
-                    &mut controlling_funcs_for_component,
-                )
-                .await?;
-
-            let value_is_for = AttributeValue::is_for(ctx, current_attribute_value.id()).await?;
-
-            // Gather the Attribute Prototype Arguments that take the thing the
-            // current value is for (prop, or socket) as an input
-            let relevant_apas = {
-                let attribute_prototype_argument_idxs = workspace_snapshot
-                    .incoming_sources_for_edge_weight_kind(
-                        value_is_for,
-                        EdgeWeightKindDiscriminants::PrototypeArgumentValue,
+            let step = match index.step(current_attribute_value.id()) {
+                Some(step) => step.clone(),
+                None => {
+                    let step = Self::step_for_value(
+                        ctx,
+                        current_attribute_value.id(),
+                        &mut controlling_funcs_for_component,
                     )
                     .await?;
-
-                let mut relevant_apas = vec![];
-                for apa_idx in attribute_prototype_argument_idxs {
-                    let apa = workspace_snapshot
-                        .get_node_weight(apa_idx)
-                        .await?
-                        .get_attribute_prototype_argument_node_weight()?;
-
-                    match apa.targets() {
-                        // If there are no targets, this is a schema-level attribute prototype argument
-                        None => relevant_apas.push(apa),
-                        Some(targets) => {
-                            if targets.source_component_id == current_component_id {
-                                // Both "deleted" and not deleted Components can feed data into
-                                // "deleted" Components. **ONLY** not deleted Components can feed
-                                // data into not deleted Components.
-                                if Component::should_data_flow_between_components(
-                                    ctx,
-                                    targets.destination_component_id,
-                                    targets.source_component_id,
-                                )
-                                .await
-                                .map_err(|e| AttributeValueError::Component(Box::new(e)))?
-                                {
-                                    relevant_apas.push(apa)
-                                }
-                            }
-                        }
-                    }
+                    index.insert(current_attribute_value.id(), step.clone());
+                    step
                 }
-                relevant_apas
             };
 
-            match value_is_for {
-                ValueIsFor::Prop(prop_id) => {
-                    let prop = Prop::get_by_id(ctx, prop_id).await?;
-                    if prop.kind == PropKind::Object {
-                        // The children of an object might themselves be the
-                        // input to another function, so we have to add them to
-                        // the calculation of the graph, as we encounter them.
-                        // We use `seen_list` to ensure we don't reprocess these
-                        // values or the parents of these values.
-                        for child_value_id in AttributeValue::get_child_av_ids_in_order(
-                            ctx,
-                            current_attribute_value.id(),
-                        )
-                        .await?
-                        {
-                            if !seen_list.contains(&child_value_id) {
-                                work_queue.push_back(WorkQueueValue::ObjectChild(child_value_id));
-                            }
-                        }
-                    }
-                }
-                // Check if this value is an output socket as the attribute
-                // value might have implicit dependendcies based on the ancestry
-                // (aka frames/nested frames) note: we filter out non-deleted
-                // targets if the source component is set to be deleted
-                ValueIsFor::OutputSocket(_) => {
-                    let maybe_values_depend_on =
-                        match ComponentOutputSocket::find_inferred_connections(
-                            ctx,
-                            current_attribute_value.id(),
-                        )
-                        .await
-                        {
-                            Ok(values) => values,
-                            // When we first run dvu, the component type might not be set yet.
-                            // In this case, we can assume there aren't downstream inputs that need to
-                            // be queued up.
-                            Err(ComponentError::ComponentMissingTypeValueMaterializedView(_)) => {
-                                vec![]
-                            }
-                            Err(err) => return Err(AttributeValueError::Component(Box::new(err))),
-                        };
-
-                    for component_input_socket in maybe_values_depend_on {
-                        // Both "deleted" and not deleted Components can feed data into
-                        // "deleted" Components. **ONLY** not deleted Components can feed
-                        // data into not deleted Components.
-                        let destination_component_id =
-                            AttributeValue::component_id(ctx, current_attribute_value.id()).await?;
-                        if Component::should_data_flow_between_components(
-                            ctx,
-                            destination_component_id,
-                            component_input_socket.component_id,
-                        )
-                        .await
-                        .map_err(|e| AttributeValueError::Component(Box::new(e)))?
-                        {
-                            work_queue.push_back(WorkQueueValue::Discovered(
-                                component_input_socket.attribute_value_id,
-                            ));
-                            self.value_depends_on(
-                                component_input_socket.attribute_value_id,
-                                current_attribute_value.id(),
-                            );
-
-                            found_deps = true;
-                        }
-                    }
+            // The children of an object might themselves be the input to another function, so
+            // we have to add them to the calculation of the graph, as we encounter them. We use
+            // `seen_list` to ensure we don't reprocess these values or the parents of these
+            // values.
+            for &child_value_id in &step.object_child_ids {
+                if !seen_list.contains(&child_value_id) {
+                    self.discovered_through(child_value_id, current_attribute_value.id());
+                    work_queue.push_back(WorkQueueValue::ObjectChild(child_value_id));
                 }
-                _ => {}
             }
 
-            // Find the values that are set by the prototype for the relevant
-            // AttributePrototypeArguments, and declare that these values depend
-            // on the value of the current value
-            for apa in relevant_apas {
-                let prototype_id =
-                    AttributePrototypeArgument::prototype_id_for_argument_id(ctx, apa.id().into())
-                        .await?;
-
-                let attribute_value_ids =
-                    AttributePrototype::attribute_value_ids(ctx, prototype_id).await?;
-
-                for attribute_value_id in attribute_value_ids {
-                    let filter_component_id = match apa.targets() {
-                        None => current_component_id,
-                        Some(targets) => targets.destination_component_id,
-                    };
-                    let component_id =
-                        AttributeValue::component_id(ctx, attribute_value_id).await?;
-
-                    if component_id != filter_component_id {
-                        continue;
-                    }
-
-                    // If the input to this function is a value that is a child of another dynamic
-                    // function, we should just depend on the controlling value in the dependency
-                    // graph, since we can't guarantee that the controlled value won't be destroyed
-                    // when "populating" nested value
-                    let controlling_attribute_value_id = Self::get_controlling_attribute_value_id(
+            // Check if this value is an output socket as the attribute
+            // value might have implicit dependendcies based on the ancestry
+            // (aka frames/nested frames) note: we filter out non-deleted
+            // targets if the source component is set to be deleted
+            if let Some(inferred) = &step.inferred {
+                for &(input_socket_component_id, input_socket_value_id) in
+                    &inferred.input_socket_values
+                {
+                    // Both "deleted" and not deleted Components can feed data into
+                    // "deleted" Components. **ONLY** not deleted Components can feed
+                    // data into not deleted Components.
+                    if Component::should_data_flow_between_components(
                         ctx,
-                        component_id,
-                        attribute_value_id,
-                        &mut controlling_funcs_for_component,
+                        step.component_id,
+                        input_socket_component_id,
                     )
-                    .await?;
-
-                    work_queue
-                        .push_back(WorkQueueValue::Discovered(controlling_attribute_value_id));
-                    self.inner.id_depends_on(
-                        controlling_attribute_value_id,
-                        current_attribute_value_controlling_value_id,
-                    );
+                    .await
+                    .map_err(|e| AttributeValueError::Component(Box::new(e)))?
+                    {
+                        self.discovered_through(
+                            input_socket_value_id,
+                            current_attribute_value.id(),
+                        );
+                        work_queue.push_back(WorkQueueValue::Discovered(input_socket_value_id));
+                        self.value_depends_on(input_socket_value_id, current_attribute_value.id());
 
-                    found_deps = true;
+                        found_deps = true;
+                    }
                 }
             }
 
+            for &dependent_value_id in &step.dependent_ids {
+                self.discovered_through(dependent_value_id, current_attribute_value.id());
+                work_queue.push_back(WorkQueueValue::Discovered(dependent_value_id));
+                self.inner
+                    .id_depends_on(dependent_value_id, step.controlling_value_id);
+
+                found_deps = true;
+            }
+
             // Parent props always depend on their children, even if those parents do not have
             // "dependent" functions. Adding them to the graph ensures we execute the entire set of
             // dependent values here (suppose for example an output socket depends on the root
             // prop, we have to be sure we add that output socket to the graph if a child of root
             // changes, because if a child of root has changed, then the view of root to the leaves
             // will also change)
-            if let Some(parent_attribute_value_id) = AttributeValue::parent_attribute_value_id(
-                ctx,
-                current_attribute_value_controlling_value_id,
-            )
-            .await?
-            {
+            if let Some(parent_attribute_value_id) = step.parent_value_id {
                 // If this is one of child values we added speculatively we
                 // should only walk the parent tree if we have actually found a
                 // dep for this value.  Otherwise we will add unnecessary values
@@ -363,17 +260,236 @@ impl DependentValueGraph {
                     continue;
                 }
 
+                self.discovered_through(parent_attribute_value_id, current_attribute_value.id());
                 work_queue.push_back(WorkQueueValue::Discovered(parent_attribute_value_id));
-                self.inner.id_depends_on(
-                    parent_attribute_value_id,
-                    current_attribute_value_controlling_value_id,
-                );
+                self.inner
+                    .id_depends_on(parent_attribute_value_id, step.controlling_value_id);
             }
         }
 
         Ok(())
     }
 
+    /// Walks the prototypes and arguments that take the value as an input, to find what depends
+    /// on it.
+    async fn step_for_value(
+        ctx: &DalContext,
+        attribute_value_id: AttributeValueId,
+        controlling_funcs_for_component: &mut HashMap<
+            ComponentId,
+            HashMap<AttributeValueId, ControllingFuncData>,
+        >,
+    ) -> AttributeValueResult<ValueStep> {
+        let workspace_snapshot = ctx.workspace_snapshot()?;
+
+        let current_component_id = AttributeValue::component_id(ctx, attribute_value_id).await?;
+
+        // We need to be sure to only construct the graph out of
+        // "controlling" values. However, controlled values can still be
+        // inputs to functions, so we need to find the prototypes that
+        // depend on them!
+        let controlling_value_id = Self::get_controlling_attribute_value_id(
+            ctx,
+            current_component_id,
+            attribute_value_id,
+            controlling_funcs_for_component,
+        )
+        .await?;
+
+        let value_is_for = AttributeValue::is_for(ctx, attribute_value_id).await?;
+
+        let mut step = ValueStep {
+            component_id: current_component_id,
+            controlling_value_id,
+            object_child_ids: vec![],
+            dependent_ids: vec![],
+            parent_value_id: None,
+            other_component_ids: HashSet::new(),
+            inferred: None,
+        };
+
+        // Check if this value is an output socket as the attribute
+        // value might have implicit dependendcies based on the ancestry
+        // (aka frames/nested frames) note: we filter out non-deleted
+        // targets if the source component is set to be deleted
+        if let ValueIsFor::OutputSocket(_) = value_is_for {
+            step.inferred = Some(
+                Self::inferred_step_for_output_socket(
+                    ctx,
+                    current_component_id,
+                    attribute_value_id,
+                )
+                .await?,
+            );
+        }
+
+        // Gather the Attribute Prototype Arguments that take the thing the
+        // current value is for (prop, or socket) as an input
+        let relevant_apas = {
+            let attribute_prototype_argument_idxs = workspace_snapshot
+                .incoming_sources_for_edge_weight_kind(
+                    value_is_for,
+                    EdgeWeightKindDiscriminants::PrototypeArgumentValue,
+                )
+                .await?;
+
+            let mut relevant_apas = vec![];
+            for apa_idx in attribute_prototype_argument_idxs {
+                let apa = workspace_snapshot
+                    .get_node_weight(apa_idx)
+                    .await?
+                    .get_attribute_prototype_argument_node_weight()?;
+
+                match apa.targets() {
+                    // If there are no targets, this is a schema-level attribute prototype argument
+                    None => relevant_apas.push(apa),
+                    Some(targets) => {
+                        if targets.source_component_id == current_component_id {
+                            step.other_component_ids
+                                .insert(targets.destination_component_id);
+
+                            // Both "deleted" and not deleted Components can feed data into
+                            // "deleted" Components. **ONLY** not deleted Components can feed
+                            // data into not deleted Components.
+                            if Component::should_data_flow_between_components(
+                                ctx,
+                                targets.destination_component_id,
+                                targets.source_component_id,
+                            )
+                            .await
+                            .map_err(|e| AttributeValueError::Component(Box::new(e)))?
+                            {
+                                relevant_apas.push(apa)
+                            }
+                        }
+                    }
+                }
+            }
+            relevant_apas
+        };
+
+        if let ValueIsFor::Prop(prop_id) = value_is_for {
+            let prop = Prop::get_by_id(ctx, prop_id).await?;
+            if prop.kind == PropKind::Object {
+                step.object_child_ids =
+                    AttributeValue::get_child_av_ids_in_order(ctx, attribute_value_id).await?;
+            }
+        }
+
+        // Find the values that are set by the prototype for the relevant
+        // AttributePrototypeArguments, and declare that these values depend
+        // on the value of the current value
+        for apa in relevant_apas {
+            let prototype_id =
+                AttributePrototypeArgument::prototype_id_for_argument_id(ctx, apa.id().into())
+                    .await?;
+
+            let attribute_value_ids =
+                AttributePrototype::attribute_value_ids(ctx, prototype_id).await?;
+
+            for attribute_value_id in attribute_value_ids {
+                let filter_component_id = match apa.targets() {
+                    None => current_component_id,
+                    Some(targets) => targets.destination_component_id,
+                };
+                let component_id = AttributeValue::component_id(ctx, attribute_value_id).await?;
+
+                if component_id != filter_component_id {
+                    continue;
+                }
+
+                // If the input to this function is a value that is a child of another dynamic
+                // function, we should just depend on the controlling value in the dependency
+                // graph, since we can't guarantee that the controlled value won't be destroyed
+                // when "populating" nested value
+                let controlling_attribute_value_id = Self::get_controlling_attribute_value_id(
+                    ctx,
+                    component_id,
+                    attribute_value_id,
+                    controlling_funcs_for_component,
+                )
+                .await?;
+
+                step.dependent_ids.push(controlling_attribute_value_id);
+            }
+        }
+
+        step.parent_value_id =
+            AttributeValue::parent_attribute_value_id(ctx, controlling_value_id).await?;
+
+        Ok(step)
+    }
+
+    /// Finds the input sockets the output socket is inferred to be connected to. Nothing can be
+    /// inferred for a component outside of any frame, so that takes no look at its neighbours.
+    async fn inferred_step_for_output_socket(
+        ctx: &DalContext,
+        component_id: ComponentId,
+        attribute_value_id: AttributeValueId,
+    ) -> AttributeValueResult<InferredStep> {
+        let workspace_snapshot = ctx.workspace_snapshot()?;
+
+        let has_parent = Component::get_parent_by_id(ctx, component_id)
+            .await
+            .map_err(|e| AttributeValueError::Component(Box::new(e)))?
+            .is_some();
+        let has_children = !workspace_snapshot
+            .frame_contains_components(component_id)
+            .await
+            .map_err(|e| AttributeValueError::Component(Box::new(e)))?
+            .is_empty();
+        if !has_parent && !has_children {
+            return Ok(InferredStep::default());
+        }
+
+        let input_sockets =
+            match ComponentOutputSocket::find_inferred_connections(ctx, attribute_value_id).await {
+                Ok(input_sockets) => input_sockets,
+                // When we first run dvu, the component type might not be set yet.
+                // In this case, we can assume there aren't downstream inputs that need to
+                // be queued up.
+                Err(ComponentError::ComponentMissingTypeValueMaterializedView(_)) => vec![],
+                Err(err) => return Err(AttributeValueError::Component(Box::new(err))),
+            };
+        let tree_component_ids = workspace_snapshot
+            .inferred_connection_graph(ctx)
+            .await?
+            .component_ids_in_tree(component_id);
+
+        Ok(InferredStep {
+            input_socket_values: input_sockets
+                .into_iter()
+                .map(|input_socket| (input_socket.component_id, input_socket.attribute_value_id))
+                .collect(),
+            tree_component_ids,
+        })
+    }
+
+    fn discovered_through(&mut self, value_id: AttributeValueId, cause_id: AttributeValueId) {
+        if !self.initial_value_ids.contains(&value_id) {
+            self.causes.entry(value_id).or_insert(cause_id);
+        }
+    }
+
+    /// Why the value is in the graph: the values it was discovered through, starting with one
+    /// of the values the graph was built for and ending with the value itself. `None` if the
+    /// value was never in the graph.
+    pub fn cause_chain(&self, value_id: AttributeValueId) -> Option<Vec<AttributeValueId>> {
+        if !self.initial_value_ids.contains(&value_id) && !self.causes.contains_key(&value_id) {
+            return None;
+        }
+
+        let mut chain = vec![value_id];
+        let mut current_id = value_id;
+        while let Some(&cause_id) = self.causes.get(&current_id) {
+            chain.push(cause_id);
+            current_id = cause_id;
+        }
+        chain.reverse();
+
+        Some(chain)
+    }
+
     pub async fn debug_dot(&self, ctx: &DalContext, suffix: Option<&str>) {
         let mut is_for_map = HashMap::new();
 
@@ -0,0 +1,728 @@
+//! What [`DependentValueGraph`](super::DependentValueGraph) found out about each value by walking
+//! the snapshot, stored for each change set so that the next graph only has to walk the values
+//! that the changes since can affect.
+//!
+//! For each value, the index keeps a [`ValueStep`]: the values that depend on it, its parent,
+//! its object children and, for output sockets, the input sockets it is inferred to be
+//! connected to through frames. Building a graph out of the index takes no walk of the snapshot,
+//! and its topological order comes out of the steps alone.
+//!
+//! The index of a change set is stored along with the address of the snapshot it holds for, and
+//! a version that counts up with every change to it. A step is tagged with the components whose
+//! structure it depends on (see [`ChangeScopeKind::Structure`]), and a step with inferred
+//! connections also with the components of its tree of frames, whose values (like their types)
+//! decide what is inferred. It is kept up to date like this:
+//!
+//! - When the rebaser performs updates on a change set, it works out what they can affect from
+//!   the updated nodes and edges (see [`DependentValueIndexScope`]), drops the tagged steps, and
+//!   moves the index on to the new snapshot with [`DependentValueIndex::advance`].
+//! - When a graph is built, the steps missing from the index are walked and stored. An index that
+//!   was not moved along with its change set (because its snapshot was written some other way)
+//!   is brought up to date by diffing the two snapshots, or dropped if the old one is gone.
+//! - A change set without an index starts from the index of any change set at the same snapshot,
+//!   which is usually the one it was created from.
+//!
+//! The process that stored an index last also keeps it in memory, so that it is only read back
+//! when another process changed it since.
+//!
+//! Dependent values updates also record why each value they recompute was part of the graph,
+//! which [`DependentValueIndex::cause_chain`] looks up.
+
+use std::collections::{HashMap, HashSet};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use si_data_pg::{InstrumentedTransaction, PgError, PgPoolError, PgRow};
+use si_events::WorkspaceSnapshotAddress;
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::workspace_snapshot::change_set_cache::ChangeSetCache;
+use crate::workspace_snapshot::graph::detect_updates::Update;
+use crate::workspace_snapshot::graph::{ChangeScope, ChangeScopeKind};
+use crate::workspace_snapshot::WorkspaceSnapshotError;
+use crate::{ChangeSetId, ComponentId, DalContext, TransactionsError, WorkspaceSnapshot};
+
+use super::{AttributeValueId, DependentValueGraph};
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum DependentValueIndexError {
+    #[error("pg error: {0}")]
+    Pg(#[from] PgError),
+    #[error("pg pool error: {0}")]
+    PgPool(#[from] PgPoolError),
+    #[error("serde json error: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("transactions error: {0}")]
+    Transactions(#[from] TransactionsError),
+    #[error("workspace snapshot error: {0}")]
+    WorkspaceSnapshot(#[from] WorkspaceSnapshotError),
+}
+
+pub type DependentValueIndexResult<T> = Result<T, DependentValueIndexError>;
+
+/// How many change sets to keep indexes in memory for.
+const MAX_CACHED_INDEXES: usize = 64;
+
+/// Selects the stored indexes, with a row for each of their steps.
+const SELECT_INDEXES_WITH_STEPS: &str =
+    "SELECT i.workspace_snapshot_address, i.version, s.attribute_value_id, s.step
+    FROM dependent_value_indexes AS i
+    LEFT JOIN dependent_value_index_steps AS s ON s.change_set_id = i.change_set_id";
+
+static CACHED_INDEXES: Lazy<ChangeSetCache<DependentValueIndex>> =
+    Lazy::new(|| ChangeSetCache::new(MAX_CACHED_INDEXES));
+
+/// What walking the prototypes and arguments for a single value found.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ValueStep {
+    pub(crate) component_id: ComponentId,
+    /// The value in control of this one, which is the value itself unless a parent of it is set
+    /// by a dynamic function
+    pub(crate) controlling_value_id: AttributeValueId,
+    pub(crate) object_child_ids: Vec<AttributeValueId>,
+    /// The controlling values of the values that take this one as an argument
+    pub(crate) dependent_ids: Vec<AttributeValueId>,
+    pub(crate) parent_value_id: Option<AttributeValueId>,
+    /// The components other than its own that the walk looked at
+    pub(crate) other_component_ids: HashSet<ComponentId>,
+    /// For output sockets, the input sockets they are inferred to be connected to
+    pub(crate) inferred: Option<InferredStep>,
+}
+
+/// The input sockets an output socket is inferred to be connected to through frames.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InferredStep {
+    /// The values of the input sockets, with their components
+    pub(crate) input_socket_values: Vec<(ComponentId, AttributeValueId)>,
+    /// The components in the tree of frames of the output socket's component. Empty if it is
+    /// not in one, since nothing is inferred then.
+    pub(crate) tree_component_ids: HashSet<ComponentId>,
+}
+
+impl ValueStep {
+    fn component_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        std::iter::once(self.component_id)
+            .chain(self.other_component_ids.iter().copied())
+            .chain(self.tree_component_ids())
+    }
+
+    fn tree_component_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.inferred
+            .iter()
+            .flat_map(|inferred| inferred.tree_component_ids.iter().copied())
+    }
+}
+
+/// What a set of changes can drop from a [`DependentValueIndex`]: the steps that depend on the
+/// structure of the components in one scope, and the inferred connections that depend on the
+/// values of the components in the other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DependentValueIndexScope {
+    structure: ChangeScope,
+    values: ChangeScope,
+}
+
+impl DependentValueIndexScope {
+    pub fn nothing() -> Self {
+        Self {
+            structure: ChangeScope::nothing(),
+            values: ChangeScope::nothing(),
+        }
+    }
+
+    fn same_for_both(scope: ChangeScope) -> Self {
+        Self {
+            structure: scope.clone(),
+            values: scope,
+        }
+    }
+
+    fn is_everything(&self) -> bool {
+        self.structure == ChangeScope::Everything || self.values == ChangeScope::Everything
+    }
+
+    /// Adds what `updates` can affect going by the snapshot. For updates performed in place, this
+    /// has to be done both before and after they are performed.
+    pub async fn add_updates(
+        &mut self,
+        workspace_snapshot: &WorkspaceSnapshot,
+        updates: &[Update],
+    ) {
+        self.structure.merge(
+            workspace_snapshot
+                .partial_change_scope_of_updates(updates, ChangeScopeKind::Structure)
+                .await,
+        );
+        self.values.merge(
+            workspace_snapshot
+                .partial_change_scope_of_updates(updates, ChangeScopeKind::StructureAndValues)
+                .await,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct DependentValueIndex {
+    change_set_id: ChangeSetId,
+    /// The snapshot the steps hold for
+    address: WorkspaceSnapshotAddress,
+    /// The version of the stored index this started from, if one was stored for the change set
+    stored_version: Option<i64>,
+    steps: HashMap<AttributeValueId, ValueStep>,
+    /// The values whose steps depend on the structure of each component
+    value_ids_by_component: HashMap<ComponentId, HashSet<AttributeValueId>>,
+    /// The values whose inferred connections depend on the values of each component
+    value_ids_by_tree_component: HashMap<ComponentId, HashSet<AttributeValueId>>,
+    /// The steps that are not stored yet
+    inserted: HashSet<AttributeValueId>,
+    /// The steps that have to be removed from the stored index
+    removed: HashSet<AttributeValueId>,
+    hits: usize,
+    misses: usize,
+}
+
+impl DependentValueIndex {
+    fn new(change_set_id: ChangeSetId, address: WorkspaceSnapshotAddress) -> Self {
+        Self {
+            change_set_id,
+            address,
+            stored_version: None,
+            steps: HashMap::new(),
+            value_ids_by_component: HashMap::new(),
+            value_ids_by_tree_component: HashMap::new(),
+            inserted: HashSet::new(),
+            removed: HashSet::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The index for the change set of the context, brought up to date with its snapshot.
+    #[instrument(
+        name = "dependent_value_index.for_context",
+        level = "debug",
+        skip_all,
+        fields(
+            si.dependent_value_index.cached = Empty,
+            si.dependent_value_index.steps = Empty,
+        )
+    )]
+    pub(crate) async fn for_context(ctx: &DalContext) -> DependentValueIndexResult<Self> {
+        let span = current_span_for_instrument_at!("debug");
+        let workspace_snapshot = ctx.workspace_snapshot()?;
+        let address = workspace_snapshot.id().await;
+        let change_set_id = ctx.change_set_id();
+
+        let client = ctx.pg_pool().get().await?;
+        let stored = client
+            .query_opt(
+                "SELECT version FROM dependent_value_indexes WHERE change_set_id = $1",
+                &[&change_set_id],
+            )
+            .await?;
+
+        let mut index = match stored {
+            Some(row) => {
+                let version: i64 = row.try_get("version")?;
+                match CACHED_INDEXES.take(change_set_id) {
+                    Some(cached) if cached.stored_version == Some(version) => {
+                        span.record("si.dependent_value_index.cached", true);
+                        cached
+                    }
+                    _ => {
+                        let rows = client
+                            .query(
+                                &format!("{SELECT_INDEXES_WITH_STEPS} WHERE i.change_set_id = $1"),
+                                &[&change_set_id],
+                            )
+                            .await?;
+                        Self::from_rows(change_set_id, rows)?
+                            .unwrap_or_else(|| Self::new(change_set_id, address))
+                    }
+                }
+            }
+            None => {
+                // Start from the index of another change set at the same snapshot, if there is one
+                let rows = client
+                    .query(
+                        &format!(
+                            "{SELECT_INDEXES_WITH_STEPS} WHERE i.change_set_id = (
+                                SELECT change_set_id FROM dependent_value_indexes
+                                WHERE workspace_snapshot_address = $1
+                                ORDER BY updated_at DESC
+                                LIMIT 1
+                            )"
+                        ),
+                        &[&address],
+                    )
+                    .await?;
+                match Self::from_rows(change_set_id, rows)? {
+                    Some(mut index) => {
+                        index.stored_version = None;
+                        index.inserted = index.steps.keys().copied().collect();
+                        index
+                    }
+                    None => Self::new(change_set_id, address),
+                }
+            }
+        };
+
+        let [structure, values] = workspace_snapshot
+            .change_scopes_since_address(
+                ctx,
+                index.address,
+                [
+                    ChangeScopeKind::Structure,
+                    ChangeScopeKind::StructureAndValues,
+                ],
+            )
+            .await?;
+        index.invalidate(&DependentValueIndexScope { structure, values });
+        index.address = address;
+        span.record("si.dependent_value_index.steps", index.steps.len());
+
+        Ok(index)
+    }
+
+    fn from_rows(
+        change_set_id: ChangeSetId,
+        rows: Vec<PgRow>,
+    ) -> DependentValueIndexResult<Option<Self>> {
+        let mut index: Option<Self> = None;
+        for row in rows {
+            if index.is_none() {
+                let mut new_index =
+                    Self::new(change_set_id, row.try_get("workspace_snapshot_address")?);
+                new_index.stored_version = Some(row.try_get("version")?);
+                index = Some(new_index);
+            }
+            let step: Option<serde_json::Value> = row.try_get("step")?;
+            if let (Some(index), Some(step)) = (&mut index, step) {
+                let value_id: AttributeValueId = row.try_get("attribute_value_id")?;
+                index.insert_stored(value_id, serde_json::from_value(step)?);
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Stores what changed in the index since it was taken for the context, unless the stored
+    /// index moved on in the meantime. What the changes that have not been written yet can affect
+    /// is dropped first, since they may never be.
+    #[instrument(
+        name = "dependent_value_index.keep",
+        level = "debug",
+        skip_all,
+        fields(
+            si.dependent_value_index.hits = self.hits,
+            si.dependent_value_index.misses = self.misses,
+            si.dependent_value_index.stored = Empty,
+        )
+    )]
+    pub(crate) async fn keep(mut self, ctx: &DalContext) -> DependentValueIndexResult<()> {
+        let span = current_span_for_instrument_at!("debug");
+        if self.address == WorkspaceSnapshotAddress::nil() {
+            return Ok(());
+        }
+        self.invalidate(&DependentValueIndexScope::same_for_both(
+            ctx.workspace_snapshot()?.unwritten_change_scope().await,
+        ));
+
+        let mut client = ctx.pg_pool().get().await?;
+        let txn = client.transaction().await?;
+        let stored = txn
+            .query_opt(
+                "SELECT workspace_snapshot_address, version FROM dependent_value_indexes
+                WHERE change_set_id = $1
+                FOR UPDATE",
+                &[&self.change_set_id],
+            )
+            .await?;
+
+        let stored_version = match stored {
+            None => {
+                let row = txn
+                    .query_opt(
+                        "INSERT INTO dependent_value_indexes
+                            (change_set_id, workspace_snapshot_address)
+                        VALUES ($1, $2)
+                        ON CONFLICT (change_set_id) DO NOTHING
+                        RETURNING version",
+                        &[&self.change_set_id, &self.address],
+                    )
+                    .await?;
+                match row {
+                    Some(row) => {
+                        let all: Vec<AttributeValueId> = self.steps.keys().copied().collect();
+                        self.store_steps(&txn, &all).await?;
+                        Some(row.try_get("version")?)
+                    }
+                    // Another index was stored for the change set in the meantime
+                    None => None,
+                }
+            }
+            Some(row) => {
+                let address: WorkspaceSnapshotAddress =
+                    row.try_get("workspace_snapshot_address")?;
+                let version: i64 = row.try_get("version")?;
+
+                if self.stored_version == Some(version) {
+                    if self.inserted.is_empty()
+                        && self.removed.is_empty()
+                        && address == self.address
+                    {
+                        Some(version)
+                    } else {
+                        let removed: Vec<String> =
+                            self.removed.iter().map(ToString::to_string).collect();
+                        txn.execute(
+                            "DELETE FROM dependent_value_index_steps
+                            WHERE change_set_id = $1 AND attribute_value_id = ANY($2::text[])",
+                            &[&self.change_set_id, &removed],
+                        )
+                        .await?;
+                        let inserted: Vec<AttributeValueId> =
+                            self.inserted.iter().copied().collect();
+                        self.store_steps(&txn, &inserted).await?;
+                        Some(Self::moved_on(&txn, self.change_set_id, self.address).await?)
+                    }
+                } else if address == self.address {
+                    // Someone else changed the index, but it holds for the same snapshot, so the
+                    // steps walked here hold for it too. It is not the index in memory anymore.
+                    let inserted: Vec<AttributeValueId> = self.inserted.iter().copied().collect();
+                    self.store_steps(&txn, &inserted).await?;
+                    Self::moved_on(&txn, self.change_set_id, self.address).await?;
+                    None
+                } else {
+                    None
+                }
+            }
+        };
+        txn.commit().await?;
+
+        span.record("si.dependent_value_index.stored", stored_version.is_some());
+        if let Some(stored_version) = stored_version {
+            self.stored_version = Some(stored_version);
+            self.inserted.clear();
+            self.removed.clear();
+            self.hits = 0;
+            self.misses = 0;
+            CACHED_INDEXES.keep(self.change_set_id, self);
+        }
+
+        Ok(())
+    }
+
+    /// Moves the stored index of the change set from the snapshot at `from` on to the one at
+    /// `to`, dropping the steps that the changes in between can affect. Nothing happens if the
+    /// stored index is not at `from`; it gets brought up to date when it is used next.
+    #[instrument(
+        name = "dependent_value_index.advance",
+        level = "debug",
+        skip(ctx, scope),
+        fields(si.dependent_value_index.removed = Empty)
+    )]
+    pub async fn advance(
+        ctx: &DalContext,
+        change_set_id: ChangeSetId,
+        from: WorkspaceSnapshotAddress,
+        to: WorkspaceSnapshotAddress,
+        scope: &DependentValueIndexScope,
+    ) -> DependentValueIndexResult<()> {
+        let span = current_span_for_instrument_at!("debug");
+
+        let mut client = ctx.pg_pool().get().await?;
+        let txn = client.transaction().await?;
+        let Some(row) = txn
+            .query_opt(
+                "SELECT workspace_snapshot_address, version FROM dependent_value_indexes
+                WHERE change_set_id = $1
+                FOR UPDATE",
+                &[&change_set_id],
+            )
+            .await?
+        else {
+            return Ok(());
+        };
+        let address: WorkspaceSnapshotAddress = row.try_get("workspace_snapshot_address")?;
+        let version: i64 = row.try_get("version")?;
+        if address != from {
+            return Ok(());
+        }
+
+        let removed = match (&scope.structure, &scope.values) {
+            (ChangeScope::Components(structure), ChangeScope::Components(values)) => {
+                if structure.is_empty() && values.is_empty() {
+                    0
+                } else {
+                    let structure: Vec<String> =
+                        structure.iter().map(ToString::to_string).collect();
+                    let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+                    txn.execute(
+                        "DELETE FROM dependent_value_index_steps
+                        WHERE change_set_id = $1
+                            AND (component_ids ?| $2 OR value_component_ids ?| $3)",
+                        &[&change_set_id, &structure, &values],
+                    )
+                    .await?
+                }
+            }
+            _ => {
+                txn.execute(
+                    "DELETE FROM dependent_value_index_steps WHERE change_set_id = $1",
+                    &[&change_set_id],
+                )
+                .await?
+            }
+        };
+        let new_version = Self::moved_on(&txn, change_set_id, to).await?;
+        txn.commit().await?;
+        span.record("si.dependent_value_index.removed", removed);
+
+        // Do the same to the index in memory, so that it does not have to be read back
+        if let Some(mut cached) = CACHED_INDEXES.take(change_set_id) {
+            if cached.stored_version == Some(version) && cached.address == from {
+                cached.invalidate(scope);
+                cached.address = to;
+                cached.stored_version = Some(new_version);
+                cached.removed.clear();
+                CACHED_INDEXES.keep(change_set_id, cached);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records why each of the values in the graph is part of it, for when they are about to be
+    /// recomputed, replacing what was recorded for them before.
+    pub async fn record_cause_chains(
+        ctx: &DalContext,
+        graph: &DependentValueGraph,
+    ) -> DependentValueIndexResult<()> {
+        let cause_chains: Vec<serde_json::Value> = graph
+            .all_value_ids()
+            .into_iter()
+            .filter_map(|value_id| {
+                graph.cause_chain(value_id).map(|cause_chain| {
+                    json!({ "attribute_value_id": value_id, "cause_chain": cause_chain })
+                })
+            })
+            .collect();
+        if cause_chains.is_empty() {
+            return Ok(());
+        }
+
+        ctx.txns()
+            .await?
+            .pg()
+            .query_none(
+                "INSERT INTO dependent_value_causes (change_set_id, attribute_value_id, cause_chain)
+                SELECT $1::ident, c.attribute_value_id, c.cause_chain
+                FROM jsonb_to_recordset($2) AS c(attribute_value_id text, cause_chain jsonb)
+                ON CONFLICT (change_set_id, attribute_value_id)
+                DO UPDATE SET cause_chain = excluded.cause_chain, created_at = CLOCK_TIMESTAMP()",
+                &[&ctx.change_set_id(), &serde_json::Value::Array(cause_chains)],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Why the value was recomputed the last time a dependent values update in the change set
+    /// of the context recomputed it: the values it was found through, starting with one of the
+    /// values the update was for and ending with the value itself. `None` if no update
+    /// recomputed it yet.
+    pub async fn cause_chain(
+        ctx: &DalContext,
+        value_id: AttributeValueId,
+    ) -> DependentValueIndexResult<Option<Vec<AttributeValueId>>> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(
+                "SELECT cause_chain FROM dependent_value_causes
+                WHERE change_set_id = $1 AND attribute_value_id = $2",
+                &[&ctx.change_set_id(), &value_id],
+            )
+            .await?;
+
+        Ok(match row {
+            Some(row) => {
+                let cause_chain: serde_json::Value = row.try_get("cause_chain")?;
+                Some(serde_json::from_value(cause_chain)?)
+            }
+            None => None,
+        })
+    }
+
+    /// The snapshot that the stored index of the change set of the context holds for, if one
+    /// is stored.
+    pub async fn stored_address(
+        ctx: &DalContext,
+    ) -> DependentValueIndexResult<Option<WorkspaceSnapshotAddress>> {
+        let row = ctx
+            .pg_pool()
+            .get()
+            .await?
+            .query_opt(
+                "SELECT workspace_snapshot_address FROM dependent_value_indexes
+                WHERE change_set_id = $1",
+                &[&ctx.change_set_id()],
+            )
+            .await?;
+
+        Ok(match row {
+            Some(row) => Some(row.try_get("workspace_snapshot_address")?),
+            None => None,
+        })
+    }
+
+    pub(crate) fn step(&mut self, value_id: AttributeValueId) -> Option<&ValueStep> {
+        let step = self.steps.get(&value_id);
+        match step {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        step
+    }
+
+    pub(crate) fn insert(&mut self, value_id: AttributeValueId, step: ValueStep) {
+        self.inserted.insert(value_id);
+        self.removed.remove(&value_id);
+        self.insert_stored(value_id, step);
+    }
+
+    fn insert_stored(&mut self, value_id: AttributeValueId, step: ValueStep) {
+        for component_id in step.component_ids() {
+            self.value_ids_by_component
+                .entry(component_id)
+                .or_default()
+                .insert(value_id);
+        }
+        for component_id in step.tree_component_ids() {
+            self.value_ids_by_tree_component
+                .entry(component_id)
+                .or_default()
+                .insert(value_id);
+        }
+        self.steps.insert(value_id, step);
+    }
+
+    async fn store_steps(
+        &self,
+        txn: &InstrumentedTransaction<'_>,
+        value_ids: &[AttributeValueId],
+    ) -> DependentValueIndexResult<()> {
+        let mut steps = Vec::with_capacity(value_ids.len());
+        for value_id in value_ids {
+            let Some(step) = self.steps.get(value_id) else {
+                continue;
+            };
+            steps.push(json!({
+                "attribute_value_id": value_id,
+                "component_ids": step.component_ids().collect::<HashSet<_>>(),
+                "value_component_ids": step.tree_component_ids().collect::<HashSet<_>>(),
+                "step": step,
+            }));
+        }
+        if steps.is_empty() {
+            return Ok(());
+        }
+
+        txn.execute(
+            "INSERT INTO dependent_value_index_steps
+                (change_set_id, attribute_value_id, component_ids, value_component_ids, step)
+            SELECT $1::ident, s.attribute_value_id, s.component_ids, s.value_component_ids, s.step
+            FROM jsonb_to_recordset($2) AS s(
+                attribute_value_id text,
+                component_ids jsonb,
+                value_component_ids jsonb,
+                step jsonb
+            )
+            ON CONFLICT (change_set_id, attribute_value_id)
+            DO UPDATE SET component_ids = excluded.component_ids,
+                value_component_ids = excluded.value_component_ids,
+                step = excluded.step",
+            &[&self.change_set_id, &serde_json::Value::Array(steps)],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Points the stored index at the snapshot, returning its new version.
+    async fn moved_on(
+        txn: &InstrumentedTransaction<'_>,
+        change_set_id: ChangeSetId,
+        address: WorkspaceSnapshotAddress,
+    ) -> DependentValueIndexResult<i64> {
+        let row = txn
+            .query_one(
+                "UPDATE dependent_value_indexes
+                SET workspace_snapshot_address = $2,
+                    version = version + 1,
+                    updated_at = CLOCK_TIMESTAMP()
+                WHERE change_set_id = $1
+                RETURNING version",
+                &[&change_set_id, &address],
+            )
+            .await?;
+
+        Ok(row.try_get("version")?)
+    }
+
+    fn invalidate(&mut self, scope: &DependentValueIndexScope) {
+        if scope.is_everything() {
+            self.removed.extend(self.steps.keys().copied());
+            self.inserted.clear();
+            self.steps.clear();
+            self.value_ids_by_component.clear();
+            self.value_ids_by_tree_component.clear();
+            return;
+        }
+
+        let mut value_ids = HashSet::new();
+        if let ChangeScope::Components(component_ids) = &scope.structure {
+            for component_id in component_ids {
+                if let Some(ids) = self.value_ids_by_component.get(component_id) {
+                    value_ids.extend(ids.iter().copied());
+                }
+            }
+        }
+        if let ChangeScope::Components(component_ids) = &scope.values {
+            for component_id in component_ids {
+                if let Some(ids) = self.value_ids_by_tree_component.get(component_id) {
+                    value_ids.extend(ids.iter().copied());
+                }
+            }
+        }
+
+        for value_id in value_ids {
+            self.remove(value_id);
+        }
+    }
+
+    fn remove(&mut self, value_id: AttributeValueId) {
+        let Some(step) = self.steps.remove(&value_id) else {
+            return;
+        };
+        for component_id in step.component_ids() {
+            if let Some(ids) = self.value_ids_by_component.get_mut(&component_id) {
+                ids.remove(&value_id);
+            }
+        }
+        for component_id in step.tree_component_ids() {
+            if let Some(ids) = self.value_ids_by_tree_component.get_mut(&component_id) {
+                ids.remove(&value_id);
+            }
+        }
+        self.inserted.remove(&value_id);
+        self.removed.insert(value_id);
+    }
+}
@@ -0,0 +1,481 @@
+//! Where an [`AttributeValue`] came from: the func that set it, where each of the func's
+//! arguments came from (props, sockets, secrets or static values), and the same for each of the
+//! values those arguments came from, up to a given depth.
+//!
+//! Each value is traced once per depth, so values that several others come from (through
+//! diamond-shaped dependencies) do not make the work grow with the number of paths to them.
+
+use std::collections::{HashMap, HashSet};
+
+use async_recursion::async_recursion;
+use serde::{Deserialize, Serialize};
+use si_events::FuncRunId;
+use si_layer_cache::LayerDbError;
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::attribute::prototype::argument::static_value::StaticArgumentValue;
+use crate::attribute::prototype::argument::value_source::{ValueSource, ValueSourceError};
+use crate::attribute::prototype::argument::{
+    AttributePrototypeArgument, AttributePrototypeArgumentError,
+};
+use crate::attribute::prototype::AttributePrototypeError;
+use crate::component::socket::ComponentInputSocket;
+use crate::component::ControllingFuncData;
+use crate::func::argument::{FuncArgument, FuncArgumentError};
+use crate::workspace_snapshot::WorkspaceSnapshotError;
+use crate::{
+    AttributePrototype, AttributePrototypeId, Component, ComponentError, ComponentId, DalContext,
+    Func, FuncError, FuncId, InputSocketId, OutputSocketId, PropId, SecretId,
+};
+
+use super::{AttributeValue, AttributeValueError, AttributeValueId, ValueIsFor};
+
+/// How many of the most recent runs for a value to look through for the one that produced it.
+const FUNC_RUN_LOOKBACK: i64 = 20;
+
+/// The deepest a trace goes, whatever depth is asked for.
+pub const MAX_PROVENANCE_DEPTH: usize = 20;
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum AttributeValueProvenanceError {
+    #[error("attribute prototype error: {0}")]
+    AttributePrototype(#[from] AttributePrototypeError),
+    #[error("attribute prototype argument error: {0}")]
+    AttributePrototypeArgument(#[from] AttributePrototypeArgumentError),
+    #[error("attribute value error: {0}")]
+    AttributeValue(#[from] AttributeValueError),
+    #[error("attribute value not found: {0}")]
+    AttributeValueNotFound(AttributeValueId),
+    #[error("component error: {0}")]
+    Component(#[from] ComponentError),
+    #[error("func error: {0}")]
+    Func(#[from] FuncError),
+    #[error("func argument error: {0}")]
+    FuncArgument(#[from] FuncArgumentError),
+    #[error("layer db error: {0}")]
+    LayerDb(#[from] LayerDbError),
+    #[error("value source error: {0}")]
+    ValueSource(#[from] ValueSourceError),
+    #[error("workspace snapshot error: {0}")]
+    WorkspaceSnapshot(#[from] WorkspaceSnapshotError),
+}
+
+pub type AttributeValueProvenanceResult<T> = Result<T, AttributeValueProvenanceError>;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueProvenance {
+    pub attribute_value_id: AttributeValueId,
+    pub component_id: ComponentId,
+    pub path: Option<String>,
+    pub value: Option<serde_json::Value>,
+    pub origin: ValueOrigin,
+    pub prototype_id: AttributePrototypeId,
+    pub func_id: FuncId,
+    pub func_name: String,
+    /// The run of the func that produced the current value. Runs of intrinsic funcs are not
+    /// recorded, so there is none for values set by those.
+    pub func_run_id: Option<FuncRunId>,
+    pub arguments: Vec<ArgumentProvenance>,
+}
+
+/// Why a value has the prototype it has.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ValueOrigin {
+    /// Set on the component itself, overriding the prototype of the schema variant
+    Overridden,
+    /// Set by the prototype of the schema variant
+    Defaulted,
+    /// Set as part of the object, array or map that the func of an ancestor returned, so its own
+    /// prototype does not matter
+    ControlledBy { value: UpstreamValue },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgumentProvenance {
+    pub name: String,
+    /// Whether the argument is passed to the func. Deleted components only feed other deleted
+    /// components.
+    pub is_used: bool,
+    pub source: ArgumentSource,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ArgumentSource {
+    InputSocket {
+        input_socket_id: InputSocketId,
+        values: Vec<UpstreamValue>,
+    },
+    /// An output socket of another component, connected explicitly or inferred from frames
+    OutputSocket {
+        output_socket_id: OutputSocketId,
+        component_id: ComponentId,
+        inferred: bool,
+        values: Vec<UpstreamValue>,
+    },
+    Prop {
+        prop_id: PropId,
+        values: Vec<UpstreamValue>,
+    },
+    Secret {
+        secret_id: SecretId,
+    },
+    StaticValue {
+        value: serde_json::Value,
+    },
+}
+
+/// A value that another value came from.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamValue {
+    pub attribute_value_id: AttributeValueId,
+    /// Where the value came from in turn, unless the trace did not go that far
+    pub provenance: Option<Box<AttributeValueProvenance>>,
+}
+
+/// What one call to [`AttributeValueProvenance::trace`] has worked out so far.
+#[derive(Debug, Default)]
+struct Trace {
+    /// The values being traced further down, so that a value that (wrongly) comes from itself
+    /// is not followed forever
+    tracing: HashSet<AttributeValueId>,
+    /// The values traced already, by how many levels further they were traced
+    traced: HashMap<(AttributeValueId, usize), AttributeValueProvenance>,
+    /// [`Component::list_av_controlling_func_ids_for_id`] for the components traced through
+    controlling_func_data: HashMap<ComponentId, HashMap<AttributeValueId, ControllingFuncData>>,
+}
+
+impl Trace {
+    async fn controlling_value_id(
+        &mut self,
+        ctx: &DalContext,
+        component_id: ComponentId,
+        attribute_value_id: AttributeValueId,
+    ) -> AttributeValueProvenanceResult<Option<AttributeValueId>> {
+        if !self.controlling_func_data.contains_key(&component_id) {
+            let controlling_func_data =
+                Component::list_av_controlling_func_ids_for_id(ctx, component_id).await?;
+            self.controlling_func_data
+                .insert(component_id, controlling_func_data);
+        }
+
+        Ok(self
+            .controlling_func_data
+            .get(&component_id)
+            .and_then(|controlling_func_data| controlling_func_data.get(&attribute_value_id))
+            .map(|controlling_func_data| controlling_func_data.av_id)
+            .filter(|&av_id| av_id != attribute_value_id))
+    }
+}
+
+impl AttributeValueProvenance {
+    /// Traces where the value came from, following the values it came from up to `depth`
+    /// levels, or [`MAX_PROVENANCE_DEPTH`] if that is less.
+    #[instrument(name = "attribute_value.provenance.trace", level = "debug", skip(ctx))]
+    pub async fn trace(
+        ctx: &DalContext,
+        attribute_value_id: AttributeValueId,
+        depth: usize,
+    ) -> AttributeValueProvenanceResult<Self> {
+        if ctx
+            .workspace_snapshot()?
+            .get_node_index_by_id_opt(attribute_value_id)
+            .await
+            .is_none()
+        {
+            return Err(AttributeValueProvenanceError::AttributeValueNotFound(
+                attribute_value_id,
+            ));
+        }
+
+        Self::trace_inner(
+            ctx,
+            attribute_value_id,
+            depth.min(MAX_PROVENANCE_DEPTH),
+            &mut Trace::default(),
+        )
+        .await
+    }
+
+    #[async_recursion]
+    async fn trace_inner(
+        ctx: &DalContext,
+        attribute_value_id: AttributeValueId,
+        depth: usize,
+        trace: &mut Trace,
+    ) -> AttributeValueProvenanceResult<Self> {
+        if let Some(provenance) = trace.traced.get(&(attribute_value_id, depth)) {
+            return Ok(provenance.clone());
+        }
+
+        trace.tracing.insert(attribute_value_id);
+
+        let attribute_value = AttributeValue::get_by_id(ctx, attribute_value_id).await?;
+        let component_id = AttributeValue::component_id(ctx, attribute_value_id).await?;
+        let prototype_id = AttributeValue::prototype_id(ctx, attribute_value_id).await?;
+        let func_id = AttributePrototype::func_id(ctx, prototype_id).await?;
+
+        let controlling_value_id = trace
+            .controlling_value_id(ctx, component_id, attribute_value_id)
+            .await?;
+        let origin = match controlling_value_id {
+            Some(controlling_value_id) => ValueOrigin::ControlledBy {
+                value: Self::upstream(ctx, controlling_value_id, depth, trace).await?,
+            },
+            None => {
+                if AttributeValue::component_prototype_id(ctx, attribute_value_id)
+                    .await?
+                    .is_some()
+                {
+                    ValueOrigin::Overridden
+                } else {
+                    ValueOrigin::Defaulted
+                }
+            }
+        };
+
+        let provenance = Self {
+            attribute_value_id,
+            component_id,
+            path: AttributeValue::get_path_for_id(ctx, attribute_value_id).await?,
+            value: attribute_value.view(ctx).await?,
+            origin,
+            prototype_id,
+            func_id,
+            func_name: Func::get_by_id_or_error(ctx, func_id).await?.name,
+            func_run_id: Self::func_run_id(ctx, &attribute_value).await?,
+            arguments: Self::arguments(
+                ctx,
+                attribute_value_id,
+                component_id,
+                prototype_id,
+                depth,
+                trace,
+            )
+            .await?,
+        };
+
+        trace.tracing.remove(&attribute_value_id);
+        trace
+            .traced
+            .insert((attribute_value_id, depth), provenance.clone());
+
+        Ok(provenance)
+    }
+
+    async fn arguments(
+        ctx: &DalContext,
+        attribute_value_id: AttributeValueId,
+        component_id: ComponentId,
+        prototype_id: AttributePrototypeId,
+        depth: usize,
+        trace: &mut Trace,
+    ) -> AttributeValueProvenanceResult<Vec<ArgumentProvenance>> {
+        let mut arguments = vec![];
+
+        for apa_id in AttributePrototypeArgument::list_ids_for_prototype(ctx, prototype_id).await? {
+            let apa = AttributePrototypeArgument::get_by_id(ctx, apa_id).await?;
+            // The arguments for connections live on the prototypes of schema variants, so only
+            // the ones for connections into this component count
+            if apa
+                .targets()
+                .is_some_and(|targets| targets.destination_component_id != component_id)
+            {
+                continue;
+            }
+            let source_component_id = apa
+                .targets()
+                .map(|targets| targets.source_component_id)
+                .unwrap_or(component_id);
+
+            let is_used = Component::should_data_flow_between_components(
+                ctx,
+                component_id,
+                source_component_id,
+            )
+            .await?;
+            let func_argument_id =
+                AttributePrototypeArgument::func_argument_id_by_id(ctx, apa_id).await?;
+            let name = FuncArgument::get_name_by_id(ctx, func_argument_id).await?;
+
+            let value_source = AttributePrototypeArgument::value_source_by_id(ctx, apa_id)
+                .await?
+                .ok_or(AttributeValueError::AttributePrototypeArgumentMissingValueSource(apa_id))?;
+            let source = match value_source {
+                ValueSource::InputSocket(input_socket_id) => ArgumentSource::InputSocket {
+                    input_socket_id,
+                    values: Self::upstream_values(
+                        ctx,
+                        value_source,
+                        source_component_id,
+                        depth,
+                        trace,
+                    )
+                    .await?,
+                },
+                ValueSource::OutputSocket(output_socket_id) => ArgumentSource::OutputSocket {
+                    output_socket_id,
+                    component_id: source_component_id,
+                    inferred: false,
+                    values: Self::upstream_values(
+                        ctx,
+                        value_source,
+                        source_component_id,
+                        depth,
+                        trace,
+                    )
+                    .await?,
+                },
+                ValueSource::Prop(prop_id) => ArgumentSource::Prop {
+                    prop_id,
+                    values: Self::upstream_values(
+                        ctx,
+                        value_source,
+                        source_component_id,
+                        depth,
+                        trace,
+                    )
+                    .await?,
+                },
+                ValueSource::Secret(secret_id) => ArgumentSource::Secret { secret_id },
+                ValueSource::StaticArgumentValue(static_argument_value_id) => {
+                    ArgumentSource::StaticValue {
+                        value: StaticArgumentValue::get_by_id(ctx, static_argument_value_id)
+                            .await?
+                            .value,
+                    }
+                }
+            };
+
+            arguments.push(ArgumentProvenance {
+                name,
+                is_used,
+                source,
+            });
+        }
+
+        // Input sockets also get the values of the output sockets they are inferred to be
+        // connected to through frames, as the only argument of their func
+        if let ValueIsFor::InputSocket(input_socket_id) =
+            AttributeValue::is_for(ctx, attribute_value_id).await?
+        {
+            if let Some(component_input_socket) =
+                ComponentInputSocket::get_by_ids(ctx, component_id, input_socket_id).await?
+            {
+                let func_id = AttributePrototype::func_id(ctx, prototype_id).await?;
+                if let Some(func_argument) = FuncArgument::list_for_func(ctx, func_id).await?.pop()
+                {
+                    for component_output_socket in
+                        ComponentInputSocket::find_inferred_connections(ctx, component_input_socket)
+                            .await?
+                    {
+                        let is_used = Component::should_data_flow_between_components(
+                            ctx,
+                            component_id,
+                            component_output_socket.component_id,
+                        )
+                        .await?;
+
+                        arguments.push(ArgumentProvenance {
+                            name: func_argument.name.clone(),
+                            is_used,
+                            source: ArgumentSource::OutputSocket {
+                                output_socket_id: component_output_socket.output_socket_id,
+                                component_id: component_output_socket.component_id,
+                                inferred: true,
+                                values: vec![
+                                    Self::upstream(
+                                        ctx,
+                                        component_output_socket.attribute_value_id,
+                                        depth,
+                                        trace,
+                                    )
+                                    .await?,
+                                ],
+                            },
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    async fn upstream_values(
+        ctx: &DalContext,
+        value_source: ValueSource,
+        component_id: ComponentId,
+        depth: usize,
+        trace: &mut Trace,
+    ) -> AttributeValueProvenanceResult<Vec<UpstreamValue>> {
+        let mut values = vec![];
+        for attribute_value_id in value_source
+            .attribute_values_for_component_id(ctx, component_id)
+            .await?
+        {
+            values.push(Self::upstream(ctx, attribute_value_id, depth, trace).await?);
+        }
+
+        Ok(values)
+    }
+
+    /// A value one level further up, traced unless the trace is deep enough already.
+    async fn upstream(
+        ctx: &DalContext,
+        attribute_value_id: AttributeValueId,
+        depth: usize,
+        trace: &mut Trace,
+    ) -> AttributeValueProvenanceResult<UpstreamValue> {
+        let provenance = if depth == 0 || trace.tracing.contains(&attribute_value_id) {
+            None
+        } else {
+            Some(Box::new(
+                Self::trace_inner(ctx, attribute_value_id, depth - 1, trace).await?,
+            ))
+        };
+
+        Ok(UpstreamValue {
+            attribute_value_id,
+            provenance,
+        })
+    }
+
+    /// The most recent run for the value that returned its current value.
+    async fn func_run_id(
+        ctx: &DalContext,
+        attribute_value: &AttributeValue,
+    ) -> AttributeValueProvenanceResult<Option<FuncRunId>> {
+        let Some(value_address) = attribute_value.value else {
+            return Ok(None);
+        };
+        let value_hash = value_address.content_hash();
+
+        Ok(ctx
+            .layer_db()
+            .func_run()
+            .list_recent_for_attribute_value_id(
+                ctx.events_tenancy().workspace_pk,
+                attribute_value.id.into(),
+                FUNC_RUN_LOOKBACK,
+            )
+            .await?
+            .into_iter()
+            .find(|func_run| func_run.result_value_cas_address() == Some(value_hash))
+            .map(|func_run| func_run.id()))
+    }
+}
@@ -16,7 +16,7 @@ use crate::{
 };
 use module_index_client::{ModuleDetailsResponse, ModuleIndexClient, ModuleIndexClientError};
 use si_data_pg::{PgError, PgRow};
-use si_pkg::{SiPkg, SiPkgError};
+use si_pkg::{SiPkg, SiPkgError, SiPkgSignature};
 
 pk!(CachedModuleId);
 
@@ -62,6 +62,7 @@ pub struct CachedModule {
     pub latest_hash: String,
     pub created_at: DateTime<Utc>,
     pub package_data: Option<Vec<u8>>,
+    pub signature: Option<SiPkgSignature>,
 }
 
 impl From<CachedModule> for si_frontend_types::UninstalledVariant {
@@ -85,6 +86,8 @@ impl TryFrom<PgRow> for CachedModule {
     fn try_from(row: PgRow) -> Result<Self, Self::Error> {
         let component_type_string: String = row.try_get("component_type")?;
         let component_type = ComponentType::from_str(&component_type_string)?;
+        let signature: Option<String> = row.try_get("signature")?;
+        let signature_public_key: Option<String> = row.try_get("signature_public_key")?;
 
         Ok(Self {
             id: row.try_get("id")?,
@@ -99,6 +102,12 @@ impl TryFrom<PgRow> for CachedModule {
             latest_hash: row.try_get("latest_hash")?,
             created_at: row.try_get("created_at")?,
             package_data: row.try_get("package_data")?,
+            signature: signature
+                .zip(signature_public_key)
+                .map(|(signature, public_key)| SiPkgSignature {
+                    public_key,
+                    signature,
+                }),
         })
     }
 }
@@ -107,7 +116,8 @@ impl CachedModule {
     pub async fn si_pkg(&mut self, ctx: &DalContext) -> CachedModuleResult<SiPkg> {
         let package_data = self.package_data(ctx).await?;
         // slow_rt, and cache this
-        Ok(SiPkg::load_from_bytes(package_data)?)
+        let pkg = SiPkg::load_from_bytes(package_data)?;
+        Ok(pkg.with_signature(self.signature.clone()))
     }
 
     async fn package_data(&mut self, ctx: &DalContext) -> CachedModuleResult<&[u8]> {
@@ -238,7 +248,9 @@ impl CachedModule {
                 component_type,
                 latest_hash,
                 created_at,
-                package_data
+                package_data,
+                signature,
+                signature_public_key
             FROM cached_modules
             WHERE schema_id = $1
             ORDER BY schema_id, created_at DESC
@@ -271,7 +283,9 @@ impl CachedModule {
                 component_type,
                 latest_hash,
                 created_at,
-                NULL::bytea AS package_data
+                NULL::bytea AS package_data,
+                signature,
+                signature_public_key
             FROM cached_modules
             ORDER BY schema_id, created_at DESC
         ";
@@ -307,10 +321,12 @@ impl CachedModule {
                 component_type,
                 latest_hash,
                 created_at,
-                package_data
+                package_data,
+                signature,
+                signature_public_key
             ) VALUES (
                 $1, $2, $3, $4, $5, $6,
-                $7, $8, $9, $10, $11
+                $7, $8, $9, $10, $11, $12, $13
             ) RETURNING
                 id,
                 schema_id,
@@ -323,7 +339,9 @@ impl CachedModule {
                 component_type,
                 latest_hash,
                 created_at,
-                NULL::bytea AS package_data
+                NULL::bytea AS package_data,
+                signature,
+                signature_public_key
         ";
 
         let Some(schema_id) = module_details.schema_id() else {
@@ -385,6 +403,8 @@ impl CachedModule {
                     &module_details.latest_hash,
                     &module_details.created_at,
                     &bytes_ref,
+                    &module_details.signature,
+                    &module_details.signature_public_key,
                 ],
             )
             .await?;
@@ -6,13 +6,14 @@ use std::time::Duration;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use si_data_pg::{PgError, PgRow};
-use si_events::{ulid::Ulid, WorkspaceSnapshotAddress};
+use si_events::{audit_log::AuditLogKind, ulid::Ulid, WorkspaceSnapshotAddress};
 use si_layer_cache::LayerDbError;
 use telemetry::prelude::*;
 use thiserror::Error;
 use tokio::time;
 
 use crate::billing_publish::BillingPublishError;
+use crate::change_set::approval::{ApprovalRole, ChangeSetApproval};
 use crate::slow_rt::SlowRuntimeError;
 use crate::workspace_snapshot::graph::RebaseBatch;
 use crate::{
@@ -26,6 +27,7 @@ use crate::{
     WorkspaceError,
 };
 
+pub mod approval;
 pub mod event;
 pub mod status;
 pub mod view;
@@ -35,8 +37,12 @@ const FIND_ANCESTORS_QUERY: &str = include_str!("queries/change_set/find_ancesto
 #[remain::sorted]
 #[derive(Debug, Error)]
 pub enum ChangeSetError {
+    #[error("approval requirements not met for change set: {0}")]
+    ApprovalRequirementsNotMet(ChangeSetId),
     #[error("billing publish error: {0}")]
     BillingPublish(#[from] Box<BillingPublishError>),
+    #[error("change set {0} was changed after it was approved")]
+    ChangedSinceApproval(ChangeSetId),
     #[error("change set not approved for apply. Current state: {0}")]
     ChangeSetNotApprovedForApply(ChangeSetStatus),
     #[error("change set with id {0} not found")]
@@ -422,30 +428,77 @@ impl ChangeSet {
             .await?;
 
         self.status = status;
+        ChangeSetApproval::clear_for_change_set(ctx, self.id).await?;
 
         Ok(())
     }
 
     /// First, transitions the status of the [`ChangeSet`] to [`ChangeSetStatus::NeedsApproval`]
-    /// then [`ChangeSetStatus::Approved`]. Next, checks if DVU Roots still exist. Finally,
+    /// then [`ChangeSetStatus::Approved`], counting the user in the [`DalContext`] as an
+    /// [`ApprovalRole::Approver`]. Next, checks if DVU Roots still exist. Finally,
     /// lock every [`SchemaVariant`] and [`Func`] that is currently unlocked
-    pub async fn prepare_for_force_apply(ctx: &DalContext) -> ChangeSetResult<()> {
+    ///
+    /// The [`required approvals`](Self::required_approvals) still have to be satisfied unless
+    /// `override_approval_policies` is set, in which case the override is written to the audit
+    /// log. Callers must only allow the override for users who can manage the workspace.
+    pub async fn prepare_for_force_apply(
+        ctx: &DalContext,
+        override_approval_policies: bool,
+    ) -> ChangeSetResult<()> {
         // first change the status to approved and who did it
         let mut change_set = ChangeSet::find(ctx, ctx.change_set_id())
             .await?
             .ok_or(TransactionsError::ChangeSetNotFound(ctx.change_set_id()))?;
 
         change_set.request_change_set_approval(ctx).await?;
+        let user_pk = Self::extract_userid_from_context_or_error(ctx).await?;
+        ChangeSetApproval::upsert(
+            ctx,
+            change_set.id,
+            user_pk,
+            &[ApprovalRole::Approver],
+            change_set.workspace_snapshot_address,
+        )
+        .await?;
+
+        let unsatisfied_policies: Vec<String> = change_set
+            .required_approvals(ctx)
+            .await?
+            .into_iter()
+            .filter(|required| !required.is_satisfied)
+            .map(|required| required.policy_name)
+            .collect();
+        if !unsatisfied_policies.is_empty() {
+            if !override_approval_policies {
+                return Err(ChangeSetError::ApprovalRequirementsNotMet(change_set.id));
+            }
+            ctx.write_audit_log(
+                AuditLogKind::OverrideApprovalPolicies {
+                    change_set_id: change_set.id.into(),
+                    policy_names: unsatisfied_policies,
+                },
+                change_set.name.to_owned(),
+            )
+            .await?;
+        }
+
         // then approve it
-        change_set.approve_change_set_for_apply(ctx).await?;
+        change_set.mark_approved(ctx, user_pk).await?;
         // then do the rest
-        Self::prepare_for_apply(ctx).await
+        Self::prepare_for_apply_inner(ctx, !override_approval_policies).await
     }
 
     /// First, checks if DVU Roots still exist. Next, ensures the [`ChangeSet`] has an
-    /// [`ChangeSetStatus::Approved`]. Finally,
-    /// lock every [`SchemaVariant`] and [`Func`] that is currently unlocked
+    /// [`ChangeSetStatus::Approved`] and, if any [`required approvals`](Self::required_approvals)
+    /// apply, that they are satisfied by approvals given since it last changed. Finally, lock every [`SchemaVariant`] and [`Func`] that is currently unlocked
     pub async fn prepare_for_apply(ctx: &DalContext) -> ChangeSetResult<()> {
+        Self::prepare_for_apply_inner(ctx, true).await
+    }
+
+    async fn prepare_for_apply_inner(
+        ctx: &DalContext,
+        enforce_approval_policies: bool,
+    ) -> ChangeSetResult<()> {
         let change_set = ChangeSet::find(ctx, ctx.change_set_id())
             .await?
             .ok_or(TransactionsError::ChangeSetNotFound(ctx.change_set_id()))?;
@@ -475,6 +528,24 @@ impl ChangeSet {
             ));
         }
 
+        // Without a policy that applies, the approval status is all that counts, as it was before
+        // policies existed. With one, an approval only covers the change set as it was when it
+        // was given.
+        let required_approvals = change_set.required_approvals(ctx).await?;
+        if !required_approvals.is_empty() {
+            if !change_set.has_current_approval(ctx).await? {
+                return Err(ChangeSetError::ChangedSinceApproval(change_set.id));
+            }
+
+            if enforce_approval_policies
+                && !required_approvals
+                    .iter()
+                    .all(|required| required.is_satisfied)
+            {
+                return Err(ChangeSetError::ApprovalRequirementsNotMet(change_set.id));
+            }
+        }
+
         // Lock all unlocked variants
         for schema_id in Schema::list_ids(ctx).await.map_err(Box::new)? {
             let schema = Schema::get_by_id_or_error(ctx, schema_id)
@@ -507,8 +578,38 @@ impl ChangeSet {
         Ok(())
     }
 
+    /// Approves the [`ChangeSet`] as a workspace [`ApprovalRole::Approver`].
     pub async fn approve_change_set_for_apply(&mut self, ctx: &DalContext) -> ChangeSetResult<()> {
+        self.approve_change_set_for_apply_with_roles(ctx, &[ApprovalRole::Approver])
+            .await
+    }
+
+    /// Records the approval of the user in the [`DalContext`] in the given roles. The
+    /// [`ChangeSet`] only transitions to [`ChangeSetStatus::Approved`] once every
+    /// [`required approval`](Self::required_approvals) is satisfied.
+    pub async fn approve_change_set_for_apply_with_roles(
+        &mut self,
+        ctx: &DalContext,
+        roles: &[ApprovalRole],
+    ) -> ChangeSetResult<()> {
         let user_pk = Self::extract_userid_from_context_or_error(ctx).await?;
+        ChangeSetApproval::upsert(
+            ctx,
+            self.id,
+            user_pk,
+            roles,
+            self.workspace_snapshot_address,
+        )
+        .await?;
+
+        if self.approval_requirements_met(ctx).await? {
+            self.mark_approved(ctx, user_pk).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn mark_approved(&mut self, ctx: &DalContext, user_pk: UserPk) -> ChangeSetResult<()> {
         let status = ChangeSetStatus::Approved;
         ctx.txns()
             .await?
@@ -940,6 +1041,28 @@ impl ChangeSet {
         }
     }
 
+    /// The addresses of the snapshots that any change set, in any workspace, points at.
+    pub async fn workspace_snapshot_addresses_in_use(
+        ctx: &DalContext,
+    ) -> ChangeSetResult<HashSet<WorkspaceSnapshotAddress>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(
+                "SELECT DISTINCT workspace_snapshot_address FROM change_set_pointers",
+                &[],
+            )
+            .await?;
+
+        let mut addresses = HashSet::with_capacity(rows.len());
+        for row in rows {
+            addresses.insert(row.try_get("workspace_snapshot_address")?);
+        }
+
+        Ok(addresses)
+    }
+
     /// Walk the graph of change sets up to the change set that has no "base
     /// change set id" and return the set.
     pub async fn ancestors(
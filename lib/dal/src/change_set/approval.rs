@@ -0,0 +1,555 @@
+//! Declarative approval policies for applying a [`ChangeSet`].
+//!
+//! Each workspace may configure an ordered list of [`ApprovalPolicies`](ApprovalPolicy). When
+//! a [`ChangeSet`] is evaluated, every node touched by the updates that would be applied to its
+//! base is classified into an [`ApprovalSubject`]. Each subject is claimed by the first policy
+//! whose [`ApprovalPolicyMatcher`] matches it, and every claimed policy becomes a
+//! [`RequiredApproval`] that must be satisfied before the [`ChangeSet`] can be applied.
+//!
+//! Subjects that no policy claims need no approval, and a workspace without policies keeps the
+//! single approval gate of [`ChangeSet::approve_change_set_for_apply`].
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use si_data_pg::PgRow;
+use si_events::{ulid::Ulid, WorkspaceSnapshotAddress};
+use strum::{AsRefStr, Display, EnumString};
+
+use crate::change_set::{ChangeSet, ChangeSetError, ChangeSetId, ChangeSetResult};
+use crate::workspace_snapshot::content_address::ContentAddressDiscriminants;
+use crate::workspace_snapshot::graph::detect_updates::Update;
+use crate::workspace_snapshot::node_weight::NodeWeight;
+use crate::workspace_snapshot::{SchemaVariantExt, WorkspaceSnapshotResult};
+use crate::{
+    AttributeValueId, ComponentId, DalContext, EdgeWeightKindDiscriminants,
+    NodeWeightDiscriminants, SchemaId, UserPk, WorkspacePk, WorkspaceSnapshot,
+};
+
+/// The capacity in which a user approved a [`ChangeSet`].
+#[remain::sorted]
+#[derive(
+    AsRefStr,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Display,
+    EnumString,
+    Eq,
+    Hash,
+    PartialEq,
+    Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum ApprovalRole {
+    /// A workspace approver (or owner)
+    #[default]
+    Approver,
+    /// A user allowed to approve changes to secrets
+    SecretManager,
+}
+
+/// What a [`ChangeSet`] touches, as far as approval policies are concerned.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ApprovalSubject {
+    /// An [`Action`](crate::action::Action) was enqueued, removed or modified
+    Action,
+    /// A schema, schema variant, func or any of their children changed
+    Asset,
+    /// A [`Component`](crate::Component) or one of its values changed. The schema is `None` when
+    /// it could not be determined from either snapshot.
+    Component(Option<SchemaId>),
+    /// A [`Secret`](crate::Secret) changed
+    Secret,
+    /// A view or the geometry of a component within a view changed
+    View,
+}
+
+/// Selects the [`ApprovalSubjects`](ApprovalSubject) an [`ApprovalPolicy`] applies to.
+#[remain::sorted]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ApprovalPolicyMatcher {
+    Actions,
+    Any,
+    Assets,
+    Components,
+    #[serde(rename_all = "camelCase")]
+    ComponentsOfSchema {
+        schema_id: SchemaId,
+    },
+    Secrets,
+    Views,
+}
+
+impl ApprovalPolicyMatcher {
+    pub fn matches(&self, subject: ApprovalSubject) -> bool {
+        match (self, subject) {
+            (Self::Any, _)
+            | (Self::Actions, ApprovalSubject::Action)
+            | (Self::Assets, ApprovalSubject::Asset)
+            | (Self::Components, ApprovalSubject::Component(_))
+            | (Self::Secrets, ApprovalSubject::Secret)
+            | (Self::Views, ApprovalSubject::View) => true,
+            // If we cannot tell which schema a component belongs to, err on the side of
+            // requiring the approval.
+            (Self::ComponentsOfSchema { .. }, ApprovalSubject::Component(None)) => true,
+            (Self::ComponentsOfSchema { schema_id }, ApprovalSubject::Component(Some(id))) => {
+                *schema_id == id
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A rule stating how many approvals, and from which [`ApprovalRole`], are needed when a
+/// [`ChangeSet`] touches the subjects selected by its [`ApprovalPolicyMatcher`].
+///
+/// A policy requiring zero approvals exempts the subjects it matches from any later policy.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalPolicy {
+    pub name: String,
+    pub matcher: ApprovalPolicyMatcher,
+    pub required_approvals: u32,
+    #[serde(default)]
+    pub role: ApprovalRole,
+}
+
+impl ApprovalPolicy {
+    /// Lists the approval policies for the workspace in the [`DalContext`], in evaluation order.
+    pub async fn list_for_workspace(ctx: &DalContext) -> ChangeSetResult<Vec<Self>> {
+        let workspace_pk = ctx.workspace_pk()?;
+        Self::list_for_workspace_pk(ctx, workspace_pk).await
+    }
+
+    async fn list_for_workspace_pk(
+        ctx: &DalContext,
+        workspace_pk: WorkspacePk,
+    ) -> ChangeSetResult<Vec<Self>> {
+        let maybe_row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(
+                "SELECT policies FROM change_set_approval_policies WHERE workspace_id = $1",
+                &[&workspace_pk],
+            )
+            .await?;
+
+        match maybe_row {
+            Some(row) => {
+                let json: serde_json::Value = row.try_get("policies")?;
+                Ok(serde_json::from_value(json)?)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Replaces the approval policies for the workspace in the [`DalContext`].
+    pub async fn set_for_workspace(ctx: &DalContext, policies: &[Self]) -> ChangeSetResult<()> {
+        let workspace_pk = ctx.workspace_pk()?;
+        let json = serde_json::to_value(policies)?;
+        ctx.txns()
+            .await?
+            .pg()
+            .query_none(
+                "INSERT INTO change_set_approval_policies (workspace_id, policies) VALUES ($1, $2)
+                ON CONFLICT (workspace_id)
+                DO UPDATE SET policies = $2, updated_at = CLOCK_TIMESTAMP()",
+                &[&workspace_pk, &json],
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// An approval given to a [`ChangeSet`] by a user. An approval only counts while the
+/// [`ChangeSet`] still points at the snapshot it was given for.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSetApproval {
+    pub change_set_id: ChangeSetId,
+    pub user_id: UserPk,
+    pub roles: Vec<ApprovalRole>,
+    pub workspace_snapshot_address: Option<WorkspaceSnapshotAddress>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<PgRow> for ChangeSetApproval {
+    type Error = ChangeSetError;
+
+    fn try_from(value: PgRow) -> Result<Self, Self::Error> {
+        let roles: serde_json::Value = value.try_get("roles")?;
+        Ok(Self {
+            change_set_id: value.try_get("change_set_id")?,
+            user_id: value.try_get("user_id")?,
+            roles: serde_json::from_value(roles)?,
+            workspace_snapshot_address: value.try_get("workspace_snapshot_address")?,
+            created_at: value.try_get("created_at")?,
+        })
+    }
+}
+
+impl ChangeSetApproval {
+    /// Records (or replaces) the approval of a user for a [`ChangeSet`] at the given snapshot.
+    pub async fn upsert(
+        ctx: &DalContext,
+        change_set_id: ChangeSetId,
+        user_id: UserPk,
+        roles: &[ApprovalRole],
+        workspace_snapshot_address: WorkspaceSnapshotAddress,
+    ) -> ChangeSetResult<()> {
+        let roles = serde_json::to_value(roles)?;
+        ctx.txns()
+            .await?
+            .pg()
+            .query_none(
+                "INSERT INTO change_set_approvals (change_set_id, user_id, roles, workspace_snapshot_address)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (change_set_id, user_id)
+                DO UPDATE SET roles = $3, workspace_snapshot_address = $4, created_at = CLOCK_TIMESTAMP()",
+                &[&change_set_id, &user_id, &roles, &workspace_snapshot_address],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_for_change_set(
+        ctx: &DalContext,
+        change_set_id: ChangeSetId,
+    ) -> ChangeSetResult<Vec<Self>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(
+                "SELECT * FROM change_set_approvals WHERE change_set_id = $1 ORDER BY created_at",
+                &[&change_set_id],
+            )
+            .await?;
+
+        rows.into_iter().map(Self::try_from).collect()
+    }
+
+    pub async fn clear_for_change_set(
+        ctx: &DalContext,
+        change_set_id: ChangeSetId,
+    ) -> ChangeSetResult<()> {
+        ctx.txns()
+            .await?
+            .pg()
+            .query_none(
+                "DELETE FROM change_set_approvals WHERE change_set_id = $1",
+                &[&change_set_id],
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// An [`ApprovalPolicy`] that applies to a [`ChangeSet`] and how far it is from being satisfied.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequiredApproval {
+    pub policy_name: String,
+    pub required_approvals: u32,
+    pub role: ApprovalRole,
+    pub approved_by: Vec<UserPk>,
+    pub is_satisfied: bool,
+}
+
+impl ChangeSet {
+    /// Evaluates the workspace's [`ApprovalPolicies`](ApprovalPolicy) against the updates that
+    /// would be applied to the base change set, returning one [`RequiredApproval`] per policy
+    /// that claims at least one touched subject and requires at least one approval.
+    pub async fn required_approvals(
+        &self,
+        ctx: &DalContext,
+    ) -> ChangeSetResult<Vec<RequiredApproval>> {
+        let policies = ApprovalPolicy::list_for_workspace_pk(ctx, self.workspace_id()?).await?;
+        if policies.is_empty() || self.base_change_set_id.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let subjects = self.approval_subjects(ctx).await?;
+        let mut claimed = vec![false; policies.len()];
+        for subject in subjects {
+            if let Some(index) = policies
+                .iter()
+                .position(|policy| policy.matcher.matches(subject))
+            {
+                claimed[index] = true;
+            }
+        }
+
+        let approvals = self.current_approvals(ctx).await?;
+
+        Ok(policies
+            .into_iter()
+            .zip(claimed)
+            .filter(|(policy, claimed)| *claimed && policy.required_approvals > 0)
+            .map(|(policy, _)| {
+                let approved_by: Vec<UserPk> = approvals
+                    .iter()
+                    .filter(|approval| approval.roles.contains(&policy.role))
+                    .map(|approval| approval.user_id)
+                    .collect();
+                let is_satisfied = approved_by.len() >= policy.required_approvals as usize;
+
+                RequiredApproval {
+                    policy_name: policy.name,
+                    required_approvals: policy.required_approvals,
+                    role: policy.role,
+                    approved_by,
+                    is_satisfied,
+                }
+            })
+            .collect())
+    }
+
+    /// The approvals given while the [`ChangeSet`] pointed at its current snapshot. Approvals
+    /// given before it last changed no longer count.
+    pub async fn current_approvals(
+        &self,
+        ctx: &DalContext,
+    ) -> ChangeSetResult<Vec<ChangeSetApproval>> {
+        Ok(ChangeSetApproval::list_for_change_set(ctx, self.id)
+            .await?
+            .into_iter()
+            .filter(|approval| {
+                approval.workspace_snapshot_address == Some(self.workspace_snapshot_address)
+            })
+            .collect())
+    }
+
+    /// Returns true if anyone approved the [`ChangeSet`] as it is now.
+    pub async fn has_current_approval(&self, ctx: &DalContext) -> ChangeSetResult<bool> {
+        Ok(!self.current_approvals(ctx).await?.is_empty())
+    }
+
+    /// Returns true if every [`RequiredApproval`] for this [`ChangeSet`] is satisfied.
+    pub async fn approval_requirements_met(&self, ctx: &DalContext) -> ChangeSetResult<bool> {
+        Ok(self
+            .required_approvals(ctx)
+            .await?
+            .iter()
+            .all(|required| required.is_satisfied))
+    }
+
+    /// Classifies every node touched by the updates that would be applied to the base change
+    /// set into an [`ApprovalSubject`].
+    pub async fn approval_subjects(
+        &self,
+        ctx: &DalContext,
+    ) -> ChangeSetResult<HashSet<ApprovalSubject>> {
+        let Some(rebase_batch) = self.detect_updates_that_will_be_applied(ctx).await? else {
+            return Ok(HashSet::new());
+        };
+        let base_change_set_id = self
+            .base_change_set_id
+            .ok_or(ChangeSetError::NoBaseChangeSet(self.id))?;
+
+        let current_snapshot = ctx.workspace_snapshot().map_err(Box::new)?;
+        let base_snapshot = WorkspaceSnapshot::find_for_change_set(ctx, base_change_set_id)
+            .await
+            .map_err(Box::new)?;
+
+        // Edges belong to their source node, except for edges out of category nodes, where the
+        // interesting node is the one being added to or removed from the category.
+        let mut touched_node_ids = HashSet::new();
+        for update in rebase_batch.updates() {
+            let node_id: Ulid = match update {
+                Update::NewNode { node_weight } | Update::ReplaceNode { node_weight } => {
+                    node_weight.id()
+                }
+                Update::NewEdge {
+                    source,
+                    destination,
+                    ..
+                }
+                | Update::RemoveEdge {
+                    source,
+                    destination,
+                    ..
+                } => {
+                    if source.node_weight_kind == NodeWeightDiscriminants::Category {
+                        destination.id.into()
+                    } else {
+                        source.id.into()
+                    }
+                }
+            };
+            touched_node_ids.insert(node_id);
+        }
+
+        let mut subjects = HashSet::new();
+        for node_id in touched_node_ids {
+            // Nodes removed by this change set only exist in the base snapshot.
+            let snapshot = if current_snapshot
+                .get_node_index_by_id_opt(node_id)
+                .await
+                .is_some()
+            {
+                current_snapshot.as_ref()
+            } else if base_snapshot
+                .get_node_index_by_id_opt(node_id)
+                .await
+                .is_some()
+            {
+                &base_snapshot
+            } else {
+                continue;
+            };
+
+            let node_weight = snapshot
+                .get_node_weight_by_id(node_id)
+                .await
+                .map_err(Box::new)?;
+            if let Some(subject) = classify_node_weight(snapshot, node_weight)
+                .await
+                .map_err(Box::new)?
+            {
+                subjects.insert(subject);
+            }
+        }
+
+        Ok(subjects)
+    }
+}
+
+async fn classify_node_weight(
+    snapshot: &WorkspaceSnapshot,
+    node_weight: NodeWeight,
+) -> WorkspaceSnapshotResult<Option<ApprovalSubject>> {
+    let subject = match &node_weight {
+        NodeWeight::Action(_) => ApprovalSubject::Action,
+        NodeWeight::Component(component) => {
+            component_subject(snapshot, component.id().into()).await?
+        }
+        NodeWeight::Secret(_) => ApprovalSubject::Secret,
+        NodeWeight::Geometry(_) | NodeWeight::View(_) => ApprovalSubject::View,
+        NodeWeight::ActionPrototype(_)
+        | NodeWeight::Func(_)
+        | NodeWeight::FuncArgument(_)
+        | NodeWeight::InputSocket(_)
+        | NodeWeight::ManagementPrototype(_)
+        | NodeWeight::Prop(_)
+        | NodeWeight::SchemaVariant(_) => ApprovalSubject::Asset,
+        NodeWeight::Category(_)
+        | NodeWeight::DependentValueRoot(_)
+        | NodeWeight::FinishedDependentValueRoot(_) => return Ok(None),
+        NodeWeight::AttributePrototypeArgument(_)
+        | NodeWeight::AttributeValue(_)
+        | NodeWeight::Ordering(_) => value_subject(snapshot, node_weight.clone()).await?,
+        NodeWeight::Content(content) => match content.content_address_discriminants() {
+            ContentAddressDiscriminants::AttributePrototype
+            | ContentAddressDiscriminants::StaticArgumentValue
+            | ContentAddressDiscriminants::ValidationOutput => {
+                value_subject(snapshot, node_weight.clone()).await?
+            }
+            ContentAddressDiscriminants::Secret => ApprovalSubject::Secret,
+            ContentAddressDiscriminants::Geometry | ContentAddressDiscriminants::View => {
+                ApprovalSubject::View
+            }
+            ContentAddressDiscriminants::ActionPrototype
+            | ContentAddressDiscriminants::Func
+            | ContentAddressDiscriminants::FuncArg
+            | ContentAddressDiscriminants::InputSocket
+            | ContentAddressDiscriminants::ManagementPrototype
+            | ContentAddressDiscriminants::Module
+            | ContentAddressDiscriminants::OutputSocket
+            | ContentAddressDiscriminants::Prop
+            | ContentAddressDiscriminants::Schema
+            | ContentAddressDiscriminants::SchemaVariant
+            | ContentAddressDiscriminants::ValidationPrototype => ApprovalSubject::Asset,
+            ContentAddressDiscriminants::Component => ApprovalSubject::Component(None),
+            ContentAddressDiscriminants::DeprecatedAction
+            | ContentAddressDiscriminants::DeprecatedActionBatch
+            | ContentAddressDiscriminants::DeprecatedActionRunner
+            | ContentAddressDiscriminants::JsonValue
+            | ContentAddressDiscriminants::Root => return Ok(None),
+        },
+    };
+
+    Ok(Some(subject))
+}
+
+/// Values hanging off of a component belong to that component. Anything else with an attribute
+/// prototype (props, sockets) is part of an asset.
+async fn value_subject(
+    snapshot: &WorkspaceSnapshot,
+    node_weight: NodeWeight,
+) -> WorkspaceSnapshotResult<ApprovalSubject> {
+    let Ok(Some(attribute_value_id)) = snapshot.associated_attribute_value_id(node_weight).await
+    else {
+        return Ok(ApprovalSubject::Asset);
+    };
+
+    match component_id_for_attribute_value(snapshot, attribute_value_id).await? {
+        Some(component_id) => component_subject(snapshot, component_id).await,
+        None => Ok(ApprovalSubject::Component(None)),
+    }
+}
+
+async fn component_subject(
+    snapshot: &WorkspaceSnapshot,
+    component_id: ComponentId,
+) -> WorkspaceSnapshotResult<ApprovalSubject> {
+    let Ok(schema_variant_id) = snapshot
+        .schema_variant_id_for_component_id(component_id)
+        .await
+    else {
+        return Ok(ApprovalSubject::Component(None));
+    };
+
+    let schema_id = snapshot
+        .schema_id_for_schema_variant_id(schema_variant_id)
+        .await
+        .ok();
+
+    Ok(ApprovalSubject::Component(schema_id))
+}
+
+/// Same walk as [`AttributeValue::component_id`](crate::AttributeValue::component_id), but
+/// against an arbitrary snapshot so that it works for values removed by the change set.
+async fn component_id_for_attribute_value(
+    snapshot: &WorkspaceSnapshot,
+    attribute_value_id: AttributeValueId,
+) -> WorkspaceSnapshotResult<Option<ComponentId>> {
+    let mut current_id: Ulid = attribute_value_id.into();
+    while let Some(parent_idx) = snapshot
+        .incoming_sources_for_edge_weight_kind(current_id, EdgeWeightKindDiscriminants::Contain)
+        .await?
+        .first()
+        .copied()
+    {
+        current_id = snapshot.get_node_weight(parent_idx).await?.id();
+    }
+
+    for edge_kind in [
+        EdgeWeightKindDiscriminants::Root,
+        EdgeWeightKindDiscriminants::SocketValue,
+    ] {
+        if let Some(component_idx) = snapshot
+            .incoming_sources_for_edge_weight_kind(current_id, edge_kind)
+            .await?
+            .first()
+            .copied()
+        {
+            return Ok(Some(
+                snapshot.get_node_weight(component_idx).await?.id().into(),
+            ));
+        }
+    }
+
+    Ok(None)
+}
@@ -4,7 +4,7 @@
 use itertools::Itertools;
 use petgraph::Direction::Outgoing;
 use serde::{Deserialize, Serialize};
-use si_pkg::KeyOrIndex;
+use si_pkg::{KeyOrIndex, PkgSpec, PkgSpecDiff, SchemaSpec, SpecError};
 use socket::{ComponentInputSocket, ComponentOutputSocket};
 use std::collections::{hash_map, HashMap, HashSet, VecDeque};
 use std::hash::Hash;
@@ -32,9 +32,11 @@ use crate::change_status::ChangeStatus;
 use crate::code_view::CodeViewError;
 use crate::diagram::{SummaryDiagramEdge, SummaryDiagramInferredEdge};
 use crate::func::argument::FuncArgumentError;
-use crate::history_event::HistoryEventMetadata;
+use crate::history_event::{HistoryEventError, HistoryEventMetadata};
 use crate::layer_db_types::{ComponentContent, ComponentContentV2};
 use crate::module::{Module, ModuleError};
+use crate::pkg::export::PkgExporter;
+use crate::pkg::PkgError;
 use crate::prop::{PropError, PropPath};
 use crate::qualification::QualificationError;
 use crate::schema::variant::leaves::LeafKind;
@@ -72,10 +74,13 @@ pub mod debug;
 pub mod diff;
 pub mod frame;
 pub mod inferred_connection_graph;
+pub mod portable;
 pub mod properties;
 pub mod qualification;
+pub mod query;
 pub mod resource;
 pub mod socket;
+pub mod upgrade;
 
 #[remain::sorted]
 #[derive(Debug, Error)]
@@ -128,6 +133,8 @@ pub enum ComponentError {
     FuncArgumentError(#[from] FuncArgumentError),
     #[error("helper error: {0}")]
     Helper(#[from] HelperError),
+    #[error("history event error: {0}")]
+    HistoryEvent(#[from] HistoryEventError),
     #[error("InferredConnectionGraph Error: {0}")]
     InferredConnectionGraph(#[from] InferredConnectionGraphError),
     #[error("input socket error: {0}")]
@@ -176,6 +183,10 @@ pub enum ComponentError {
     ParseFloat(#[from] ParseFloatError),
     #[error(transparent)]
     ParseInt(#[from] ParseIntError),
+    #[error("pkg error: {0}")]
+    Pkg(#[from] Box<PkgError>),
+    #[error("pkg spec error: {0}")]
+    PkgSpec(#[from] SpecError),
     #[error("prop error: {0}")]
     Prop(#[from] PropError),
     #[error("found prop id ({0}) that is not a prop")]
@@ -208,6 +219,10 @@ pub enum ComponentError {
     TryLock(#[from] TryLockError),
     #[error("unexpected explicit source ({0}) and inferred source ({1}) for input socket match ({2:?}) with an arity of one")]
     UnexpectedExplicitAndInferredSources(ComponentId, ComponentId, ComponentInputSocket),
+    #[error(
+        "component {0} cannot be upgraded to schema variant {1}, which is for a different schema"
+    )]
+    UpgradeTargetForDifferentSchema(ComponentId, SchemaVariantId),
     #[error("value source for known prop attribute value {0} is not a prop id")]
     ValueSourceForPropValueNotPropId(AttributeValueId),
     #[error("workspace error: {0}")]
@@ -3329,18 +3344,25 @@ impl Component {
         Ok(components)
     }
 
+    /// The schema variant this component would be upgraded to: the unlocked variant of its
+    /// schema if there is one, otherwise the default.
+    async fn newest_schema_variant_id(
+        ctx: &DalContext,
+        schema_id: SchemaId,
+    ) -> ComponentResult<SchemaVariantId> {
+        Ok(
+            match SchemaVariant::get_unlocked_for_schema(ctx, schema_id).await? {
+                Some(unlocked_schema_variant) => unlocked_schema_variant.id(),
+                None => SchemaVariant::get_default_id_for_schema(ctx, schema_id).await?,
+            },
+        )
+    }
+
     /// Is there a newer version of the schema variant that this component is using?
     pub async fn can_be_upgraded(&self, ctx: &DalContext) -> ComponentResult<bool> {
         let schema_variant = self.schema_variant(ctx).await?;
         let schema = self.schema(ctx).await?;
-        let default_schema_variant_id =
-            SchemaVariant::get_default_id_for_schema(ctx, schema.id()).await?;
-
-        let newest_schema_variant_id =
-            match SchemaVariant::get_unlocked_for_schema(ctx, schema.id()).await? {
-                Some(unlocked_schema_variant) => unlocked_schema_variant.id(),
-                None => default_schema_variant_id,
-            };
+        let newest_schema_variant_id = Self::newest_schema_variant_id(ctx, schema.id()).await?;
 
         Ok(if newest_schema_variant_id != schema_variant.id() {
             // There's a chance that the exact same asset was installed in
@@ -3361,6 +3383,54 @@ impl Component {
         })
     }
 
+    /// Describes what would change for this component if it were upgraded to the newest version
+    /// of its schema variant: props, sockets, functions and function bindings. Returns `None` if
+    /// the component cannot be upgraded.
+    pub async fn upgrade_diff(&self, ctx: &DalContext) -> ComponentResult<Option<PkgSpecDiff>> {
+        if !self.can_be_upgraded(ctx).await? {
+            return Ok(None);
+        }
+
+        let schema = self.schema(ctx).await?;
+        let current_schema_variant = self.schema_variant(ctx).await?;
+        let newest_schema_variant = SchemaVariant::get_by_id_or_error(
+            ctx,
+            Self::newest_schema_variant_id(ctx, schema.id()).await?,
+        )
+        .await?;
+
+        let current_spec =
+            Self::standalone_variant_pkg_spec(ctx, &schema, &current_schema_variant).await?;
+        let newest_spec =
+            Self::standalone_variant_pkg_spec(ctx, &schema, &newest_schema_variant).await?;
+
+        Ok(Some(current_spec.diff(&newest_spec)))
+    }
+
+    async fn standalone_variant_pkg_spec(
+        ctx: &DalContext,
+        schema: &Schema,
+        schema_variant: &SchemaVariant,
+    ) -> ComponentResult<PkgSpec> {
+        let (variant_spec, funcs) =
+            PkgExporter::export_variant_standalone(ctx, schema_variant, schema.name(), None)
+                .await
+                .map_err(Box::new)?;
+
+        Ok(PkgSpec::builder()
+            .name(schema.name())
+            .version(schema_variant.version())
+            .created_by(ctx.history_actor().email(ctx).await?)
+            .funcs(funcs)
+            .schema(
+                SchemaSpec::builder()
+                    .name(schema.name())
+                    .variant(variant_spec)
+                    .build()?,
+            )
+            .build()?)
+    }
+
     /// Add a [`Manages`](`crate::edge_weight::EdgeWeightKind::Manages`) edge
     /// from a manager component to a managed component, if the managed
     /// component is based on a managed schema
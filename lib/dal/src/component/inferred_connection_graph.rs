@@ -176,6 +176,31 @@ impl InferredConnectionGraph {
         Ok(results)
     }
 
+    /// The components in the same tree of frames as the component, including itself. What gets
+    /// inferred for any of them can depend on every one of them.
+    pub fn component_ids_in_tree(&self, component_id: ComponentId) -> HashSet<ComponentId> {
+        let mut component_ids = HashSet::from([component_id]);
+        let Some(&start_component_index) = self.index_by_component_id.get(&component_id) else {
+            return component_ids;
+        };
+
+        let mut seen = HashSet::from([start_component_index]);
+        let mut stack = vec![start_component_index];
+        while let Some(node_index) = stack.pop() {
+            for neighbor_index in self.down_component_graph.neighbors_undirected(node_index) {
+                if seen.insert(neighbor_index) {
+                    if let Some(node_weight) = self.down_component_graph.node_weight(neighbor_index)
+                    {
+                        component_ids.insert(node_weight.component.id());
+                    }
+                    stack.push(neighbor_index);
+                }
+            }
+        }
+
+        component_ids
+    }
+
     #[instrument(
         name = "component.inferred_connection_graph.inferred_connections_for_component_stack",
         level = "debug",
@@ -0,0 +1,736 @@
+//! Copy a selection of [`Components`](crate::Component) out of one change set and into another,
+//! possibly in a different workspace.
+//!
+//! [`PortableComponents`] is a serializable document made of [`si_pkg`] component and edge specs.
+//! Every component records the schema it was made from (by the module index schema id when there
+//! is one) and the version of its schema variant, its geometry, and the attribute values that were
+//! set on it directly. Connections between components in the selection travel as
+//! [`EdgeSpecKind::Configuration`] edges, and frame membership as [`EdgeSpecKind::Symbolic`] edges
+//! from the child to its frame.
+//!
+//! Attribute functions set on a component itself travel with their arguments, as long as every
+//! argument takes its value from a prop or an input socket of the same component. Overrides that
+//! can't be described that way, or that the destination can't resolve, are listed as
+//! [unportable](UnportableAttribute) instead of being dropped silently.
+//!
+//! Secrets, resources and values computed by functions are not copied: they are recomputed in the
+//! destination once the components are connected.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use si_events::ulid::Ulid;
+use si_pkg::{
+    AttrFuncInputSpec, AttrFuncInputSpecKind, AttributeValuePath, AttributeValueSpec,
+    ComponentSpec, ComponentSpecVariant, EdgeSpec, EdgeSpecKind, FuncSpecBackendKind, PositionSpec,
+    SpecError,
+};
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::attribute::prototype::argument::value_source::ValueSource;
+use crate::attribute::prototype::argument::{
+    AttributePrototypeArgument, AttributePrototypeArgumentError,
+};
+use crate::attribute::prototype::AttributePrototypeError;
+use crate::attribute::value::AttributeValueError;
+use crate::component::frame::{Frame, FrameError};
+use crate::func::argument::{FuncArgument, FuncArgumentError};
+use crate::func::binding::attribute::AttributeBinding;
+use crate::func::binding::{
+    AttributeArgumentBinding, AttributeFuncArgumentSource, AttributeFuncDestination,
+    EventualParent, FuncBindingError,
+};
+use crate::func::FuncError;
+use crate::module::{Module, ModuleError};
+use crate::prop::{PropError, PropPath};
+use crate::socket::input::InputSocketError;
+use crate::socket::output::OutputSocketError;
+use crate::{
+    AttributePrototype, AttributePrototypeId, AttributeValue, AttributeValueId, Component,
+    ComponentError, ComponentId, ComponentType, DalContext, Func, InputSocket, OutputSocket, Prop,
+    PropKind, Schema, SchemaError, SchemaVariant, SchemaVariantError, SchemaVariantId,
+};
+
+/// The trees under `/root` whose values are copied.
+const COPIED_TREES: &[&str] = &["si", "domain"];
+/// Set through [`Component::new`] rather than as an attribute.
+const NAME_PATH: &str = "/root/si/name";
+/// Set through [`Component::set_type_by_id`] so frames are handled properly.
+const TYPE_PATH: &str = "/root/si/type";
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum PortableComponentsError {
+    #[error("attribute prototype error: {0}")]
+    AttributePrototype(#[from] AttributePrototypeError),
+    #[error("attribute prototype argument error: {0}")]
+    AttributePrototypeArgument(#[from] AttributePrototypeArgumentError),
+    #[error("attribute value error: {0}")]
+    AttributeValue(#[from] AttributeValueError),
+    #[error("component error: {0}")]
+    Component(#[from] ComponentError),
+    #[error("frame error: {0}")]
+    Frame(#[from] FrameError),
+    #[error("func error: {0}")]
+    Func(#[from] FuncError),
+    #[error("func argument error: {0}")]
+    FuncArgument(#[from] FuncArgumentError),
+    #[error("func binding error: {0}")]
+    FuncBinding(#[from] FuncBindingError),
+    #[error("input socket error: {0}")]
+    InputSocket(#[from] InputSocketError),
+    #[error("module error: {0}")]
+    Module(#[from] ModuleError),
+    #[error("output socket error: {0}")]
+    OutputSocket(#[from] OutputSocketError),
+    #[error("prop error: {0}")]
+    Prop(#[from] PropError),
+    #[error("schema error: {0}")]
+    Schema(#[from] SchemaError),
+    #[error("schema variant error: {0}")]
+    SchemaVariant(#[from] SchemaVariantError),
+    #[error("serde json error: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("spec error: {0}")]
+    Spec(#[from] SpecError),
+    #[error("component {0} does not refer to its schema variant by schema id and version")]
+    UnsupportedVariant(String),
+}
+
+pub type PortableComponentsResult<T> = Result<T, PortableComponentsError>;
+
+/// A selection of components, with their frame children, the connections between them, their
+/// attribute values and their geometry. See the [module docs](self).
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortableComponents {
+    pub components: Vec<ComponentSpec>,
+    pub edges: Vec<EdgeSpec>,
+    /// Attribute functions set on the exported components that the document can't carry.
+    #[serde(default)]
+    pub unportable_attributes: Vec<UnportableAttribute>,
+}
+
+/// An attribute function set on a component that was not copied.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnportableAttribute {
+    /// The unique id of the component in the document.
+    pub component_unique_id: String,
+    /// The prop path of the value, like `/root/domain/name`.
+    pub path: String,
+    pub func_name: String,
+    pub reason: UnportableAttributeReason,
+}
+
+/// Why an [`UnportableAttribute`] was not copied.
+#[remain::sorted]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum UnportableAttributeReason {
+    /// An argument takes its value from a static value, a secret or an output socket.
+    ArgumentSource { argument: String },
+    /// The value is inside a map or an array, whose elements can't be addressed by prop path.
+    InsideMapOrArray,
+    /// The destination func has no argument with this name.
+    MissingArgument { argument: String },
+    /// The destination has no attribute func with this name.
+    MissingFunc,
+    /// The destination schema variant has no input socket with this name.
+    MissingInputSocket { socket_name: String },
+    /// The destination schema variant has no prop at this path.
+    MissingProp { prop_path: String },
+}
+
+/// A schema variant that a [`PortableComponents`] document needs but that the destination change
+/// set does not have.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingSchemaVariant {
+    pub schema_id: String,
+    pub schema_name: String,
+    pub variant_version: String,
+}
+
+/// What [`PortableComponents::import`] did.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentImportReport {
+    /// The components created, by their unique id in the document.
+    pub component_ids: HashMap<String, ComponentId>,
+    /// Each schema variant that could not be found, once.
+    pub missing_schema_variants: Vec<MissingSchemaVariant>,
+    /// Unique ids of the components that were not created because their schema variant is
+    /// missing. Connections to them and frame membership through them are dropped.
+    pub skipped_components: Vec<String>,
+    /// Attribute functions that were not set, both those the document could not carry and those
+    /// the destination could not resolve.
+    pub unportable_attributes: Vec<UnportableAttribute>,
+}
+
+impl PortableComponents {
+    /// Export the components, everything inside any frames among them, and the connections and
+    /// frame memberships between all of those.
+    #[instrument(level = "info", name = "component.portable.export", skip(ctx))]
+    pub async fn export(
+        ctx: &DalContext,
+        component_ids: &[ComponentId],
+    ) -> PortableComponentsResult<Self> {
+        let mut selected = vec![];
+        let mut seen = HashSet::new();
+        let mut work_queue: VecDeque<ComponentId> = component_ids.iter().copied().collect();
+        while let Some(component_id) = work_queue.pop_front() {
+            if seen.insert(component_id) {
+                selected.push(component_id);
+                work_queue.extend(Component::get_children_for_id(ctx, component_id).await?);
+            }
+        }
+
+        let mut document = Self::default();
+        for &component_id in &selected {
+            let component = Component::get_by_id(ctx, component_id).await?;
+            let (spec, unportable_attributes) = component_spec(ctx, &component).await?;
+            document.components.push(spec);
+            document.unportable_attributes.extend(unportable_attributes);
+
+            if let Some(parent_id) = Component::get_parent_by_id(ctx, component_id).await? {
+                if seen.contains(&parent_id) {
+                    document.edges.push(
+                        EdgeSpec::builder()
+                            .edge_kind(EdgeSpecKind::Symbolic)
+                            .from_component_unique_id(component_id.to_string())
+                            .from_socket_name("")
+                            .to_component_unique_id(parent_id.to_string())
+                            .to_socket_name("")
+                            .creation_user_pk(None::<String>)
+                            .deletion_user_pk(None::<String>)
+                            .deleted_implicitly(false)
+                            .build()?,
+                    );
+                }
+            }
+
+            for connection in component.incoming_connections(ctx).await? {
+                if !seen.contains(&connection.from_component_id) {
+                    continue;
+                }
+                let output_socket =
+                    OutputSocket::get_by_id(ctx, connection.from_output_socket_id).await?;
+                let input_socket =
+                    InputSocket::get_by_id(ctx, connection.to_input_socket_id).await?;
+                document.edges.push(
+                    EdgeSpec::builder()
+                        .edge_kind(EdgeSpecKind::Configuration)
+                        .from_component_unique_id(connection.from_component_id.to_string())
+                        .from_socket_name(output_socket.name())
+                        .to_component_unique_id(component_id.to_string())
+                        .to_socket_name(input_socket.name())
+                        .creation_user_pk(None::<String>)
+                        .deletion_user_pk(None::<String>)
+                        .deleted_implicitly(false)
+                        .unique_id(connection.attribute_prototype_argument_id.to_string())
+                        .build()?,
+                );
+            }
+        }
+
+        Ok(document)
+    }
+
+    /// Create the components of the document in the current change set, matching each one to a
+    /// schema variant with the same schema and version. Components whose schema variant cannot be
+    /// found are skipped and reported rather than failing the import.
+    #[instrument(level = "info", name = "component.portable.import", skip_all)]
+    pub async fn import(
+        &self,
+        ctx: &DalContext,
+    ) -> PortableComponentsResult<ComponentImportReport> {
+        let mut report = ComponentImportReport {
+            unportable_attributes: self.unportable_attributes.clone(),
+            ..Default::default()
+        };
+        let mut schema_variant_ids: HashMap<(&str, &str), Option<SchemaVariantId>> = HashMap::new();
+
+        for spec in &self.components {
+            let ComponentSpecVariant::SchemaVariant {
+                schema_id,
+                schema_name,
+                variant_version,
+            } = &spec.variant
+            else {
+                return Err(PortableComponentsError::UnsupportedVariant(
+                    spec.unique_id.to_owned(),
+                ));
+            };
+
+            let key = (schema_id.as_str(), variant_version.as_str());
+            let schema_variant_id = match schema_variant_ids.get(&key) {
+                Some(schema_variant_id) => *schema_variant_id,
+                None => {
+                    let schema_variant_id =
+                        find_schema_variant(ctx, schema_id, schema_name, variant_version).await?;
+                    if schema_variant_id.is_none() {
+                        report.missing_schema_variants.push(MissingSchemaVariant {
+                            schema_id: schema_id.to_owned(),
+                            schema_name: schema_name.to_owned(),
+                            variant_version: variant_version.to_owned(),
+                        });
+                    }
+                    schema_variant_ids.insert(key, schema_variant_id);
+                    schema_variant_id
+                }
+            };
+            let Some(schema_variant_id) = schema_variant_id else {
+                report.skipped_components.push(spec.unique_id.to_owned());
+                continue;
+            };
+
+            let mut component = Component::new(ctx, &spec.name, schema_variant_id).await?;
+            component
+                .set_geometry(
+                    ctx,
+                    spec.position.x.to_owned(),
+                    spec.position.y.to_owned(),
+                    spec.position.width.to_owned(),
+                    spec.position.height.to_owned(),
+                )
+                .await?;
+            for attribute in &spec.attributes {
+                if attribute.backend_kind != FuncSpecBackendKind::JsAttribute {
+                    set_attribute(ctx, component.id(), attribute).await?;
+                } else if let (AttributeValuePath::Prop { path, .. }, Err(reason)) = (
+                    &attribute.path,
+                    set_attribute_override(ctx, component.id(), schema_variant_id, attribute)
+                        .await?,
+                ) {
+                    report.unportable_attributes.push(UnportableAttribute {
+                        component_unique_id: spec.unique_id.to_owned(),
+                        path: path.to_owned(),
+                        func_name: attribute.func_unique_id.to_owned(),
+                        reason,
+                    });
+                }
+            }
+
+            report
+                .component_ids
+                .insert(spec.unique_id.to_owned(), component.id());
+        }
+
+        for edge in &self.edges {
+            let (Some(&from_component_id), Some(&to_component_id)) = (
+                report.component_ids.get(&edge.from_component_unique_id),
+                report.component_ids.get(&edge.to_component_unique_id),
+            ) else {
+                continue;
+            };
+
+            match edge.edge_kind {
+                EdgeSpecKind::Configuration => {
+                    let output_socket = OutputSocket::find_with_name_or_error(
+                        ctx,
+                        &edge.from_socket_name,
+                        Component::schema_variant_id(ctx, from_component_id).await?,
+                    )
+                    .await?;
+                    let input_socket = InputSocket::find_with_name_or_error(
+                        ctx,
+                        &edge.to_socket_name,
+                        Component::schema_variant_id(ctx, to_component_id).await?,
+                    )
+                    .await?;
+                    Component::connect(
+                        ctx,
+                        from_component_id,
+                        output_socket.id(),
+                        to_component_id,
+                        input_socket.id(),
+                    )
+                    .await?;
+                }
+                EdgeSpecKind::Symbolic => {
+                    Frame::upsert_parent(ctx, from_component_id, to_component_id).await?;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+async fn component_spec(
+    ctx: &DalContext,
+    component: &Component,
+) -> PortableComponentsResult<(ComponentSpec, Vec<UnportableAttribute>)> {
+    let schema = component.schema(ctx).await?;
+    let schema_variant = component.schema_variant(ctx).await?;
+    // Prefer the module index schema id, which is the same in every workspace the schema is
+    // installed in.
+    let schema_id = match Module::find_for_member_id(ctx, schema.id())
+        .await?
+        .and_then(|module| module.schema_id())
+    {
+        Some(module_schema_id) => module_schema_id.to_string(),
+        None => schema.id().to_string(),
+    };
+    let geometry = component.geometry(ctx).await?.into_raw();
+    let (attributes, unportable_attributes) = attribute_specs(ctx, component.id()).await?;
+
+    let spec = ComponentSpec::builder()
+        .name(component.name(ctx).await?)
+        .position(
+            PositionSpec::builder()
+                .x(geometry.x)
+                .y(geometry.y)
+                .width(geometry.width)
+                .height(geometry.height)
+                .build()?,
+        )
+        .variant(ComponentSpecVariant::SchemaVariant {
+            schema_id,
+            schema_name: schema.name().to_owned(),
+            variant_version: schema_variant.version().to_owned(),
+        })
+        .needs_destroy(false)
+        .deletion_user_pk(None::<String>)
+        .unique_id(component.id().to_string())
+        .deleted(false)
+        .attributes(attributes)
+        .build()?;
+
+    Ok((spec, unportable_attributes))
+}
+
+/// The values and attribute functions set directly on the component. Maps and arrays are copied
+/// whole when anything in them was set directly, since their elements can't be addressed by prop
+/// path alone; for the same reason, functions set on their elements are unportable.
+async fn attribute_specs(
+    ctx: &DalContext,
+    component_id: ComponentId,
+) -> PortableComponentsResult<(Vec<AttributeValueSpec>, Vec<UnportableAttribute>)> {
+    let root_attribute_value_id = Component::root_attribute_value_id(ctx, component_id).await?;
+    let mut work_queue = VecDeque::new();
+    for child_id in AttributeValue::get_child_av_ids_in_order(ctx, root_attribute_value_id).await? {
+        let prop = AttributeValue::prop(ctx, child_id).await?;
+        if COPIED_TREES.contains(&prop.name.as_str()) {
+            work_queue.push_back((child_id, format!("/root/{}", prop.name)));
+        }
+    }
+
+    let mut specs = vec![];
+    let mut unportable_attributes = vec![];
+    while let Some((attribute_value_id, path)) = work_queue.pop_front() {
+        if let Some((prototype_id, func)) = override_of(ctx, attribute_value_id).await? {
+            match override_spec(ctx, prototype_id, &func, &path).await? {
+                Ok(spec) => specs.push(spec),
+                Err(reason) => unportable_attributes.push(UnportableAttribute {
+                    component_unique_id: component_id.to_string(),
+                    path,
+                    func_name: func.name,
+                    reason,
+                }),
+            }
+            continue;
+        }
+
+        let prop = AttributeValue::prop(ctx, attribute_value_id).await?;
+        let copied = match prop.kind {
+            PropKind::Object => {
+                for child_id in
+                    AttributeValue::get_child_av_ids_in_order(ctx, attribute_value_id).await?
+                {
+                    let child_prop = AttributeValue::prop(ctx, child_id).await?;
+                    work_queue.push_back((child_id, format!("{path}/{}", child_prop.name)));
+                }
+                false
+            }
+            PropKind::Array | PropKind::Map => {
+                for func in overrides_in_tree(ctx, attribute_value_id).await? {
+                    unportable_attributes.push(UnportableAttribute {
+                        component_unique_id: component_id.to_string(),
+                        path: path.to_owned(),
+                        func_name: func.name,
+                        reason: UnportableAttributeReason::InsideMapOrArray,
+                    });
+                }
+                is_set_in_tree_directly(ctx, attribute_value_id).await?
+            }
+            _ => path != NAME_PATH && is_set_directly(ctx, attribute_value_id).await?,
+        };
+        if !copied {
+            continue;
+        }
+
+        let Some(value) = AttributeValue::get_by_id(ctx, attribute_value_id)
+            .await?
+            .view(ctx)
+            .await?
+        else {
+            continue;
+        };
+        let func = AttributeValue::prototype_func(ctx, attribute_value_id).await?;
+        specs.push(
+            AttributeValueSpec::builder()
+                .path(AttributeValuePath::Prop {
+                    path,
+                    key_or_index: None,
+                })
+                .func_unique_id(func.name)
+                .func_binding_args(serde_json::json!({ "value": value }))
+                .backend_kind(func.backend_kind)
+                .response_type(func.backend_response_type)
+                .value(value)
+                .component_specific(true)
+                .build()?,
+        );
+    }
+
+    Ok((specs, unportable_attributes))
+}
+
+/// The component prototype of the value and its func, if the value is set on the component by a
+/// function other than an intrinsic one.
+async fn override_of(
+    ctx: &DalContext,
+    attribute_value_id: AttributeValueId,
+) -> PortableComponentsResult<Option<(AttributePrototypeId, Func)>> {
+    let Some(prototype_id) =
+        AttributeValue::component_prototype_id(ctx, attribute_value_id).await?
+    else {
+        return Ok(None);
+    };
+    let func_id = AttributePrototype::func_id(ctx, prototype_id).await?;
+    let func = Func::get_by_id_or_error(ctx, func_id).await?;
+
+    Ok((!func.is_intrinsic()).then_some((prototype_id, func)))
+}
+
+/// The funcs set on the component below a map or an array value.
+async fn overrides_in_tree(
+    ctx: &DalContext,
+    attribute_value_id: AttributeValueId,
+) -> PortableComponentsResult<Vec<Func>> {
+    let mut funcs = vec![];
+    let mut work_queue =
+        VecDeque::from(AttributeValue::get_child_av_ids_in_order(ctx, attribute_value_id).await?);
+    while let Some(attribute_value_id) = work_queue.pop_front() {
+        match override_of(ctx, attribute_value_id).await? {
+            Some((_, func)) => funcs.push(func),
+            None => work_queue
+                .extend(AttributeValue::get_child_av_ids_in_order(ctx, attribute_value_id).await?),
+        }
+    }
+
+    Ok(funcs)
+}
+
+/// Describe a function set on the component by its name and, for each argument, the prop path or
+/// input socket name it takes its value from.
+async fn override_spec(
+    ctx: &DalContext,
+    prototype_id: AttributePrototypeId,
+    func: &Func,
+    path: &str,
+) -> PortableComponentsResult<Result<AttributeValueSpec, UnportableAttributeReason>> {
+    let mut inputs = vec![];
+    for apa_id in AttributePrototypeArgument::list_ids_for_prototype(ctx, prototype_id).await? {
+        let func_argument_id =
+            AttributePrototypeArgument::func_argument_id_by_id(ctx, apa_id).await?;
+        let argument = FuncArgument::get_by_id_or_error(ctx, func_argument_id)
+            .await?
+            .name;
+        let input = match AttributePrototypeArgument::value_source_by_id(ctx, apa_id).await? {
+            Some(ValueSource::Prop(prop_id)) => AttrFuncInputSpec::builder()
+                .kind(AttrFuncInputSpecKind::Prop)
+                .name(argument)
+                .prop_path(Prop::path_by_id(ctx, prop_id).await?)
+                .build()?,
+            Some(ValueSource::InputSocket(input_socket_id)) => AttrFuncInputSpec::builder()
+                .kind(AttrFuncInputSpecKind::InputSocket)
+                .name(argument)
+                .socket_name(InputSocket::get_by_id(ctx, input_socket_id).await?.name())
+                .build()?,
+            _ => return Ok(Err(UnportableAttributeReason::ArgumentSource { argument })),
+        };
+        inputs.push(input);
+    }
+
+    Ok(Ok(AttributeValueSpec::builder()
+        .path(AttributeValuePath::Prop {
+            path: path.to_owned(),
+            key_or_index: None,
+        })
+        .func_unique_id(func.name.to_owned())
+        .func_binding_args(serde_json::json!({}))
+        .backend_kind(func.backend_kind)
+        .response_type(func.backend_response_type)
+        .component_specific(true)
+        .inputs(inputs)
+        .build()?))
+}
+
+async fn is_set_directly(
+    ctx: &DalContext,
+    attribute_value_id: AttributeValueId,
+) -> PortableComponentsResult<bool> {
+    Ok(
+        AttributeValue::component_prototype_id(ctx, attribute_value_id)
+            .await?
+            .is_some()
+            && AttributeValue::is_set_by_intrinsic(ctx, attribute_value_id).await?
+            && !AttributeValue::is_set_by_unset(ctx, attribute_value_id).await?,
+    )
+}
+
+async fn is_set_in_tree_directly(
+    ctx: &DalContext,
+    attribute_value_id: AttributeValueId,
+) -> PortableComponentsResult<bool> {
+    let mut work_queue = VecDeque::from([attribute_value_id]);
+    while let Some(attribute_value_id) = work_queue.pop_front() {
+        if is_set_directly(ctx, attribute_value_id).await? {
+            return Ok(true);
+        }
+        work_queue
+            .extend(AttributeValue::get_child_av_ids_in_order(ctx, attribute_value_id).await?);
+    }
+
+    Ok(false)
+}
+
+async fn set_attribute(
+    ctx: &DalContext,
+    component_id: ComponentId,
+    attribute: &AttributeValueSpec,
+) -> PortableComponentsResult<()> {
+    let (AttributeValuePath::Prop { path, .. }, Some(value)) = (&attribute.path, &attribute.value)
+    else {
+        return Ok(());
+    };
+
+    if path == TYPE_PATH {
+        let component_type: ComponentType = serde_json::from_value(value.to_owned())?;
+        Component::set_type_by_id(ctx, component_id, component_type).await?;
+    } else {
+        let prop_path: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        let attribute_value_id =
+            Component::attribute_value_for_prop_by_id(ctx, component_id, &prop_path).await?;
+        AttributeValue::update(ctx, attribute_value_id, Some(value.to_owned())).await?;
+    }
+
+    Ok(())
+}
+
+/// Set the function described by [`override_spec`] on the component, unless the destination is
+/// missing the func, one of its arguments, or a prop or input socket it reads from.
+async fn set_attribute_override(
+    ctx: &DalContext,
+    component_id: ComponentId,
+    schema_variant_id: SchemaVariantId,
+    attribute: &AttributeValueSpec,
+) -> PortableComponentsResult<Result<(), UnportableAttributeReason>> {
+    let AttributeValuePath::Prop { path, .. } = &attribute.path else {
+        return Ok(Ok(()));
+    };
+    let Some(func_id) = Func::find_id_by_name(ctx, &attribute.func_unique_id).await? else {
+        return Ok(Err(UnportableAttributeReason::MissingFunc));
+    };
+    let prop_path = PropPath::new(path.trim_start_matches('/').split('/'));
+    let Some(prop_id) = Prop::find_prop_id_by_path_opt(ctx, schema_variant_id, &prop_path).await?
+    else {
+        return Ok(Err(UnportableAttributeReason::MissingProp {
+            prop_path: path.to_owned(),
+        }));
+    };
+
+    let mut arguments = vec![];
+    for input in &attribute.inputs {
+        let Some(func_argument) =
+            FuncArgument::find_by_name_for_func(ctx, input.name(), func_id).await?
+        else {
+            return Ok(Err(UnportableAttributeReason::MissingArgument {
+                argument: input.name().to_owned(),
+            }));
+        };
+        let source = match input {
+            AttrFuncInputSpec::Prop { prop_path, .. } => {
+                let prop_path = PropPath::from(prop_path);
+                match Prop::find_prop_id_by_path_opt(ctx, schema_variant_id, &prop_path).await? {
+                    Some(prop_id) => AttributeFuncArgumentSource::Prop(prop_id),
+                    None => {
+                        return Ok(Err(UnportableAttributeReason::MissingProp {
+                            prop_path: prop_path.with_replaced_sep_and_prefix("/"),
+                        }))
+                    }
+                }
+            }
+            AttrFuncInputSpec::InputSocket { socket_name, .. } => {
+                match InputSocket::find_with_name(ctx, socket_name, schema_variant_id).await? {
+                    Some(input_socket) => {
+                        AttributeFuncArgumentSource::InputSocket(input_socket.id())
+                    }
+                    None => {
+                        return Ok(Err(UnportableAttributeReason::MissingInputSocket {
+                            socket_name: socket_name.to_owned(),
+                        }))
+                    }
+                }
+            }
+            AttrFuncInputSpec::OutputSocket { name, .. } => {
+                return Ok(Err(UnportableAttributeReason::ArgumentSource {
+                    argument: name.to_owned(),
+                }))
+            }
+        };
+        arguments.push(AttributeArgumentBinding {
+            func_argument_id: func_argument.id,
+            attribute_prototype_argument_id: None,
+            attribute_func_input_location: source,
+        });
+    }
+
+    AttributeBinding::upsert_attribute_binding(
+        ctx,
+        func_id,
+        Some(EventualParent::Component(component_id)),
+        AttributeFuncDestination::Prop(prop_id),
+        arguments,
+    )
+    .await?;
+
+    Ok(Ok(()))
+}
+
+/// Find a variant of the schema with the given version. The schema is looked up by module index
+/// schema id, then by its id in this workspace, then by name for schemas installed before module
+/// index schema ids existed.
+async fn find_schema_variant(
+    ctx: &DalContext,
+    schema_id: &str,
+    schema_name: &str,
+    variant_version: &str,
+) -> PortableComponentsResult<Option<SchemaVariantId>> {
+    let mut schemas = vec![];
+    if let Ok(schema_ulid) = Ulid::from_string(schema_id) {
+        if let Some(module) = Module::find_for_module_schema_id(ctx, schema_ulid).await? {
+            schemas.extend(module.list_associated_schemas(ctx).await?);
+        }
+        if let Some(schema) = Schema::get_by_id(ctx, schema_ulid.into()).await? {
+            schemas.push(schema);
+        }
+    }
+    if let Some(schema) = Schema::find_by_name(ctx, schema_name).await? {
+        schemas.push(schema);
+    }
+
+    for schema in schemas {
+        for schema_variant in SchemaVariant::list_for_schema(ctx, schema.id()).await? {
+            if schema_variant.version() == variant_version {
+                return Ok(Some(schema_variant.id()));
+            }
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,424 @@
+//! Find [`Components`](crate::Component) in a change set by what they are and what their props
+//! are set to, without loading every component's properties.
+//!
+//! A [`ComponentQuery`] is a small filter language:
+//!
+//! ```json
+//! {
+//!   "kind": "all",
+//!   "queries": [
+//!     { "kind": "schema", "name": "AWS::EC2::Instance" },
+//!     { "kind": "prop", "path": "/root/domain/region", "value": { "equals": "us-east-1" } },
+//!     { "kind": "not", "query": { "kind": "resourceStatus", "status": "ok" } }
+//!   ]
+//! }
+//! ```
+//!
+//! Prop values are looked up through an index for each prop path that is built the first time a
+//! query needs it. The index of a change set is kept in memory between queries, along with the
+//! address of the snapshot it was built from, and only the values of the components that changed
+//! since (see [`ChangeScope`]) are read again. New values count as changes, so values that
+//! dependent values updates compute elsewhere are read again too.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use async_recursion::async_recursion;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use si_events::WorkspaceSnapshotAddress;
+use telemetry::prelude::*;
+use thiserror::Error;
+use veritech_client::ResourceStatus;
+
+use crate::attribute::value::AttributeValueError;
+use crate::prop::{PropError, PropPath};
+use crate::schema::variant::SchemaVariantError;
+use crate::workspace_snapshot::change_set_cache::ChangeSetCache;
+use crate::workspace_snapshot::graph::{ChangeScope, ChangeScopeKind};
+use crate::workspace_snapshot::WorkspaceSnapshotError;
+use crate::{
+    AttributeValue, Component, ComponentError, ComponentId, DalContext, Prop, SchemaVariant,
+    SchemaVariantId,
+};
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum ComponentQueryError {
+    #[error("attribute value error: {0}")]
+    AttributeValue(#[from] AttributeValueError),
+    #[error("component error: {0}")]
+    Component(#[from] ComponentError),
+    #[error("invalid regex {0:?}: {1}")]
+    InvalidRegex(String, #[source] regex::Error),
+    #[error("prop error: {0}")]
+    Prop(#[from] PropError),
+    #[error("prop path must start with /root: {0}")]
+    PropPathOutsideRoot(String),
+    #[error("schema variant error: {0}")]
+    SchemaVariant(#[from] SchemaVariantError),
+    #[error("serde json error: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("workspace snapshot error: {0}")]
+    WorkspaceSnapshot(#[from] WorkspaceSnapshotError),
+}
+
+pub type ComponentQueryResult<T> = Result<T, ComponentQueryError>;
+
+/// A filter over the components of a change set. See the [module docs](self) for what it
+/// looks like as JSON.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ComponentQuery {
+    /// Components matching every one of the queries (all components if there are none).
+    All {
+        queries: Vec<ComponentQuery>,
+    },
+    /// Components matching at least one of the queries (none if there are none).
+    Any {
+        queries: Vec<ComponentQuery>,
+    },
+    /// Components with an explicit connection to or from the component, in either direction.
+    ConnectedTo {
+        component_id: ComponentId,
+    },
+    /// Components inside the frame. Components in frames nested inside it are included unless
+    /// `direct_only` is set.
+    InFrame {
+        frame_id: ComponentId,
+        #[serde(default)]
+        direct_only: bool,
+    },
+    Not {
+        query: Box<ComponentQuery>,
+    },
+    /// Components with a value at the prop path (e.g. `/root/domain/region`) that matches. For
+    /// props inside arrays and maps, any one of the values matching is enough.
+    Prop {
+        path: String,
+        value: ValueMatch,
+    },
+    /// Components with a resource in the status.
+    ResourceStatus {
+        status: ResourceStatus,
+    },
+    /// Components of the schema with the name.
+    Schema {
+        name: String,
+    },
+}
+
+/// How a [`ComponentQuery::Prop`] value is matched.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ValueMatch {
+    Equals(serde_json::Value),
+    /// String values that start with the prefix.
+    Prefix(String),
+    /// String values that the regex matches somewhere in.
+    Regex(String),
+}
+
+/// The values of every component for a prop path.
+type PropValues = HashMap<ComponentId, Vec<serde_json::Value>>;
+
+/// How many change sets to keep indexes for.
+const MAX_INDEXES: usize = 64;
+
+static INDEXES: Lazy<ChangeSetCache<ComponentQueryIndex>> =
+    Lazy::new(|| ChangeSetCache::new(MAX_INDEXES));
+
+/// Prop values by prop path, as of the snapshot at `address`.
+#[derive(Debug)]
+struct ComponentQueryIndex {
+    address: WorkspaceSnapshotAddress,
+    values_by_path: HashMap<String, IndexedPropValues>,
+}
+
+#[derive(Debug)]
+struct IndexedPropValues {
+    values: Arc<PropValues>,
+    /// The components whose values may have changed since they were read
+    stale_component_ids: HashSet<ComponentId>,
+}
+
+impl ComponentQueryIndex {
+    /// The index for the change set of the context, with the values of the components that
+    /// changed since it was kept marked as stale.
+    async fn for_context(ctx: &DalContext) -> ComponentQueryResult<Self> {
+        let workspace_snapshot = ctx.workspace_snapshot()?;
+        let address = workspace_snapshot.id().await;
+
+        let mut index = match INDEXES.take(ctx.change_set_id()) {
+            Some(index) => index,
+            None => {
+                return Ok(Self {
+                    address,
+                    values_by_path: HashMap::new(),
+                })
+            }
+        };
+
+        let scope = workspace_snapshot
+            .change_scope_since_address(ctx, index.address, ChangeScopeKind::StructureAndValues)
+            .await?;
+        index.invalidate(scope);
+        index.address = address;
+
+        Ok(index)
+    }
+
+    /// Keeps the index for the next query in the change set of the context. Values read from
+    /// changes that have not been written yet are marked as stale first, since they may never
+    /// be.
+    async fn keep(mut self, ctx: &DalContext) -> ComponentQueryResult<()> {
+        if self.address == WorkspaceSnapshotAddress::nil() {
+            return Ok(());
+        }
+        self.invalidate(ctx.workspace_snapshot()?.unwritten_change_scope().await);
+        INDEXES.keep(ctx.change_set_id(), self);
+
+        Ok(())
+    }
+
+    fn invalidate(&mut self, scope: ChangeScope) {
+        match scope {
+            ChangeScope::Components(component_ids) => {
+                for indexed in self.values_by_path.values_mut() {
+                    indexed
+                        .stale_component_ids
+                        .extend(component_ids.iter().copied());
+                }
+            }
+            ChangeScope::Everything => self.values_by_path.clear(),
+        }
+    }
+
+    /// The values of every component at the prop path, reading only the ones that are not
+    /// indexed yet or are stale.
+    async fn prop_values(
+        &mut self,
+        ctx: &DalContext,
+        path: &str,
+    ) -> ComponentQueryResult<Arc<PropValues>> {
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        if parts.first() != Some(&"root") {
+            return Err(ComponentQueryError::PropPathOutsideRoot(path.to_owned()));
+        }
+        let prop_path = PropPath::new(&parts);
+
+        // Without an index for the path, every component is stale
+        let (mut values, stale_component_ids) = match self.values_by_path.remove(prop_path.as_str())
+        {
+            Some(indexed) => (indexed.values, Some(indexed.stale_component_ids)),
+            None => (Arc::default(), None),
+        };
+        let is_stale = |component_id: &ComponentId| match &stale_component_ids {
+            Some(stale_component_ids) => stale_component_ids.contains(component_id),
+            None => true,
+        };
+
+        if !matches!(&stale_component_ids, Some(ids) if ids.is_empty()) {
+            let values = Arc::make_mut(&mut values);
+            values.retain(|component_id, _| !is_stale(component_id));
+
+            let mut schema_variant_ids = HashSet::new();
+            for component_id in Component::list_ids(ctx).await? {
+                if is_stale(&component_id) {
+                    schema_variant_ids
+                        .insert(Component::schema_variant_id(ctx, component_id).await?);
+                }
+            }
+
+            // Each schema variant has its own prop at the path, with the values of all of its
+            // components
+            for schema_variant_id in schema_variant_ids {
+                let Some(prop_id) =
+                    Prop::find_prop_id_by_path_opt(ctx, schema_variant_id, &prop_path).await?
+                else {
+                    continue;
+                };
+                for attribute_value_id in
+                    Prop::all_attribute_values_everywhere_for_prop_id(ctx, prop_id).await?
+                {
+                    let component_id =
+                        AttributeValue::component_id(ctx, attribute_value_id).await?;
+                    if !is_stale(&component_id) {
+                        continue;
+                    }
+                    let value = AttributeValue::get_by_id(ctx, attribute_value_id)
+                        .await?
+                        .view(ctx)
+                        .await?;
+                    if let Some(value) = value {
+                        values.entry(component_id).or_default().push(value);
+                    }
+                }
+            }
+        }
+
+        self.values_by_path.insert(
+            prop_path.as_str().to_owned(),
+            IndexedPropValues {
+                values: values.clone(),
+                stale_component_ids: HashSet::new(),
+            },
+        );
+
+        Ok(values)
+    }
+}
+
+impl ComponentQuery {
+    /// The ids of the components that match, in order.
+    #[instrument(name = "component.query.run", level = "info", skip_all)]
+    pub async fn run(&self, ctx: &DalContext) -> ComponentQueryResult<Vec<ComponentId>> {
+        let candidates = Component::list_ids(ctx).await?.into_iter().collect();
+        let mut schema_names = HashMap::new();
+        let mut index = ComponentQueryIndex::for_context(ctx).await?;
+
+        let matching = self
+            .matching(ctx, &candidates, &mut schema_names, &mut index)
+            .await?;
+        index.keep(ctx).await?;
+
+        Ok(matching.into_iter().collect())
+    }
+
+    #[async_recursion]
+    async fn matching(
+        &self,
+        ctx: &DalContext,
+        candidates: &BTreeSet<ComponentId>,
+        schema_names: &mut HashMap<SchemaVariantId, String>,
+        index: &mut ComponentQueryIndex,
+    ) -> ComponentQueryResult<BTreeSet<ComponentId>> {
+        Ok(match self {
+            Self::All { queries } => {
+                let mut matching = candidates.clone();
+                for query in queries {
+                    if matching.is_empty() {
+                        break;
+                    }
+                    matching = query.matching(ctx, &matching, schema_names, index).await?;
+                }
+                matching
+            }
+            Self::Any { queries } => {
+                let mut matching = BTreeSet::new();
+                for query in queries {
+                    let remaining = candidates.difference(&matching).copied().collect();
+                    matching.extend(query.matching(ctx, &remaining, schema_names, index).await?);
+                }
+                matching
+            }
+            Self::ConnectedTo { component_id } => {
+                let mut connected = HashSet::new();
+                for connection in Component::incoming_connections_for_id(ctx, *component_id).await?
+                {
+                    connected.insert(connection.from_component_id);
+                }
+                for connection in Component::outgoing_connections_for_id(ctx, *component_id).await?
+                {
+                    connected.insert(connection.to_component_id);
+                }
+                candidates
+                    .iter()
+                    .filter(|id| connected.contains(id))
+                    .copied()
+                    .collect()
+            }
+            Self::InFrame {
+                frame_id,
+                direct_only,
+            } => {
+                let mut matching = BTreeSet::new();
+                for &component_id in candidates {
+                    let mut parent_id = Component::get_parent_by_id(ctx, component_id).await?;
+                    while let Some(id) = parent_id {
+                        if id == *frame_id {
+                            matching.insert(component_id);
+                            break;
+                        }
+                        if *direct_only {
+                            break;
+                        }
+                        parent_id = Component::get_parent_by_id(ctx, id).await?;
+                    }
+                }
+                matching
+            }
+            Self::Not { query } => {
+                let excluded = query.matching(ctx, candidates, schema_names, index).await?;
+                candidates.difference(&excluded).copied().collect()
+            }
+            Self::Prop { path, value } => {
+                let matches = value.matcher()?;
+                let values = index.prop_values(ctx, path).await?;
+                candidates
+                    .iter()
+                    .filter(|id| {
+                        values
+                            .get(id)
+                            .is_some_and(|values| values.iter().any(&matches))
+                    })
+                    .copied()
+                    .collect()
+            }
+            Self::ResourceStatus { status } => {
+                let status = serde_json::to_value(status)?;
+                let values = index.prop_values(ctx, "/root/resource/status").await?;
+                candidates
+                    .iter()
+                    .filter(|id| {
+                        values
+                            .get(id)
+                            .is_some_and(|values| values.contains(&status))
+                    })
+                    .copied()
+                    .collect()
+            }
+            Self::Schema { name } => {
+                let mut matching = BTreeSet::new();
+                for &component_id in candidates {
+                    let schema_variant_id = Component::schema_variant_id(ctx, component_id).await?;
+                    if !schema_names.contains_key(&schema_variant_id) {
+                        let schema =
+                            SchemaVariant::schema_for_schema_variant_id(ctx, schema_variant_id)
+                                .await?;
+                        schema_names.insert(schema_variant_id, schema.name().to_owned());
+                    }
+                    if schema_names.get(&schema_variant_id) == Some(name) {
+                        matching.insert(component_id);
+                    }
+                }
+                matching
+            }
+        })
+    }
+}
+
+impl ValueMatch {
+    fn matcher(&self) -> ComponentQueryResult<Box<dyn Fn(&serde_json::Value) -> bool + Send>> {
+        Ok(match self {
+            Self::Equals(expected) => {
+                let expected = expected.clone();
+                Box::new(move |value| *value == expected)
+            }
+            Self::Prefix(prefix) => {
+                let prefix = prefix.clone();
+                Box::new(move |value| value.as_str().is_some_and(|s| s.starts_with(&prefix)))
+            }
+            Self::Regex(pattern) => {
+                let regex = Regex::new(pattern)
+                    .map_err(|err| ComponentQueryError::InvalidRegex(pattern.clone(), err))?;
+                Box::new(move |value| value.as_str().is_some_and(|s| regex.is_match(s)))
+            }
+        })
+    }
+}
@@ -0,0 +1,242 @@
+//! This module contains [`ComponentUpgradeReport`], a preview of what upgrading a [`Component`]
+//! to another [`SchemaVariant`] does to its values and connections, along with the functions
+//! for upgrading many [`Components`](Component) of a [`Schema`](crate::Schema) at once.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+
+use crate::component::{ComponentError, ComponentResult};
+use crate::{
+    AttributeValue, Component, ComponentId, DalContext, InputSocket, OutputSocket, Prop,
+    SchemaVariant, SchemaVariantId,
+};
+
+/// Whether a connection comes into or goes out of the [`Component`] being upgraded.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionDirection {
+    Incoming,
+    Outgoing,
+}
+
+/// A connection that cannot be restored after an upgrade, because the target
+/// [`SchemaVariant`] has no socket with the same name.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LostConnection {
+    pub other_component_id: ComponentId,
+    pub socket_name: String,
+    pub direction: ConnectionDirection,
+}
+
+/// What upgrading a [`Component`] to another [`SchemaVariant`] would do. Only values that were
+/// set on the component are reported; values coming from the schema variant's defaults are
+/// regenerated by the upgrade either way.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentUpgradeReport {
+    pub component_id: ComponentId,
+    pub component_name: String,
+    pub from_schema_variant_id: SchemaVariantId,
+    pub to_schema_variant_id: SchemaVariantId,
+    /// Paths of values that are copied over to the upgraded component.
+    pub props_preserved: Vec<String>,
+    /// Paths of values whose prop does not exist on the target schema variant.
+    pub props_dropped: Vec<String>,
+    /// Paths of values whose prop changed kind (or secret kind), so the upgraded component
+    /// goes back to the default.
+    pub props_reset_to_default: Vec<String>,
+    pub connections_lost: Vec<LostConnection>,
+}
+
+impl Component {
+    /// Describes what [`Self::upgrade_to_new_variant`] would do to the given component, without
+    /// changing anything.
+    #[instrument(level = "debug", skip(ctx))]
+    pub async fn upgrade_report(
+        ctx: &DalContext,
+        component_id: ComponentId,
+        schema_variant_id: SchemaVariantId,
+    ) -> ComponentResult<ComponentUpgradeReport> {
+        let component = Self::get_by_id(ctx, component_id).await?;
+        let from_schema_variant_id = Self::schema_variant_id(ctx, component_id).await?;
+
+        let mut target_props = HashMap::new();
+        for prop in SchemaVariant::all_props(ctx, schema_variant_id).await? {
+            let path = prop.path(ctx).await?;
+            target_props.insert(path.as_owned_parts(), prop);
+        }
+
+        let mut props_preserved = vec![];
+        let mut props_dropped = vec![];
+        let mut props_reset_to_default = vec![];
+
+        let root_id = Self::root_attribute_value_id(ctx, component_id).await?;
+        let mut value_q = VecDeque::from([root_id]);
+        while let Some(av_id) = value_q.pop_front() {
+            value_q.extend(AttributeValue::get_child_av_ids_in_order(ctx, av_id).await?);
+
+            if AttributeValue::component_prototype_id(ctx, av_id)
+                .await?
+                .is_none()
+            {
+                continue;
+            }
+
+            let prop_id = AttributeValue::is_for(ctx, av_id)
+                .await?
+                .prop_id()
+                .ok_or(ComponentError::ValueSourceForPropValueNotPropId(av_id))?;
+            let prop_path = Prop::path_by_id(ctx, prop_id).await?.as_owned_parts();
+            let path = match AttributeValue::get_path_for_id(ctx, av_id).await? {
+                Some(path) => path,
+                None => prop_path.join("/"),
+            };
+
+            match target_props.get(&prop_path) {
+                None => props_dropped.push(path),
+                Some(target_prop) => {
+                    let prop = Prop::get_by_id(ctx, prop_id).await?;
+                    if target_prop.kind != prop.kind
+                        || target_prop.secret_kind_widget_option()
+                            != prop.secret_kind_widget_option()
+                    {
+                        props_reset_to_default.push(path);
+                    } else {
+                        props_preserved.push(path);
+                    }
+                }
+            }
+        }
+
+        let mut target_input_sockets = HashSet::new();
+        for input_socket_id in
+            InputSocket::list_ids_for_schema_variant(ctx, schema_variant_id).await?
+        {
+            let input_socket = InputSocket::get_by_id(ctx, input_socket_id).await?;
+            target_input_sockets.insert(input_socket.name().to_string());
+        }
+
+        let mut target_output_sockets = HashSet::new();
+        for output_socket_id in
+            OutputSocket::list_ids_for_schema_variant(ctx, schema_variant_id).await?
+        {
+            let output_socket = OutputSocket::get_by_id(ctx, output_socket_id).await?;
+            target_output_sockets.insert(output_socket.name().to_string());
+        }
+
+        let mut connections_lost = vec![];
+        for incoming in component.incoming_connections(ctx).await? {
+            let socket = InputSocket::get_by_id(ctx, incoming.to_input_socket_id).await?;
+            if !target_input_sockets.contains(socket.name()) {
+                connections_lost.push(LostConnection {
+                    other_component_id: incoming.from_component_id,
+                    socket_name: socket.name().to_string(),
+                    direction: ConnectionDirection::Incoming,
+                });
+            }
+        }
+        for outgoing in component.outgoing_connections(ctx).await? {
+            let socket = OutputSocket::get_by_id(ctx, outgoing.from_output_socket_id).await?;
+            if !target_output_sockets.contains(socket.name()) {
+                connections_lost.push(LostConnection {
+                    other_component_id: outgoing.to_component_id,
+                    socket_name: socket.name().to_string(),
+                    direction: ConnectionDirection::Outgoing,
+                });
+            }
+        }
+
+        Ok(ComponentUpgradeReport {
+            component_id,
+            component_name: component.name(ctx).await?,
+            from_schema_variant_id,
+            to_schema_variant_id: schema_variant_id,
+            props_preserved,
+            props_dropped,
+            props_reset_to_default,
+            connections_lost,
+        })
+    }
+
+    /// The components that an upgrade to the given [`SchemaVariant`] applies to: the selected
+    /// components, or every component of the variant's schema if there is no selection.
+    /// Components already on the target variant are left out. Selecting a component of another
+    /// schema is an error.
+    pub async fn list_ids_to_upgrade(
+        ctx: &DalContext,
+        schema_variant_id: SchemaVariantId,
+        selection: Option<Vec<ComponentId>>,
+    ) -> ComponentResult<Vec<ComponentId>> {
+        let schema_id =
+            SchemaVariant::schema_id_for_schema_variant_id(ctx, schema_variant_id).await?;
+
+        let candidates = match selection {
+            Some(component_ids) => {
+                for &component_id in &component_ids {
+                    if Self::schema_for_component_id(ctx, component_id).await?.id() != schema_id {
+                        return Err(ComponentError::UpgradeTargetForDifferentSchema(
+                            component_id,
+                            schema_variant_id,
+                        ));
+                    }
+                }
+                component_ids
+            }
+            None => {
+                let mut component_ids = vec![];
+                for schema_variant in SchemaVariant::list_for_schema(ctx, schema_id).await? {
+                    component_ids
+                        .extend(SchemaVariant::list_component_ids(ctx, schema_variant.id()).await?);
+                }
+                component_ids
+            }
+        };
+
+        let mut seen = HashSet::new();
+        let mut component_ids = vec![];
+        for component_id in candidates {
+            if seen.insert(component_id)
+                && Self::schema_variant_id(ctx, component_id).await? != schema_variant_id
+            {
+                component_ids.push(component_id);
+            }
+        }
+
+        Ok(component_ids)
+    }
+
+    /// Upgrades the components (see [`Self::list_ids_to_upgrade`]) to the given
+    /// [`SchemaVariant`], returning a report for each of them. The reports are all computed
+    /// before anything is upgraded. With `dry_run`, nothing is upgraded at all.
+    ///
+    /// Upgraded components keep their ids, so any one of them can be put back the way it was
+    /// with [`Self::restore_from_base_change_set`].
+    #[instrument(level = "info", skip(ctx))]
+    pub async fn bulk_upgrade(
+        ctx: &DalContext,
+        schema_variant_id: SchemaVariantId,
+        selection: Option<Vec<ComponentId>>,
+        dry_run: bool,
+    ) -> ComponentResult<Vec<ComponentUpgradeReport>> {
+        let component_ids = Self::list_ids_to_upgrade(ctx, schema_variant_id, selection).await?;
+
+        let mut reports = Vec::with_capacity(component_ids.len());
+        for &component_id in &component_ids {
+            reports.push(Self::upgrade_report(ctx, component_id, schema_variant_id).await?);
+        }
+
+        if !dry_run {
+            for component_id in component_ids {
+                Self::get_by_id(ctx, component_id)
+                    .await?
+                    .upgrade_to_new_variant(ctx, schema_variant_id)
+                    .await?;
+            }
+        }
+
+        Ok(reports)
+    }
+}
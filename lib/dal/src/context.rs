@@ -33,14 +33,16 @@ use crate::feature_flags::FeatureFlagService;
 use crate::jetstream_streams::JetstreamStreams;
 use crate::job::definition::AttributeValueBasedJobIdentifier;
 use crate::layer_db_types::ContentTypes;
+use crate::pkg::PkgSignatureVerifier;
 use crate::slow_rt::SlowRuntimeError;
+use crate::workspace_snapshot::chunk::WorkspaceSnapshotChunkEntry;
 use crate::workspace_snapshot::graph::{RebaseBatch, WorkspaceSnapshotGraph};
 use crate::workspace_snapshot::DependentValueRoot;
 use crate::{audit_logging, slow_rt, EncryptedSecret, Workspace, WorkspaceError};
 use crate::{
     change_set::{ChangeSet, ChangeSetId},
     job::{
-        definition::ActionJob,
+        definition::{ActionJob, SecretKeyRotationJob},
         processor::{JobQueueProcessor, JobQueueProcessorError},
         producer::{BlockingJobError, BlockingJobResult, JobProducer},
         queue::JobQueue,
@@ -50,7 +52,13 @@ use crate::{
     WorkspaceSnapshot,
 };
 
-pub type DalLayerDb = LayerDb<ContentTypes, EncryptedSecret, WorkspaceSnapshotGraph, RebaseBatch>;
+pub type DalLayerDb = LayerDb<
+    ContentTypes,
+    EncryptedSecret,
+    WorkspaceSnapshotGraph,
+    RebaseBatch,
+    WorkspaceSnapshotChunkEntry,
+>;
 
 /// A context type which contains handles to common core service dependencies.
 ///
@@ -84,6 +92,8 @@ pub struct ServicesContext {
     feature_flag_service: FeatureFlagService,
     /// Dedicated executor for running CPU-intensive tasks
     compute_executor: DedicatedExecutor,
+    /// Checks the signatures of packages installed from the module index
+    pkg_signature_verifier: PkgSignatureVerifier,
 }
 
 impl ServicesContext {
@@ -118,9 +128,16 @@ impl ServicesContext {
             layer_db,
             feature_flag_service,
             compute_executor,
+            pkg_signature_verifier: PkgSignatureVerifier::default(),
         }
     }
 
+    /// Sets the verifier used to check the signatures of packages installed from the module
+    /// index. Signatures are not checked unless this is set.
+    pub fn set_pkg_signature_verifier(&mut self, pkg_signature_verifier: PkgSignatureVerifier) {
+        self.pkg_signature_verifier = pkg_signature_verifier;
+    }
+
     /// Consumes and returns [`DalContextBuilder`].
     pub fn into_builder(self, blocking: bool) -> DalContextBuilder {
         DalContextBuilder {
@@ -189,6 +206,11 @@ impl ServicesContext {
         &self.compute_executor
     }
 
+    /// Gets a reference to the package signature verifier
+    pub fn pkg_signature_verifier(&self) -> &PkgSignatureVerifier {
+        &self.pkg_signature_verifier
+    }
+
     /// Builds and returns a new [`Connections`].
     pub async fn connections(&self) -> PgPoolResult<Connections> {
         let pg_conn = self.pg_pool.get().await?;
@@ -806,6 +828,14 @@ impl DalContext {
         Ok(())
     }
 
+    pub async fn enqueue_secret_key_rotation(
+        &self,
+        job: Box<SecretKeyRotationJob>,
+    ) -> TransactionsResult<()> {
+        self.txns().await?.job_queue.enqueue_job(job).await;
+        Ok(())
+    }
+
     /// Add the node ids to the workspace snapshot graph and enqueue a dependent values update.
     /// This update will only be run on commit if blocking_commit is used. If commit is used, the
     /// DVU debouncer will run the job. Note that the DVU debouncer might still pick up the job
@@ -37,7 +37,7 @@ use crate::{
     ActionPrototypeId, AttributeValue, AttributeValueId, ChangeSet, ChangeSetError, Component,
     ComponentError, ComponentId, DalContext, EncryptedSecret, Func, FuncBackendKind, FuncError,
     FuncId, KeyPairError, Prop, PropId, SchemaVariant, SchemaVariantError, Secret, SecretError,
-    WsEvent, WsEventError, WsEventResult, WsPayload,
+    SecretId, SecretUsage, WsEvent, WsEventError, WsEventResult, WsPayload,
 };
 use crate::{HistoryEventError, TransactionsError};
 
@@ -216,7 +216,7 @@ impl FuncRunner {
                 ctx.events_tenancy(),
                 ctx.events_actor(),
             )?;
-            let before = FuncRunner::before_funcs(ctx, component_id).await?;
+            let (before, used_secret_ids) = FuncRunner::before_funcs(ctx, component_id).await?;
 
             let component_id = component_id.into();
 
@@ -276,6 +276,8 @@ impl FuncRunner {
                 )
                 .await?;
 
+            SecretUsage::record_for_func_run(ctx, &func_run, &used_secret_ids).await?;
+
             Ok(FuncRunner {
                 func_run,
                 func,
@@ -672,7 +674,7 @@ impl FuncRunner {
             let function_args: CasValue = args.clone().into();
 
             let component_id = AttributeValue::component_id(ctx, attribute_value_id).await?;
-            let before = FuncRunner::before_funcs(ctx, component_id).await?;
+            let (before, used_secret_ids) = FuncRunner::before_funcs(ctx, component_id).await?;
 
             let component_id = component_id.into();
             let attribute_value_id = attribute_value_id.into();
@@ -770,6 +772,8 @@ impl FuncRunner {
                         ctx.events_actor(),
                     )
                     .await?;
+
+                SecretUsage::record_for_func_run(ctx, &func_run, &used_secret_ids).await?;
             }
 
             Ok(FuncRunner {
@@ -867,7 +871,8 @@ impl FuncRunner {
                 ContentHash::new("".as_bytes())
             };
 
-            let before = FuncRunner::before_funcs(ctx, manager_component_id).await?;
+            let (before, used_secret_ids) =
+                FuncRunner::before_funcs(ctx, manager_component_id).await?;
             let manager_component = Component::get_by_id(ctx, manager_component_id).await?;
             let component_name = manager_component.name(ctx).await?;
             let schema_name = manager_component.schema(ctx).await?.name;
@@ -944,6 +949,8 @@ impl FuncRunner {
                 )
                 .await?;
 
+            SecretUsage::record_for_func_run(ctx, &func_run, &used_secret_ids).await?;
+
             Ok(FuncRunner {
                 func_run,
                 func,
@@ -1069,7 +1076,7 @@ impl FuncRunner {
                 ContentHash::new("".as_bytes())
             };
 
-            let before = FuncRunner::before_funcs(ctx, component_id).await?;
+            let (before, used_secret_ids) = FuncRunner::before_funcs(ctx, component_id).await?;
             let component = Component::get_by_id(ctx, component_id).await?;
             let component_name = component.name(ctx).await?;
             let schema_name = component.schema(ctx).await?.name;
@@ -1157,6 +1164,8 @@ impl FuncRunner {
                 )
                 .await?;
 
+            SecretUsage::record_for_func_run(ctx, &func_run, &used_secret_ids).await?;
+
             Ok(FuncRunner {
                 func_run,
                 func,
@@ -1267,18 +1276,19 @@ impl FuncRunner {
     }
 
     /// This _private_ method collects all [`BeforeFunctions`](BeforeFunction) for a given
-    /// [`ComponentId`](Component).
+    /// [`ComponentId`](Component), along with the [`Secrets`](Secret) included in them.
     #[instrument(name = "func_runner.before_funcs", level = "debug", skip_all)]
     async fn before_funcs(
         ctx: &DalContext,
         component_id: ComponentId,
-    ) -> FuncRunnerResult<Vec<BeforeFunction>> {
+    ) -> FuncRunnerResult<(Vec<BeforeFunction>, Vec<SecretId>)> {
         let ordered_before_funcs_with_secret_keys =
             Self::ordered_before_funcs_with_secret_keys(ctx, component_id).await?;
 
         let mut before_functions = Vec::new();
+        let mut used_secret_ids = Vec::new();
 
-        for (key, funcs) in ordered_before_funcs_with_secret_keys {
+        for (secret_id, key, funcs) in ordered_before_funcs_with_secret_keys {
             let encrypted_secret = EncryptedSecret::get_by_key(ctx, key)
                 .await?
                 .ok_or(SecretError::EncryptedSecretNotFound(key))?;
@@ -1301,6 +1311,10 @@ impl FuncRunner {
             // Re-encrypt raw Value for transmission to Veritech
             encrypt_value_tree(&mut arg, ctx.encryption_key())?;
 
+            if !funcs.is_empty() {
+                used_secret_ids.push(secret_id);
+            }
+
             for func in funcs {
                 before_functions.push(BeforeFunction {
                     handler: func
@@ -1314,11 +1328,11 @@ impl FuncRunner {
             }
         }
 
-        Ok(before_functions)
+        Ok((before_functions, used_secret_ids))
     }
 
     /// This _private_ method generates a flattened graph of before [`Funcs`](Func) with corresponding
-    /// [`keys`](EncryptedSecretKey).
+    /// [`Secrets`](Secret) and their [`keys`](EncryptedSecretKey).
     #[instrument(
         name = "func_runner.before_funcs.ordered_before_funcs_with_secret_keys",
         level = "debug",
@@ -1327,7 +1341,7 @@ impl FuncRunner {
     async fn ordered_before_funcs_with_secret_keys(
         ctx: &DalContext,
         component_id: ComponentId,
-    ) -> FuncRunnerResult<Vec<(EncryptedSecretKey, Vec<Func>)>> {
+    ) -> FuncRunnerResult<Vec<(SecretId, EncryptedSecretKey, Vec<Func>)>> {
         let mut ordered_before_funcs_with_secret_keys = Vec::new();
 
         let mut work_queue = VecDeque::new();
@@ -1416,7 +1430,7 @@ impl FuncRunner {
                             work_queue.push_back(source_component_id);
                         }
                     }
-                    ValueSource::Secret(_) => {
+                    ValueSource::Secret(secret_id) => {
                         let auth_funcs =
                             Self::auth_funcs_for_secret_child_prop_id(ctx, secret_child_prop_id)
                                 .await?;
@@ -1428,7 +1442,8 @@ impl FuncRunner {
                         // this check. As it is written here, we only load the other funcs if a secret is populated.
                         if let Some(value) = maybe_value {
                             let key = Secret::key_from_value_in_attribute_value(value)?;
-                            ordered_before_funcs_with_secret_keys.push((key, auth_funcs));
+                            ordered_before_funcs_with_secret_keys
+                                .push((secret_id, key, auth_funcs));
                         }
                     }
                     value_source => {
@@ -20,7 +20,7 @@ use crate::{
     attribute::value::AttributeValueError,
     job::definition::dependent_values_update::DependentValueUpdateError,
     job::producer::BlockingJobError, job::producer::JobProducerError, AccessBuilder,
-    ActionPrototypeId, ComponentError, ComponentId, DalContext, DalContextBuilder,
+    ActionPrototypeId, ComponentError, ComponentId, DalContext, DalContextBuilder, SecretError,
     StandardModelError, TransactionsError, Visibility, WorkspaceSnapshotError, WsEventError,
 };
 
@@ -67,6 +67,8 @@ pub enum JobConsumerError {
     Prop(#[from] PropError),
     #[error("execution of job {0} failed after {1} retry attempts")]
     RetriesFailed(String, u32),
+    #[error("secret error: {0}")]
+    Secret(#[from] SecretError),
     #[error("serde json error: {0}")]
     SerdeJson(#[from] serde_json::Error),
     #[error("standard model error: {0}")]
@@ -1,9 +1,11 @@
 mod action;
 pub mod compute_validation;
 pub mod dependent_values_update;
+mod secret_key_rotation;
 
 pub use action::ActionJob;
 pub use dependent_values_update::DependentValuesUpdate;
+pub use secret_key_rotation::SecretKeyRotationJob;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
 pub enum AttributeValueBasedJobIdentifier {
@@ -17,7 +17,10 @@ use tokio::{
 use ulid::Ulid;
 
 use crate::{
-    attribute::value::{dependent_value_graph::DependentValueGraph, AttributeValueError},
+    attribute::value::{
+        dependent_value_graph::DependentValueGraph, AttributeValueError, DependentValueIndex,
+        DependentValueIndexError,
+    },
     job::{
         consumer::{
             JobCompletionState, JobConsumer, JobConsumerError, JobConsumerMetadata,
@@ -39,6 +42,8 @@ pub enum DependentValueUpdateError {
     AttributeValue(#[from] AttributeValueError),
     #[error("component error: {0}")]
     Component(#[from] ComponentError),
+    #[error("dependent value index error: {0}")]
+    DependentValueIndex(#[from] DependentValueIndexError),
     #[error("prop error: {0}")]
     Prop(#[from] PropError),
     #[error("status update error: {0}")]
@@ -271,6 +276,7 @@ impl DependentValuesUpdate {
                 dependency_graph.remove_value(value_id);
             }
         }
+        DependentValueIndex::record_cause_chains(ctx, &dependency_graph).await?;
         let all_value_ids = dependency_graph.all_value_ids();
         metric!(counter.dvu.values_to_run = all_value_ids.len());
 
@@ -317,11 +323,22 @@ impl DependentValuesUpdate {
                             attribute_value_id,
                         );
 
+                        // The values that led to this one, so that it is possible to tell why
+                        // a value was recomputed
+                        let cause_chain = dependency_graph
+                            .cause_chain(attribute_value_id)
+                            .unwrap_or_default()
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(" -> ");
+
                         update_join_set.spawn(values_from_prototype_function_execution(
                             id,
                             parent_span,
                             ctx.clone(),
                             attribute_value_id,
+                            cause_chain,
                             self.set_value_lock.clone(),
                             status_update,
                         ));
@@ -494,6 +511,7 @@ async fn execution_error_detail(
     skip_all,
     fields(
         si.attribute_value.id = %attribute_value_id,
+        si.dependent_values_update.cause_chain = %cause_chain,
     ),
 )]
 async fn values_from_prototype_function_execution(
@@ -501,6 +519,7 @@ async fn values_from_prototype_function_execution(
     parent_span: Span,
     ctx: DalContext,
     attribute_value_id: AttributeValueId,
+    cause_chain: String,
     set_value_lock: Arc<RwLock<()>>,
     status_update: Option<StatusUpdate>,
 ) -> (Ulid, DependentValueUpdateResult<(FuncRunValue, Func)>) {
@@ -0,0 +1,161 @@
+use std::convert::TryFrom;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+
+use crate::{
+    job::{
+        consumer::{
+            JobCompletionState, JobConsumer, JobConsumerError, JobConsumerMetadata,
+            JobConsumerResult, JobInfo,
+        },
+        producer::{JobProducer, JobProducerResult},
+    },
+    AccessBuilder, DalContext, SecretKeyRotation, SecretKeyRotationId, SecretResult, Visibility,
+};
+
+/// The number of secrets re-encrypted (and committed) per batch. Committing in batches keeps each
+/// rebase small and lets an interrupted rotation resume from the last committed batch.
+const BATCH_SIZE: usize = 50;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SecretKeyRotationJobArgs {
+    rotation_id: SecretKeyRotationId,
+}
+
+impl From<SecretKeyRotationJob> for SecretKeyRotationJobArgs {
+    fn from(value: SecretKeyRotationJob) -> Self {
+        Self {
+            rotation_id: value.rotation_id,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SecretKeyRotationJob {
+    rotation_id: SecretKeyRotationId,
+    access_builder: AccessBuilder,
+    visibility: Visibility,
+    job: Option<JobInfo>,
+}
+
+impl SecretKeyRotationJob {
+    pub fn new(
+        access_builder: AccessBuilder,
+        visibility: Visibility,
+        rotation_id: SecretKeyRotationId,
+    ) -> Box<Self> {
+        Box::new(Self {
+            rotation_id,
+            access_builder,
+            visibility,
+            job: None,
+        })
+    }
+}
+
+impl JobProducer for SecretKeyRotationJob {
+    fn arg(&self) -> JobProducerResult<serde_json::Value> {
+        Ok(serde_json::to_value(SecretKeyRotationJobArgs::from(
+            self.clone(),
+        ))?)
+    }
+}
+
+impl JobConsumerMetadata for SecretKeyRotationJob {
+    fn type_name(&self) -> String {
+        "SecretKeyRotationJob".to_string()
+    }
+
+    fn access_builder(&self) -> AccessBuilder {
+        self.access_builder
+    }
+
+    fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+}
+
+#[async_trait]
+impl JobConsumer for SecretKeyRotationJob {
+    #[instrument(
+        name = "secret_key_rotation_job.run",
+        skip_all,
+        level = "info",
+        fields(
+            si.secret_key_rotation.id = %self.rotation_id,
+        )
+    )]
+    async fn run(&self, ctx: &mut DalContext) -> JobConsumerResult<JobCompletionState> {
+        let mut rotation = SecretKeyRotation::get_by_id(ctx, self.rotation_id).await?;
+
+        if let Err(err) = self.rotate(ctx, &mut rotation).await {
+            error!(si.error.message = ?err, "secret key rotation batch failed");
+
+            // Throw away the partial batch, but keep every batch committed so far. The rotation
+            // can be resumed later and will skip the secrets already rotated.
+            ctx.rollback().await?;
+            ctx.update_visibility_and_snapshot_to_visibility(self.visibility.change_set_id)
+                .await?;
+            rotation.fail(ctx, err.to_string()).await?;
+            ctx.commit_no_rebase().await?;
+        }
+
+        Ok(JobCompletionState::Done)
+    }
+}
+
+impl SecretKeyRotationJob {
+    /// Plans every change set up front, so each one is listed once and the total is known before
+    /// the first batch, then rotates the pending secrets of each change set in batches.
+    async fn rotate(
+        &self,
+        ctx: &mut DalContext,
+        rotation: &mut SecretKeyRotation,
+    ) -> SecretResult<()> {
+        let mut plans = Vec::new();
+        for change_set_id in SecretKeyRotation::change_set_ids(ctx).await? {
+            ctx.update_visibility_and_snapshot_to_visibility(change_set_id)
+                .await?;
+            plans.push((change_set_id, rotation.plan(ctx).await?));
+        }
+
+        let total = plans.iter().map(|(_, plan)| plan.total()).sum::<usize>();
+        let rotated = plans.iter().map(|(_, plan)| plan.rotated).sum::<usize>();
+        rotation
+            .record_progress(ctx, total as i64, rotated as i64)
+            .await?;
+
+        for (change_set_id, plan) in plans {
+            ctx.update_visibility_and_snapshot_to_visibility(change_set_id)
+                .await?;
+            for batch in plan.pending.chunks(BATCH_SIZE) {
+                rotation.rotate_secrets(ctx, batch).await?;
+                ctx.blocking_commit().await?;
+                ctx.update_snapshot_to_visibility().await?;
+            }
+        }
+
+        ctx.update_visibility_and_snapshot_to_visibility(self.visibility.change_set_id)
+            .await?;
+        rotation.complete(ctx).await?;
+        ctx.blocking_commit().await?;
+
+        Ok(())
+    }
+}
+
+impl TryFrom<JobInfo> for SecretKeyRotationJob {
+    type Error = JobConsumerError;
+
+    fn try_from(job: JobInfo) -> Result<Self, Self::Error> {
+        let args = SecretKeyRotationJobArgs::deserialize(&job.arg)?;
+        Ok(Self {
+            rotation_id: args.rotation_id,
+            access_builder: job.access_builder,
+            visibility: job.visibility,
+            job: Some(job),
+        })
+    }
+}
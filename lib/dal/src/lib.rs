@@ -111,8 +111,13 @@ pub use secret::SecretDefinitionView;
 pub use secret::SecretDefinitionViewError;
 pub use secret::SecretError;
 pub use secret::SecretId;
+pub use secret::SecretKeyRotation;
+pub use secret::SecretKeyRotationId;
+pub use secret::SecretKeyRotationPlan;
+pub use secret::SecretKeyRotationStatus;
 pub use secret::SecretResult;
 pub use secret::SecretUpdatedPayload;
+pub use secret::SecretUsage;
 pub use secret::SecretVersion;
 pub use secret::SecretView;
 pub use secret::SecretViewError;
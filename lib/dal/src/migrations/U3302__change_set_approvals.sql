@@ -0,0 +1,15 @@
+CREATE TABLE change_set_approval_policies
+(
+    workspace_id                ident primary key,
+    policies                    jsonb                    NOT NULL DEFAULT '[]'::jsonb,
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP()
+);
+
+CREATE TABLE change_set_approvals
+(
+    change_set_id               ident                    NOT NULL,
+    user_id                     ident                    NOT NULL,
+    roles                       jsonb                    NOT NULL DEFAULT '[]'::jsonb,
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    PRIMARY KEY (change_set_id, user_id)
+);
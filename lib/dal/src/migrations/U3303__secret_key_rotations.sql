@@ -0,0 +1,17 @@
+CREATE TABLE secret_key_rotations
+(
+    id                          ident primary key default ident_create_v1(),
+    workspace_id                ident                    NOT NULL,
+    change_set_id               ident                    NOT NULL,
+    key_pair_pk                 ident                    NOT NULL,
+    status                      text                     NOT NULL,
+    total_secrets               bigint                   NOT NULL DEFAULT 0,
+    rotated_secrets             bigint                   NOT NULL DEFAULT 0,
+    error                       text,
+    created_by_user_id          ident,
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    finished_at                 timestamp with time zone
+);
+
+CREATE INDEX IF NOT EXISTS secret_key_rotations_workspace_id_idx ON secret_key_rotations (workspace_id);
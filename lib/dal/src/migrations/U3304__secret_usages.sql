@@ -0,0 +1,17 @@
+CREATE TABLE secret_usages
+(
+    id                          ident primary key default ident_create_v1(),
+    workspace_id                ident                    NOT NULL,
+    change_set_id               ident                    NOT NULL,
+    secret_id                   ident                    NOT NULL,
+    func_run_id                 ident                    NOT NULL,
+    function_name               text                     NOT NULL,
+    function_kind               text                     NOT NULL,
+    component_id                ident,
+    action_id                   ident,
+    user_id                     ident,
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP()
+);
+
+CREATE INDEX IF NOT EXISTS secret_usages_workspace_id_secret_id_created_at_idx
+    ON secret_usages (workspace_id, secret_id, created_at DESC);
@@ -0,0 +1,3 @@
+ALTER TABLE cached_modules
+    ADD COLUMN signature            text,
+    ADD COLUMN signature_public_key text;
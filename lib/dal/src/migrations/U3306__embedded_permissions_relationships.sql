@@ -0,0 +1,9 @@
+CREATE TABLE embedded_permissions_relationships
+(
+    object_type  text NOT NULL,
+    object_id    text NOT NULL,
+    relation     text NOT NULL,
+    subject_type text NOT NULL,
+    subject_id   text NOT NULL,
+    PRIMARY KEY (object_type, object_id, relation, subject_type, subject_id)
+);
@@ -0,0 +1 @@
+ALTER TABLE change_set_approvals ADD COLUMN workspace_snapshot_address text;
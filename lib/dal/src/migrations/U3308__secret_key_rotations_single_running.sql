@@ -0,0 +1,14 @@
+-- Only the newest running rotation of a workspace is kept running; any duplicates are failed so
+-- that they can be inspected.
+UPDATE secret_key_rotations
+SET status     = 'Failed',
+    error      = 'superseded by a concurrently started rotation',
+    updated_at = CLOCK_TIMESTAMP()
+WHERE status = 'Running'
+  AND id NOT IN (SELECT DISTINCT ON (workspace_id) id
+                 FROM secret_key_rotations
+                 WHERE status = 'Running'
+                 ORDER BY workspace_id, created_at DESC);
+
+CREATE UNIQUE INDEX IF NOT EXISTS secret_key_rotations_running_workspace_id_idx
+    ON secret_key_rotations (workspace_id) WHERE status = 'Running';
@@ -0,0 +1,29 @@
+CREATE TABLE dependent_value_indexes
+(
+    change_set_id               ident primary key,
+    workspace_snapshot_address  text                     NOT NULL,
+    version                     bigint                   NOT NULL DEFAULT 1,
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP()
+);
+
+CREATE INDEX IF NOT EXISTS dependent_value_indexes_workspace_snapshot_address_idx
+    ON dependent_value_indexes (workspace_snapshot_address);
+
+CREATE TABLE dependent_value_index_steps
+(
+    change_set_id               ident                    NOT NULL,
+    attribute_value_id          ident                    NOT NULL,
+    component_ids               jsonb                    NOT NULL DEFAULT '[]'::jsonb,
+    value_component_ids         jsonb                    NOT NULL DEFAULT '[]'::jsonb,
+    step                        jsonb                    NOT NULL,
+    PRIMARY KEY (change_set_id, attribute_value_id)
+);
+
+CREATE TABLE dependent_value_causes
+(
+    change_set_id               ident                    NOT NULL,
+    attribute_value_id          ident                    NOT NULL,
+    cause_chain                 jsonb                    NOT NULL DEFAULT '[]'::jsonb,
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    PRIMARY KEY (change_set_id, attribute_value_id)
+);
@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use si_pkg::{FuncSpecBackendKind, FuncSpecBackendResponseType, SiPkgError, SpecError};
+use si_pkg::{
+    FuncSpecBackendKind, FuncSpecBackendResponseType, SiPkgError, SiPkgSignatureError, SpecError,
+};
 use std::collections::HashMap;
 use thiserror::Error;
 use url::ParseError;
@@ -28,10 +30,19 @@ use crate::{AttributePrototypeId, FuncId, HistoryEventError, PropId, PropKind};
 
 use crate::module::ModuleError;
 use crate::socket::connection_annotation::ConnectionAnnotationError;
+pub use dependency::{
+    ensure_dependencies_installed, import_pkgs_with_dependencies, installed_module_satisfies,
+    pkgs_in_dependency_order,
+};
 pub use import::{import_pkg, import_pkg_from_pkg, ImportOptions};
+pub use signature::{
+    module_signature, PkgSignatureConfig, PkgSignaturePolicy, PkgSignatureVerifier,
+};
 
+mod dependency;
 pub mod export;
 pub mod import;
+mod signature;
 
 #[remain::sorted]
 #[derive(Debug, Error)]
@@ -56,6 +67,8 @@ pub enum PkgError {
     ConnectionAnnotation(#[from] ConnectionAnnotationError),
     #[error("expected data on an SiPkg node, but none found: {0}")]
     DataNotFound(String),
+    #[error("packages depend on each other: {0}")]
+    DependencyCycle(String),
     #[error("func error: {0}")]
     Func(#[from] FuncError),
     #[error("func argument error: {0}")]
@@ -72,6 +85,8 @@ pub enum PkgError {
     ManagementPrototype(#[from] ManagementPrototypeError),
     #[error("Missing Func {1} for AttributePrototype {0}")]
     MissingAttributePrototypeFunc(AttributePrototypeId, FuncId),
+    #[error("{0} depends on {1} ({2}), which is not installed")]
+    MissingDependency(String, String, String),
     #[error("Func {0} missing from exported funcs")]
     MissingExportedFunc(FuncId),
     #[error("Cannot find FuncArgument {0} for Func {1}")]
@@ -110,6 +125,8 @@ pub enum PkgError {
     SchemaVariant(#[from] SchemaVariantError),
     #[error("json serialization error: {0}")]
     SerdeJson(#[from] serde_json::Error),
+    #[error("package signature error: {0}")]
+    Signature(#[from] SiPkgSignatureError),
     #[error("taking output socket as input for a prop is unsupported for name ({0}) and socket name ({1})")]
     TakingOutputSocketAsInputForPropUnsupported(String, String),
     #[error("transactions error: {0}")]
@@ -0,0 +1,236 @@
+//! Installing packages together with the modules they depend on.
+
+use si_pkg::{DependencySpec, SiPkg};
+use telemetry::prelude::*;
+
+use crate::module::Module;
+use crate::{DalContext, SchemaVariantId};
+
+use super::{import_pkg_from_pkg, ImportOptions, PkgError, PkgResult};
+
+/// Returns an error naming the first dependency of the package that no installed module
+/// satisfies.
+pub async fn ensure_dependencies_installed(ctx: &DalContext, pkg: &SiPkg) -> PkgResult<()> {
+    let dependencies = pkg.dependencies()?;
+    if dependencies.is_empty() {
+        return Ok(());
+    }
+
+    let installed = Module::list_installed(ctx).await?;
+    for dependency in dependencies {
+        let spec = DependencySpec::try_from(dependency)?;
+        if !installed
+            .iter()
+            .any(|module| spec.matches(module.name(), module.version()))
+        {
+            return Err(PkgError::MissingDependency(
+                pkg.metadata()?.name().to_owned(),
+                spec.name,
+                spec.version_constraint,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether one installed module satisfies every one of the given dependencies, which all name
+/// the same module. Such a dependency does not need to be installed, even if a newer version of
+/// it is available.
+pub fn installed_module_satisfies(installed: &[Module], specs: &[DependencySpec]) -> bool {
+    !specs.is_empty()
+        && installed.iter().any(|module| {
+            specs
+                .iter()
+                .all(|spec| spec.matches(module.name(), module.version()))
+        })
+}
+
+/// Orders the packages so that each one comes after the packages it depends on, keeping the
+/// given order otherwise. A dependency can be satisfied by one of the installed modules or by
+/// another package in the list; anything else is an error, as is a cycle.
+pub fn pkgs_in_dependency_order<T>(
+    pkgs: Vec<(SiPkg, T)>,
+    installed: &[Module],
+) -> PkgResult<Vec<(SiPkg, T)>> {
+    let mut names = Vec::with_capacity(pkgs.len());
+    let mut versions = Vec::with_capacity(pkgs.len());
+    let mut dependencies = Vec::with_capacity(pkgs.len());
+    for (pkg, _) in &pkgs {
+        let metadata = pkg.metadata()?;
+        names.push(metadata.name().to_owned());
+        versions.push(metadata.version().to_owned());
+        dependencies.push(
+            pkg.dependencies()?
+                .into_iter()
+                .map(DependencySpec::try_from)
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+    }
+
+    // For each package, the packages in the list it has to be installed after
+    let mut edges: Vec<Vec<usize>> = vec![vec![]; pkgs.len()];
+    for (idx, pkg_dependencies) in dependencies.iter().enumerate() {
+        for spec in pkg_dependencies {
+            if installed
+                .iter()
+                .any(|module| spec.matches(module.name(), module.version()))
+            {
+                continue;
+            }
+
+            let dependency_idx = (0..pkgs.len())
+                .find(|other| *other != idx && spec.matches(&names[*other], &versions[*other]))
+                .ok_or_else(|| {
+                    PkgError::MissingDependency(
+                        names[idx].to_owned(),
+                        spec.name.to_owned(),
+                        spec.version_constraint.to_owned(),
+                    )
+                })?;
+            edges[idx].push(dependency_idx);
+        }
+    }
+
+    let mut order = Vec::with_capacity(pkgs.len());
+    let mut visited = vec![false; pkgs.len()];
+    let mut path = Vec::new();
+    for idx in 0..pkgs.len() {
+        visit(idx, &edges, &names, &mut visited, &mut path, &mut order)?;
+    }
+
+    let mut pkgs: Vec<Option<(SiPkg, T)>> = pkgs.into_iter().map(Some).collect();
+    Ok(order
+        .into_iter()
+        .filter_map(|idx| pkgs[idx].take())
+        .collect())
+}
+
+fn visit(
+    idx: usize,
+    edges: &[Vec<usize>],
+    names: &[String],
+    visited: &mut [bool],
+    path: &mut Vec<usize>,
+    order: &mut Vec<usize>,
+) -> PkgResult<()> {
+    if let Some(position) = path.iter().position(|on_path| *on_path == idx) {
+        let mut cycle: Vec<&str> = path[position..]
+            .iter()
+            .map(|on_path| names[*on_path].as_str())
+            .collect();
+        cycle.push(&names[idx]);
+        return Err(PkgError::DependencyCycle(cycle.join(" -> ")));
+    }
+    if visited[idx] {
+        return Ok(());
+    }
+
+    path.push(idx);
+    for dependency_idx in &edges[idx] {
+        visit(*dependency_idx, edges, names, visited, path, order)?;
+    }
+    path.pop();
+
+    visited[idx] = true;
+    order.push(idx);
+
+    Ok(())
+}
+
+/// Installs the packages in dependency order (see [`pkgs_in_dependency_order`]). Packages that
+/// are already installed are skipped, so a dependency shared by several packages is installed
+/// once. Returns the schema variants installed from each package, in install order.
+pub async fn import_pkgs_with_dependencies(
+    ctx: &DalContext,
+    pkgs: Vec<(SiPkg, ImportOptions)>,
+) -> PkgResult<Vec<(SiPkg, Vec<SchemaVariantId>)>> {
+    let installed = Module::list_installed(ctx).await?;
+    let ordered = pkgs_in_dependency_order(pkgs, &installed)?;
+
+    let mut results = Vec::with_capacity(ordered.len());
+    for (pkg, options) in ordered {
+        let root_hash = pkg.hash()?.to_string();
+        if Module::find_by_root_hash(ctx, &root_hash).await?.is_some() {
+            debug!(%root_hash, "skipping package that is already installed");
+            continue;
+        }
+
+        let (_, schema_variant_ids, _) = import_pkg_from_pkg(ctx, &pkg, Some(options)).await?;
+        results.push((pkg, schema_variant_ids));
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use si_pkg::PkgSpec;
+
+    use super::*;
+
+    fn pkg(name: &str, dependencies: &[(&str, &str)]) -> (SiPkg, ()) {
+        let mut builder = PkgSpec::builder();
+        builder
+            .name(name)
+            .version("1")
+            .created_by("sally@systeminit.com");
+        for (dependency_name, version_constraint) in dependencies {
+            builder.dependency(
+                DependencySpec::builder()
+                    .name(*dependency_name)
+                    .version_constraint(*version_constraint)
+                    .build()
+                    .expect("should build dependency"),
+            );
+        }
+
+        let spec = builder.build().expect("should build pkg spec");
+        (SiPkg::load_from_spec(spec).expect("should load pkg"), ())
+    }
+
+    fn names(pkgs: Vec<(SiPkg, ())>) -> Vec<String> {
+        pkgs.into_iter()
+            .map(|(pkg, _)| {
+                pkg.metadata()
+                    .expect("should get metadata")
+                    .name()
+                    .to_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn orders_dependencies_first() {
+        let ordered = pkgs_in_dependency_order(
+            vec![
+                pkg("ec2", &[("region", ">=1"), ("credential", "*")]),
+                pkg("s3", &[]),
+                pkg("region", &[("credential", "*")]),
+                pkg("credential", &[]),
+            ],
+            &[],
+        )
+        .expect("should order pkgs");
+
+        assert_eq!(vec!["credential", "region", "ec2", "s3"], names(ordered));
+    }
+
+    #[test]
+    fn finds_cycles_and_missing_dependencies() {
+        let result = pkgs_in_dependency_order(
+            vec![
+                pkg("a", &[("b", "*")]),
+                pkg("b", &[("c", "*")]),
+                pkg("c", &[("a", "*")]),
+            ],
+            &[],
+        );
+        assert!(
+            matches!(&result, Err(PkgError::DependencyCycle(cycle)) if cycle == "a -> b -> c -> a")
+        );
+
+        let result = pkgs_in_dependency_order(vec![pkg("a", &[("b", ">1")]), pkg("b", &[])], &[]);
+        assert!(matches!(result, Err(PkgError::MissingDependency(..))));
+    }
+}
@@ -34,7 +34,7 @@ use crate::{
 };
 use crate::{AttributePrototype, AttributePrototypeId};
 
-use super::{PkgError, PkgResult};
+use super::{ensure_dependencies_installed, PkgError, PkgResult};
 
 #[derive(Clone, Debug)]
 pub enum Thing {
@@ -73,6 +73,10 @@ pub struct ImportOptions {
     /// A list of "past hashes" for this module, used to find the existing
     /// schema if a schema_id is not provided
     pub past_module_hashes: Option<Vec<String>>,
+    /// If set to `true`, the package signature is checked against the configured trust store
+    /// before anything is imported. Packages built locally (builtins, authored assets) leave this
+    /// unset.
+    pub verify_signature: bool,
 }
 
 const SPECIAL_CASE_FUNCS: [&str; 2] = ["si:resourcePayloadToValue", "si:normalizeToArray"];
@@ -233,14 +237,26 @@ pub async fn import_pkg_from_pkg(
     Vec<SchemaVariantId>,
     Option<Vec<bool /*ImportSkips*/>>,
 )> {
-    let root_hash = pkg.hash()?.to_string();
-
     let options = options.unwrap_or_default();
 
+    if options.verify_signature {
+        ctx.services_context()
+            .pkg_signature_verifier()
+            .verify(pkg)?;
+    }
+
+    let root_hash = pkg.hash()?.to_string();
+
     if Module::find_by_root_hash(ctx, &root_hash).await?.is_some() {
         return Err(PkgError::PackageAlreadyInstalled(root_hash));
     }
 
+    // Unrecorded installs leave no module behind to satisfy a dependency, so there is nothing to
+    // check them against
+    if !options.no_record {
+        ensure_dependencies_installed(ctx, pkg).await?;
+    }
+
     let metadata = pkg.metadata()?;
 
     let installed_module: Option<Module> = if options.no_record {
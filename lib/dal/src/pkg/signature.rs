@@ -0,0 +1,102 @@
+//! Verification of package signatures against a configured trust store before import.
+
+use module_index_client::ModuleDetailsResponse;
+use serde::{Deserialize, Serialize};
+use si_pkg::{SiPkg, SiPkgSignature, SiPkgTrustStore};
+use strum::{Display, EnumString};
+use telemetry::prelude::*;
+
+use super::PkgResult;
+
+/// What to do when a package being installed is unsigned, or is not signed by a trusted key.
+#[remain::sorted]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Display, EnumString, Eq, PartialEq, Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum PkgSignaturePolicy {
+    /// Signatures are not checked.
+    #[default]
+    Off,
+    /// Packages that fail verification are not installed.
+    Require,
+    /// Packages that fail verification are installed, but a warning is logged.
+    Warn,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PkgSignatureConfig {
+    #[serde(default)]
+    pub policy: PkgSignaturePolicy,
+    /// The base64 encoded ed25519 public keys whose signatures are trusted.
+    #[serde(default)]
+    pub trusted_public_keys: Vec<String>,
+}
+
+/// Checks package signatures according to a [`PkgSignaturePolicy`].
+#[derive(Clone, Debug, Default)]
+pub struct PkgSignatureVerifier {
+    policy: PkgSignaturePolicy,
+    trust_store: SiPkgTrustStore,
+}
+
+impl PkgSignatureVerifier {
+    pub fn new(policy: PkgSignaturePolicy, trust_store: SiPkgTrustStore) -> Self {
+        Self {
+            policy,
+            trust_store,
+        }
+    }
+
+    pub fn from_config(config: PkgSignatureConfig) -> PkgResult<Self> {
+        Ok(Self::new(
+            config.policy,
+            SiPkgTrustStore::from_public_keys(config.trusted_public_keys)?,
+        ))
+    }
+
+    pub fn policy(&self) -> PkgSignaturePolicy {
+        self.policy
+    }
+
+    /// Verifies the signature attached to the package, returning an error only if the policy
+    /// requires a trusted signature.
+    pub fn verify(&self, pkg: &SiPkg) -> PkgResult<()> {
+        if self.policy == PkgSignaturePolicy::Off {
+            return Ok(());
+        }
+
+        let metadata = pkg.metadata()?;
+        match self
+            .trust_store
+            .verify(metadata.hash(), metadata.signature())
+        {
+            Ok(()) => Ok(()),
+            Err(err) => match self.policy {
+                PkgSignaturePolicy::Require => Err(err.into()),
+                PkgSignaturePolicy::Off | PkgSignaturePolicy::Warn => {
+                    warn!(
+                        si.error.message = ?err,
+                        pkg.name = metadata.name(),
+                        "installing package that failed signature verification",
+                    );
+                    Ok(())
+                }
+            },
+        }
+    }
+}
+
+/// Gets the signature that the module index stored alongside a module, if it was signed.
+pub fn module_signature(module_details: &ModuleDetailsResponse) -> Option<SiPkgSignature> {
+    module_details
+        .signature
+        .clone()
+        .zip(module_details.signature_public_key.clone())
+        .map(|(signature, public_key)| SiPkgSignature {
+            public_key,
+            signature,
+        })
+}
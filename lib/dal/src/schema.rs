@@ -547,6 +547,7 @@ impl Schema {
                     &si_pkg,
                     Some(ImportOptions {
                         schema_id: Some(schema_id.into()),
+                        verify_signature: true,
                         ..Default::default()
                     }),
                 )
@@ -38,6 +38,9 @@ use crate::{
     SchemaVariant, SchemaVariantError, SchemaVariantId,
 };
 
+pub mod asset_code;
+pub mod json_schema;
+
 #[allow(missing_docs)]
 #[remain::sorted]
 #[derive(Error, Debug)]
@@ -70,6 +73,12 @@ pub enum VariantAuthoringError {
     HistoryEvent(#[from] HistoryEventError),
     #[error("input socket error: {0}")]
     InputSocket(#[from] InputSocketError),
+    #[error("json schema at {0} is not an object with properties")]
+    JsonSchemaNotAnObject(String),
+    #[error("json schema not found at {0}")]
+    JsonSchemaNotFound(String),
+    #[error("json schema reference not found: {0}")]
+    JsonSchemaRefNotFound(String),
     #[error("layer db error: {0}")]
     LayerDb(#[from] LayerDbError),
     #[error("trying to modify locked variant: {0}")]
@@ -221,6 +230,38 @@ impl VariantAuthoringClient {
         .await
     }
 
+    /// Creates a [`SchemaVariant`] whose asset func is generated from a JSON Schema document, or
+    /// from the schema at `root_pointer` within it (e.g. `/components/schemas/Pet` in an OpenAPI
+    /// document). See [`json_schema`] for how the schema maps to props.
+    #[instrument(
+        name = "variant.authoring.create_variant_from_json_schema",
+        level = "info",
+        skip_all
+    )]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_schema_and_variant_from_json_schema(
+        ctx: &DalContext,
+        name: impl Into<String>,
+        description: Option<String>,
+        link: Option<String>,
+        category: impl Into<String>,
+        color: impl Into<String>,
+        document: &serde_json::Value,
+        root_pointer: Option<&str>,
+    ) -> VariantAuthoringResult<SchemaVariant> {
+        let code = json_schema::asset_code_from_json_schema(document, root_pointer)?;
+        Self::create_schema_and_variant_from_code(
+            ctx,
+            name,
+            description,
+            link,
+            category,
+            color,
+            code,
+        )
+        .await
+    }
+
     #[instrument(
         name = "variant.authoring.new_schema_with_cloned_variant",
         level = "info",
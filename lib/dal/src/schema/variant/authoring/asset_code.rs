@@ -0,0 +1,183 @@
+//! Building blocks for generating the code of an asset function from a schema that describes
+//! its props elsewhere, such as a JSON Schema (see [`json_schema`](super::json_schema)) or a
+//! CloudFormation resource schema.
+
+use std::fmt::Display;
+
+use serde_json::Value;
+
+use crate::PropKind;
+
+/// The indentation of each level of the generated code.
+pub const INDENT: &str = "  ";
+
+/// A prop of a generated asset, rendered as a `PropBuilder` chain with [`Self::render`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetCodeProp {
+    pub name: String,
+    pub kind: PropKind,
+    pub documentation: Option<String>,
+    /// A Joi expression, e.g. `Joi.string().required()`.
+    pub validation_format: Option<String>,
+    pub default_value: Option<Value>,
+    pub children: Vec<AssetCodeProp>,
+    pub entry: Option<Box<AssetCodeProp>>,
+}
+
+impl AssetCodeProp {
+    pub fn new(name: impl Into<String>, kind: PropKind, documentation: Option<String>) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            documentation,
+            validation_format: None,
+            default_value: None,
+            children: vec![],
+            entry: None,
+        }
+    }
+
+    /// Renders the prop as an argument to a function call, `depth` levels deep.
+    pub fn render(&self, depth: usize) -> String {
+        let indent = INDENT.repeat(depth);
+        let mut code = format!("{indent}new PropBuilder()\n");
+        let mut call = |method: String| code.push_str(&format!("{indent}{INDENT}.{method}\n"));
+
+        call(format!("setName({})", js_string(&self.name)));
+        call(format!("setKind({})", js_string(&self.kind.to_string())));
+        if let Some(documentation) = &self.documentation {
+            call(format!("setDocumentation({})", js_string(documentation)));
+        }
+        if let Some(validation_format) = &self.validation_format {
+            call(format!("setValidationFormat({validation_format})"));
+        }
+        if let Some(default_value) = &self.default_value {
+            call(format!("setDefaultValue({default_value})"));
+        }
+        for child in &self.children {
+            call(format!(
+                "addChild(\n{}{indent}{INDENT})",
+                child.render(depth + 2)
+            ));
+        }
+        if let Some(entry) = &self.entry {
+            call(format!(
+                "setEntry(\n{}{indent}{INDENT})",
+                entry.render(depth + 2)
+            ));
+        }
+        // The comma belongs to the argument list the prop is passed in
+        call("build(),".to_owned());
+
+        code
+    }
+}
+
+/// Builds the Joi validation format of a prop. A format that only checks the type is left out,
+/// since the prop kind already does, unless the prop is required or the format was started with
+/// [`Self::always`].
+#[derive(Debug, Clone)]
+pub struct ValidationFormat {
+    format: String,
+    constrained: bool,
+}
+
+impl ValidationFormat {
+    pub fn new(base: &str) -> Self {
+        Self {
+            format: base.to_owned(),
+            constrained: false,
+        }
+    }
+
+    /// A format that is kept even without further constraints.
+    pub fn always(base: &str) -> Self {
+        Self {
+            format: base.to_owned(),
+            constrained: true,
+        }
+    }
+
+    /// The prop kind and validation format of a number. Integer props can't hold fractions, so
+    /// numbers are kept as strings that have to parse as numbers.
+    pub fn number(integer: bool) -> (PropKind, Self) {
+        match integer {
+            true => (PropKind::Integer, Self::new("Joi.number().integer()")),
+            false => (PropKind::String, Self::always("Joi.number()")),
+        }
+    }
+
+    /// Appends a constraint, e.g. `.min(1)`.
+    pub fn constrain(&mut self, method: &str, argument: impl Display) -> &mut Self {
+        self.format.push_str(&format!(".{method}({argument})"));
+        self.constrained = true;
+        self
+    }
+
+    /// Appends a constraint without arguments, e.g. `.email()`.
+    pub fn constrain_format(&mut self, method: &str) -> &mut Self {
+        self.format.push_str(method);
+        self.constrained = true;
+        self
+    }
+
+    /// Restricts the value to the given JSON values.
+    pub fn valid(&mut self, values: &[Value]) -> &mut Self {
+        let values = values
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.constrain("valid", values)
+    }
+
+    /// Requires the value to match the regular expression.
+    pub fn pattern(&mut self, pattern: &str) -> &mut Self {
+        self.constrain("pattern", format!("new RegExp({})", js_string(pattern)))
+    }
+
+    pub fn finish(mut self, required: bool) -> Option<String> {
+        if required {
+            self.format.push_str(".required()");
+        }
+        (self.constrained || required).then_some(self.format)
+    }
+}
+
+/// A JavaScript string literal. JSON string literals are valid JavaScript.
+pub fn js_string(value: &str) -> String {
+    Value::String(value.to_owned()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn validation_formats() {
+        assert_eq!(None, ValidationFormat::new("Joi.string()").finish(false));
+        assert_eq!(
+            Some("Joi.boolean().required()".to_owned()),
+            ValidationFormat::new("Joi.boolean()").finish(true)
+        );
+
+        let mut format = ValidationFormat::new("Joi.string()");
+        format
+            .valid(&[json!("a"), json!("b")])
+            .pattern("^\"[a-z]+$")
+            .constrain("min", 1);
+        assert_eq!(
+            Some(r#"Joi.string().valid("a", "b").pattern(new RegExp("^\"[a-z]+$")).min(1)"#),
+            format.finish(false).as_deref()
+        );
+
+        let (kind, format) = ValidationFormat::number(false);
+        assert_eq!(PropKind::String, kind);
+        assert_eq!(Some("Joi.number()"), format.finish(false).as_deref());
+        let (kind, format) = ValidationFormat::number(true);
+        assert_eq!(PropKind::Integer, kind);
+        assert_eq!(None, format.finish(false));
+    }
+}
@@ -0,0 +1,598 @@
+//! Generates the code of an asset function from a JSON Schema, or from one of the component
+//! schemas of an OpenAPI document, so that APIs which already describe their objects don't
+//! have to be described again by hand.
+//!
+//! Each property of the root schema becomes a domain prop. Nested objects become object props,
+//! arrays become array props, and objects with an `additionalProperties` schema become map props.
+//! `enum`, `format`, `pattern`, bounds and `required` become validation formats, `description`
+//! becomes the prop's documentation and scalar `default`s become default values.
+
+use serde_json::{Map, Value};
+
+use crate::PropKind;
+
+use super::asset_code::{AssetCodeProp, ValidationFormat, INDENT};
+use super::{VariantAuthoringError, VariantAuthoringResult};
+
+/// Generates asset function code from the schema at `root_pointer` (a JSON pointer, e.g.
+/// `/components/schemas/Pet`) in the document, or from the whole document if there is no
+/// pointer. `$ref`s are resolved against the whole document.
+pub fn asset_code_from_json_schema(
+    document: &Value,
+    root_pointer: Option<&str>,
+) -> VariantAuthoringResult<String> {
+    let props = Generator::new(document).root_props(root_pointer)?;
+
+    let mut code = String::new();
+    code.push_str("function main() {\n");
+    code.push_str(&format!("{INDENT}const asset = new AssetBuilder();\n"));
+    for prop in &props {
+        code.push('\n');
+        code.push_str(&format!(
+            "{INDENT}asset.addProp(\n{}{INDENT});\n",
+            prop.render(2)
+        ));
+    }
+    code.push_str(&format!("\n{INDENT}return asset.build();\n}}\n"));
+
+    Ok(code)
+}
+
+struct Generator<'a> {
+    document: &'a Value,
+    /// The `$ref`s being expanded, outermost first. Props can't be recursive, so a schema that
+    /// contains itself is cut off where it repeats.
+    refs_in_progress: Vec<String>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(document: &'a Value) -> Self {
+        Self {
+            document,
+            refs_in_progress: vec![],
+        }
+    }
+
+    fn root_props(
+        &mut self,
+        root_pointer: Option<&str>,
+    ) -> VariantAuthoringResult<Vec<AssetCodeProp>> {
+        let pointer = root_pointer.unwrap_or_default();
+        let root = self
+            .document
+            .pointer(pointer)
+            .ok_or_else(|| VariantAuthoringError::JsonSchemaNotFound(pointer.to_owned()))?;
+        let root = self.flatten(root)?;
+
+        let Some(properties) = root.get("properties").and_then(Value::as_object) else {
+            return Err(VariantAuthoringError::JsonSchemaNotAnObject(
+                pointer.to_owned(),
+            ));
+        };
+        let required = required_names(&root);
+
+        // The root can be referred to from within itself too
+        self.refs_in_progress.push(format!("#{pointer}"));
+        let mut props = Vec::with_capacity(properties.len());
+        for (name, schema) in properties {
+            props.push(self.prop(name, schema, required.contains(&name.as_str()))?);
+        }
+        Ok(props)
+    }
+
+    fn prop(
+        &mut self,
+        name: &str,
+        schema: &Value,
+        required: bool,
+    ) -> VariantAuthoringResult<AssetCodeProp> {
+        let empty = Map::new();
+        let schema_object = schema.as_object().unwrap_or(&empty);
+
+        if let Some(reference) = schema_object.get("$ref").and_then(Value::as_str) {
+            if self.refs_in_progress.iter().any(|r| r == reference) {
+                return Ok(string_prop(name, schema_object, required));
+            }
+
+            let mut target = self.resolve(reference)?.clone();
+            // The description next to a reference is more specific than the one it points to
+            if let (Some(description), Some(target)) =
+                (schema_object.get("description"), target.as_object_mut())
+            {
+                target.insert("description".to_owned(), description.clone());
+            }
+
+            self.refs_in_progress.push(reference.to_owned());
+            let prop = self.prop(name, &target, required);
+            self.refs_in_progress.pop();
+            return prop;
+        }
+
+        let schema = self.flatten(schema)?;
+        let schema = schema.as_object().unwrap_or(&empty);
+
+        let mut prop = match schema_type(schema) {
+            Some("string") => {
+                let mut prop = asset_prop(name, PropKind::String, schema);
+                prop.validation_format = string_validation_format(schema, required);
+                prop
+            }
+            Some(kind @ ("integer" | "number")) => {
+                let (prop_kind, format) = ValidationFormat::number(kind == "integer");
+                let mut prop = asset_prop(name, prop_kind, schema);
+                prop.validation_format = number_validation_format(format, schema, required);
+                prop
+            }
+            Some("boolean") => {
+                let mut prop = asset_prop(name, PropKind::Boolean, schema);
+                prop.validation_format = ValidationFormat::new("Joi.boolean()").finish(required);
+                prop
+            }
+            Some("array") => {
+                let mut prop = asset_prop(name, PropKind::Array, schema);
+                let items = schema.get("items").cloned().unwrap_or(Value::Null);
+                prop.entry = Some(Box::new(self.prop(
+                    &format!("{name}Item"),
+                    &items,
+                    false,
+                )?));
+                let mut format = ValidationFormat::new("Joi.array()");
+                for (keyword, method) in [("minItems", "min"), ("maxItems", "max")] {
+                    if let Some(bound) = schema.get(keyword).and_then(Value::as_u64) {
+                        format.constrain(method, bound);
+                    }
+                }
+                prop.validation_format = format.finish(required);
+                prop
+            }
+            Some("object") => match schema.get("properties").and_then(Value::as_object) {
+                Some(properties) if !properties.is_empty() => {
+                    let mut prop = asset_prop(name, PropKind::Object, schema);
+                    let child_required = required_names(schema);
+                    for (child_name, child) in properties {
+                        let child_prop = self.prop(
+                            child_name,
+                            child,
+                            child_required.contains(&child_name.as_str()),
+                        )?;
+                        prop.children.push(child_prop);
+                    }
+                    prop.validation_format = ValidationFormat::new("Joi.object()").finish(required);
+                    prop
+                }
+                _ => match schema.get("additionalProperties") {
+                    Some(entry @ Value::Object(_)) => {
+                        let mut prop = asset_prop(name, PropKind::Map, schema);
+                        prop.entry =
+                            Some(Box::new(self.prop(&format!("{name}Item"), entry, false)?));
+                        prop.validation_format =
+                            ValidationFormat::new("Joi.object()").finish(required);
+                        prop
+                    }
+                    // Free form objects are kept as JSON strings
+                    _ => string_prop(name, schema, required),
+                },
+            },
+            // No type, or one of several types
+            _ => string_prop(name, schema, required),
+        };
+
+        if prop.kind != PropKind::Object && prop.kind != PropKind::Array {
+            prop.default_value = schema
+                .get("default")
+                .filter(|value| !value.is_object() && !value.is_array() && !value.is_null())
+                .cloned();
+        }
+
+        Ok(prop)
+    }
+
+    fn resolve(&self, reference: &str) -> VariantAuthoringResult<&'a Value> {
+        reference
+            .strip_prefix('#')
+            .and_then(|pointer| self.document.pointer(pointer))
+            .ok_or_else(|| VariantAuthoringError::JsonSchemaRefNotFound(reference.to_owned()))
+    }
+
+    /// Merges the schemas of an `allOf` into the schema (references included), so the
+    /// properties of all of them end up in one object.
+    fn flatten(&self, schema: &Value) -> VariantAuthoringResult<Value> {
+        self.flatten_all_of(schema, &mut vec![])
+    }
+
+    /// `flattening` holds the `$ref`s being merged in already. A schema that is (through other
+    /// schemas) one of its own `allOf`s has nothing more to add, so it is not merged in again.
+    fn flatten_all_of(
+        &self,
+        schema: &Value,
+        flattening: &mut Vec<String>,
+    ) -> VariantAuthoringResult<Value> {
+        let Some(all_of) = schema.get("allOf").and_then(Value::as_array) else {
+            return Ok(schema.clone());
+        };
+
+        let mut merged = schema.as_object().cloned().unwrap_or_default();
+        merged.remove("allOf");
+        for sub_schema in all_of {
+            let sub_schema = match sub_schema.get("$ref").and_then(Value::as_str) {
+                Some(reference) if flattening.iter().any(|r| r == reference) => continue,
+                Some(reference) => {
+                    flattening.push(reference.to_owned());
+                    let sub_schema = self.flatten_all_of(self.resolve(reference)?, flattening);
+                    flattening.pop();
+                    sub_schema?
+                }
+                None => self.flatten_all_of(sub_schema, flattening)?,
+            };
+            let Some(sub_schema) = sub_schema.as_object() else {
+                continue;
+            };
+
+            for (key, value) in sub_schema {
+                match (key.as_str(), merged.get_mut(key)) {
+                    ("properties", Some(Value::Object(properties))) => {
+                        if let Value::Object(more) = value {
+                            properties.extend(more.clone());
+                        }
+                    }
+                    ("required", Some(Value::Array(required))) => {
+                        if let Value::Array(more) = value {
+                            required.extend(more.iter().cloned());
+                        }
+                    }
+                    (_, Some(_)) => {}
+                    (_, None) => {
+                        merged.insert(key.to_owned(), value.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(Value::Object(merged))
+    }
+}
+
+/// The type of the schema, if it has exactly one besides `null`. Schemas without a type get
+/// the one implied by their keywords.
+fn schema_type(schema: &Map<String, Value>) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(kind)) => Some(kind.as_str()),
+        Some(Value::Array(kinds)) => {
+            let mut kinds = kinds
+                .iter()
+                .filter_map(Value::as_str)
+                .filter(|kind| *kind != "null");
+            match (kinds.next(), kinds.next()) {
+                (Some(kind), None) => Some(kind),
+                _ => None,
+            }
+        }
+        _ => {
+            if schema.contains_key("properties") || schema.contains_key("additionalProperties") {
+                Some("object")
+            } else if schema.contains_key("items") {
+                Some("array")
+            } else {
+                let values = schema.get("enum").and_then(Value::as_array)?;
+                if values.iter().all(Value::is_string) {
+                    Some("string")
+                } else if values.iter().all(|value| value.is_i64() || value.is_u64()) {
+                    Some("integer")
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+fn required_names(schema: &Map<String, Value>) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn asset_prop(name: &str, kind: PropKind, schema: &Map<String, Value>) -> AssetCodeProp {
+    let documentation = schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned);
+    AssetCodeProp::new(name, kind, documentation)
+}
+
+fn string_prop(name: &str, schema: &Map<String, Value>, required: bool) -> AssetCodeProp {
+    let mut prop = asset_prop(name, PropKind::String, schema);
+    prop.validation_format = ValidationFormat::new("Joi.string()").finish(required);
+    prop
+}
+
+fn string_validation_format(schema: &Map<String, Value>, required: bool) -> Option<String> {
+    let mut format = ValidationFormat::new("Joi.string()");
+
+    let format_method = match schema.get("format").and_then(Value::as_str) {
+        Some("email") => Some(".email()"),
+        Some("uri") | Some("url") => Some(".uri()"),
+        Some("uuid") => Some(".guid()"),
+        Some("date-time") | Some("date") => Some(".isoDate()"),
+        Some("hostname") => Some(".hostname()"),
+        Some("ipv4") => Some(r#".ip({ version: ["ipv4"] })"#),
+        Some("ipv6") => Some(r#".ip({ version: ["ipv6"] })"#),
+        _ => None,
+    };
+    if let Some(format_method) = format_method {
+        format.constrain_format(format_method);
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        format.valid(values);
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        format.pattern(pattern);
+    }
+    for (keyword, method) in [("minLength", "min"), ("maxLength", "max")] {
+        if let Some(bound) = schema.get(keyword).and_then(Value::as_u64) {
+            format.constrain(method, bound);
+        }
+    }
+
+    format.finish(required)
+}
+
+fn number_validation_format(
+    mut format: ValidationFormat,
+    schema: &Map<String, Value>,
+    required: bool,
+) -> Option<String> {
+    // Draft 4 marks the bounds themselves as exclusive, later drafts give exclusive bounds
+    // their own keywords
+    let exclusive = |keyword: &str| schema.get(keyword).and_then(Value::as_bool) == Some(true);
+    let minimum = match exclusive("exclusiveMinimum") {
+        true => "greater",
+        false => "min",
+    };
+    let maximum = match exclusive("exclusiveMaximum") {
+        true => "less",
+        false => "max",
+    };
+    let bounds = [
+        ("minimum", minimum),
+        ("maximum", maximum),
+        ("exclusiveMinimum", "greater"),
+        ("exclusiveMaximum", "less"),
+    ];
+    for (keyword, method) in bounds {
+        if let Some(bound) = schema.get(keyword).filter(|bound| bound.is_number()) {
+            format.constrain(method, bound);
+        }
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        format.valid(values);
+    }
+
+    format.finish(required)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn find<'a>(props: &'a [AssetCodeProp], name: &str) -> &'a AssetCodeProp {
+        props
+            .iter()
+            .find(|prop| prop.name == name)
+            .expect("prop should be generated")
+    }
+
+    #[test]
+    fn recursive_all_of_is_merged_once() {
+        let document = json!({
+            "components": {
+                "schemas": {
+                    "Node": {
+                        "allOf": [
+                            { "$ref": "#/components/schemas/Node" },
+                            { "$ref": "#/components/schemas/Named" }
+                        ],
+                        "properties": { "value": { "type": "string" } }
+                    },
+                    "Named": {
+                        "allOf": [{ "$ref": "#/components/schemas/Node" }],
+                        "properties": { "name": { "type": "string" } }
+                    }
+                }
+            }
+        });
+
+        let props = Generator::new(&document)
+            .root_props(Some("/components/schemas/Node"))
+            .expect("should generate props");
+
+        let names: Vec<&str> = props.iter().map(|prop| prop.name.as_str()).collect();
+        assert_eq!(vec!["value", "name"], names);
+    }
+
+    #[test]
+    fn props_from_openapi_component_schema() {
+        let document = json!({
+            "openapi": "3.0.0",
+            "components": {
+                "schemas": {
+                    "Base": {
+                        "type": "object",
+                        "properties": {
+                            "id": { "type": "string", "format": "uuid", "description": "Unique id" }
+                        },
+                        "required": ["id"]
+                    },
+                    "Owner": {
+                        "type": "object",
+                        "properties": {
+                            "email": { "type": "string", "format": "email" },
+                            "pets": {
+                                "type": "array",
+                                "items": { "$ref": "#/components/schemas/Pet" }
+                            }
+                        }
+                    },
+                    "Pet": {
+                        "allOf": [{ "$ref": "#/components/schemas/Base" }],
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string", "minLength": 1 },
+                            "status": { "enum": ["available", "sold"], "default": "available" },
+                            "age": { "type": "integer", "minimum": 0, "exclusiveMaximum": 100 },
+                            "weight": { "type": ["number", "null"] },
+                            "vaccinated": { "type": "boolean" },
+                            "labels": {
+                                "type": "object",
+                                "additionalProperties": { "type": "string" }
+                            },
+                            "extra": { "type": "object" },
+                            "owner": {
+                                "$ref": "#/components/schemas/Owner",
+                                "description": "Who owns it"
+                            }
+                        },
+                        "required": ["name"]
+                    }
+                }
+            }
+        });
+
+        let props = Generator::new(&document)
+            .root_props(Some("/components/schemas/Pet"))
+            .expect("should generate props");
+
+        let names: Vec<&str> = props.iter().map(|prop| prop.name.as_str()).collect();
+        assert_eq!(
+            vec![
+                "name",
+                "status",
+                "age",
+                "weight",
+                "vaccinated",
+                "labels",
+                "extra",
+                "owner",
+                "id"
+            ],
+            names
+        );
+
+        let id = find(&props, "id");
+        assert_eq!(Some("Unique id"), id.documentation.as_deref());
+        assert_eq!(
+            Some("Joi.string().guid().required()"),
+            id.validation_format.as_deref()
+        );
+        assert_eq!(
+            Some("Joi.string().min(1).required()"),
+            find(&props, "name").validation_format.as_deref()
+        );
+
+        let status = find(&props, "status");
+        assert_eq!(PropKind::String, status.kind);
+        assert_eq!(
+            Some(r#"Joi.string().valid("available", "sold")"#),
+            status.validation_format.as_deref()
+        );
+        assert_eq!(Some(json!("available")), status.default_value);
+
+        assert_eq!(
+            Some("Joi.number().integer().min(0).less(100)"),
+            find(&props, "age").validation_format.as_deref()
+        );
+        let weight = find(&props, "weight");
+        assert_eq!(PropKind::String, weight.kind);
+        assert_eq!(Some("Joi.number()"), weight.validation_format.as_deref());
+        assert_eq!(PropKind::Boolean, find(&props, "vaccinated").kind);
+
+        let labels = find(&props, "labels");
+        assert_eq!(PropKind::Map, labels.kind);
+        assert_eq!(
+            Some(PropKind::String),
+            labels.entry.as_ref().map(|entry| entry.kind)
+        );
+        assert_eq!(PropKind::String, find(&props, "extra").kind);
+
+        let owner = find(&props, "owner");
+        assert_eq!(PropKind::Object, owner.kind);
+        assert_eq!(Some("Who owns it"), owner.documentation.as_deref());
+        assert_eq!(
+            Some("Joi.string().email()"),
+            find(&owner.children, "email").validation_format.as_deref()
+        );
+
+        // Pet refers to itself through owner.pets, which is where it gets cut off
+        let pets = find(&owner.children, "pets");
+        assert_eq!(PropKind::Array, pets.kind);
+        assert_eq!(
+            Some(PropKind::String),
+            pets.entry.as_ref().map(|entry| entry.kind)
+        );
+    }
+
+    #[test]
+    fn renders_asset_code() {
+        let code = asset_code_from_json_schema(
+            &json!({
+                "type": "object",
+                "properties": {
+                    "tags": {
+                        "type": "array",
+                        "items": { "type": "string", "description": "A \"tag\"" }
+                    }
+                }
+            }),
+            None,
+        )
+        .expect("should generate code");
+
+        assert_eq!(
+            r#"function main() {
+  const asset = new AssetBuilder();
+
+  asset.addProp(
+    new PropBuilder()
+      .setName("tags")
+      .setKind("array")
+      .setEntry(
+        new PropBuilder()
+          .setName("tagsItem")
+          .setKind("string")
+          .setDocumentation("A \"tag\"")
+          .build(),
+      )
+      .build(),
+  );
+
+  return asset.build();
+}
+"#,
+            code
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            asset_code_from_json_schema(&json!({ "type": "string" }), None),
+            Err(VariantAuthoringError::JsonSchemaNotAnObject(_))
+        ));
+        assert!(matches!(
+            asset_code_from_json_schema(&json!({}), Some("/definitions/Missing")),
+            Err(VariantAuthoringError::JsonSchemaNotFound(_))
+        ));
+        assert!(matches!(
+            asset_code_from_json_schema(
+                &json!({ "properties": { "a": { "$ref": "#/definitions/Missing" } } }),
+                None
+            ),
+            Err(VariantAuthoringError::JsonSchemaRefNotFound(reference))
+                if reference == "#/definitions/Missing"
+        ));
+    }
+}
@@ -65,13 +65,15 @@ use crate::{
     id, implement_add_edge_to, AttributePrototype, AttributeValue, AttributeValueId,
     ChangeSetError, ComponentError, ComponentId, DalContext, Func, FuncError, FuncId, HelperError,
     HistoryActor, HistoryEventError, KeyPair, KeyPairError, Prop, SchemaVariant,
-    SchemaVariantError, StandardModelError, Timestamp, TransactionsError, UserPk,
+    SchemaVariantError, StandardModelError, Timestamp, TransactionsError, UserPk, WorkspacePk,
 };
 use si_events::encrypted_secret::EncryptedSecretKeyParseError;
 
 mod algorithm;
 mod definition_view;
 mod event;
+mod rotation;
+mod usage;
 mod view;
 
 pub use algorithm::SecretAlgorithm;
@@ -81,6 +83,11 @@ pub use definition_view::SecretDefinitionViewError;
 pub use event::SecretCreatedPayload;
 pub use event::SecretDeletedPayload;
 pub use event::SecretUpdatedPayload;
+pub use rotation::SecretKeyRotation;
+pub use rotation::SecretKeyRotationId;
+pub use rotation::SecretKeyRotationPlan;
+pub use rotation::SecretKeyRotationStatus;
+pub use usage::SecretUsage;
 pub use view::SecretView;
 pub use view::SecretViewError;
 pub use view::SecretViewResult;
@@ -133,12 +140,18 @@ pub enum SecretError {
     Prop(#[from] PropError),
     #[error("schema variant error: {0}")]
     SchemaVariant(#[from] SchemaVariantError),
+    #[error("secret key rotation already running for workspace: {0}")]
+    SecretKeyRotationAlreadyRunning(WorkspacePk),
+    #[error("secret key rotation not found: {0}")]
+    SecretKeyRotationNotFound(SecretKeyRotationId),
     #[error("secret not found: {0}")]
     SecretNotFound(SecretId),
     #[error("serde json error: {0}")]
     SerdeJson(#[from] serde_json::Error),
     #[error("standard model error: {0}")]
     StandardModelError(#[from] StandardModelError),
+    #[error("strum parse error: {0}")]
+    StrumParse(#[from] strum::ParseError),
     #[error("symmetric crypto error: {0}")]
     SymmetricCrypto(#[from] SymmetricCryptoError),
     #[error("transactions error: {0}")]
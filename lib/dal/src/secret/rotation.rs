@@ -0,0 +1,416 @@
+//! This module contains [`SecretKeyRotation`], which re-encrypts every [`EncryptedSecret`] in a
+//! workspace under a new [`KeyPair`] and the active symmetric key.
+//!
+//! A rotation is created with [`SecretKeyRotation::begin`] and driven to completion by
+//! [`SecretKeyRotationJob`](crate::job::definition::SecretKeyRotationJob). Secrets can be created
+//! in a change set before they ever reach HEAD, so the job visits every change set returned by
+//! [`SecretKeyRotation::change_set_ids`], lists its pending secrets once with
+//! [`SecretKeyRotation::plan`] and re-encrypts them in batches with
+//! [`SecretKeyRotation::rotate_secrets`]. Whether a [`Secret`] has been rotated is derived from
+//! its [`EncryptedSecret`] rather than stored, so an interrupted or failed rotation can be resumed
+//! by beginning it again.
+//!
+//! Rotating the symmetric key is a deployment concern: configure the new key as the active key
+//! of the [`SymmetricCryptoService`](si_crypto::SymmetricCryptoService), keep the previous one
+//! in its extra keys, and run a rotation. Previous keys are still needed to decrypt secrets
+//! referenced by change sets opened before the rotation.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use si_crypto::SymmetricCryptoService;
+use si_data_pg::PgRow;
+use si_events::audit_log::AuditLogKind;
+use si_hash::Hash;
+use sodiumoxide::crypto::sealedbox;
+use strum::{AsRefStr, Display, EnumString};
+use telemetry::prelude::*;
+
+use super::{
+    EncryptedSecret, Secret, SecretAlgorithm, SecretError, SecretId, SecretResult, SecretVersion,
+};
+use crate::key_pair::KeyPairPk;
+use crate::{id, ChangeSet, ChangeSetId, DalContext, HistoryActor, KeyPair, UserPk, WorkspacePk};
+
+id!(SecretKeyRotationId);
+
+/// The state of a [`SecretKeyRotation`].
+#[remain::sorted]
+#[derive(
+    AsRefStr, Clone, Copy, Debug, Deserialize, Display, EnumString, Eq, PartialEq, Serialize,
+)]
+pub enum SecretKeyRotationStatus {
+    /// Every [`Secret`] was re-encrypted
+    Completed,
+    /// The rotation stopped on an error and may be resumed
+    Failed,
+    /// The rotation is in progress
+    Running,
+}
+
+/// The progress of re-encrypting all [`EncryptedSecrets`](EncryptedSecret) of a workspace under
+/// a new [`KeyPair`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretKeyRotation {
+    /// The identifier of the rotation
+    pub id: SecretKeyRotationId,
+    /// The workspace whose secrets are rotated
+    pub workspace_id: WorkspacePk,
+    /// The change set the rotation was started from
+    pub change_set_id: ChangeSetId,
+    /// The [`KeyPair`] secrets are re-encrypted for
+    pub key_pair_pk: KeyPairPk,
+    /// The state of the rotation
+    pub status: SecretKeyRotationStatus,
+    /// The number of secrets found across all change sets when the rotation was last planned
+    pub total_secrets: i64,
+    /// The number of secrets encrypted under the new keys as of the last batch
+    pub rotated_secrets: i64,
+    /// The error that stopped the rotation, if it failed
+    pub error: Option<String>,
+    /// The user that began the rotation
+    pub created_by_user_id: Option<UserPk>,
+    /// When the rotation began
+    pub created_at: DateTime<Utc>,
+    /// When the progress was last updated
+    pub updated_at: DateTime<Utc>,
+    /// When the rotation completed
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<PgRow> for SecretKeyRotation {
+    type Error = SecretError;
+
+    fn try_from(value: PgRow) -> Result<Self, Self::Error> {
+        let status: String = value.try_get("status")?;
+        Ok(Self {
+            id: value.try_get("id")?,
+            workspace_id: value.try_get("workspace_id")?,
+            change_set_id: value.try_get("change_set_id")?,
+            key_pair_pk: value.try_get("key_pair_pk")?,
+            status: SecretKeyRotationStatus::from_str(&status)?,
+            total_secrets: value.try_get("total_secrets")?,
+            rotated_secrets: value.try_get("rotated_secrets")?,
+            error: value.try_get("error")?,
+            created_by_user_id: value.try_get("created_by_user_id")?,
+            created_at: value.try_get("created_at")?,
+            updated_at: value.try_get("updated_at")?,
+            finished_at: value.try_get("finished_at")?,
+        })
+    }
+}
+
+/// The [`Secrets`](Secret) of a single change set, split by whether a [`SecretKeyRotation`] has
+/// already re-encrypted them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SecretKeyRotationPlan {
+    /// The number of secrets already encrypted under the rotation's keys
+    pub rotated: usize,
+    /// The secrets that still have to be re-encrypted
+    pub pending: Vec<SecretId>,
+}
+
+impl SecretKeyRotationPlan {
+    /// The number of secrets in the change set.
+    pub fn total(&self) -> usize {
+        self.rotated + self.pending.len()
+    }
+}
+
+impl SecretKeyRotation {
+    /// Begins rotating the secrets of the workspace. If a previous rotation failed, it is resumed
+    /// instead of generating another [`KeyPair`].
+    ///
+    /// Starting is a compare-and-set: a failed rotation only moves back to running if it is still
+    /// failed, and a new rotation is only inserted if no other rotation of the workspace is
+    /// unfinished. At most one rotation per workspace can be running, so beginning while one is
+    /// running returns [`SecretError::SecretKeyRotationAlreadyRunning`] rather than starting a
+    /// duplicate.
+    pub async fn begin(ctx: &DalContext) -> SecretResult<Self> {
+        let workspace_pk = ctx.workspace_pk()?;
+
+        let maybe_resumed = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(
+                "UPDATE secret_key_rotations
+                SET status = $2, error = NULL, updated_at = CLOCK_TIMESTAMP()
+                WHERE id = (
+                    SELECT id FROM secret_key_rotations
+                    WHERE workspace_id = $1 AND status = $3
+                    ORDER BY created_at DESC LIMIT 1
+                ) AND status = $3
+                RETURNING *",
+                &[
+                    &workspace_pk,
+                    &SecretKeyRotationStatus::Running.to_string(),
+                    &SecretKeyRotationStatus::Failed.to_string(),
+                ],
+            )
+            .await?;
+        if let Some(row) = maybe_resumed {
+            return Self::try_from(row);
+        }
+
+        let user_pk = match ctx.history_actor() {
+            HistoryActor::SystemInit => None,
+            HistoryActor::User(user_pk) => Some(*user_pk),
+        };
+        let key_pair = KeyPair::new(ctx, "rotated").await?;
+
+        // The partial unique index on running rotations makes a concurrent begin wait for this
+        // one and then insert nothing.
+        let maybe_row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(
+                "INSERT INTO secret_key_rotations
+                (workspace_id, change_set_id, key_pair_pk, status, created_by_user_id)
+                SELECT $1, $2, $3, $4, $5
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM secret_key_rotations WHERE workspace_id = $1 AND status != $6
+                )
+                ON CONFLICT (workspace_id) WHERE status = 'Running' DO NOTHING
+                RETURNING *",
+                &[
+                    &workspace_pk,
+                    &ctx.change_set_id(),
+                    &key_pair.pk(),
+                    &SecretKeyRotationStatus::Running.to_string(),
+                    &user_pk,
+                    &SecretKeyRotationStatus::Completed.to_string(),
+                ],
+            )
+            .await?;
+
+        match maybe_row {
+            Some(row) => Self::try_from(row),
+            None => Err(SecretError::SecretKeyRotationAlreadyRunning(workspace_pk)),
+        }
+    }
+
+    /// Gets the [`SecretKeyRotation`] for the given [`id`](SecretKeyRotationId) within the
+    /// current workspace.
+    pub async fn get_by_id(ctx: &DalContext, id: SecretKeyRotationId) -> SecretResult<Self> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(
+                "SELECT * FROM secret_key_rotations WHERE id = $1 AND workspace_id = $2",
+                &[&id, &ctx.workspace_pk()?],
+            )
+            .await?
+            .ok_or(SecretError::SecretKeyRotationNotFound(id))?;
+
+        Self::try_from(row)
+    }
+
+    /// Lists all [`SecretKeyRotations`](SecretKeyRotation) for the current workspace, newest
+    /// first.
+    pub async fn list_for_workspace(ctx: &DalContext) -> SecretResult<Vec<Self>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(
+                "SELECT * FROM secret_key_rotations WHERE workspace_id = $1 ORDER BY created_at DESC",
+                &[&ctx.workspace_pk()?],
+            )
+            .await?;
+
+        rows.into_iter().map(Self::try_from).collect()
+    }
+
+    /// Lists the change sets whose secrets are rotated: the workspace's default change set first,
+    /// followed by every other change set that is still open.
+    pub async fn change_set_ids(ctx: &DalContext) -> SecretResult<Vec<ChangeSetId>> {
+        let default_change_set_id = ctx.get_workspace_default_change_set_id().await?;
+
+        let mut change_set_ids = vec![default_change_set_id];
+        for change_set in ChangeSet::list_active(ctx).await? {
+            if change_set.id != default_change_set_id {
+                change_set_ids.push(change_set.id);
+            }
+        }
+
+        Ok(change_set_ids)
+    }
+
+    /// Lists the [`Secrets`](Secret) of the current change set once and splits them by whether
+    /// they are already encrypted under the rotation's [`KeyPair`] and the active symmetric key.
+    pub async fn plan(&self, ctx: &DalContext) -> SecretResult<SecretKeyRotationPlan> {
+        let active_key_hash = *ctx.symmetric_crypto_service().active_key_hash();
+
+        let mut plan = SecretKeyRotationPlan::default();
+        for secret in Secret::list(ctx).await? {
+            let key = secret.encrypted_secret_key();
+            let encrypted_secret = EncryptedSecret::get_by_key(ctx, key)
+                .await?
+                .ok_or(SecretError::EncryptedSecretNotFound(key))?;
+
+            if encrypted_secret.is_encrypted_with(self.key_pair_pk, &active_key_hash) {
+                plan.rotated += 1;
+            } else {
+                plan.pending.push(secret.id());
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Re-encrypts the given [`Secrets`](Secret) of the current change set and adds them to the
+    /// rotated count. Secrets that were rotated since the plan was made (for example, by a
+    /// change to HEAD being replayed onto the change set) are counted without re-encrypting
+    /// them again.
+    pub async fn rotate_secrets(
+        &mut self,
+        ctx: &DalContext,
+        secret_ids: &[SecretId],
+    ) -> SecretResult<()> {
+        let key_pair = KeyPair::get_by_pk(ctx, self.key_pair_pk).await?;
+        let active_key_hash = *ctx.symmetric_crypto_service().active_key_hash();
+
+        for &secret_id in secret_ids {
+            let secret = Secret::get_by_id_or_error(ctx, secret_id).await?;
+            let key = secret.encrypted_secret_key();
+            let encrypted_secret = EncryptedSecret::get_by_key(ctx, key)
+                .await?
+                .ok_or(SecretError::EncryptedSecretNotFound(key))?;
+            if encrypted_secret.is_encrypted_with(self.key_pair_pk, &active_key_hash) {
+                continue;
+            }
+
+            let (version, algorithm) = (encrypted_secret.version, encrypted_secret.algorithm);
+            let previous_key_pair = encrypted_secret.key_pair(ctx).await?;
+            let crypted = encrypted_secret.reseal(
+                &previous_key_pair,
+                &key_pair,
+                ctx.symmetric_crypto_service(),
+            )?;
+
+            secret
+                .update_encrypted_contents(ctx, &crypted, self.key_pair_pk, version, algorithm)
+                .await?;
+            debug!(%secret_id, change_set_id = %ctx.change_set_id(), rotation_id = %self.id, "rotated secret");
+        }
+
+        self.record_progress(
+            ctx,
+            self.total_secrets,
+            self.rotated_secrets + secret_ids.len() as i64,
+        )
+        .await
+    }
+
+    /// Records how many secrets exist across all change sets and how many of them have been
+    /// rotated.
+    pub async fn record_progress(
+        &mut self,
+        ctx: &DalContext,
+        total_secrets: i64,
+        rotated_secrets: i64,
+    ) -> SecretResult<()> {
+        ctx.txns()
+            .await?
+            .pg()
+            .query_none(
+                "UPDATE secret_key_rotations
+                SET total_secrets = $2, rotated_secrets = $3, updated_at = CLOCK_TIMESTAMP()
+                WHERE id = $1",
+                &[&self.id, &total_secrets, &rotated_secrets],
+            )
+            .await?;
+
+        self.total_secrets = total_secrets;
+        self.rotated_secrets = rotated_secrets;
+
+        Ok(())
+    }
+
+    /// Marks the rotation as completed and writes an audit log.
+    pub async fn complete(&mut self, ctx: &DalContext) -> SecretResult<()> {
+        self.set_status(ctx, SecretKeyRotationStatus::Completed, None)
+            .await?;
+        ctx.write_audit_log(
+            AuditLogKind::RotateSecretKeys {
+                rotation_id: self.id.to_string(),
+                rotated_secret_count: self.rotated_secrets as u64,
+            },
+            "Secrets".to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks the rotation as failed with the given error, so that it can be inspected and later
+    /// resumed with [`Self::begin`].
+    pub async fn fail(&mut self, ctx: &DalContext, error: impl Into<String>) -> SecretResult<()> {
+        self.set_status(ctx, SecretKeyRotationStatus::Failed, Some(error.into()))
+            .await
+    }
+
+    async fn set_status(
+        &mut self,
+        ctx: &DalContext,
+        status: SecretKeyRotationStatus,
+        error: Option<String>,
+    ) -> SecretResult<()> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "UPDATE secret_key_rotations
+                SET status = $2,
+                error = $3,
+                updated_at = CLOCK_TIMESTAMP(),
+                finished_at = CASE WHEN $2 = $4 THEN CLOCK_TIMESTAMP() ELSE NULL END
+                WHERE id = $1 RETURNING *",
+                &[
+                    &self.id,
+                    &status.to_string(),
+                    &error,
+                    &SecretKeyRotationStatus::Completed.to_string(),
+                ],
+            )
+            .await?;
+
+        *self = Self::try_from(row)?;
+
+        Ok(())
+    }
+}
+
+impl EncryptedSecret {
+    fn is_encrypted_with(&self, key_pair_pk: KeyPairPk, symmetric_key_hash: &Hash) -> bool {
+        self.key_pair_pk == key_pair_pk && &self.key_hash == symmetric_key_hash
+    }
+
+    /// Opens the sealed contents with the [`KeyPair`] they were sealed for and seals them again
+    /// for another, without deserializing the message.
+    fn reseal(
+        &self,
+        from: &KeyPair,
+        to: &KeyPair,
+        symmetric_crypto_service: &SymmetricCryptoService,
+    ) -> SecretResult<Vec<u8>> {
+        // Explicitly match on (version, algorithm) tuple to ensure that any new
+        // versions/algorithms will trigger a compilation failure
+        match (self.version, self.algorithm) {
+            (SecretVersion::V1, SecretAlgorithm::Sealedbox) => {
+                let sealed =
+                    symmetric_crypto_service.decrypt(&self.crypted, &self.nonce, &self.key_hash)?;
+                let message = sealedbox::open(&sealed, from.public_key(), from.secret_key())
+                    .map_err(|_| SecretError::DecryptionFailed)?;
+
+                Ok(sealedbox::seal(&message, to.public_key()))
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! This module contains [`SecretUsage`], a ledger entry recording that a [`Secret`] was sent to
+//! a function execution.
+//!
+//! Whereas [`Secret::find_connected_components`] answers which components are wired to a
+//! [`Secret`], the ledger answers which function runs actually received it, and when. An entry
+//! is recorded for every [`Secret`] whose before functions are included in a [`FuncRun`].
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use si_data_pg::PgRow;
+use si_events::{Actor, FuncRun, FuncRunId};
+
+use super::{SecretError, SecretId, SecretResult};
+use crate::action::ActionId;
+use crate::{ChangeSetId, ComponentId, DalContext, UserPk};
+
+/// Records that a [`Secret`](super::Secret) was included in the before functions of a
+/// [`FuncRun`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretUsage {
+    /// The secret that was used
+    pub secret_id: SecretId,
+    /// The function run the secret was sent to
+    pub func_run_id: FuncRunId,
+    /// The name of the function that was run
+    pub function_name: String,
+    /// The kind of the function that was run
+    pub function_kind: String,
+    /// The component the function ran for, if any
+    pub component_id: Option<ComponentId>,
+    /// The action that ran the function, if it was an action
+    pub action_id: Option<ActionId>,
+    /// The user that caused the function to run, if it was not the system
+    pub user_id: Option<UserPk>,
+    /// The change set the function ran in
+    pub change_set_id: ChangeSetId,
+    /// When the function run was created
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<PgRow> for SecretUsage {
+    type Error = SecretError;
+
+    fn try_from(value: PgRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            secret_id: value.try_get("secret_id")?,
+            func_run_id: value.try_get("func_run_id")?,
+            function_name: value.try_get("function_name")?,
+            function_kind: value.try_get("function_kind")?,
+            component_id: value.try_get("component_id")?,
+            action_id: value.try_get("action_id")?,
+            user_id: value.try_get("user_id")?,
+            change_set_id: value.try_get("change_set_id")?,
+            created_at: value.try_get("created_at")?,
+        })
+    }
+}
+
+impl SecretUsage {
+    /// Records that the given [`Secrets`](super::Secret) were included in the before functions of
+    /// the [`FuncRun`].
+    pub async fn record_for_func_run(
+        ctx: &DalContext,
+        func_run: &FuncRun,
+        secret_ids: &[SecretId],
+    ) -> SecretResult<()> {
+        let user_id = match func_run.actor() {
+            Actor::System => None,
+            Actor::User(user_pk) => Some(*user_pk),
+        };
+        let function_kind = func_run.function_kind().to_string();
+
+        for secret_id in secret_ids {
+            ctx.txns()
+                .await?
+                .pg()
+                .execute(
+                    "INSERT INTO secret_usages
+                    (workspace_id, change_set_id, secret_id, func_run_id, function_name,
+                     function_kind, component_id, action_id, user_id, created_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+                    &[
+                        &func_run.workspace_pk(),
+                        &func_run.change_set_id(),
+                        secret_id,
+                        &func_run.id(),
+                        &func_run.function_name(),
+                        &function_kind,
+                        &func_run.component_id(),
+                        &func_run.action_id(),
+                        &user_id,
+                        &func_run.created_at(),
+                    ],
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Lists the usages of a [`Secret`](super::Secret) within the current workspace, newest
+    /// first, optionally only those since the given time.
+    pub async fn list_for_secret(
+        ctx: &DalContext,
+        secret_id: SecretId,
+        since: Option<DateTime<Utc>>,
+    ) -> SecretResult<Vec<Self>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(
+                "SELECT * FROM secret_usages
+                WHERE workspace_id = $1 AND secret_id = $2
+                    AND ($3::timestamptz IS NULL OR created_at >= $3)
+                ORDER BY created_at DESC",
+                &[&ctx.workspace_pk()?, &secret_id, &since],
+            )
+            .await?;
+
+        rows.into_iter().map(Self::try_from).collect()
+    }
+}
@@ -0,0 +1,261 @@
+//! Create workspaces from a [`WorkspaceTemplate`]: the components of a change set, exported with
+//! [`PortableComponents`] and stored with a set of parameters.
+//!
+//! Component names and property values in a template can contain `{{ name }}` placeholders for its
+//! parameters. When the template is instantiated, a string that is nothing but a placeholder is
+//! replaced by the parameter value itself, so a parameter holding a list of CIDR ranges stays a
+//! list; placeholders inside longer strings are replaced by the value as text. Placeholders that
+//! do not name a parameter are left alone.
+//!
+//! Instantiating a template never touches HEAD: the model is created in a new change set so it can
+//! be reviewed before it is applied. That holds for new workspaces too:
+//! [`Workspace::new_from_template`] creates the workspace and then instantiates the template into
+//! a change set of it.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use si_pkg::{
+    ComponentSpec, WorkspaceTemplate, WorkspaceTemplateContentV0, WorkspaceTemplateMetadataV0,
+    WorkspaceTemplateParameterV0,
+};
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::change_set::{ChangeSet, ChangeSetError, ChangeSetId};
+use crate::component::portable::{
+    ComponentImportReport, PortableComponents, PortableComponentsError,
+};
+use crate::{
+    Component, ComponentError, DalContext, HistoryActor, TransactionsError, User, UserError,
+    Workspace, WorkspaceError, WorkspacePk,
+};
+
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\{\{\s*([A-Za-z0-9_.-]+)\s*\}\}").expect("placeholder regex is valid")
+});
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum WorkspaceTemplateError {
+    #[error("change set error: {0}")]
+    ChangeSet(#[from] ChangeSetError),
+    #[error("component error: {0}")]
+    Component(#[from] ComponentError),
+    #[error("no value given for template parameter {0:?}, which has no default")]
+    MissingParameterValue(String),
+    #[error("portable components error: {0}")]
+    PortableComponents(#[from] PortableComponentsError),
+    #[error("transactions error: {0}")]
+    Transactions(#[from] TransactionsError),
+    #[error("template has no parameter named {0:?}")]
+    UnknownParameter(String),
+    #[error("user error: {0}")]
+    User(#[from] UserError),
+    #[error("workspace error: {0}")]
+    Workspace(#[from] WorkspaceError),
+}
+
+pub type WorkspaceTemplateResult<T> = Result<T, WorkspaceTemplateError>;
+
+/// The change set a template was instantiated into, and how its components were imported.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceTemplateInstance {
+    pub change_set_id: ChangeSetId,
+    pub report: ComponentImportReport,
+}
+
+/// Make a template out of every component in the current change set.
+#[instrument(
+    level = "info",
+    name = "workspace.template.generate",
+    skip(ctx, parameters)
+)]
+pub async fn generate_template(
+    ctx: &DalContext,
+    name: &str,
+    version: &str,
+    description: &str,
+    parameters: Vec<WorkspaceTemplateParameterV0>,
+) -> WorkspaceTemplateResult<WorkspaceTemplate> {
+    let component_ids = Component::list_ids(ctx).await?;
+    let PortableComponents {
+        components,
+        edges,
+        unportable_attributes,
+    } = PortableComponents::export(ctx, &component_ids).await?;
+    if !unportable_attributes.is_empty() {
+        warn!(
+            template = %name,
+            ?unportable_attributes,
+            "template generated without some attribute functions",
+        );
+    }
+
+    let created_by = if let HistoryActor::User(user_pk) = ctx.history_actor() {
+        let user = User::get_by_pk(ctx, *user_pk)
+            .await?
+            .ok_or(WorkspaceError::InvalidUser(*user_pk))?;
+
+        user.email().clone()
+    } else {
+        "SystemInit".to_string()
+    };
+
+    Ok(WorkspaceTemplate::new(WorkspaceTemplateContentV0 {
+        metadata: WorkspaceTemplateMetadataV0 {
+            name: name.to_owned(),
+            version: version.to_owned(),
+            description: description.to_owned(),
+            created_at: Utc::now(),
+            created_by,
+        },
+        parameters,
+        components,
+        edges,
+    }))
+}
+
+/// Fork a change set from HEAD named after the template, move `ctx` to it, and create the
+/// template's components there with the parameter values substituted in. Nothing is committed.
+#[instrument(level = "info", name = "workspace.template.instantiate", skip_all)]
+pub async fn instantiate_template(
+    ctx: &mut DalContext,
+    template: WorkspaceTemplate,
+    values: HashMap<String, Value>,
+) -> WorkspaceTemplateResult<WorkspaceTemplateInstance> {
+    let WorkspaceTemplateContentV0 {
+        metadata,
+        parameters,
+        mut components,
+        edges,
+    } = template.into_latest();
+
+    let values = resolve_parameter_values(&parameters, values)?;
+    for spec in &mut components {
+        substitute_component(spec, &values);
+    }
+
+    let change_set = ChangeSet::fork_head(ctx, &metadata.name).await?;
+    ctx.update_visibility_and_snapshot_to_visibility(change_set.id)
+        .await?;
+
+    let report = PortableComponents {
+        components,
+        edges,
+        ..Default::default()
+    }
+    .import(ctx)
+    .await?;
+    if !report.missing_schema_variants.is_empty() {
+        warn!(
+            template = %metadata.name,
+            missing_schema_variants = ?report.missing_schema_variants,
+            "template instantiated without some of its components",
+        );
+    }
+
+    Ok(WorkspaceTemplateInstance {
+        change_set_id: change_set.id,
+        report,
+    })
+}
+
+impl Workspace {
+    /// Create a workspace from the builtin workspace, like [`Workspace::new_from_builtin`], with a
+    /// change set holding an instance of the template. `ctx` is moved to that change set, and
+    /// nothing is committed.
+    #[instrument(
+        level = "info",
+        name = "workspace.template.new_workspace",
+        skip(ctx, name, token, template, values)
+    )]
+    pub async fn new_from_template(
+        ctx: &mut DalContext,
+        pk: WorkspacePk,
+        name: impl AsRef<str>,
+        token: impl AsRef<str>,
+        template: WorkspaceTemplate,
+        values: HashMap<String, Value>,
+    ) -> WorkspaceTemplateResult<(Self, WorkspaceTemplateInstance)> {
+        let workspace = Self::new_from_builtin(ctx, pk, name, token).await?;
+        let instance = instantiate_template(ctx, template, values).await?;
+
+        Ok((workspace, instance))
+    }
+}
+
+/// Every parameter's value: the one given, or its default. Values for parameters the template
+/// does not have are an error, since they are most likely typos.
+fn resolve_parameter_values(
+    parameters: &[WorkspaceTemplateParameterV0],
+    mut values: HashMap<String, Value>,
+) -> WorkspaceTemplateResult<HashMap<String, Value>> {
+    if let Some(unknown) = values
+        .keys()
+        .find(|name| !parameters.iter().any(|parameter| &parameter.name == *name))
+    {
+        return Err(WorkspaceTemplateError::UnknownParameter(unknown.to_owned()));
+    }
+
+    let mut resolved = HashMap::new();
+    for parameter in parameters {
+        let value = match values.remove(&parameter.name) {
+            Some(value) => value,
+            None => parameter.default.clone().ok_or_else(|| {
+                WorkspaceTemplateError::MissingParameterValue(parameter.name.to_owned())
+            })?,
+        };
+        resolved.insert(parameter.name.to_owned(), value);
+    }
+
+    Ok(resolved)
+}
+
+fn substitute_component(spec: &mut ComponentSpec, values: &HashMap<String, Value>) {
+    spec.name = substitute_string(&spec.name, values);
+    for attribute in &mut spec.attributes {
+        if let Some(value) = attribute.value.as_mut() {
+            substitute(value, values);
+            attribute.func_binding_args = serde_json::json!({ "value": value });
+        }
+    }
+}
+
+fn substitute(value: &mut Value, values: &HashMap<String, Value>) {
+    match value {
+        Value::String(string) => {
+            if let Some(whole) = PLACEHOLDER
+                .captures(string)
+                .filter(|captures| captures[0].len() == string.len())
+                .and_then(|captures| values.get(&captures[1]))
+            {
+                *value = whole.clone();
+            } else {
+                *string = substitute_string(string, values);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| substitute(item, values)),
+        Value::Object(entries) => entries
+            .values_mut()
+            .for_each(|entry| substitute(entry, values)),
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+fn substitute_string(string: &str, values: &HashMap<String, Value>) -> String {
+    PLACEHOLDER
+        .replace_all(string, |captures: &Captures<'_>| {
+            match values.get(&captures[1]) {
+                Some(Value::String(value)) => value.to_owned(),
+                Some(value) => value.to_string(),
+                None => captures[0].to_owned(),
+            }
+        })
+        .into_owned()
+}
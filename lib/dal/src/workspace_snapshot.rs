@@ -24,6 +24,8 @@
 //     clippy::missing_panics_doc
 // )]
 
+pub(crate) mod change_set_cache;
+pub mod chunk;
 pub mod content_address;
 pub mod edge_weight;
 pub mod graph;
@@ -38,7 +40,9 @@ pub use traits::{schema::variant::SchemaVariantExt, socket::input::InputSocketEx
 
 use graph::correct_transforms::correct_transforms;
 use graph::detect_updates::Update;
-use graph::{RebaseBatch, WorkspaceSnapshotGraph};
+use graph::{
+    ChangeScope, ChangeScopeKind, FsckReport, FsckViolation, RebaseBatch, WorkspaceSnapshotGraph,
+};
 use node_weight::traits::CorrectTransformsError;
 use std::collections::{HashMap, HashSet};
 use std::sync::atomic::AtomicBool;
@@ -67,6 +71,7 @@ use crate::slow_rt::{self, SlowRuntimeError};
 use crate::socket::connection_annotation::ConnectionAnnotationError;
 use crate::socket::input::InputSocketError;
 use crate::workspace_snapshot::{
+    chunk::WorkspaceSnapshotManifest,
     content_address::ContentAddressDiscriminants,
     edge_weight::{EdgeWeight, EdgeWeightKind, EdgeWeightKindDiscriminants},
     graph::{LineageId, WorkspaceSnapshotGraphDiscriminants},
@@ -78,11 +83,14 @@ use crate::{
 };
 use crate::{
     workspace_snapshot::{graph::WorkspaceSnapshotGraphError, node_weight::NodeWeightError},
-    DalContext, TransactionsError, WorkspaceSnapshotGraphVCurrent,
+    DalContext, DalLayerDb, TransactionsError, WorkspaceSnapshotGraphVCurrent,
 };
 
 use self::node_weight::{NodeWeightDiscriminants, OrderingNodeWeight};
 
+/// How many content hashes [`WorkspaceSnapshot::fsck`] looks up in the content store at once.
+const FSCK_CONTENT_BATCH_SIZE: usize = 1000;
+
 id!(NodeId);
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -167,6 +175,14 @@ pub enum WorkspaceSnapshotError {
     Workspace(#[from] Box<WorkspaceError>),
     #[error("Tenancy missing Workspace")]
     WorkspaceMissing,
+    #[error("expected a workspace snapshot chunk at address {0}, found a manifest")]
+    WorkspaceSnapshotChunkIsManifest(WorkspaceSnapshotAddress),
+    #[error("expected a workspace snapshot manifest at address {0}, found a chunk")]
+    WorkspaceSnapshotChunkIsNotManifest(WorkspaceSnapshotAddress),
+    #[error("workspace snapshot chunk missing at address: {0}")]
+    WorkspaceSnapshotChunkMissing(WorkspaceSnapshotAddress),
+    #[error("node {0} is referred to by a workspace snapshot chunk but is in none of them")]
+    WorkspaceSnapshotChunkNodeMissing(Ulid),
     #[error("WorkspaceSnapshotGraph error: {0}")]
     WorkspaceSnapshotGraph(#[from] WorkspaceSnapshotGraphError),
     #[error("workspace snapshot graph missing at address: {0}")]
@@ -507,15 +523,22 @@ impl WorkspaceSnapshot {
             // operation, so we throw it onto the "slow" runtime, the one not
             // listening for requests/processing a nats queue
             let new_address = slow_rt::spawn(async move {
+                let previous_address = *self_clone.address.read().await;
+                let previous_manifest =
+                    Self::chunk_manifest_opt(&layer_db, previous_address).await?;
+
                 let mut working_copy = self_clone.working_copy_mut().await;
                 working_copy.cleanup_and_merkle_tree_hash()?;
 
-                let (new_address, _) = layer_db.workspace_snapshot().write(
-                    Arc::new(WorkspaceSnapshotGraph::V4(working_copy.clone())),
-                    None,
+                // Only the chunks that changed since the snapshot we started from get written
+                let manifest = WorkspaceSnapshotManifest::write(
+                    &layer_db,
+                    &working_copy,
+                    previous_manifest.as_ref(),
                     events_tenancy,
                     events_actor,
                 )?;
+                let new_address = manifest.persist(&layer_db, events_tenancy, events_actor)?;
 
                 Ok::<WorkspaceSnapshotAddress, WorkspaceSnapshotError>(new_address)
             })?
@@ -544,14 +567,91 @@ impl WorkspaceSnapshot {
         let events_tenancy = ctx.events_tenancy();
         let events_actor = ctx.events_actor();
 
-        let (address, _) = ctx.layer_db().workspace_snapshot().write(
-            self.read_only_graph.clone(),
+        let manifest = WorkspaceSnapshotManifest::write(
+            ctx.layer_db(),
+            self.read_only_graph.inner(),
             None,
             events_tenancy,
             events_actor,
         )?;
+        manifest.persist(ctx.layer_db(), events_tenancy, events_actor)
+    }
+
+    /// The chunks the snapshot at the given address is stored as, if it is stored in chunks.
+    /// Snapshots written before chunking was introduced are stored as a single graph.
+    pub async fn chunk_manifest(
+        ctx: &DalContext,
+        workspace_snapshot_addr: WorkspaceSnapshotAddress,
+    ) -> WorkspaceSnapshotResult<Option<WorkspaceSnapshotManifest>> {
+        Self::chunk_manifest_opt(ctx.layer_db(), workspace_snapshot_addr).await
+    }
+
+    async fn chunk_manifest_opt(
+        layer_db: &DalLayerDb,
+        workspace_snapshot_addr: WorkspaceSnapshotAddress,
+    ) -> WorkspaceSnapshotResult<Option<WorkspaceSnapshotManifest>> {
+        if workspace_snapshot_addr == WorkspaceSnapshotAddress::nil() {
+            return Ok(None);
+        }
+
+        WorkspaceSnapshotManifest::find(layer_db, workspace_snapshot_addr, false).await
+    }
+
+    /// Checks the integrity of the graph (see [`FsckViolation`]), including that every content
+    /// hash in it resolves in the content store. With `repair`, the violations that can be fixed
+    /// safely are fixed in the working copy first; it is up to the caller to write it.
+    #[instrument(name = "workspace_snapshot.fsck", level = "info", skip(self, ctx))]
+    pub async fn fsck(
+        &self,
+        ctx: &DalContext,
+        repair: bool,
+    ) -> WorkspaceSnapshotResult<FsckReport> {
+        let repaired = if repair {
+            self.working_copy_mut().await.fsck_repair()?
+        } else {
+            vec![]
+        };
+
+        let (mut violations, content_hashes) = {
+            let graph = self.working_copy().await;
+            let content_hashes: Vec<(Ulid, ContentHash)> = graph
+                .nodes()
+                .flat_map(|(node, _)| {
+                    node.content_store_hashes()
+                        .into_iter()
+                        // Nodes without content (like the root) have the default hash
+                        .filter(|hash| *hash != ContentHash::default())
+                        .map(|hash| (node.id(), hash))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            (graph.fsck()?, content_hashes)
+        };
+
+        let unique_hashes: Vec<ContentHash> = content_hashes
+            .iter()
+            .map(|(_, hash)| *hash)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let mut found = HashSet::new();
+        for batch in unique_hashes.chunks(FSCK_CONTENT_BATCH_SIZE) {
+            found.extend(ctx.layer_db().cas().read_many(batch).await?.into_keys());
+        }
+        violations.extend(
+            content_hashes
+                .into_iter()
+                .filter(|(_, hash)| !found.contains(hash))
+                .map(|(node_id, hash)| FsckViolation::ContentMissing {
+                    node_id,
+                    content_hash: hash.to_string(),
+                }),
+        );
 
-        Ok(address)
+        Ok(FsckReport {
+            violations,
+            repaired,
+        })
     }
 
     pub async fn id(&self) -> WorkspaceSnapshotAddress {
@@ -736,6 +836,105 @@ impl WorkspaceSnapshot {
         .await?)
     }
 
+    /// The components that changed between `base` and this snapshot, counting the changes of the
+    /// `kind` and all of the changes that have not been written yet.
+    #[instrument(
+        name = "workspace_snapshot.change_scope_since",
+        level = "debug",
+        skip_all,
+        fields()
+    )]
+    pub async fn change_scope_since(
+        &self,
+        base_workspace_snapshot: &WorkspaceSnapshot,
+        kind: ChangeScopeKind,
+    ) -> WorkspaceSnapshotResult<ChangeScope> {
+        let [scope] = self
+            .change_scopes_since(base_workspace_snapshot, [kind])
+            .await?;
+        Ok(scope)
+    }
+
+    /// Like [`Self::change_scope_since`], for each of the `kinds`, finding the changes only once.
+    pub async fn change_scopes_since<const N: usize>(
+        &self,
+        base_workspace_snapshot: &WorkspaceSnapshot,
+        kinds: [ChangeScopeKind; N],
+    ) -> WorkspaceSnapshotResult<[ChangeScope; N]> {
+        let self_clone = self.clone();
+        let base_clone = base_workspace_snapshot.clone();
+
+        Ok(slow_rt::spawn(async move {
+            let updated_graph = self_clone.working_copy().await;
+            let base_graph = base_clone.working_copy().await;
+            let updates = base_graph.detect_updates(&updated_graph);
+            let touched_scope = updated_graph.change_scope_of_touched_nodes();
+
+            kinds.map(|kind| {
+                let mut scope = base_graph.change_scope_of_updates(&updated_graph, &updates, kind);
+                scope.merge(touched_scope.clone());
+                scope
+            })
+        })?
+        .await?)
+    }
+
+    /// The components that `updates` can affect, going by this snapshot only. See
+    /// [`WorkspaceSnapshotGraphV4::partial_change_scope_of_updates`] for how to get the whole
+    /// scope of updates performed in place.
+    pub async fn partial_change_scope_of_updates(
+        &self,
+        updates: &[Update],
+        kind: ChangeScopeKind,
+    ) -> ChangeScope {
+        self.working_copy()
+            .await
+            .partial_change_scope_of_updates(updates, kind)
+    }
+
+    /// The components that the changes that have not been written yet can affect.
+    pub async fn unwritten_change_scope(&self) -> ChangeScope {
+        self.working_copy().await.change_scope_of_touched_nodes()
+    }
+
+    /// The components that the changes since the snapshot at `address` can affect, including the
+    /// ones that have not been written yet. Everything, if that snapshot is gone.
+    pub(crate) async fn change_scope_since_address(
+        &self,
+        ctx: &DalContext,
+        address: WorkspaceSnapshotAddress,
+        kind: ChangeScopeKind,
+    ) -> WorkspaceSnapshotResult<ChangeScope> {
+        let [scope] = self
+            .change_scopes_since_address(ctx, address, [kind])
+            .await?;
+        Ok(scope)
+    }
+
+    /// Like [`Self::change_scope_since_address`], for each of the `kinds`.
+    pub(crate) async fn change_scopes_since_address<const N: usize>(
+        &self,
+        ctx: &DalContext,
+        address: WorkspaceSnapshotAddress,
+        kinds: [ChangeScopeKind; N],
+    ) -> WorkspaceSnapshotResult<[ChangeScope; N]> {
+        if self.id().await == address {
+            let scope = self.unwritten_change_scope().await;
+            return Ok(kinds.map(|_| scope.clone()));
+        }
+
+        match Self::find(ctx, address).await {
+            Ok(base_workspace_snapshot) => {
+                self.change_scopes_since(&base_workspace_snapshot, kinds)
+                    .await
+            }
+            Err(err) => {
+                debug!(si.error.message = ?err, "could not find the snapshot to diff against");
+                Ok(kinds.map(|_| ChangeScope::Everything))
+            }
+        }
+    }
+
     /// Gives the exact node index endpoints of an edge.
     pub async fn edge_endpoints(
         &self,
@@ -891,22 +1090,33 @@ impl WorkspaceSnapshot {
         ctx: &DalContext,
         workspace_snapshot_addr: WorkspaceSnapshotAddress,
     ) -> WorkspaceSnapshotResult<Self> {
-        let snapshot = match ctx
-            .layer_db()
-            .workspace_snapshot()
-            .read_wait_for_memory(&workspace_snapshot_addr)
-            .await
-        {
-            Ok(snapshot) => snapshot.ok_or(
-                WorkspaceSnapshotError::WorkspaceSnapshotGraphMissing(workspace_snapshot_addr),
-            )?,
-            Err(err) => match err {
-                LayerDbError::Postcard(_) => {
+        let layer_db = ctx.layer_db();
+        // Snapshots are stored as a manifest of their chunks, unless they were written before
+        // chunking. A manifest written by another instance may not have been persisted yet, so
+        // it is only waited for once the snapshot store does not have the snapshot either.
+        let snapshot = match Self::chunk_manifest_opt(layer_db, workspace_snapshot_addr).await? {
+            Some(manifest) => Self::assemble(layer_db, manifest).await?,
+            None => match layer_db
+                .workspace_snapshot()
+                .read(&workspace_snapshot_addr)
+                .await
+            {
+                Ok(Some(snapshot)) => snapshot,
+                Ok(None) => {
+                    let manifest =
+                        WorkspaceSnapshotManifest::find(layer_db, workspace_snapshot_addr, true)
+                            .await?
+                            .ok_or(WorkspaceSnapshotError::WorkspaceSnapshotGraphMissing(
+                                workspace_snapshot_addr,
+                            ))?;
+                    Self::assemble(layer_db, manifest).await?
+                }
+                Err(LayerDbError::Postcard(_)) => {
                     return Err(WorkspaceSnapshotError::WorkspaceSnapshotNotMigrated(
                         workspace_snapshot_addr,
                     ));
                 }
-                err => Err(err)?,
+                Err(err) => Err(err)?,
             },
         };
 
@@ -920,6 +1130,18 @@ impl WorkspaceSnapshot {
         })
     }
 
+    async fn assemble(
+        layer_db: &DalLayerDb,
+        manifest: WorkspaceSnapshotManifest,
+    ) -> WorkspaceSnapshotResult<Arc<WorkspaceSnapshotGraph>> {
+        let chunks = manifest.read_chunks(layer_db).await?;
+        // Putting a large graph back together is expensive, so it goes on the "slow" runtime,
+        // like serialization on write
+        let graph = slow_rt::spawn(async move { manifest.assemble(&chunks) })?.await??;
+
+        Ok(Arc::new(WorkspaceSnapshotGraph::V4(graph)))
+    }
+
     pub async fn find_for_change_set(
         ctx: &DalContext,
         change_set_id: ChangeSetId,
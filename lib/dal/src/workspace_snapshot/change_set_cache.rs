@@ -0,0 +1,100 @@
+//! Things derived from the snapshot of a change set that are worth keeping between requests,
+//! like the indexes behind [`ComponentQuery`](crate::component::query::ComponentQuery) and
+//! [`DependentValueGraph`](crate::attribute::value::DependentValueGraph).
+//!
+//! They are kept in the memory of the process that derived them, and are gone after a restart.
+//! The [`DependentValueIndex`](crate::attribute::value::DependentValueIndex) is stored as well,
+//! and only uses this to skip reading back what it stored itself.
+//! Whatever is kept should carry the address of the snapshot it was derived from, so that it can
+//! be brought up to date with [`WorkspaceSnapshot::change_scope_since_address`](super::WorkspaceSnapshot::change_scope_since_address)
+//! when it is taken again.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::ChangeSetId;
+
+/// A value for each of the change sets used most recently.
+#[derive(Debug)]
+pub(crate) struct ChangeSetCache<T> {
+    capacity: usize,
+    entries: Mutex<ChangeSetCacheEntries<T>>,
+}
+
+#[derive(Debug)]
+struct ChangeSetCacheEntries<T> {
+    /// Counts up every time a value is kept, so the entry kept longest ago has the lowest
+    last_kept: u64,
+    by_change_set: HashMap<ChangeSetId, (u64, T)>,
+}
+
+impl<T> ChangeSetCache<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(ChangeSetCacheEntries {
+                last_kept: 0,
+                by_change_set: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Takes the value for the change set out of the cache, so that nothing else uses it while
+    /// it is being brought up to date. It has to be [kept](Self::keep) again afterwards.
+    pub(crate) fn take(&self, change_set_id: ChangeSetId) -> Option<T> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .by_change_set
+            .remove(&change_set_id)
+            .map(|(_, value)| value)
+    }
+
+    /// Keeps the value for the change set, dropping the value that was kept longest ago if the
+    /// cache is full.
+    pub(crate) fn keep(&self, change_set_id: ChangeSetId, value: T) {
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if entries.by_change_set.len() >= self.capacity
+            && !entries.by_change_set.contains_key(&change_set_id)
+        {
+            let oldest = entries
+                .by_change_set
+                .iter()
+                .min_by_key(|(_, (kept, _))| *kept)
+                .map(|(change_set_id, _)| *change_set_id);
+            if let Some(oldest) = oldest {
+                entries.by_change_set.remove(&oldest);
+            }
+        }
+
+        entries.last_kept += 1;
+        let kept = entries.last_kept;
+        entries.by_change_set.insert(change_set_id, (kept, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_the_value_kept_longest_ago() {
+        let cache = ChangeSetCache::new(2);
+        let (first, second, third) = (ChangeSetId::new(), ChangeSetId::new(), ChangeSetId::new());
+
+        cache.keep(first, 1);
+        cache.keep(second, 2);
+        let taken = cache.take(first).expect("first should be kept");
+        cache.keep(first, taken);
+        cache.keep(third, 3);
+
+        assert_eq!(None, cache.take(second));
+        assert_eq!(Some(1), cache.take(first));
+        assert_eq!(Some(3), cache.take(third));
+        assert_eq!(None, cache.take(third));
+    }
+}
@@ -0,0 +1,758 @@
+//! Storage of a [`WorkspaceSnapshotGraphV4`] as content addressed chunks, so that snapshots
+//! share everything that did not change between them instead of each being one large blob.
+//!
+//! Every [`Component`](crate::Component) and [`SchemaVariant`](crate::SchemaVariant) gets a
+//! chunk with itself and the nodes only it reaches (attribute values, props, sockets,
+//! prototypes, ...). Everything else (categories, funcs shared by variants, nodes reached from
+//! more than one component or variant, ...) goes into a "trunk" chunk. A chunk holds its nodes
+//! and the edges going out of them, so the chunks of a snapshot partition its graph.
+//!
+//! A [`WorkspaceSnapshotManifest`] lists the chunks of a snapshot along with a fingerprint of
+//! each, made of the merkle tree hashes of its nodes. When a snapshot is written, only the
+//! chunks whose fingerprint is not in the manifest of the snapshot it was read from are
+//! serialized and persisted. When one is read, the graph is assembled from its chunks, most of
+//! which are usually in the cache already because other snapshots share them.
+//!
+//! Manifests are stored next to the chunks, as [`WorkspaceSnapshotChunkEntry::Manifest`], and the
+//! address of a manifest is the address of its snapshot. Snapshots written before chunking are
+//! still read from the snapshot store.
+//!
+//! When no change set points at a snapshot anymore, [`WorkspaceSnapshotManifest::evict`] evicts
+//! its manifest along with the chunks that no manifest of a snapshot still in use refers to. Since
+//! a snapshot is only ever written from one that a change set points at, the chunks it shares with
+//! the snapshot it was read from are always among the ones kept.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use petgraph::prelude::*;
+use serde::{Deserialize, Serialize};
+use si_events::WorkspaceSnapshotAddress;
+use si_events::{merkle_tree_hash::MerkleTreeHash, ulid::Ulid, Actor, Tenancy};
+use telemetry::prelude::*;
+
+use crate::workspace_snapshot::graph::{LineageId, WorkspaceSnapshotGraphV4};
+use crate::workspace_snapshot::node_weight::NodeWeight;
+use crate::workspace_snapshot::{WorkspaceSnapshotError, WorkspaceSnapshotResult};
+use crate::{DalLayerDb, EdgeWeight};
+
+/// Some of the nodes of a [`WorkspaceSnapshotGraphV4`], along with the edges going out of them.
+/// The nodes are ordered by id and the edges by source and target, so equal subgraphs make
+/// equal chunks.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WorkspaceSnapshotChunk {
+    nodes: Vec<NodeWeight>,
+    edges: Vec<WorkspaceSnapshotChunkEdge>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct WorkspaceSnapshotChunkEdge {
+    source: Ulid,
+    target: Ulid,
+    weight: EdgeWeight,
+}
+
+/// What the chunk store holds: the chunks, and the manifests of the snapshots made of them.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum WorkspaceSnapshotChunkEntry {
+    Chunk(Arc<WorkspaceSnapshotChunk>),
+    Manifest(WorkspaceSnapshotManifest),
+}
+
+/// The chunks a snapshot is made of. This is what is stored as the snapshot itself, at the
+/// address of the snapshot.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct WorkspaceSnapshotManifest {
+    root_id: Ulid,
+    chunks: Vec<WorkspaceSnapshotChunkRef>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct WorkspaceSnapshotChunkRef {
+    /// The component or schema variant whose subgraph is in the chunk, or `None` for the trunk.
+    pub subgraph_root_id: Option<Ulid>,
+    /// A hash of the ids and merkle tree hashes of the nodes in the chunk and of its edges.
+    pub fingerprint: MerkleTreeHash,
+    pub address: WorkspaceSnapshotAddress,
+    pub node_count: usize,
+}
+
+impl WorkspaceSnapshotChunk {
+    fn new(
+        graph: &WorkspaceSnapshotGraphV4,
+        members: &[NodeIndex],
+    ) -> WorkspaceSnapshotResult<Self> {
+        let mut nodes = Vec::with_capacity(members.len());
+        let mut edges = vec![];
+        for &node_index in members {
+            let node = graph.get_node_weight(node_index)?;
+            for (target, weight) in outgoing_edges(graph, node_index)? {
+                edges.push(WorkspaceSnapshotChunkEdge {
+                    source: node.id(),
+                    target,
+                    weight: weight.clone(),
+                });
+            }
+            nodes.push(node.clone());
+        }
+
+        Ok(Self { nodes, edges })
+    }
+
+    pub fn nodes(&self) -> &[NodeWeight] {
+        &self.nodes
+    }
+
+    /// The edges going out of the nodes of the chunk, as (source id, target id, weight).
+    pub fn edges(&self) -> impl Iterator<Item = (Ulid, Ulid, &EdgeWeight)> {
+        self.edges
+            .iter()
+            .map(|edge| (edge.source, edge.target, &edge.weight))
+    }
+}
+
+impl WorkspaceSnapshotManifest {
+    pub fn root_id(&self) -> Ulid {
+        self.root_id
+    }
+
+    pub fn chunks(&self) -> &[WorkspaceSnapshotChunkRef] {
+        &self.chunks
+    }
+
+    /// Splits the graph into chunks and writes the ones that are not in the `previous` manifest.
+    /// The graph's merkle tree hashes must be up to date.
+    #[instrument(
+        name = "workspace_snapshot.chunk.write",
+        level = "debug",
+        skip_all,
+        fields(
+            si.workspace_snapshot.chunk.count = Empty,
+            si.workspace_snapshot.chunk.written = Empty,
+        )
+    )]
+    pub fn write(
+        layer_db: &DalLayerDb,
+        graph: &WorkspaceSnapshotGraphV4,
+        previous: Option<&Self>,
+        tenancy: Tenancy,
+        actor: Actor,
+    ) -> WorkspaceSnapshotResult<Self> {
+        let span = current_span_for_instrument_at!("debug");
+
+        let previous_addresses: HashMap<MerkleTreeHash, WorkspaceSnapshotAddress> = previous
+            .map(|previous| {
+                previous
+                    .chunks
+                    .iter()
+                    .map(|chunk| (chunk.fingerprint, chunk.address))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut chunks = vec![];
+        let mut written = 0;
+        for (subgraph_root_id, members) in partition(graph) {
+            let fingerprint = fingerprint(graph, &members)?;
+            let address = match previous_addresses.get(&fingerprint) {
+                Some(address) => *address,
+                None => {
+                    let chunk = WorkspaceSnapshotChunk::new(graph, &members)?;
+                    let (address, _) = layer_db.workspace_snapshot_chunk().write(
+                        Arc::new(WorkspaceSnapshotChunkEntry::Chunk(Arc::new(chunk))),
+                        tenancy,
+                        actor,
+                    )?;
+                    written += 1;
+                    address
+                }
+            };
+
+            chunks.push(WorkspaceSnapshotChunkRef {
+                subgraph_root_id,
+                fingerprint,
+                address,
+                node_count: members.len(),
+            });
+        }
+
+        span.record("si.workspace_snapshot.chunk.count", chunks.len());
+        span.record("si.workspace_snapshot.chunk.written", written);
+
+        Ok(Self {
+            root_id: graph.get_node_weight(graph.root())?.id(),
+            chunks,
+        })
+    }
+
+    /// Stores the manifest as the snapshot, returning the address of the snapshot.
+    pub fn persist(
+        self,
+        layer_db: &DalLayerDb,
+        tenancy: Tenancy,
+        actor: Actor,
+    ) -> WorkspaceSnapshotResult<WorkspaceSnapshotAddress> {
+        let (address, _) = layer_db.workspace_snapshot_chunk().write(
+            Arc::new(WorkspaceSnapshotChunkEntry::Manifest(self)),
+            tenancy,
+            actor,
+        )?;
+
+        Ok(address)
+    }
+
+    /// The manifest of the snapshot at the given address, if it is stored in chunks. With
+    /// `wait_for_memory`, a manifest that another instance wrote so recently that it has not been
+    /// persisted yet is waited for.
+    pub async fn find(
+        layer_db: &DalLayerDb,
+        address: WorkspaceSnapshotAddress,
+        wait_for_memory: bool,
+    ) -> WorkspaceSnapshotResult<Option<Self>> {
+        let chunk_db = layer_db.workspace_snapshot_chunk();
+        let entry = match wait_for_memory {
+            true => chunk_db.read_wait_for_memory(&address).await?,
+            false => chunk_db.read(&address).await?,
+        };
+
+        match entry.as_deref() {
+            Some(WorkspaceSnapshotChunkEntry::Manifest(manifest)) => Ok(Some(manifest.clone())),
+            Some(WorkspaceSnapshotChunkEntry::Chunk(_)) => Err(
+                WorkspaceSnapshotError::WorkspaceSnapshotChunkIsNotManifest(address),
+            ),
+            None => Ok(None),
+        }
+    }
+
+    /// Evicts the manifest of a snapshot that no change set points at anymore, along with its
+    /// chunks that none of the manifests of the `in_use` snapshots refer to, returning how many
+    /// chunks were evicted. Nothing is evicted if the snapshot is not stored in chunks.
+    #[instrument(
+        name = "workspace_snapshot.chunk.evict",
+        level = "debug",
+        skip_all,
+        fields(
+            si.workspace_snapshot.address = %address,
+            si.workspace_snapshot.chunk.evicted = Empty,
+        )
+    )]
+    pub async fn evict(
+        layer_db: &DalLayerDb,
+        address: WorkspaceSnapshotAddress,
+        in_use: &HashSet<WorkspaceSnapshotAddress>,
+        tenancy: Tenancy,
+        actor: Actor,
+    ) -> WorkspaceSnapshotResult<usize> {
+        let span = current_span_for_instrument_at!("debug");
+
+        let Some(manifest) = Self::find(layer_db, address, false).await? else {
+            return Ok(0);
+        };
+
+        let live = Self::find_in_use(layer_db, address, in_use).await?;
+        let unreferenced = manifest.unreferenced_chunks(&live);
+        for chunk_address in &unreferenced {
+            layer_db
+                .workspace_snapshot_chunk()
+                .evict(chunk_address, tenancy, actor)?;
+        }
+        layer_db
+            .workspace_snapshot_chunk()
+            .evict(&address, tenancy, actor)?;
+
+        span.record("si.workspace_snapshot.chunk.evicted", unreferenced.len());
+
+        Ok(unreferenced.len())
+    }
+
+    /// The manifests of the `in_use` snapshots other than `evicted`. Snapshots written before
+    /// chunking have none. A snapshot found in neither store may have been written so recently
+    /// by another instance that its manifest is not persisted yet, so it is waited for, and if it
+    /// still does not show up nothing can be collected safely.
+    async fn find_in_use(
+        layer_db: &DalLayerDb,
+        evicted: WorkspaceSnapshotAddress,
+        in_use: &HashSet<WorkspaceSnapshotAddress>,
+    ) -> WorkspaceSnapshotResult<Vec<Self>> {
+        let addresses: Vec<WorkspaceSnapshotAddress> = in_use
+            .iter()
+            .copied()
+            .filter(|address| *address != evicted && *address != WorkspaceSnapshotAddress::nil())
+            .collect();
+        let found = layer_db
+            .workspace_snapshot_chunk()
+            .read_many(&addresses)
+            .await?;
+
+        let mut manifests = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            match found.get(&address).map(|entry| entry.as_ref()) {
+                Some(WorkspaceSnapshotChunkEntry::Manifest(manifest)) => {
+                    manifests.push(manifest.clone())
+                }
+                Some(WorkspaceSnapshotChunkEntry::Chunk(_)) => {
+                    return Err(WorkspaceSnapshotError::WorkspaceSnapshotChunkIsNotManifest(
+                        address,
+                    ));
+                }
+                None => {
+                    if layer_db.workspace_snapshot().exists(&address).await? {
+                        continue;
+                    }
+                    let manifest = Self::find(layer_db, address, true).await?.ok_or(
+                        WorkspaceSnapshotError::WorkspaceSnapshotGraphMissing(address),
+                    )?;
+                    manifests.push(manifest);
+                }
+            }
+        }
+
+        Ok(manifests)
+    }
+
+    /// The chunks of this manifest that none of the `live` manifests refer to.
+    pub fn unreferenced_chunks<'a>(
+        &self,
+        live: impl IntoIterator<Item = &'a Self>,
+    ) -> Vec<WorkspaceSnapshotAddress> {
+        let mut referenced: HashSet<WorkspaceSnapshotAddress> = live
+            .into_iter()
+            .flat_map(|manifest| manifest.chunks.iter().map(|chunk| chunk.address))
+            .collect();
+
+        let mut unreferenced = vec![];
+        for chunk in &self.chunks {
+            // Marking it as referenced once it is in the list keeps duplicates out
+            if referenced.insert(chunk.address) {
+                unreferenced.push(chunk.address);
+            }
+        }
+
+        unreferenced
+    }
+
+    /// Reads the chunks of the snapshot and assembles its graph.
+    #[instrument(
+        name = "workspace_snapshot.chunk.read",
+        level = "debug",
+        skip_all,
+        fields(
+            si.workspace_snapshot.chunk.count = self.chunks.len(),
+        )
+    )]
+    pub async fn read(
+        &self,
+        layer_db: &DalLayerDb,
+    ) -> WorkspaceSnapshotResult<WorkspaceSnapshotGraphV4> {
+        let chunks = self.read_chunks(layer_db).await?;
+        self.assemble(&chunks)
+    }
+
+    /// Reads the chunks of the snapshot, in the order of [`Self::chunks`].
+    pub async fn read_chunks(
+        &self,
+        layer_db: &DalLayerDb,
+    ) -> WorkspaceSnapshotResult<Vec<Arc<WorkspaceSnapshotChunk>>> {
+        let addresses: Vec<WorkspaceSnapshotAddress> =
+            self.chunks.iter().map(|chunk| chunk.address).collect();
+        let mut found = layer_db
+            .workspace_snapshot_chunk()
+            .read_many(&addresses)
+            .await?;
+
+        let mut chunks = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let chunk = match found.get(&address) {
+                Some(chunk) => chunk.clone(),
+                // Written by another instance so recently that it has not been persisted yet
+                None => {
+                    let chunk = layer_db
+                        .workspace_snapshot_chunk()
+                        .read_wait_for_memory(&address)
+                        .await?
+                        .ok_or(WorkspaceSnapshotError::WorkspaceSnapshotChunkMissing(
+                            address,
+                        ))?;
+                    found.insert(address, chunk.clone());
+                    chunk
+                }
+            };
+            match chunk.as_ref() {
+                WorkspaceSnapshotChunkEntry::Chunk(chunk) => chunks.push(chunk.clone()),
+                WorkspaceSnapshotChunkEntry::Manifest(_) => {
+                    return Err(WorkspaceSnapshotError::WorkspaceSnapshotChunkIsManifest(
+                        address,
+                    ));
+                }
+            }
+        }
+
+        Ok(chunks)
+    }
+
+    /// Builds the graph out of the given chunks, which must be the ones of this manifest.
+    pub fn assemble(
+        &self,
+        chunks: &[Arc<WorkspaceSnapshotChunk>],
+    ) -> WorkspaceSnapshotResult<WorkspaceSnapshotGraphV4> {
+        let node_count = chunks.iter().map(|chunk| chunk.nodes.len()).sum();
+        let edge_count = chunks.iter().map(|chunk| chunk.edges.len()).sum();
+
+        let mut graph = StableDiGraph::with_capacity(node_count, edge_count);
+        let mut node_index_by_id = HashMap::with_capacity(node_count);
+        let mut node_indices_by_lineage_id: HashMap<LineageId, HashSet<NodeIndex>> = HashMap::new();
+
+        for chunk in chunks {
+            for node in &chunk.nodes {
+                let id = node.id();
+                let lineage_id = node.lineage_id();
+                let node_index = graph.add_node(node.clone());
+                node_index_by_id.insert(id, node_index);
+                node_indices_by_lineage_id
+                    .entry(lineage_id)
+                    .or_default()
+                    .insert(node_index);
+            }
+        }
+
+        for chunk in chunks {
+            for edge in &chunk.edges {
+                let source = *node_index_by_id.get(&edge.source).ok_or(
+                    WorkspaceSnapshotError::WorkspaceSnapshotChunkNodeMissing(edge.source),
+                )?;
+                let target = *node_index_by_id.get(&edge.target).ok_or(
+                    WorkspaceSnapshotError::WorkspaceSnapshotChunkNodeMissing(edge.target),
+                )?;
+                graph.add_edge(source, target, edge.weight.clone());
+            }
+        }
+
+        let root_index = *node_index_by_id.get(&self.root_id).ok_or(
+            WorkspaceSnapshotError::WorkspaceSnapshotChunkNodeMissing(self.root_id),
+        )?;
+
+        Ok(WorkspaceSnapshotGraphV4::new_from_parts(
+            graph,
+            node_index_by_id,
+            node_indices_by_lineage_id,
+            root_index,
+        ))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Owner {
+    Subgraph(Ulid),
+    Shared,
+}
+
+/// Splits the nodes of the graph into the chunks they belong to, keyed by the id of the
+/// component or schema variant whose subgraph they are in (`None` for the trunk), with the
+/// nodes of each chunk ordered by id.
+pub(crate) fn partition(
+    graph: &WorkspaceSnapshotGraphV4,
+) -> BTreeMap<Option<Ulid>, Vec<NodeIndex>> {
+    let subgraph_roots: HashMap<NodeIndex, Ulid> = graph
+        .nodes()
+        .filter(|(node, _)| {
+            matches!(
+                node,
+                NodeWeight::Component(_) | NodeWeight::SchemaVariant(_)
+            )
+        })
+        .map(|(node, node_index)| (node_index, node.id()))
+        .collect();
+
+    let mut owners: HashMap<NodeIndex, Owner> = subgraph_roots
+        .iter()
+        .map(|(node_index, id)| (*node_index, Owner::Subgraph(*id)))
+        .collect();
+    for (&subgraph_root_index, &subgraph_root_id) in &subgraph_roots {
+        let owner = Owner::Subgraph(subgraph_root_id);
+        let mut seen = HashSet::from([subgraph_root_index]);
+        let mut stack = vec![subgraph_root_index];
+        while let Some(node_index) = stack.pop() {
+            for target in graph.neighbors_directed(node_index, Outgoing) {
+                // Other subgraphs are only referred to, never entered
+                if subgraph_roots.contains_key(&target) || !seen.insert(target) {
+                    continue;
+                }
+                owners
+                    .entry(target)
+                    .and_modify(|current| {
+                        if *current != owner {
+                            *current = Owner::Shared;
+                        }
+                    })
+                    .or_insert(owner);
+                stack.push(target);
+            }
+        }
+    }
+
+    let mut chunks: BTreeMap<Option<Ulid>, Vec<(Ulid, NodeIndex)>> = BTreeMap::new();
+    for (node, node_index) in graph.nodes() {
+        let subgraph_root_id = match owners.get(&node_index) {
+            Some(Owner::Subgraph(id)) => Some(*id),
+            Some(Owner::Shared) | None => None,
+        };
+        chunks
+            .entry(subgraph_root_id)
+            .or_default()
+            .push((node.id(), node_index));
+    }
+
+    chunks
+        .into_iter()
+        .map(|(subgraph_root_id, mut members)| {
+            members.sort_unstable_by_key(|(id, _)| *id);
+            (
+                subgraph_root_id,
+                members
+                    .into_iter()
+                    .map(|(_, node_index)| node_index)
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+/// The edges going out of the node as (target id, weight), ordered by target. Parallel edges
+/// are ordered by kind.
+fn outgoing_edges(
+    graph: &WorkspaceSnapshotGraphV4,
+    node_index: NodeIndex,
+) -> WorkspaceSnapshotResult<Vec<(Ulid, &EdgeWeight)>> {
+    let mut edges = Vec::new();
+    for edge_ref in graph.edges_directed(node_index, Outgoing) {
+        let target = graph.get_node_weight(edge_ref.target())?.id();
+        edges.push((target, edge_ref.weight()));
+    }
+    edges.sort_by(|(a, a_weight), (b, b_weight)| {
+        a.cmp(b)
+            .then_with(|| format!("{:?}", a_weight.kind()).cmp(&format!("{:?}", b_weight.kind())))
+    });
+
+    Ok(edges)
+}
+
+fn fingerprint(
+    graph: &WorkspaceSnapshotGraphV4,
+    members: &[NodeIndex],
+) -> WorkspaceSnapshotResult<MerkleTreeHash> {
+    let mut hasher = MerkleTreeHash::hasher();
+    for &node_index in members {
+        let node = graph.get_node_weight(node_index)?;
+        hasher.update(node.id().to_string().as_bytes());
+        hasher.update(node.merkle_tree_hash().as_bytes());
+        for (target, weight) in outgoing_edges(graph, node_index)? {
+            hasher.update(target.to_string().as_bytes());
+            hasher.update(format!("{:?}", weight.kind()).as_bytes());
+        }
+    }
+
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use si_events::ContentHash;
+
+    use super::*;
+    use crate::{EdgeWeightKind, PropKind};
+
+    fn graph_with_components(count: usize) -> (WorkspaceSnapshotGraphV4, Vec<Ulid>) {
+        let mut graph =
+            WorkspaceSnapshotGraphV4::new_for_unit_tests().expect("could not create graph");
+        let root = graph.root();
+
+        let mut component_ids = vec![];
+        for i in 0..count {
+            let component_id = graph.generate_ulid().expect("could not generate ulid");
+            let component_index = graph
+                .add_or_replace_node(NodeWeight::new_component(
+                    component_id,
+                    component_id,
+                    ContentHash::new(format!("component {i}").as_bytes()),
+                ))
+                .expect("could not add component");
+            graph
+                .add_edge(
+                    root,
+                    EdgeWeight::new(EdgeWeightKind::new_use()),
+                    component_index,
+                )
+                .expect("could not add edge");
+
+            let prop_id = graph.generate_ulid().expect("could not generate ulid");
+            let prop_index = graph
+                .add_or_replace_node(NodeWeight::new_prop(
+                    prop_id,
+                    prop_id,
+                    PropKind::String,
+                    "name",
+                    ContentHash::new(format!("prop {i}").as_bytes()),
+                ))
+                .expect("could not add prop");
+            graph
+                .add_edge(
+                    component_index,
+                    EdgeWeight::new(EdgeWeightKind::new_use()),
+                    prop_index,
+                )
+                .expect("could not add edge");
+
+            component_ids.push(component_id);
+        }
+        graph
+            .cleanup_and_merkle_tree_hash()
+            .expect("could not update merkle tree hashes");
+
+        (graph, component_ids)
+    }
+
+    fn chunks_of(
+        graph: &WorkspaceSnapshotGraphV4,
+    ) -> Vec<(Option<Ulid>, MerkleTreeHash, WorkspaceSnapshotChunk)> {
+        partition(graph)
+            .into_iter()
+            .map(|(subgraph_root_id, members)| {
+                (
+                    subgraph_root_id,
+                    fingerprint(graph, &members).expect("could not fingerprint chunk"),
+                    WorkspaceSnapshotChunk::new(graph, &members).expect("could not make chunk"),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chunks_assemble_into_the_same_graph() {
+        let (graph, component_ids) = graph_with_components(3);
+        let chunks = chunks_of(&graph);
+
+        // One chunk per component, plus the trunk
+        assert_eq!(4, chunks.len());
+        assert_eq!(None, chunks[0].0);
+        for component_id in &component_ids {
+            let (_, _, chunk) = chunks
+                .iter()
+                .find(|(subgraph_root_id, _, _)| *subgraph_root_id == Some(*component_id))
+                .expect("component should have a chunk");
+            assert_eq!(2, chunk.nodes().len());
+            assert_eq!(1, chunk.edges().count());
+        }
+
+        let manifest = WorkspaceSnapshotManifest {
+            root_id: graph
+                .get_node_weight(graph.root())
+                .expect("root should exist")
+                .id(),
+            chunks: vec![],
+        };
+        let assembled = manifest
+            .assemble(
+                &chunks
+                    .into_iter()
+                    .map(|(_, _, chunk)| Arc::new(chunk))
+                    .collect::<Vec<_>>(),
+            )
+            .expect("could not assemble graph");
+
+        assert_eq!(graph.node_count(), assembled.node_count());
+        assert_eq!(graph.edges().count(), assembled.edges().count());
+        assert_eq!(
+            graph
+                .get_node_weight(graph.root())
+                .expect("root should exist")
+                .merkle_tree_hash(),
+            assembled
+                .get_node_weight(assembled.root())
+                .expect("root should exist")
+                .merkle_tree_hash(),
+        );
+        for (node, _) in graph.nodes() {
+            let assembled_node = assembled
+                .get_node_weight_by_id_opt(node.id())
+                .expect("node should be assembled");
+            assert_eq!(node.merkle_tree_hash(), assembled_node.merkle_tree_hash());
+        }
+    }
+
+    #[test]
+    fn only_changed_chunks_get_new_fingerprints() {
+        let (mut graph, component_ids) = graph_with_components(3);
+        let before = chunks_of(&graph);
+
+        let component_index = graph
+            .get_node_index_by_id(component_ids[1])
+            .expect("component should exist");
+        let prop_id = graph.generate_ulid().expect("could not generate ulid");
+        let prop_index = graph
+            .add_or_replace_node(NodeWeight::new_prop(
+                prop_id,
+                prop_id,
+                PropKind::String,
+                "description",
+                ContentHash::new("description".as_bytes()),
+            ))
+            .expect("could not add prop");
+        graph
+            .add_edge(
+                component_index,
+                EdgeWeight::new(EdgeWeightKind::new_use()),
+                prop_index,
+            )
+            .expect("could not add edge");
+        graph
+            .cleanup_and_merkle_tree_hash()
+            .expect("could not update merkle tree hashes");
+        let after = chunks_of(&graph);
+
+        assert_eq!(before.len(), after.len());
+        for ((subgraph_root_id, before_fingerprint, _), (_, after_fingerprint, _)) in
+            before.iter().zip(after.iter())
+        {
+            // The trunk has the root in it, whose merkle tree hash changes with any change
+            let changed = subgraph_root_id.is_none() || *subgraph_root_id == Some(component_ids[1]);
+            assert_eq!(changed, before_fingerprint != after_fingerprint);
+        }
+    }
+
+    fn manifest_of(chunk_names: &[&str]) -> WorkspaceSnapshotManifest {
+        WorkspaceSnapshotManifest {
+            root_id: Ulid::new(),
+            chunks: chunk_names
+                .iter()
+                .map(|name| WorkspaceSnapshotChunkRef {
+                    subgraph_root_id: None,
+                    fingerprint: MerkleTreeHash::new(name.as_bytes()),
+                    address: WorkspaceSnapshotAddress::new(name.as_bytes()),
+                    node_count: 1,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn only_chunks_no_live_manifest_refers_to_are_unreferenced() {
+        let evicted = manifest_of(&["trunk", "shared", "mine", "mine"]);
+        let live = [
+            manifest_of(&["other trunk", "shared"]),
+            manifest_of(&["trunk"]),
+        ];
+
+        assert_eq!(
+            vec![WorkspaceSnapshotAddress::new("mine".as_bytes())],
+            evicted.unreferenced_chunks(&live),
+        );
+        assert_eq!(
+            vec![
+                WorkspaceSnapshotAddress::new("trunk".as_bytes()),
+                WorkspaceSnapshotAddress::new("shared".as_bytes()),
+                WorkspaceSnapshotAddress::new("mine".as_bytes()),
+            ],
+            evicted.unreferenced_chunks([]),
+        );
+    }
+}
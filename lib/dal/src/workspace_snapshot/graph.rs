@@ -31,6 +31,9 @@ pub mod v4;
 pub use traits::{schema::variant::SchemaVariantExt, socket::input::InputSocketExt};
 pub use v2::WorkspaceSnapshotGraphV2;
 pub use v3::WorkspaceSnapshotGraphV3;
+pub use v4::change_scope::{ChangeScope, ChangeScopeKind};
+pub use v4::fsck::{FsckReport, FsckViolation};
+pub use v4::stats::{ComponentStats, WorkspaceSnapshotGraphStats};
 pub use v4::WorkspaceSnapshotGraphV4;
 
 pub type LineageId = Ulid;
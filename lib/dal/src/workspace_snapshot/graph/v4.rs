@@ -33,9 +33,12 @@ use crate::{
     Timestamp,
 };
 
+pub mod change_scope;
 pub mod component;
+pub mod fsck;
 pub mod schema;
 pub mod socket;
+pub mod stats;
 
 #[derive(Default, Deserialize, Serialize, Clone)]
 pub struct WorkspaceSnapshotGraphV4 {
@@ -830,6 +833,21 @@ impl WorkspaceSnapshotGraphV4 {
         // ```
         // GRAPHFILE=<filename-without-extension>; cat $GRAPHFILE.txt | dot -Tsvg -o processed-$GRAPHFILE.svg; open processed-$GRAPHFILE.svg
         // ```
+        let dot = self.tiny_dot();
+        let filename_no_extension = format!("{}-{}", Ulid::new(), suffix);
+
+        let home_str = std::env::var("HOME").expect("could not find home directory via env");
+        let home = std::path::Path::new(&home_str);
+
+        let mut file = File::create(home.join(format!("{filename_no_extension}.txt")))
+            .expect("could not create file");
+        file.write_all(dot.as_bytes())
+            .expect("could not write file");
+        println!("dot output stored in file (filename without extension: {filename_no_extension})");
+    }
+
+    /// Renders the graph in the DOT format, with nodes and edges labeled and colored by kind.
+    pub fn tiny_dot(&self) -> String {
         let dot = petgraph::dot::Dot::with_attr_getters(
             &self.graph,
             &[
@@ -991,16 +1009,8 @@ impl WorkspaceSnapshotGraphV4 {
                 )
             },
         );
-        let filename_no_extension = format!("{}-{}", Ulid::new(), suffix);
-
-        let home_str = std::env::var("HOME").expect("could not find home directory via env");
-        let home = std::path::Path::new(&home_str);
 
-        let mut file = File::create(home.join(format!("{filename_no_extension}.txt")))
-            .expect("could not create file");
-        file.write_all(format!("{dot:?}").as_bytes())
-            .expect("could not write file");
-        println!("dot output stored in file (filename without extension: {filename_no_extension})");
+        format!("{dot:?}")
     }
 
     #[inline(always)]
@@ -0,0 +1,394 @@
+//! Working out which [`Components`](crate::Component) a change to a graph can affect, so that
+//! anything derived from the graph and kept per component only has to be recomputed for those.
+
+use std::collections::HashSet;
+
+use petgraph::prelude::*;
+use si_events::ulid::Ulid;
+
+use crate::workspace_snapshot::graph::detect_updates::Update;
+use crate::workspace_snapshot::graph::WorkspaceSnapshotGraphV4;
+use crate::workspace_snapshot::node_weight::NodeWeight;
+use crate::{ComponentId, EdgeWeightKindDiscriminants};
+
+/// The components that a set of changes to a graph can affect.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangeScope {
+    /// Only these components (none at all, if it is empty)
+    Components(HashSet<ComponentId>),
+    /// Something that every component can depend on changed, like a prop or a schema variant
+    Everything,
+}
+
+impl ChangeScope {
+    pub fn nothing() -> Self {
+        Self::Components(HashSet::new())
+    }
+
+    pub fn merge(&mut self, other: ChangeScope) {
+        match (&mut *self, other) {
+            (Self::Everything, _) => {}
+            (_, Self::Everything) => *self = Self::Everything,
+            (Self::Components(components), Self::Components(other)) => components.extend(other),
+        }
+    }
+}
+
+/// Which changes count towards a [`ChangeScope`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeScopeKind {
+    /// Changes to how the graph is connected. New values for attribute values are left out,
+    /// since they do not change which values depend on which.
+    Structure,
+    /// Changes to how the graph is connected and new values for attribute values, like the
+    /// ones that dependent values updates write.
+    StructureAndValues,
+}
+
+/// Which components a single node belongs to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum NodeScope {
+    /// Nodes that nothing derived per component depends on, like geometry or actions
+    Ignored,
+    Components(HashSet<ComponentId>),
+    /// Nodes that are not owned by any component, like props and schema variants
+    Global,
+}
+
+/// The edges that go from a node to the nodes it owns. Anything else (the prop an attribute
+/// value is for, the sources of prototype arguments, frames, ...) only refers to another node.
+const OWNING_EDGE_KINDS: &[EdgeWeightKindDiscriminants] = &[
+    EdgeWeightKindDiscriminants::Contain,
+    EdgeWeightKindDiscriminants::Ordering,
+    EdgeWeightKindDiscriminants::Prototype,
+    EdgeWeightKindDiscriminants::PrototypeArgument,
+    EdgeWeightKindDiscriminants::Root,
+    EdgeWeightKindDiscriminants::Socket,
+    EdgeWeightKindDiscriminants::SocketValue,
+    EdgeWeightKindDiscriminants::Use,
+];
+
+impl WorkspaceSnapshotGraphV4 {
+    /// The components that `updates` from `self` to `updated_graph` (as found by
+    /// [`Self::detect_updates`]) can affect, counting the changes of the `kind`. An attribute
+    /// value with a new value belongs to the component it is in.
+    pub fn change_scope_of_updates(
+        &self,
+        updated_graph: &Self,
+        updates: &[Update],
+        kind: ChangeScopeKind,
+    ) -> ChangeScope {
+        Self::scope_of_updates(updates, kind, |id| {
+            Self::node_scope_in_either(self, updated_graph, id)
+        })
+    }
+
+    /// Like [`Self::change_scope_of_updates`], for when only one of the graphs is at hand, like
+    /// when the updates are performed in place. Nodes that are not in this graph count for
+    /// nothing, so this has to be called on the graph both before and after the updates are
+    /// performed, and the two scopes merged.
+    pub fn partial_change_scope_of_updates(
+        &self,
+        updates: &[Update],
+        kind: ChangeScopeKind,
+    ) -> ChangeScope {
+        Self::scope_of_updates(updates, kind, |id| {
+            match self.get_node_index_by_id_opt(id) {
+                Some(node_index) => self.node_scope(node_index),
+                None => NodeScope::Components(HashSet::new()),
+            }
+        })
+    }
+
+    fn scope_of_updates(
+        updates: &[Update],
+        kind: ChangeScopeKind,
+        node_scope: impl Fn(Ulid) -> NodeScope,
+    ) -> ChangeScope {
+        let mut scope = ChangeScope::nothing();
+        for update in updates {
+            let node_scope = match update {
+                Update::NewNode {
+                    node_weight: NodeWeight::AttributeValue(_),
+                }
+                | Update::ReplaceNode {
+                    node_weight: NodeWeight::AttributeValue(_),
+                } if kind == ChangeScopeKind::Structure => continue,
+                Update::NewNode { node_weight } | Update::ReplaceNode { node_weight } => {
+                    node_scope(node_weight.id())
+                }
+                Update::NewEdge {
+                    source,
+                    destination,
+                    ..
+                }
+                | Update::RemoveEdge {
+                    source,
+                    destination,
+                    ..
+                } => Self::edge_scope(
+                    node_scope(source.id.into()),
+                    node_scope(destination.id.into()),
+                ),
+            };
+
+            match node_scope {
+                NodeScope::Ignored => {}
+                NodeScope::Components(components) => {
+                    scope.merge(ChangeScope::Components(components))
+                }
+                NodeScope::Global => return ChangeScope::Everything,
+            }
+        }
+
+        scope
+    }
+
+    /// The components that the changes made since the merkle tree hash was last calculated can
+    /// affect, going by the touched nodes. Nodes are touched when their weight or their
+    /// outgoing edges change, so unlike [`Self::change_scope_of_updates`], this also counts new
+    /// values for attribute values.
+    pub fn change_scope_of_touched_nodes(&self) -> ChangeScope {
+        let mut scope = ChangeScope::nothing();
+        for &node_index in &self.touched_node_indices {
+            match self.node_scope(node_index) {
+                NodeScope::Ignored => {}
+                NodeScope::Components(components) => {
+                    scope.merge(ChangeScope::Components(components))
+                }
+                NodeScope::Global => return ChangeScope::Everything,
+            }
+        }
+
+        scope
+    }
+
+    /// An edge belongs to the components of its ends. It is only global if neither end belongs
+    /// to a component, so that adding a component under a category or pointing a prototype at
+    /// a func does not count as changing everything.
+    fn edge_scope(source: NodeScope, destination: NodeScope) -> NodeScope {
+        match (source, destination) {
+            (NodeScope::Components(mut components), NodeScope::Components(other)) => {
+                components.extend(other);
+                NodeScope::Components(components)
+            }
+            (NodeScope::Components(components), _) | (_, NodeScope::Components(components)) => {
+                NodeScope::Components(components)
+            }
+            (NodeScope::Global, _) | (_, NodeScope::Global) => NodeScope::Global,
+            (NodeScope::Ignored, NodeScope::Ignored) => NodeScope::Ignored,
+        }
+    }
+
+    /// Nodes that were removed are only in the base graph, and new ones only in the updated one.
+    fn node_scope_in_either(base_graph: &Self, updated_graph: &Self, id: Ulid) -> NodeScope {
+        match updated_graph.get_node_index_by_id_opt(id) {
+            Some(node_index) => updated_graph.node_scope(node_index),
+            None => match base_graph.get_node_index_by_id_opt(id) {
+                Some(node_index) => base_graph.node_scope(node_index),
+                None => NodeScope::Global,
+            },
+        }
+    }
+
+    /// Walks up the owning edges of the node until it reaches components. If there is a way up
+    /// that does not go through a component, the node is global.
+    fn node_scope(&self, node_index: NodeIndex) -> NodeScope {
+        if node_index == self.root() {
+            return NodeScope::Ignored;
+        }
+        match self.get_node_weight_opt(node_index) {
+            // Removed nodes are accounted for by whatever they were removed from
+            None => return NodeScope::Components(HashSet::new()),
+            // Categories only change when something is added under them or removed from them,
+            // which is accounted for by the other end of the edge
+            Some(
+                NodeWeight::Action(_)
+                | NodeWeight::ActionPrototype(_)
+                | NodeWeight::Category(_)
+                | NodeWeight::DependentValueRoot(_)
+                | NodeWeight::FinishedDependentValueRoot(_)
+                | NodeWeight::Func(_)
+                | NodeWeight::FuncArgument(_)
+                | NodeWeight::Geometry(_)
+                | NodeWeight::ManagementPrototype(_)
+                | NodeWeight::Secret(_)
+                | NodeWeight::View(_),
+            ) => return NodeScope::Ignored,
+            Some(_) => {}
+        }
+
+        let mut components = HashSet::new();
+        let mut seen = HashSet::from([node_index]);
+        let mut stack = vec![node_index];
+
+        while let Some(node_index) = stack.pop() {
+            match self.get_node_weight_opt(node_index) {
+                Some(node_weight @ NodeWeight::Component(_)) => {
+                    components.insert(node_weight.id().into());
+                    continue;
+                }
+                Some(NodeWeight::AttributePrototypeArgument(apa)) => {
+                    // Connections between components live on the prototypes of schema variants,
+                    // but only matter to the components at either end
+                    if let Some(targets) = apa.targets() {
+                        components.insert(targets.source_component_id);
+                        components.insert(targets.destination_component_id);
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+
+            let mut owners = self
+                .graph
+                .edges_directed(node_index, Incoming)
+                .filter(|edge_ref| OWNING_EDGE_KINDS.contains(&edge_ref.weight().kind().into()))
+                .map(|edge_ref| edge_ref.source())
+                .peekable();
+            if owners.peek().is_none() {
+                return NodeScope::Global;
+            }
+            for owner in owners {
+                if seen.insert(owner) {
+                    stack.push(owner);
+                }
+            }
+        }
+
+        NodeScope::Components(components)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use si_events::ContentHash;
+
+    use crate::workspace_snapshot::content_address::ContentAddress;
+    use crate::workspace_snapshot::graph::WorkspaceSnapshotGraphV4;
+    use crate::workspace_snapshot::node_weight::NodeWeight;
+    use crate::{ComponentId, EdgeWeight, EdgeWeightKind, PropKind};
+
+    use super::{ChangeScope, ChangeScopeKind};
+
+    #[test]
+    fn scopes_changes_to_the_components_they_are_in() {
+        let mut graph =
+            WorkspaceSnapshotGraphV4::new_for_unit_tests().expect("could not make graph");
+        let root_index = graph.root();
+
+        let component_id = graph.generate_ulid().expect("could not generate ulid");
+        let component_index = graph
+            .add_or_replace_node(NodeWeight::new_component(
+                component_id,
+                component_id,
+                ContentHash::new(component_id.to_string().as_bytes()),
+            ))
+            .expect("could not add component");
+        graph
+            .add_edge(
+                root_index,
+                EdgeWeight::new(EdgeWeightKind::new_use()),
+                component_index,
+            )
+            .expect("could not add edge");
+        graph
+            .cleanup_and_merkle_tree_hash()
+            .expect("could not hash graph");
+        let base_graph = graph.clone();
+
+        let attribute_value_id = graph.generate_ulid().expect("could not generate ulid");
+        let attribute_value_index = graph
+            .add_or_replace_node(NodeWeight::new_attribute_value(
+                attribute_value_id,
+                attribute_value_id,
+                Some(ContentAddress::JsonValue(ContentHash::new(b"{}"))),
+                None,
+            ))
+            .expect("could not add attribute value");
+        graph
+            .add_edge(
+                component_index,
+                EdgeWeight::new(EdgeWeightKind::Root),
+                attribute_value_index,
+            )
+            .expect("could not add edge");
+
+        let only_the_component =
+            ChangeScope::Components(HashSet::from([ComponentId::from(component_id)]));
+        assert_eq!(only_the_component, graph.change_scope_of_touched_nodes());
+
+        graph
+            .cleanup_and_merkle_tree_hash()
+            .expect("could not hash graph");
+        let updates = base_graph.detect_updates(&graph);
+        assert_eq!(
+            only_the_component,
+            base_graph.change_scope_of_updates(&graph, &updates, ChangeScopeKind::Structure)
+        );
+        let base_graph = graph.clone();
+
+        // A new value only counts when values do
+        graph
+            .add_or_replace_node(NodeWeight::new_attribute_value(
+                attribute_value_id,
+                attribute_value_id,
+                Some(ContentAddress::JsonValue(ContentHash::new(b"[]"))),
+                None,
+            ))
+            .expect("could not replace attribute value");
+        graph
+            .cleanup_and_merkle_tree_hash()
+            .expect("could not hash graph");
+        let updates = base_graph.detect_updates(&graph);
+        assert_eq!(
+            ChangeScope::nothing(),
+            base_graph.change_scope_of_updates(&graph, &updates, ChangeScopeKind::Structure)
+        );
+        assert_eq!(
+            only_the_component,
+            base_graph.change_scope_of_updates(
+                &graph,
+                &updates,
+                ChangeScopeKind::StructureAndValues
+            )
+        );
+
+        // Performing the updates in place gives the same scope, going by both sides
+        let mut updated_in_place = base_graph.clone();
+        let mut in_place_scope = updated_in_place
+            .partial_change_scope_of_updates(&updates, ChangeScopeKind::StructureAndValues);
+        updated_in_place
+            .perform_updates(&updates)
+            .expect("could not perform updates");
+        in_place_scope.merge(
+            updated_in_place
+                .partial_change_scope_of_updates(&updates, ChangeScopeKind::StructureAndValues),
+        );
+        assert_eq!(only_the_component, in_place_scope);
+
+        let prop_id = graph.generate_ulid().expect("could not generate ulid");
+        let prop_index = graph
+            .add_or_replace_node(NodeWeight::new_prop(
+                prop_id,
+                prop_id,
+                PropKind::String,
+                "name",
+                ContentHash::new(b"name"),
+            ))
+            .expect("could not add prop");
+        graph
+            .add_edge(
+                root_index,
+                EdgeWeight::new(EdgeWeightKind::new_use()),
+                prop_index,
+            )
+            .expect("could not add edge");
+        assert_eq!(
+            ChangeScope::Everything,
+            graph.change_scope_of_touched_nodes()
+        );
+    }
+}
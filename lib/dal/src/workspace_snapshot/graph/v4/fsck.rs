@@ -0,0 +1,788 @@
+//! Integrity checks ("fsck") for [`WorkspaceSnapshotGraphV4`].
+//!
+//! The rest of the dal assumes a number of invariants about the graph that nothing enforces when
+//! it is written. When one of them is broken, we usually find out through a confusing error far
+//! away from the cause. [`WorkspaceSnapshotGraphV4::fsck`] checks all of them and reports every
+//! violation along with the ids of the nodes involved, and
+//! [`WorkspaceSnapshotGraphV4::fsck_repair`] fixes the ones that can be fixed without losing
+//! information.
+//!
+//! Checking that content hashes resolve in the content store needs the layer db, so it lives in
+//! [`WorkspaceSnapshot::fsck`](crate::WorkspaceSnapshot::fsck).
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use petgraph::{algo, prelude::*, visit::Dfs};
+use serde::{Deserialize, Serialize};
+use si_events::ulid::Ulid;
+
+use crate::workspace_snapshot::graph::{WorkspaceSnapshotGraphResult, WorkspaceSnapshotGraphV4};
+use crate::workspace_snapshot::node_weight::NodeWeight;
+use crate::{EdgeWeight, EdgeWeightKind, NodeWeightDiscriminants};
+
+/// A broken invariant found by [`WorkspaceSnapshotGraphV4::fsck`].
+#[remain::sorted]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum FsckViolation {
+    /// Attribute values for sockets have no [`Prop`](EdgeWeightKind::Prop) edge, all others
+    /// have exactly one.
+    AttributeValuePropEdges {
+        attribute_value_id: Ulid,
+        prop_ids: Vec<Ulid>,
+    },
+    /// The content store has nothing at a content hash the node refers to.
+    ContentMissing {
+        node_id: Ulid,
+        content_hash: String,
+    },
+    Cycle {
+        node_ids: Vec<Ulid>,
+    },
+    /// `node_indices_by_lineage_id` does not match the lineage ids of the nodes in the graph.
+    LineageIndexMismatch {
+        lineage_id: Ulid,
+        node_id: Option<Ulid>,
+    },
+    MerkleTreeHashMismatch {
+        node_id: Ulid,
+        stored: String,
+        computed: String,
+    },
+    /// A container with more than one ordering node.
+    MultipleOrderingNodes {
+        container_id: Ulid,
+        ordering_ids: Vec<Ulid>,
+    },
+    /// `node_index_by_id` does not match the ids of the nodes in the graph.
+    NodeIndexMismatch {
+        node_id: Ulid,
+    },
+    /// An ordering node that is the ordering node of more than one container.
+    OrderingContainers {
+        ordering_id: Ulid,
+        container_ids: Vec<Ulid>,
+    },
+    /// The order of an ordering node does not match the children of its container.
+    OrderingMismatch {
+        container_id: Ulid,
+        ordering_id: Ulid,
+        /// [`Contain`](EdgeWeightKind::Contain) children of the container that are not in the
+        /// order.
+        missing_from_order: Vec<Ulid>,
+        /// Ids in the order that are not children of the container, or that are in it more
+        /// than once.
+        not_children: Vec<Ulid>,
+    },
+    OrphanedNode {
+        node_id: Ulid,
+        node_kind: NodeWeightDiscriminants,
+    },
+}
+
+impl FsckViolation {
+    /// Whether [`WorkspaceSnapshotGraphV4::fsck_repair`] fixes this kind of violation.
+    pub fn is_repairable(&self) -> bool {
+        match self {
+            Self::LineageIndexMismatch { .. }
+            | Self::MerkleTreeHashMismatch { .. }
+            | Self::NodeIndexMismatch { .. }
+            | Self::OrderingMismatch { .. }
+            | Self::OrphanedNode { .. } => true,
+            Self::AttributeValuePropEdges { .. }
+            | Self::ContentMissing { .. }
+            | Self::Cycle { .. }
+            | Self::MultipleOrderingNodes { .. }
+            | Self::OrderingContainers { .. } => false,
+        }
+    }
+
+    /// Whether merkle tree hashes cannot be calculated (or mean nothing) while this violation
+    /// is there.
+    fn prevents_merkle_tree_hashing(&self) -> bool {
+        matches!(
+            self,
+            Self::Cycle { .. }
+                | Self::MultipleOrderingNodes { .. }
+                | Self::OrderingContainers { .. }
+        )
+    }
+}
+
+impl fmt::Display for FsckViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AttributeValuePropEdges {
+                attribute_value_id,
+                prop_ids,
+            } => write!(
+                f,
+                "attribute value {attribute_value_id} has {} prop edge(s): {prop_ids:?}",
+                prop_ids.len()
+            ),
+            Self::ContentMissing {
+                node_id,
+                content_hash,
+            } => write!(f, "content {content_hash} of node {node_id} is missing"),
+            Self::Cycle { node_ids } => write!(f, "cycle through nodes {node_ids:?}"),
+            Self::LineageIndexMismatch {
+                lineage_id,
+                node_id,
+            } => write!(
+                f,
+                "lineage index for {lineage_id} does not match the graph (node: {node_id:?})"
+            ),
+            Self::MerkleTreeHashMismatch {
+                node_id,
+                stored,
+                computed,
+            } => write!(
+                f,
+                "merkle tree hash of node {node_id} is {stored}, but should be {computed}"
+            ),
+            Self::MultipleOrderingNodes {
+                container_id,
+                ordering_ids,
+            } => write!(
+                f,
+                "container {container_id} has {} ordering nodes: {ordering_ids:?}",
+                ordering_ids.len()
+            ),
+            Self::NodeIndexMismatch { node_id } => {
+                write!(f, "node index for {node_id} does not match the graph")
+            }
+            Self::OrderingContainers {
+                ordering_id,
+                container_ids,
+            } => write!(
+                f,
+                "ordering node {ordering_id} belongs to {} containers: {container_ids:?}",
+                container_ids.len()
+            ),
+            Self::OrderingMismatch {
+                container_id,
+                ordering_id,
+                missing_from_order,
+                not_children,
+            } => write!(
+                f,
+                "ordering node {ordering_id} of {container_id} is missing children {missing_from_order:?} \
+                 and orders non-children {not_children:?}"
+            ),
+            Self::OrphanedNode { node_id, node_kind } => {
+                write!(f, "{node_kind} node {node_id} is not reachable from the root")
+            }
+        }
+    }
+}
+
+/// What a check of a snapshot found, and what it fixed before looking if it was asked to repair.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FsckReport {
+    pub violations: Vec<FsckViolation>,
+    pub repaired: Vec<FsckViolation>,
+}
+
+impl FsckReport {
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl WorkspaceSnapshotGraphV4 {
+    /// Checks every invariant of the graph, returning the violations found. Only fails if the
+    /// graph is too broken to be walked at all.
+    pub fn fsck(&self) -> WorkspaceSnapshotGraphResult<Vec<FsckViolation>> {
+        let mut violations = vec![];
+
+        self.fsck_attribute_value_prop_edges(&mut violations)?;
+        self.fsck_orderings(&mut violations)?;
+        self.fsck_orphans(&mut violations);
+        self.fsck_indexes(&mut violations);
+        self.fsck_cycles(&mut violations);
+        // The violations that get in the way of merkle tree hashes already point at the actual
+        // problem, so there is no point in also reporting every hash above it
+        if !violations
+            .iter()
+            .any(FsckViolation::prevents_merkle_tree_hashing)
+        {
+            self.fsck_merkle_tree_hashes(&mut violations)?;
+        }
+
+        Ok(violations)
+    }
+
+    /// Fixes the violations that can be fixed without losing information (see
+    /// [`FsckViolation::is_repairable`]), returning the ones that were fixed. Orphaned nodes are
+    /// removed, the indexes are rebuilt, orders are made to match their containers' children
+    /// (keeping the relative order of the ids that stay), and merkle tree hashes are
+    /// recalculated.
+    pub fn fsck_repair(&mut self) -> WorkspaceSnapshotGraphResult<Vec<FsckViolation>> {
+        let violations = self.fsck()?;
+        let can_hash = !violations
+            .iter()
+            .any(FsckViolation::prevents_merkle_tree_hashing);
+        let repairable: Vec<FsckViolation> = violations
+            .into_iter()
+            .filter(FsckViolation::is_repairable)
+            .collect();
+        if repairable.is_empty() {
+            return Ok(repairable);
+        }
+
+        if repairable
+            .iter()
+            .any(|violation| matches!(violation, FsckViolation::OrphanedNode { .. }))
+        {
+            let reachable = self.reachable_from_root();
+            let orphans: Vec<NodeIndex> = self
+                .graph
+                .node_indices()
+                .filter(|node_index| !reachable.contains(node_index))
+                .collect();
+            for node_index in orphans {
+                self.graph.remove_node(node_index);
+            }
+        }
+        self.rebuild_indexes();
+        for violation in &repairable {
+            if let FsckViolation::OrderingMismatch {
+                container_id,
+                ordering_id,
+                missing_from_order,
+                not_children,
+            } = violation
+            {
+                self.repair_order(
+                    *container_id,
+                    *ordering_id,
+                    missing_from_order,
+                    not_children,
+                )?;
+            }
+        }
+        if can_hash {
+            self.recalculate_entire_merkle_tree_hash()?;
+        }
+
+        Ok(repairable)
+    }
+
+    fn fsck_attribute_value_prop_edges(
+        &self,
+        violations: &mut Vec<FsckViolation>,
+    ) -> WorkspaceSnapshotGraphResult<()> {
+        for (node, node_index) in self.nodes() {
+            if !matches!(node, NodeWeight::AttributeValue(_)) {
+                continue;
+            }
+
+            let mut prop_ids = vec![];
+            let mut is_for_socket = false;
+            for edge_ref in self.graph.edges_directed(node_index, Outgoing) {
+                match edge_ref.weight().kind() {
+                    EdgeWeightKind::Prop => {
+                        prop_ids.push(self.get_node_weight(edge_ref.target())?.id())
+                    }
+                    EdgeWeightKind::Socket => is_for_socket = true,
+                    _ => {}
+                }
+            }
+
+            let expected = if is_for_socket { 0 } else { 1 };
+            if prop_ids.len() != expected {
+                violations.push(FsckViolation::AttributeValuePropEdges {
+                    attribute_value_id: node.id(),
+                    prop_ids,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fsck_orderings(
+        &self,
+        violations: &mut Vec<FsckViolation>,
+    ) -> WorkspaceSnapshotGraphResult<()> {
+        let mut ordering_ids_by_container: HashMap<NodeIndex, Vec<Ulid>> = HashMap::new();
+        for (node, ordering_index) in self.nodes() {
+            let NodeWeight::Ordering(ordering) = node else {
+                continue;
+            };
+
+            let container_indices: Vec<NodeIndex> = self
+                .graph
+                .edges_directed(ordering_index, Incoming)
+                .filter(|edge_ref| edge_ref.weight().kind() == &EdgeWeightKind::Ordering)
+                .map(|edge_ref| edge_ref.source())
+                .collect();
+            let container_index = match container_indices.as_slice() {
+                // Nothing else points at an ordering node, so this is an orphan
+                [] => continue,
+                [container_index] => {
+                    ordering_ids_by_container
+                        .entry(*container_index)
+                        .or_default()
+                        .push(ordering.id());
+                    *container_index
+                }
+                _ => {
+                    violations.push(FsckViolation::OrderingContainers {
+                        ordering_id: ordering.id(),
+                        container_ids: container_indices
+                            .iter()
+                            .map(|index| self.get_node_weight(*index).map(NodeWeight::id))
+                            .collect::<WorkspaceSnapshotGraphResult<_>>()?,
+                    });
+                    continue;
+                }
+            };
+
+            let mut children = HashSet::new();
+            let mut contained_children = vec![];
+            for edge_ref in self.graph.edges_directed(container_index, Outgoing) {
+                let child_id = self.get_node_weight(edge_ref.target())?.id();
+                match edge_ref.weight().kind() {
+                    EdgeWeightKind::Ordering => continue,
+                    EdgeWeightKind::Contain(_) => contained_children.push(child_id),
+                    _ => {}
+                }
+                children.insert(child_id);
+            }
+
+            let mut seen = HashSet::new();
+            let not_children: Vec<Ulid> = ordering
+                .order()
+                .iter()
+                .filter(|id| !children.contains(*id) || !seen.insert(**id))
+                .copied()
+                .collect();
+            let mut missing_from_order: Vec<Ulid> = contained_children
+                .into_iter()
+                .filter(|id| !seen.contains(id))
+                .collect();
+            missing_from_order.sort();
+            missing_from_order.dedup();
+
+            if !missing_from_order.is_empty() || !not_children.is_empty() {
+                violations.push(FsckViolation::OrderingMismatch {
+                    container_id: self.get_node_weight(container_index)?.id(),
+                    ordering_id: ordering.id(),
+                    missing_from_order,
+                    not_children,
+                });
+            }
+        }
+
+        for (container_index, mut ordering_ids) in ordering_ids_by_container {
+            if ordering_ids.len() > 1 {
+                ordering_ids.sort();
+                violations.push(FsckViolation::MultipleOrderingNodes {
+                    container_id: self.get_node_weight(container_index)?.id(),
+                    ordering_ids,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fsck_orphans(&self, violations: &mut Vec<FsckViolation>) {
+        let reachable = self.reachable_from_root();
+        for (node, node_index) in self.nodes() {
+            if !reachable.contains(&node_index) {
+                violations.push(FsckViolation::OrphanedNode {
+                    node_id: node.id(),
+                    node_kind: node.into(),
+                });
+            }
+        }
+    }
+
+    fn fsck_indexes(&self, violations: &mut Vec<FsckViolation>) {
+        let mut mismatched_ids = HashSet::new();
+        for (node, node_index) in self.nodes() {
+            if self.node_index_by_id.get(&node.id()) != Some(&node_index) {
+                mismatched_ids.insert(node.id());
+            }
+        }
+        for (id, node_index) in &self.node_index_by_id {
+            if self.get_node_weight_opt(*node_index).map(NodeWeight::id) != Some(*id) {
+                mismatched_ids.insert(*id);
+            }
+        }
+        let mut mismatched_ids: Vec<Ulid> = mismatched_ids.into_iter().collect();
+        mismatched_ids.sort();
+        violations.extend(
+            mismatched_ids
+                .into_iter()
+                .map(|node_id| FsckViolation::NodeIndexMismatch { node_id }),
+        );
+
+        let mut mismatched_lineages = HashSet::new();
+        for (node, node_index) in self.nodes() {
+            let indexed = self
+                .node_indices_by_lineage_id
+                .get(&node.lineage_id())
+                .is_some_and(|node_indices| node_indices.contains(&node_index));
+            if !indexed {
+                mismatched_lineages.insert((node.lineage_id(), Some(node.id())));
+            }
+        }
+        for (lineage_id, node_indices) in &self.node_indices_by_lineage_id {
+            for node_index in node_indices {
+                match self.get_node_weight_opt(*node_index) {
+                    Some(node) if node.lineage_id() == *lineage_id => {}
+                    node => {
+                        mismatched_lineages.insert((*lineage_id, node.map(NodeWeight::id)));
+                    }
+                }
+            }
+        }
+        let mut mismatched_lineages: Vec<(Ulid, Option<Ulid>)> =
+            mismatched_lineages.into_iter().collect();
+        mismatched_lineages.sort();
+        violations.extend(
+            mismatched_lineages
+                .into_iter()
+                .map(
+                    |(lineage_id, node_id)| FsckViolation::LineageIndexMismatch {
+                        lineage_id,
+                        node_id,
+                    },
+                ),
+        );
+    }
+
+    fn fsck_cycles(&self, violations: &mut Vec<FsckViolation>) {
+        if self.is_acyclic_directed() {
+            return;
+        }
+
+        for component in algo::tarjan_scc(&self.graph) {
+            let is_cycle = match component.as_slice() {
+                [node_index] => self.graph.contains_edge(*node_index, *node_index),
+                _ => true,
+            };
+            if is_cycle {
+                let mut node_ids: Vec<Ulid> = component
+                    .iter()
+                    .filter_map(|node_index| self.node_index_to_id(*node_index))
+                    .collect();
+                node_ids.sort();
+                violations.push(FsckViolation::Cycle { node_ids });
+            }
+        }
+    }
+
+    fn fsck_merkle_tree_hashes(
+        &self,
+        violations: &mut Vec<FsckViolation>,
+    ) -> WorkspaceSnapshotGraphResult<()> {
+        let mut recalculated = self.clone();
+        recalculated.recalculate_entire_merkle_tree_hash()?;
+
+        let reachable = self.reachable_from_root();
+        for (node, node_index) in self.nodes() {
+            if !reachable.contains(&node_index) {
+                continue;
+            }
+            let computed = recalculated.get_node_weight(node_index)?.merkle_tree_hash();
+            if node.merkle_tree_hash() != computed {
+                violations.push(FsckViolation::MerkleTreeHashMismatch {
+                    node_id: node.id(),
+                    stored: node.merkle_tree_hash().to_string(),
+                    computed: computed.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn reachable_from_root(&self) -> HashSet<NodeIndex> {
+        let mut reachable = HashSet::new();
+        let mut dfs = Dfs::new(&self.graph, self.root_index);
+        while let Some(node_index) = dfs.next(&self.graph) {
+            reachable.insert(node_index);
+        }
+
+        reachable
+    }
+
+    fn rebuild_indexes(&mut self) {
+        let mut node_index_by_id = HashMap::new();
+        let mut node_indices_by_lineage_id: HashMap<Ulid, HashSet<NodeIndex>> = HashMap::new();
+        for (node, node_index) in self.nodes() {
+            node_index_by_id.insert(node.id(), node_index);
+            node_indices_by_lineage_id
+                .entry(node.lineage_id())
+                .or_default()
+                .insert(node_index);
+        }
+
+        self.node_index_by_id = node_index_by_id;
+        self.node_indices_by_lineage_id = node_indices_by_lineage_id;
+    }
+
+    fn repair_order(
+        &mut self,
+        container_id: Ulid,
+        ordering_id: Ulid,
+        missing_from_order: &[Ulid],
+        not_children: &[Ulid],
+    ) -> WorkspaceSnapshotGraphResult<()> {
+        // The container or ordering node may have been an orphan that is gone now
+        let (Some(container_index), Some(ordering_index)) = (
+            self.get_node_index_by_id_opt(container_id),
+            self.get_node_index_by_id_opt(ordering_id),
+        ) else {
+            return Ok(());
+        };
+
+        let children: HashSet<Ulid> = self
+            .graph
+            .edges_directed(container_index, Outgoing)
+            .filter(|edge_ref| edge_ref.weight().kind() != &EdgeWeightKind::Ordering)
+            .filter_map(|edge_ref| self.node_index_to_id(edge_ref.target()))
+            .collect();
+
+        let NodeWeight::Ordering(ordering) = self.get_node_weight(ordering_index)? else {
+            return Ok(());
+        };
+        let mut seen = HashSet::new();
+        let mut new_order: Vec<Ulid> = ordering
+            .order()
+            .iter()
+            .filter(|id| children.contains(*id) && seen.insert(**id))
+            .copied()
+            .collect();
+        new_order.extend(missing_from_order.iter().filter(|id| seen.insert(**id)));
+
+        // Keep the ordinal edges in line with the order
+        for id in not_children.iter().chain(missing_from_order) {
+            let Some(target_index) = self.get_node_index_by_id_opt(*id) else {
+                continue;
+            };
+            let ordinal_edges: Vec<EdgeIndex> = self
+                .graph
+                .edges_connecting(ordering_index, target_index)
+                .filter(|edge_ref| edge_ref.weight().kind() == &EdgeWeightKind::Ordinal)
+                .map(|edge_ref| edge_ref.id())
+                .collect();
+            if !new_order.contains(id) {
+                for edge_index in ordinal_edges {
+                    self.graph.remove_edge(edge_index);
+                }
+            } else if ordinal_edges.is_empty() {
+                self.graph.add_edge(
+                    ordering_index,
+                    target_index,
+                    EdgeWeight::new(EdgeWeightKind::Ordinal),
+                );
+            }
+        }
+
+        if let Some(NodeWeight::Ordering(ordering)) = self.graph.node_weight_mut(ordering_index) {
+            ordering.set_order(new_order);
+        }
+        self.touch_node(ordering_index);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use si_events::ContentHash;
+
+    use super::*;
+    use crate::workspace_snapshot::content_address::ContentAddress;
+    use crate::PropKind;
+
+    fn graph_with_ordered_container() -> (WorkspaceSnapshotGraphV4, NodeIndex, Vec<Ulid>) {
+        let mut graph =
+            WorkspaceSnapshotGraphV4::new_for_unit_tests().expect("could not create graph");
+        let root = graph.root();
+
+        let prop_id = graph.generate_ulid().expect("could not generate ulid");
+        let prop_index = graph
+            .add_or_replace_node(NodeWeight::new_prop(
+                prop_id,
+                prop_id,
+                PropKind::Array,
+                "tags",
+                ContentHash::new("tags".as_bytes()),
+            ))
+            .expect("could not add prop");
+        graph
+            .add_edge(root, EdgeWeight::new(EdgeWeightKind::new_use()), prop_index)
+            .expect("could not add edge");
+
+        let container_id = graph.generate_ulid().expect("could not generate ulid");
+        let container_index = graph
+            .add_ordered_node(NodeWeight::new_attribute_value(
+                container_id,
+                container_id,
+                None,
+                None,
+            ))
+            .expect("could not add container");
+        graph
+            .add_edge(
+                root,
+                EdgeWeight::new(EdgeWeightKind::new_use()),
+                container_index,
+            )
+            .expect("could not add edge");
+        graph
+            .add_edge(
+                container_index,
+                EdgeWeight::new(EdgeWeightKind::Prop),
+                prop_index,
+            )
+            .expect("could not add edge");
+
+        let mut child_ids = vec![];
+        for i in 0..3 {
+            let child_id = graph.generate_ulid().expect("could not generate ulid");
+            let child_index = graph
+                .add_or_replace_node(NodeWeight::new_attribute_value(
+                    child_id,
+                    child_id,
+                    None,
+                    Some(ContentAddress::JsonValue(ContentHash::new(
+                        format!("tag {i}").as_bytes(),
+                    ))),
+                ))
+                .expect("could not add child");
+            graph
+                .add_ordered_edge(
+                    container_index,
+                    EdgeWeight::new(EdgeWeightKind::Contain(None)),
+                    child_index,
+                )
+                .expect("could not add edge");
+            graph
+                .add_edge(
+                    child_index,
+                    EdgeWeight::new(EdgeWeightKind::Prop),
+                    prop_index,
+                )
+                .expect("could not add edge");
+            child_ids.push(child_id);
+        }
+        graph
+            .cleanup_and_merkle_tree_hash()
+            .expect("could not update merkle tree hashes");
+
+        (graph, container_index, child_ids)
+    }
+
+    #[test]
+    fn healthy_graph_has_no_violations() {
+        let (graph, _, _) = graph_with_ordered_container();
+
+        assert_eq!(
+            Vec::<FsckViolation>::new(),
+            graph.fsck().expect("could not fsck")
+        );
+    }
+
+    #[test]
+    fn repairs_indexes_orders_orphans_and_hashes() {
+        let (mut graph, container_index, child_ids) = graph_with_ordered_container();
+        let ordering_index = graph
+            .ordering_node_index_for_container(container_index)
+            .expect("could not get ordering node")
+            .expect("container should have an ordering node");
+
+        // Forget a child in the order and the index
+        if let Some(NodeWeight::Ordering(ordering)) = graph.graph.node_weight_mut(ordering_index) {
+            ordering.remove_from_order(child_ids[1]);
+        }
+        graph.node_index_by_id.remove(&child_ids[2]);
+        // Leave a node behind that nothing points to
+        let orphan_id = graph.generate_ulid().expect("could not generate ulid");
+        graph
+            .add_or_replace_node(NodeWeight::new_attribute_value(
+                orphan_id, orphan_id, None, None,
+            ))
+            .expect("could not add orphan");
+
+        let violations = graph.fsck().expect("could not fsck");
+        let container_id = graph
+            .get_node_weight(container_index)
+            .expect("container should exist")
+            .id();
+        assert!(violations.contains(&FsckViolation::OrderingMismatch {
+            container_id,
+            ordering_id: graph
+                .get_node_weight(ordering_index)
+                .expect("ordering should exist")
+                .id(),
+            missing_from_order: vec![child_ids[1]],
+            not_children: vec![],
+        }));
+        assert!(violations.contains(&FsckViolation::NodeIndexMismatch {
+            node_id: child_ids[2]
+        }));
+        assert!(violations.contains(&FsckViolation::OrphanedNode {
+            node_id: orphan_id,
+            node_kind: NodeWeightDiscriminants::AttributeValue,
+        }));
+        assert!(violations
+            .iter()
+            .any(|violation| matches!(violation, FsckViolation::MerkleTreeHashMismatch { .. })));
+        // The orphan has no prop either, but that is not something we can fix
+        assert!(
+            violations.contains(&FsckViolation::AttributeValuePropEdges {
+                attribute_value_id: orphan_id,
+                prop_ids: vec![],
+            })
+        );
+
+        let repaired = graph.fsck_repair().expect("could not repair");
+        assert!(repaired.iter().all(FsckViolation::is_repairable));
+        assert_eq!(
+            Vec::<FsckViolation>::new(),
+            graph.fsck().expect("could not fsck")
+        );
+        assert_eq!(
+            Some(vec![child_ids[0], child_ids[2], child_ids[1]]),
+            graph
+                .ordered_children_for_node(container_index)
+                .expect("could not get ordered children")
+                .map(|indices| indices
+                    .into_iter()
+                    .filter_map(|index| graph.node_index_to_id(index))
+                    .collect::<Vec<_>>())
+        );
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let (mut graph, container_index, _) = graph_with_ordered_container();
+        let root = graph.root();
+        graph.graph.add_edge(
+            container_index,
+            root,
+            EdgeWeight::new(EdgeWeightKind::new_use()),
+        );
+
+        let violations = graph.fsck().expect("could not fsck");
+        assert!(violations
+            .iter()
+            .any(|violation| matches!(violation, FsckViolation::Cycle { node_ids } if node_ids.len() == 2)));
+        assert!(!violations
+            .iter()
+            .any(|violation| matches!(violation, FsckViolation::MerkleTreeHashMismatch { .. })));
+    }
+}
@@ -0,0 +1,185 @@
+//! Summary statistics for [`WorkspaceSnapshotGraphV4`], for getting a feel for what a snapshot
+//! is made of without walking the whole graph by hand.
+
+use std::collections::BTreeMap;
+
+use petgraph::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::workspace_snapshot::chunk::partition;
+use crate::workspace_snapshot::graph::WorkspaceSnapshotGraphV4;
+use crate::workspace_snapshot::node_weight::NodeWeight;
+use crate::{ComponentId, EdgeWeightKindDiscriminants, NodeWeightDiscriminants};
+
+/// What a [`WorkspaceSnapshotGraphV4`] is made of, as returned by
+/// [`WorkspaceSnapshotGraphV4::stats`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSnapshotGraphStats {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub nodes_by_kind: BTreeMap<NodeWeightDiscriminants, usize>,
+    pub edges_by_kind: BTreeMap<EdgeWeightKindDiscriminants, usize>,
+    /// The components, largest first
+    pub components: Vec<ComponentStats>,
+}
+
+/// The size of the subgraph that belongs to a single component: its attribute values, geometry
+/// and so on, but not anything it shares with other components, like its schema variant.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentStats {
+    pub component_id: ComponentId,
+    pub node_count: usize,
+    /// The edges going out of the nodes of the component
+    pub edge_count: usize,
+}
+
+impl WorkspaceSnapshotGraphV4 {
+    pub fn stats(&self) -> WorkspaceSnapshotGraphStats {
+        let mut stats = WorkspaceSnapshotGraphStats::default();
+
+        for (node, _) in self.nodes() {
+            stats.node_count += 1;
+            *stats.nodes_by_kind.entry(node.into()).or_default() += 1;
+        }
+        for (edge, _, _) in self.edges() {
+            stats.edge_count += 1;
+            *stats.edges_by_kind.entry(edge.kind().into()).or_default() += 1;
+        }
+
+        // Components own the same subgraphs they are chunked by when the snapshot is written
+        for (subgraph_root_id, members) in partition(self) {
+            let Some(subgraph_root_id) = subgraph_root_id else {
+                continue;
+            };
+            let is_component = self
+                .node_index_by_id
+                .get(&subgraph_root_id)
+                .and_then(|&node_index| self.get_node_weight_opt(node_index))
+                .is_some_and(|node| matches!(node, NodeWeight::Component(_)));
+            if !is_component {
+                continue;
+            }
+
+            stats.components.push(ComponentStats {
+                component_id: subgraph_root_id.into(),
+                node_count: members.len(),
+                edge_count: members
+                    .iter()
+                    .map(|&node_index| self.graph.edges_directed(node_index, Outgoing).count())
+                    .sum(),
+            });
+        }
+        stats.components.sort_by(|a, b| {
+            b.node_count
+                .cmp(&a.node_count)
+                .then(a.component_id.cmp(&b.component_id))
+        });
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use si_events::ContentHash;
+
+    use crate::workspace_snapshot::content_address::ContentAddress;
+    use crate::workspace_snapshot::graph::WorkspaceSnapshotGraphV4;
+    use crate::workspace_snapshot::node_weight::NodeWeight;
+    use crate::{
+        ComponentId, EdgeWeight, EdgeWeightKind, EdgeWeightKindDiscriminants,
+        NodeWeightDiscriminants,
+    };
+
+    #[test]
+    fn counts_nodes_edges_and_component_sizes() {
+        let mut graph =
+            WorkspaceSnapshotGraphV4::new_for_unit_tests().expect("could not make graph");
+        let root_index = graph.root();
+        let before = graph.stats();
+
+        let mut component_ids = vec![];
+        for attribute_value_count in [1, 3] {
+            let component_id = graph.generate_ulid().expect("could not generate ulid");
+            let component_index = graph
+                .add_or_replace_node(NodeWeight::new_component(
+                    component_id,
+                    component_id,
+                    ContentHash::new(component_id.to_string().as_bytes()),
+                ))
+                .expect("could not add component");
+            graph
+                .add_edge(
+                    root_index,
+                    EdgeWeight::new(EdgeWeightKind::new_use()),
+                    component_index,
+                )
+                .expect("could not add edge");
+
+            for _ in 0..attribute_value_count {
+                let attribute_value_id = graph.generate_ulid().expect("could not generate ulid");
+                let attribute_value_index = graph
+                    .add_or_replace_node(NodeWeight::new_attribute_value(
+                        attribute_value_id,
+                        attribute_value_id,
+                        Some(ContentAddress::JsonValue(ContentHash::new(b"{}"))),
+                        None,
+                    ))
+                    .expect("could not add attribute value");
+                graph
+                    .add_edge(
+                        component_index,
+                        EdgeWeight::new(EdgeWeightKind::Contain(None)),
+                        attribute_value_index,
+                    )
+                    .expect("could not add edge");
+            }
+            component_ids.push(component_id);
+        }
+
+        let stats = graph.stats();
+
+        assert_eq!(before.node_count + 6, stats.node_count);
+        assert_eq!(before.edge_count + 6, stats.edge_count);
+        assert_eq!(
+            Some(&4),
+            stats
+                .nodes_by_kind
+                .get(&NodeWeightDiscriminants::AttributeValue)
+        );
+        assert_eq!(
+            Some(&2),
+            stats.nodes_by_kind.get(&NodeWeightDiscriminants::Component)
+        );
+        assert_eq!(
+            Some(&4),
+            stats
+                .edges_by_kind
+                .get(&EdgeWeightKindDiscriminants::Contain)
+        );
+        assert_eq!(
+            before
+                .edges_by_kind
+                .get(&EdgeWeightKindDiscriminants::Use)
+                .copied()
+                .unwrap_or_default()
+                + 2,
+            stats.edges_by_kind[&EdgeWeightKindDiscriminants::Use]
+        );
+
+        let component_sizes: Vec<_> = stats
+            .components
+            .iter()
+            .map(|component| (component.component_id, component.node_count))
+            .collect();
+        assert_eq!(
+            vec![
+                (ComponentId::from(component_ids[1]), 4),
+                (ComponentId::from(component_ids[0]), 2)
+            ],
+            component_sizes
+        );
+    }
+}
@@ -110,6 +110,14 @@ impl SnapshotGraphMigrator {
 
             let snapshot_address = change_set.workspace_snapshot_address;
 
+            // Only current graphs are ever written in chunks
+            if WorkspaceSnapshot::chunk_manifest(&ctx_after_migration, snapshot_address)
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+
             let new_snapshot = match self
                 .migrate_snapshot(&ctx_after_migration, snapshot_address)
                 .await
@@ -221,6 +229,29 @@ impl SnapshotGraphMigrator {
 
         Ok(working_graph)
     }
+
+    /// Migrates the graph as far as it can be without a content store: V1 graphs become V2
+    /// graphs. V2 -> V3 reads schema variant and input socket content, and V3 -> V4 writes the
+    /// default view, so those steps are left to [`Self::migrate_snapshot`], which picks up from
+    /// whatever version this returns.
+    pub fn migrate_offline(
+        &mut self,
+        mut working_graph: WorkspaceSnapshotGraph,
+    ) -> SnapshotGraphMigratorResult<WorkspaceSnapshotGraph> {
+        loop {
+            match working_graph {
+                WorkspaceSnapshotGraph::V1(inner_graph) => {
+                    working_graph = WorkspaceSnapshotGraph::V2(migrate_v1_to_v2(inner_graph)?);
+                }
+                WorkspaceSnapshotGraph::Legacy
+                | WorkspaceSnapshotGraph::V2(_)
+                | WorkspaceSnapshotGraph::V3(_)
+                | WorkspaceSnapshotGraph::V4(_) => break,
+            }
+        }
+
+        Ok(working_graph)
+    }
 }
 
 impl Default for SnapshotGraphMigrator {
@@ -120,7 +120,15 @@ pub type NodeWeightResult<T> = Result<T, NodeWeightError>;
 /// **WARNING**: the order of this enum is important! Do not re-order elements.
 /// New variants must go at the end, even if it's not in lexical order!
 #[derive(Debug, Serialize, Deserialize, Clone, EnumDiscriminants, PartialEq, Eq)]
-#[strum_discriminants(derive(strum::Display, Hash, Serialize, Deserialize, EnumIter))]
+#[strum_discriminants(derive(
+    strum::Display,
+    Hash,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    PartialOrd,
+    Ord
+))]
 pub enum NodeWeight {
     Action(ActionNodeWeight),
     ActionPrototype(ActionPrototypeNodeWeight),
@@ -1,4 +1,7 @@
-use dal::{AttributeValue, DalContext};
+use dal::attribute::value::provenance::{
+    ArgumentSource, AttributeValueProvenance, AttributeValueProvenanceError, ValueOrigin,
+};
+use dal::{AttributeValue, AttributeValueId, DalContext};
 use dal_test::expected::ExpectComponent;
 use dal_test::helpers::ChangeSetTestHelpers;
 use dal_test::{test, Result};
@@ -28,3 +31,67 @@ async fn arguments_for_prototype_function_execution(ctx: &mut DalContext) -> Res
     );
     Ok(())
 }
+
+#[test]
+async fn provenance_follows_arguments_to_their_values(ctx: &mut DalContext) -> Result<()> {
+    // As above, "/root/domain/name" is set by the identity function from "/root/si/name"
+    let expected = "follow me upstream";
+    let component = ExpectComponent::create_named(ctx, "swifty", expected).await;
+    let name_prop = component.prop(ctx, ["root", "domain", "name"]).await;
+    let si_name_prop = component.prop(ctx, ["root", "si", "name"]).await;
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx).await?;
+
+    let name_av_id = name_prop.attribute_value(ctx).await.id();
+    let si_name_av_id = si_name_prop.attribute_value(ctx).await.id();
+    let provenance = AttributeValueProvenance::trace(ctx, name_av_id, 1).await?;
+
+    assert_eq!(component.id(), provenance.component_id);
+    assert_eq!(Some(json!(expected)), provenance.value);
+    assert_eq!(ValueOrigin::Defaulted, provenance.origin);
+    assert_eq!("si:identity", provenance.func_name);
+    assert_eq!(1, provenance.arguments.len());
+
+    let argument = &provenance.arguments[0];
+    assert_eq!("identity", argument.name);
+    assert!(argument.is_used);
+    let ArgumentSource::Prop { prop_id, values } = &argument.source else {
+        panic!("argument should come from a prop: {:?}", argument.source);
+    };
+    assert_eq!(si_name_prop.prop().id(), *prop_id);
+    assert_eq!(1, values.len());
+    assert_eq!(si_name_av_id, values[0].attribute_value_id);
+    let upstream = values[0]
+        .provenance
+        .as_ref()
+        .expect("upstream value is traced at depth 1");
+    assert_eq!(Some(json!(expected)), upstream.value);
+
+    // Nothing is traced past the depth asked for
+    let shallow = AttributeValueProvenance::trace(ctx, name_av_id, 0).await?;
+    let ArgumentSource::Prop { values, .. } = &shallow.arguments[0].source else {
+        panic!("argument should come from a prop");
+    };
+    assert_eq!(None, values[0].provenance);
+
+    // Setting the value on the component overrides the prototype of the schema variant
+    name_prop.set(ctx, "overridden").await;
+    let provenance = AttributeValueProvenance::trace(ctx, name_av_id, 1).await?;
+    assert_eq!(ValueOrigin::Overridden, provenance.origin);
+    assert_eq!(Some(json!("overridden")), provenance.value);
+
+    Ok(())
+}
+
+#[test]
+async fn provenance_of_unknown_value_is_not_found(ctx: &mut DalContext) -> Result<()> {
+    let attribute_value_id = AttributeValueId::new();
+
+    let result = AttributeValueProvenance::trace(ctx, attribute_value_id, 1).await;
+
+    assert!(matches!(
+        result,
+        Err(AttributeValueProvenanceError::AttributeValueNotFound(id)) if id == attribute_value_id
+    ));
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use dal::change_set::approval::{ApprovalPolicy, ApprovalPolicyMatcher, ApprovalRole};
 use dal::change_set::view::OpenChangeSetsView;
 use dal::{
     context::TransactionsErrorDiscriminants, DalContext, DalContextBuilder, HistoryActor,
@@ -472,3 +473,276 @@ async fn change_set_approval_flow(ctx: &mut DalContext) {
         .collect_vec();
     assert_eq!(components.len(), 2);
 }
+
+#[test]
+async fn change_set_approval_policies(ctx: &mut DalContext) {
+    let new_change_set = ChangeSetTestHelpers::fork_from_head_change_set(ctx)
+        .await
+        .expect("could not fork head");
+    let component = create_component_for_default_schema_name(ctx, "small odd lego", "small")
+        .await
+        .expect("could not create component");
+    let schema = component.schema(ctx).await.expect("could not get schema");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update");
+
+    ApprovalPolicy::set_for_workspace(
+        ctx,
+        &[
+            ApprovalPolicy {
+                name: "views".to_owned(),
+                matcher: ApprovalPolicyMatcher::Views,
+                required_approvals: 0,
+                role: ApprovalRole::Approver,
+            },
+            ApprovalPolicy {
+                name: "legos".to_owned(),
+                matcher: ApprovalPolicyMatcher::ComponentsOfSchema {
+                    schema_id: schema.id(),
+                },
+                required_approvals: 2,
+                role: ApprovalRole::Approver,
+            },
+            ApprovalPolicy {
+                name: "secrets".to_owned(),
+                matcher: ApprovalPolicyMatcher::Secrets,
+                required_approvals: 1,
+                role: ApprovalRole::SecretManager,
+            },
+        ],
+    )
+    .await
+    .expect("could not set approval policies");
+
+    let mut change_set = ChangeSet::find(ctx, new_change_set.id)
+        .await
+        .expect("could not find change set")
+        .expect("change set is some");
+
+    // only the lego policy applies, since nothing touches secrets and views need no approval
+    let required = change_set
+        .required_approvals(ctx)
+        .await
+        .expect("could not get required approvals");
+    assert_eq!(
+        vec!["legos".to_owned()],
+        required.iter().map(|r| r.policy_name.clone()).collect_vec()
+    );
+    assert!(!required[0].is_satisfied);
+
+    change_set
+        .request_change_set_approval(ctx)
+        .await
+        .expect("could not request approval");
+    change_set
+        .approve_change_set_for_apply(ctx)
+        .await
+        .expect("could not approve");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update");
+
+    // one approval is not enough
+    let mut change_set = ChangeSet::find(ctx, new_change_set.id)
+        .await
+        .expect("could not find change set")
+        .expect("change set is some");
+    assert_eq!(change_set.status, ChangeSetStatus::NeedsApproval);
+    assert!(
+        ChangeSetTestHelpers::apply_change_set_to_base_approvals(ctx)
+            .await
+            .is_err()
+    );
+
+    // a second approver satisfies the policy
+    let original_actor = *ctx.history_actor();
+    let second_approver = create_user(ctx).await.expect("could not create user");
+    ctx.update_history_actor(HistoryActor::User(second_approver.pk()));
+    change_set
+        .approve_change_set_for_apply(ctx)
+        .await
+        .expect("could not approve");
+    ctx.update_history_actor(original_actor);
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update");
+
+    let change_set = ChangeSet::find(ctx, new_change_set.id)
+        .await
+        .expect("could not find change set")
+        .expect("change set is some");
+    assert_eq!(change_set.status, ChangeSetStatus::Approved);
+    let required = change_set
+        .required_approvals(ctx)
+        .await
+        .expect("could not get required approvals");
+    assert_eq!(2, required[0].approved_by.len());
+    assert!(required[0].is_satisfied);
+
+    ChangeSetTestHelpers::apply_change_set_to_base_approvals(ctx)
+        .await
+        .expect("could not apply to head");
+}
+
+#[test]
+async fn force_apply_respects_approval_policies_unless_overridden(ctx: &mut DalContext) {
+    let new_change_set = ChangeSetTestHelpers::fork_from_head_change_set(ctx)
+        .await
+        .expect("could not fork head");
+    create_component_for_default_schema_name(ctx, "small odd lego", "small")
+        .await
+        .expect("could not create component");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update");
+
+    ApprovalPolicy::set_for_workspace(
+        ctx,
+        &[ApprovalPolicy {
+            name: "components".to_owned(),
+            matcher: ApprovalPolicyMatcher::Components,
+            required_approvals: 2,
+            role: ApprovalRole::Approver,
+        }],
+    )
+    .await
+    .expect("could not set approval policies");
+
+    // the user forcing the apply only counts as one approver
+    let error = ChangeSet::prepare_for_force_apply(ctx, false)
+        .await
+        .expect_err("force apply should not bypass approval policies");
+    assert!(matches!(
+        error,
+        dal::ChangeSetError::ApprovalRequirementsNotMet(id) if id == new_change_set.id
+    ));
+
+    ChangeSet::prepare_for_force_apply(ctx, true)
+        .await
+        .expect("could not force apply with an override");
+    let change_set = ChangeSet::find(ctx, new_change_set.id)
+        .await
+        .expect("could not find change set")
+        .expect("change set is some");
+    assert_eq!(change_set.status, ChangeSetStatus::Approved);
+}
+
+#[test]
+async fn approvals_do_not_survive_changes(ctx: &mut DalContext) {
+    let new_change_set = ChangeSetTestHelpers::fork_from_head_change_set(ctx)
+        .await
+        .expect("could not fork head");
+    create_component_for_default_schema_name(ctx, "small odd lego", "small")
+        .await
+        .expect("could not create component");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update");
+    ApprovalPolicy::set_for_workspace(
+        ctx,
+        &[ApprovalPolicy {
+            name: "components".to_owned(),
+            matcher: ApprovalPolicyMatcher::Components,
+            required_approvals: 1,
+            role: ApprovalRole::Approver,
+        }],
+    )
+    .await
+    .expect("could not set approval policies");
+
+    let mut change_set = ChangeSet::find(ctx, new_change_set.id)
+        .await
+        .expect("could not find change set")
+        .expect("change set is some");
+    change_set
+        .request_change_set_approval(ctx)
+        .await
+        .expect("could not request approval");
+    change_set
+        .approve_change_set_for_apply(ctx)
+        .await
+        .expect("could not approve");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update");
+
+    // change the change set after it was approved
+    create_component_for_default_schema_name(ctx, "small odd lego", "sneaky")
+        .await
+        .expect("could not create component");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update");
+
+    let change_set = ChangeSet::find(ctx, new_change_set.id)
+        .await
+        .expect("could not find change set")
+        .expect("change set is some");
+    assert!(!change_set
+        .has_current_approval(ctx)
+        .await
+        .expect("could not check approvals"));
+    let error = ChangeSet::prepare_for_apply(ctx)
+        .await
+        .expect_err("a stale approval should not allow an apply");
+    assert!(matches!(
+        error,
+        dal::ChangeSetError::ChangedSinceApproval(id) if id == new_change_set.id
+    ));
+}
+
+#[test]
+async fn apply_without_approval_policies(ctx: &mut DalContext) {
+    let new_change_set = ChangeSetTestHelpers::fork_from_head_change_set(ctx)
+        .await
+        .expect("could not fork head");
+    create_component_for_default_schema_name(ctx, "small odd lego", "small")
+        .await
+        .expect("could not create component");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update");
+
+    let mut change_set = ChangeSet::find(ctx, new_change_set.id)
+        .await
+        .expect("could not find change set")
+        .expect("change set is some");
+    change_set
+        .request_change_set_approval(ctx)
+        .await
+        .expect("could not request approval");
+    change_set
+        .approve_change_set_for_apply(ctx)
+        .await
+        .expect("could not approve");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update");
+
+    // without a policy, changes after the approval (or an approval given before approvals were
+    // recorded) do not stop the apply
+    create_component_for_default_schema_name(ctx, "small odd lego", "later")
+        .await
+        .expect("could not create component");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update");
+
+    let change_set = ChangeSet::find(ctx, new_change_set.id)
+        .await
+        .expect("could not find change set")
+        .expect("change set is some");
+    assert!(change_set
+        .required_approvals(ctx)
+        .await
+        .expect("could not get required approvals")
+        .is_empty());
+    assert!(!change_set
+        .has_current_approval(ctx)
+        .await
+        .expect("could not check approvals"));
+    ChangeSet::prepare_for_apply(ctx)
+        .await
+        .expect("could not prepare for apply");
+}
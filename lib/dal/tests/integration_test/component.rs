@@ -13,9 +13,12 @@ use serde_json::json;
 
 mod debug;
 mod delete;
+mod dependent_value_graph;
 mod get_code;
 mod get_diff;
+mod portable;
 mod property_order;
+mod query;
 mod set_type;
 mod upgrade;
 
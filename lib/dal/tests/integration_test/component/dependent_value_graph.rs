@@ -0,0 +1,300 @@
+use std::collections::HashSet;
+
+use dal::attribute::value::{DependentValueGraph, DependentValueIndex};
+use dal::prop::{Prop, PropPath};
+use dal::workspace_snapshot::DependentValueRoot;
+use dal::{
+    AttributeValue, AttributeValueId, Component, ComponentId, DalContext, InputSocket, OutputSocket,
+};
+use dal_test::helpers::{
+    connect_components_with_socket_names, create_component_for_default_schema_name,
+    ChangeSetTestHelpers,
+};
+use dal_test::test;
+use pretty_assertions_sorted::assert_eq;
+
+async fn value_for_path(
+    ctx: &DalContext,
+    component_id: ComponentId,
+    path: &[&str],
+) -> AttributeValueId {
+    let variant_id = Component::schema_variant_id(ctx, component_id)
+        .await
+        .expect("find variant id for component");
+    let prop_id = Prop::find_prop_id_by_path(ctx, variant_id, &PropPath::new(path))
+        .await
+        .expect("able to find prop");
+
+    Component::attribute_values_for_prop_id(ctx, component_id, prop_id)
+        .await
+        .expect("able to get attribute values for prop")
+        .first()
+        .copied()
+        .expect("get first value id")
+}
+
+async fn wormhole_values(
+    ctx: &DalContext,
+    component_id: ComponentId,
+) -> (AttributeValueId, AttributeValueId) {
+    let rigid_designator_value_id = value_for_path(
+        ctx,
+        component_id,
+        &[
+            "root",
+            "domain",
+            "possible_world_a",
+            "wormhole_1",
+            "wormhole_2",
+            "wormhole_3",
+            "rigid_designator",
+        ],
+    )
+    .await;
+    let naming_and_necessity_value_id = value_for_path(
+        ctx,
+        component_id,
+        &[
+            "root",
+            "domain",
+            "possible_world_b",
+            "wormhole_1",
+            "wormhole_2",
+            "wormhole_3",
+            "naming_and_necessity",
+        ],
+    )
+    .await;
+
+    (rigid_designator_value_id, naming_and_necessity_value_id)
+}
+
+async fn graph_for(ctx: &DalContext, value_id: AttributeValueId) -> DependentValueGraph {
+    DependentValueGraph::new(ctx, vec![DependentValueRoot::Unfinished(value_id.into())])
+        .await
+        .expect("able to generate update graph")
+}
+
+#[test]
+async fn explains_why_values_are_in_the_graph(ctx: &mut DalContext) {
+    let component = create_component_for_default_schema_name(ctx, "starfield", "across")
+        .await
+        .expect("could not create component");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let (rigid_designator_value_id, naming_and_necessity_value_id) =
+        wormhole_values(ctx, component.id()).await;
+    let graph = graph_for(ctx, rigid_designator_value_id).await;
+
+    let chain = graph
+        .cause_chain(naming_and_necessity_value_id)
+        .expect("value is in the graph");
+    assert_eq!(Some(&rigid_designator_value_id), chain.first());
+    assert_eq!(Some(&naming_and_necessity_value_id), chain.last());
+    for value_id in &chain {
+        assert!(graph.contains_value(*value_id));
+    }
+
+    assert_eq!(
+        Some(vec![rigid_designator_value_id]),
+        graph.cause_chain(rigid_designator_value_id)
+    );
+}
+
+#[test]
+async fn graphs_built_from_the_index_match_fresh_ones(ctx: &mut DalContext) {
+    let first = create_component_for_default_schema_name(ctx, "starfield", "across")
+        .await
+        .expect("could not create component");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let (first_rigid_designator_value_id, first_naming_and_necessity_value_id) =
+        wormhole_values(ctx, first.id()).await;
+    let fresh: HashSet<_> = graph_for(ctx, first_rigid_designator_value_id)
+        .await
+        .all_value_ids()
+        .into_iter()
+        .collect();
+    let indexed: HashSet<_> = graph_for(ctx, first_rigid_designator_value_id)
+        .await
+        .all_value_ids()
+        .into_iter()
+        .collect();
+    assert_eq!(fresh, indexed);
+
+    // A new component changes the snapshot, but not what the values of the first one depend on
+    let second = create_component_for_default_schema_name(ctx, "starfield", "the universe")
+        .await
+        .expect("could not create component");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let after_change: HashSet<_> = graph_for(ctx, first_rigid_designator_value_id)
+        .await
+        .all_value_ids()
+        .into_iter()
+        .collect();
+    assert_eq!(fresh, after_change);
+
+    let (second_rigid_designator_value_id, second_naming_and_necessity_value_id) =
+        wormhole_values(ctx, second.id()).await;
+    let second_graph = graph_for(ctx, second_rigid_designator_value_id).await;
+    assert!(second_graph.contains_value(second_naming_and_necessity_value_id));
+    assert!(!second_graph.contains_value(first_naming_and_necessity_value_id));
+}
+
+#[test]
+async fn changes_across_a_connection_invalidate_the_index(ctx: &mut DalContext) {
+    let pet_shop = create_component_for_default_schema_name(ctx, "pet_shop", "Petopia")
+        .await
+        .expect("could not create component");
+    let pirate = create_component_for_default_schema_name(ctx, "pirate", "Long John Silver")
+        .await
+        .expect("could not create component");
+    connect_components_with_socket_names(
+        ctx,
+        pet_shop.id(),
+        "parrot_names",
+        pirate.id(),
+        "parrot_names",
+    )
+    .await
+    .expect("could not connect components with socket names");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let output_socket_id = OutputSocket::find_with_name_or_error(
+        ctx,
+        "parrot_names",
+        Component::schema_variant_id(ctx, pet_shop.id())
+            .await
+            .expect("find variant id for component"),
+    )
+    .await
+    .expect("could not find output socket")
+    .id();
+    let output_value_id = OutputSocket::component_attribute_value_for_output_socket_id(
+        ctx,
+        output_socket_id,
+        pet_shop.id(),
+    )
+    .await
+    .expect("could not find output socket value");
+    let input_socket_id = InputSocket::find_with_name_or_error(
+        ctx,
+        "parrot_names",
+        Component::schema_variant_id(ctx, pirate.id())
+            .await
+            .expect("find variant id for component"),
+    )
+    .await
+    .expect("could not find input socket")
+    .id();
+    let input_value_id = InputSocket::component_attribute_value_for_input_socket_id(
+        ctx,
+        input_socket_id,
+        pirate.id(),
+    )
+    .await
+    .expect("could not find input socket value");
+
+    // Data does not flow out of a component that is set to be deleted into one that is not
+    pet_shop
+        .set_to_delete(ctx, true)
+        .await
+        .expect("could not set component to delete");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+    assert!(!graph_for(ctx, output_value_id)
+        .await
+        .contains_value(input_value_id));
+
+    // Setting the pirate to be deleted only changes the pirate, but the pet shop looked at it
+    // when walking its output socket, so what the index kept for the pet shop has to go
+    let pirate = pirate
+        .set_to_delete(ctx, true)
+        .await
+        .expect("could not set component to delete");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+    assert!(graph_for(ctx, output_value_id)
+        .await
+        .contains_value(input_value_id));
+
+    // And back again, starting from what the index kept for the graph just built
+    pirate
+        .set_to_delete(ctx, false)
+        .await
+        .expect("could not set component to delete");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+    assert!(!graph_for(ctx, output_value_id)
+        .await
+        .contains_value(input_value_id));
+}
+
+#[test]
+async fn the_index_is_stored_and_moved_along_with_the_change_set(ctx: &mut DalContext) {
+    let component = create_component_for_default_schema_name(ctx, "starfield", "across")
+        .await
+        .expect("could not create component");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    // The dependent values update stored the index, and the rebaser moved it on to the snapshot
+    // the update wrote
+    let address = ctx
+        .workspace_snapshot()
+        .expect("could not get workspace snapshot")
+        .id()
+        .await;
+    assert_eq!(
+        Some(address),
+        DependentValueIndex::stored_address(ctx)
+            .await
+            .expect("could not get stored address")
+    );
+
+    let (rigid_designator_value_id, naming_and_necessity_value_id) =
+        wormhole_values(ctx, component.id()).await;
+    AttributeValue::update(
+        ctx,
+        rigid_designator_value_id,
+        Some(serde_json::json!("hesperus")),
+    )
+    .await
+    .expect("could not update value");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let address = ctx
+        .workspace_snapshot()
+        .expect("could not get workspace snapshot")
+        .id()
+        .await;
+    assert_eq!(
+        Some(address),
+        DependentValueIndex::stored_address(ctx)
+            .await
+            .expect("could not get stored address")
+    );
+
+    // Why the update recomputed a value can be looked up afterwards
+    let chain = DependentValueIndex::cause_chain(ctx, naming_and_necessity_value_id)
+        .await
+        .expect("could not get cause chain")
+        .expect("value was recomputed");
+    assert_eq!(Some(&rigid_designator_value_id), chain.first());
+    assert_eq!(Some(&naming_and_necessity_value_id), chain.last());
+}
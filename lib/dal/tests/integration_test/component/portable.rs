@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+
+use dal::attribute::prototype::argument::value_source::ValueSource;
+use dal::attribute::prototype::argument::AttributePrototypeArgument;
+use dal::component::frame::Frame;
+use dal::component::portable::{
+    PortableComponents, UnportableAttribute, UnportableAttributeReason,
+};
+use dal::func::argument::FuncArgument;
+use dal::func::binding::attribute::AttributeBinding;
+use dal::func::binding::{
+    AttributeArgumentBinding, AttributeFuncArgumentSource, AttributeFuncDestination, EventualParent,
+};
+use dal::prop::PropPath;
+use dal::{
+    AttributePrototype, AttributeValue, Component, ComponentId, ComponentType, DalContext, Func,
+    FuncId, Prop, PropId, SchemaVariantId,
+};
+use dal_test::helpers::{
+    connect_components_with_socket_names, create_component_for_default_schema_name,
+    create_component_for_schema_name_with_type, update_attribute_value_for_component,
+    ChangeSetTestHelpers,
+};
+use dal_test::test;
+use pretty_assertions_sorted::assert_eq;
+use serde_json::json;
+use si_pkg::{AttrFuncInputSpec, AttributeValuePath, ComponentSpecVariant};
+
+async fn components_by_name(ctx: &DalContext) -> HashMap<String, Component> {
+    let mut components = HashMap::new();
+    for component in Component::list(ctx)
+        .await
+        .expect("could not list components")
+    {
+        components.insert(
+            component.name(ctx).await.expect("could not get name"),
+            component,
+        );
+    }
+    components
+}
+
+async fn create_golden_layout(ctx: &mut DalContext) -> ComponentId {
+    let mut vault = create_component_for_schema_name_with_type(
+        ctx,
+        "fallout",
+        "vault",
+        ComponentType::ConfigurationFrameDown,
+    )
+    .await
+    .expect("could not create component");
+    vault
+        .set_geometry(ctx, "100", "200", Some("500"), Some("400"))
+        .await
+        .expect("could not set geometry");
+    update_attribute_value_for_component(ctx, vault.id(), &["root", "domain", "rads"], json!(5))
+        .await
+        .expect("could not update attribute value");
+
+    let pet_shop = create_component_for_default_schema_name(ctx, "pet_shop", "petopia")
+        .await
+        .expect("could not create component");
+    let pirate = create_component_for_default_schema_name(ctx, "pirate", "long john")
+        .await
+        .expect("could not create component");
+    for child_id in [pet_shop.id(), pirate.id()] {
+        Frame::upsert_parent(ctx, child_id, vault.id())
+            .await
+            .expect("could not upsert parent");
+    }
+    connect_components_with_socket_names(
+        ctx,
+        pet_shop.id(),
+        "parrot_names",
+        pirate.id(),
+        "parrot_names",
+    )
+    .await
+    .expect("could not connect components");
+
+    // Connected to the selection, but not part of it
+    let outsider = create_component_for_default_schema_name(ctx, "pet_shop", "outsider")
+        .await
+        .expect("could not create component");
+    connect_components_with_socket_names(
+        ctx,
+        outsider.id(),
+        "parrot_names",
+        pirate.id(),
+        "parrot_names",
+    )
+    .await
+    .expect("could not connect components");
+
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    vault.id()
+}
+
+#[test]
+async fn copies_a_frame_into_another_change_set(ctx: &mut DalContext) {
+    let vault_id = create_golden_layout(ctx).await;
+    let document = PortableComponents::export(ctx, &[vault_id])
+        .await
+        .expect("could not export components");
+    assert_eq!(3, document.components.len());
+
+    // The document is what gets stored and carried around
+    let document: PortableComponents = serde_json::from_value(
+        serde_json::to_value(&document).expect("could not serialize document"),
+    )
+    .expect("could not deserialize document");
+
+    ChangeSetTestHelpers::fork_from_head_change_set(ctx)
+        .await
+        .expect("could not fork change set");
+    let report = document
+        .import(ctx)
+        .await
+        .expect("could not import components");
+    assert!(report.missing_schema_variants.is_empty());
+    assert!(report.skipped_components.is_empty());
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let components = components_by_name(ctx).await;
+    let mut names: Vec<&str> = components.keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(vec!["long john", "petopia", "vault"], names);
+
+    let vault = &components["vault"];
+    assert_eq!(
+        ComponentType::ConfigurationFrameDown,
+        vault.get_type(ctx).await.expect("could not get type")
+    );
+    let geometry = vault
+        .geometry(ctx)
+        .await
+        .expect("could not get geometry")
+        .into_raw();
+    assert_eq!(
+        (
+            "100".to_string(),
+            "200".to_string(),
+            Some("500".to_string()),
+            Some("400".to_string())
+        ),
+        (geometry.x, geometry.y, geometry.width, geometry.height)
+    );
+    let rads = vault
+        .attribute_value_for_prop(ctx, &["root", "domain", "rads"])
+        .await
+        .expect("could not find attribute value");
+    assert_eq!(
+        Some(json!(5)),
+        AttributeValue::get_by_id(ctx, rads)
+            .await
+            .expect("could not get attribute value")
+            .value(ctx)
+            .await
+            .expect("could not get value")
+    );
+
+    let pet_shop = &components["petopia"];
+    let pirate = &components["long john"];
+    for child in [pet_shop, pirate] {
+        assert_eq!(
+            Some(vault.id()),
+            child.parent(ctx).await.expect("could not get parent")
+        );
+    }
+    let incoming = pirate
+        .incoming_connections(ctx)
+        .await
+        .expect("could not get incoming connections");
+    assert_eq!(
+        vec![pet_shop.id()],
+        incoming
+            .iter()
+            .map(|connection| connection.from_component_id)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+async fn reports_missing_schema_variants(ctx: &mut DalContext) {
+    let vault_id = create_golden_layout(ctx).await;
+    let mut document = PortableComponents::export(ctx, &[vault_id])
+        .await
+        .expect("could not export components");
+
+    let mut pet_shop_unique_id = None;
+    for spec in &mut document.components {
+        if let ComponentSpecVariant::SchemaVariant {
+            schema_name,
+            variant_version,
+            ..
+        } = &mut spec.variant
+        {
+            if schema_name == "pet_shop" {
+                *variant_version = "from-the-future".to_string();
+                pet_shop_unique_id = Some(spec.unique_id.clone());
+            }
+        }
+    }
+    let pet_shop_unique_id = pet_shop_unique_id.expect("pet shop was not exported");
+
+    ChangeSetTestHelpers::fork_from_head_change_set(ctx)
+        .await
+        .expect("could not fork change set");
+    let report = document
+        .import(ctx)
+        .await
+        .expect("could not import components");
+
+    assert_eq!(vec![pet_shop_unique_id], report.skipped_components);
+    assert_eq!(
+        vec!["pet_shop"],
+        report
+            .missing_schema_variants
+            .iter()
+            .map(|missing| missing.schema_name.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(2, report.component_ids.len());
+
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+    let components = components_by_name(ctx).await;
+    assert!(components["long john"]
+        .incoming_connections(ctx)
+        .await
+        .expect("could not get incoming connections")
+        .is_empty());
+}
+
+async fn prop_id(ctx: &DalContext, schema_variant_id: SchemaVariantId, path: &[&str]) -> PropId {
+    Prop::find_prop_id_by_path(ctx, schema_variant_id, &PropPath::new(path))
+        .await
+        .expect("could not find prop")
+}
+
+async fn set_payload_func(
+    ctx: &DalContext,
+    component_id: ComponentId,
+    func_id: FuncId,
+    destination: &[&str],
+    source: AttributeFuncArgumentSource,
+) {
+    let schema_variant_id = Component::schema_variant_id(ctx, component_id)
+        .await
+        .expect("could not get schema variant id");
+    let payload = FuncArgument::find_by_name_for_func(ctx, "payload", func_id)
+        .await
+        .expect("could not find func argument")
+        .expect("func argument not found");
+    AttributeBinding::upsert_attribute_binding(
+        ctx,
+        func_id,
+        Some(EventualParent::Component(component_id)),
+        AttributeFuncDestination::Prop(prop_id(ctx, schema_variant_id, destination).await),
+        vec![AttributeArgumentBinding {
+            func_argument_id: payload.id,
+            attribute_prototype_argument_id: None,
+            attribute_func_input_location: source,
+        }],
+    )
+    .await
+    .expect("could not set attribute func on component");
+}
+
+#[test]
+async fn copies_attribute_funcs_set_on_a_component(ctx: &mut DalContext) {
+    let component = create_component_for_default_schema_name(ctx, "starfield", "constellation")
+        .await
+        .expect("could not create component");
+    let schema_variant_id = Component::schema_variant_id(ctx, component.id())
+        .await
+        .expect("could not get schema variant id");
+    let func_id = Func::find_id_by_name(ctx, "test:resourcePayloadToValue")
+        .await
+        .expect("could not find func")
+        .expect("func not found");
+    let name_prop_id = prop_id(ctx, schema_variant_id, &["root", "domain", "name"]).await;
+    set_payload_func(
+        ctx,
+        component.id(),
+        func_id,
+        &["root", "domain", "freestar"],
+        AttributeFuncArgumentSource::Prop(name_prop_id),
+    )
+    .await;
+    set_payload_func(
+        ctx,
+        component.id(),
+        func_id,
+        &["root", "domain", "hidden_prop"],
+        AttributeFuncArgumentSource::StaticArgument(json!({ "sol": "system" })),
+    )
+    .await;
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let document = PortableComponents::export(ctx, &[component.id()])
+        .await
+        .expect("could not export components");
+    let freestar = document.components[0]
+        .attributes
+        .iter()
+        .find(|attribute| {
+            matches!(
+                &attribute.path,
+                AttributeValuePath::Prop { path, .. } if path == "/root/domain/freestar"
+            )
+        })
+        .expect("func set on freestar was not exported");
+    assert_eq!("test:resourcePayloadToValue", freestar.func_unique_id);
+    assert_eq!(
+        vec![AttrFuncInputSpec::Prop {
+            name: "payload".to_string(),
+            prop_path: PropPath::new(["root", "domain", "name"]).into(),
+            unique_id: None,
+            deleted: false,
+        }],
+        freestar.inputs
+    );
+    let unportable_attributes = vec![UnportableAttribute {
+        component_unique_id: component.id().to_string(),
+        path: "/root/domain/hidden_prop".to_string(),
+        func_name: "test:resourcePayloadToValue".to_string(),
+        reason: UnportableAttributeReason::ArgumentSource {
+            argument: "payload".to_string(),
+        },
+    }];
+    assert_eq!(unportable_attributes, document.unportable_attributes);
+
+    ChangeSetTestHelpers::fork_from_head_change_set(ctx)
+        .await
+        .expect("could not fork change set");
+    let report = document
+        .import(ctx)
+        .await
+        .expect("could not import components");
+    assert_eq!(unportable_attributes, report.unportable_attributes);
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let copy_id = report.component_ids[&component.id().to_string()];
+    let freestar_value_id =
+        Component::attribute_value_for_prop_by_id(ctx, copy_id, &["root", "domain", "freestar"])
+            .await
+            .expect("could not find attribute value");
+    let prototype_id = AttributeValue::component_prototype_id(ctx, freestar_value_id)
+        .await
+        .expect("could not get component prototype")
+        .expect("func was not set on the copy");
+    assert_eq!(
+        func_id,
+        AttributePrototype::func_id(ctx, prototype_id)
+            .await
+            .expect("could not get func id")
+    );
+    let mut sources = vec![];
+    for apa_id in AttributePrototypeArgument::list_ids_for_prototype(ctx, prototype_id)
+        .await
+        .expect("could not list arguments")
+    {
+        sources.push(
+            AttributePrototypeArgument::value_source_by_id(ctx, apa_id)
+                .await
+                .expect("could not get value source"),
+        );
+    }
+    assert_eq!(vec![Some(ValueSource::Prop(name_prop_id))], sources);
+
+    let hidden_prop_value_id =
+        Component::attribute_value_for_prop_by_id(ctx, copy_id, &["root", "domain", "hidden_prop"])
+            .await
+            .expect("could not find attribute value");
+    assert!(
+        AttributeValue::component_prototype_id(ctx, hidden_prop_value_id)
+            .await
+            .expect("could not get component prototype")
+            .is_none()
+    );
+}
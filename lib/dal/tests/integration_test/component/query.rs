@@ -0,0 +1,199 @@
+use dal::component::query::{ComponentQuery, ValueMatch};
+use dal::{ComponentId, DalContext};
+use dal_test::helpers::{
+    connect_components_with_socket_names, create_component_for_default_schema_name,
+    update_attribute_value_for_component, ChangeSetTestHelpers,
+};
+use dal_test::test;
+use pretty_assertions_sorted::assert_eq;
+use serde_json::json;
+
+fn name(value: ValueMatch) -> ComponentQuery {
+    ComponentQuery::Prop {
+        path: "/root/si/name".to_string(),
+        value,
+    }
+}
+
+async fn run(ctx: &DalContext, query: &ComponentQuery) -> Vec<ComponentId> {
+    query.run(ctx).await.expect("could not run query")
+}
+
+#[test]
+async fn query_by_schema_and_prop_values(ctx: &DalContext) {
+    let mut ids = vec![];
+    for (schema_name, name) in [
+        ("starfield", "alpha-1"),
+        ("starfield", "alpha-2"),
+        ("starfield", "beta"),
+        ("fallout", "alpha-3"),
+    ] {
+        ids.push(
+            create_component_for_default_schema_name(ctx, schema_name, name)
+                .await
+                .expect("could not create component")
+                .id(),
+        );
+    }
+    let (alpha_1, alpha_2, beta, alpha_3) = (ids[0], ids[1], ids[2], ids[3]);
+
+    let starfield_alphas = ComponentQuery::All {
+        queries: vec![
+            ComponentQuery::Schema {
+                name: "starfield".to_string(),
+            },
+            name(ValueMatch::Prefix("alpha".to_string())),
+        ],
+    };
+    let mut expected = vec![alpha_1, alpha_2];
+    expected.sort();
+    assert_eq!(expected, run(ctx, &starfield_alphas).await);
+
+    let mut expected = vec![alpha_3, beta];
+    expected.sort();
+    assert_eq!(
+        expected,
+        run(
+            ctx,
+            &ComponentQuery::Any {
+                queries: vec![
+                    name(ValueMatch::Regex("^b".to_string())),
+                    name(ValueMatch::Equals(json!("alpha-3"))),
+                ],
+            }
+        )
+        .await
+    );
+
+    let mut expected = vec![alpha_2, beta, alpha_3];
+    expected.sort();
+    assert_eq!(
+        expected,
+        run(
+            ctx,
+            &ComponentQuery::Not {
+                query: Box::new(name(ValueMatch::Equals(json!("alpha-1")))),
+            }
+        )
+        .await
+    );
+
+    // Changing a value makes the cached index stale
+    update_attribute_value_for_component(ctx, beta, &["root", "si", "name"], json!("alpha-4"))
+        .await
+        .expect("could not update name");
+    let mut expected = vec![alpha_1, alpha_2, beta];
+    expected.sort();
+    assert_eq!(expected, run(ctx, &starfield_alphas).await);
+}
+
+#[test]
+async fn query_index_follows_written_changes(ctx: &mut DalContext) {
+    let alpha = create_component_for_default_schema_name(ctx, "starfield", "alpha")
+        .await
+        .expect("could not create component")
+        .id();
+    let beta = create_component_for_default_schema_name(ctx, "starfield", "beta")
+        .await
+        .expect("could not create component")
+        .id();
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let alphas = name(ValueMatch::Prefix("alpha".to_string()));
+    assert_eq!(vec![alpha], run(ctx, &alphas).await);
+
+    // The index was kept for the snapshot before this change, so the changed component has to
+    // be read again once the change is written
+    update_attribute_value_for_component(ctx, beta, &["root", "si", "name"], json!("alpha-2"))
+        .await
+        .expect("could not update name");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let mut expected = vec![alpha, beta];
+    expected.sort();
+    assert_eq!(expected, run(ctx, &alphas).await);
+
+    // A change that is never written does not stick in the index
+    update_attribute_value_for_component(ctx, alpha, &["root", "si", "name"], json!("gamma"))
+        .await
+        .expect("could not update name");
+    assert_eq!(vec![beta], run(ctx, &alphas).await);
+    ctx.workspace_snapshot()
+        .expect("could not get workspace snapshot")
+        .revert()
+        .await;
+    assert_eq!(expected, run(ctx, &alphas).await);
+}
+
+#[test]
+async fn query_index_follows_values_computed_elsewhere(ctx: &mut DalContext) {
+    let vault = create_component_for_default_schema_name(ctx, "fallout", "vault")
+        .await
+        .expect("could not create component")
+        .id();
+    let ship = create_component_for_default_schema_name(ctx, "starfield", "ship")
+        .await
+        .expect("could not create component")
+        .id();
+    connect_components_with_socket_names(ctx, vault, "bethesda", ship, "bethesda")
+        .await
+        .expect("could not connect components");
+    update_attribute_value_for_component(
+        ctx,
+        vault,
+        &["root", "domain", "special"],
+        json!("before"),
+    )
+    .await
+    .expect("could not update special");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let attributes = |value: &str| ComponentQuery::Prop {
+        path: "/root/domain/attributes".to_string(),
+        value: ValueMatch::Equals(json!(value)),
+    };
+    assert_eq!(vec![ship], run(ctx, &attributes("before")).await);
+
+    // The ship's attributes come through its connection to the vault, so changing the vault in
+    // another context only changes the ship's values, when the dependent values update runs
+    let mut other_ctx = ctx.clone();
+    other_ctx
+        .update_snapshot_to_visibility()
+        .await
+        .expect("could not update snapshot to visibility");
+    update_attribute_value_for_component(
+        &other_ctx,
+        vault,
+        &["root", "domain", "special"],
+        json!("after"),
+    )
+    .await
+    .expect("could not update special");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(&mut other_ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    ctx.update_snapshot_to_visibility()
+        .await
+        .expect("could not update snapshot to visibility");
+    assert!(run(ctx, &attributes("before")).await.is_empty());
+    assert_eq!(vec![ship], run(ctx, &attributes("after")).await);
+}
+
+#[test]
+async fn query_rejects_invalid_regex(ctx: &DalContext) {
+    create_component_for_default_schema_name(ctx, "starfield", "alpha")
+        .await
+        .expect("could not create component");
+
+    assert!(name(ValueMatch::Regex("(".to_string()))
+        .run(ctx)
+        .await
+        .is_err());
+}
@@ -13,6 +13,7 @@ use dal_test::test;
 use itertools::Itertools;
 use pretty_assertions_sorted::{assert_eq, assert_ne};
 use serde_json::json;
+use si_pkg::{PropSpecDiff, PropSpecKind};
 use std::collections::VecDeque;
 // TODO test that validates that components that exist on locked variants aren't auto upgraded, but can be upgraded manually
 
@@ -874,6 +875,224 @@ async fn upgrade_frame_with_child(ctx: &mut DalContext) {
     );
 }
 
+#[test]
+async fn upgrade_diff_for_locked_variant(ctx: &mut DalContext) {
+    let variant_zero = VariantAuthoringClient::create_schema_and_variant(
+        ctx,
+        "diffTestAsset",
+        None,
+        None,
+        "Integration Tests",
+        "#00b0b0",
+    )
+    .await
+    .expect("Unable to create new asset");
+    save_schema_variant_code(
+        ctx,
+        variant_zero.clone(),
+        "function main() {
+            const removed = new PropBuilder().setName(\"removed\").setKind(\"string\").build();
+            return new AssetBuilder().addProp(removed).build();
+        }",
+    )
+    .await;
+
+    let component = create_component_for_default_schema_name(ctx, "diffTestAsset", "diff")
+        .await
+        .expect("could not create component");
+    assert_eq!(
+        None,
+        component
+            .upgrade_diff(ctx)
+            .await
+            .expect("could not get upgrade diff")
+    );
+
+    // Lock the variant the component is on, then change the props on an unlocked copy
+    SchemaVariant::get_by_id_or_error(ctx, variant_zero.id())
+        .await
+        .expect("could not get variant")
+        .lock(ctx)
+        .await
+        .expect("could not lock variant");
+    let variant_one = VariantAuthoringClient::create_unlocked_variant_copy(ctx, variant_zero.id())
+        .await
+        .expect("could not create unlocked copy");
+    save_schema_variant_code(
+        ctx,
+        variant_one,
+        "function main() {
+            const added = new PropBuilder().setName(\"added\").setKind(\"integer\").build();
+            return new AssetBuilder().addProp(added).build();
+        }",
+    )
+    .await;
+
+    let upgrade_diff = component
+        .upgrade_diff(ctx)
+        .await
+        .expect("could not get upgrade diff")
+        .expect("component should be upgradable");
+    assert_eq!(1, upgrade_diff.schemas.len());
+    assert_eq!(
+        vec![
+            PropSpecDiff::Added {
+                path: "root/domain/added".to_owned(),
+                kind: PropSpecKind::Number,
+            },
+            PropSpecDiff::Removed {
+                path: "root/domain/removed".to_owned(),
+                kind: PropSpecKind::String,
+            },
+        ],
+        upgrade_diff.schemas[0].props
+    );
+}
+
+#[test]
+async fn bulk_upgrade_with_dry_run(ctx: &mut DalContext) {
+    let variant_zero = VariantAuthoringClient::create_schema_and_variant(
+        ctx,
+        "bulkUpgradeAsset",
+        None,
+        None,
+        "Integration Tests",
+        "#00b0b0",
+    )
+    .await
+    .expect("Unable to create new asset");
+    save_schema_variant_code(
+        ctx,
+        variant_zero.clone(),
+        "function main() {
+            const kept = new PropBuilder().setName(\"kept\").setKind(\"string\").build();
+            const removed = new PropBuilder().setName(\"removed\").setKind(\"string\").build();
+            const changed = new PropBuilder().setName(\"changed\").setKind(\"string\").build();
+            return new AssetBuilder().addProp(kept).addProp(removed).addProp(changed).build();
+        }",
+    )
+    .await;
+
+    let mut component_ids = vec![];
+    for name in ["first", "second"] {
+        let component = create_component_for_default_schema_name(ctx, "bulkUpgradeAsset", name)
+            .await
+            .expect("could not create component");
+        let domain_av_id = component
+            .domain_prop_attribute_value(ctx)
+            .await
+            .expect("able to get domain prop");
+        AttributeValue::update(
+            ctx,
+            domain_av_id,
+            Some(json!({ "kept": name, "removed": name, "changed": name })),
+        )
+        .await
+        .expect("update failed");
+        component_ids.push(component.id());
+    }
+
+    SchemaVariant::get_by_id_or_error(ctx, variant_zero.id())
+        .await
+        .expect("could not get variant")
+        .lock(ctx)
+        .await
+        .expect("could not lock variant");
+    let variant_one = VariantAuthoringClient::create_unlocked_variant_copy(ctx, variant_zero.id())
+        .await
+        .expect("could not create unlocked copy");
+    save_schema_variant_code(
+        ctx,
+        variant_one.clone(),
+        "function main() {
+            const kept = new PropBuilder().setName(\"kept\").setKind(\"string\").build();
+            const changed = new PropBuilder().setName(\"changed\").setKind(\"integer\").build();
+            return new AssetBuilder().addProp(kept).addProp(changed).build();
+        }",
+    )
+    .await;
+    let variant_one_id = SchemaVariant::get_unlocked_for_schema(
+        ctx,
+        variant_zero.schema(ctx).await.expect("get schema").id(),
+    )
+    .await
+    .expect("could not get unlocked variant")
+    .expect("has an unlocked variant")
+    .id();
+
+    // A dry run reports on every component of the schema without upgrading any of them
+    let reports = Component::bulk_upgrade(ctx, variant_one_id, None, true)
+        .await
+        .expect("could not preview upgrade");
+    assert_eq!(2, reports.len());
+    for report in &reports {
+        assert!(component_ids.contains(&report.component_id));
+        assert_eq!(variant_zero.id(), report.from_schema_variant_id);
+        assert_eq!(variant_one_id, report.to_schema_variant_id);
+        assert!(report
+            .props_preserved
+            .contains(&"root/domain/kept".to_owned()));
+        assert_eq!(vec!["root/domain/removed".to_owned()], report.props_dropped);
+        assert_eq!(
+            vec!["root/domain/changed".to_owned()],
+            report.props_reset_to_default
+        );
+        assert!(report.connections_lost.is_empty());
+    }
+    for &component_id in &component_ids {
+        assert_eq!(
+            variant_zero.id(),
+            Component::schema_variant_id(ctx, component_id)
+                .await
+                .expect("get schema variant id")
+        );
+    }
+
+    // Upgrading only the selected component leaves the other one alone
+    let reports = Component::bulk_upgrade(ctx, variant_one_id, Some(vec![component_ids[0]]), false)
+        .await
+        .expect("could not upgrade components");
+    assert_eq!(1, reports.len());
+    assert_eq!(
+        variant_one_id,
+        Component::schema_variant_id(ctx, component_ids[0])
+            .await
+            .expect("get schema variant id")
+    );
+    assert_eq!(
+        variant_zero.id(),
+        Component::schema_variant_id(ctx, component_ids[1])
+            .await
+            .expect("get schema variant id")
+    );
+
+    // The rest of the schema's components are upgraded when there is no selection
+    let reports = Component::bulk_upgrade(ctx, variant_one_id, None, false)
+        .await
+        .expect("could not upgrade components");
+    assert_eq!(
+        vec![component_ids[1]],
+        reports
+            .iter()
+            .map(|report| report.component_id)
+            .collect_vec()
+    );
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("could not commit and update snapshot to visibility");
+
+    let upgraded = Component::get_by_id(ctx, component_ids[1])
+        .await
+        .expect("could not get component");
+    let domain = upgraded
+        .view(ctx)
+        .await
+        .expect("could not get view")
+        .and_then(|view| view.get("domain").cloned())
+        .expect("has a domain");
+    assert_eq!(json!({ "kept": "second" }), domain);
+}
+
 async fn update_schema_variant_component_type(
     ctx: &mut DalContext,
     variant: ExpectSchemaVariant,
@@ -926,3 +1145,27 @@ async fn update_schema_variant_description(
         .expect("could not get updated variant")
         .into()
 }
+
+async fn save_schema_variant_code(ctx: &mut DalContext, variant: SchemaVariant, code: &str) {
+    let schema = variant
+        .schema(ctx)
+        .await
+        .expect("Unable to get the schema for the variant");
+    VariantAuthoringClient::save_variant_content(
+        ctx,
+        variant.id(),
+        schema.name.clone(),
+        variant.display_name(),
+        variant.category(),
+        variant.description(),
+        variant.link(),
+        variant.color(),
+        variant.component_type(),
+        Some(code.to_owned()),
+    )
+    .await
+    .expect("save variant contents");
+    VariantAuthoringClient::regenerate_variant(ctx, variant.id())
+        .await
+        .expect("unable to regenerate variant");
+}
@@ -0,0 +1,113 @@
+use dal::DalContext;
+use dal_test::fixture::{ExpectedState, Fixture};
+use dal_test::test;
+use pretty_assertions_sorted::assert_eq;
+
+#[test]
+async fn loads_and_asserts_a_fixture(ctx: &mut DalContext) {
+    let loaded = Fixture::from_yaml(
+        r#"
+secrets:
+  - name: todd
+    definition: dummy
+    value: { value: todd }
+components:
+  - name: vault
+    schema: fallout
+    type: configurationFrameDown
+  - name: source
+    schema: dummy-secret
+    secrets:
+      dummy: todd
+  - name: destination
+    schema: fallout
+    parent: vault
+    props:
+      /root/domain/special: boom
+      /root/domain/rads: 5
+connections:
+  - from: source
+    output: dummy
+    to: destination
+    input: dummy
+"#,
+    )
+    .load(ctx)
+    .await;
+
+    ExpectedState::from_yaml(
+        r#"
+components:
+  vault:
+    schema: fallout
+    type: configurationFrameDown
+  destination:
+    parent: vault
+    props:
+      /root/domain/name: destination
+      /root/domain/special: boom
+      /root/domain/rads: 5
+      /root/domain/active: true
+    secrets:
+      dummy: todd
+connections:
+  - from: source
+    output: dummy
+    to: destination
+    input: dummy
+"#,
+    )
+    .assert(ctx, &loaded)
+    .await;
+
+    // Everything is also there to use from code
+    assert_eq!(
+        Some(serde_json::json!("boom")),
+        loaded
+            .component("destination")
+            .prop(ctx, ["root", "domain", "special"])
+            .await
+            .view(ctx)
+            .await
+    );
+}
+
+#[test]
+async fn json_fixtures_match_yaml_ones(ctx: &mut DalContext) {
+    let yaml = Fixture::from_yaml(
+        r#"
+components:
+  - name: vault
+    schema: fallout
+    type: configurationFrameDown
+    props:
+      /root/domain/rads: 5
+"#,
+    );
+    let json = Fixture::from_json(
+        r#"{
+            "components": [{
+                "name": "vault",
+                "schema": "fallout",
+                "type": "configurationFrameDown",
+                "props": { "/root/domain/rads": 5 }
+            }]
+        }"#,
+    );
+    assert_eq!(yaml, json);
+
+    let loaded = json.load(ctx).await;
+    ExpectedState::from_json(
+        r#"{
+            "components": {
+                "vault": {
+                    "type": "configurationFrameDown",
+                    "props": { "/root/domain/name": "vault", "/root/domain/special": null }
+                }
+            },
+            "connections": []
+        }"#,
+    )
+    .assert(ctx, &loaded)
+    .await;
+}
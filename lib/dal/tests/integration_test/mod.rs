@@ -9,6 +9,7 @@ mod cycle_check_guard;
 mod dependent_values_update;
 mod deserialize;
 mod diagram;
+mod fixture;
 mod frame;
 mod func;
 mod input_sources;
@@ -25,3 +26,4 @@ mod schema;
 mod secret;
 mod validations;
 mod workspace;
+mod workspace_snapshot;
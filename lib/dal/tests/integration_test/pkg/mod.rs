@@ -1,9 +1,16 @@
+use dal::module::Module;
 use dal::pkg::export::PkgExporter;
-use dal::pkg::{import_pkg_from_pkg, ImportOptions};
+use dal::pkg::{
+    import_pkg_from_pkg, import_pkgs_with_dependencies, installed_module_satisfies, ImportOptions,
+    PkgError, PkgSignaturePolicy, PkgSignatureVerifier,
+};
 use dal::schema::variant::authoring::VariantAuthoringClient;
 use dal::{DalContext, FuncBackendKind, FuncBackendResponseType};
 use dal_test::test;
-use si_pkg::{FuncSpec, FuncSpecData, PkgSpec, SchemaSpec, SchemaSpecData, SiPkg};
+use si_pkg::{
+    DependencySpec, FuncSpec, FuncSpecData, PkgSpec, SchemaSpec, SchemaSpecData, SiPkg,
+    SiPkgSignatureError, SiPkgSigningKey, SiPkgTrustStore,
+};
 
 #[test]
 async fn import_pkg_from_pkg_set_latest_default(ctx: &mut DalContext) {
@@ -111,3 +118,203 @@ async fn import_pkg_from_pkg_set_latest_default(ctx: &mut DalContext) {
         Some(variants.pop().expect("should pop"))
     );
 }
+
+#[test]
+async fn import_pkg_from_pkg_requires_trusted_signature(ctx: &DalContext) {
+    let signing_key = SiPkgSigningKey::generate();
+    let trust_store = SiPkgTrustStore::from_public_keys([signing_key.public_key()])
+        .expect("should build trust store");
+
+    let mut services_context = ctx.services_context();
+    services_context.set_pkg_signature_verifier(PkgSignatureVerifier::new(
+        PkgSignaturePolicy::Require,
+        trust_store,
+    ));
+    let require_ctx = services_context
+        .into_builder(false)
+        .build(ctx.access_builder().build(*ctx.visibility()))
+        .await
+        .expect("should build ctx");
+
+    let func_spec = FuncSpec::builder()
+        .name("signed")
+        .unique_id("signed")
+        .data(
+            FuncSpecData::builder()
+                .name("signed")
+                .backend_kind(FuncBackendKind::JsAttribute)
+                .response_type(FuncBackendResponseType::String)
+                .handler("main")
+                .code_plaintext("function main() { return \"signed\"; }")
+                .build()
+                .expect("should build data"),
+        )
+        .build()
+        .expect("should build func spec");
+    let pkg_spec = PkgSpec::builder()
+        .name("signed")
+        .created_by("sally@systeminit.com")
+        .func(func_spec)
+        .version("0")
+        .build()
+        .expect("should build");
+    let pkg = SiPkg::load_from_spec(pkg_spec).expect("should load from spec");
+    let options = Some(ImportOptions {
+        verify_signature: true,
+        ..Default::default()
+    });
+
+    // Unsigned packages are refused
+    let result = import_pkg_from_pkg(&require_ctx, &pkg, options.clone()).await;
+    assert!(matches!(
+        result,
+        Err(PkgError::Signature(SiPkgSignatureError::NotSigned(_)))
+    ));
+
+    // As are packages signed by a key that is not trusted
+    let untrusted_signature =
+        SiPkgSigningKey::generate().sign(pkg.hash().expect("should get hash"));
+    let untrusted_pkg = pkg.clone().with_signature(Some(untrusted_signature));
+    let result = import_pkg_from_pkg(&require_ctx, &untrusted_pkg, options.clone()).await;
+    assert!(matches!(
+        result,
+        Err(PkgError::Signature(
+            SiPkgSignatureError::UntrustedPublicKey(_, _)
+        ))
+    ));
+
+    // A package signed by a trusted key is installed
+    let signature = signing_key.sign(pkg.hash().expect("should get hash"));
+    let signed_pkg = pkg.with_signature(Some(signature));
+    import_pkg_from_pkg(&require_ctx, &signed_pkg, options)
+        .await
+        .expect("should import signed pkg");
+}
+
+fn pkg_with_dependencies(name: &str, dependencies: &[(&str, &str)]) -> SiPkg {
+    let func_spec = FuncSpec::builder()
+        .name(name)
+        .unique_id(name)
+        .data(
+            FuncSpecData::builder()
+                .name(name)
+                .backend_kind(FuncBackendKind::JsAttribute)
+                .response_type(FuncBackendResponseType::String)
+                .handler("main")
+                .code_plaintext(format!("function main() {{ return \"{name}\"; }}"))
+                .build()
+                .expect("should build data"),
+        )
+        .build()
+        .expect("should build func spec");
+
+    let mut builder = PkgSpec::builder();
+    builder
+        .name(name)
+        .created_by("sally@systeminit.com")
+        .func(func_spec)
+        .version("2024-01-01");
+    for (dependency_name, version_constraint) in dependencies {
+        builder.dependency(
+            DependencySpec::builder()
+                .name(*dependency_name)
+                .version_constraint(*version_constraint)
+                .build()
+                .expect("should build dependency"),
+        );
+    }
+
+    SiPkg::load_from_spec(builder.build().expect("should build")).expect("should load from spec")
+}
+
+#[test]
+async fn import_pkgs_with_dependencies_installs_dependencies_first(ctx: &DalContext) {
+    let credential = pkg_with_dependencies("aws-credential", &[]);
+    let region = pkg_with_dependencies("aws-region", &[("aws-credential", "*")]);
+    let ec2 = pkg_with_dependencies(
+        "aws-ec2",
+        &[("aws-region", ">=2023-12-01"), ("aws-credential", "*")],
+    );
+
+    // On its own, a package fails before anything is installed when its dependencies are not
+    let result = import_pkg_from_pkg(ctx, &ec2, None).await;
+    assert!(matches!(result, Err(PkgError::MissingDependency(..))));
+
+    let installed = import_pkgs_with_dependencies(
+        ctx,
+        vec![
+            (ec2, ImportOptions::default()),
+            (region, ImportOptions::default()),
+            (credential, ImportOptions::default()),
+        ],
+    )
+    .await
+    .expect("should import pkgs");
+
+    let installed_names: Vec<String> = installed
+        .iter()
+        .map(|(pkg, _)| {
+            pkg.metadata()
+                .expect("should get metadata")
+                .name()
+                .to_owned()
+        })
+        .collect();
+    assert_eq!(
+        vec!["aws-credential", "aws-region", "aws-ec2"],
+        installed_names
+    );
+
+    // Installing again skips what is already installed, and an installed module satisfies the
+    // dependencies of a new package
+    let s3 = pkg_with_dependencies("aws-s3", &[("aws-credential", "2024-01-01")]);
+    let installed = import_pkgs_with_dependencies(
+        ctx,
+        vec![
+            (s3, ImportOptions::default()),
+            (
+                pkg_with_dependencies("aws-credential", &[]),
+                ImportOptions::default(),
+            ),
+        ],
+    )
+    .await
+    .expect("should import pkgs");
+    assert_eq!(1, installed.len());
+}
+
+#[test]
+async fn installed_module_satisfies_dependencies_on_an_older_version(ctx: &DalContext) {
+    import_pkg_from_pkg(ctx, &pkg_with_dependencies("aws-credential", &[]), None)
+        .await
+        .expect("should import pkg");
+    let installed = Module::list_installed(ctx)
+        .await
+        .expect("should list installed modules");
+
+    let spec = |version_constraint: &str| {
+        DependencySpec::builder()
+            .name("aws-credential")
+            .version_constraint(version_constraint)
+            .build()
+            .expect("should build dependency")
+    };
+
+    // The installed version satisfies every dependent, so a newer one is not needed
+    assert!(installed_module_satisfies(
+        &installed,
+        &[spec(">=2023-12-01"), spec("*")]
+    ));
+
+    // A dependent that needs something newer, or constraints the installed version does not meet
+    // together, still require an install
+    assert!(!installed_module_satisfies(
+        &installed,
+        &[spec(">=2023-12-01"), spec(">=2024-06-01")]
+    ));
+    assert!(!installed_module_satisfies(
+        &installed,
+        &[spec(">=2023-12-01"), spec("<2024-01-01")]
+    ));
+    assert!(!installed_module_satisfies(&installed, &[]));
+}
@@ -1,5 +1,6 @@
 mod clone_variant;
 mod create_variant;
+mod create_variant_from_json_schema;
 mod delete_unlocked_variant;
 mod regenerate;
 mod save_variant;
@@ -0,0 +1,125 @@
+use dal::prop::PropPath;
+use dal::schema::variant::authoring::VariantAuthoringClient;
+use dal::{DalContext, Prop, PropKind, SchemaVariantId};
+use dal_test::test;
+use serde_json::json;
+
+#[test]
+async fn create_variant_from_openapi_document(ctx: &mut DalContext) {
+    let document = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Pet Store", "version": "1.0.0" },
+        "paths": {},
+        "components": {
+            "schemas": {
+                "Pet": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": { "type": "string", "description": "What the pet is called" },
+                        "status": { "type": "string", "enum": ["available", "sold"] },
+                        "age": { "type": "integer", "minimum": 0 },
+                        "tags": {
+                            "type": "array",
+                            "items": { "$ref": "#/components/schemas/Tag" }
+                        },
+                        "labels": {
+                            "type": "object",
+                            "additionalProperties": { "type": "string" }
+                        },
+                        "vaccinated": { "type": "boolean", "default": false }
+                    }
+                },
+                "Tag": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "name": { "type": "string" }
+                    }
+                }
+            }
+        }
+    });
+
+    let variant = VariantAuthoringClient::create_schema_and_variant_from_json_schema(
+        ctx,
+        "Pet",
+        None,
+        None,
+        "Integration Tests",
+        "#00b0b0",
+        &document,
+        Some("/components/schemas/Pet"),
+    )
+    .await
+    .expect("could not create variant from json schema");
+    assert!(!variant.is_locked());
+
+    let name = find_prop(ctx, variant.id(), &["name"]).await;
+    assert_eq!(PropKind::String, name.kind);
+    assert_eq!(
+        Some("What the pet is called"),
+        name.documentation.as_deref()
+    );
+    assert_eq!(
+        Some("Joi.string().required()"),
+        name.validation_format.as_deref()
+    );
+    assert_eq!(
+        Some(r#"Joi.string().valid("available", "sold")"#),
+        find_prop(ctx, variant.id(), &["status"])
+            .await
+            .validation_format
+            .as_deref()
+    );
+    assert_eq!(
+        Some("Joi.number().integer().min(0)"),
+        find_prop(ctx, variant.id(), &["age"])
+            .await
+            .validation_format
+            .as_deref()
+    );
+
+    assert_eq!(
+        PropKind::Array,
+        find_prop(ctx, variant.id(), &["tags"]).await.kind
+    );
+    assert_eq!(
+        PropKind::Object,
+        find_prop(ctx, variant.id(), &["tags", "tagsItem"])
+            .await
+            .kind
+    );
+    assert_eq!(
+        PropKind::Integer,
+        find_prop(ctx, variant.id(), &["tags", "tagsItem", "id"])
+            .await
+            .kind
+    );
+    assert_eq!(
+        PropKind::Map,
+        find_prop(ctx, variant.id(), &["labels"]).await.kind
+    );
+    assert_eq!(
+        PropKind::String,
+        find_prop(ctx, variant.id(), &["labels", "labelsItem"])
+            .await
+            .kind
+    );
+
+    let vaccinated = find_prop(ctx, variant.id(), &["vaccinated"]).await;
+    assert_eq!(PropKind::Boolean, vaccinated.kind);
+    assert_eq!(
+        Some(json!(false)),
+        Prop::default_value(ctx, vaccinated.id())
+            .await
+            .expect("could not get default value")
+    );
+}
+
+async fn find_prop(ctx: &DalContext, schema_variant_id: SchemaVariantId, path: &[&str]) -> Prop {
+    let path = PropPath::new(["root", "domain"].iter().chain(path));
+    Prop::find_prop_by_path(ctx, schema_variant_id, &path)
+        .await
+        .expect("prop should exist")
+}
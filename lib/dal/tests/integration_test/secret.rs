@@ -3,7 +3,10 @@ use dal::prop::PropPath;
 use dal::property_editor::values::PropertyEditorValues;
 use dal::qualification::QualificationSubCheckStatus;
 use dal::secret::DecryptedSecret;
-use dal::{Component, DalContext, EncryptedSecret, Prop, Secret, SecretAlgorithm, SecretVersion};
+use dal::{
+    Component, DalContext, EncryptedSecret, Prop, Secret, SecretAlgorithm, SecretError,
+    SecretKeyRotation, SecretKeyRotationStatus, SecretVersion,
+};
 use dal_test::expected::{self, ExpectComponent};
 use dal_test::helpers::{
     create_component_for_default_schema_name, encrypt_message, ChangeSetTestHelpers,
@@ -104,6 +107,107 @@ async fn encrypt_decrypt_round_trip(ctx: &DalContext, nw: &WorkspaceSignup) {
     assert_eq!(message, actual_message);
 }
 
+#[test]
+async fn rotate_secret_keys(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let message = serde_json::json!({"song": "Tennessee Whiskey"});
+    let crypted = sodiumoxide::crypto::sealedbox::seal(
+        &serde_json::to_vec(&message).expect("failed to serialize message"),
+        nw.key_pair.public_key(),
+    );
+
+    let mut secrets = Vec::new();
+    for _ in 0..3 {
+        let name = generate_fake_name().expect("could not generate fake name");
+        let secret = Secret::new(
+            ctx,
+            &name,
+            "imasecret".to_owned(),
+            None,
+            &crypted,
+            nw.key_pair.pk(),
+            Default::default(),
+            Default::default(),
+        )
+        .await
+        .expect("failed to create encrypted secret");
+        secrets.push(secret.id());
+    }
+
+    // Rotate only part of the plan to ensure progress is tracked.
+    let mut rotation = SecretKeyRotation::begin(ctx)
+        .await
+        .expect("could not begin rotation");
+    assert_eq!(SecretKeyRotationStatus::Running, rotation.status);
+    let plan = rotation.plan(ctx).await.expect("could not plan rotation");
+    assert_eq!(0, plan.rotated);
+    assert_eq!(3, plan.total());
+    rotation
+        .record_progress(ctx, plan.total() as i64, plan.rotated as i64)
+        .await
+        .expect("could not record progress");
+    rotation
+        .rotate_secrets(ctx, &plan.pending[..2])
+        .await
+        .expect("could not rotate secrets");
+    assert_eq!(3, rotation.total_secrets);
+    assert_eq!(2, rotation.rotated_secrets);
+
+    // A running rotation cannot be started a second time.
+    let result = SecretKeyRotation::begin(ctx).await;
+    assert!(matches!(
+        result,
+        Err(SecretError::SecretKeyRotationAlreadyRunning(_))
+    ));
+
+    // Beginning again after a failure resumes the rotation rather than starting another one, and
+    // only the secrets that were not rotated yet are left in the plan.
+    rotation
+        .fail(ctx, "interrupted")
+        .await
+        .expect("could not fail rotation");
+    let mut resumed = SecretKeyRotation::begin(ctx)
+        .await
+        .expect("could not resume rotation");
+    assert_eq!(rotation.id, resumed.id);
+    assert_eq!(SecretKeyRotationStatus::Running, resumed.status);
+    let plan = resumed.plan(ctx).await.expect("could not plan rotation");
+    assert_eq!(2, plan.rotated);
+    assert_eq!(1, plan.pending.len());
+    resumed
+        .rotate_secrets(ctx, &plan.pending)
+        .await
+        .expect("could not rotate secrets");
+    resumed
+        .complete(ctx)
+        .await
+        .expect("could not complete rotation");
+    assert_eq!(SecretKeyRotationStatus::Completed, resumed.status);
+    assert_eq!(3, resumed.rotated_secrets);
+
+    // Every secret is now encrypted under the new key pair and still decrypts to the message.
+    for secret_id in secrets {
+        let secret = Secret::get_by_id_or_error(ctx, secret_id)
+            .await
+            .expect("could not perform get by id or secret not found");
+        let encrypted_secret = EncryptedSecret::get_by_key(ctx, secret.encrypted_secret_key())
+            .await
+            .expect("failed to perform get by key for encrypted secret")
+            .expect("no encrypted secret found");
+        let key_pair = encrypted_secret
+            .key_pair(ctx)
+            .await
+            .expect("could not get key pair");
+        assert_eq!(resumed.key_pair_pk, key_pair.pk());
+        assert_ne!(nw.key_pair.pk(), key_pair.pk());
+
+        let decrypted = encrypted_secret
+            .decrypt(ctx)
+            .await
+            .expect("failed to decrypt encrypted secret");
+        assert_eq!(message, prepare_decrypted_secret_for_assertions(&decrypted));
+    }
+}
+
 #[test]
 async fn update_metadata_and_encrypted_contents(ctx: &DalContext, nw: &WorkspaceSignup) {
     let pkey = nw.key_pair.public_key();
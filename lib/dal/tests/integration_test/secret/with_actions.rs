@@ -4,7 +4,9 @@ use dal::prop::PropPath;
 use dal::property_editor::values::PropertyEditorValues;
 use dal::qualification::QualificationSubCheckStatus;
 use dal::Workspace;
-use dal::{AttributeValue, Component, DalContext, InputSocket, OutputSocket, Prop, Secret};
+use dal::{
+    AttributeValue, Component, DalContext, InputSocket, OutputSocket, Prop, Secret, SecretUsage,
+};
 use dal_test::helpers::ChangeSetTestHelpers;
 use dal_test::helpers::{create_component_for_default_schema_name, encrypt_message};
 use dal_test::{test, WorkspaceSignup};
@@ -232,4 +234,21 @@ async fn create_action_using_secret(ctx: &mut DalContext, nw: &WorkspaceSignup)
             .expect("could not get materialized view")
             .expect("empty materialized view") // actual
     );
+
+    // Ensure that the secret usage ledger recorded the action run that received the secret.
+    let usages = SecretUsage::list_for_secret(ctx, secret.id(), None)
+        .await
+        .expect("could not list secret usages");
+    let action_usage = usages
+        .iter()
+        .find(|usage| usage.action_id == Some(create_action_id))
+        .expect("no usage recorded for the create action");
+    assert_eq!(
+        Some(destination_component.id()), // expected
+        action_usage.component_id,        // actual
+    );
+    assert_eq!(
+        "Action",                            // expected
+        action_usage.function_kind.as_str(), // actual
+    );
 }
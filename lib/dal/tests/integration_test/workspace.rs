@@ -1,11 +1,17 @@
+use std::collections::HashMap;
+
 use dal::change_set::view::OpenChangeSetsView;
 use dal::diagram::Diagram;
-use dal::{DalContext, Workspace};
+use dal::workspace::template::{generate_template, instantiate_template, WorkspaceTemplateError};
+use dal::{Component, DalContext, Workspace, WorkspacePk};
 use dal_test::helpers::{
-    create_component_for_default_schema_name, ChangeSetTestHelpers, PropEditorTestView,
+    create_component_for_default_schema_name, update_attribute_value_for_component,
+    ChangeSetTestHelpers, PropEditorTestView,
 };
 use dal_test::test;
 use pretty_assertions_sorted::assert_eq;
+use serde_json::json;
+use si_pkg::WorkspaceTemplateParameterV0;
 
 #[test]
 async fn export_import_loop(ctx: &mut DalContext) {
@@ -108,3 +114,151 @@ async fn export_import_loop(ctx: &mut DalContext) {
             .expect("get value for domain/name")
     );
 }
+
+#[test]
+async fn instantiate_template_with_parameters(ctx: &mut DalContext) {
+    let vault = create_component_for_default_schema_name(ctx, "fallout", "{{ environment }}-vault")
+        .await
+        .expect("could not create component");
+    update_attribute_value_for_component(
+        ctx,
+        vault.id(),
+        &["root", "domain", "special"],
+        json!("{{ region }}"),
+    )
+    .await
+    .expect("could not update attribute value");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("commit and update snapshot to visibility");
+
+    let template = generate_template(
+        ctx,
+        "golden",
+        "1.0",
+        "a vault per environment",
+        vec![
+            WorkspaceTemplateParameterV0 {
+                name: "environment".to_string(),
+                description: None,
+                default: None,
+            },
+            WorkspaceTemplateParameterV0 {
+                name: "region".to_string(),
+                description: Some("where the vault lives".to_string()),
+                default: Some(json!("us-east-1")),
+            },
+        ],
+    )
+    .await
+    .expect("could not generate template");
+
+    // Values for parameters the template does not have are rejected
+    let result = instantiate_template(
+        ctx,
+        template.clone(),
+        HashMap::from([
+            ("environment".to_string(), json!("prod")),
+            ("regoin".to_string(), json!("us-west-2")),
+        ]),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(WorkspaceTemplateError::UnknownParameter(name)) if name == "regoin"
+    ));
+
+    let instance = instantiate_template(
+        ctx,
+        template,
+        HashMap::from([("environment".to_string(), json!("prod"))]),
+    )
+    .await
+    .expect("could not instantiate template");
+    assert_eq!(ctx.change_set_id(), instance.change_set_id);
+    assert!(instance.report.missing_schema_variants.is_empty());
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("commit and update snapshot to visibility");
+
+    let components = Component::list(ctx)
+        .await
+        .expect("could not list components");
+    assert_eq!(1, components.len());
+    let component = components.first().expect("get component");
+    assert_eq!(
+        "prod-vault",
+        component.name(ctx).await.expect("could not get name")
+    );
+    assert_eq!(
+        Some(&json!("us-east-1")),
+        PropEditorTestView::for_component_id(ctx, component.id())
+            .await
+            .expect("could not get property editor test view")
+            .get_value(&["root", "domain", "special"])
+            .expect("could not get value")
+            .get("value")
+    );
+}
+
+#[test]
+async fn create_workspace_from_template(ctx: &mut DalContext) {
+    create_component_for_default_schema_name(ctx, "fallout", "{{ environment }}-vault")
+        .await
+        .expect("could not create component");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("commit and update snapshot to visibility");
+
+    let template = generate_template(
+        ctx,
+        "golden",
+        "1.0",
+        "a vault per environment",
+        vec![WorkspaceTemplateParameterV0 {
+            name: "environment".to_string(),
+            description: None,
+            default: None,
+        }],
+    )
+    .await
+    .expect("could not generate template");
+
+    let (workspace, instance) = Workspace::new_from_template(
+        ctx,
+        WorkspacePk::generate(),
+        "golden workspace",
+        "token",
+        template,
+        HashMap::from([("environment".to_string(), json!("staging"))]),
+    )
+    .await
+    .expect("could not create workspace from template");
+    ChangeSetTestHelpers::commit_and_update_snapshot_to_visibility(ctx)
+        .await
+        .expect("commit and update snapshot to visibility");
+
+    // The model is in a change set of the new workspace, and HEAD is left alone
+    assert_eq!(Some(*workspace.pk()), ctx.tenancy().workspace_pk_opt());
+    assert_eq!(instance.change_set_id, ctx.change_set_id());
+    assert_ne!(workspace.default_change_set_id(), ctx.change_set_id());
+    assert!(instance.report.missing_schema_variants.is_empty());
+
+    let components = Component::list(ctx)
+        .await
+        .expect("could not list components");
+    assert_eq!(1, components.len());
+    let component = components.first().expect("get component");
+    assert_eq!(
+        "staging-vault",
+        component.name(ctx).await.expect("could not get name")
+    );
+
+    ctx.update_visibility_and_snapshot_to_visibility(workspace.default_change_set_id())
+        .await
+        .expect("could not switch to head");
+    assert!(Component::list(ctx)
+        .await
+        .expect("could not list components")
+        .is_empty());
+}
@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+
+use dal::workspace_snapshot::chunk::WorkspaceSnapshotManifest;
+use dal::workspace_snapshot::node_weight::NodeWeight;
+use dal::{ComponentId, DalContext, WorkspaceSnapshot};
+use dal_test::helpers::{
+    create_component_for_default_schema_name, update_attribute_value_for_component,
+};
+use dal_test::test;
+use serde_json::json;
+use si_events::WorkspaceSnapshotAddress;
+
+#[test]
+async fn unchanged_subgraphs_share_chunks(ctx: &DalContext) {
+    let changed = create_component_for_default_schema_name(ctx, "starfield", "changed")
+        .await
+        .expect("could not create component");
+    let unchanged = create_component_for_default_schema_name(ctx, "starfield", "unchanged")
+        .await
+        .expect("could not create component");
+
+    let snapshot = ctx.workspace_snapshot().expect("could not get snapshot");
+    let before_address = snapshot.write(ctx).await.expect("could not write snapshot");
+
+    update_attribute_value_for_component(
+        ctx,
+        changed.id(),
+        &["root", "si", "name"],
+        json!("changed again"),
+    )
+    .await
+    .expect("could not update name");
+    let after_address = snapshot.write(ctx).await.expect("could not write snapshot");
+    assert_ne!(before_address, after_address);
+
+    let before = WorkspaceSnapshot::chunk_manifest(ctx, before_address)
+        .await
+        .expect("could not read manifest")
+        .expect("snapshot should be chunked");
+    let after = WorkspaceSnapshot::chunk_manifest(ctx, after_address)
+        .await
+        .expect("could not read manifest")
+        .expect("snapshot should be chunked");
+    let chunk_address = |manifest: &WorkspaceSnapshotManifest, id: ComponentId| {
+        manifest
+            .chunks()
+            .iter()
+            .find(|chunk| chunk.subgraph_root_id == Some(id.into()))
+            .expect("component should have a chunk")
+            .address
+    };
+
+    assert_eq!(
+        chunk_address(&before, unchanged.id()),
+        chunk_address(&after, unchanged.id())
+    );
+    assert_ne!(
+        chunk_address(&before, changed.id()),
+        chunk_address(&after, changed.id())
+    );
+
+    // Reading the snapshot back assembles the same graph out of its chunks
+    let found = WorkspaceSnapshot::find(ctx, after_address)
+        .await
+        .expect("could not find snapshot");
+    let merkle_tree_hashes = |nodes: Vec<(NodeWeight, _)>| {
+        nodes
+            .into_iter()
+            .map(|(node, _)| (node.id(), node.merkle_tree_hash()))
+            .collect::<HashMap<_, _>>()
+    };
+    assert_eq!(
+        merkle_tree_hashes(snapshot.nodes().await.expect("could not get nodes")),
+        merkle_tree_hashes(found.nodes().await.expect("could not get nodes")),
+    );
+}
+
+#[test]
+async fn evicting_a_snapshot_collects_only_its_own_chunks(ctx: &DalContext) {
+    let changed = create_component_for_default_schema_name(ctx, "starfield", "changed")
+        .await
+        .expect("could not create component");
+    let unchanged = create_component_for_default_schema_name(ctx, "starfield", "unchanged")
+        .await
+        .expect("could not create component");
+
+    let snapshot = ctx.workspace_snapshot().expect("could not get snapshot");
+    let before_address = snapshot.write(ctx).await.expect("could not write snapshot");
+
+    update_attribute_value_for_component(
+        ctx,
+        changed.id(),
+        &["root", "si", "name"],
+        json!("changed again"),
+    )
+    .await
+    .expect("could not update name");
+    let after_address = snapshot.write(ctx).await.expect("could not write snapshot");
+
+    let before = WorkspaceSnapshot::chunk_manifest(ctx, before_address)
+        .await
+        .expect("could not read manifest")
+        .expect("snapshot should be chunked");
+    let after = WorkspaceSnapshot::chunk_manifest(ctx, after_address)
+        .await
+        .expect("could not read manifest")
+        .expect("snapshot should be chunked");
+    let chunk_address = |manifest: &WorkspaceSnapshotManifest, id: ComponentId| {
+        manifest
+            .chunks()
+            .iter()
+            .find(|chunk| chunk.subgraph_root_id == Some(id.into()))
+            .expect("component should have a chunk")
+            .address
+    };
+    let unreferenced = before.unreferenced_chunks([&after]);
+    assert!(unreferenced.contains(&chunk_address(&before, changed.id())));
+    assert!(!unreferenced.contains(&chunk_address(&before, unchanged.id())));
+
+    let evicted = WorkspaceSnapshotManifest::evict(
+        ctx.layer_db(),
+        before_address,
+        &HashSet::from([after_address]),
+        ctx.events_tenancy(),
+        ctx.events_actor(),
+    )
+    .await
+    .expect("could not evict snapshot");
+    assert_eq!(unreferenced.len(), evicted);
+
+    let in_memory = |address: WorkspaceSnapshotAddress| {
+        ctx.layer_db()
+            .workspace_snapshot_chunk()
+            .cache
+            .contains(&address.to_string())
+    };
+    assert!(!in_memory(before_address));
+    for address in unreferenced {
+        assert!(!in_memory(address));
+    }
+    assert!(in_memory(after_address));
+    for chunk in after.chunks() {
+        assert!(in_memory(chunk.address));
+    }
+
+    // The snapshot still in use reads back from the chunks it shared with the evicted one
+    WorkspaceSnapshot::find(ctx, after_address)
+        .await
+        .expect("could not find snapshot");
+}
@@ -1,5 +1,5 @@
 use reqwest::StatusCode;
-use si_pkg::WorkspaceExport;
+use si_pkg::{SiPkgSignature, WorkspaceExport, WorkspaceTemplate};
 use thiserror::Error;
 use ulid::Ulid;
 use url::Url;
@@ -104,6 +104,7 @@ impl ModuleIndexClient {
         module_bytes: Vec<u8>,
         module_schema_variant_id: Option<String>,
         module_schema_variant_version: Option<String>,
+        module_signature: Option<SiPkgSignature>,
     ) -> ModuleIndexClientResult<ModuleDetailsResponse> {
         let module_upload_part = reqwest::multipart::Part::bytes(module_bytes)
             .file_name(format!("{module_name}_{module_version}.tar"));
@@ -139,6 +140,18 @@ impl ModuleIndexClient {
             );
         }
 
+        if let Some(signature) = module_signature {
+            multipart_form = multipart_form
+                .part(
+                    MODULE_SIGNATURE_FIELD_NAME,
+                    reqwest::multipart::Part::text(signature.signature),
+                )
+                .part(
+                    MODULE_SIGNATURE_PUBLIC_KEY_FIELD_NAME,
+                    reqwest::multipart::Part::text(signature.public_key),
+                );
+        }
+
         let upload_url = self.base_url.join("modules")?;
         let upload_response = reqwest::Client::new()
             .post(upload_url)
@@ -218,6 +231,28 @@ impl ModuleIndexClient {
             .await?)
     }
 
+    /// Every module the given module depends on, directly or not, in the order they should be
+    /// installed.
+    pub async fn resolve_dependencies(
+        &self,
+        module_id: Ulid,
+    ) -> ModuleIndexClientResult<ResolveDependenciesResponse> {
+        let dependencies_url = self
+            .base_url
+            .join("modules/")?
+            .join(&format!("{}/", module_id))?
+            .join("dependencies")?;
+
+        Ok(reqwest::Client::new()
+            .get(dependencies_url)
+            .bearer_auth(&self.auth_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
     pub async fn get_builtin(&self, module_id: Ulid) -> ModuleIndexClientResult<Vec<u8>> {
         let download_url = self
             .base_url
@@ -293,6 +328,53 @@ impl ModuleIndexClient {
         Ok(export_data)
     }
 
+    /// Uploads a [`WorkspaceTemplate`] (route: POST /workspace_templates).
+    pub async fn upload_workspace_template(
+        &self,
+        template_name: &str,
+        template_version: &str,
+        content: WorkspaceTemplate,
+    ) -> ModuleIndexClientResult<()> {
+        let bytes = serde_json::to_vec(&content).map_err(ModuleIndexClientError::Serialization)?;
+
+        let upload_part = reqwest::multipart::Part::bytes(bytes)
+            .file_name(format!("{template_name}_{template_version}.json"));
+
+        let upload_url = self.base_url.join("workspace_templates")?;
+
+        reqwest::Client::new()
+            .post(upload_url)
+            .multipart(reqwest::multipart::Form::new().part("workspace template", upload_part))
+            .bearer_auth(&self.auth_token)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Downloads a [`WorkspaceTemplate`] (route: GET /workspace_templates/:module_id/download).
+    pub async fn download_workspace_template(
+        &self,
+        module_id: Ulid,
+    ) -> ModuleIndexClientResult<WorkspaceTemplate> {
+        let download_url = self
+            .base_url
+            .join("workspace_templates/")?
+            .join(&format!("{}/", module_id.to_string()))?
+            .join("download")?;
+        let response = reqwest::Client::new()
+            .get(download_url)
+            .bearer_auth(&self.auth_token)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let bytes = response.bytes().await?;
+
+        serde_json::from_slice(&bytes).map_err(ModuleIndexClientError::Deserialization)
+    }
+
     /// Lists all of the latest, _promoted_ [`Modules`](Model) (route: GET /modules/latest).
     pub async fn list_latest_modules(&self) -> ModuleIndexClientResult<ListLatestModulesResponse> {
         let url = self.base_url.join("modules/")?.join("latest")?;
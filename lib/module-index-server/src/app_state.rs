@@ -2,13 +2,12 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::extract::FromRef;
-use s3::creds::Credentials as AwsCredentials;
 use sea_orm::DatabaseConnection;
 pub use si_posthog::PosthogClient;
 
 use tokio::sync::{mpsc, Mutex};
 
-use crate::{jwt_key::JwtPublicSigningKey, s3::S3Config};
+use crate::{jwt_key::JwtPublicSigningKey, storage::ModuleStorageRef};
 
 #[remain::sorted]
 #[derive(Debug, Eq, PartialEq)]
@@ -20,8 +19,7 @@ pub struct AppState {
     pg_pool: DatabaseConnection,
     jwt_public_signing_key: JwtPublicSigningKey,
     posthog_client: PosthogClient,
-    aws_creds: AwsCredentials,
-    s3_config: S3Config,
+    storage: ModuleStorageRef,
     token_emails: Arc<Mutex<HashMap<String, String>>>,
 
     // see notes in sdf AppState
@@ -36,16 +34,14 @@ impl AppState {
         pg_pool: DatabaseConnection,
         jwt_public_signing_key: JwtPublicSigningKey,
         posthog_client: PosthogClient,
-        aws_creds: AwsCredentials,
-        s3_config: S3Config,
+        storage: ModuleStorageRef,
         tmp_shutdown_tx: mpsc::Sender<ShutdownSource>,
     ) -> Self {
         Self {
             pg_pool,
             jwt_public_signing_key,
             posthog_client,
-            aws_creds,
-            s3_config,
+            storage,
             token_emails: Arc::new(Mutex::new(HashMap::new())),
             _tmp_shutdown_tx: Arc::new(tmp_shutdown_tx),
         }
@@ -66,13 +62,9 @@ impl AppState {
         &self.posthog_client
     }
 
-    /// Gets a reference to the aws creds.
-    pub fn aws_creds(&self) -> &AwsCredentials {
-        &self.aws_creds
-    }
-    /// Gets a reference to the s3 config (bucket, region, etc)
-    pub fn s3_config(&self) -> &S3Config {
-        &self.s3_config
+    /// Gets a reference to the storage that module and workspace blobs are kept in.
+    pub fn storage(&self) -> &ModuleStorageRef {
+        &self.storage
     }
 
     /// Clones the ArcMutex that holds a hashmap between auth tokens and emails
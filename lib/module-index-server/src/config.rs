@@ -12,7 +12,7 @@ use thiserror::Error;
 pub use si_settings::{StandardConfig, StandardConfigFile};
 use ulid::Ulid;
 
-use crate::s3::S3Config;
+use crate::{s3::S3Config, storage::StorageConfig};
 
 #[remain::sorted]
 #[derive(Debug, Error)]
@@ -56,6 +56,9 @@ pub struct Config {
     posthog: PosthogConfig,
 
     s3: S3Config,
+
+    #[builder(default)]
+    storage: StorageConfig,
 }
 
 impl StandardConfig for Config {
@@ -96,6 +99,12 @@ impl Config {
     pub fn s3(&self) -> &S3Config {
         &self.s3
     }
+
+    /// Gets the config's module storage backend
+    #[must_use]
+    pub fn storage(&self) -> &StorageConfig {
+        &self.storage
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -112,6 +121,8 @@ pub struct ConfigFile {
     pub posthog: PosthogConfig,
     #[serde(default)]
     pub s3: S3Config,
+    #[serde(default)]
+    pub storage: StorageConfig,
 }
 
 impl Default for ConfigFile {
@@ -129,6 +140,7 @@ impl Default for ConfigFile {
             jwt_signing_public_key_path: default_jwt_signing_public_key_path(),
             posthog: Default::default(),
             s3: Default::default(),
+            storage: Default::default(),
         }
     }
 }
@@ -150,6 +162,7 @@ impl TryFrom<ConfigFile> for Config {
         config.jwt_signing_public_key_path(value.jwt_signing_public_key_path.try_into()?);
         config.posthog(value.posthog);
         config.s3(value.s3);
+        config.storage(value.storage);
         config.build().map_err(Into::into)
     }
 }
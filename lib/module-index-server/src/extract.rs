@@ -1,56 +1,29 @@
-use std::fmt;
+use std::{convert::Infallible, fmt};
 
 use axum::{async_trait, extract::FromRequestParts, http::request::Parts, Json};
 use hyper::StatusCode;
-use s3::{Bucket as S3Bucket, Region as AwsRegion};
 use sea_orm::{DatabaseTransaction, TransactionTrait};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use ulid::Ulid;
 
 use super::app_state::AppState;
-use crate::jwt_key::{JwtKeyError, JwtPublicSigningKey};
+use crate::{
+    jwt_key::{JwtKeyError, JwtPublicSigningKey},
+    storage::ModuleStorageRef,
+};
 
-pub struct ExtractedS3Bucket(pub S3Bucket);
+pub struct ExtractedStorage(pub ModuleStorageRef);
 
 #[async_trait]
-impl FromRequestParts<AppState> for ExtractedS3Bucket {
-    type Rejection = (StatusCode, Json<serde_json::Value>);
+impl FromRequestParts<AppState> for ExtractedStorage {
+    type Rejection = Infallible;
 
     async fn from_request_parts(
         _parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        let region = match state.s3_config().region.parse::<AwsRegion>() {
-            Ok(region) => region,
-            Err(err) => {
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({
-                        "error": {
-                            "message": err.to_string(),
-                            "code": 42,
-                            "statusCode": 500
-                        }
-                    })),
-                ))
-            }
-        };
-
-        let bucket = S3Bucket::new(&state.s3_config().bucket, region, state.aws_creds().clone())
-            .map_err(|err| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({
-                        "error": {
-                            "message": err.to_string(),
-                            "code": 42,
-                            "statusCode": 500
-                        }
-                    })),
-                )
-            })?;
-        Ok(ExtractedS3Bucket(bucket))
+        Ok(ExtractedStorage(state.storage().clone()))
     }
 }
 
@@ -6,6 +6,7 @@ mod models;
 mod routes;
 mod s3;
 pub mod server;
+mod storage;
 mod whoami;
 
 pub use crate::{
@@ -14,4 +15,5 @@ pub use crate::{
         StandardConfig, StandardConfigFile,
     },
     server::{Server, ServerError},
+    storage::StorageConfig,
 };
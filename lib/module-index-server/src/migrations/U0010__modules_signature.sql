@@ -0,0 +1,3 @@
+ALTER TABLE modules
+    ADD signature TEXT,
+    ADD signature_public_key TEXT;
@@ -0,0 +1,23 @@
+ALTER TABLE modules
+    ADD schema_category TEXT,
+    ADD install_count   BIGINT NOT NULL DEFAULT 0,
+    ADD search_text     TEXT;
+
+-- Modules uploaded before this migration have no schema category, but their names, descriptions
+-- and func names are still searchable
+UPDATE modules
+SET search_text = concat_ws(' ', name, description, (SELECT string_agg(concat_ws(' ', func ->> 'name', func ->> 'displayName'), ' ')
+                                                     FROM json_array_elements(metadata -> 'funcs') AS func))
+WHERE metadata IS NOT NULL AND json_typeof(metadata -> 'funcs') = 'array';
+
+UPDATE modules
+SET search_text = concat_ws(' ', name, description)
+WHERE search_text IS NULL;
+
+ALTER TABLE modules
+    ADD search_vector tsvector GENERATED ALWAYS AS (to_tsvector('simple', coalesce(search_text, ''))) STORED;
+
+CREATE INDEX ON modules USING GIN (search_vector);
+CREATE INDEX ON modules (owner_user_id);
+CREATE INDEX ON modules (created_at);
+CREATE INDEX ON modules (install_count);
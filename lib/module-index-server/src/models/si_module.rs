@@ -37,6 +37,17 @@ pub struct Model {
     #[sea_orm(column_type = r##"custom("ident")"##, nullable)]
     pub schema_variant_id: Option<SchemaVariantId>,
     pub schema_variant_version: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub signature: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub signature_public_key: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub schema_category: Option<String>,
+    pub install_count: i64,
+    /// The text that full text search runs over: name, description, schema category and func
+    /// names. The database keeps a `search_vector` generated from it.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub search_text: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -44,6 +55,7 @@ pub struct Model {
 pub enum ModuleKind {
     Module,
     WorkspaceBackup,
+    WorkspaceTemplate,
 }
 
 impl ModuleKind {
@@ -51,6 +63,7 @@ impl ModuleKind {
         match self {
             ModuleKind::Module => "module".into(),
             ModuleKind::WorkspaceBackup => "workspaceBackup".into(),
+            ModuleKind::WorkspaceTemplate => "workspaceTemplate".into(),
         }
     }
 }
@@ -62,6 +75,7 @@ impl FromStr for ModuleKind {
         Ok(match s {
             "module" => ModuleKind::Module,
             "workspaceBackup" => ModuleKind::WorkspaceBackup,
+            "workspaceTemplate" => ModuleKind::WorkspaceTemplate,
             _ => return Err(sea_query::ValueTypeErr),
         })
     }
@@ -151,6 +165,10 @@ pub fn make_module_details_response(
             .schema_variant_id
             .map(|schema_variant_id| schema_variant_id.to_string()),
         schema_variant_version: module.schema_variant_version,
+        signature: module.signature,
+        signature_public_key: module.signature_public_key,
+        schema_category: module.schema_category,
+        install_count: module.install_count,
         past_hashes: Some(
             linked_modules
                 .into_iter()
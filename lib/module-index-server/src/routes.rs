@@ -15,14 +15,17 @@ use tower_http::cors::CorsLayer;
 mod download_builtin_route;
 mod download_module_route;
 mod download_workspace_route;
+mod download_workspace_template_route;
 mod get_module_details_route;
 mod list_builtins_route;
 mod list_latest_modules_route;
 mod list_modules_route;
 pub(crate) mod promote_builtin_route;
 pub(crate) mod reject_module_route;
+mod resolve_dependencies_route;
 pub(crate) mod upsert_module_route;
 mod upsert_workspace_route;
+mod upsert_workspace_template_route;
 
 use super::{app_state::AppState, server::ServerError};
 
@@ -52,6 +55,14 @@ pub fn routes(state: AppState) -> Router {
             "/workspace/:module_id/download",
             get(download_workspace_route::download_workspace_route),
         )
+        .route(
+            "/workspace_templates",
+            post(upsert_workspace_template_route::upsert_workspace_template_route),
+        )
+        .route(
+            "/workspace_templates/:module_id/download",
+            get(download_workspace_template_route::download_workspace_template_route),
+        )
         .route("/modules", post(upsert_module_route::upsert_module_route))
         .route(
             "/modules/:module_id",
@@ -65,6 +76,10 @@ pub fn routes(state: AppState) -> Router {
             "/modules/:module_id/download_builtin",
             get(download_builtin_route::download_builtin_route),
         )
+        .route(
+            "/modules/:module_id/dependencies",
+            get(resolve_dependencies_route::resolve_dependencies_route),
+        )
         .route(
             "/modules/:module_id/reject",
             post(reject_module_route::reject_module),
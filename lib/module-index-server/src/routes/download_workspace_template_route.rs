@@ -0,0 +1,61 @@
+use axum::{
+    extract::Path,
+    response::{IntoResponse, Response},
+    Json,
+};
+use hyper::StatusCode;
+use sea_orm::{DbErr, EntityTrait};
+use thiserror::Error;
+
+use crate::{
+    extract::{Authorization, DbConnection, ExtractedStorage},
+    models::si_module::{self, ModuleId, ModuleKind},
+    storage::{ModuleDownload, StorageError},
+};
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum DownloadModuleError {
+    #[error("db error: {0}")]
+    DbErr(#[from] DbErr),
+    #[error(r#"Module "{0}" not found"#)]
+    NotFound(ModuleId),
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
+}
+
+// TODO: figure out how to not keep this serialization logic here
+impl IntoResponse for DownloadModuleError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = match self {
+            Self::NotFound(_) | Self::Storage(StorageError::NotFound(_)) => {
+                (StatusCode::NOT_FOUND, self.to_string())
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        };
+
+        let body = Json(
+            serde_json::json!({ "error": { "message": error_message, "code": 42, "statusCode": status.as_u16() } }),
+        );
+
+        (status, body).into_response()
+    }
+}
+
+pub async fn download_workspace_template_route(
+    Path(module_id): Path<ModuleId>,
+    Authorization { .. }: Authorization,
+    ExtractedStorage(storage): ExtractedStorage,
+    DbConnection(txn): DbConnection,
+) -> Result<ModuleDownload, DownloadModuleError> {
+    let module = match si_module::Entity::find_by_id(module_id).one(&txn).await? {
+        Some(module) if module.kind == ModuleKind::WorkspaceTemplate => module,
+        _ => return Err(DownloadModuleError::NotFound(module_id)),
+    };
+
+    let download = storage
+        .download(&format!("{}.workspace_template", module.latest_hash))
+        .await?;
+
+    Ok(download)
+}
@@ -1,18 +1,26 @@
+use std::collections::HashMap;
+
 use axum::{
     extract::{Query, State},
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 use hyper::StatusCode;
-use module_index_types::ListModulesResponse;
-use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use module_index_types::{ListModulesResponse, ModuleSort};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, DbErr, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
     app_state::AppState,
     extract::{Authorization, DbConnection},
-    models::si_module::{self, make_module_details_response, SchemaIdReferenceLink},
+    models::si_module::{
+        self, make_module_details_response, ModuleId, SchemaId, SchemaIdReferenceLink,
+    },
     whoami::{is_systeminit_auth_token, WhoamiError},
 };
 
@@ -44,6 +52,20 @@ pub struct ListModulesRequest {
     pub name: Option<String>,
     pub kind: Option<si_module::ModuleKind>,
     pub su: Option<bool>,
+    /// Full text search over name, description, schema category and func names
+    pub search: Option<String>,
+    /// Matches the owner's user id exactly or their display name in part
+    pub owner: Option<String>,
+    /// Only builtins if true, only non builtins if false. Builtins are left out by default.
+    pub builtin: Option<bool>,
+    pub schema_id: Option<SchemaId>,
+    pub category: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub sort: Option<ModuleSort>,
+    /// Zero based. Only used along with `page_size`.
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
 }
 
 pub async fn list_module_route(
@@ -61,12 +83,14 @@ pub async fn list_module_route(
         && is_systeminit_auth_token(&auth_token, state.token_emails()).await?;
 
     let kind = request.kind.unwrap_or(si_module::ModuleKind::Module);
+    let builtin = request.builtin.unwrap_or(false);
 
     // filters
     let query = query
         .filter(si_module::Column::RejectedAt.is_null())
         .filter(si_module::Column::Kind.eq(kind.to_db_kind()));
-    let query = if !su {
+    // Builtins are available to everyone, so they are not limited to the user's own modules
+    let query = if !su && !builtin {
         let user_id = user_claim.user_pk.to_string();
         query.filter(si_module::Column::OwnerUserId.eq(user_id))
     } else {
@@ -77,22 +101,95 @@ pub async fn list_module_route(
     } else {
         query
     };
+    let query = match request.search.as_deref().map(str::trim) {
+        Some(search) if !search.is_empty() => query.filter(Expr::cust_with_values(
+            "search_vector @@ websearch_to_tsquery('simple', $1)",
+            [search],
+        )),
+        _ => query,
+    };
+    let query = if let Some(owner) = request.owner {
+        query.filter(
+            Condition::any()
+                .add(si_module::Column::OwnerUserId.eq(owner.as_str()))
+                .add(si_module::Column::OwnerDisplayName.contains(owner)),
+        )
+    } else {
+        query
+    };
+    let query = if let Some(schema_id) = request.schema_id {
+        query.filter(si_module::Column::SchemaId.eq(schema_id))
+    } else {
+        query
+    };
+    let query = if let Some(category) = request.category {
+        query.filter(si_module::Column::SchemaCategory.eq(category))
+    } else {
+        query
+    };
+    let query = if let Some(created_after) = request.created_after {
+        query.filter(si_module::Column::CreatedAt.gte(created_after))
+    } else {
+        query
+    };
+    let query = if let Some(created_before) = request.created_before {
+        query.filter(si_module::Column::CreatedAt.lt(created_before))
+    } else {
+        query
+    };
 
-    // We want to filter out the builtins from the list as they will already be in our system
-    let query = query.filter(si_module::Column::IsBuiltinAt.is_null());
+    // We want to filter out the builtins from the list as they will already be in our system,
+    // unless builtins are what was asked for
+    let query = if builtin {
+        query.filter(si_module::Column::IsBuiltinAt.is_not_null())
+    } else {
+        query.filter(si_module::Column::IsBuiltinAt.is_null())
+    };
 
     // ordering
-    let query = query
-        .order_by_desc(si_module::Column::OwnerUserId)
-        .order_by_desc(si_module::Column::CreatedAt);
+    let query = match request.sort {
+        None => query
+            .order_by_desc(si_module::Column::OwnerUserId)
+            .order_by_desc(si_module::Column::CreatedAt),
+        Some(ModuleSort::Recent) => query.order_by_desc(si_module::Column::CreatedAt),
+        Some(ModuleSort::Installs) => query
+            .order_by_desc(si_module::Column::InstallCount)
+            .order_by_desc(si_module::Column::CreatedAt),
+    };
+
+    let total = query.clone().count(&txn).await?;
+
+    // Joining in the linked modules multiplies the rows, so the page is picked out first and
+    // the linked modules are fetched for just the modules on it
+    let page_modules = match request.page_size {
+        Some(page_size) => {
+            query
+                .offset(request.page.unwrap_or(0).saturating_mul(page_size))
+                .limit(page_size)
+                .all(&txn)
+                .await?
+        }
+        None => query.all(&txn).await?,
+    };
+    let page_ids: Vec<ModuleId> = page_modules.iter().map(|module| module.id).collect();
 
-    let modules = query
+    let mut linked_by_id: HashMap<ModuleId, _> = si_module::Entity::find()
+        .filter(si_module::Column::Id.is_in(page_ids.iter().copied()))
         .find_with_linked(SchemaIdReferenceLink)
         .all(&txn)
         .await?
         .into_iter()
+        .map(|(module, linked_modules)| (module.id, (module, linked_modules)))
+        .collect();
+
+    let modules = page_ids
+        .into_iter()
+        .filter_map(|id| linked_by_id.remove(&id))
         .map(|(module, linked_modules)| make_module_details_response(module, linked_modules))
         .collect();
 
-    Ok(Json(ListModulesResponse { modules }))
+    Ok(Json(ListModulesResponse {
+        modules,
+        total: Some(total),
+    }))
 }
@@ -14,7 +14,7 @@ use crate::models::si_module::make_module_details_response;
 use crate::routes::upsert_module_route::UpsertModuleError;
 use crate::whoami::{is_systeminit_auth_token, WhoamiError};
 use crate::{
-    extract::{Authorization, DbConnection, ExtractedS3Bucket},
+    extract::{Authorization, DbConnection, ExtractedStorage},
     models::si_module::{self, ModuleId},
 };
 
@@ -51,7 +51,7 @@ pub async fn reject_module(
         user_claim: _user_claim,
         auth_token,
     }: Authorization,
-    ExtractedS3Bucket(_s3_bucket): ExtractedS3Bucket,
+    ExtractedStorage(_storage): ExtractedStorage,
     DbConnection(txn): DbConnection,
     State(state): State<AppState>,
     mut multipart: Multipart,
@@ -0,0 +1,287 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::Path,
+    response::{IntoResponse, Response},
+    Json,
+};
+use hyper::StatusCode;
+use module_index_types::{DependencyMetadata, ResolveDependenciesResponse};
+use sea_orm::{ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use si_pkg::DependencySpec;
+use thiserror::Error;
+
+use crate::{
+    extract::{Authorization, DbConnection},
+    models::si_module::{
+        self, make_module_details_response, ModuleId, ModuleKind, SchemaIdReferenceLink,
+    },
+};
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum ResolveDependenciesError {
+    #[error("db error: {0}")]
+    DbErr(#[from] DbErr),
+    #[error("modules depend on each other: {0}")]
+    DependencyCycle(String),
+    #[error(r#"Module "{0}" not found"#)]
+    NotFound(ModuleId),
+    #[error(r#"no module satisfies the dependency of "{0}" on "{1}" ({2})"#)]
+    Unresolved(String, String, String),
+}
+
+// TODO: figure out how to not keep this serialization logic here
+impl IntoResponse for ResolveDependenciesError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = match self {
+            Self::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            Self::DependencyCycle(_) | Self::Unresolved(..) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, self.to_string())
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        };
+
+        let body = Json(
+            serde_json::json!({ "error": { "message": error_message, "code": 42, "statusCode": status.as_u16() } }),
+        );
+
+        (status, body).into_response()
+    }
+}
+
+pub async fn resolve_dependencies_route(
+    Path(module_id): Path<ModuleId>,
+    Authorization { .. }: Authorization,
+    DbConnection(txn): DbConnection,
+) -> Result<Json<ResolveDependenciesResponse>, ResolveDependenciesError> {
+    let module = si_module::Entity::find_by_id(module_id)
+        .one(&txn)
+        .await?
+        .ok_or(ResolveDependenciesError::NotFound(module_id))?;
+
+    // A depth first walk of the dependencies, where a module is added to the install order once
+    // everything it depends on has been. The stack holds the chain of modules being resolved, so
+    // finding a module already on it means there is a cycle.
+    let mut install_order: Vec<ModuleId> = Vec::new();
+    let mut resolved: HashSet<ModuleId> = HashSet::new();
+    let root_dependencies = dependencies(&module);
+    let mut stack = vec![(module, root_dependencies)];
+
+    loop {
+        let Some((dependent, remaining)) = stack.last_mut() else {
+            break;
+        };
+        let Some(dependency) = remaining.pop() else {
+            if let Some((module, _)) = stack.pop() {
+                resolved.insert(module.id);
+                install_order.push(module.id);
+            }
+            continue;
+        };
+        let dependent_name = dependent.name.to_owned();
+
+        let candidate = resolve(&txn, &dependent_name, &dependency).await?;
+        if resolved.contains(&candidate.id) {
+            continue;
+        }
+        if let Some(position) = stack
+            .iter()
+            .position(|(module, _)| module.id == candidate.id)
+        {
+            let mut cycle: Vec<&str> = stack[position..]
+                .iter()
+                .map(|(module, _)| module.name.as_str())
+                .collect();
+            cycle.push(&candidate.name);
+            return Err(ResolveDependenciesError::DependencyCycle(
+                cycle.join(" -> "),
+            ));
+        }
+
+        let candidate_dependencies = dependencies(&candidate);
+        stack.push((candidate, candidate_dependencies));
+    }
+
+    // The module itself is always resolved last
+    install_order.pop();
+
+    let mut details_by_id: HashMap<ModuleId, _> = si_module::Entity::find()
+        .filter(si_module::Column::Id.is_in(install_order.iter().copied()))
+        .find_with_linked(SchemaIdReferenceLink)
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|(module, linked_modules)| {
+            (
+                module.id,
+                make_module_details_response(module, linked_modules),
+            )
+        })
+        .collect();
+
+    let modules = install_order
+        .into_iter()
+        .filter_map(|id| details_by_id.remove(&id))
+        .collect();
+
+    Ok(Json(ResolveDependenciesResponse { modules }))
+}
+
+/// The declared dependencies of a module, reversed so that popping them off visits them in the
+/// order they were declared. Modules published before dependencies existed, or with metadata
+/// that does not parse, have none.
+fn dependencies(module: &si_module::Model) -> Vec<DependencyMetadata> {
+    module
+        .metadata
+        .get("dependencies")
+        .and_then(|dependencies| {
+            serde_json::from_value::<Vec<DependencyMetadata>>(dependencies.to_owned()).ok()
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .collect()
+}
+
+/// The version in the metadata of a module. Modules without one only satisfy dependencies on
+/// any version.
+fn version(module: &si_module::Model) -> &str {
+    module
+        .metadata
+        .get("version")
+        .and_then(|version| version.as_str())
+        .unwrap_or_default()
+}
+
+/// Finds the newest module that satisfies the dependency.
+async fn resolve(
+    txn: &DatabaseTransaction,
+    dependent_name: &str,
+    dependency: &DependencyMetadata,
+) -> Result<si_module::Model, ResolveDependenciesError> {
+    let spec = DependencySpec {
+        name: dependency.name.to_owned(),
+        version_constraint: dependency.version_constraint.to_owned(),
+    };
+
+    let candidates = si_module::Entity::find()
+        .filter(si_module::Column::Kind.eq(ModuleKind::Module))
+        .filter(si_module::Column::Name.eq(dependency.name.as_str()))
+        .filter(si_module::Column::RejectedAt.is_null())
+        .order_by_desc(si_module::Column::CreatedAt)
+        .all(txn)
+        .await?;
+
+    for candidate in candidates {
+        if spec.matches(&candidate.name, version(&candidate)) {
+            return Ok(candidate);
+        }
+    }
+
+    Err(ResolveDependenciesError::Unresolved(
+        dependent_name.to_owned(),
+        dependency.name.to_owned(),
+        dependency.version_constraint.to_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use module_index_types::ExtraMetadata;
+    use serde_json::json;
+    use ulid::Ulid;
+
+    use super::*;
+
+    fn module(metadata: serde_json::Value) -> si_module::Model {
+        let now = Utc::now().into();
+        si_module::Model {
+            id: ModuleId(Ulid::new()),
+            name: "legacy".to_owned(),
+            description: None,
+            owner_user_id: "owner".to_owned(),
+            owner_display_name: None,
+            metadata,
+            latest_hash: "hash".to_owned(),
+            latest_hash_created_at: now,
+            created_at: now,
+            rejected_at: None,
+            rejected_by_display_name: None,
+            kind: ModuleKind::Module,
+            is_builtin_at: None,
+            is_builtin_at_by_display_name: None,
+            schema_id: None,
+            schema_variant_id: None,
+            schema_variant_version: None,
+            signature: None,
+            signature_public_key: None,
+            schema_category: None,
+            install_count: 0,
+            search_text: None,
+        }
+    }
+
+    #[test]
+    fn legacy_metadata_has_no_dependencies() {
+        // Published before versions and funcs were recorded in the metadata
+        let legacy = module(json!({ "schemas": ["legacy"] }));
+
+        assert!(dependencies(&legacy).is_empty());
+        assert_eq!("", version(&legacy));
+
+        let any_version = DependencySpec {
+            name: "legacy".to_owned(),
+            version_constraint: "*".to_owned(),
+        };
+        let some_version = DependencySpec {
+            name: "legacy".to_owned(),
+            version_constraint: ">=1.0.0".to_owned(),
+        };
+        assert!(any_version.matches(&legacy.name, version(&legacy)));
+        assert!(!some_version.matches(&legacy.name, version(&legacy)));
+    }
+
+    #[test]
+    fn partial_metadata_keeps_its_dependencies() {
+        let partial = module(json!({
+            "version": "2.0.0",
+            "dependencies": [
+                { "name": "first", "versionConstraint": "*" },
+                { "name": "second", "versionConstraint": ">=1.0.0" },
+            ],
+        }));
+
+        let names: Vec<String> = dependencies(&partial)
+            .into_iter()
+            .map(|dependency| dependency.name)
+            .collect();
+        assert_eq!(vec!["second".to_owned(), "first".to_owned()], names);
+        assert_eq!("2.0.0", version(&partial));
+    }
+
+    #[test]
+    fn malformed_dependencies_are_ignored() {
+        let malformed = module(json!({ "version": "1.0.0", "dependencies": "first" }));
+
+        assert!(dependencies(&malformed).is_empty());
+    }
+
+    #[test]
+    fn current_metadata_round_trips() {
+        let metadata = ExtraMetadata {
+            version: "1.2.3".to_owned(),
+            schemas: vec!["current".to_owned()],
+            funcs: vec![],
+            dependencies: vec![DependencyMetadata {
+                name: "first".to_owned(),
+                version_constraint: "^1".to_owned(),
+            }],
+        };
+        let current = module(serde_json::to_value(metadata).expect("could not serialize"));
+
+        assert_eq!(1, dependencies(&current).len());
+        assert_eq!("1.2.3", version(&current));
+    }
+}
@@ -6,25 +6,26 @@ use axum::{
 use chrono::{DateTime, FixedOffset, Offset, Utc};
 use hyper::StatusCode;
 use module_index_types::{
-    ExtraMetadata, FuncMetadata, ModuleDetailsResponse, MODULE_SCHEMA_VARIANT_ID_FIELD_NAME,
-    MODULE_SCHEMA_VARIANT_VERSION_FIELD_NAME,
+    DependencyMetadata, ExtraMetadata, FuncMetadata, ModuleDetailsResponse,
+    MODULE_SCHEMA_VARIANT_ID_FIELD_NAME, MODULE_SCHEMA_VARIANT_VERSION_FIELD_NAME,
+    MODULE_SIGNATURE_FIELD_NAME, MODULE_SIGNATURE_PUBLIC_KEY_FIELD_NAME,
 };
 use module_index_types::{
     MODULE_BASED_ON_HASH_FIELD_NAME, MODULE_BUNDLE_FIELD_NAME, MODULE_SCHEMA_ID_FIELD_NAME,
 };
-use s3::error::S3Error;
 use sea_orm::{ActiveModelTrait, ColumnTrait, DbErr, EntityTrait, QueryFilter, QuerySelect, Set};
 use serde::{Deserialize, Serialize};
-use si_pkg::{SiPkg, SiPkgError, SiPkgKind};
+use si_pkg::{SiPkg, SiPkgError, SiPkgKind, SiPkgSignature, SiPkgSignatureError};
 use telemetry::prelude::*;
 use thiserror::Error;
 use ulid::Ulid;
 
 use crate::{
-    extract::{Authorization, DbConnection, ExtractedS3Bucket},
+    extract::{Authorization, DbConnection, ExtractedStorage},
     models::si_module::{
         self, make_module_details_response, ModuleId, ModuleKind, SchemaId, SchemaVariantId,
     },
+    storage::StorageError,
 };
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -44,12 +45,16 @@ pub enum UpsertModuleError {
     Multipart(#[from] MultipartError),
     #[error("module with {0} could not be found after insert!")]
     NotFoundAfterInsert(ModuleId),
-    #[error("s3 error: {0}")]
-    S3Error(#[from] S3Error),
     #[error("JSON serialization/deserialization error: {0}")]
     SerdeJson(#[from] serde_json::Error),
+    #[error("module signature and signature public key must be uploaded together")]
+    SignatureIncomplete,
     #[error("module parsing error: {0}")]
     SiPkgError(#[from] SiPkgError),
+    #[error("module signature error: {0}")]
+    SiPkgSignature(#[from] SiPkgSignatureError),
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
     #[error("Ulid decode error: {0}")]
     UlidDecode(#[from] ulid::DecodeError),
     #[error("upload is required")]
@@ -74,7 +79,7 @@ impl IntoResponse for UpsertModuleError {
 // #[debug_handler]
 pub async fn upsert_module_route(
     Authorization { user_claim, .. }: Authorization,
-    ExtractedS3Bucket(s3_bucket): ExtractedS3Bucket,
+    ExtractedStorage(storage): ExtractedStorage,
     DbConnection(txn): DbConnection,
     mut multipart: Multipart,
 ) -> Result<Json<ModuleDetailsResponse>, UpsertModuleError> {
@@ -83,6 +88,8 @@ pub async fn upsert_module_route(
     let mut module_schema_id = None;
     let mut module_schema_variant_id = None;
     let mut module_schema_variant_version = None;
+    let mut module_signature = None;
+    let mut module_signature_public_key = None;
     while let Some(field) = multipart.next_field().await? {
         match field.name() {
             Some(MODULE_BUNDLE_FIELD_NAME) => {
@@ -100,6 +107,12 @@ pub async fn upsert_module_route(
             Some(MODULE_SCHEMA_VARIANT_VERSION_FIELD_NAME) => {
                 module_schema_variant_version = Some(field.text().await?);
             }
+            Some(MODULE_SIGNATURE_FIELD_NAME) => {
+                module_signature = Some(field.text().await?);
+            }
+            Some(MODULE_SIGNATURE_PUBLIC_KEY_FIELD_NAME) => {
+                module_signature_public_key = Some(field.text().await?);
+            }
             _ => debug!("Unknown multipart form field on module upload, skipping..."),
         }
     }
@@ -115,6 +128,21 @@ pub async fn upsert_module_route(
         &module_metadata, &module_based_on_hash, &module_schema_id
     );
 
+    // A signature is optional, but if one is provided it must be valid for this module. Whether
+    // the signer is trusted is up to whoever installs the module.
+    let signature = match (module_signature, module_signature_public_key) {
+        (None, None) => None,
+        (Some(signature), Some(public_key)) => {
+            let signature = SiPkgSignature {
+                public_key,
+                signature,
+            };
+            signature.verify(module_metadata.hash())?;
+            Some(signature)
+        }
+        _ => return Err(UpsertModuleError::SignatureIncomplete),
+    };
+
     let version = module_metadata.version().to_owned();
     let module_kind = match module_metadata.kind() {
         SiPkgKind::WorkspaceBackup => ModuleKind::WorkspaceBackup,
@@ -123,7 +151,7 @@ pub async fn upsert_module_route(
 
     let new_schema_id = Some(SchemaId(Ulid::new()));
     let schema_id = match module_kind {
-        ModuleKind::WorkspaceBackup => None,
+        ModuleKind::WorkspaceBackup | ModuleKind::WorkspaceTemplate => None,
         ModuleKind::Module => match module_schema_id {
             Some(schema_id_string) => Some(SchemaId(Ulid::from_string(&schema_id_string)?)),
             None => match module_based_on_hash {
@@ -159,11 +187,12 @@ pub async fn upsert_module_route(
         info!("module gets schema id: {}", schema_id.0);
     }
 
-    let schemas: Vec<String> = loaded_module
-        .schemas()?
+    let loaded_schemas = loaded_module.schemas()?;
+    let schemas: Vec<String> = loaded_schemas.iter().map(|s| s.name().to_owned()).collect();
+    let schema_category = loaded_schemas
         .iter()
-        .map(|s| s.name().to_owned())
-        .collect();
+        .find_map(|s| s.data().map(|data| data.category().to_owned()))
+        .filter(|category| !category.is_empty());
     let funcs: Vec<FuncMetadata> = loaded_module
         .funcs()?
         .iter()
@@ -174,8 +203,25 @@ pub async fn upsert_module_route(
         })
         .collect();
 
+    let dependencies: Vec<DependencyMetadata> = loaded_module
+        .dependencies()?
+        .iter()
+        .map(|d| DependencyMetadata {
+            name: d.name().to_owned(),
+            version_constraint: d.version_constraint().to_owned(),
+        })
+        .collect();
+
+    let search_text = search_text(
+        module_metadata.name(),
+        module_metadata.description(),
+        schema_category.as_deref(),
+        &schemas,
+        &funcs,
+    );
+
     let schema_variant_id = match module_kind {
-        ModuleKind::WorkspaceBackup => None,
+        ModuleKind::WorkspaceBackup | ModuleKind::WorkspaceTemplate => None,
         ModuleKind::Module => match module_schema_variant_id {
             Some(schema_variant_id_string) => Some(SchemaVariantId(Ulid::from_string(
                 &schema_variant_id_string,
@@ -199,18 +245,22 @@ pub async fn upsert_module_route(
             version,
             schemas,
             funcs,
+            dependencies,
         })?),
         kind: Set(module_kind),
         schema_id: Set(schema_id),
         schema_variant_id: Set(schema_variant_id),
         schema_variant_version: Set(module_schema_variant_version),
+        signature: Set(signature.as_ref().map(|s| s.signature.to_owned())),
+        signature_public_key: Set(signature.map(|s| s.public_key)),
+        schema_category: Set(schema_category),
+        search_text: Set(Some(search_text)),
         ..Default::default() // all other attributes are `NotSet`
     };
 
     // TODO: put below
-    // upload to s3
-    s3_bucket
-        .put_object(format!("{}.sipkg", module_metadata.hash()), &data)
+    storage
+        .put(&format!("{}.sipkg", module_metadata.hash()), &data)
         .await?;
 
     let new_module: si_module::Model = new_module.insert(&txn).await?;
@@ -226,3 +276,26 @@ pub async fn upsert_module_route(
 
     Ok(Json(make_module_details_response(module, linked_modules)))
 }
+
+/// Everything a module can be found by in a full text search, joined into one document.
+fn search_text(
+    name: &str,
+    description: &str,
+    schema_category: Option<&str>,
+    schemas: &[String],
+    funcs: &[FuncMetadata],
+) -> String {
+    let mut parts = vec![name, description];
+    parts.extend(schema_category);
+    parts.extend(schemas.iter().map(String::as_str));
+    for func in funcs {
+        parts.push(&func.name);
+        parts.extend(func.display_name.as_deref());
+    }
+
+    parts
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
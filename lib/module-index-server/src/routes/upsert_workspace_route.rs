@@ -5,7 +5,6 @@ use axum::{
 };
 use chrono::{DateTime, FixedOffset, Offset, Utc};
 use hyper::StatusCode;
-use s3::error::S3Error;
 use sea_orm::{ActiveModelTrait, DbErr, Set};
 use serde::{Deserialize, Serialize};
 use si_hash::Hash;
@@ -15,8 +14,9 @@ use thiserror::Error;
 
 use crate::models::si_module::ModuleKind;
 use crate::{
-    extract::{Authorization, DbConnection, ExtractedS3Bucket},
+    extract::{Authorization, DbConnection, ExtractedStorage},
     models::si_module,
+    storage::StorageError,
 };
 use module_index_types::ExtraMetadata;
 
@@ -35,12 +35,12 @@ pub enum UpsertWorkspaceError {
     IoError(#[from] std::io::Error),
     #[error("multipart decode error: {0}")]
     Multipart(#[from] MultipartError),
-    #[error("s3 error: {0}")]
-    S3Error(#[from] S3Error),
     #[error("JSON serialization/deserialization error: {0}")]
     SerdeJson(#[from] serde_json::Error),
     #[error("module parsing error: {0}")]
     SiPkgError(#[from] SiPkgError),
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
     #[error("upload is required")]
     UploadRequiredError,
 }
@@ -62,7 +62,7 @@ impl IntoResponse for UpsertWorkspaceError {
 
 pub async fn upsert_workspace_route(
     Authorization { user_claim, .. }: Authorization,
-    ExtractedS3Bucket(s3_bucket): ExtractedS3Bucket,
+    ExtractedStorage(storage): ExtractedStorage,
     DbConnection(txn): DbConnection,
     mut multipart: Multipart,
 ) -> Result<(), UpsertWorkspaceError> {
@@ -95,13 +95,14 @@ pub async fn upsert_workspace_route(
             version,
             schemas: vec![],
             funcs: vec![],
+            dependencies: vec![],
         })?),
 
         ..Default::default() // all other attributes are `NotSet`
     };
 
-    s3_bucket
-        .put_object(format!("{}.workspace_export", hash), &data)
+    storage
+        .put(&format!("{}.workspace_export", hash), &data)
         .await?;
 
     let _new_module: si_module::Model = dbg!(new_module.insert(&txn).await)?;
@@ -0,0 +1,111 @@
+use axum::{
+    extract::{multipart::MultipartError, Multipart},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, FixedOffset, Offset, Utc};
+use hyper::StatusCode;
+use sea_orm::{ActiveModelTrait, DbErr, Set};
+use si_hash::Hash;
+use si_pkg::{SiPkgError, WorkspaceTemplate};
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::models::si_module::ModuleKind;
+use crate::{
+    extract::{Authorization, DbConnection, ExtractedStorage},
+    models::si_module,
+    storage::StorageError,
+};
+use module_index_types::ExtraMetadata;
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum UpsertWorkspaceTemplateError {
+    #[error("db error: {0}")]
+    DbErr(#[from] DbErr),
+    #[error("file upload error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("multipart decode error: {0}")]
+    Multipart(#[from] MultipartError),
+    #[error("JSON serialization/deserialization error: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("module parsing error: {0}")]
+    SiPkgError(#[from] SiPkgError),
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("upload is required")]
+    UploadRequiredError,
+}
+
+// TODO: figure out how to not keep this serialization logic here
+impl IntoResponse for UpsertWorkspaceTemplateError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = match self {
+            Self::Multipart(_) | Self::SerdeJson(_) | Self::UploadRequiredError => {
+                (StatusCode::BAD_REQUEST, self.to_string())
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        };
+
+        error!("upsert error: {}", &error_message);
+
+        let body = Json(
+            serde_json::json!({ "error": { "message": error_message, "code": 42, "statusCode": status.as_u16() } }),
+        );
+
+        (status, body).into_response()
+    }
+}
+
+pub async fn upsert_workspace_template_route(
+    Authorization { user_claim, .. }: Authorization,
+    ExtractedStorage(storage): ExtractedStorage,
+    DbConnection(txn): DbConnection,
+    mut multipart: Multipart,
+) -> Result<(), UpsertWorkspaceTemplateError> {
+    let field = match multipart.next_field().await? {
+        Some(f) => f,
+        None => return Err(UpsertWorkspaceTemplateError::UploadRequiredError),
+    };
+    let data = field.bytes().await?;
+
+    let content: WorkspaceTemplate = serde_json::from_slice(&data)?;
+
+    let template_metadata = &content.into_latest().metadata;
+    let hash = Hash::new(&data).to_string();
+    let version = template_metadata.version.to_owned();
+
+    info!("upserting workspace template: {:?}", &template_metadata);
+
+    let new_module = si_module::ActiveModel {
+        name: Set(template_metadata.name.to_owned()),
+        description: Set(Some(template_metadata.description.to_owned())),
+        owner_user_id: Set(user_claim.user_pk.to_string()),
+        owner_display_name: Set(Some(template_metadata.created_by.to_owned())),
+        latest_hash: Set(hash.to_string()),
+        latest_hash_created_at: Set(DateTime::<FixedOffset>::from_naive_utc_and_offset(
+            Utc::now().naive_utc(),
+            Utc.fix(),
+        )),
+        kind: Set(ModuleKind::WorkspaceTemplate),
+        metadata: Set(serde_json::to_value(ExtraMetadata {
+            version,
+            schemas: vec![],
+            funcs: vec![],
+            dependencies: vec![],
+        })?),
+
+        ..Default::default() // all other attributes are `NotSet`
+    };
+
+    storage
+        .put(&format!("{}.workspace_template", hash), &data)
+        .await?;
+
+    let _new_module: si_module::Model = new_module.insert(&txn).await?;
+
+    txn.commit().await?;
+
+    Ok(())
+}
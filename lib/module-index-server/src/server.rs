@@ -5,7 +5,6 @@ use super::routes;
 use axum::routing::IntoMakeService;
 use axum::Router;
 use hyper::server::{accept::Accept, conn::AddrIncoming};
-use s3::creds::{error::CredentialsError, Credentials as AwsCredentials};
 use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr};
 use si_data_pg::{PgPool, PgPoolConfig, PgPoolError};
 use si_posthog::{PosthogClient, PosthogConfig};
@@ -22,7 +21,7 @@ use tower_http::trace::{DefaultMakeSpan, TraceLayer};
 use crate::{
     app_state::{AppState, ShutdownSource},
     jwt_key::{JwtKeyError, JwtPublicSigningKey},
-    s3::S3Config,
+    storage::{self, ModuleStorageRef, StorageError},
     Config,
 };
 
@@ -35,10 +34,6 @@ mod embedded_migrations {
 #[remain::sorted]
 #[derive(Debug, Error)]
 pub enum ServerError {
-    #[error("bad aws config")]
-    AwsConfigError,
-    #[error("aws creds error: {0}")]
-    CredentialsError(#[from] CredentialsError),
     #[error("db error: {0}")]
     DbErr(#[from] DbErr),
     #[error("hyper server error")]
@@ -53,6 +48,8 @@ pub enum ServerError {
     SerdeJson(#[from] serde_json::Error),
     #[error("failed to setup signal handler")]
     Signal(#[source] io::Error),
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
 }
 
 impl From<PgPoolError> for ServerError {
@@ -80,28 +77,10 @@ impl Server<(), ()> {
     ) -> Result<(Server<AddrIncoming, SocketAddr>, broadcast::Receiver<()>)> {
         // socket_addr
 
-        // try to load aws creds from a few different places
-        let aws_creds = match (&config.s3().access_key_id, &config.s3().secret_access_key) {
-            (Some(aws_key), Some(aws_secret)) => {
-                AwsCredentials::new(Some(aws_key), Some(aws_secret), None, None, None)?
-            }
-            (None, None) => match AwsCredentials::from_env() {
-                Ok(creds) => creds,
-                Err(CredentialsError::MissingEnvVar(_, _)) => AwsCredentials::from_profile(None)?,
-                Err(err) => return Err(err.into()),
-            },
-            _ => {
-                return Err(ServerError::AwsConfigError);
-            }
-        };
+        let storage = storage::from_config(config.storage(), config.s3())?;
 
-        let (service, shutdown_rx, shutdown_broadcast_rx) = build_service(
-            pg_pool,
-            jwt_public_signing_key,
-            posthog_client,
-            aws_creds,
-            config.s3().clone(),
-        )?;
+        let (service, shutdown_rx, shutdown_broadcast_rx) =
+            build_service(pg_pool, jwt_public_signing_key, posthog_client, storage)?;
 
         info!(
             "binding to HTTP socket; socket_addr={}",
@@ -224,8 +203,7 @@ pub fn build_service(
     pg_pool: DatabaseConnection,
     jwt_public_signing_key: JwtPublicSigningKey,
     posthog_client: PosthogClient,
-    aws_creds: AwsCredentials,
-    s3_config: S3Config,
+    storage: ModuleStorageRef,
 ) -> Result<(Router, oneshot::Receiver<()>, broadcast::Receiver<()>)> {
     let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
     let (shutdown_broadcast_tx, shutdown_broadcast_rx) = broadcast::channel(1);
@@ -234,8 +212,7 @@ pub fn build_service(
         pg_pool,
         jwt_public_signing_key,
         posthog_client,
-        aws_creds,
-        s3_config,
+        storage,
         shutdown_tx,
     );
 
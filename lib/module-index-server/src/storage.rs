@@ -0,0 +1,112 @@
+//! Where uploaded module and workspace blobs are kept. Blobs are addressed by a key made from
+//! their content hash, e.g. `{hash}.sipkg`, so a key is only ever written with the same bytes.
+
+use std::{fmt, path::PathBuf, sync::Arc};
+
+use axum::{
+    async_trait,
+    body::Bytes,
+    http::header,
+    response::{IntoResponse, Redirect, Response},
+};
+use s3::{creds::error::CredentialsError, error::S3Error};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::s3::S3Config;
+
+mod file_system;
+mod s3_bucket;
+
+pub use self::{file_system::FileSystemStorage, s3_bucket::S3Storage};
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("bad aws config")]
+    AwsConfig,
+    #[error("aws creds error: {0}")]
+    Credentials(#[from] CredentialsError),
+    #[error("invalid storage key: {0}")]
+    InvalidKey(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("no blob stored under key: {0}")]
+    NotFound(String),
+    #[error("s3 error: {0}")]
+    S3(#[from] S3Error),
+    #[error("invalid s3 region {0}: {1}")]
+    S3Region(String, String),
+}
+
+pub type StorageResult<T> = Result<T, StorageError>;
+
+/// Selects the [`ModuleStorage`] implementation the server uses.
+#[remain::sorted]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(tag = "backend", rename_all = "camelCase")]
+pub enum StorageConfig {
+    /// Blobs are kept under a local directory.
+    #[serde(rename_all = "camelCase")]
+    FileSystem { path: PathBuf },
+    /// Blobs are kept in the bucket described by the `s3` config.
+    #[default]
+    S3,
+}
+
+/// How a route hands a stored blob back to the client.
+pub enum ModuleDownload {
+    /// The client should fetch the blob from this (presigned) url.
+    Redirect(String),
+    /// The blob itself.
+    Bytes(Bytes),
+}
+
+impl IntoResponse for ModuleDownload {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Redirect(url) => Redirect::temporary(&url).into_response(),
+            Self::Bytes(bytes) => {
+                ([(header::CONTENT_TYPE, "application/octet-stream")], bytes).into_response()
+            }
+        }
+    }
+}
+
+/// A store for module and workspace blobs.
+#[async_trait]
+pub trait ModuleStorage: fmt::Debug + Send + Sync {
+    /// Stores the blob under the given key.
+    async fn put(&self, key: &str, data: &[u8]) -> StorageResult<()>;
+
+    /// Returns a way for the client to download the blob stored under the given key.
+    async fn download(&self, key: &str) -> StorageResult<ModuleDownload>;
+}
+
+/// A shared handle to the configured [`ModuleStorage`].
+pub type ModuleStorageRef = Arc<dyn ModuleStorage>;
+
+/// Builds the storage selected in the config.
+pub fn from_config(
+    storage_config: &StorageConfig,
+    s3_config: &S3Config,
+) -> StorageResult<ModuleStorageRef> {
+    Ok(match storage_config {
+        StorageConfig::FileSystem { path } => Arc::new(FileSystemStorage::new(path)?),
+        StorageConfig::S3 => Arc::new(S3Storage::from_config(s3_config)?),
+    })
+}
+
+/// Keys become file names, so they may only contain characters that are safe in one.
+fn validate_key(key: &str) -> StorageResult<()> {
+    if key.is_empty()
+        || key.starts_with('.')
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
+    {
+        return Err(StorageError::InvalidKey(key.to_owned()));
+    }
+
+    Ok(())
+}
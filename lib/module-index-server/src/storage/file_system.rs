@@ -0,0 +1,177 @@
+use std::{io, path::PathBuf};
+
+use axum::async_trait;
+use tokio::fs;
+use ulid::Ulid;
+
+use super::{validate_key, ModuleDownload, ModuleStorage, StorageError, StorageResult};
+
+/// Keeps blobs in a local directory, for installs without object storage. Blobs are spread over
+/// subdirectories named after the first two characters of their key so that no single directory
+/// grows too large.
+#[derive(Debug)]
+pub struct FileSystemStorage {
+    root: PathBuf,
+}
+
+impl FileSystemStorage {
+    /// Uses `root` as the storage directory, creating it if needed.
+    pub fn new(root: impl Into<PathBuf>) -> StorageResult<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    fn blob_path(&self, key: &str) -> StorageResult<PathBuf> {
+        validate_key(key)?;
+        let shard: String = key.chars().take(2).collect();
+        Ok(self.root.join(shard).join(key))
+    }
+}
+
+#[async_trait]
+impl ModuleStorage for FileSystemStorage {
+    async fn put(&self, key: &str, data: &[u8]) -> StorageResult<()> {
+        let path = self.blob_path(key)?;
+        // Keys are content addressed, so a blob that is already stored has the same bytes
+        if fs::try_exists(&path).await? {
+            return Ok(());
+        }
+
+        let dir = path.parent().unwrap_or(self.root.as_path());
+        fs::create_dir_all(dir).await?;
+
+        // Write beside the blob and rename into place so that a reader never sees a partial blob
+        let tmp_path = dir.join(format!(".{key}.{}.tmp", Ulid::new()));
+        fs::write(&tmp_path, data).await?;
+        if let Err(err) = fs::rename(&tmp_path, &path).await {
+            let _ = fs::remove_file(&tmp_path).await;
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    async fn download(&self, key: &str) -> StorageResult<ModuleDownload> {
+        let data = fs::read(self.blob_path(key)?)
+            .await
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => StorageError::NotFound(key.to_owned()),
+                _ => err.into(),
+            })?;
+        Ok(ModuleDownload::Bytes(data.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    struct TestRoot(PathBuf);
+
+    impl TestRoot {
+        fn new() -> Self {
+            Self(env::temp_dir().join(format!("module-index-storage-{}", Ulid::new())))
+        }
+    }
+
+    impl Drop for TestRoot {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    async fn downloaded_bytes(storage: &FileSystemStorage, key: &str) -> Vec<u8> {
+        match storage.download(key).await.expect("could not download") {
+            ModuleDownload::Bytes(bytes) => bytes.to_vec(),
+            ModuleDownload::Redirect(url) => panic!("expected bytes, got a redirect to {url}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trips_blobs() {
+        let root = TestRoot::new();
+        let storage = FileSystemStorage::new(&root.0).expect("could not create storage");
+
+        storage
+            .put("abcdef.sipkg", b"module bytes")
+            .await
+            .expect("could not put blob");
+
+        assert_eq!(
+            b"module bytes".to_vec(),
+            downloaded_bytes(&storage, "abcdef.sipkg").await
+        );
+    }
+
+    #[tokio::test]
+    async fn shards_blobs_by_key_prefix() {
+        let root = TestRoot::new();
+        let storage = FileSystemStorage::new(&root.0).expect("could not create storage");
+
+        storage
+            .put("abcdef.sipkg", b"module bytes")
+            .await
+            .expect("could not put blob");
+
+        assert!(root.0.join("ab").join("abcdef.sipkg").is_file());
+    }
+
+    #[tokio::test]
+    async fn writes_leave_no_temporary_files_and_keep_existing_blobs() {
+        let root = TestRoot::new();
+        let storage = FileSystemStorage::new(&root.0).expect("could not create storage");
+
+        storage
+            .put("abcdef.sipkg", b"first")
+            .await
+            .expect("could not put blob");
+        // Keys are content addressed, so a second write under the same key is skipped
+        storage
+            .put("abcdef.sipkg", b"second")
+            .await
+            .expect("could not put blob again");
+
+        let entries: Vec<String> = std::fs::read_dir(root.0.join("ab"))
+            .expect("could not read shard")
+            .map(|entry| {
+                entry
+                    .expect("could not read entry")
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        assert_eq!(vec!["abcdef.sipkg".to_string()], entries);
+        assert_eq!(
+            b"first".to_vec(),
+            downloaded_bytes(&storage, "abcdef.sipkg").await
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_blobs_are_not_found() {
+        let root = TestRoot::new();
+        let storage = FileSystemStorage::new(&root.0).expect("could not create storage");
+
+        assert!(matches!(
+            storage.download("abcdef.sipkg").await,
+            Err(StorageError::NotFound(key)) if key == "abcdef.sipkg"
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_keys_that_are_not_file_names() {
+        let root = TestRoot::new();
+        let storage = FileSystemStorage::new(&root.0).expect("could not create storage");
+
+        for key in ["", "../escape", ".hidden", "a/b"] {
+            assert!(matches!(
+                storage.put(key, b"bytes").await,
+                Err(StorageError::InvalidKey(_))
+            ));
+        }
+    }
+}
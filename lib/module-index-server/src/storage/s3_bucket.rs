@@ -0,0 +1,60 @@
+use axum::async_trait;
+use s3::{
+    creds::{error::CredentialsError, Credentials as AwsCredentials},
+    Bucket as S3Bucket, Region as AwsRegion,
+};
+
+use super::{ModuleDownload, ModuleStorage, StorageError, StorageResult};
+use crate::s3::S3Config;
+
+/// How long a presigned download url stays valid, in seconds.
+const PRESIGNED_URL_EXPIRY_SECS: u32 = 60 * 5;
+
+/// Keeps blobs in an S3 bucket and hands out presigned urls to download them.
+#[derive(Debug)]
+pub struct S3Storage {
+    bucket: S3Bucket,
+}
+
+impl S3Storage {
+    pub fn from_config(s3_config: &S3Config) -> StorageResult<Self> {
+        // try to load aws creds from a few different places
+        let aws_creds = match (&s3_config.access_key_id, &s3_config.secret_access_key) {
+            (Some(aws_key), Some(aws_secret)) => {
+                AwsCredentials::new(Some(aws_key), Some(aws_secret), None, None, None)?
+            }
+            (None, None) => match AwsCredentials::from_env() {
+                Ok(creds) => creds,
+                Err(CredentialsError::MissingEnvVar(_, _)) => AwsCredentials::from_profile(None)?,
+                Err(err) => return Err(err.into()),
+            },
+            _ => {
+                return Err(StorageError::AwsConfig);
+            }
+        };
+
+        let region = s3_config
+            .region
+            .parse::<AwsRegion>()
+            .map_err(|err| StorageError::S3Region(s3_config.region.to_owned(), err.to_string()))?;
+        let bucket = S3Bucket::new(&s3_config.bucket, region, aws_creds)?;
+
+        Ok(Self { bucket })
+    }
+}
+
+#[async_trait]
+impl ModuleStorage for S3Storage {
+    async fn put(&self, key: &str, data: &[u8]) -> StorageResult<()> {
+        self.bucket.put_object(key, data).await?;
+        Ok(())
+    }
+
+    async fn download(&self, key: &str) -> StorageResult<ModuleDownload> {
+        let url = self
+            .bucket
+            .presign_get(key, PRESIGNED_URL_EXPIRY_SECS, None)
+            .await?;
+        Ok(ModuleDownload::Redirect(url))
+    }
+}
@@ -7,6 +7,8 @@ pub const MODULE_BASED_ON_HASH_FIELD_NAME: &str = "based_on_hash";
 pub const MODULE_SCHEMA_ID_FIELD_NAME: &str = "schema_id";
 pub const MODULE_SCHEMA_VARIANT_ID_FIELD_NAME: &str = "schema_variant_id";
 pub const MODULE_SCHEMA_VARIANT_VERSION_FIELD_NAME: &str = "schema_variant_version";
+pub const MODULE_SIGNATURE_FIELD_NAME: &str = "signature";
+pub const MODULE_SIGNATURE_PUBLIC_KEY_FIELD_NAME: &str = "signature_public_key";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -26,6 +28,20 @@ pub struct BuiltinsDetailsResponse {
 #[serde(rename_all = "camelCase")]
 pub struct ListModulesResponse {
     pub modules: Vec<ModuleDetailsResponse>,
+    /// The number of modules matching the request, across all pages
+    #[serde(default)]
+    pub total: Option<u64>,
+}
+
+/// The order of modules in a [`ListModulesResponse`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModuleSort {
+    /// Most installed first
+    Installs,
+    /// Most recently published first
+    #[default]
+    Recent,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +60,17 @@ pub struct ModuleDetailsResponse {
     pub past_hashes: Option<Vec<String>>,
     pub schema_variant_id: Option<String>,
     pub schema_variant_version: Option<String>,
+    /// The base64 encoded detached ed25519 signature over the module hash, if it was signed
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// The base64 encoded public key that made the signature
+    #[serde(default)]
+    pub signature_public_key: Option<String>,
+    #[serde(default)]
+    pub schema_category: Option<String>,
+    /// How many times the module has been downloaded for install
+    #[serde(default)]
+    pub install_count: i64,
 }
 
 impl ModuleDetailsResponse {
@@ -68,6 +95,25 @@ pub struct ExtraMetadata {
     pub version: String,
     pub schemas: Vec<String>,
     pub funcs: Vec<FuncMetadata>,
+    #[serde(default)]
+    pub dependencies: Vec<DependencyMetadata>,
+}
+
+/// A module that has to be installed before the module that declares it. See
+/// `si_pkg::DependencySpec` for the version constraint format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyMetadata {
+    pub name: String,
+    pub version_constraint: String,
+}
+
+/// Every module a module depends on, directly or not, in the order they should be installed:
+/// each module comes after all of the modules it depends on. The module itself is not included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveDependenciesResponse {
+    pub modules: Vec<ModuleDetailsResponse>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
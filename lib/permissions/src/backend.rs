@@ -0,0 +1,181 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use si_data_spicedb::{Permission, Relationship, Relationships, SpiceDbClient, ZedToken};
+use strum::{Display, EnumString};
+
+use crate::{
+    embedded::{EmbeddedPermissionsBackend, EmbeddedPermissionsConfig},
+    Error, Result,
+};
+
+/// The operations a store of relationships must support for the builders in this crate to
+/// create, read and check permissions against it.
+///
+/// [`SpiceDbClient`] is the production implementation, while
+/// [`EmbeddedPermissionsBackend`] evaluates the same schema in-process for development and tests.
+#[async_trait]
+pub trait PermissionsBackend: Send {
+    /// Replaces the schema used to validate relationships and evaluate permissions.
+    async fn write_schema(&mut self, schema: &str) -> Result<Option<ZedToken>>;
+
+    /// Reads the relationships matching the object and relation of the given relationship.
+    async fn read_relationship(&mut self, relationship: Relationship) -> Result<Relationships>;
+
+    /// Writes the given relationships.
+    async fn create_relationships(
+        &mut self,
+        relationships: Relationships,
+    ) -> Result<Option<ZedToken>>;
+
+    /// Removes the given relationships.
+    async fn delete_relationships(
+        &mut self,
+        relationships: Relationships,
+    ) -> Result<Option<ZedToken>>;
+
+    /// Checks if the subject holds the permission on the resource.
+    async fn check_permissions(&mut self, permission: Permission) -> Result<bool>;
+}
+
+#[async_trait]
+impl PermissionsBackend for SpiceDbClient {
+    async fn write_schema(&mut self, schema: &str) -> Result<Option<ZedToken>> {
+        SpiceDbClient::write_schema(self, schema)
+            .await
+            .map_err(Error::SpiceDb)
+    }
+
+    async fn read_relationship(&mut self, relationship: Relationship) -> Result<Relationships> {
+        SpiceDbClient::read_relationship(self, relationship)
+            .await
+            .map_err(Error::SpiceDb)
+    }
+
+    async fn create_relationships(
+        &mut self,
+        relationships: Relationships,
+    ) -> Result<Option<ZedToken>> {
+        SpiceDbClient::create_relationships(self, relationships)
+            .await
+            .map_err(Error::SpiceDb)
+    }
+
+    async fn delete_relationships(
+        &mut self,
+        relationships: Relationships,
+    ) -> Result<Option<ZedToken>> {
+        SpiceDbClient::delete_relationships(self, relationships)
+            .await
+            .map_err(Error::SpiceDb)
+    }
+
+    async fn check_permissions(&mut self, permission: Permission) -> Result<bool> {
+        SpiceDbClient::check_permissions(self, permission)
+            .await
+            .map_err(Error::SpiceDb)
+    }
+}
+
+/// Selects which [`PermissionsBackend`] a service uses.
+#[remain::sorted]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Display, EnumString, Eq, PartialEq, Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum PermissionsBackendKind {
+    /// Evaluate permissions in-process with [`EmbeddedPermissionsBackend`].
+    Embedded,
+    /// Evaluate permissions with a SpiceDB server.
+    #[default]
+    SpiceDb,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PermissionsConfig {
+    #[serde(default)]
+    pub backend: PermissionsBackendKind,
+    #[serde(default)]
+    pub embedded: EmbeddedPermissionsConfig,
+}
+
+/// A [`PermissionsBackend`] chosen at runtime from a [`PermissionsConfig`].
+#[remain::sorted]
+#[derive(Clone)]
+pub enum PermissionsClient {
+    Embedded(EmbeddedPermissionsBackend),
+    SpiceDb(SpiceDbClient),
+}
+
+impl PermissionsClient {
+    pub fn kind(&self) -> PermissionsBackendKind {
+        match self {
+            Self::Embedded(_) => PermissionsBackendKind::Embedded,
+            Self::SpiceDb(_) => PermissionsBackendKind::SpiceDb,
+        }
+    }
+}
+
+impl From<EmbeddedPermissionsBackend> for PermissionsClient {
+    fn from(value: EmbeddedPermissionsBackend) -> Self {
+        Self::Embedded(value)
+    }
+}
+
+impl From<SpiceDbClient> for PermissionsClient {
+    fn from(value: SpiceDbClient) -> Self {
+        Self::SpiceDb(value)
+    }
+}
+
+#[async_trait]
+impl PermissionsBackend for PermissionsClient {
+    async fn write_schema(&mut self, schema: &str) -> Result<Option<ZedToken>> {
+        match self {
+            Self::Embedded(backend) => backend.write_schema(schema).await,
+            Self::SpiceDb(client) => PermissionsBackend::write_schema(client, schema).await,
+        }
+    }
+
+    async fn read_relationship(&mut self, relationship: Relationship) -> Result<Relationships> {
+        match self {
+            Self::Embedded(backend) => backend.read_relationship(relationship).await,
+            Self::SpiceDb(client) => {
+                PermissionsBackend::read_relationship(client, relationship).await
+            }
+        }
+    }
+
+    async fn create_relationships(
+        &mut self,
+        relationships: Relationships,
+    ) -> Result<Option<ZedToken>> {
+        match self {
+            Self::Embedded(backend) => backend.create_relationships(relationships).await,
+            Self::SpiceDb(client) => {
+                PermissionsBackend::create_relationships(client, relationships).await
+            }
+        }
+    }
+
+    async fn delete_relationships(
+        &mut self,
+        relationships: Relationships,
+    ) -> Result<Option<ZedToken>> {
+        match self {
+            Self::Embedded(backend) => backend.delete_relationships(relationships).await,
+            Self::SpiceDb(client) => {
+                PermissionsBackend::delete_relationships(client, relationships).await
+            }
+        }
+    }
+
+    async fn check_permissions(&mut self, permission: Permission) -> Result<bool> {
+        match self {
+            Self::Embedded(backend) => backend.check_permissions(permission).await,
+            Self::SpiceDb(client) => {
+                PermissionsBackend::check_permissions(client, permission).await
+            }
+        }
+    }
+}
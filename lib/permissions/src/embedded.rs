@@ -0,0 +1,411 @@
+//! An in-process [`PermissionsBackend`] for development and tests.
+//!
+//! Relationships are kept in memory or in a Postgres table and permissions are evaluated against
+//! the same schema we load into SpiceDB, restricted to direct relations and permission unions.
+
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use async_recursion::async_recursion;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use si_data_pg::{PgError, PgPool, PgPoolError};
+use si_data_spicedb::{Permission, PermissionsObject, Relationship, Relationships, ZedToken};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::{PermissionsBackend, Result};
+
+mod schema;
+
+use schema::{Member, Schema};
+
+/// The schema deployed to SpiceDB. This is a copy of `component/spicedb/schema.zed`, kept inside
+/// the crate so that every build can find it; a test checks that the two match.
+pub const DEFAULT_SCHEMA: &str = include_str!("embedded/schema.zed");
+
+/// Permissions may reference each other; this bounds how deep a check may recurse.
+const MAX_CHECK_DEPTH: usize = 50;
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum EmbeddedError {
+    #[error("maximum permission check depth exceeded while evaluating {0}")]
+    MaxDepthExceeded(String),
+    #[error("pg error: {0}")]
+    Pg(#[from] PgError),
+    #[error("pg pool error: {0}")]
+    PgPool(#[from] PgPoolError),
+    #[error("schema parse error: {0}")]
+    SchemaParse(String),
+    #[error("subject type {subject_type} is not allowed for relation {definition}#{relation}")]
+    SubjectTypeNotAllowed {
+        definition: String,
+        relation: String,
+        subject_type: String,
+    },
+    #[error("unknown definition: {0}")]
+    UnknownDefinition(String),
+    #[error("{definition} has no relation or permission named {name}")]
+    UnknownMember { definition: String, name: String },
+    #[error("unsupported schema construct: {0}")]
+    UnsupportedSchema(String),
+}
+
+pub type EmbeddedResult<T> = std::result::Result<T, EmbeddedError>;
+
+/// Where the embedded backend keeps its relationships.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddedStorage {
+    /// Relationships live for as long as the process.
+    #[default]
+    Memory,
+    /// Relationships are stored in the service's Postgres database.
+    Postgres,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct EmbeddedPermissionsConfig {
+    #[serde(default)]
+    pub storage: EmbeddedStorage,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+struct Tuple {
+    object_type: String,
+    object_id: String,
+    relation: String,
+    subject_type: String,
+    subject_id: String,
+}
+
+impl Tuple {
+    fn from_relationship(relationship: &Relationship) -> Self {
+        Self {
+            object_type: relationship.object().r#type().to_owned(),
+            object_id: relationship.object().id().to_owned(),
+            relation: relationship.relation().to_owned(),
+            subject_type: relationship.subject().r#type().to_owned(),
+            subject_id: relationship.subject().id().to_owned(),
+        }
+    }
+
+    fn into_relationship(self) -> Relationship {
+        Relationship::new(
+            PermissionsObject::new(self.object_type, self.object_id),
+            self.relation,
+            PermissionsObject::new(self.subject_type, self.subject_id),
+            None,
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Store {
+    Memory(Arc<RwLock<BTreeSet<Tuple>>>),
+    Postgres(PgPool),
+}
+
+impl Store {
+    async fn insert(&self, tuples: Vec<Tuple>) -> EmbeddedResult<()> {
+        match self {
+            Self::Memory(set) => {
+                set.write().await.extend(tuples);
+            }
+            Self::Postgres(pg_pool) => {
+                let client = pg_pool.get().await?;
+                for tuple in tuples {
+                    client
+                        .execute(
+                            "INSERT INTO embedded_permissions_relationships
+                                (object_type, object_id, relation, subject_type, subject_id)
+                                VALUES ($1, $2, $3, $4, $5)
+                                ON CONFLICT DO NOTHING",
+                            &[
+                                &tuple.object_type,
+                                &tuple.object_id,
+                                &tuple.relation,
+                                &tuple.subject_type,
+                                &tuple.subject_id,
+                            ],
+                        )
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, tuples: Vec<Tuple>) -> EmbeddedResult<()> {
+        match self {
+            Self::Memory(set) => {
+                let mut set = set.write().await;
+                for tuple in tuples {
+                    set.remove(&tuple);
+                }
+            }
+            Self::Postgres(pg_pool) => {
+                let client = pg_pool.get().await?;
+                for tuple in tuples {
+                    client
+                        .execute(
+                            "DELETE FROM embedded_permissions_relationships
+                                WHERE object_type = $1 AND object_id = $2 AND relation = $3
+                                AND subject_type = $4 AND subject_id = $5",
+                            &[
+                                &tuple.object_type,
+                                &tuple.object_id,
+                                &tuple.relation,
+                                &tuple.subject_type,
+                                &tuple.subject_id,
+                            ],
+                        )
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the tuples matching the filter, where an empty object id or relation matches
+    /// anything, mirroring SpiceDB's relationship filters.
+    async fn find(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        relation: &str,
+    ) -> EmbeddedResult<Vec<Tuple>> {
+        match self {
+            Self::Memory(set) => Ok(set
+                .read()
+                .await
+                .iter()
+                .filter(|tuple| {
+                    tuple.object_type == object_type
+                        && (object_id.is_empty() || tuple.object_id == object_id)
+                        && (relation.is_empty() || tuple.relation == relation)
+                })
+                .cloned()
+                .collect()),
+            Self::Postgres(pg_pool) => {
+                let client = pg_pool.get().await?;
+                let rows = client
+                    .query(
+                        "SELECT object_type, object_id, relation, subject_type, subject_id
+                            FROM embedded_permissions_relationships
+                            WHERE object_type = $1
+                            AND ($2 = '' OR object_id = $2)
+                            AND ($3 = '' OR relation = $3)",
+                        &[&object_type, &object_id, &relation],
+                    )
+                    .await?;
+
+                let mut tuples = Vec::with_capacity(rows.len());
+                for row in rows {
+                    tuples.push(Tuple {
+                        object_type: row.try_get("object_type").map_err(PgError::from)?,
+                        object_id: row.try_get("object_id").map_err(PgError::from)?,
+                        relation: row.try_get("relation").map_err(PgError::from)?,
+                        subject_type: row.try_get("subject_type").map_err(PgError::from)?,
+                        subject_id: row.try_get("subject_id").map_err(PgError::from)?,
+                    });
+                }
+                Ok(tuples)
+            }
+        }
+    }
+
+    async fn contains(&self, tuple: &Tuple) -> EmbeddedResult<bool> {
+        match self {
+            Self::Memory(set) => Ok(set.read().await.contains(tuple)),
+            Self::Postgres(_) => Ok(self
+                .find(&tuple.object_type, &tuple.object_id, &tuple.relation)
+                .await?
+                .iter()
+                .any(|found| found == tuple)),
+        }
+    }
+}
+
+/// Evaluates permissions in-process instead of calling out to SpiceDB.
+///
+/// Clones share their schema and relationships, so a single backend can be handed to every
+/// request handler the same way a [`SpiceDbClient`](si_data_spicedb::SpiceDbClient) is.
+#[derive(Clone, Debug)]
+pub struct EmbeddedPermissionsBackend {
+    schema: Arc<RwLock<Schema>>,
+    store: Store,
+    revision: Arc<AtomicU64>,
+}
+
+impl EmbeddedPermissionsBackend {
+    /// Creates a backend holding relationships in memory, using [`DEFAULT_SCHEMA`].
+    pub fn in_memory() -> EmbeddedResult<Self> {
+        Self::new(Store::Memory(Default::default()))
+    }
+
+    /// Creates a backend holding relationships in Postgres, using [`DEFAULT_SCHEMA`].
+    ///
+    /// The `embedded_permissions_relationships` table is created by the dal migrations.
+    pub fn postgres(pg_pool: PgPool) -> EmbeddedResult<Self> {
+        Self::new(Store::Postgres(pg_pool))
+    }
+
+    pub fn from_config(
+        config: &EmbeddedPermissionsConfig,
+        pg_pool: &PgPool,
+    ) -> EmbeddedResult<Self> {
+        match config.storage {
+            EmbeddedStorage::Memory => Self::in_memory(),
+            EmbeddedStorage::Postgres => Self::postgres(pg_pool.clone()),
+        }
+    }
+
+    fn new(store: Store) -> EmbeddedResult<Self> {
+        Ok(Self {
+            schema: Arc::new(RwLock::new(Schema::parse(DEFAULT_SCHEMA)?)),
+            store,
+            revision: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    fn next_zed_token(&self) -> ZedToken {
+        let revision = self.revision.fetch_add(1, Ordering::SeqCst) + 1;
+        ZedToken::new(format!("embedded-{revision}"))
+    }
+
+    async fn validate(&self, tuples: &[Tuple]) -> EmbeddedResult<()> {
+        let schema = self.schema.read().await;
+        for tuple in tuples {
+            let definition = schema.definition(&tuple.object_type)?;
+            match definition.member(&tuple.relation) {
+                Some(Member::Relation(subject_types)) => {
+                    if !subject_types.contains(&tuple.subject_type) {
+                        return Err(EmbeddedError::SubjectTypeNotAllowed {
+                            definition: tuple.object_type.clone(),
+                            relation: tuple.relation.clone(),
+                            subject_type: tuple.subject_type.clone(),
+                        });
+                    }
+                }
+                Some(Member::Permission(_)) | None => {
+                    return Err(EmbeddedError::UnknownMember {
+                        definition: tuple.object_type.clone(),
+                        name: tuple.relation.clone(),
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[async_recursion]
+    async fn check(
+        &self,
+        schema: &Schema,
+        object: &PermissionsObject,
+        name: &str,
+        subject: &PermissionsObject,
+        depth: usize,
+    ) -> EmbeddedResult<bool> {
+        if depth > MAX_CHECK_DEPTH {
+            return Err(EmbeddedError::MaxDepthExceeded(format!(
+                "{}:{}#{name}",
+                object.r#type(),
+                object.id()
+            )));
+        }
+
+        let definition = schema.definition(object.r#type())?;
+        match definition.member(name) {
+            Some(Member::Relation(_)) => {
+                self.store
+                    .contains(&Tuple {
+                        object_type: object.r#type().to_owned(),
+                        object_id: object.id().to_owned(),
+                        relation: name.to_owned(),
+                        subject_type: subject.r#type().to_owned(),
+                        subject_id: subject.id().to_owned(),
+                    })
+                    .await
+            }
+            Some(Member::Permission(members)) => {
+                for member in members {
+                    if self
+                        .check(schema, object, member, subject, depth + 1)
+                        .await?
+                    {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            None => Err(EmbeddedError::UnknownMember {
+                definition: object.r#type().to_owned(),
+                name: name.to_owned(),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl PermissionsBackend for EmbeddedPermissionsBackend {
+    async fn write_schema(&mut self, schema: &str) -> Result<Option<ZedToken>> {
+        let schema = Schema::parse(schema)?;
+        *self.schema.write().await = schema;
+        Ok(Some(self.next_zed_token()))
+    }
+
+    async fn read_relationship(&mut self, relationship: Relationship) -> Result<Relationships> {
+        Ok(self
+            .store
+            .find(
+                relationship.object().r#type(),
+                relationship.object().id(),
+                relationship.relation(),
+            )
+            .await?
+            .into_iter()
+            .map(Tuple::into_relationship)
+            .collect())
+    }
+
+    async fn create_relationships(
+        &mut self,
+        relationships: Relationships,
+    ) -> Result<Option<ZedToken>> {
+        let tuples: Vec<_> = relationships.iter().map(Tuple::from_relationship).collect();
+        self.validate(&tuples).await?;
+        self.store.insert(tuples).await?;
+        Ok(Some(self.next_zed_token()))
+    }
+
+    async fn delete_relationships(
+        &mut self,
+        relationships: Relationships,
+    ) -> Result<Option<ZedToken>> {
+        let tuples = relationships.iter().map(Tuple::from_relationship).collect();
+        self.store.remove(tuples).await?;
+        Ok(Some(self.next_zed_token()))
+    }
+
+    async fn check_permissions(&mut self, permission: Permission) -> Result<bool> {
+        let schema = self.schema.read().await;
+        Ok(self
+            .check(
+                &schema,
+                permission.resource(),
+                permission.permission(),
+                permission.subject(),
+                0,
+            )
+            .await?)
+    }
+}
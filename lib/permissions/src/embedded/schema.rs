@@ -0,0 +1,233 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{EmbeddedError, EmbeddedResult};
+
+/// The subset of the SpiceDB schema language understood by the embedded backend: definitions
+/// with direct relations on object types and permissions that are unions of relations or other
+/// permissions.
+///
+/// ```text
+/// definition user {}
+///
+/// definition workspace {
+///     relation approver: user
+///     relation owner: user
+///     permission approve = approver + owner
+///     permission manage = owner
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Schema {
+    definitions: BTreeMap<String, Definition>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Definition {
+    relations: BTreeMap<String, BTreeSet<String>>,
+    permissions: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Member<'a> {
+    Permission(&'a [String]),
+    Relation(&'a BTreeSet<String>),
+}
+
+impl Schema {
+    pub(crate) fn parse(text: &str) -> EmbeddedResult<Self> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+
+        let mut definitions = BTreeMap::new();
+        while !parser.is_done() {
+            parser.expect("definition")?;
+            let name = parser.identifier()?;
+            parser.expect("{")?;
+            let definition = parser.definition_body(&name)?;
+            definitions.insert(name, definition);
+        }
+
+        let schema = Self { definitions };
+        schema.validate()?;
+        Ok(schema)
+    }
+
+    pub(crate) fn definition(&self, name: &str) -> EmbeddedResult<&Definition> {
+        self.definitions
+            .get(name)
+            .ok_or_else(|| EmbeddedError::UnknownDefinition(name.to_owned()))
+    }
+
+    fn validate(&self) -> EmbeddedResult<()> {
+        for (definition_name, definition) in &self.definitions {
+            for subject_types in definition.relations.values() {
+                for subject_type in subject_types {
+                    self.definition(subject_type)?;
+                }
+            }
+            for (permission, members) in &definition.permissions {
+                for member in members {
+                    if definition.member(member).is_none() {
+                        return Err(EmbeddedError::SchemaParse(format!(
+                            "{definition_name}#{permission} references unknown member {member}"
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Definition {
+    pub(crate) fn member(&self, name: &str) -> Option<Member<'_>> {
+        if let Some(subject_types) = self.relations.get(name) {
+            Some(Member::Relation(subject_types))
+        } else {
+            self.permissions
+                .get(name)
+                .map(|members| Member::Permission(members))
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn is_done(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> EmbeddedResult<&'a str> {
+        let token = self
+            .peek()
+            .ok_or_else(|| EmbeddedError::SchemaParse("unexpected end of schema".to_owned()))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> EmbeddedResult<()> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(EmbeddedError::SchemaParse(format!(
+                "expected '{expected}', found '{token}'"
+            ))),
+        }
+    }
+
+    fn identifier(&mut self) -> EmbeddedResult<String> {
+        let token = self.next()?;
+        if token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '/')
+        {
+            Ok(token.to_owned())
+        } else {
+            Err(EmbeddedError::SchemaParse(format!(
+                "expected an identifier, found '{token}'"
+            )))
+        }
+    }
+
+    fn definition_body(&mut self, definition_name: &str) -> EmbeddedResult<Definition> {
+        let mut definition = Definition::default();
+        loop {
+            match self.next()? {
+                "}" => return Ok(definition),
+                "relation" => {
+                    let name = self.identifier()?;
+                    self.expect(":")?;
+                    let mut subject_types = BTreeSet::new();
+                    subject_types.insert(self.identifier()?);
+                    while self.peek() == Some("|") {
+                        self.next()?;
+                        subject_types.insert(self.identifier()?);
+                    }
+                    self.reject_unsupported(definition_name, &name)?;
+                    definition.relations.insert(name, subject_types);
+                }
+                "permission" => {
+                    let name = self.identifier()?;
+                    self.expect("=")?;
+                    let mut members = vec![self.identifier()?];
+                    while self.peek() == Some("+") {
+                        self.next()?;
+                        members.push(self.identifier()?);
+                    }
+                    self.reject_unsupported(definition_name, &name)?;
+                    definition.permissions.insert(name, members);
+                }
+                token => {
+                    return Err(EmbeddedError::SchemaParse(format!(
+                        "unexpected '{token}' in definition {definition_name}"
+                    )))
+                }
+            }
+        }
+    }
+
+    fn reject_unsupported(&self, definition_name: &str, name: &str) -> EmbeddedResult<()> {
+        match self.peek() {
+            Some(token @ ("#" | "*" | "-" | "&" | "->")) => Err(EmbeddedError::UnsupportedSchema(
+                format!("'{token}' in {definition_name}#{name}"),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> EmbeddedResult<Vec<String>> {
+    let mut tokens = Vec::new();
+
+    for line in text.lines() {
+        let line = match line.find("//") {
+            Some(index) => &line[..index],
+            None => line,
+        };
+
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                c if c.is_whitespace() => {}
+                '{' | '}' | ':' | '|' | '+' | '=' | '#' | '*' | '&' => tokens.push(c.to_string()),
+                '-' => {
+                    if chars.peek() == Some(&'>') {
+                        chars.next();
+                        tokens.push("->".to_owned());
+                    } else {
+                        tokens.push("-".to_owned());
+                    }
+                }
+                c if c.is_ascii_alphanumeric() || c == '_' || c == '/' => {
+                    let mut identifier = c.to_string();
+                    while let Some(&next) = chars.peek() {
+                        if next.is_ascii_alphanumeric() || next == '_' || next == '/' {
+                            identifier.push(next);
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    tokens.push(identifier);
+                }
+                c => {
+                    return Err(EmbeddedError::SchemaParse(format!(
+                        "unexpected character '{c}'"
+                    )))
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
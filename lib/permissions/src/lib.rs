@@ -1,13 +1,24 @@
-use si_data_spicedb::{
-    PermissionsObject, Relationship, Relationships, SpiceDbClient, SpiceDbError, ZedToken,
-};
+use si_data_spicedb::{PermissionsObject, Relationship, Relationships, SpiceDbError, ZedToken};
 use si_events::{UserPk, WorkspacePk};
 use std::result;
 use thiserror::Error;
 
+mod backend;
+pub mod embedded;
+
+pub use backend::{
+    PermissionsBackend, PermissionsBackendKind, PermissionsClient, PermissionsConfig,
+};
+pub use embedded::{
+    EmbeddedError, EmbeddedPermissionsBackend, EmbeddedPermissionsConfig, EmbeddedStorage,
+    DEFAULT_SCHEMA,
+};
+
 #[remain::sorted]
 #[derive(Error, Debug)]
 pub enum Error {
+    #[error("embedded permissions error: {0}")]
+    Embedded(#[from] EmbeddedError),
     #[error("Builder must contain object, permission, and subject.")]
     PermissionBuilder,
     #[error(
@@ -32,6 +43,7 @@ pub enum ObjectType {
 #[strum(serialize_all = "snake_case")]
 pub enum Permission {
     Approve,
+    ApproveSecrets,
     Manage,
 }
 
@@ -40,6 +52,7 @@ pub enum Permission {
 pub enum Relation {
     Approver,
     Owner,
+    SecretManager,
 }
 
 /// RelationBuilder allows defining a relationship in SpiceDb.
@@ -101,40 +114,35 @@ impl RelationBuilder {
         self
     }
 
-    /// Creates a new relationship in SpiceDb
-    pub async fn create(&self, client: &mut SpiceDbClient) -> Result<Option<ZedToken>> {
+    /// Creates a new relationship in the permissions backend
+    pub async fn create(&self, client: &mut impl PermissionsBackend) -> Result<Option<ZedToken>> {
         match self.check() {
-            Ok(relationship) => client
-                .create_relationships(vec![relationship])
-                .await
-                .map_err(Error::SpiceDb),
+            Ok(relationship) => client.create_relationships(vec![relationship]).await,
             Err(err) => Err(err),
         }
     }
 
-    /// Deletes an existing relationship in SpiceDb
-    pub async fn delete(&self, client: &mut SpiceDbClient) -> Result<Option<ZedToken>> {
+    /// Deletes an existing relationship in the permissions backend
+    pub async fn delete(&self, client: &mut impl PermissionsBackend) -> Result<Option<ZedToken>> {
         match self.check() {
-            Ok(relationship) => client
-                .delete_relationships(vec![relationship])
-                .await
-                .map_err(Error::SpiceDb),
+            Ok(relationship) => client.delete_relationships(vec![relationship]).await,
             Err(err) => Err(err),
         }
     }
 
-    /// Reads existing relations in SpiceDb for a given object and relation
-    pub async fn read(&self, client: &mut SpiceDbClient) -> Result<Relationships> {
+    /// Reads existing relations in the permissions backend for a given object and relation
+    pub async fn read(&self, client: &mut impl PermissionsBackend) -> Result<Relationships> {
         match (self.object.clone(), self.relation) {
-            (Some(object), Some(relation)) => client
-                .read_relationship(Relationship::new(
-                    object,
-                    relation,
-                    PermissionsObject::empty(),
-                    self.zed_token.clone(),
-                ))
-                .await
-                .map_err(Error::SpiceDb),
+            (Some(object), Some(relation)) => {
+                client
+                    .read_relationship(Relationship::new(
+                        object,
+                        relation,
+                        PermissionsObject::empty(),
+                        self.zed_token.clone(),
+                    ))
+                    .await
+            }
             _ => Err(Error::RelationBuilder {
                 required_fields: vec!["object".to_string(), "relation".to_string()],
             }),
@@ -226,12 +234,9 @@ impl PermissionBuilder {
     }
 
     /// Checks if the given subject has the given permission in the given object
-    pub async fn has_permission(&self, client: &mut SpiceDbClient) -> Result<bool> {
+    pub async fn has_permission(&self, client: &mut impl PermissionsBackend) -> Result<bool> {
         match self.check() {
-            Ok(perms) => Ok(client
-                .check_permissions(perms)
-                .await
-                .map_err(Error::SpiceDb)?),
+            Ok(perms) => client.check_permissions(perms).await,
             Err(err) => Err(err),
         }
     }
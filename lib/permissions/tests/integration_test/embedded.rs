@@ -0,0 +1,189 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use buck2_resources::Buck2Resources;
+use indoc::indoc;
+use permissions::{
+    EmbeddedPermissionsBackend, ObjectType, Permission, PermissionBuilder, PermissionsBackend,
+    Relation, RelationBuilder, DEFAULT_SCHEMA,
+};
+
+#[tokio::test]
+async fn add_remove_approver_from_workspace() {
+    let mut backend = EmbeddedPermissionsBackend::in_memory().expect("failed to create backend");
+
+    let user_id = "scott".to_string();
+    let workspace_id = "123".to_string();
+
+    let relation = RelationBuilder::new()
+        .object(ObjectType::Workspace, workspace_id.clone())
+        .relation(Relation::Approver)
+        .subject(ObjectType::User, user_id.clone());
+
+    let zed_token = relation
+        .create(&mut backend)
+        .await
+        .expect("could not create relationship")
+        .expect("could not unwrap zed token");
+
+    let can_approve = PermissionBuilder::new()
+        .object(ObjectType::Workspace, workspace_id.clone())
+        .permission(Permission::Approve)
+        .subject(ObjectType::User, user_id.clone())
+        .zed_token(zed_token);
+
+    assert!(can_approve
+        .has_permission(&mut backend)
+        .await
+        .expect("could not check permission"));
+
+    let approvers = RelationBuilder::new()
+        .object(ObjectType::Workspace, workspace_id.clone())
+        .relation(Relation::Approver)
+        .read(&mut backend)
+        .await
+        .expect("could not read relationships");
+    assert_eq!(1, approvers.len());
+    assert_eq!(user_id, approvers[0].subject().id());
+
+    relation
+        .delete(&mut backend)
+        .await
+        .expect("could not delete permission");
+
+    assert!(!can_approve
+        .has_permission(&mut backend)
+        .await
+        .expect("could not check permission"));
+}
+
+#[tokio::test]
+async fn owner_can_approve_through_permission_union() {
+    let mut backend = EmbeddedPermissionsBackend::in_memory().expect("failed to create backend");
+
+    RelationBuilder::new()
+        .object(ObjectType::Workspace, "123")
+        .relation(Relation::Owner)
+        .subject(ObjectType::User, "owner")
+        .create(&mut backend)
+        .await
+        .expect("could not create relationship");
+
+    for permission in [Permission::Approve, Permission::Manage] {
+        assert!(PermissionBuilder::new()
+            .object(ObjectType::Workspace, "123")
+            .permission(permission)
+            .subject(ObjectType::User, "owner")
+            .has_permission(&mut backend)
+            .await
+            .expect("could not check permission"));
+    }
+
+    assert!(!PermissionBuilder::new()
+        .object(ObjectType::Workspace, "456")
+        .permission(Permission::Approve)
+        .subject(ObjectType::User, "owner")
+        .has_permission(&mut backend)
+        .await
+        .expect("could not check permission"));
+}
+
+#[tokio::test]
+async fn clones_share_relationships() {
+    let mut backend = EmbeddedPermissionsBackend::in_memory().expect("failed to create backend");
+    let mut clone = backend.clone();
+
+    RelationBuilder::new()
+        .object(ObjectType::Workspace, "123")
+        .relation(Relation::Approver)
+        .subject(ObjectType::User, "scott")
+        .create(&mut backend)
+        .await
+        .expect("could not create relationship");
+
+    assert!(PermissionBuilder::new()
+        .object(ObjectType::Workspace, "123")
+        .permission(Permission::Approve)
+        .subject(ObjectType::User, "scott")
+        .has_permission(&mut clone)
+        .await
+        .expect("could not check permission"));
+}
+
+#[tokio::test]
+async fn rejects_relationships_not_in_schema() {
+    let mut backend = EmbeddedPermissionsBackend::in_memory().expect("failed to create backend");
+
+    let result = RelationBuilder::new()
+        .object(ObjectType::User, "scott")
+        .relation(Relation::Approver)
+        .subject(ObjectType::Workspace, "123")
+        .create(&mut backend)
+        .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn write_schema() {
+    let mut backend = EmbeddedPermissionsBackend::in_memory().expect("failed to create backend");
+
+    backend
+        .write_schema(indoc! {"
+            // Plan comment
+            definition plan {}
+
+            definition user {}
+
+            definition workspace {
+                relation approver: user
+                permission approve = approver
+            }
+        "})
+        .await
+        .expect("failed to write schema");
+
+    let result = RelationBuilder::new()
+        .object(ObjectType::Workspace, "123")
+        .relation(Relation::Owner)
+        .subject(ObjectType::User, "scott")
+        .create(&mut backend)
+        .await;
+    assert!(result.is_err());
+
+    let result = backend
+        .write_schema(indoc! {"
+            definition user {}
+
+            definition workspace {
+                relation viewer: user
+                permission view = viewer - banned
+            }
+        "})
+        .await;
+    assert!(result.is_err());
+}
+
+#[allow(clippy::disallowed_methods)] // Used to determine if running in testing
+fn spicedb_schema_path() -> PathBuf {
+    if env::var("BUCK_RUN_BUILD_ID").is_ok() || env::var("BUCK_BUILD_ID").is_ok() {
+        Buck2Resources::read()
+            .expect("should be able to read buck2 resources")
+            .get_ends_with("schema.zed")
+            .expect("should be able to get the spicedb schema")
+    } else {
+        Path::new(&env::var("CARGO_MANIFEST_DIR").expect("should be run with cargo or buck2"))
+            .join("../../component/spicedb/schema.zed")
+    }
+}
+
+#[test]
+fn default_schema_matches_the_spicedb_schema() {
+    let spicedb_schema =
+        std::fs::read_to_string(spicedb_schema_path()).expect("failed to read spicedb schema");
+
+    assert_eq!(
+        spicedb_schema, DEFAULT_SCHEMA,
+        "lib/permissions/src/embedded/schema.zed must be a copy of component/spicedb/schema.zed"
+    );
+}
@@ -6,6 +6,8 @@ use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
 use si_data_spicedb::{Client, SpiceDbClient, SpiceDbConfig};
 
+mod embedded;
+
 const ENV_VAR_SPICEDB_URL: &str = "SI_TEST_SPICEDB_URL";
 
 fn spicedb_config() -> SpiceDbConfig {
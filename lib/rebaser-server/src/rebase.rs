@@ -1,7 +1,8 @@
 use audit_logs::AuditLogsError;
 use dal::{
+    attribute::value::{DependentValueIndex, DependentValueIndexScope},
     change_set::{ChangeSet, ChangeSetError, ChangeSetId},
-    workspace_snapshot::WorkspaceSnapshotError,
+    workspace_snapshot::{chunk::WorkspaceSnapshotManifest, WorkspaceSnapshotError},
     DalContext, TransactionsError, Workspace, WorkspaceError, WorkspacePk, WorkspaceSnapshot,
     WsEvent, WsEventError,
 };
@@ -106,12 +107,22 @@ pub async fn perform_rebase(
         .await?;
     debug!("corrected transforms: {:?}", start.elapsed());
 
+    // What the updates can affect has to be worked out both before and after they are
+    // performed, since they are performed in place.
+    let mut dependent_value_index_scope = DependentValueIndexScope::nothing();
+    dependent_value_index_scope
+        .add_updates(&to_rebase_workspace_snapshot, &corrected_updates)
+        .await;
     to_rebase_workspace_snapshot
         .perform_updates(&corrected_updates)
         .await?;
+    dependent_value_index_scope
+        .add_updates(&to_rebase_workspace_snapshot, &corrected_updates)
+        .await;
 
     debug!("updates complete: {:?}", start.elapsed());
 
+    let mut rebased_workspace_snapshot_address = None;
     if !corrected_updates.is_empty() {
         // Once all updates have been performed, we can write out, mark everything as recently seen
         // and update the pointer.
@@ -123,6 +134,7 @@ pub async fn perform_rebase(
 
         debug!("pointer updated: {:?}", start.elapsed());
 
+        rebased_workspace_snapshot_address = Some(to_rebase_workspace_snapshot.id().await);
         ctx.set_workspace_snapshot(to_rebase_workspace_snapshot);
     }
     let updates_count = rebase_batch.updates().len();
@@ -133,6 +145,22 @@ pub async fn perform_rebase(
     // Before replying to the requester, we must commit.
     ctx.commit_no_rebase().await?;
 
+    if let Some(rebased_workspace_snapshot_address) = rebased_workspace_snapshot_address {
+        // Move the dependent value index along, so that the next dependent values update does
+        // not have to diff the snapshots to bring it up to date
+        if let Err(err) = DependentValueIndex::advance(
+            ctx,
+            to_rebase_change_set.id,
+            to_rebase_workspace_snapshot_address,
+            rebased_workspace_snapshot_address,
+            &dependent_value_index_scope,
+        )
+        .await
+        {
+            error!(?err, "dependent value index advance error");
+        }
+    }
+
     {
         let ctx_clone = ctx.clone();
         server_tracker.spawn(async move {
@@ -231,6 +259,17 @@ pub(crate) async fn evict_unused_snapshots(
             ctx.events_tenancy(),
             ctx.events_actor(),
         )?;
+        // Snapshots written in chunks are stored as a manifest in the chunk store. Its chunks go
+        // with it unless the snapshot of another change set is made of them too.
+        let in_use = ChangeSet::workspace_snapshot_addresses_in_use(ctx).await?;
+        WorkspaceSnapshotManifest::evict(
+            ctx.layer_db(),
+            *workspace_snapshot_address,
+            &in_use,
+            ctx.events_tenancy(),
+            ctx.events_actor(),
+        )
+        .await?;
     }
     Ok(())
 }
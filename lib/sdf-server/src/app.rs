@@ -4,7 +4,7 @@ use asset_sprayer::AssetSprayer;
 use axum::Router;
 use dal::{JwtPublicSigningKey, ServicesContext};
 use nats_multiplexer_client::MultiplexerClient;
-use si_data_spicedb::SpiceDbClient;
+use permissions::PermissionsClient;
 use si_posthog::PosthogClient;
 use telemetry::prelude::*;
 use tokio::sync::RwLock;
@@ -34,7 +34,7 @@ impl AxumApp {
         create_workspace_allowlist: Vec<WorkspacePermissions>,
         application_runtime_mode: Arc<RwLock<ApplicationRuntimeMode>>,
         shutdown_token: CancellationToken,
-        spicedb_client: Option<SpiceDbClient>,
+        permissions_client: Option<PermissionsClient>,
     ) -> Self {
         Self::inner_from_services(
             services_context,
@@ -49,7 +49,7 @@ impl AxumApp {
             create_workspace_allowlist,
             application_runtime_mode,
             shutdown_token,
-            spicedb_client,
+            permissions_client,
         )
     }
 
@@ -72,7 +72,7 @@ impl AxumApp {
         create_workspace_allowlist: Vec<WorkspacePermissions>,
         application_runtime_mode: Arc<RwLock<ApplicationRuntimeMode>>,
         shutdown_token: CancellationToken,
-        spicedb_client: SpiceDbClient,
+        permissions_client: PermissionsClient,
     ) -> Self {
         Self::inner_from_services(
             services_context,
@@ -87,7 +87,7 @@ impl AxumApp {
             create_workspace_allowlist,
             application_runtime_mode,
             shutdown_token,
-            Some(spicedb_client),
+            Some(permissions_client),
         )
     }
 
@@ -109,7 +109,7 @@ impl AxumApp {
         create_workspace_allowlist: Vec<WorkspacePermissions>,
         application_runtime_mode: Arc<RwLock<ApplicationRuntimeMode>>,
         shutdown_token: CancellationToken,
-        spicedb_client: Option<SpiceDbClient>,
+        permissions_client: Option<PermissionsClient>,
     ) -> Self {
         let state = AppState::new(
             services_context,
@@ -124,7 +124,7 @@ impl AxumApp {
             create_workspace_allowlist,
             application_runtime_mode,
             shutdown_token,
-            spicedb_client,
+            permissions_client,
         );
 
         let path_filter = Box::new(|path: &str| match path {
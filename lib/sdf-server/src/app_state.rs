@@ -4,7 +4,7 @@ use asset_sprayer::AssetSprayer;
 use axum::extract::FromRef;
 use dal::JwtPublicSigningKey;
 use nats_multiplexer_client::MultiplexerClient;
-use si_data_spicedb::SpiceDbClient;
+use permissions::PermissionsClient;
 use std::fmt;
 use tokio::sync::{Mutex, RwLock};
 use tokio_util::sync::CancellationToken;
@@ -35,7 +35,7 @@ pub struct AppState {
     create_workspace_allowlist: Vec<WorkspacePermissions>,
     pub application_runtime_mode: Arc<RwLock<ApplicationRuntimeMode>>,
     shutdown_token: CancellationToken,
-    spicedb_client: Option<SpiceDbClient>,
+    permissions_client: Option<PermissionsClient>,
 }
 
 impl AppState {
@@ -53,7 +53,7 @@ impl AppState {
         create_workspace_allowlist: Vec<WorkspacePermissions>,
         application_runtime_mode: Arc<RwLock<ApplicationRuntimeMode>>,
         shutdown_token: CancellationToken,
-        spicedb_client: Option<SpiceDbClient>,
+        permissions_client: Option<PermissionsClient>,
     ) -> Self {
         let nats_multiplexer_clients = NatsMultiplexerClients {
             ws: Arc::new(Mutex::new(ws_multiplexer_client)),
@@ -73,7 +73,7 @@ impl AppState {
             create_workspace_allowlist,
             application_runtime_mode,
             shutdown_token,
-            spicedb_client,
+            permissions_client,
         }
     }
 
@@ -113,12 +113,12 @@ impl AppState {
         &self.shutdown_token
     }
 
-    pub fn spicedb_client(&mut self) -> Option<&mut SpiceDbClient> {
-        self.spicedb_client.as_mut()
+    pub fn permissions_client(&mut self) -> Option<&mut PermissionsClient> {
+        self.permissions_client.as_mut()
     }
 
-    pub fn spicedb_client_clone(&self) -> Option<SpiceDbClient> {
-        self.spicedb_client.clone()
+    pub fn permissions_client_clone(&self) -> Option<PermissionsClient> {
+        self.permissions_client.clone()
     }
 }
 
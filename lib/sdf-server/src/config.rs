@@ -1,5 +1,7 @@
 use asset_sprayer::config::{AssetSprayerConfig, SIOpenAIConfig};
 use dal::jwt_key::JwtConfig;
+use dal::pkg::PkgSignatureConfig;
+use permissions::PermissionsConfig;
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 use si_crypto::VeritechCryptoConfig;
 use si_data_spicedb::SpiceDbConfig;
@@ -135,6 +137,12 @@ pub struct Config {
     #[builder(default)]
     spicedb: SpiceDbConfig,
 
+    #[builder(default)]
+    permissions: PermissionsConfig,
+
+    #[builder(default)]
+    pkg_signatures: PkgSignatureConfig,
+
     pkgs_path: CanonicalFile,
 
     boot_feature_flags: HashSet<FeatureFlag>,
@@ -254,6 +262,18 @@ impl Config {
     pub fn spicedb(&self) -> &SpiceDbConfig {
         &self.spicedb
     }
+
+    /// Gets a reference to the config's permissions backend config
+    #[must_use]
+    pub fn permissions(&self) -> &PermissionsConfig {
+        &self.permissions
+    }
+
+    /// Gets a reference to the config's package signature verification config
+    #[must_use]
+    pub fn pkg_signatures(&self) -> &PkgSignatureConfig {
+        &self.pkg_signatures
+    }
 }
 
 impl ConfigBuilder {
@@ -304,6 +324,10 @@ pub struct ConfigFile {
     create_workspace_allowlist: Vec<WorkspacePermissions>,
     #[serde(default)]
     spicedb: SpiceDbConfig,
+    #[serde(default)]
+    permissions: PermissionsConfig,
+    #[serde(default)]
+    pkg_signatures: PkgSignatureConfig,
 }
 
 impl Default for ConfigFile {
@@ -327,6 +351,8 @@ impl Default for ConfigFile {
             create_workspace_permissions: Default::default(),
             create_workspace_allowlist: Default::default(),
             spicedb: Default::default(),
+            permissions: Default::default(),
+            pkg_signatures: Default::default(),
         }
     }
 }
@@ -361,6 +387,8 @@ impl TryFrom<ConfigFile> for Config {
             create_workspace_permissions: value.create_workspace_permissions,
             create_workspace_allowlist: value.create_workspace_allowlist,
             spicedb: value.spicedb,
+            permissions: value.permissions,
+            pkg_signatures: value.pkg_signatures,
         })
     }
 }
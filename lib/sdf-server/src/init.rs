@@ -1,8 +1,9 @@
 use std::{path::PathBuf, sync::Arc};
 
 use dal::{
-    feature_flags::FeatureFlagService, jwt_key::JwtConfig, DalLayerDb, DedicatedExecutor,
-    JetstreamStreams, JobQueueProcessor, JwtPublicSigningKey, NatsProcessor, ServicesContext,
+    feature_flags::FeatureFlagService, jwt_key::JwtConfig, pkg::PkgSignatureVerifier, DalLayerDb,
+    DedicatedExecutor, JetstreamStreams, JobQueueProcessor, JwtPublicSigningKey, NatsProcessor,
+    ServicesContext,
 };
 use rebaser_client::RebaserClient;
 use si_crypto::{
@@ -39,6 +40,8 @@ pub enum InitError {
     NatsClient(#[source] si_data_nats::NatsError),
     #[error("pg pool error: {0}")]
     PgPool(#[from] Box<si_data_pg::PgPoolError>),
+    #[error("package signature config error: {0}")]
+    PkgSignature(#[source] Box<dal::pkg::PkgError>),
     #[error("posthog client error: {0}")]
     Posthog(#[from] si_posthog::PosthogError),
     #[error("rebaser client error: {0}")]
@@ -85,7 +88,7 @@ pub(crate) async fn services_context_from_config(
     )
     .await?;
 
-    let services_context = ServicesContext::new(
+    let mut services_context = ServicesContext::new(
         pg_pool,
         nats.clone(),
         jetstream_streams,
@@ -100,6 +103,10 @@ pub(crate) async fn services_context_from_config(
         feature_flags_service,
         compute_executor,
     );
+    services_context.set_pkg_signature_verifier(
+        PkgSignatureVerifier::from_config(config.pkg_signatures().clone())
+            .map_err(|err| InitError::PkgSignature(Box::new(err)))?,
+    );
 
     Ok((services_context, layer_db_graceful_shutdown))
 }
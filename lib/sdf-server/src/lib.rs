@@ -63,9 +63,11 @@ pub enum ServerError {
     Init(#[from] init::InitError),
     #[error("nats multipler error: {0}")]
     NatsMultiplexer(#[from] ::nats_multiplexer::MultiplexerError),
+    #[error("permissions backend error: {0}")]
+    Permissions(#[from] permissions::Error),
     #[error("Failed to set up signal handler")]
     Signal(#[source] io::Error),
-    #[error("Permissions error: {0}")]
+    #[error("spicedb error: {0}")]
     SpiceDb(#[from] SpiceDbError),
     #[error("unix domain socket incoming stream error: {0}")]
     Uds(#[from] uds::UdsIncomingStreamError),
@@ -71,7 +71,7 @@ where
                     Err(err) => return Ok(err.into_response()),
                 };
 
-            if let Some(client) = me.state.spicedb_client() {
+            if let Some(client) = me.state.permissions_client() {
                 let is_allowed = match PermissionBuilder::new()
                     .workspace_object(claim.workspace_pk.into())
                     .permission(me.permission)
@@ -253,6 +253,7 @@ async fn install_builtins(
                         is_builtin: true,
                         schema_id: module.schema_id().map(Into::into),
                         past_module_hashes: module.past_hashes,
+                        verify_signature: true,
                         ..Default::default()
                     }),
                 )
@@ -293,10 +294,12 @@ async fn fetch_builtin(
     module: &ModuleDetailsResponse,
     module_index_client: &ModuleIndexClient,
 ) -> MigratorResult<SiPkg> {
-    let module = module_index_client
+    let module_bytes = module_index_client
         .get_builtin(Ulid::from_string(&module.id).unwrap_or_default())
         .await
         .map_err(MigratorError::migrate_builtins)?;
 
-    SiPkg::load_from_bytes(&module).map_err(MigratorError::migrate_builtins)
+    Ok(SiPkg::load_from_bytes(&module_bytes)
+        .map_err(MigratorError::migrate_builtins)?
+        .with_signature(dal::pkg::module_signature(module)))
 }
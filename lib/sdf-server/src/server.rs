@@ -1,11 +1,15 @@
 use std::{fmt, future::IntoFuture as _, net::SocketAddr, path::PathBuf, sync::Arc};
 
-use asset_sprayer::AssetSprayer;
+use asset_sprayer::{
+    provider::{LocalModelProvider, OpenAIProvider},
+    AssetSprayer,
+};
 use axum::{async_trait, routing::IntoMakeService, Router};
 use dal::{JwtPublicSigningKey, ServicesContext};
 use hyper::server::accept::Accept;
 use nats_multiplexer::Multiplexer;
 use nats_multiplexer_client::MultiplexerClient;
+use permissions::{EmbeddedPermissionsBackend, PermissionsBackendKind, PermissionsClient};
 use si_data_nats::NatsClient;
 use si_data_spicedb::SpiceDbClient;
 use si_posthog::PosthogClient;
@@ -96,23 +100,42 @@ impl Server {
         )
         .await?;
 
-        let asset_sprayer = config
-            .openai()
-            .clone()
-            .into_openai_config_opt()
-            .map(|openai_config| {
-                AssetSprayer::new(
-                    async_openai::Client::with_config(openai_config),
-                    config.asset_sprayer().clone(),
-                )
-            });
+        // A local model takes precedence, so that prompts don't leave our infrastructure
+        let asset_sprayer = match config.asset_sprayer().local_model.clone() {
+            Some(local_model) => Some(AssetSprayer::new(
+                Arc::new(LocalModelProvider::from(local_model)),
+                config.asset_sprayer().clone(),
+            )),
+            None => config
+                .openai()
+                .clone()
+                .into_openai_config_opt()
+                .map(|openai_config| {
+                    AssetSprayer::new(
+                        Arc::new(OpenAIProvider::new(async_openai::Client::with_config(
+                            openai_config,
+                        ))),
+                        config.asset_sprayer().clone(),
+                    )
+                }),
+        };
 
         let application_runtime_mode = Arc::new(RwLock::new(ApplicationRuntimeMode::Running));
 
-        let mut spicedb_client = None;
-        if config.spicedb().enabled {
-            spicedb_client = Some(SpiceDbClient::new(config.spicedb()).await?);
-        }
+        let permissions_client: Option<PermissionsClient> = match config.permissions().backend {
+            PermissionsBackendKind::Embedded => Some(
+                EmbeddedPermissionsBackend::from_config(
+                    &config.permissions().embedded,
+                    services_context.pg_pool(),
+                )
+                .map_err(permissions::Error::from)?
+                .into(),
+            ),
+            PermissionsBackendKind::SpiceDb if config.spicedb().enabled => {
+                Some(SpiceDbClient::new(config.spicedb()).await?.into())
+            }
+            PermissionsBackendKind::SpiceDb => None,
+        };
 
         prepare_maintenance_mode_watcher(application_runtime_mode.clone(), token.clone())?;
 
@@ -136,7 +159,7 @@ impl Server {
             config.create_workspace_allowlist().clone(),
             application_runtime_mode,
             token,
-            spicedb_client,
+            permissions_client,
         )
         .await
     }
@@ -157,7 +180,7 @@ impl Server {
         create_workspace_allowlist: Vec<WorkspacePermissions>,
         application_runtime_mode: Arc<RwLock<ApplicationRuntimeMode>>,
         token: CancellationToken,
-        spicedb_client: Option<SpiceDbClient>,
+        permissions_client: Option<PermissionsClient>,
     ) -> ServerResult<Self> {
         let app = AxumApp::from_services(
             services_context.clone(),
@@ -171,7 +194,7 @@ impl Server {
             create_workspace_allowlist,
             application_runtime_mode,
             token.clone(),
-            spicedb_client,
+            permissions_client,
         )
         .into_inner();
 
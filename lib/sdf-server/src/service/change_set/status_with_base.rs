@@ -1,8 +1,8 @@
 use axum::Json;
-use dal::Visibility;
+use dal::{change_set::approval::RequiredApproval, ChangeSet, Visibility};
 use serde::{Deserialize, Serialize};
 
-use super::ChangeSetResult;
+use super::{ChangeSetError, ChangeSetResult};
 use crate::extract::{AccessBuilder, HandlerContext};
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -18,6 +18,7 @@ pub struct StatusWithBaseResponse {
     pub base_has_updates: bool,
     pub change_set_has_updates: bool,
     pub conflicts_with_base: bool,
+    pub required_approvals: Vec<RequiredApproval>,
 }
 
 pub async fn status_with_base(
@@ -25,7 +26,12 @@ pub async fn status_with_base(
     AccessBuilder(request_ctx): AccessBuilder,
     Json(request): Json<StatusWithBaseRequest>,
 ) -> ChangeSetResult<Json<StatusWithBaseResponse>> {
-    let _ctx = builder.build(request_ctx.build(request.visibility)).await?;
+    let ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    let change_set = ChangeSet::find(&ctx, request.visibility.change_set_id)
+        .await?
+        .ok_or(ChangeSetError::ChangeSetNotFound)?;
+    let required_approvals = change_set.required_approvals(&ctx).await?;
 
     // let change_set = ChangeSet::find(&ctx, request.visibility.change_set_id)
     //     .await?
@@ -68,5 +74,6 @@ pub async fn status_with_base(
         base_has_updates: false,
         change_set_has_updates: false,
         conflicts_with_base: false,
+        required_approvals,
     }))
 }
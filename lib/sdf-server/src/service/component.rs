@@ -25,6 +25,7 @@ use crate::{service::component::conflicts_for_component::conflicts_for_component
 
 use super::ApiError;
 
+pub mod bulk_upgrade;
 pub mod conflicts_for_component;
 pub mod debug;
 pub mod delete_property_editor_value;
@@ -34,11 +35,13 @@ pub mod get_diff;
 pub mod get_property_editor_schema;
 pub mod get_property_editor_values;
 pub mod get_resource;
+pub mod get_upgrade_diff;
 pub mod insert_property_editor_value;
 pub mod json;
 pub mod list_qualifications;
 pub mod refresh;
 pub mod restore_default_function;
+pub mod revert_upgrade;
 pub mod set_name;
 pub mod set_resource_id;
 pub mod set_type;
@@ -74,6 +77,8 @@ pub enum ComponentError {
     KeyAlreadyExists(String),
     #[error("component not found for id: {0}")]
     NotFound(ComponentId),
+    #[error("component {0} does not exist on the base change set")]
+    NotOnHead(ComponentId),
     #[error(transparent)]
     Prop(#[from] PropError),
     #[error("property editor error: {0}")]
@@ -131,7 +136,7 @@ impl IntoResponse for ComponentError {
             ComponentError::SchemaVariantUpgradeSkipped => {
                 (StatusCode::NOT_MODIFIED, self.to_string())
             }
-            ComponentError::KeyAlreadyExists(_) => {
+            ComponentError::KeyAlreadyExists(_) | ComponentError::NotOnHead(_) => {
                 (StatusCode::UNPROCESSABLE_ENTITY, self.to_string())
             }
             ComponentError::DalComponent(err) => match err {
@@ -186,5 +191,8 @@ pub fn routes() -> Router<AppState> {
         .route("/debug", get(debug::debug_component))
         .route("/json", get(json::json))
         .route("/upgrade_component", post(upgrade::upgrade))
+        .route("/get_upgrade_diff", get(get_upgrade_diff::get_upgrade_diff))
+        .route("/bulk_upgrade", post(bulk_upgrade::bulk_upgrade))
+        .route("/revert_upgrade", post(revert_upgrade::revert_upgrade))
         .route("/conflicts", get(conflicts_for_component))
 }
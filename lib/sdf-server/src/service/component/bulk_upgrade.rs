@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Host, OriginalUri},
+    Json,
+};
+use dal::{
+    action::{Action, ActionState},
+    component::upgrade::ComponentUpgradeReport,
+    ChangeSet, Component, ComponentId, SchemaVariantId, Visibility,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    extract::{AccessBuilder, HandlerContext, PosthogClient},
+    service::{
+        component::{ComponentError, ComponentResult},
+        force_change_set_response::ForceChangeSetResponse,
+    },
+    track,
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkUpgradeRequest {
+    pub schema_variant_id: SchemaVariantId,
+    /// Upgrade only these components. Every component of the schema is upgraded when unset.
+    pub component_ids: Option<Vec<ComponentId>>,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+pub type BulkUpgradeResponse = Vec<ComponentUpgradeReport>;
+
+pub async fn bulk_upgrade(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Host(host_name): Host,
+    Json(request): Json<BulkUpgradeRequest>,
+) -> ComponentResult<ForceChangeSetResponse<BulkUpgradeResponse>> {
+    let mut ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    if request.dry_run {
+        let reports =
+            Component::bulk_upgrade(&ctx, request.schema_variant_id, request.component_ids, true)
+                .await?;
+        return Ok(ForceChangeSetResponse::new(None, reports));
+    }
+
+    let force_change_set_id = ChangeSet::force_new(&mut ctx).await?;
+
+    let component_ids =
+        Component::list_ids_to_upgrade(&ctx, request.schema_variant_id, request.component_ids)
+            .await?;
+    if component_ids.is_empty() {
+        return Err(ComponentError::SchemaVariantUpgradeSkipped);
+    }
+
+    // block the whole upgrade if any of the components has running or dispatched actions!
+    for &component_id in &component_ids {
+        let current_blocking_actions = Action::find_for_states_and_component_id(
+            &ctx,
+            component_id,
+            [ActionState::Dispatched, ActionState::Running].to_vec(),
+        )
+        .await?;
+        if !current_blocking_actions.is_empty() {
+            return Err(ComponentError::UpgradeSkippedDueToActions);
+        }
+    }
+
+    let reports = Component::bulk_upgrade(
+        &ctx,
+        request.schema_variant_id,
+        Some(component_ids.clone()),
+        false,
+    )
+    .await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        &host_name,
+        "bulk_upgrade_components",
+        serde_json::json!({
+            "how": "/component/bulk_upgrade",
+            "component_ids": component_ids,
+            "new_schema_variant_id": request.schema_variant_id,
+            "change_set_id": ctx.change_set_id(),
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(ForceChangeSetResponse::new(force_change_set_id, reports))
+}
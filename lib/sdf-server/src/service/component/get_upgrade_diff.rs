@@ -0,0 +1,57 @@
+use axum::{
+    extract::{Host, OriginalUri, Query},
+    Json,
+};
+use dal::{Component, ComponentId, Visibility};
+use serde::{Deserialize, Serialize};
+use si_pkg::PkgSpecDiff;
+
+use super::ComponentResult;
+use crate::{
+    extract::{AccessBuilder, HandlerContext, PosthogClient},
+    track,
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetUpgradeDiffRequest {
+    pub component_id: ComponentId,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetUpgradeDiffResponse {
+    /// `None` if the component is already on the newest version of its schema variant
+    pub upgrade_diff: Option<PkgSpecDiff>,
+}
+
+pub async fn get_upgrade_diff(
+    OriginalUri(original_uri): OriginalUri,
+    Host(host_name): Host,
+    PosthogClient(posthog_client): PosthogClient,
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Query(request): Query<GetUpgradeDiffRequest>,
+) -> ComponentResult<Json<GetUpgradeDiffResponse>> {
+    let ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    let component = Component::get_by_id(&ctx, request.component_id).await?;
+    let upgrade_diff = component.upgrade_diff(&ctx).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        &host_name,
+        "get_upgrade_diff",
+        serde_json::json!({
+            "how": "/component/get_upgrade_diff",
+            "component_id": request.component_id.clone(),
+            "change_set_id": ctx.change_set_id(),
+        }),
+    );
+
+    Ok(Json(GetUpgradeDiffResponse { upgrade_diff }))
+}
@@ -0,0 +1,66 @@
+use axum::{
+    extract::{Host, OriginalUri},
+    Json,
+};
+use dal::{ChangeSet, Component, ComponentId, Visibility};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    extract::{AccessBuilder, HandlerContext, PosthogClient},
+    service::{
+        component::{ComponentError, ComponentResult},
+        force_change_set_response::ForceChangeSetResponse,
+    },
+    track,
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertUpgradeRequest {
+    pub component_id: ComponentId,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+/// Puts a single upgraded [`Component`](dal::Component) back the way it is on the base change
+/// set, leaving any other upgrades in the change set alone.
+pub async fn revert_upgrade(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Host(host_name): Host,
+    Json(request): Json<RevertUpgradeRequest>,
+) -> ComponentResult<ForceChangeSetResponse<()>> {
+    let mut ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    let force_change_set_id = ChangeSet::force_new(&mut ctx).await?;
+
+    if !Component::exists_on_head(&ctx, vec![request.component_id])
+        .await?
+        .contains(&request.component_id)
+    {
+        return Err(ComponentError::NotOnHead(request.component_id));
+    }
+
+    Component::restore_from_base_change_set(&ctx, request.component_id).await?;
+    let schema_variant_id = Component::schema_variant_id(&ctx, request.component_id).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        &host_name,
+        "revert_component_upgrade",
+        serde_json::json!({
+            "how": "/component/revert_upgrade",
+            "component_id": request.component_id,
+            "component_schema_variant_id": schema_variant_id,
+            "change_set_id": ctx.change_set_id(),
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(ForceChangeSetResponse::empty(force_change_set_id))
+}
@@ -7,6 +7,7 @@ use axum::{
     Router,
 };
 use convert_case::{Case, Casing};
+use dal::workspace::template::WorkspaceTemplateError;
 use dal::{
     pkg::PkgError as DalPkgError, ChangeSetError, ChangeSetId, DalContextBuilder, FuncError,
     SchemaError, SchemaId, SchemaVariantError, SchemaVariantId, StandardModelError, TenancyError,
@@ -33,10 +34,12 @@ const MAX_NAME_SEARCH_ATTEMPTS: usize = 100;
 pub mod approval_process;
 pub mod builtin_module_spec;
 mod export_workspace;
+mod export_workspace_template;
 pub mod get_module;
 pub mod import_workspace_vote;
 pub mod install_module;
 mod install_workspace;
+mod install_workspace_template;
 pub mod list_modules;
 pub mod reject_module;
 pub mod remote_module_spec;
@@ -132,6 +135,8 @@ pub enum ModuleError {
     WorkspaceNotFound(WorkspacePk),
     #[error("workspace snapshot error: {0}")]
     WorkspaceSnapshot(#[from] WorkspaceSnapshotError),
+    #[error("workspace template error: {0}")]
+    WorkspaceTemplate(#[from] WorkspaceTemplateError),
     #[error("could not publish websocket event: {0}")]
     WsEvent(#[from] WsEventError),
 }
@@ -147,6 +152,10 @@ impl IntoResponse for ModuleError {
             | ModuleError::SchemaNotFoundForVariant(_)
             | ModuleError::SchemaVariantNotFound(_)
             | ModuleError::WorkspaceNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            ModuleError::WorkspaceTemplate(
+                WorkspaceTemplateError::MissingParameterValue(_)
+                | WorkspaceTemplateError::UnknownParameter(_),
+            ) => (StatusCode::BAD_REQUEST, self.to_string()),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
@@ -248,12 +257,20 @@ pub fn routes() -> Router<AppState> {
             "/export_workspace",
             post(export_workspace::export_workspace),
         )
+        .route(
+            "/export_workspace_template",
+            post(export_workspace_template::export_workspace_template),
+        )
         .route("/get_module_by_hash", get(get_module::get_module_by_hash))
         .route("/install_module", post(install_module::install_module))
         .route(
             "/install_workspace",
             post(install_workspace::install_workspace),
         )
+        .route(
+            "/install_workspace_template",
+            post(install_workspace_template::install_workspace_template),
+        )
         .route("/list_modules", get(list_modules::list_modules))
         .route(
             "/remote_module_spec",
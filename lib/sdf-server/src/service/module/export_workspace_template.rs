@@ -0,0 +1,89 @@
+use axum::{
+    extract::{Host, OriginalUri},
+    Json,
+};
+use dal::{workspace::template::generate_template, Visibility};
+use serde::{Deserialize, Serialize};
+use si_pkg::WorkspaceTemplateParameterV0;
+use telemetry::prelude::*;
+
+use crate::{
+    extract::{AccessBuilder, HandlerContext, PosthogClient, RawAccessToken},
+    service::module::{ModuleError, ModuleResult},
+    track,
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportWorkspaceTemplateRequest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub parameters: Vec<WorkspaceTemplateParameterV0>,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+pub async fn export_workspace_template(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    RawAccessToken(raw_access_token): RawAccessToken,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Host(host_name): Host,
+    Json(request): Json<ExportWorkspaceTemplateRequest>,
+) -> ModuleResult<()> {
+    let ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    if request.name.trim().is_empty() {
+        return Err(ModuleError::PackageNameEmpty);
+    }
+    if request.version.trim().is_empty() {
+        return Err(ModuleError::PackageVersionEmpty);
+    }
+
+    let index_client = {
+        let module_index_url = match ctx.module_index_url() {
+            Some(url) => url,
+            None => return Err(ModuleError::ModuleIndexNotConfigured),
+        };
+
+        module_index_client::ModuleIndexClient::new(module_index_url.try_into()?, &raw_access_token)
+    };
+
+    info!("Exporting workspace template {}", &request.name);
+    let parameter_names: Vec<String> = request
+        .parameters
+        .iter()
+        .map(|parameter| parameter.name.to_owned())
+        .collect();
+    let template = generate_template(
+        &ctx,
+        &request.name,
+        &request.version,
+        &request.description,
+        request.parameters,
+    )
+    .await?;
+
+    index_client
+        .upload_workspace_template(&request.name, &request.version, template)
+        .await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        &host_name,
+        "export_workspace_template",
+        serde_json::json!({
+            "template_name": request.name,
+            "template_version": request.version,
+            "template_parameters": parameter_names,
+        }),
+    );
+
+    Ok(())
+}
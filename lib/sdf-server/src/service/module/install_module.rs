@@ -1,15 +1,21 @@
+use std::collections::{HashMap, HashSet};
+
 use axum::{
     extract::{Host, OriginalUri},
     Json,
 };
 use dal::{
-    pkg::{import_pkg_from_pkg, ImportOptions},
+    module::Module,
+    pkg::{
+        import_pkg_from_pkg, import_pkgs_with_dependencies, installed_module_satisfies,
+        module_signature, pkgs_in_dependency_order, ImportOptions,
+    },
     ChangeSet, Func, Schema, SchemaVariant, Visibility, WsEvent,
 };
-use module_index_client::ModuleIndexClient;
+use module_index_client::{ExtraMetadata, ModuleDetailsResponse, ModuleIndexClient};
 use serde::{Deserialize, Serialize};
 use si_frontend_types::SchemaVariant as FrontendVariant;
-use si_pkg::SiPkg;
+use si_pkg::{DependencySpec, SiPkg};
 use ulid::Ulid;
 
 use crate::{
@@ -85,12 +91,72 @@ pub async fn install_module(
         ids_with_details.push((id, module_details));
     }
 
-    // After validating that we can install the modules, get on with it.
+    // Everything the modules depend on that is not installed yet goes in first. A dependency
+    // that was also asked for directly is installed along with the other requested modules, and
+    // one that an installed module already satisfies is left alone rather than being replaced by
+    // the newest version.
+    let requested_ids: HashSet<String> = ids_with_details
+        .iter()
+        .map(|(id, _)| id.to_string())
+        .collect();
+    let mut declared_dependencies = HashMap::new();
+    let mut resolved_dependencies = Vec::new();
+    for (id, module_details) in &ids_with_details {
+        collect_declared_dependencies(&mut declared_dependencies, module_details);
+        for dependency in module_index_client.resolve_dependencies(*id).await?.modules {
+            collect_declared_dependencies(&mut declared_dependencies, &dependency);
+            resolved_dependencies.push(dependency);
+        }
+    }
+
+    let previously_installed_modules = Module::list_installed(&ctx).await?;
+    let mut dependency_ids = HashSet::new();
+    let mut dependency_pkgs = Vec::new();
+    for dependency in resolved_dependencies {
+        if requested_ids.contains(&dependency.id)
+            || !dependency_ids.insert(dependency.id.to_owned())
+        {
+            continue;
+        }
+        let specs = declared_dependencies
+            .get(&dependency.name)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if installed_module_satisfies(&previously_installed_modules, specs)
+            || Module::find_by_root_hash(&ctx, &dependency.latest_hash)
+                .await?
+                .is_some()
+        {
+            continue;
+        }
+
+        let pkg_data = module_index_client
+            .download_module(Ulid::from_string(&dependency.id)?)
+            .await?;
+        let pkg = SiPkg::load_from_bytes(&pkg_data)?.with_signature(module_signature(&dependency));
+        let options = ImportOptions {
+            schema_id: dependency.schema_id().map(Into::into),
+            past_module_hashes: dependency.past_hashes,
+            verify_signature: true,
+            ..Default::default()
+        };
+        dependency_pkgs.push((pkg, options));
+    }
+    import_pkgs_with_dependencies(&ctx, dependency_pkgs).await?;
+
+    let mut requested_pkgs = Vec::with_capacity(ids_with_details.len());
     for (id, module_details) in ids_with_details {
         let pkg_data = module_index_client.download_module(id).await?;
 
-        let pkg = SiPkg::load_from_bytes(&pkg_data)?;
+        let pkg =
+            SiPkg::load_from_bytes(&pkg_data)?.with_signature(module_signature(&module_details));
+        requested_pkgs.push((pkg, (id, module_details)));
+    }
+    let installed_modules = Module::list_installed(&ctx).await?;
 
+    // After validating that we can install the modules, get on with it.
+    for (pkg, (id, module_details)) in pkgs_in_dependency_order(requested_pkgs, &installed_modules)?
+    {
         let (schema_id, past_module_hashes) = if pkg.schemas()?.len() > 1 {
             (None, None)
         } else {
@@ -106,6 +172,7 @@ pub async fn install_module(
             Some(ImportOptions {
                 schema_id,
                 past_module_hashes,
+                verify_signature: true,
                 ..Default::default()
             }),
         )
@@ -155,3 +222,25 @@ pub async fn install_module(
 
     Ok(ForceChangeSetResponse::new(force_change_set_id, variants))
 }
+
+/// Collects the dependencies a module declares in its index metadata, by the name of the module
+/// they depend on.
+fn collect_declared_dependencies(
+    declared_dependencies: &mut HashMap<String, Vec<DependencySpec>>,
+    module_details: &ModuleDetailsResponse,
+) {
+    // Modules published before dependencies existed may not have this metadata at all
+    let Ok(metadata) = serde_json::from_value::<ExtraMetadata>(module_details.metadata.to_owned())
+    else {
+        return;
+    };
+    for dependency in metadata.dependencies {
+        declared_dependencies
+            .entry(dependency.name.to_owned())
+            .or_default()
+            .push(DependencySpec {
+                name: dependency.name,
+                version_constraint: dependency.version_constraint,
+            });
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Host, OriginalUri},
+    Json,
+};
+use dal::{
+    workspace::template::{instantiate_template, WorkspaceTemplateInstance},
+    WsEvent,
+};
+use module_index_client::ModuleIndexClient;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::{
+    extract::{AccessBuilder, HandlerContext, PosthogClient, RawAccessToken},
+    service::module::{ModuleError, ModuleResult},
+    track,
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallWorkspaceTemplateRequest {
+    pub id: Ulid,
+    #[serde(default)]
+    pub values: HashMap<String, serde_json::Value>,
+}
+
+/// Creates a change set holding the template's model, with the parameter values substituted in.
+pub async fn install_workspace_template(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    RawAccessToken(raw_access_token): RawAccessToken,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Host(host_name): Host,
+    Json(request): Json<InstallWorkspaceTemplateRequest>,
+) -> ModuleResult<Json<WorkspaceTemplateInstance>> {
+    let mut ctx = builder.build_head(request_ctx).await?;
+
+    let template = {
+        let module_index_url = match ctx.module_index_url() {
+            Some(url) => url,
+            None => return Err(ModuleError::ModuleIndexNotConfigured),
+        };
+        let module_index_client =
+            ModuleIndexClient::new(module_index_url.try_into()?, &raw_access_token);
+        module_index_client
+            .download_workspace_template(request.id)
+            .await?
+    };
+
+    let instance = instantiate_template(&mut ctx, template, request.values).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        &host_name,
+        "install_workspace_template",
+        serde_json::json!({
+            "template_id": request.id,
+            "component_count": instance.report.component_ids.len(),
+            "missing_schema_variants": instance.report.missing_schema_variants,
+        }),
+    );
+
+    WsEvent::change_set_created(&ctx, instance.change_set_id)
+        .await?
+        .publish_on_commit(&ctx)
+        .await?;
+
+    ctx.commit().await?;
+
+    Ok(Json(instance))
+}
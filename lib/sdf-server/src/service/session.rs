@@ -4,6 +4,7 @@ use axum::{
     routing::{get, post},
     Router,
 };
+use dal::workspace::template::WorkspaceTemplateError;
 use dal::{
     KeyPairError, StandardModelError, TransactionsError, UserError, UserPk, WorkspaceError,
     WorkspacePk,
@@ -38,6 +39,10 @@ pub enum SessionError {
     KeyPair(#[from] KeyPairError),
     #[error("login failed")]
     LoginFailed,
+    #[error("module index error: {0}")]
+    ModuleIndex(#[from] module_index_client::ModuleIndexClientError),
+    #[error("module index not configured")]
+    ModuleIndexNotConfigured,
     #[error(transparent)]
     Nats(#[from] si_data_nats::NatsError),
     #[error("Permissions error: {0}")]
@@ -50,6 +55,8 @@ pub enum SessionError {
     SpiceDb(#[from] SpiceDbError),
     #[error(transparent)]
     StandardModel(#[from] StandardModelError),
+    #[error("url parse error: {0}")]
+    Url(#[from] url::ParseError),
     #[error("user error: {0}")]
     User(#[from] UserError),
     #[error(transparent)]
@@ -58,6 +65,8 @@ pub enum SessionError {
     WorkspaceNotYetMigrated(WorkspacePk),
     #[error("invalid workspace permission: {0}")]
     WorkspacePermission(&'static str),
+    #[error("workspace template error: {0}")]
+    WorkspaceTemplate(#[from] WorkspaceTemplateError),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -75,6 +84,10 @@ impl IntoResponse for SessionError {
             SessionError::InvalidWorkspace(_) => (StatusCode::CONFLICT, self.to_string()),
             SessionError::WorkspacePermission(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
             SessionError::AuthApiError(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+            SessionError::WorkspaceTemplate(
+                WorkspaceTemplateError::MissingParameterValue(_)
+                | WorkspaceTemplateError::UnknownParameter(_),
+            ) => (StatusCode::BAD_REQUEST, self.to_string()),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
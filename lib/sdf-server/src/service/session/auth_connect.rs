@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use axum::{
     extract::{Host, OriginalUri, State},
     Json,
@@ -7,11 +9,12 @@ use dal::{
     WorkspaceSnapshotGraph,
 };
 use hyper::Uri;
-use permissions::{ObjectType, Relation, RelationBuilder};
+use module_index_client::ModuleIndexClient;
+use permissions::{ObjectType, PermissionsClient, Relation, RelationBuilder};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use si_data_spicedb::SpiceDbClient;
 use telemetry::tracing::warn;
+use ulid::Ulid;
 
 use super::{SessionError, SessionResult};
 use crate::{
@@ -25,6 +28,16 @@ use crate::{
 pub struct AuthConnectRequest {
     pub code: String,
     pub on_demand_assets: Option<bool>,
+    /// The template to start the workspace from, if connecting creates it
+    pub workspace_template: Option<AuthConnectWorkspaceTemplate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthConnectWorkspaceTemplate {
+    pub id: Ulid,
+    #[serde(default)]
+    pub values: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -95,7 +108,8 @@ async fn find_or_create_user_and_workspace(
     create_workspace_permissions: WorkspacePermissionsMode,
     create_workspace_allowlist: &[String],
     on_demand_assets: bool,
-    spicedb_client: Option<&mut SpiceDbClient>,
+    workspace_template: Option<(AuthConnectWorkspaceTemplate, &str)>,
+    permissions_client: Option<&mut PermissionsClient>,
 ) -> SessionResult<(User, Workspace)> {
     // lookup user or create if we've never seen it before
     let maybe_user = User::get_by_pk(&ctx, auth_api_user.id).await?;
@@ -115,6 +129,7 @@ async fn find_or_create_user_and_workspace(
     ctx.update_history_actor(HistoryActor::User(user.pk()));
 
     // lookup workspace or create if we've never seen it before
+    let mut instantiated_template = false;
     let maybe_workspace = Workspace::get_by_pk(&ctx, &auth_api_workspace.id).await?;
     let workspace = match maybe_workspace {
         Some(mut workspace) => {
@@ -149,7 +164,45 @@ async fn find_or_create_user_and_workspace(
                 ));
             }
 
-            let workspace = if on_demand_assets {
+            let workspace = if let Some((workspace_template, raw_access_token)) = workspace_template
+            {
+                let template = {
+                    let module_index_url = ctx
+                        .module_index_url()
+                        .ok_or(SessionError::ModuleIndexNotConfigured)?;
+                    let module_index_client =
+                        ModuleIndexClient::new(module_index_url.try_into()?, raw_access_token);
+                    module_index_client
+                        .download_workspace_template(workspace_template.id)
+                        .await?
+                };
+
+                let (workspace, instance) = Workspace::new_from_template(
+                    &mut ctx,
+                    auth_api_workspace.id,
+                    auth_api_workspace.display_name.clone(),
+                    auth_api_workspace.token,
+                    template,
+                    workspace_template.values,
+                )
+                .await?;
+                instantiated_template = true;
+
+                track(
+                    &posthog_client,
+                    &ctx,
+                    original_uri,
+                    host_name,
+                    "install_workspace_template",
+                    serde_json::json!({
+                        "template_id": workspace_template.id,
+                        "component_count": instance.report.component_ids.len(),
+                        "missing_schema_variants": instance.report.missing_schema_variants,
+                    }),
+                );
+
+                workspace
+            } else if on_demand_assets {
                 Workspace::new_for_on_demand_assets(
                     &mut ctx,
                     auth_api_workspace.id,
@@ -188,7 +241,7 @@ async fn find_or_create_user_and_workspace(
         }
     };
 
-    if let Some(client) = spicedb_client {
+    if let Some(client) = permissions_client {
         // the creator is the owner. Currently, owners cannot be changed so this should always be
         // true. Once we map the auth-api roles to spicedb we can rely on that to tell us this
         // information.
@@ -205,7 +258,12 @@ async fn find_or_create_user_and_workspace(
     // ensure workspace is associated to user
     user.associate_workspace(&ctx, *workspace.pk()).await?;
 
-    ctx.commit_no_rebase().await?;
+    // The template's model is in the snapshot of its change set, which only a rebase writes
+    if instantiated_template {
+        ctx.commit().await?;
+    } else {
+        ctx.commit_no_rebase().await?;
+    }
 
     Ok((user, workspace))
 }
@@ -249,7 +307,10 @@ pub async fn auth_connect(
         state.create_workspace_permissions(),
         state.create_workspace_allowlist(),
         request.on_demand_assets.unwrap_or(false),
-        state.spicedb_client_clone().as_mut(),
+        request
+            .workspace_template
+            .map(|workspace_template| (workspace_template, res_body.token.as_str())),
+        state.permissions_client_clone().as_mut(),
     )
     .await?;
 
@@ -298,7 +359,8 @@ pub async fn auth_reconnect(
         state.create_workspace_permissions(),
         state.create_workspace_allowlist(),
         auth_response_body.on_demand_assets.unwrap_or(false),
-        state.spicedb_client_clone().as_mut(),
+        None,
+        state.permissions_client_clone().as_mut(),
     )
     .await?;
 
@@ -337,7 +399,7 @@ pub async fn user_has_permission_to_create_workspace(
 }
 
 async fn set_owner_as_owner(
-    client: &mut SpiceDbClient,
+    client: &mut PermissionsClient,
     user_id: String,
     workspace_id: String,
 ) -> SessionResult<()> {
@@ -4,9 +4,8 @@ use axum::{
     Json,
 };
 use dal::{DalContext, User};
-use permissions::{ObjectType, Relation, RelationBuilder};
+use permissions::{ObjectType, PermissionsClient, Relation, RelationBuilder};
 use serde::{Deserialize, Serialize};
-use si_data_spicedb::SpiceDbClient;
 use strum::{Display, EnumString};
 
 use super::{SessionError, SessionResult};
@@ -82,7 +81,7 @@ pub async fn refresh_workspace_members(
     let ctx = builder.build_head(access_builder).await?;
     let posthog_client = PosthogClient(posthog_client.clone());
 
-    if let Some(client) = state.spicedb_client() {
+    if let Some(client) = state.permissions_client() {
         let approvers: Vec<_> = workspace_members
             .clone()
             .into_iter()
@@ -118,7 +117,7 @@ pub async fn refresh_workspace_members(
 
 async fn sync_workspace_approvers(
     ctx: &DalContext,
-    client: &mut SpiceDbClient,
+    client: &mut PermissionsClient,
     workspace_id: String,
     new_approver_ids: Vec<String>,
     original_uri: &Uri,
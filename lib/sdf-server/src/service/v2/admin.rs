@@ -17,6 +17,7 @@ use thiserror::Error;
 
 use crate::{extract::AdminAccessBuilder, service::ApiError, AppState};
 
+mod fsck;
 mod get_snapshot;
 mod kill_execution;
 mod list_change_sets;
@@ -179,6 +180,10 @@ pub fn v2_routes(state: AppState) -> Router<AppState> {
             "/workspaces/:workspace_pk/change_sets",
             get(list_change_sets::list_change_sets),
         )
+        .route(
+            "/workspaces/:workspace_pk/change_sets/:change_set_id/fsck",
+            post(fsck::fsck),
+        )
         .route(
             "/workspaces/:workspace_pk/change_sets/:change_set_id/get_snapshot",
             get(get_snapshot::get_snapshot),
@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Host, OriginalUri, Path, Query},
+    response::Json,
+};
+use dal::{
+    workspace_snapshot::graph::FsckReport, ChangeSet, ChangeSetId, WorkspacePk, WorkspaceSnapshot,
+    WorkspaceSnapshotAddress,
+};
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+
+use super::{AdminAPIError, AdminAPIResult};
+use crate::{
+    extract::{AccessBuilder, HandlerContext, PosthogClient},
+    track_no_ctx,
+};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct FsckRequest {
+    /// Fix the violations that can be fixed safely and point the change set at the result
+    #[serde(default)]
+    pub repair: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FsckResponse {
+    #[serde(flatten)]
+    report: FsckReport,
+    /// The snapshot the change set points at after the check, which is a new one if anything
+    /// was repaired
+    workspace_snapshot_address: WorkspaceSnapshotAddress,
+}
+
+#[instrument(
+    name = "admin.fsck",
+    level = "info",
+    skip_all,
+    fields(
+        si.change_set.id = %change_set_id,
+        si.workspace.id = %workspace_pk,
+        si.workspace_snapshot.address = Empty,
+        si.workspace_snapshot.fsck.violations = Empty,
+        si.workspace_snapshot.fsck.repaired = Empty,
+    ),
+)]
+pub async fn fsck(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Host(host_name): Host,
+    Path((workspace_pk, change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
+    Query(request): Query<FsckRequest>,
+) -> AdminAPIResult<Json<FsckResponse>> {
+    let span = current_span_for_instrument_at!("info");
+
+    let ctx = builder.build_head(access_builder).await?;
+
+    let mut change_set = ChangeSet::find(&ctx, change_set_id)
+        .await?
+        .ok_or(AdminAPIError::ChangeSetNotFound(change_set_id))?;
+
+    let snapshot = WorkspaceSnapshot::find(&ctx, change_set.workspace_snapshot_address).await?;
+    let report = snapshot.fsck(&ctx, request.repair).await?;
+
+    let workspace_snapshot_address = if report.repaired.is_empty() {
+        change_set.workspace_snapshot_address
+    } else {
+        let address = snapshot.write(&ctx).await?;
+        change_set.update_pointer(&ctx, address).await?;
+        ctx.commit_no_rebase().await?;
+        address
+    };
+
+    span.record(
+        "si.workspace_snapshot.address",
+        workspace_snapshot_address.to_string(),
+    );
+    span.record(
+        "si.workspace_snapshot.fsck.violations",
+        report.violations.len(),
+    );
+    span.record("si.workspace_snapshot.fsck.repaired", report.repaired.len());
+
+    track_no_ctx(
+        &posthog_client,
+        &original_uri,
+        &host_name,
+        ctx.history_actor().distinct_id(),
+        Some(workspace_pk.to_string()),
+        Some(change_set_id.to_string()),
+        "admin.fsck",
+        serde_json::json!({
+            "workspace_snapshot_address": workspace_snapshot_address.to_string(),
+            "violations": report.violations.len(),
+            "repaired": report.repaired.len(),
+        }),
+    );
+
+    Ok(Json(FsckResponse {
+        report,
+        workspace_snapshot_address,
+    }))
+}
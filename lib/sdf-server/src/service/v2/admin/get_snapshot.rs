@@ -3,7 +3,7 @@ use axum::{
     response::Response,
 };
 use base64::prelude::*;
-use dal::{ChangeSet, ChangeSetId, WorkspacePk};
+use dal::{ChangeSet, ChangeSetId, WorkspacePk, WorkspaceSnapshot};
 use hyper::{header, Body};
 
 use crate::{
@@ -29,15 +29,25 @@ pub async fn get_snapshot(
 
     let snap_addr = change_set.workspace_snapshot_address;
 
-    let bytes = ctx
-        .layer_db()
-        .workspace_snapshot()
-        .read_bytes_from_durable_storage(&snap_addr)
-        .await?
-        .ok_or(AdminAPIError::WorkspaceSnapshotNotFound(
-            snap_addr,
-            change_set_id,
-        ))?;
+    // A chunked snapshot is only a list of chunk addresses, which is useless outside of this
+    // environment, so we hand out the assembled graph instead
+    let bytes = match WorkspaceSnapshot::chunk_manifest(&ctx, snap_addr).await? {
+        Some(_) => {
+            WorkspaceSnapshot::find(&ctx, snap_addr)
+                .await?
+                .serialized()
+                .await?
+        }
+        None => ctx
+            .layer_db()
+            .workspace_snapshot()
+            .read_bytes_from_durable_storage(&snap_addr)
+            .await?
+            .ok_or(AdminAPIError::WorkspaceSnapshotNotFound(
+                snap_addr,
+                change_set_id,
+            ))?,
+    };
 
     let base64 = tokio::task::spawn_blocking(|| BASE64_STANDARD.encode(bytes)).await?;
 
@@ -12,6 +12,7 @@ use thiserror::Error;
 use crate::{middleware::WorkspacePermissionLayer, service::ApiError, AppState};
 
 mod apply;
+mod approval_policy;
 mod approve;
 mod cancel_approval_request;
 mod force_apply;
@@ -35,6 +36,8 @@ pub enum Error {
     DvuRootsNotEmpty(ChangeSetId),
     #[error("func error: {0}")]
     Func(#[from] dal::FuncError),
+    #[error("only workspace managers may override approval policies")]
+    OverrideNotPermitted,
     #[error("permissions error: {0}")]
     Permissions(#[from] permissions::Error),
     #[error("schema error: {0}")]
@@ -56,8 +59,15 @@ pub enum Error {
 impl IntoResponse for Error {
     fn into_response(self) -> axum::response::Response {
         let status_code = match &self {
+            Self::ChangeSet(dal::ChangeSetError::ApprovalRequirementsNotMet(_)) => {
+                StatusCode::PRECONDITION_FAILED
+            }
+            Self::ChangeSet(dal::ChangeSetError::ChangedSinceApproval(_)) => {
+                StatusCode::PRECONDITION_FAILED
+            }
             Self::ChangeSetApply(_) => StatusCode::CONFLICT,
             Self::DvuRootsNotEmpty(_) => StatusCode::PRECONDITION_FAILED,
+            Self::OverrideNotPermitted => StatusCode::FORBIDDEN,
             Self::Transactions(dal::TransactionsError::BadWorkspaceAndChangeSet) => {
                 StatusCode::FORBIDDEN
             }
@@ -75,6 +85,16 @@ type Result<T> = result::Result<T, Error>;
 pub fn v2_routes(state: AppState) -> Router<AppState> {
     Router::new()
         .route("/apply", post(apply::apply))
+        .route(
+            "/approval_policies",
+            get(approval_policy::list_policies).merge(post(approval_policy::set_policies).layer(
+                WorkspacePermissionLayer::new(state.clone(), permissions::Permission::Manage),
+            )),
+        )
+        .route(
+            "/required_approvals",
+            get(approval_policy::required_approvals),
+        )
         .route(
             "/request_approval",
             post(request_approval::request_approval),
@@ -0,0 +1,49 @@
+use axum::{extract::Path, Json};
+use dal::{
+    change_set::approval::{ApprovalPolicy, RequiredApproval},
+    ChangeSet, ChangeSetId, WorkspacePk,
+};
+
+use super::{Error, Result};
+use crate::extract::{AccessBuilder, HandlerContext};
+
+pub async fn list_policies(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Path((_workspace_pk, _change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
+) -> Result<Json<Vec<ApprovalPolicy>>> {
+    let ctx = builder.build_head(request_ctx).await?;
+
+    Ok(Json(ApprovalPolicy::list_for_workspace(&ctx).await?))
+}
+
+pub async fn set_policies(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Path((_workspace_pk, _change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
+    Json(policies): Json<Vec<ApprovalPolicy>>,
+) -> Result<Json<Vec<ApprovalPolicy>>> {
+    let ctx = builder.build_head(request_ctx).await?;
+
+    ApprovalPolicy::set_for_workspace(&ctx, &policies).await?;
+
+    ctx.commit_no_rebase().await?;
+
+    Ok(Json(policies))
+}
+
+pub async fn required_approvals(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Path((_workspace_pk, change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
+) -> Result<Json<Vec<RequiredApproval>>> {
+    let ctx = builder
+        .build(request_ctx.build(change_set_id.into()))
+        .await?;
+
+    let change_set = ChangeSet::find(&ctx, change_set_id)
+        .await?
+        .ok_or(Error::ChangeSetNotFound(change_set_id))?;
+
+    Ok(Json(change_set.required_approvals(&ctx).await?))
+}
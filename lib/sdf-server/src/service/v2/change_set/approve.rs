@@ -1,7 +1,8 @@
-use axum::extract::{Host, OriginalUri, Path};
-use dal::{ChangeSet, ChangeSetId, WorkspacePk, WsEvent};
+use axum::extract::{Host, OriginalUri, Path, State};
+use dal::{change_set::approval::ApprovalRole, ChangeSet, ChangeSetId, WorkspacePk, WsEvent};
+use permissions::{Permission, PermissionBuilder};
 
-use super::{Error, Result};
+use super::{AppState, Error, Result};
 use crate::{
     extract::{AccessBuilder, HandlerContext, PosthogClient},
     track,
@@ -13,7 +14,8 @@ pub async fn approve(
     PosthogClient(posthog_client): PosthogClient,
     OriginalUri(original_uri): OriginalUri,
     Host(host_name): Host,
-    Path((_workspace_pk, change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
+    State(mut state): State<AppState>,
+    Path((workspace_pk, change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
 ) -> Result<()> {
     let ctx = builder
         .build(request_ctx.build(change_set_id.into()))
@@ -39,7 +41,25 @@ pub async fn approve(
         .await?
         .ok_or(Error::ChangeSetNotFound(ctx.change_set_id()))?;
     let old_status = change_set.status;
-    change_set.approve_change_set_for_apply(&ctx).await?;
+
+    // The route is guarded by the approve permission, so every approver counts towards
+    // policies requiring an approver. Secret managers are determined here.
+    let mut roles = vec![ApprovalRole::Approver];
+    if let Some(client) = state.permissions_client() {
+        let user_pk = ChangeSet::extract_userid_from_context_or_error(&ctx).await?;
+        if PermissionBuilder::new()
+            .workspace_object(workspace_pk.into())
+            .permission(Permission::ApproveSecrets)
+            .user_subject(user_pk.into())
+            .has_permission(client)
+            .await?
+        {
+            roles.push(ApprovalRole::SecretManager);
+        }
+    }
+    change_set
+        .approve_change_set_for_apply_with_roles(&ctx, &roles)
+        .await?;
 
     track(
         &posthog_client,
@@ -1,25 +1,58 @@
-use axum::extract::{Host, OriginalUri, Path};
+use axum::{
+    extract::{Host, OriginalUri, Path, State},
+    Json,
+};
 use dal::{ChangeSet, ChangeSetId, WorkspacePk};
+use permissions::{Permission, PermissionBuilder};
+use serde::{Deserialize, Serialize};
 
-use super::{Error, Result};
+use super::{AppState, Error, Result};
 use crate::{
     extract::{AccessBuilder, HandlerContext, PosthogClient},
     track,
 };
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForceApplyRequest {
+    /// Apply even if the workspace's approval policies are not satisfied. Only users who can
+    /// manage the workspace may do this, and every override is written to the audit log.
+    #[serde(default)]
+    pub override_approval_policies: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn force_apply(
     HandlerContext(builder): HandlerContext,
     AccessBuilder(request_ctx): AccessBuilder,
     PosthogClient(posthog_client): PosthogClient,
     OriginalUri(original_uri): OriginalUri,
     Host(host_name): Host,
-    Path((_workspace_pk, change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
+    State(mut state): State<AppState>,
+    Path((workspace_pk, change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
+    request: Option<Json<ForceApplyRequest>>,
 ) -> Result<()> {
     let mut ctx = builder
         .build(request_ctx.build(change_set_id.into()))
         .await?;
 
-    ChangeSet::prepare_for_force_apply(&ctx).await?;
+    let Json(request) = request.unwrap_or_default();
+    if request.override_approval_policies {
+        if let Some(client) = state.permissions_client() {
+            let user_pk = ChangeSet::extract_userid_from_context_or_error(&ctx).await?;
+            if !PermissionBuilder::new()
+                .workspace_object(workspace_pk.into())
+                .permission(Permission::Manage)
+                .user_subject(user_pk.into())
+                .has_permission(client)
+                .await?
+            {
+                return Err(Error::OverrideNotPermitted);
+            }
+        }
+    }
+
+    ChangeSet::prepare_for_force_apply(&ctx, request.override_approval_policies).await?;
 
     // We need to run a commit before apply so changes get saved
     ctx.commit().await?;
@@ -34,6 +67,7 @@ pub async fn force_apply(
         "apply_change_set",
         serde_json::json!({
             "merged_change_set": change_set_id,
+            "override_approval_policies": request.override_approval_policies,
         }),
     );
 
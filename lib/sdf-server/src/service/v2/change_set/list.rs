@@ -28,7 +28,7 @@ pub async fn list_actionable(
     for change_set in open_change_sets {
         views.push(change_set.into_frontend_type(&ctx).await?);
     }
-    let client = state.spicedb_client().ok_or(Error::SpiceDBNotFound)?;
+    let client = state.permissions_client().ok_or(Error::SpiceDBNotFound)?;
     let existing_approvers = RelationBuilder::new()
         .object(ObjectType::Workspace, workspace_pk)
         .relation(Relation::Owner) // TODO(WENDY) - this should be Relation::Approver but it isn't working yet
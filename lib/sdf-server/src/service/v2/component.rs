@@ -0,0 +1,113 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use dal::{
+    attribute::value::provenance::{AttributeValueProvenance, AttributeValueProvenanceError},
+    component::query::{ComponentQuery, ComponentQueryError},
+    AttributeValueId, ChangeSetId, ComponentId, TransactionsError, WorkspacePk,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    extract::{AccessBuilder, HandlerContext},
+    service::ApiError,
+    AppState,
+};
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum ComponentApiError {
+    #[error("attribute value provenance error: {0}")]
+    AttributeValueProvenance(#[from] AttributeValueProvenanceError),
+    #[error("component query error: {0}")]
+    ComponentQuery(#[from] ComponentQueryError),
+    #[error("transactions error: {0}")]
+    Transactions(#[from] TransactionsError),
+}
+
+pub type ComponentApiResult<T> = Result<T, ComponentApiError>;
+
+impl IntoResponse for ComponentApiError {
+    fn into_response(self) -> Response {
+        let status_code = match &self {
+            ComponentApiError::ComponentQuery(
+                ComponentQueryError::InvalidRegex(..) | ComponentQueryError::PropPathOutsideRoot(_),
+            ) => StatusCode::BAD_REQUEST,
+            ComponentApiError::AttributeValueProvenance(
+                AttributeValueProvenanceError::AttributeValueNotFound(_),
+            ) => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        ApiError::new(status_code, self.to_string()).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryResponse {
+    component_ids: Vec<ComponentId>,
+}
+
+/// Finds the components in the change set that match a [`ComponentQuery`].
+pub async fn query(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    Path((_workspace_pk, change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
+    Json(query): Json<ComponentQuery>,
+) -> ComponentApiResult<Json<QueryResponse>> {
+    let ctx = builder
+        .build(access_builder.build(change_set_id.into()))
+        .await?;
+
+    let component_ids = query.run(&ctx).await?;
+
+    Ok(Json(QueryResponse { component_ids }))
+}
+
+/// How many levels of upstream values a provenance trace follows when the request does not say.
+/// Deeper requests are cut off at `MAX_PROVENANCE_DEPTH`.
+const DEFAULT_PROVENANCE_DEPTH: usize = 10;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvenanceRequest {
+    depth: Option<usize>,
+}
+
+/// Explains where an attribute value came from. See [`AttributeValueProvenance`].
+pub async fn provenance(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    Path((_workspace_pk, change_set_id, attribute_value_id)): Path<(
+        WorkspacePk,
+        ChangeSetId,
+        AttributeValueId,
+    )>,
+    Query(request): Query<ProvenanceRequest>,
+) -> ComponentApiResult<Json<AttributeValueProvenance>> {
+    let ctx = builder
+        .build(access_builder.build(change_set_id.into()))
+        .await?;
+
+    let provenance = AttributeValueProvenance::trace(
+        &ctx,
+        attribute_value_id,
+        request.depth.unwrap_or(DEFAULT_PROVENANCE_DEPTH),
+    )
+    .await?;
+
+    Ok(Json(provenance))
+}
+
+pub fn v2_routes() -> Router<AppState> {
+    Router::new().route("/query", post(query)).route(
+        "/attribute-values/:attribute_value_id/provenance",
+        get(provenance),
+    )
+}
@@ -61,6 +61,7 @@ pub async fn contribute(
             payload,
             Some(request.schema_variant_id.to_string()),
             Some(schema_variant_version),
+            None,
         )
         .await?;
 
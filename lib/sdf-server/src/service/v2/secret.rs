@@ -0,0 +1,57 @@
+use std::result;
+
+use axum::{
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Router,
+};
+use thiserror::Error;
+
+use crate::{middleware::WorkspacePermissionLayer, service::ApiError, AppState};
+
+mod rotation;
+mod usage;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("sinceDays is too large: {0}")]
+    InvalidSinceDays(u32),
+    #[error("secret error: {0}")]
+    Secret(#[from] dal::SecretError),
+    #[error("transactions error: {0}")]
+    Transactions(#[from] dal::TransactionsError),
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> axum::response::Response {
+        let status_code = match &self {
+            Self::InvalidSinceDays(_) => StatusCode::BAD_REQUEST,
+            Self::Secret(dal::SecretError::SecretKeyRotationAlreadyRunning(_)) => {
+                StatusCode::CONFLICT
+            }
+            Self::Secret(dal::SecretError::SecretKeyRotationNotFound(_)) => StatusCode::NOT_FOUND,
+            Self::Transactions(dal::TransactionsError::BadWorkspaceAndChangeSet) => {
+                StatusCode::FORBIDDEN
+            }
+            _ => ApiError::DEFAULT_ERROR_STATUS_CODE,
+        };
+
+        ApiError::new(status_code, self).into_response()
+    }
+}
+
+type Result<T> = result::Result<T, Error>;
+
+pub fn v2_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/rotations",
+            get(rotation::list_rotations).merge(post(rotation::begin_rotation).layer(
+                WorkspacePermissionLayer::new(state, permissions::Permission::Manage),
+            )),
+        )
+        .route("/rotations/:rotation_id", get(rotation::get_rotation))
+        .route("/:secret_id/usages", get(usage::list_usages))
+}
@@ -0,0 +1,55 @@
+use axum::{extract::Path, Json};
+use dal::{
+    job::definition::SecretKeyRotationJob, ChangeSetId, SecretKeyRotation, SecretKeyRotationId,
+    WorkspacePk,
+};
+
+use super::Result;
+use crate::extract::{AccessBuilder, HandlerContext};
+
+/// Begins a rotation (or resumes a failed one) and enqueues the job that drives it. A rotation
+/// that is already running is not started again and returns a conflict.
+pub async fn begin_rotation(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Path((_workspace_pk, _change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
+) -> Result<Json<SecretKeyRotation>> {
+    let ctx = builder.build_head(request_ctx).await?;
+
+    let rotation = SecretKeyRotation::begin(&ctx).await?;
+
+    ctx.enqueue_secret_key_rotation(SecretKeyRotationJob::new(
+        ctx.access_builder(),
+        *ctx.visibility(),
+        rotation.id,
+    ))
+    .await?;
+
+    ctx.commit().await?;
+
+    Ok(Json(rotation))
+}
+
+pub async fn list_rotations(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Path((_workspace_pk, _change_set_id)): Path<(WorkspacePk, ChangeSetId)>,
+) -> Result<Json<Vec<SecretKeyRotation>>> {
+    let ctx = builder.build_head(request_ctx).await?;
+
+    Ok(Json(SecretKeyRotation::list_for_workspace(&ctx).await?))
+}
+
+pub async fn get_rotation(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Path((_workspace_pk, _change_set_id, rotation_id)): Path<(
+        WorkspacePk,
+        ChangeSetId,
+        SecretKeyRotationId,
+    )>,
+) -> Result<Json<SecretKeyRotation>> {
+    let ctx = builder.build_head(request_ctx).await?;
+
+    Ok(Json(SecretKeyRotation::get_by_id(&ctx, rotation_id).await?))
+}
@@ -0,0 +1,39 @@
+use axum::{
+    extract::{Path, Query},
+    Json,
+};
+use chrono::{Duration, Utc};
+use dal::{ChangeSetId, SecretId, SecretUsage, WorkspacePk};
+use serde::{Deserialize, Serialize};
+
+use super::{Error, Result};
+use crate::extract::{AccessBuilder, HandlerContext};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ListUsagesRequest {
+    /// Only include usages from this many days ago onwards.
+    since_days: Option<u32>,
+}
+
+pub async fn list_usages(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Path((_workspace_pk, _change_set_id, secret_id)): Path<(WorkspacePk, ChangeSetId, SecretId)>,
+    Query(request): Query<ListUsagesRequest>,
+) -> Result<Json<Vec<SecretUsage>>> {
+    let ctx = builder.build_head(request_ctx).await?;
+
+    let since = request
+        .since_days
+        .map(|days| {
+            Duration::try_days(days.into())
+                .and_then(|duration| Utc::now().checked_sub_signed(duration))
+                .ok_or(Error::InvalidSinceDays(days))
+        })
+        .transpose()?;
+
+    Ok(Json(
+        SecretUsage::list_for_secret(&ctx, secret_id, since).await?,
+    ))
+}
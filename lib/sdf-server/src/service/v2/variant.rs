@@ -14,6 +14,7 @@ use crate::{service::ApiError, AppState};
 pub mod create_unlocked_copy;
 mod delete_unlocked_variant;
 mod generate_aws_asset_schema;
+mod generate_cloudformation_asset_schema;
 mod get_variant;
 mod list_variants;
 
@@ -52,6 +53,9 @@ impl IntoResponse for SchemaVariantsAPIError {
             Self::Transactions(dal::TransactionsError::BadWorkspaceAndChangeSet) => {
                 StatusCode::FORBIDDEN
             }
+            Self::AssetSprayer(AssetSprayerError::CloudFormationRefNotFound(_)) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
             Self::CannotDeleteVariantWithComponents | Self::CannotDeleteLockedSchemaVariant(_) => {
                 StatusCode::PRECONDITION_FAILED
             }
@@ -80,4 +84,8 @@ pub fn v2_routes() -> Router<AppState> {
             "/:schema_variant_id/generate_aws_asset_schema",
             get(generate_aws_asset_schema::generate_aws_asset_schema),
         )
+        .route(
+            "/:schema_variant_id/generate_cloudformation_asset_schema",
+            post(generate_cloudformation_asset_schema::generate_cloudformation_asset_schema),
+        )
 }
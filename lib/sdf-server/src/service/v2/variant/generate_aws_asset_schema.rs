@@ -1,7 +1,7 @@
 use axum::extract::{Host, OriginalUri, Path, Query};
 use dal::{
-    schema::variant::authoring::VariantAuthoringClient, ChangeSet, ChangeSetId, SchemaVariant,
-    SchemaVariantId, WorkspacePk, WsEvent,
+    schema::variant::authoring::VariantAuthoringClient, ChangeSet, ChangeSetId, DalContext,
+    SchemaVariant, SchemaVariantId, WorkspacePk, WsEvent,
 };
 use serde::{Deserialize, Serialize};
 
@@ -45,24 +45,7 @@ pub async fn generate_aws_asset_schema(
         .aws_asset_schema(&aws_command.command, &aws_command.subcommand)
         .await?;
 
-    // Update the function
-    let schema_variant = SchemaVariant::get_by_id_or_error(&ctx, schema_variant_id).await?;
-    let schema_id = SchemaVariant::schema_id_for_schema_variant_id(&ctx, schema_variant_id).await?;
-    let variant = schema_variant.into_frontend_type(&ctx, schema_id).await?;
-
-    VariantAuthoringClient::save_variant_content(
-        &ctx,
-        schema_variant_id,
-        &variant.schema_name,
-        variant.display_name.clone(),
-        variant.category.clone(),
-        variant.description.clone(),
-        variant.link.clone(),
-        variant.color.clone(),
-        variant.component_type.into(),
-        Some(code),
-    )
-    .await?;
+    let variant = save_generated_asset_schema(&ctx, schema_variant_id, code).await?;
 
     track(
         &posthog_client,
@@ -79,16 +62,43 @@ pub async fn generate_aws_asset_schema(
         }),
     );
 
+    ctx.commit().await?;
+
+    Ok(ForceChangeSetResponse::empty(force_change_set_id))
+}
+
+/// Replaces the code of the schema variant's asset function with generated code.
+pub(super) async fn save_generated_asset_schema(
+    ctx: &DalContext,
+    schema_variant_id: SchemaVariantId,
+    code: String,
+) -> SchemaVariantsAPIResult<si_frontend_types::SchemaVariant> {
+    let schema_variant = SchemaVariant::get_by_id_or_error(ctx, schema_variant_id).await?;
+    let schema_id = SchemaVariant::schema_id_for_schema_variant_id(ctx, schema_variant_id).await?;
+    let variant = schema_variant.into_frontend_type(ctx, schema_id).await?;
+
+    VariantAuthoringClient::save_variant_content(
+        ctx,
+        schema_variant_id,
+        &variant.schema_name,
+        variant.display_name.clone(),
+        variant.category.clone(),
+        variant.description.clone(),
+        variant.link.clone(),
+        variant.color.clone(),
+        variant.component_type.into(),
+        Some(code),
+    )
+    .await?;
+
     WsEvent::schema_variant_updated(
-        &ctx,
+        ctx,
         schema_id,
-        SchemaVariant::get_by_id_or_error(&ctx, schema_variant_id).await?,
+        SchemaVariant::get_by_id_or_error(ctx, schema_variant_id).await?,
     )
     .await?
-    .publish_on_commit(&ctx)
+    .publish_on_commit(ctx)
     .await?;
 
-    ctx.commit().await?;
-
-    Ok(ForceChangeSetResponse::empty(force_change_set_id))
+    Ok(variant)
 }
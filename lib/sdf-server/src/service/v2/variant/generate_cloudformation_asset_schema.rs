@@ -0,0 +1,59 @@
+use asset_sprayer::cloudformation::CfnResourceSchema;
+use axum::{
+    extract::{Host, OriginalUri, Path},
+    Json,
+};
+use dal::{ChangeSet, ChangeSetId, SchemaVariantId, WorkspacePk};
+
+use crate::{
+    extract::{AccessBuilder, HandlerContext, PosthogClient},
+    service::force_change_set_response::ForceChangeSetResponse,
+    track,
+};
+
+use super::{generate_aws_asset_schema::save_generated_asset_schema, SchemaVariantsAPIResult};
+
+/// Generates the asset function from a CloudFormation resource provider schema. Unlike
+/// [`generate_aws_asset_schema`](super::generate_aws_asset_schema), the same resource schema
+/// always generates the same code.
+pub async fn generate_cloudformation_asset_schema(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Host(host_name): Host,
+    Path((_workspace_pk, change_set_id, schema_variant_id)): Path<(
+        WorkspacePk,
+        ChangeSetId,
+        SchemaVariantId,
+    )>,
+    Json(resource_schema): Json<CfnResourceSchema>,
+) -> SchemaVariantsAPIResult<ForceChangeSetResponse<()>> {
+    let mut ctx = builder
+        .build(access_builder.build(change_set_id.into()))
+        .await?;
+    let force_change_set_id = ChangeSet::force_new(&mut ctx).await?;
+
+    let code = resource_schema.generate_asset()?.render();
+    let variant = save_generated_asset_schema(&ctx, schema_variant_id, code).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        &host_name,
+        "generate_cloudformation_asset_schema",
+        serde_json::json!({
+            "variant_id": schema_variant_id,
+            "variant_category": variant.category.clone(),
+            "variant_name": variant.schema_name.clone(),
+            "variant_display_name": variant.display_name.clone(),
+            "asset_func_id": variant.asset_func_id,
+            "cloudformation_type_name": resource_schema.type_name,
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(ForceChangeSetResponse::empty(force_change_set_id))
+}
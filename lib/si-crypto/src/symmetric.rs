@@ -151,6 +151,11 @@ impl SymmetricCryptoService {
         Ok(Self::new(active_key, extra_keys))
     }
 
+    /// Returns the [`Hash`] of the active [`SymmetricKey`], which is used for all encryption.
+    pub fn active_key_hash(&self) -> &Hash {
+        self.active_key_hash.as_ref()
+    }
+
     /// Generates a new [`SymmetricKey`].
     pub fn generate_key() -> SymmetricKey {
         SymmetricKey(secretbox::gen_key())
@@ -12,6 +12,12 @@ use spicedb_grpc::authzed::api::v1::{self, ObjectReference, SubjectReference};
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ZedToken(String);
 
+impl ZedToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+}
+
 impl ops::Deref for ZedToken {
     type Target = str;
 
@@ -248,7 +254,23 @@ impl Permission {
         i32::from(v1::check_permission_response::Permissionship::HasPermission) == permissionship
     }
 
+    pub fn resource(&self) -> &PermissionsObject {
+        &self.resource
+    }
+
+    pub fn permission(&self) -> &str {
+        &self.permission
+    }
+
+    pub fn subject(&self) -> &PermissionsObject {
+        &self.subject
+    }
+
     pub fn set_zed_token(&mut self, zed_token: Option<ZedToken>) {
         self.zed_token = zed_token;
     }
+
+    pub fn zed_token(&self) -> Option<&ZedToken> {
+        self.zed_token.as_ref()
+    }
 }
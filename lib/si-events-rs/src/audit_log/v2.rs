@@ -50,4 +50,12 @@ pub enum AuditLogKindV2 {
         after_secret_name: Option<String>,
         after_secret_id: Option<SecretId>,
     },
+    RotateSecretKeys {
+        rotation_id: String,
+        rotated_secret_count: u64,
+    },
+    OverrideApprovalPolicies {
+        change_set_id: ChangeSetId,
+        policy_names: Vec<String>,
+    },
 }
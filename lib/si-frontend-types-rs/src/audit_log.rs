@@ -73,6 +73,16 @@ pub enum AuditLogDeserializedMetadata {
         after_secret_name: Option<String>,
         after_secret_id: Option<SecretId>,
     },
+    #[serde(rename_all = "camelCase")]
+    RotateSecretKeys {
+        rotation_id: String,
+        rotated_secret_count: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    OverrideApprovalPolicies {
+        change_set_id: ChangeSetId,
+        policy_names: Vec<String>,
+    },
 }
 
 impl AuditLogDeserializedMetadata {
@@ -87,6 +97,8 @@ impl AuditLogDeserializedMetadata {
             Discrim::UpdatePropertyEditorValueForSecret => {
                 ("Updated secret in Component", "Property for Secret")
             }
+            Discrim::RotateSecretKeys => ("Rotated encryption keys for", "Secrets"),
+            Discrim::OverrideApprovalPolicies => ("Overrode approval policies for", "Change Set"),
         }
     }
 }
@@ -162,6 +174,20 @@ impl From<AuditLogKind> for AuditLogDeserializedMetadata {
                 after_secret_name,
                 after_secret_id,
             },
+            AuditLogKind::RotateSecretKeys {
+                rotation_id,
+                rotated_secret_count,
+            } => Self::RotateSecretKeys {
+                rotation_id,
+                rotated_secret_count,
+            },
+            AuditLogKind::OverrideApprovalPolicies {
+                change_set_id,
+                policy_names,
+            } => Self::OverrideApprovalPolicies {
+                change_set_id,
+                policy_names,
+            },
         }
     }
 }
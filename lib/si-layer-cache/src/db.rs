@@ -25,7 +25,7 @@ use crate::{
 
 use self::{
     cache_updates::CacheUpdatesTask, cas::CasDb, rebase_batch::RebaseBatchDb,
-    workspace_snapshot::WorkspaceSnapshotDb,
+    workspace_snapshot::WorkspaceSnapshotDb, workspace_snapshot_chunk::WorkspaceSnapshotChunkDb,
 };
 
 mod cache_updates;
@@ -36,14 +36,21 @@ pub mod func_run_log;
 pub mod rebase_batch;
 pub mod serialize;
 pub mod workspace_snapshot;
+pub mod workspace_snapshot_chunk;
 
 #[derive(Debug, Clone)]
-pub struct LayerDb<CasValue, EncryptedSecretValue, WorkspaceSnapshotValue, RebaseBatchValue>
-where
+pub struct LayerDb<
+    CasValue,
+    EncryptedSecretValue,
+    WorkspaceSnapshotValue,
+    RebaseBatchValue,
+    WorkspaceSnapshotChunkValue,
+> where
     CasValue: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
     EncryptedSecretValue: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
     WorkspaceSnapshotValue: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
     RebaseBatchValue: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+    WorkspaceSnapshotChunkValue: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
 {
     cas: CasDb<CasValue>,
     encrypted_secret: EncryptedSecretDb<EncryptedSecretValue>,
@@ -51,6 +58,7 @@ where
     func_run_log: FuncRunLogDb,
     rebase_batch: RebaseBatchDb<RebaseBatchValue>,
     workspace_snapshot: WorkspaceSnapshotDb<WorkspaceSnapshotValue>,
+    workspace_snapshot_chunk: WorkspaceSnapshotChunkDb<WorkspaceSnapshotChunkValue>,
     pg_pool: PgPool,
     nats_client: NatsClient,
     persister_client: PersisterClient,
@@ -58,13 +66,26 @@ where
     instance_id: Ulid,
 }
 
-impl<CasValue, EncryptedSecretValue, WorkspaceSnapshotValue, RebaseBatchValue>
-    LayerDb<CasValue, EncryptedSecretValue, WorkspaceSnapshotValue, RebaseBatchValue>
+impl<
+        CasValue,
+        EncryptedSecretValue,
+        WorkspaceSnapshotValue,
+        RebaseBatchValue,
+        WorkspaceSnapshotChunkValue,
+    >
+    LayerDb<
+        CasValue,
+        EncryptedSecretValue,
+        WorkspaceSnapshotValue,
+        RebaseBatchValue,
+        WorkspaceSnapshotChunkValue,
+    >
 where
     CasValue: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
     EncryptedSecretValue: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
     WorkspaceSnapshotValue: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
     RebaseBatchValue: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+    WorkspaceSnapshotChunkValue: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
 {
     #[instrument(name = "layer_db.init.from_config", level = "info", skip_all)]
     pub async fn from_config(
@@ -175,14 +196,17 @@ where
         )
         .await?;
 
+        // Snapshots are written as chunks and manifests, which live in the chunk cache below, so
+        // the snapshot cache only holds snapshots that were written before chunking. The two
+        // caches split the 45% that snapshots had before, giving most of it to the chunks.
         let snapshot_cache: Arc<LayerCache<Arc<WorkspaceSnapshotValue>>> = LayerCache::new(
             workspace_snapshot::CACHE_NAME,
             pg_pool.clone(),
             cache_config
                 .clone()
                 .with_name(workspace_snapshot::CACHE_NAME)
-                .with_memory_percentage(45)
-                .with_disk_percentage(45)
+                .with_memory_percentage(15)
+                .with_disk_percentage(15)
                 .with_path_join(workspace_snapshot::CACHE_NAME),
             compute_executor.clone(),
             tracker.clone(),
@@ -190,6 +214,22 @@ where
         )
         .await?;
 
+        let snapshot_chunk_cache: Arc<LayerCache<Arc<WorkspaceSnapshotChunkValue>>> =
+            LayerCache::new(
+                workspace_snapshot_chunk::CACHE_NAME,
+                pg_pool.clone(),
+                cache_config
+                    .clone()
+                    .with_name(workspace_snapshot_chunk::CACHE_NAME)
+                    .with_memory_percentage(30)
+                    .with_disk_percentage(30)
+                    .with_path_join(workspace_snapshot_chunk::CACHE_NAME),
+                compute_executor.clone(),
+                tracker.clone(),
+                token.clone(),
+            )
+            .await?;
+
         let cache_updates_task = CacheUpdatesTask::create(
             instance_id,
             &nats_client,
@@ -199,6 +239,7 @@ where
             func_run_log_cache.clone(),
             rebase_batch_cache.clone(),
             snapshot_cache.clone(),
+            snapshot_chunk_cache.clone(),
             token.clone(),
         )
         .await?;
@@ -221,6 +262,8 @@ where
         let func_run_log = FuncRunLogDb::new(func_run_log_cache, persister_client.clone());
         let workspace_snapshot = WorkspaceSnapshotDb::new(snapshot_cache, persister_client.clone());
         let rebase_batch = RebaseBatchDb::new(rebase_batch_cache, persister_client.clone());
+        let workspace_snapshot_chunk =
+            WorkspaceSnapshotChunkDb::new(snapshot_chunk_cache, persister_client.clone());
 
         let activity = ActivityClient::new(instance_id, nats_client.clone(), token.clone());
         let graceful_shutdown = LayerDbGracefulShutdown { tracker, token };
@@ -232,6 +275,7 @@ where
             func_run,
             func_run_log,
             workspace_snapshot,
+            workspace_snapshot_chunk,
             pg_pool,
             persister_client,
             nats_client,
@@ -278,6 +322,12 @@ where
         &self.workspace_snapshot
     }
 
+    pub fn workspace_snapshot_chunk(
+        &self,
+    ) -> &WorkspaceSnapshotChunkDb<WorkspaceSnapshotChunkValue> {
+        &self.workspace_snapshot_chunk
+    }
+
     pub fn instance_id(&self) -> Ulid {
         self.instance_id
     }
@@ -29,6 +29,7 @@ pub struct FuncRunDb {
     persister_client: PersisterClient,
     ready_many_for_workspace_id_query: String,
     get_last_qualification_for_attribute_value_id: String,
+    list_recent_for_attribute_value_id: String,
     list_action_history: String,
     get_last_action_by_action_id: String,
 }
@@ -47,6 +48,12 @@ impl FuncRunDb {
                    ORDER BY updated_at DESC
                    LIMIT 1",
             ),
+            list_recent_for_attribute_value_id: format!(
+                "SELECT value FROM {DBNAME}
+                   WHERE attribute_value_id = $2 AND workspace_id = $1
+                   ORDER BY updated_at DESC
+                   LIMIT $3",
+            ),
             list_action_history: format!(
                 "SELECT value FROM {DBNAME}
                    WHERE function_kind = 'Action' AND workspace_id = $1
@@ -144,6 +151,32 @@ impl FuncRunDb {
         Ok(None)
     }
 
+    /// The most recent runs for the attribute value, in any change set of the workspace, newest
+    /// first.
+    pub async fn list_recent_for_attribute_value_id(
+        &self,
+        workspace_id: WorkspacePk,
+        attribute_value_id: AttributeValueId,
+        limit: i64,
+    ) -> LayerDbResult<Vec<FuncRun>> {
+        let maybe_rows = self
+            .cache
+            .pg()
+            .query(
+                &self.list_recent_for_attribute_value_id,
+                &[&workspace_id, &attribute_value_id, &limit],
+            )
+            .await?;
+
+        let mut func_runs = vec![];
+        for row in maybe_rows.unwrap_or_default() {
+            let postcard_bytes: Vec<u8> = row.get("value");
+            func_runs.push(serialize::from_bytes(&postcard_bytes[..])?);
+        }
+
+        Ok(func_runs)
+    }
+
     pub async fn write(
         &self,
         value: Arc<FuncRun>,
@@ -151,6 +151,16 @@ where
         Ok(reader)
     }
 
+    /// Whether the snapshot is stored here, in memory or in durable storage, without reading it.
+    pub async fn exists(&self, key: &WorkspaceSnapshotAddress) -> LayerDbResult<bool> {
+        let key = key.to_string();
+        if self.cache.contains(&key) {
+            return Ok(true);
+        }
+
+        self.cache.pg().contains_key(&key).await
+    }
+
     /// Used for when we want to get the exact bytes we're storing for this
     /// snapshot, useful when converting an out of date snapshot into a new one
     #[instrument(
@@ -0,0 +1,177 @@
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use serde::{de::DeserializeOwned, Serialize};
+use si_events::{Actor, Tenancy, WorkspaceSnapshotAddress};
+use telemetry::prelude::*;
+
+use crate::{
+    error::LayerDbResult,
+    event::{LayeredEvent, LayeredEventKind},
+    layer_cache::LayerCache,
+    persister::{PersisterClient, PersisterStatusReader},
+};
+
+use super::serialize;
+
+pub const DBNAME: &str = "workspace_snapshot_chunks";
+pub const CACHE_NAME: &str = "workspace_snapshot_chunks";
+pub const PARTITION_KEY: &str = "workspace_snapshot_chunks";
+
+/// Content addressed pieces of workspace snapshots. A snapshot stored in chunks is a small
+/// manifest, kept in this db next to its chunks, that lists the addresses of the chunks, so
+/// chunks that did not change are shared between snapshots instead of being written again.
+///
+/// Manifests are evicted like snapshots once no change set points at them (see
+/// [`Self::evict`]). Chunks are shared by any number of manifests, so it is up to the caller to
+/// only evict the chunks of an evicted manifest that no remaining manifest refers to.
+#[derive(Debug, Clone)]
+pub struct WorkspaceSnapshotChunkDb<V>
+where
+    V: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    pub cache: Arc<LayerCache<Arc<V>>>,
+    persister_client: PersisterClient,
+}
+
+impl<V> WorkspaceSnapshotChunkDb<V>
+where
+    V: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    pub fn new(cache: Arc<LayerCache<Arc<V>>>, persister_client: PersisterClient) -> Self {
+        Self {
+            cache,
+            persister_client,
+        }
+    }
+
+    /// Writes the chunk. A chunk that is already in memory is still sent to the persister, since
+    /// being in memory does not mean it made it to durable storage; writing the same address
+    /// twice leaves the stored chunk as it was.
+    pub fn write(
+        &self,
+        value: Arc<V>,
+        tenancy: Tenancy,
+        actor: Actor,
+    ) -> LayerDbResult<(WorkspaceSnapshotAddress, PersisterStatusReader)> {
+        let postcard_value = serialize::to_vec(&value)?;
+
+        let key = WorkspaceSnapshotAddress::new(&postcard_value);
+        let cache_key: Arc<str> = key.to_string().into();
+
+        self.cache.insert(cache_key.clone(), value);
+
+        let event = LayeredEvent::new(
+            LayeredEventKind::SnapshotChunkWrite,
+            Arc::new(DBNAME.to_string()),
+            cache_key,
+            Arc::new(postcard_value),
+            Arc::new("workspace_snapshot_chunk".to_string()),
+            None,
+            tenancy,
+            actor,
+        );
+        let reader = self.persister_client.write_event(event)?;
+
+        Ok((key, reader))
+    }
+
+    /// Evicts a manifest, for when no change set points at its snapshot anymore, or a chunk that
+    /// no manifest refers to anymore. Evicting a chunk that a manifest still refers to leaves that
+    /// snapshot unreadable.
+    pub fn evict(
+        &self,
+        key: &WorkspaceSnapshotAddress,
+        tenancy: Tenancy,
+        actor: Actor,
+    ) -> LayerDbResult<PersisterStatusReader> {
+        let cache_key = key.to_string();
+        self.cache.remove_from_memory(&cache_key);
+
+        let event = LayeredEvent::new(
+            LayeredEventKind::SnapshotChunkEvict,
+            Arc::new(DBNAME.to_string()),
+            cache_key.into(),
+            Arc::new(Vec::new()),
+            Arc::new("workspace_snapshot_chunk".to_string()),
+            None,
+            tenancy,
+            actor,
+        );
+        let reader = self.persister_client.evict_event(event)?;
+
+        Ok(reader)
+    }
+
+    #[instrument(
+        name = "workspace_snapshot_chunk.read",
+        level = "debug",
+        skip_all,
+        fields(
+            si.workspace_snapshot_chunk.address = %key,
+        )
+    )]
+    pub async fn read(&self, key: &WorkspaceSnapshotAddress) -> LayerDbResult<Option<Arc<V>>> {
+        self.cache.get(key.to_string().into()).await
+    }
+
+    /// Reads the chunks that are in memory or in durable storage, fetching the ones that are
+    /// not in memory with a single query.
+    #[instrument(
+        name = "workspace_snapshot_chunk.read_many",
+        level = "debug",
+        skip_all,
+        fields(
+            si.workspace_snapshot_chunk.count = keys.len(),
+        )
+    )]
+    pub async fn read_many(
+        &self,
+        keys: &[WorkspaceSnapshotAddress],
+    ) -> LayerDbResult<HashMap<WorkspaceSnapshotAddress, Arc<V>>> {
+        self.cache.get_bulk(keys).await
+    }
+
+    /// Chunks and manifests are written right before the change set points at them, so they can
+    /// be far from durable storage when the snapshot is read. Like
+    /// [`WorkspaceSnapshotDb::read_wait_for_memory`](super::workspace_snapshot::WorkspaceSnapshotDb::read_wait_for_memory),
+    /// this waits for the value to show up in memory before falling back to the other layers.
+    #[instrument(
+        name = "workspace_snapshot_chunk.read_wait_for_memory",
+        level = "debug",
+        skip_all,
+        fields(
+            si.layer_cache.memory_cache.hit = Empty,
+            si.layer_cache.memory_cache.read_wait_ms = Empty,
+            si.layer_cache.memory_cache.retries = Empty,
+            si.workspace_snapshot_chunk.address = %key,
+        )
+    )]
+    pub async fn read_wait_for_memory(
+        &self,
+        key: &WorkspaceSnapshotAddress,
+    ) -> LayerDbResult<Option<Arc<V>>> {
+        let span = current_span_for_instrument_at!("debug");
+
+        let key: Arc<str> = key.to_string().into();
+        const MAX_TRIES: i32 = 2000;
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(1));
+        let mut tried = 0;
+        let read_wait = Instant::now();
+        while tried < MAX_TRIES {
+            if let Some(v) = self.cache.cache().get(&key).await {
+                span.record("si.layer_cache.memory_cache.hit", true);
+                span.record(
+                    "si.layer_cache.memory_cache.read_wait_ms",
+                    read_wait.elapsed().as_millis(),
+                );
+                span.record("si.layer_cache.memory_cache.retries", tried);
+                return Ok(Some(v));
+            }
+            tried += 1;
+            interval.tick().await;
+        }
+
+        span.record("si.layer_cache.memory_cache.hit", false);
+        self.cache.get(key.to_string().into()).await
+    }
+}
@@ -99,6 +99,8 @@ pub enum LayeredEventKind {
     Raw,
     RebaseBatchEvict,
     RebaseBatchWrite,
+    SnapshotChunkEvict,
+    SnapshotChunkWrite,
     SnapshotEvict,
     SnapshotWrite,
 }
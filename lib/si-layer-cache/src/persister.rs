@@ -328,6 +328,8 @@ impl PersistEventTask {
             | LayeredEventKind::Raw
             | LayeredEventKind::RebaseBatchEvict
             | LayeredEventKind::RebaseBatchWrite
+            | LayeredEventKind::SnapshotChunkEvict
+            | LayeredEventKind::SnapshotChunkWrite
             | LayeredEventKind::SnapshotEvict
             | LayeredEventKind::SnapshotWrite => {
                 pg_layer
@@ -14,7 +14,7 @@ use ulid::Ulid;
 
 use crate::integration_test::{setup_compute_executor, setup_nats_client, setup_pg_db};
 
-type TestLayerDb = LayerDb<Arc<String>, Arc<String>, String, String>;
+type TestLayerDb = LayerDb<Arc<String>, Arc<String>, String, String, String>;
 
 #[tokio::test]
 async fn subscribe_rebaser_requests_work_queue() {
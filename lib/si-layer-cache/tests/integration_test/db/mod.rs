@@ -2,3 +2,4 @@ mod cas;
 mod func_run;
 mod func_run_log;
 mod workspace_snapshot;
+mod workspace_snapshot_chunk;
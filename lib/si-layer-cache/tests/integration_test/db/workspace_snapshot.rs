@@ -9,7 +9,7 @@ use tokio_util::sync::CancellationToken;
 
 use crate::integration_test::{setup_compute_executor, setup_nats_client, setup_pg_db};
 
-type TestLayerDb = LayerDb<String, String, String, String>;
+type TestLayerDb = LayerDb<String, String, String, String, String>;
 
 #[tokio::test]
 async fn write_to_db() {
@@ -95,6 +95,12 @@ async fn evict_from_db() {
 
     let key_str: Arc<str> = key.to_string().into();
 
+    assert!(ldb
+        .workspace_snapshot()
+        .exists(&key)
+        .await
+        .expect("cannot check for snapshot"));
+
     let status = ldb
         .workspace_snapshot()
         .evict(
@@ -122,6 +128,11 @@ async fn evict_from_db() {
             .is_none(),
         "found item in database when it should have been evicted"
     );
+    assert!(!ldb
+        .workspace_snapshot()
+        .exists(&key)
+        .await
+        .expect("cannot check for snapshot"));
 }
 
 #[tokio::test]
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use si_events::{Actor, ChangeSetId, Tenancy, UserPk, WorkspacePk};
+use si_layer_cache::db::serialize;
+use si_layer_cache::hybrid_cache::CacheConfig;
+use si_layer_cache::{persister::PersistStatus, LayerDb};
+use tokio_util::sync::CancellationToken;
+
+use crate::integration_test::{setup_compute_executor, setup_nats_client, setup_pg_db};
+
+type TestLayerDb = LayerDb<String, String, String, String, String>;
+
+#[tokio::test]
+async fn write_to_db() {
+    let token = CancellationToken::new();
+
+    let (ldb, _): (TestLayerDb, _) = LayerDb::from_services(
+        setup_pg_db("workspace_snapshot_chunk_write_to_db").await,
+        setup_nats_client(Some("workspace_snapshot_chunk_write_to_db".to_string())).await,
+        setup_compute_executor(),
+        CacheConfig::default(),
+        token,
+    )
+    .await
+    .expect("cannot create layerdb");
+    ldb.pg_migrate().await.expect("migrate layer db");
+
+    let value: Arc<String> = Arc::new("jawbreaker".into());
+    let tenancy = Tenancy::new(WorkspacePk::new(), ChangeSetId::new());
+    let actor = Actor::User(UserPk::new());
+    let (key, status) = ldb
+        .workspace_snapshot_chunk()
+        .write(value.clone(), tenancy, actor)
+        .expect("failed to write to layerdb");
+
+    match status.get_status().await.expect("failed to get status") {
+        PersistStatus::Finished => {}
+        PersistStatus::Error(e) => panic!("Write failed; {e}"),
+    }
+
+    let key_str: Arc<str> = key.to_string().into();
+
+    // Are we in memory?
+    let in_memory = ldb
+        .workspace_snapshot_chunk()
+        .cache
+        .cache()
+        .get(&key_str)
+        .await;
+    assert_eq!(Some(value.clone()), in_memory);
+
+    // Are we in pg?
+    let in_pg_postcard = ldb
+        .workspace_snapshot_chunk()
+        .cache
+        .pg()
+        .get(&key_str)
+        .await
+        .expect("error getting data from pg")
+        .expect("no chunk in pg");
+    let in_pg: String =
+        serialize::from_bytes(&in_pg_postcard[..]).expect("cannot deserialize data");
+    assert_eq!(value.as_ref(), &in_pg);
+
+    // Writing the same chunk again is persisted again, without conflicting with the first write
+    let (same_key, same_status) = ldb
+        .workspace_snapshot_chunk()
+        .write(value.clone(), tenancy, actor)
+        .expect("failed to write to layerdb");
+    assert_eq!(key, same_key);
+    match same_status
+        .get_status()
+        .await
+        .expect("failed to get status")
+    {
+        PersistStatus::Finished => {}
+        PersistStatus::Error(e) => panic!("Second write failed; {e}"),
+    }
+
+    let read = ldb
+        .workspace_snapshot_chunk()
+        .read_wait_for_memory(&key)
+        .await
+        .expect("failed to read chunk");
+    assert_eq!(Some(value), read);
+}
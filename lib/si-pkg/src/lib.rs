@@ -1,13 +1,23 @@
 pub(crate) mod node;
 mod pkg;
+mod signature;
+mod source_dir;
 mod spec;
 mod workspace;
 
 pub use pkg::*;
+pub use signature::{
+    SiPkgSignature, SiPkgSignatureError, SiPkgSignatureResult, SiPkgSigningKey, SiPkgTrustStore,
+};
+pub use source_dir::{
+    PkgSourceDir, PkgSourceDirError, PkgSourceDirResult, PKG_SOURCE_DIR_FUNCS_DIR_NAME,
+    PKG_SOURCE_DIR_SPEC_FILE_NAME,
+};
 pub use spec::*;
 pub use workspace::{
     WorkspaceExport, WorkspaceExportChangeSetV0, WorkspaceExportContentV0,
-    WorkspaceExportMetadataV0,
+    WorkspaceExportMetadataV0, WorkspaceTemplate, WorkspaceTemplateContentV0,
+    WorkspaceTemplateMetadataV0, WorkspaceTemplateParameterV0,
 };
 
 #[cfg(test)]
@@ -163,4 +173,55 @@ mod tests {
 
         let _ = dbg!(props.lock().await);
     }
+
+    #[tokio::test]
+    async fn pkg_dependencies_round_trip() {
+        let spec = PkgSpec::builder()
+            .name("aws-ec2")
+            .version("2024-01-01")
+            .created_by("sally@systeminit.com")
+            .dependency(
+                DependencySpec::builder()
+                    .name("aws-credential")
+                    .version_constraint(">=2023-12-01")
+                    .build()
+                    .expect("able to build dependency"),
+            )
+            .build()
+            .expect("able to build pkg spec");
+        let without_dependencies = PkgSpec {
+            dependencies: vec![],
+            ..spec.clone()
+        };
+
+        let pkg = SiPkg::load_from_spec(spec.clone()).expect("failed to load spec");
+        let pkg_data = pkg.write_to_bytes().expect("failed to serialize pkg");
+        let read_pkg = SiPkg::load_from_bytes(&pkg_data).expect("failed to load pkg from bytes");
+
+        let dependencies = read_pkg.dependencies().expect("able to get dependencies");
+        assert_eq!(1, dependencies.len());
+        let dependency = dependencies.first().expect("has a dependency");
+        assert_eq!("aws-credential", dependency.name());
+        assert_eq!(">=2023-12-01", dependency.version_constraint());
+
+        assert_eq!(
+            spec.dependencies,
+            read_pkg
+                .to_spec()
+                .await
+                .expect("able to get spec")
+                .dependencies
+        );
+
+        let pkg_without_dependencies =
+            SiPkg::load_from_spec(without_dependencies).expect("failed to load spec");
+        assert!(pkg_without_dependencies
+            .dependencies()
+            .expect("able to get dependencies")
+            .is_empty());
+        assert_ne!(
+            pkg.hash().expect("able to hash"),
+            pkg_without_dependencies.hash().expect("able to hash")
+        );
+    }
 }
@@ -20,6 +20,7 @@ mod change_set;
 mod change_set_child;
 mod component;
 mod component_child;
+mod dependency;
 mod edge;
 mod func;
 mod func_argument;
@@ -47,6 +48,7 @@ pub(crate) use self::{
     change_set_child::{ChangeSetChild, ChangeSetChildNode},
     component::ComponentNode,
     component_child::ComponentChildNode,
+    dependency::DependencyNode,
     edge::EdgeNode,
     func::FuncNode,
     func_argument::FuncArgumentNode,
@@ -74,6 +76,7 @@ const NODE_KIND_CHANGE_SET: &str = "change_set";
 const NODE_KIND_CHANGE_SET_CHILD: &str = "change_set_child";
 const NODE_KIND_COMPONENT: &str = "component";
 const NODE_KIND_COMPONENT_CHILD: &str = "component_child";
+const NODE_KIND_DEPENDENCY: &str = "dependency";
 const NODE_KIND_EDGE: &str = "edge";
 const NODE_KIND_FUNC: &str = "func";
 const NODE_KIND_FUNC_ARGUMENT: &str = "func_argument";
@@ -147,6 +150,7 @@ pub enum PkgNode {
     ChangeSetChild(ChangeSetChildNode),
     Component(ComponentNode),
     ComponentChild(ComponentChildNode),
+    Dependency(DependencyNode),
     Edge(EdgeNode),
     Func(FuncNode),
     FuncArgument(FuncArgumentNode),
@@ -176,6 +180,7 @@ impl PkgNode {
     pub const CHANGE_SET_CHILD_KIND_STR: &'static str = NODE_KIND_CHANGE_SET_CHILD;
     pub const COMPONENT_KIND_STR: &'static str = NODE_KIND_COMPONENT;
     pub const COMPONENT_CHILD_KIND_STR: &'static str = NODE_KIND_COMPONENT_CHILD;
+    pub const DEPENDENCY_KIND_STR: &'static str = NODE_KIND_DEPENDENCY;
     pub const NODE_KIND_EDGE_STR: &'static str = NODE_KIND_EDGE;
     pub const FUNC_KIND_STR: &'static str = NODE_KIND_FUNC;
     pub const FUNC_ARGUMENT_KIND_STR: &'static str = NODE_KIND_FUNC_ARGUMENT;
@@ -204,6 +209,7 @@ impl PkgNode {
             Self::ChangeSetChild(_) => NODE_KIND_CHANGE_SET_CHILD,
             Self::Component(_) => NODE_KIND_COMPONENT,
             Self::ComponentChild(_) => NODE_KIND_COMPONENT_CHILD,
+            Self::Dependency(_) => NODE_KIND_DEPENDENCY,
             Self::Edge(_) => NODE_KIND_EDGE,
             Self::Func(_) => NODE_KIND_FUNC,
             Self::FuncArgument(_) => NODE_KIND_FUNC_ARGUMENT,
@@ -237,6 +243,7 @@ impl NameStr for PkgNode {
             Self::ChangeSetChild(node) => node.name(),
             Self::Component(node) => node.name(),
             Self::ComponentChild(node) => node.name(),
+            Self::Dependency(node) => node.name(),
             Self::Edge(_) => NODE_KIND_EDGE,
             Self::Func(node) => node.name(),
             Self::FuncArgument(node) => node.name(),
@@ -273,6 +280,7 @@ impl WriteBytes for PkgNode {
             Self::ChangeSetChild(node) => node.write_bytes(writer)?,
             Self::Component(node) => node.write_bytes(writer)?,
             Self::ComponentChild(node) => node.write_bytes(writer)?,
+            Self::Dependency(node) => node.write_bytes(writer)?,
             Self::Edge(node) => node.write_bytes(writer)?,
             Self::Func(node) => node.write_bytes(writer)?,
             Self::FuncArgument(node) => node.write_bytes(writer)?,
@@ -323,6 +331,7 @@ impl ReadBytes for PkgNode {
             NODE_KIND_COMPONENT_CHILD => {
                 ComponentChildNode::read_bytes(reader)?.map(Self::ComponentChild)
             }
+            NODE_KIND_DEPENDENCY => DependencyNode::read_bytes(reader)?.map(Self::Dependency),
             NODE_KIND_EDGE => EdgeNode::read_bytes(reader)?.map(Self::Edge),
             NODE_KIND_FUNC => FuncNode::read_bytes(reader)?.map(Self::Func),
             NODE_KIND_FUNC_ARGUMENT => {
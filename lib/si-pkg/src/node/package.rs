@@ -117,12 +117,23 @@ impl NodeChild for PkgSpec {
                 workspace_name: self.workspace_name.to_owned(),
             }),
             match self.kind {
-                SiPkgKind::Module => vec![
-                    Box::new(PackageCategory::Schemas(self.schemas.clone()))
-                        as Box<dyn NodeChild<NodeType = Self::NodeType>>,
-                    Box::new(PackageCategory::Funcs(self.funcs.clone()))
-                        as Box<dyn NodeChild<NodeType = Self::NodeType>>,
-                ],
+                SiPkgKind::Module => {
+                    let mut children = vec![
+                        Box::new(PackageCategory::Schemas(self.schemas.clone()))
+                            as Box<dyn NodeChild<NodeType = Self::NodeType>>,
+                        Box::new(PackageCategory::Funcs(self.funcs.clone()))
+                            as Box<dyn NodeChild<NodeType = Self::NodeType>>,
+                    ];
+                    // Only packages that declare dependencies get the category, so the hashes of
+                    // all other packages are unchanged
+                    if !self.dependencies.is_empty() {
+                        children.push(Box::new(PackageCategory::Dependencies(
+                            self.dependencies.clone(),
+                        ))
+                            as Box<dyn NodeChild<NodeType = Self::NodeType>>);
+                    }
+                    children
+                }
                 SiPkgKind::WorkspaceBackup => {
                     vec![
                         Box::new(PackageCategory::ChangeSets(self.change_sets.clone()))
@@ -17,6 +17,7 @@ mod attribute_value;
 mod auth_func;
 mod change_set;
 mod component;
+mod dependency;
 mod edge;
 mod func;
 mod leaf_function;
@@ -32,13 +33,15 @@ mod variant;
 
 pub use {
     action_func::*, attr_func_input::*, attribute_value::*, auth_func::*, change_set::*,
-    component::*, edge::*, func::*, leaf_function::*, management_func::*, map_key_func::*,
-    position::*, prop::*, root_prop_func::*, schema::*, si_prop_func::*, socket::*, variant::*,
+    component::*, dependency::*, edge::*, func::*, leaf_function::*, management_func::*,
+    map_key_func::*, position::*, prop::*, root_prop_func::*, schema::*, si_prop_func::*,
+    socket::*, variant::*,
 };
 
 use crate::{
     node::{CategoryNode, PkgNode},
-    spec::{FuncSpec, PkgSpec, SchemaVariantSpecPropRoot, SpecError},
+    signature::SiPkgSignature,
+    spec::{DependencySpec, FuncSpec, PkgSpec, SchemaVariantSpecPropRoot, SpecError},
 };
 
 #[remain::sorted]
@@ -101,6 +104,7 @@ impl From<Infallible> for SiPkgError {
 #[derive(Clone, Debug)]
 pub struct SiPkg {
     tree: Arc<ObjectTree<PkgNode>>,
+    signature: Option<SiPkgSignature>,
 }
 
 impl SiPkg {
@@ -114,6 +118,7 @@ impl SiPkg {
 
         Ok(Self {
             tree: Arc::new(tree),
+            signature: None,
         })
     }
 
@@ -127,9 +132,21 @@ impl SiPkg {
 
         Ok(Self {
             tree: Arc::new(tree),
+            signature: None,
         })
     }
 
+    /// Attaches a detached signature over the root hash of this package. The signature is not
+    /// written into the package bytes.
+    pub fn with_signature(mut self, signature: Option<SiPkgSignature>) -> Self {
+        self.signature = signature;
+        self
+    }
+
+    pub fn signature(&self) -> Option<&SiPkgSignature> {
+        self.signature.as_ref()
+    }
+
     pub fn write_to_bytes(&self) -> PkgResult<Vec<u8>> {
         Ok(TarWriter::new(&self.tree)?.bytes())
     }
@@ -137,7 +154,10 @@ impl SiPkg {
     pub fn metadata(&self) -> PkgResult<SiPkgMetadata> {
         let (graph, root_idx) = self.as_petgraph();
 
-        SiPkgMetadata::from_graph(graph, root_idx)
+        let mut metadata = SiPkgMetadata::from_graph(graph, root_idx)?;
+        metadata.signature = self.signature.clone();
+
+        Ok(metadata)
     }
 
     pub fn hash(&self) -> PkgResult<Hash> {
@@ -179,6 +199,20 @@ impl SiPkg {
         Ok(schemas)
     }
 
+    /// The modules this package needs installed before it can be installed.
+    pub fn dependencies(&self) -> PkgResult<Vec<SiPkgDependency>> {
+        let (graph, root_idx) = self.as_petgraph();
+
+        let node_idxs = category_node_idxs(CategoryNode::Dependencies, graph, root_idx)?;
+        let mut dependencies = Vec::with_capacity(node_idxs.len());
+
+        for node_idx in node_idxs {
+            dependencies.push(SiPkgDependency::from_graph(graph, node_idx)?);
+        }
+
+        Ok(dependencies)
+    }
+
     pub fn change_sets(&self) -> PkgResult<Vec<SiPkgChangeSet>> {
         let (graph, root_idx) = self.as_petgraph();
 
@@ -242,6 +276,10 @@ impl SiPkg {
             builder.schema(schema.to_spec().await?);
         }
 
+        for dependency in self.dependencies()? {
+            builder.dependency(DependencySpec::try_from(dependency)?);
+        }
+
         if let SiPkgKind::WorkspaceBackup = metadata.kind() {
             if let Some(default_change_set) = metadata.default_change_set() {
                 builder.default_change_set(default_change_set);
@@ -366,6 +404,7 @@ pub struct SiPkgMetadata {
     workspace_pk: Option<String>,
     workspace_name: Option<String>,
     hash: Hash,
+    signature: Option<SiPkgSignature>,
 }
 
 impl SiPkgMetadata {
@@ -392,6 +431,7 @@ impl SiPkgMetadata {
             workspace_pk: metadata_node.workspace_pk,
             workspace_name: metadata_node.workspace_name,
             hash: metadata_hashed_node.hash(),
+            signature: None,
         })
     }
 
@@ -434,4 +474,8 @@ impl SiPkgMetadata {
     pub fn hash(&self) -> Hash {
         self.hash
     }
+
+    pub fn signature(&self) -> Option<&SiPkgSignature> {
+        self.signature.as_ref()
+    }
 }
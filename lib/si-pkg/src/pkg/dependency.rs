@@ -0,0 +1,66 @@
+use object_tree::{Hash, HashedNode};
+use petgraph::prelude::*;
+
+use super::{PkgResult, SiPkgError, Source};
+
+use crate::{node::PkgNode, DependencySpec};
+
+#[derive(Clone, Debug)]
+pub struct SiPkgDependency<'a> {
+    name: String,
+    version_constraint: String,
+    hash: Hash,
+    source: Source<'a>,
+}
+
+impl<'a> SiPkgDependency<'a> {
+    pub fn from_graph(
+        graph: &'a Graph<HashedNode<PkgNode>, ()>,
+        node_idx: NodeIndex,
+    ) -> PkgResult<Self> {
+        let hashed_node = &graph[node_idx];
+        let node = match hashed_node.inner() {
+            PkgNode::Dependency(node) => node.clone(),
+            unexpected => {
+                return Err(SiPkgError::UnexpectedPkgNodeType(
+                    PkgNode::DEPENDENCY_KIND_STR,
+                    unexpected.node_kind_str(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            name: node.name,
+            version_constraint: node.version_constraint,
+            hash: hashed_node.hash(),
+            source: Source::new(graph, node_idx),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn version_constraint(&self) -> &str {
+        self.version_constraint.as_str()
+    }
+
+    pub fn hash(&self) -> Hash {
+        self.hash
+    }
+
+    pub fn source(&self) -> &Source<'a> {
+        &self.source
+    }
+}
+
+impl<'a> TryFrom<SiPkgDependency<'a>> for DependencySpec {
+    type Error = SiPkgError;
+
+    fn try_from(value: SiPkgDependency<'a>) -> Result<Self, Self::Error> {
+        Ok(DependencySpec::builder()
+            .name(value.name)
+            .version_constraint(value.version_constraint)
+            .build()?)
+    }
+}
@@ -0,0 +1,203 @@
+//! Detached ed25519 signatures over the root hash of an [`SiPkg`](crate::SiPkg).
+//!
+//! A signature is not part of the package tree (signing the tree would change its hash), so it
+//! travels alongside the package bytes: module-index stores it next to the package and returns it
+//! with the module details, and whoever loads the package attaches it with
+//! [`SiPkg::with_signature`](crate::SiPkg::with_signature).
+
+use base64::{engine::general_purpose, Engine};
+use object_tree::Hash;
+use serde::{Deserialize, Serialize};
+use sodiumoxide::crypto::sign;
+use thiserror::Error;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum SiPkgSignatureError {
+    #[error("base64 decode error: {0}")]
+    Base64Decode(#[from] base64::DecodeError),
+    #[error("invalid public key")]
+    InvalidPublicKey,
+    #[error("invalid secret key")]
+    InvalidSecretKey,
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("package with hash {0} is not signed")]
+    NotSigned(Hash),
+    #[error("signature for package with hash {0} does not match")]
+    SignatureMismatch(Hash),
+    #[error("package with hash {0} is signed by untrusted public key {1}")]
+    UntrustedPublicKey(Hash, String),
+}
+
+pub type SiPkgSignatureResult<T> = Result<T, SiPkgSignatureError>;
+
+/// A detached signature over a package root hash, along with the public key that made it. Both
+/// are base64 encoded.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiPkgSignature {
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl SiPkgSignature {
+    /// Checks that this signature was made over the given package hash by its public key. This
+    /// does not say whether the public key is trusted; see [`SiPkgTrustStore::verify`].
+    pub fn verify(&self, hash: Hash) -> SiPkgSignatureResult<()> {
+        let public_key = decode_public_key(&self.public_key)?;
+        let signature_bytes = general_purpose::STANDARD.decode(&self.signature)?;
+        let signature = sign::Signature::try_from(signature_bytes.as_slice())
+            .map_err(|_| SiPkgSignatureError::InvalidSignature)?;
+
+        if sign::verify_detached(&signature, signed_message(hash).as_bytes(), &public_key) {
+            Ok(())
+        } else {
+            Err(SiPkgSignatureError::SignatureMismatch(hash))
+        }
+    }
+}
+
+/// An ed25519 key used by package authors to sign packages.
+#[derive(Clone)]
+pub struct SiPkgSigningKey {
+    secret_key: sign::SecretKey,
+}
+
+impl SiPkgSigningKey {
+    /// Generates a new random signing key.
+    pub fn generate() -> Self {
+        let (_public_key, secret_key) = sign::gen_keypair();
+        Self { secret_key }
+    }
+
+    /// Loads a signing key from its base64 encoded secret key.
+    pub fn from_base64(secret_key: impl AsRef<str>) -> SiPkgSignatureResult<Self> {
+        let bytes = general_purpose::STANDARD.decode(secret_key.as_ref())?;
+        let secret_key =
+            sign::SecretKey::from_slice(&bytes).ok_or(SiPkgSignatureError::InvalidSecretKey)?;
+        Ok(Self { secret_key })
+    }
+
+    /// The base64 encoded secret key.
+    pub fn to_base64(&self) -> String {
+        general_purpose::STANDARD.encode(self.secret_key.as_ref())
+    }
+
+    /// The base64 encoded public key, suitable for adding to a [`SiPkgTrustStore`].
+    pub fn public_key(&self) -> String {
+        general_purpose::STANDARD.encode(self.secret_key.public_key().as_ref())
+    }
+
+    /// Signs the package root hash.
+    pub fn sign(&self, hash: Hash) -> SiPkgSignature {
+        let signature = sign::sign_detached(signed_message(hash).as_bytes(), &self.secret_key);
+
+        SiPkgSignature {
+            public_key: self.public_key(),
+            signature: general_purpose::STANDARD.encode(signature.to_bytes()),
+        }
+    }
+}
+
+impl std::fmt::Debug for SiPkgSigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SiPkgSigningKey")
+            .field("public_key", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The set of public keys whose package signatures are trusted.
+#[derive(Clone, Debug, Default)]
+pub struct SiPkgTrustStore {
+    public_keys: Vec<sign::PublicKey>,
+}
+
+impl SiPkgTrustStore {
+    /// Builds a trust store from base64 encoded public keys.
+    pub fn from_public_keys<I, S>(public_keys: I) -> SiPkgSignatureResult<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let public_keys = public_keys
+            .into_iter()
+            .map(|public_key| decode_public_key(public_key.as_ref()))
+            .collect::<SiPkgSignatureResult<_>>()?;
+
+        Ok(Self { public_keys })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.public_keys.is_empty()
+    }
+
+    /// Checks that the package with the given hash carries a valid signature made by a trusted
+    /// public key.
+    pub fn verify(
+        &self,
+        hash: Hash,
+        signature: Option<&SiPkgSignature>,
+    ) -> SiPkgSignatureResult<()> {
+        let signature = signature.ok_or(SiPkgSignatureError::NotSigned(hash))?;
+        let public_key = decode_public_key(&signature.public_key)?;
+
+        if !self.public_keys.contains(&public_key) {
+            return Err(SiPkgSignatureError::UntrustedPublicKey(
+                hash,
+                signature.public_key.to_owned(),
+            ));
+        }
+
+        signature.verify(hash)
+    }
+}
+
+fn decode_public_key(public_key: &str) -> SiPkgSignatureResult<sign::PublicKey> {
+    let bytes = general_purpose::STANDARD.decode(public_key)?;
+    sign::PublicKey::from_slice(&bytes).ok_or(SiPkgSignatureError::InvalidPublicKey)
+}
+
+/// The message that is signed: the hex encoded root hash, so that a signature can be produced
+/// and checked from the hash alone.
+fn signed_message(hash: Hash) -> String {
+    hash.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_and_verify() {
+        sodiumoxide::init().expect("crypto failed to init");
+
+        let key = SiPkgSigningKey::generate();
+        let hash = Hash::new(b"package");
+        let signature = key.sign(hash);
+
+        let trust_store =
+            SiPkgTrustStore::from_public_keys([key.public_key()]).expect("invalid public key");
+        trust_store
+            .verify(hash, Some(&signature))
+            .expect("signature should verify");
+
+        assert!(matches!(
+            trust_store.verify(Hash::new(b"other package"), Some(&signature)),
+            Err(SiPkgSignatureError::SignatureMismatch(_))
+        ));
+        assert!(matches!(
+            trust_store.verify(hash, None),
+            Err(SiPkgSignatureError::NotSigned(_))
+        ));
+
+        let untrusted =
+            SiPkgTrustStore::from_public_keys([SiPkgSigningKey::generate().public_key()])
+                .expect("invalid public key");
+        assert!(matches!(
+            untrusted.verify(hash, Some(&signature)),
+            Err(SiPkgSignatureError::UntrustedPublicKey(_, _))
+        ));
+    }
+}
@@ -0,0 +1,241 @@
+//! An editable, reviewable form of a package on disk: the [`PkgSpec`] as pretty printed JSON, with
+//! the code of each func pulled out into its own `.ts` file.
+//!
+//! ```text
+//! my-package/
+//!   pkg.json
+//!   funcs/
+//!     awsEc2Create.ts
+//!     awsEc2Refresh.ts
+//! ```
+//!
+//! In `pkg.json` the `codeBase64` of every func that has code is left empty; the code is read
+//! back from the func's file when the directory is loaded.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use base64::{
+    alphabet,
+    engine::{general_purpose, DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use thiserror::Error;
+
+use crate::PkgSpec;
+
+pub const PKG_SOURCE_DIR_SPEC_FILE_NAME: &str = "pkg.json";
+pub const PKG_SOURCE_DIR_FUNCS_DIR_NAME: &str = "funcs";
+const FUNC_CODE_EXTENSION: &str = "ts";
+
+/// Func code is written without padding, but packages built elsewhere may have padded it.
+const CODE_DECODE_ENGINE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum PkgSourceDirError {
+    #[error("base64 decode error for func {0}: {1}")]
+    Base64Decode(String, #[source] base64::DecodeError),
+    #[error("code for func {0} is not valid utf-8")]
+    FuncCodeNotUtf8(String),
+    #[error("io error at {0}: {1}")]
+    Io(PathBuf, #[source] std::io::Error),
+    #[error("json error at {0}: {1}")]
+    Json(PathBuf, #[source] serde_json::Error),
+}
+
+pub type PkgSourceDirResult<T> = Result<T, PkgSourceDirError>;
+
+/// Reads and writes the directory form of a [`PkgSpec`].
+#[derive(Clone, Copy, Debug)]
+pub struct PkgSourceDir;
+
+impl PkgSourceDir {
+    /// Writes the spec into `path`, creating it if needed. Existing files for the package are
+    /// overwritten.
+    pub async fn write(spec: &PkgSpec, path: impl AsRef<Path>) -> PkgSourceDirResult<()> {
+        let path = path.as_ref();
+        let funcs_path = path.join(PKG_SOURCE_DIR_FUNCS_DIR_NAME);
+        create_dir_all(&funcs_path).await?;
+
+        let mut spec = spec.clone();
+        let file_names = func_file_names(&spec);
+        for (func, file_name) in spec.funcs.iter_mut().zip(file_names) {
+            let (Some(data), Some(file_name)) = (func.data.as_mut(), file_name) else {
+                continue;
+            };
+
+            let code = CODE_DECODE_ENGINE
+                .decode(&data.code_base64)
+                .map_err(|err| PkgSourceDirError::Base64Decode(func.name.to_owned(), err))?;
+            let code = String::from_utf8(code)
+                .map_err(|_| PkgSourceDirError::FuncCodeNotUtf8(func.name.to_owned()))?;
+            write(&funcs_path.join(file_name), code.as_bytes()).await?;
+
+            data.code_base64 = String::new();
+        }
+
+        let spec_path = path.join(PKG_SOURCE_DIR_SPEC_FILE_NAME);
+        let spec_json = serde_json::to_vec_pretty(&spec)
+            .map_err(|err| PkgSourceDirError::Json(spec_path.to_owned(), err))?;
+        write(&spec_path, &spec_json).await
+    }
+
+    /// Reads the spec back from `path`. A func whose code file is missing keeps whatever
+    /// `codeBase64` is in `pkg.json`.
+    pub async fn read(path: impl AsRef<Path>) -> PkgSourceDirResult<PkgSpec> {
+        let path = path.as_ref();
+        let funcs_path = path.join(PKG_SOURCE_DIR_FUNCS_DIR_NAME);
+
+        let spec_path = path.join(PKG_SOURCE_DIR_SPEC_FILE_NAME);
+        let spec_json = tokio::fs::read(&spec_path)
+            .await
+            .map_err(|err| PkgSourceDirError::Io(spec_path.to_owned(), err))?;
+        let mut spec: PkgSpec = serde_json::from_slice(&spec_json)
+            .map_err(|err| PkgSourceDirError::Json(spec_path.to_owned(), err))?;
+
+        let file_names = func_file_names(&spec);
+        for (func, file_name) in spec.funcs.iter_mut().zip(file_names) {
+            let (Some(data), Some(file_name)) = (func.data.as_mut(), file_name) else {
+                continue;
+            };
+
+            let code_path = funcs_path.join(file_name);
+            match tokio::fs::read(&code_path).await {
+                Ok(code) => data.code_base64 = general_purpose::STANDARD_NO_PAD.encode(code),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(PkgSourceDirError::Io(code_path, err)),
+            }
+        }
+
+        Ok(spec)
+    }
+}
+
+/// The code file name for each func in the spec, in order, or `None` for funcs without code.
+/// Names come from the func name; funcs whose names collide are told apart by unique id.
+fn func_file_names(spec: &PkgSpec) -> Vec<Option<String>> {
+    let mut used = HashSet::new();
+
+    spec.funcs
+        .iter()
+        .map(|func| {
+            func.data.as_ref()?;
+
+            let mut stem = sanitize_file_stem(&func.name);
+            if !used.insert(stem.to_owned()) {
+                stem = format!("{stem}-{}", sanitize_file_stem(&func.unique_id));
+                used.insert(stem.to_owned());
+            }
+
+            Some(format!("{stem}.{FUNC_CODE_EXTENSION}"))
+        })
+        .collect()
+}
+
+fn sanitize_file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if stem.is_empty() {
+        "func".to_owned()
+    } else {
+        stem
+    }
+}
+
+async fn create_dir_all(path: &Path) -> PkgSourceDirResult<()> {
+    tokio::fs::create_dir_all(path)
+        .await
+        .map_err(|err| PkgSourceDirError::Io(path.to_owned(), err))
+}
+
+async fn write(path: &Path, contents: &[u8]) -> PkgSourceDirResult<()> {
+    tokio::fs::write(path, contents)
+        .await
+        .map_err(|err| PkgSourceDirError::Io(path.to_owned(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuncSpec, FuncSpecBackendKind, FuncSpecBackendResponseType, FuncSpecData};
+
+    use super::*;
+
+    fn func(name: &str, unique_id: &str, code: &str) -> FuncSpec {
+        FuncSpec::builder()
+            .name(name)
+            .unique_id(unique_id)
+            .data(
+                FuncSpecData::builder()
+                    .name(name)
+                    .handler("main")
+                    .code_plaintext(code)
+                    .backend_kind(FuncSpecBackendKind::JsAttribute)
+                    .response_type(FuncSpecBackendResponseType::String)
+                    .build()
+                    .expect("able to build func data"),
+            )
+            .build()
+            .expect("able to build func")
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let dir = tempfile::tempdir().expect("able to create temp dir");
+
+        let spec = PkgSpec::builder()
+            .name("pkg")
+            .version("1")
+            .created_by("sally@systeminit.com")
+            .func(func(
+                "si:setString",
+                "one",
+                "function main() { return 'one'; }",
+            ))
+            .func(func(
+                "si:setString",
+                "two",
+                "function main() { return 'two'; }",
+            ))
+            .build()
+            .expect("able to build pkg spec");
+
+        PkgSourceDir::write(&spec, dir.path())
+            .await
+            .expect("able to write source dir");
+
+        let funcs_path = dir.path().join(PKG_SOURCE_DIR_FUNCS_DIR_NAME);
+        assert_eq!(
+            "function main() { return 'one'; }",
+            std::fs::read_to_string(funcs_path.join("si_setString.ts"))
+                .expect("able to read func code")
+        );
+        assert_eq!(
+            "function main() { return 'two'; }",
+            std::fs::read_to_string(funcs_path.join("si_setString-two.ts"))
+                .expect("able to read func code")
+        );
+
+        let read_spec = PkgSourceDir::read(dir.path())
+            .await
+            .expect("able to read source dir");
+        assert_eq!(
+            serde_json::to_value(&spec).expect("able to serialize"),
+            serde_json::to_value(&read_spec).expect("able to serialize")
+        );
+    }
+}
@@ -9,6 +9,8 @@ mod attribute_value;
 mod authentication_func;
 mod change_set;
 mod component;
+mod dependency;
+mod diff;
 mod edge;
 mod func;
 mod leaf_function;
@@ -20,12 +22,14 @@ mod root_prop_func;
 mod schema;
 mod si_prop_func;
 mod socket;
+mod validate;
 mod variant;
 
 pub use {
     action_func::*, attr_func_input::*, attribute_value::*, authentication_func::*, change_set::*,
-    component::*, edge::*, func::*, leaf_function::*, management_func::*, map_key_func::*,
-    position::*, prop::*, root_prop_func::*, schema::*, si_prop_func::*, socket::*, variant::*,
+    component::*, dependency::*, diff::*, edge::*, func::*, leaf_function::*, management_func::*,
+    map_key_func::*, position::*, prop::*, root_prop_func::*, schema::*, si_prop_func::*,
+    socket::*, validate::*, variant::*,
 };
 
 use super::SiPkgKind;
@@ -66,6 +70,10 @@ pub struct PkgSpec {
     #[builder(setter(each(name = "change_set", into)), default)]
     #[serde(default)]
     pub change_sets: Vec<ChangeSetSpec>,
+
+    #[builder(setter(each(name = "dependency", into)), default)]
+    #[serde(default)]
+    pub dependencies: Vec<DependencySpec>,
 }
 
 impl PkgSpec {
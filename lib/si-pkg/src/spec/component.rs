@@ -20,6 +20,13 @@ pub enum ComponentSpecVariant {
     WorkspaceVariant {
         variant_unique_id: String,
     },
+    /// A variant found by the id of its schema (the module index schema id when there is one)
+    /// and its version, for components copied between workspaces.
+    SchemaVariant {
+        schema_id: String,
+        schema_name: String,
+        variant_version: String,
+    },
 }
 
 #[derive(Builder, Clone, Debug, Deserialize, Serialize)]
@@ -0,0 +1,161 @@
+use std::cmp::Ordering;
+
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+use super::SpecError;
+
+/// Matches any version of a dependency.
+pub const ANY_VERSION: &str = "*";
+
+/// A module that has to be installed before the package that depends on it.
+///
+/// The version constraint is `*` for any version, a bare version (or `=version`) for exactly that
+/// version, or a comma separated list of comparisons (`>=`, `>`, `<=`, `<`), all of which must
+/// hold. Versions are compared part by part, splitting on `.`, `-` and `+`, with parts that are
+/// both numbers compared as numbers.
+#[derive(Builder, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[builder(build_fn(error = "SpecError"))]
+pub struct DependencySpec {
+    #[builder(setter(into))]
+    pub name: String,
+    #[builder(setter(into), default = "ANY_VERSION.to_owned()")]
+    #[serde(default = "any_version")]
+    pub version_constraint: String,
+}
+
+fn any_version() -> String {
+    ANY_VERSION.to_owned()
+}
+
+impl DependencySpec {
+    pub fn builder() -> DependencySpecBuilder {
+        DependencySpecBuilder::default()
+    }
+
+    /// Whether a module with this name and version satisfies the dependency.
+    pub fn matches(&self, name: &str, version: &str) -> bool {
+        self.name == name && version_matches(&self.version_constraint, version)
+    }
+
+    /// Whether the version constraint can be parsed at all.
+    pub fn version_constraint_is_valid(&self) -> bool {
+        parse_constraint(&self.version_constraint).is_some()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Comparison {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+fn parse_constraint(constraint: &str) -> Option<Vec<(Comparison, &str)>> {
+    let constraint = constraint.trim();
+    if constraint.is_empty() || constraint == ANY_VERSION {
+        return Some(vec![]);
+    }
+
+    constraint
+        .split(',')
+        .map(|part| {
+            let part = part.trim();
+            let (comparison, version) = if let Some(version) = part.strip_prefix(">=") {
+                (Comparison::Gte, version)
+            } else if let Some(version) = part.strip_prefix("<=") {
+                (Comparison::Lte, version)
+            } else if let Some(version) = part.strip_prefix('>') {
+                (Comparison::Gt, version)
+            } else if let Some(version) = part.strip_prefix('<') {
+                (Comparison::Lt, version)
+            } else if let Some(version) = part.strip_prefix('=') {
+                (Comparison::Eq, version)
+            } else {
+                (Comparison::Eq, part)
+            };
+
+            let version = version.trim();
+            if version.is_empty() || version.contains(ANY_VERSION) {
+                None
+            } else {
+                Some((comparison, version))
+            }
+        })
+        .collect()
+}
+
+fn version_matches(constraint: &str, version: &str) -> bool {
+    let Some(comparisons) = parse_constraint(constraint) else {
+        return false;
+    };
+
+    comparisons.into_iter().all(|(comparison, wanted)| {
+        let ordering = compare_versions(version, wanted);
+        match comparison {
+            Comparison::Eq => ordering == Ordering::Equal,
+            Comparison::Gt => ordering == Ordering::Greater,
+            Comparison::Gte => ordering != Ordering::Less,
+            Comparison::Lt => ordering == Ordering::Less,
+            Comparison::Lte => ordering != Ordering::Greater,
+        }
+    })
+}
+
+fn compare_versions(left: &str, right: &str) -> Ordering {
+    let split = |version: &str| {
+        version
+            .trim()
+            .split(['.', '-', '+'])
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+    };
+    let (left, right) = (split(left), split(right));
+
+    for (left_part, right_part) in left.iter().zip(right.iter()) {
+        let ordering = match (left_part.parse::<u64>(), right_part.parse::<u64>()) {
+            (Ok(left_number), Ok(right_number)) => left_number.cmp(&right_number),
+            _ => left_part.cmp(right_part),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    left.len().cmp(&right.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependency(version_constraint: &str) -> DependencySpec {
+        DependencySpec::builder()
+            .name("aws-credential")
+            .version_constraint(version_constraint)
+            .build()
+            .expect("able to build dependency")
+    }
+
+    #[test]
+    fn version_constraints() {
+        assert!(dependency("*").matches("aws-credential", "2024-01-01"));
+        assert!(!dependency("*").matches("aws-region", "2024-01-01"));
+
+        assert!(dependency("2024-01-01").matches("aws-credential", "2024-01-01"));
+        assert!(dependency("=2024-01-01").matches("aws-credential", "2024-01-01"));
+        assert!(!dependency("2024-01-01").matches("aws-credential", "2024-01-02"));
+
+        assert!(dependency(">=1.2").matches("aws-credential", "1.10"));
+        assert!(!dependency(">=1.2").matches("aws-credential", "1.1.9"));
+        assert!(dependency(">=1.2, <2").matches("aws-credential", "1.2.0"));
+        assert!(!dependency(">=1.2, <2").matches("aws-credential", "2.0"));
+        assert!(dependency(">20240101").matches("aws-credential", "20240530171205.472516000"));
+
+        assert!(!dependency(">=").version_constraint_is_valid());
+        assert!(!dependency(">=").matches("aws-credential", "1"));
+    }
+}
@@ -0,0 +1,758 @@
+//! Structural differences between two versions of a [`PkgSpec`], so that the impact of an upgrade
+//! can be shown before it is made.
+
+use std::collections::{BTreeMap, HashMap};
+
+use base64::{engine::general_purpose, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    FuncSpec, FuncSpecData, PkgSpec, PropSpecKind, SchemaSpec, SchemaVariantSpec, SocketSpec,
+    SocketSpecKind, PROP_PATH_SEPARATOR,
+};
+
+const DIFF_PATH_SEPARATOR: &str = "/";
+const NEWLINE: &str = "\n";
+
+/// The structured changes between two [`PkgSpecs`](PkgSpec), as produced by [`PkgSpec::diff`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PkgSpecDiff {
+    pub schemas: Vec<SchemaSpecDiff>,
+    pub funcs: Vec<FuncSpecDiff>,
+}
+
+impl PkgSpecDiff {
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty() && self.funcs.is_empty()
+    }
+}
+
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SpecChange {
+    Added,
+    Changed,
+    Removed,
+}
+
+/// The changes to a schema. For a changed schema, the props, sockets and bindings are those of
+/// the default variant on each side.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaSpecDiff {
+    pub name: String,
+    pub change: SpecChange,
+    pub props: Vec<PropSpecDiff>,
+    pub sockets: Vec<SocketSpecDiff>,
+    pub bindings: Vec<BindingSpecDiff>,
+}
+
+#[remain::sorted]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "camelCase")]
+pub enum PropSpecDiff {
+    Added {
+        path: String,
+        kind: PropSpecKind,
+    },
+    Removed {
+        path: String,
+        kind: PropSpecKind,
+    },
+    Retyped {
+        path: String,
+        before: PropSpecKind,
+        after: PropSpecKind,
+    },
+}
+
+#[remain::sorted]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "camelCase")]
+pub enum SocketSpecDiff {
+    Added {
+        name: String,
+        kind: Option<SocketSpecKind>,
+    },
+    Changed {
+        name: String,
+        kind: Option<SocketSpecKind>,
+        fields: Vec<FieldDiff>,
+    },
+    Removed {
+        name: String,
+        kind: Option<SocketSpecKind>,
+    },
+}
+
+/// A change to a binding of a function to a variant: an action, qualification, attribute
+/// function, and so on. `binding` describes where the function is bound, e.g.
+/// `attribute/root/domain/region`.
+#[remain::sorted]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "camelCase")]
+pub enum BindingSpecDiff {
+    Added {
+        binding: String,
+        func: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Changed {
+        binding: String,
+        before_func: String,
+        after_func: String,
+        inputs_changed: bool,
+    },
+    Removed {
+        binding: String,
+        func: String,
+    },
+}
+
+#[remain::sorted]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "camelCase")]
+pub enum FuncSpecDiff {
+    Added {
+        name: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Changed {
+        name: String,
+        fields: Vec<FieldDiff>,
+        /// A line diff of the code, with each line prefixed by `-`, `+` or a space
+        code_diff: Option<String>,
+    },
+    Removed {
+        name: String,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldDiff {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+impl PkgSpec {
+    /// Computes the structured changes from this spec to `other`. Schemas and funcs are matched
+    /// by name, since unique ids are not stable across versions of a module.
+    pub fn diff(&self, other: &PkgSpec) -> PkgSpecDiff {
+        let self_func_names = func_names_by_unique_id(&self.funcs);
+        let other_func_names = func_names_by_unique_id(&other.funcs);
+
+        let self_schemas: BTreeMap<&str, &SchemaSpec> = self
+            .schemas
+            .iter()
+            .filter(|schema| !schema.deleted)
+            .map(|schema| (schema.name.as_str(), schema))
+            .collect();
+        let other_schemas: BTreeMap<&str, &SchemaSpec> = other
+            .schemas
+            .iter()
+            .filter(|schema| !schema.deleted)
+            .map(|schema| (schema.name.as_str(), schema))
+            .collect();
+
+        let mut schemas = Vec::new();
+        for (name, schema) in &self_schemas {
+            match other_schemas.get(name) {
+                None => schemas.push(SchemaSpecDiff::new(name, SpecChange::Removed)),
+                Some(other_schema) => {
+                    let (Some(before), Some(after)) =
+                        (default_variant(schema), default_variant(other_schema))
+                    else {
+                        continue;
+                    };
+
+                    let diff = SchemaSpecDiff {
+                        name: name.to_string(),
+                        change: SpecChange::Changed,
+                        props: diff_props(before, after),
+                        sockets: diff_sockets(before, after),
+                        bindings: diff_bindings(
+                            &bindings_for_variant(before, &self_func_names),
+                            &bindings_for_variant(after, &other_func_names),
+                        ),
+                    };
+                    if !(diff.props.is_empty()
+                        && diff.sockets.is_empty()
+                        && diff.bindings.is_empty())
+                    {
+                        schemas.push(diff);
+                    }
+                }
+            }
+        }
+        for name in other_schemas.keys() {
+            if !self_schemas.contains_key(name) {
+                schemas.push(SchemaSpecDiff::new(name, SpecChange::Added));
+            }
+        }
+
+        PkgSpecDiff {
+            schemas,
+            funcs: diff_funcs(&self.funcs, &other.funcs),
+        }
+    }
+}
+
+impl SchemaSpecDiff {
+    fn new(name: &str, change: SpecChange) -> Self {
+        Self {
+            name: name.to_owned(),
+            change,
+            props: vec![],
+            sockets: vec![],
+            bindings: vec![],
+        }
+    }
+}
+
+fn default_variant(schema: &SchemaSpec) -> Option<&SchemaVariantSpec> {
+    let default_unique_id = schema
+        .data
+        .as_ref()
+        .and_then(|data| data.default_schema_variant.as_deref());
+
+    schema
+        .variants
+        .iter()
+        .find(|variant| {
+            default_unique_id.is_some() && variant.unique_id.as_deref() == default_unique_id
+        })
+        .or_else(|| {
+            schema
+                .variants
+                .iter()
+                .rev()
+                .find(|variant| !variant.deleted)
+        })
+}
+
+fn func_names_by_unique_id(funcs: &[FuncSpec]) -> HashMap<&str, &str> {
+    funcs
+        .iter()
+        .map(|func| (func.unique_id.as_str(), func.name.as_str()))
+        .collect()
+}
+
+fn diff_path(path: &str) -> String {
+    path.replace(PROP_PATH_SEPARATOR, DIFF_PATH_SEPARATOR)
+}
+
+fn diff_props(before: &SchemaVariantSpec, after: &SchemaVariantSpec) -> Vec<PropSpecDiff> {
+    let before_root = before.make_fake_root_prop();
+    let after_root = after.make_fake_root_prop();
+    let before_props = before_root.build_prop_spec_index_map();
+    let after_props = after_root.build_prop_spec_index_map();
+
+    let mut diffs = Vec::new();
+    for (path, (prop, _)) in &before_props {
+        match after_props.get(path) {
+            None => diffs.push(PropSpecDiff::Removed {
+                path: diff_path(path),
+                kind: prop.kind(),
+            }),
+            Some((after_prop, _)) if after_prop.kind() != prop.kind() => {
+                diffs.push(PropSpecDiff::Retyped {
+                    path: diff_path(path),
+                    before: prop.kind(),
+                    after: after_prop.kind(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for (path, (prop, _)) in &after_props {
+        if !before_props.contains_key(path) {
+            diffs.push(PropSpecDiff::Added {
+                path: diff_path(path),
+                kind: prop.kind(),
+            });
+        }
+    }
+
+    diffs.sort_by(|a, b| prop_diff_path(a).cmp(prop_diff_path(b)));
+    diffs
+}
+
+fn prop_diff_path(diff: &PropSpecDiff) -> &str {
+    match diff {
+        PropSpecDiff::Added { path, .. }
+        | PropSpecDiff::Removed { path, .. }
+        | PropSpecDiff::Retyped { path, .. } => path,
+    }
+}
+
+fn sockets_by_key(variant: &SchemaVariantSpec) -> BTreeMap<(String, String), &SocketSpec> {
+    variant
+        .sockets
+        .iter()
+        .map(|socket| {
+            let kind = socket
+                .kind()
+                .map(|kind| kind.to_string())
+                .unwrap_or_default();
+            ((kind, socket.name.to_owned()), socket)
+        })
+        .collect()
+}
+
+fn diff_sockets(before: &SchemaVariantSpec, after: &SchemaVariantSpec) -> Vec<SocketSpecDiff> {
+    let before_sockets = sockets_by_key(before);
+    let after_sockets = sockets_by_key(after);
+
+    let mut diffs = Vec::new();
+    for (key, socket) in &before_sockets {
+        match after_sockets.get(key) {
+            None => diffs.push(SocketSpecDiff::Removed {
+                name: socket.name.to_owned(),
+                kind: socket.kind(),
+            }),
+            Some(after_socket) => {
+                let mut fields = Vec::new();
+                if let (Some(before_data), Some(after_data)) = (&socket.data, &after_socket.data) {
+                    push_field_diff(&mut fields, "arity", &before_data.arity, &after_data.arity);
+                    push_field_diff(
+                        &mut fields,
+                        "connectionAnnotations",
+                        &before_data.connection_annotations,
+                        &after_data.connection_annotations,
+                    );
+                    push_field_diff(
+                        &mut fields,
+                        "uiHidden",
+                        &before_data.ui_hidden,
+                        &after_data.ui_hidden,
+                    );
+                }
+                if !fields.is_empty() {
+                    diffs.push(SocketSpecDiff::Changed {
+                        name: socket.name.to_owned(),
+                        kind: socket.kind(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    for (key, socket) in &after_sockets {
+        if !before_sockets.contains_key(key) {
+            diffs.push(SocketSpecDiff::Added {
+                name: socket.name.to_owned(),
+                kind: socket.kind(),
+            });
+        }
+    }
+
+    diffs
+}
+
+/// The function bound at a binding, and the inputs it is bound with.
+type Binding = (String, Value);
+
+fn bindings_for_variant(
+    variant: &SchemaVariantSpec,
+    func_names: &HashMap<&str, &str>,
+) -> BTreeMap<String, Binding> {
+    let func_name = |unique_id: &str| {
+        func_names
+            .get(unique_id)
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| unique_id.to_owned())
+    };
+
+    let mut bindings = BTreeMap::new();
+
+    // Bindings that may appear more than once are keyed by their func as well, so a different
+    // func shows up as one binding removed and another added.
+    for action_func in variant.action_funcs.iter().filter(|f| !f.deleted) {
+        let func = func_name(&action_func.func_unique_id);
+        bindings.insert(
+            format!("action/{}/{func}", action_func.kind),
+            (func, Value::Null),
+        );
+    }
+    for auth_func in variant.auth_funcs.iter().filter(|f| !f.deleted) {
+        let func = func_name(&auth_func.func_unique_id);
+        bindings.insert(format!("authentication/{func}"), (func, Value::Null));
+    }
+    for leaf_function in variant.leaf_functions.iter().filter(|f| !f.deleted) {
+        let func = func_name(&leaf_function.func_unique_id);
+        bindings.insert(
+            format!("{}/{func}", leaf_function.leaf_kind),
+            (
+                func,
+                serde_json::to_value(&leaf_function.inputs).unwrap_or_default(),
+            ),
+        );
+    }
+    for management_func in &variant.management_funcs {
+        bindings.insert(
+            format!("management/{}", management_func.name),
+            (func_name(&management_func.func_unique_id), Value::Null),
+        );
+    }
+
+    for socket in &variant.sockets {
+        if let Some(func_unique_id) = socket
+            .data
+            .as_ref()
+            .and_then(|data| data.func_unique_id.as_deref())
+        {
+            let kind = socket
+                .kind()
+                .map(|kind| kind.to_string())
+                .unwrap_or_default();
+            bindings.insert(
+                format!("socket/{kind}/{}", socket.name),
+                (
+                    func_name(func_unique_id),
+                    serde_json::to_value(&socket.inputs).unwrap_or_default(),
+                ),
+            );
+        }
+    }
+
+    let root = variant.make_fake_root_prop();
+    for (path, (prop, _)) in root.build_prop_spec_index_map() {
+        if let Some(func_unique_id) = prop.func_unique_id() {
+            bindings.insert(
+                format!("attribute/{}", diff_path(&path)),
+                (
+                    func_name(func_unique_id),
+                    serde_json::to_value(prop.inputs()).unwrap_or_default(),
+                ),
+            );
+        }
+    }
+    for root_prop_func in variant.root_prop_funcs.iter().filter(|f| !f.deleted) {
+        bindings.insert(
+            format!(
+                "attribute/root/{}",
+                root_prop_func.prop.path_parts().join(DIFF_PATH_SEPARATOR)
+            ),
+            (
+                func_name(&root_prop_func.func_unique_id),
+                serde_json::to_value(&root_prop_func.inputs).unwrap_or_default(),
+            ),
+        );
+    }
+    for si_prop_func in variant.si_prop_funcs.iter().filter(|f| !f.deleted) {
+        bindings.insert(
+            format!("attribute/root/si/{}", si_prop_func.kind),
+            (
+                func_name(&si_prop_func.func_unique_id),
+                serde_json::to_value(&si_prop_func.inputs).unwrap_or_default(),
+            ),
+        );
+    }
+
+    bindings
+}
+
+fn diff_bindings(
+    before: &BTreeMap<String, Binding>,
+    after: &BTreeMap<String, Binding>,
+) -> Vec<BindingSpecDiff> {
+    let mut diffs = Vec::new();
+    for (binding, (before_func, before_inputs)) in before {
+        match after.get(binding) {
+            None => diffs.push(BindingSpecDiff::Removed {
+                binding: binding.to_owned(),
+                func: before_func.to_owned(),
+            }),
+            Some((after_func, after_inputs)) => {
+                if before_func != after_func || before_inputs != after_inputs {
+                    diffs.push(BindingSpecDiff::Changed {
+                        binding: binding.to_owned(),
+                        before_func: before_func.to_owned(),
+                        after_func: after_func.to_owned(),
+                        inputs_changed: before_inputs != after_inputs,
+                    });
+                }
+            }
+        }
+    }
+    for (binding, (func, _)) in after {
+        if !before.contains_key(binding) {
+            diffs.push(BindingSpecDiff::Added {
+                binding: binding.to_owned(),
+                func: func.to_owned(),
+            });
+        }
+    }
+
+    diffs
+}
+
+fn diff_funcs(before: &[FuncSpec], after: &[FuncSpec]) -> Vec<FuncSpecDiff> {
+    let before_funcs: BTreeMap<&str, &FuncSpec> = before
+        .iter()
+        .filter(|func| !func.deleted)
+        .map(|func| (func.name.as_str(), func))
+        .collect();
+    let after_funcs: BTreeMap<&str, &FuncSpec> = after
+        .iter()
+        .filter(|func| !func.deleted)
+        .map(|func| (func.name.as_str(), func))
+        .collect();
+
+    let mut diffs = Vec::new();
+    for (name, func) in &before_funcs {
+        match after_funcs.get(name) {
+            None => diffs.push(FuncSpecDiff::Removed {
+                name: name.to_string(),
+            }),
+            Some(after_func) => {
+                let mut fields = Vec::new();
+                push_field_diff(
+                    &mut fields,
+                    "arguments",
+                    &func.arguments,
+                    &after_func.arguments,
+                );
+
+                let mut code_diff = None;
+                if let (Some(before_data), Some(after_data)) = (&func.data, &after_func.data) {
+                    push_field_diff(
+                        &mut fields,
+                        "handler",
+                        &before_data.handler,
+                        &after_data.handler,
+                    );
+                    push_field_diff(
+                        &mut fields,
+                        "backendKind",
+                        &before_data.backend_kind,
+                        &after_data.backend_kind,
+                    );
+                    push_field_diff(
+                        &mut fields,
+                        "responseType",
+                        &before_data.response_type,
+                        &after_data.response_type,
+                    );
+                    push_field_diff(
+                        &mut fields,
+                        "displayName",
+                        &before_data.display_name,
+                        &after_data.display_name,
+                    );
+                    push_field_diff(
+                        &mut fields,
+                        "description",
+                        &before_data.description,
+                        &after_data.description,
+                    );
+
+                    if before_data.code_base64 != after_data.code_base64 {
+                        code_diff = Some(code_line_diff(before_data, after_data));
+                    }
+                }
+
+                if !fields.is_empty() || code_diff.is_some() {
+                    diffs.push(FuncSpecDiff::Changed {
+                        name: name.to_string(),
+                        fields,
+                        code_diff,
+                    });
+                }
+            }
+        }
+    }
+    for name in after_funcs.keys() {
+        if !before_funcs.contains_key(name) {
+            diffs.push(FuncSpecDiff::Added {
+                name: name.to_string(),
+            });
+        }
+    }
+
+    diffs
+}
+
+fn push_field_diff<T: Serialize>(fields: &mut Vec<FieldDiff>, field: &str, before: &T, after: &T) {
+    let before = serde_json::to_value(before).unwrap_or_default();
+    let after = serde_json::to_value(after).unwrap_or_default();
+    if before != after {
+        fields.push(FieldDiff {
+            field: field.to_owned(),
+            before,
+            after,
+        });
+    }
+}
+
+fn code_plaintext(data: &FuncSpecData) -> String {
+    general_purpose::STANDARD_NO_PAD
+        .decode(data.code_base64.trim_end_matches('='))
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_else(|| data.code_base64.to_owned())
+}
+
+fn code_line_diff(before: &FuncSpecData, after: &FuncSpecData) -> String {
+    let before_code = code_plaintext(before);
+    let after_code = code_plaintext(after);
+
+    diff::lines(&before_code, &after_code)
+        .into_iter()
+        .map(|line| match line {
+            diff::Result::Left(left) => format!("-{left}"),
+            diff::Result::Both(unchanged, _) => format!(" {unchanged}"),
+            diff::Result::Right(right) => format!("+{right}"),
+        })
+        .collect::<Vec<_>>()
+        .join(NEWLINE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        FuncSpecBackendKind, FuncSpecBackendResponseType, PropSpec, SchemaSpecData,
+        SocketSpecArity, SocketSpecData,
+    };
+
+    fn func(name: &str, code: &str) -> FuncSpec {
+        FuncSpec::builder()
+            .name(name)
+            .unique_id(name)
+            .data(
+                FuncSpecData::builder()
+                    .name(name)
+                    .handler("main")
+                    .code_plaintext(code)
+                    .backend_kind(FuncSpecBackendKind::JsAttribute)
+                    .response_type(FuncSpecBackendResponseType::String)
+                    .build()
+                    .expect("able to build func data"),
+            )
+            .build()
+            .expect("able to build func")
+    }
+
+    fn pkg(props: Vec<PropSpec>, socket_arity: SocketSpecArity, funcs: Vec<FuncSpec>) -> PkgSpec {
+        let variant = SchemaVariantSpec::builder()
+            .version("v0")
+            .unique_id("variant")
+            .props(props)
+            .socket(
+                SocketSpec::builder()
+                    .name("region")
+                    .data(
+                        SocketSpecData::builder()
+                            .name("region")
+                            .kind(SocketSpecKind::Input)
+                            .arity(socket_arity)
+                            .build()
+                            .expect("able to build socket data"),
+                    )
+                    .build()
+                    .expect("able to build socket"),
+            )
+            .build()
+            .expect("able to build variant");
+
+        PkgSpec::builder()
+            .name("pkg")
+            .version("0")
+            .created_by("sally@systeminit.com")
+            .schema(
+                SchemaSpec::builder()
+                    .name("server")
+                    .data(
+                        SchemaSpecData::builder()
+                            .name("server")
+                            .category("test")
+                            .default_schema_variant("variant")
+                            .build()
+                            .expect("able to build schema data"),
+                    )
+                    .variant(variant)
+                    .build()
+                    .expect("able to build schema"),
+            )
+            .funcs(funcs)
+            .build()
+            .expect("able to build pkg")
+    }
+
+    fn prop(name: &str, kind: PropSpecKind) -> PropSpec {
+        PropSpec::builder()
+            .name(name)
+            .kind(kind)
+            .build()
+            .expect("able to build prop")
+    }
+
+    #[test]
+    fn diff_between_versions() {
+        let before = pkg(
+            vec![
+                prop("region", PropSpecKind::String),
+                prop("port", PropSpecKind::String),
+            ],
+            SocketSpecArity::Many,
+            vec![func("getRegion", "return 1;\nreturn 2;"), func("old", "")],
+        );
+        let after = pkg(
+            vec![
+                prop("port", PropSpecKind::Number),
+                prop("zone", PropSpecKind::String),
+            ],
+            SocketSpecArity::One,
+            vec![func("getRegion", "return 1;\nreturn 3;"), func("new", "")],
+        );
+
+        let diff = before.diff(&after);
+
+        let schema = diff.schemas.first().expect("schema should have changed");
+        assert_eq!(SpecChange::Changed, schema.change);
+        assert_eq!(
+            vec![
+                PropSpecDiff::Retyped {
+                    path: "root/domain/port".to_owned(),
+                    before: PropSpecKind::String,
+                    after: PropSpecKind::Number,
+                },
+                PropSpecDiff::Removed {
+                    path: "root/domain/region".to_owned(),
+                    kind: PropSpecKind::String,
+                },
+                PropSpecDiff::Added {
+                    path: "root/domain/zone".to_owned(),
+                    kind: PropSpecKind::String,
+                },
+            ],
+            schema.props
+        );
+        assert!(matches!(
+            schema.sockets.as_slice(),
+            [SocketSpecDiff::Changed { name, fields, .. }]
+                if name == "region" && fields.len() == 1 && fields[0].field == "arity"
+        ));
+
+        assert_eq!(3, diff.funcs.len());
+        assert!(diff.funcs.contains(&FuncSpecDiff::Changed {
+            name: "getRegion".to_owned(),
+            fields: vec![],
+            code_diff: Some(" return 1;\n-return 2;\n+return 3;".to_owned()),
+        }));
+        assert!(diff.funcs.contains(&FuncSpecDiff::Removed {
+            name: "old".to_owned()
+        }));
+        assert!(diff.funcs.contains(&FuncSpecDiff::Added {
+            name: "new".to_owned()
+        }));
+
+        assert!(before.diff(&before).is_empty());
+    }
+}
@@ -471,7 +471,8 @@ impl PropSpec {
 }
 
 #[remain::sorted]
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum PropSpecKind {
     Array,
     Boolean,
@@ -0,0 +1,355 @@
+//! Consistency checks for a [`PkgSpec`] that the builders cannot enforce on their own, so that a
+//! broken package can be caught before it is installed.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{PkgSpec, PropSpec, PropSpecKind, SchemaVariantSpec, SocketSpec, PROP_PATH_SEPARATOR};
+
+const LOCATION_PATH_SEPARATOR: &str = "/";
+
+/// A problem found in a [`PkgSpec`] by [`PkgSpec::validate`].
+#[remain::sorted]
+#[derive(Clone, Debug, Deserialize, Eq, Error, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SpecValidationError {
+    #[error("{location} refers to func {func_unique_id}, which is not in the package")]
+    DanglingFuncUniqueId {
+        location: String,
+        func_unique_id: String,
+    },
+    #[error("{location} has a default value that does not match its kind ({kind:?}): {value}")]
+    DefaultValueKindMismatch {
+        location: String,
+        kind: PropSpecKind,
+        value: Value,
+    },
+    #[error("func unique id {0} is used by more than one func")]
+    DuplicateFuncUniqueId(String),
+    #[error("{location} has invalid connection annotations ({annotations}): {message}")]
+    InvalidConnectionAnnotations {
+        location: String,
+        annotations: String,
+        message: String,
+    },
+    #[error("dependency {name} has an invalid version constraint: {version_constraint}")]
+    InvalidDependencyVersionConstraint {
+        name: String,
+        version_constraint: String,
+    },
+}
+
+impl PkgSpec {
+    /// Checks that every func referenced by the schema variants is in the package, that socket
+    /// connection annotations and dependency version constraints parse, and that prop default
+    /// values match their prop kinds.
+    pub fn validate(&self) -> Vec<SpecValidationError> {
+        let mut errors = Vec::new();
+
+        let mut func_unique_ids = HashSet::new();
+        for func in &self.funcs {
+            if !func_unique_ids.insert(func.unique_id.as_str()) {
+                errors.push(SpecValidationError::DuplicateFuncUniqueId(
+                    func.unique_id.to_owned(),
+                ));
+            }
+        }
+
+        for dependency in &self.dependencies {
+            if !dependency.version_constraint_is_valid() {
+                errors.push(SpecValidationError::InvalidDependencyVersionConstraint {
+                    name: dependency.name.to_owned(),
+                    version_constraint: dependency.version_constraint.to_owned(),
+                });
+            }
+        }
+
+        for schema in &self.schemas {
+            for variant in &schema.variants {
+                let location = format!(
+                    "schema {} variant {}",
+                    schema.name,
+                    variant.unique_id.as_deref().unwrap_or(&variant.version)
+                );
+                validate_variant(variant, &location, &func_unique_ids, &mut errors);
+            }
+        }
+
+        errors
+    }
+}
+
+fn validate_variant(
+    variant: &SchemaVariantSpec,
+    location: &str,
+    func_unique_ids: &HashSet<&str>,
+    errors: &mut Vec<SpecValidationError>,
+) {
+    // Every binding of a func to this variant, as (binding description, func unique id)
+    let mut func_references: Vec<(String, &str)> = Vec::new();
+
+    if let Some(data) = &variant.data {
+        func_references.push(("asset func".to_owned(), &data.func_unique_id));
+    }
+    for action_func in &variant.action_funcs {
+        func_references.push((
+            format!("{} action", action_func.kind),
+            &action_func.func_unique_id,
+        ));
+    }
+    for auth_func in &variant.auth_funcs {
+        func_references.push(("authentication func".to_owned(), &auth_func.func_unique_id));
+    }
+    for leaf_function in &variant.leaf_functions {
+        func_references.push((
+            format!("{} func", leaf_function.leaf_kind),
+            &leaf_function.func_unique_id,
+        ));
+    }
+    for management_func in &variant.management_funcs {
+        func_references.push((
+            format!("management func {}", management_func.name),
+            &management_func.func_unique_id,
+        ));
+    }
+    for si_prop_func in &variant.si_prop_funcs {
+        func_references.push((
+            format!(
+                "prop {}",
+                si_prop_func.kind.prop_path().join(LOCATION_PATH_SEPARATOR)
+            ),
+            &si_prop_func.func_unique_id,
+        ));
+    }
+    for root_prop_func in &variant.root_prop_funcs {
+        func_references.push((
+            format!(
+                "prop {}",
+                root_prop_func
+                    .prop
+                    .path_parts()
+                    .join(LOCATION_PATH_SEPARATOR)
+            ),
+            &root_prop_func.func_unique_id,
+        ));
+    }
+    for socket in &variant.sockets {
+        if let Some(func_unique_id) = socket
+            .data
+            .as_ref()
+            .and_then(|data| data.func_unique_id.as_deref())
+        {
+            func_references.push((format!("socket {}", socket.name), func_unique_id));
+        }
+    }
+
+    let root = variant.make_fake_root_prop();
+    for (path, (prop, _)) in root.build_prop_spec_index_map() {
+        let path = path.replace(PROP_PATH_SEPARATOR, LOCATION_PATH_SEPARATOR);
+
+        if let Some(func_unique_id) = prop.func_unique_id() {
+            func_references.push((format!("prop {path}"), func_unique_id));
+        }
+        if let PropSpec::Map {
+            map_key_funcs: Some(map_key_funcs),
+            ..
+        } = prop
+        {
+            for map_key_func in map_key_funcs {
+                func_references.push((
+                    format!("prop {path} key {}", map_key_func.key),
+                    &map_key_func.func_unique_id,
+                ));
+            }
+        }
+
+        if let Some(default_value) = prop.data().and_then(|data| data.default_value.as_ref()) {
+            if !default_value_matches_kind(default_value, prop.kind()) {
+                errors.push(SpecValidationError::DefaultValueKindMismatch {
+                    location: format!("{location} prop {path}"),
+                    kind: prop.kind(),
+                    value: default_value.to_owned(),
+                });
+            }
+        }
+    }
+
+    for (binding, func_unique_id) in func_references {
+        if !func_unique_ids.contains(func_unique_id) {
+            errors.push(SpecValidationError::DanglingFuncUniqueId {
+                location: format!("{location} {binding}"),
+                func_unique_id: func_unique_id.to_owned(),
+            });
+        }
+    }
+
+    for socket in &variant.sockets {
+        if let Err(message) = validate_connection_annotations(socket) {
+            errors.push(SpecValidationError::InvalidConnectionAnnotations {
+                location: format!("{location} socket {}", socket.name),
+                annotations: socket
+                    .data
+                    .as_ref()
+                    .map(|data| data.connection_annotations.to_owned())
+                    .unwrap_or_default(),
+                message,
+            });
+        }
+    }
+}
+
+fn default_value_matches_kind(value: &Value, kind: PropSpecKind) -> bool {
+    match kind {
+        PropSpecKind::Array => value.is_array(),
+        PropSpecKind::Boolean => value.is_boolean(),
+        PropSpecKind::Json => true,
+        PropSpecKind::Map | PropSpecKind::Object => value.is_object(),
+        PropSpecKind::Number => value.is_number(),
+        PropSpecKind::String => value.is_string(),
+    }
+}
+
+/// Connection annotations are a JSON array, either of annotation strings (the older format) or of
+/// objects holding a non-empty `tokens` array of strings.
+fn validate_connection_annotations(socket: &SocketSpec) -> Result<(), String> {
+    let Some(data) = &socket.data else {
+        return Ok(());
+    };
+
+    let annotations: Vec<Value> =
+        serde_json::from_str(&data.connection_annotations).map_err(|err| err.to_string())?;
+    for annotation in annotations {
+        match annotation {
+            Value::String(annotation) if !annotation.trim().is_empty() => {}
+            Value::Object(annotation) => {
+                let tokens = annotation
+                    .get("tokens")
+                    .and_then(Value::as_array)
+                    .ok_or("annotation has no tokens array")?;
+                if tokens.is_empty() {
+                    return Err("annotation has no tokens".to_owned());
+                }
+                if !tokens
+                    .iter()
+                    .all(|token| token.as_str().is_some_and(|token| !token.is_empty()))
+                {
+                    return Err("annotation tokens must be non-empty strings".to_owned());
+                }
+            }
+            other => return Err(format!("unexpected annotation {other}")),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        FuncSpec, FuncSpecBackendKind, FuncSpecBackendResponseType, FuncSpecData, SchemaSpec,
+        SchemaVariantSpec, SchemaVariantSpecData, SocketSpec, SocketSpecData, SocketSpecKind,
+    };
+
+    use super::*;
+
+    #[test]
+    fn validate_finds_broken_references() {
+        let asset_func = FuncSpec::builder()
+            .name("asset")
+            .unique_id("asset")
+            .data(
+                FuncSpecData::builder()
+                    .name("asset")
+                    .handler("main")
+                    .code_plaintext("function main() {}")
+                    .backend_kind(FuncSpecBackendKind::JsSchemaVariantDefinition)
+                    .response_type(FuncSpecBackendResponseType::SchemaVariantDefinition)
+                    .build()
+                    .expect("able to build func data"),
+            )
+            .build()
+            .expect("able to build func");
+
+        let variant = SchemaVariantSpec::builder()
+            .version("v0")
+            .data(
+                SchemaVariantSpecData::builder()
+                    .version("v0")
+                    .func_unique_id("asset")
+                    .build()
+                    .expect("able to build variant data"),
+            )
+            .domain_prop(
+                PropSpec::builder()
+                    .name("port")
+                    .kind(PropSpecKind::Number)
+                    .default_value(serde_json::json!("eighty"))
+                    .build()
+                    .expect("able to build prop"),
+            )
+            .domain_prop(
+                PropSpec::builder()
+                    .name("region")
+                    .kind(PropSpecKind::String)
+                    .func_unique_id("missing")
+                    .build()
+                    .expect("able to build prop"),
+            )
+            .socket(
+                SocketSpec::builder()
+                    .name("region")
+                    .data(
+                        SocketSpecData::builder()
+                            .name("region")
+                            .kind(SocketSpecKind::Input)
+                            .connection_annotations("[{\"tokens\":[]}]")
+                            .build()
+                            .expect("able to build socket data"),
+                    )
+                    .build()
+                    .expect("able to build socket"),
+            )
+            .build()
+            .expect("able to build variant");
+
+        let spec = PkgSpec::builder()
+            .name("pkg")
+            .version("1")
+            .created_by("sally@systeminit.com")
+            .func(asset_func.clone())
+            .func(asset_func)
+            .schema(
+                SchemaSpec::builder()
+                    .name("schema")
+                    .variant(variant)
+                    .build()
+                    .expect("able to build schema"),
+            )
+            .build()
+            .expect("able to build pkg spec");
+
+        let errors = spec.validate();
+        assert_eq!(4, errors.len(), "{errors:?}");
+        assert!(errors.contains(&SpecValidationError::DuplicateFuncUniqueId(
+            "asset".to_owned()
+        )));
+        assert!(errors.contains(&SpecValidationError::DanglingFuncUniqueId {
+            location: "schema schema variant v0 prop root/domain/region".to_owned(),
+            func_unique_id: "missing".to_owned(),
+        }));
+        assert!(
+            errors.contains(&SpecValidationError::DefaultValueKindMismatch {
+                location: "schema schema variant v0 prop root/domain/port".to_owned(),
+                kind: PropSpecKind::Number,
+                value: serde_json::json!("eighty"),
+            })
+        );
+        assert!(errors.iter().any(|error| matches!(
+            error,
+            SpecValidationError::InvalidConnectionAnnotations { .. }
+        )));
+    }
+}
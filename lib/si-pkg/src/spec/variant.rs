@@ -325,7 +325,7 @@ impl SchemaVariantSpec {
             .collect()
     }
 
-    fn make_fake_root_prop(&self) -> PropSpec {
+    pub(crate) fn make_fake_root_prop(&self) -> PropSpec {
         let mut root = PropSpec::builder();
         root.kind(PropSpecKind::Object).name("root");
         for root_prop_kind in SchemaVariantSpecPropRoot::iter() {
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use ulid::Ulid;
 
+use crate::{ComponentSpec, EdgeSpec};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum WorkspaceExport {
     V0(WorkspaceExportContentV0),
@@ -49,3 +51,48 @@ pub struct WorkspaceExportMetadataV0 {
     pub workspace_pk: Ulid,
     pub workspace_name: String,
 }
+
+/// A model that new workspaces are created from. Its components are stored as component and edge
+/// specs, and `{{ name }}` placeholders for its parameters in component names and property values
+/// are substituted when it is instantiated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WorkspaceTemplate {
+    V0(WorkspaceTemplateContentV0),
+}
+
+impl WorkspaceTemplate {
+    pub fn new(content: WorkspaceTemplateContentV0) -> Self {
+        WorkspaceTemplate::V0(content)
+    }
+
+    // This function should always return the latest version, updating the contents if necessary
+    pub fn into_latest(self) -> WorkspaceTemplateContentV0 {
+        let WorkspaceTemplate::V0(template) = self;
+        template
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceTemplateContentV0 {
+    pub metadata: WorkspaceTemplateMetadataV0,
+    pub parameters: Vec<WorkspaceTemplateParameterV0>,
+    pub components: Vec<ComponentSpec>,
+    pub edges: Vec<EdgeSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceTemplateMetadataV0 {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub created_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceTemplateParameterV0 {
+    pub name: String,
+    pub description: Option<String>,
+    /// Used when no value is given for the parameter. Parameters without one are required.
+    pub default: Option<serde_json::Value>,
+}
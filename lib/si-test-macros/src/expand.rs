@@ -380,8 +380,8 @@ pub(crate) trait FnSetupExpander {
 
         let var = Ident::new("veritech_server", Span::call_site());
         self.code_extend(quote! {
-            let #var = ::dal_test::veritech_server_for_uds_cyclone(
-                #test_context.nats_config().clone(),
+            let #var = ::dal_test::veritech_for_tests(
+                &#test_context,
                 #cancellation_token.clone(),
             ).await?;
         });
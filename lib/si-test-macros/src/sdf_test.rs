@@ -227,7 +227,7 @@ struct SdfTestFnSetupExpander {
     router: Option<Rc<Ident>>,
     auth_token: Option<Rc<Ident>>,
     auth_token_ref: Option<Rc<Ident>>,
-    spicedb_client: Option<Rc<Ident>>,
+    permissions_client: Option<Rc<Ident>>,
 }
 
 impl SdfTestFnSetupExpander {
@@ -262,7 +262,7 @@ impl SdfTestFnSetupExpander {
             router: None,
             auth_token: None,
             auth_token_ref: None,
-            spicedb_client: None,
+            permissions_client: None,
         }
     }
 
@@ -365,7 +365,7 @@ impl SdfTestFnSetupExpander {
         let ws_multiplexer_client = ws_multiplexer_client.as_ref();
         let crdt_multiplexer_client = self.setup_crdt_multiplexer_client();
         let crdt_multiplexer_client = crdt_multiplexer_client.as_ref();
-        let spicedb_client = self.setup_spicedb_client();
+        let permissions_client = self.setup_permissions_client();
 
         let var = Ident::new("router", Span::call_site());
         self.code_extend(quote! {
@@ -390,7 +390,7 @@ impl SdfTestFnSetupExpander {
                         ::tokio::sync::RwLock::new(::sdf_server::ApplicationRuntimeMode::Running)
                     ),
                     #cancellation_token.clone(),
-                    #spicedb_client,
+                    #permissions_client,
                 ).into_inner()
             };
         });
@@ -433,20 +433,18 @@ impl SdfTestFnSetupExpander {
         self.auth_token_ref.as_ref().unwrap().clone()
     }
 
-    fn setup_spicedb_client(&mut self) -> Rc<Ident> {
-        if let Some(ref ident) = self.spicedb_client {
+    fn setup_permissions_client(&mut self) -> Rc<Ident> {
+        if let Some(ref ident) = self.permissions_client {
             return ident.clone();
         }
 
-        let var = Ident::new("spicedb_client", Span::call_site());
+        let var = Ident::new("permissions_client", Span::call_site());
         self.code_extend(quote! {
-            let #var = {
-               ::si_data_spicedb::SpiceDbClient::new_sync(&::si_data_spicedb::SpiceDbConfig::default())?
-            };
+            let #var = ::dal_test::permissions_client().await?;
         });
-        self.spicedb_client = Some(Rc::new(var));
+        self.permissions_client = Some(Rc::new(var));
 
-        self.spicedb_client.as_ref().unwrap().clone()
+        self.permissions_client.as_ref().unwrap().clone()
     }
 
     fn finish(self) -> SdfTestFnSetup {
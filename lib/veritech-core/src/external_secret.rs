@@ -0,0 +1,195 @@
+//! References to secrets that live outside of System Initiative.
+//!
+//! Rather than submitting the secret material itself, a secret may contain references of the
+//! following shape anywhere in its value tree:
+//!
+//! ```json
+//! { "siExternalSecret": { "env": "AWS_SECRET_ACCESS_KEY" } }
+//! ```
+//!
+//! Only the reference is stored (and encrypted) by System Initiative. Veritech resolves each
+//! reference on its host just before executing the function and redacts the resolved value like
+//! any other decrypted secret. The supported providers are `env` (an environment variable),
+//! `file` (a file path) and `store` (a key in the configured secret store).
+
+use std::path::PathBuf;
+
+use serde_json::Value;
+use thiserror::Error;
+
+const MARKER_FIELD: &str = "siExternalSecret";
+const ENV_PROVIDER: &str = "env";
+const FILE_PROVIDER: &str = "file";
+const STORE_PROVIDER: &str = "store";
+
+#[derive(Debug, Error)]
+pub enum ExternalSecretReferenceError {
+    #[error("invalid json pointer: {0}")]
+    InvalidJSONPointer(String),
+    #[error("external secret reference at {0} is not a single provider and string reference")]
+    InvalidReference(String),
+    #[error("unknown external secret provider at {0}: {1}")]
+    UnknownProvider(String, String),
+}
+
+/// A reference to a secret value held by an external provider.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExternalSecretReference {
+    /// An environment variable on the veritech host.
+    Env(String),
+    /// A file on the veritech host, whose contents (without a trailing newline) are the value.
+    File(PathBuf),
+    /// A key in the secret store configured for veritech.
+    Store(String),
+}
+
+impl ExternalSecretReference {
+    /// Builds the JSON value that refers to this external secret.
+    pub fn to_value(&self) -> Value {
+        let (provider, reference) = match self {
+            Self::Env(name) => (ENV_PROVIDER, name.to_owned()),
+            Self::File(path) => (FILE_PROVIDER, path.to_string_lossy().into_owned()),
+            Self::Store(key) => (STORE_PROVIDER, key.to_owned()),
+        };
+
+        serde_json::json!({ MARKER_FIELD: { provider: reference } })
+    }
+}
+
+/// Finds every [`ExternalSecretReference`] in a value tree, returning each alongside the JSON
+/// pointer at which it was found so the caller can replace it with the resolved value.
+pub fn find_external_secret_references(
+    value: &Value,
+) -> Result<Vec<(String, ExternalSecretReference)>, ExternalSecretReferenceError> {
+    let mut references = Vec::new();
+    let mut json_pointer_stack = vec!["".to_owned()];
+
+    while let Some(pointer) = json_pointer_stack.pop() {
+        match value.pointer(&pointer) {
+            Some(value) => match value {
+                Value::Array(array) => {
+                    json_pointer_stack.extend(
+                        array
+                            .iter()
+                            .enumerate()
+                            .map(|(index, _element)| format!("{pointer}/{index}")),
+                    );
+                }
+                Value::Object(object) => match object.get(MARKER_FIELD) {
+                    Some(reference) => {
+                        let reference = parse_reference(&pointer, reference)?;
+                        references.push((pointer, reference));
+                    }
+                    None => {
+                        json_pointer_stack.extend(
+                            object
+                                .iter()
+                                .map(|(key, _value)| format!("{pointer}/{key}")),
+                        );
+                    }
+                },
+                Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => {
+                    // Nothing to do
+                }
+            },
+            None => return Err(ExternalSecretReferenceError::InvalidJSONPointer(pointer)),
+        }
+    }
+
+    Ok(references)
+}
+
+fn parse_reference(
+    pointer: &str,
+    value: &Value,
+) -> Result<ExternalSecretReference, ExternalSecretReferenceError> {
+    let invalid = || ExternalSecretReferenceError::InvalidReference(pointer.to_owned());
+
+    let object = value.as_object().ok_or_else(invalid)?;
+    if object.len() != 1 {
+        return Err(invalid());
+    }
+    let (provider, reference) = object.iter().next().ok_or_else(invalid)?;
+    let reference = reference.as_str().ok_or_else(invalid)?.to_owned();
+
+    match provider.as_str() {
+        ENV_PROVIDER => Ok(ExternalSecretReference::Env(reference)),
+        FILE_PROVIDER => Ok(ExternalSecretReference::File(reference.into())),
+        STORE_PROVIDER => Ok(ExternalSecretReference::Store(reference)),
+        unknown => Err(ExternalSecretReferenceError::UnknownProvider(
+            pointer.to_owned(),
+            unknown.to_owned(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn finds_nested_references() {
+        let value = json!({
+            "kind": "aws",
+            "credentials": {
+                "accessKeyId": ExternalSecretReference::Env("AWS_ACCESS_KEY_ID".to_owned()).to_value(),
+                "secretAccessKey": ExternalSecretReference::File("/run/secrets/aws".into()).to_value(),
+            },
+            "tokens": [ExternalSecretReference::Store("github/token".to_owned()).to_value()],
+        });
+
+        let mut references =
+            find_external_secret_references(&value).expect("failed to find references");
+        references.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            vec![
+                (
+                    "/credentials/accessKeyId".to_owned(),
+                    ExternalSecretReference::Env("AWS_ACCESS_KEY_ID".to_owned()),
+                ),
+                (
+                    "/credentials/secretAccessKey".to_owned(),
+                    ExternalSecretReference::File("/run/secrets/aws".into()),
+                ),
+                (
+                    "/tokens/0".to_owned(),
+                    ExternalSecretReference::Store("github/token".to_owned()),
+                ),
+            ],
+            references
+        );
+    }
+
+    #[test]
+    fn no_references() {
+        let value = json!({"password": "Drummer", "tags": ["a", "b"]});
+
+        assert!(find_external_secret_references(&value)
+            .expect("failed to find references")
+            .is_empty());
+    }
+
+    #[test]
+    fn unknown_provider() {
+        let value = json!({"token": {MARKER_FIELD: {"vault": "token"}}});
+
+        assert!(matches!(
+            find_external_secret_references(&value),
+            Err(ExternalSecretReferenceError::UnknownProvider(pointer, provider))
+                if pointer == "/token" && provider == "vault",
+        ));
+    }
+
+    #[test]
+    fn invalid_reference() {
+        let value = json!({"token": {MARKER_FIELD: {"env": "A", "file": "/b"}}});
+
+        assert!(matches!(
+            find_external_secret_references(&value),
+            Err(ExternalSecretReferenceError::InvalidReference(pointer)) if pointer == "/token",
+        ));
+    }
+}
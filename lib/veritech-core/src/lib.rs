@@ -18,10 +18,14 @@ use cyclone_core::{
 use si_data_nats::{async_nats, jetstream, Subject};
 
 mod crypto;
+mod external_secret;
 
 pub use crypto::{
     decrypt_value_tree, encrypt_value_tree, VeritechValueDecryptError, VeritechValueEncryptError,
 };
+pub use external_secret::{
+    find_external_secret_references, ExternalSecretReference, ExternalSecretReferenceError,
+};
 
 const NATS_WORK_QUEUE_STREAM_NAME: &str = "VERITECH_REQUESTS";
 const NATS_WORK_QUEUE_STREAM_SUBJECTS: &[&str] = &["veritech.requests.>"];
@@ -10,7 +10,7 @@ use tokio::sync::oneshot;
 use tokio::sync::Mutex;
 use veritech_core::ExecutionId;
 
-use crate::server::ServerMetadata;
+use crate::{external_secret::ExternalSecretResolver, server::ServerMetadata};
 
 /// Application state.
 #[derive(Clone, Debug)]
@@ -21,6 +21,7 @@ pub struct AppState {
     // If that changes, then I hope you read this comment before that happens.
     pub cyclone_pool: PoolNoodle<LocalUdsInstance, LocalUdsInstanceSpec>,
     pub decryption_key: Arc<VeritechDecryptionKey>,
+    pub external_secrets: Arc<ExternalSecretResolver>,
     // TODO(nick,fletcher,scott): make this mutable at runtime.
     pub cyclone_client_execution_timeout: Duration,
     pub nats: NatsClient,
@@ -33,6 +34,7 @@ impl AppState {
         metadata: Arc<ServerMetadata>,
         cyclone_pool: PoolNoodle<LocalUdsInstance, LocalUdsInstanceSpec>,
         decryption_key: Arc<VeritechDecryptionKey>,
+        external_secrets: Arc<ExternalSecretResolver>,
         cyclone_client_execution_timeout: Duration,
         nats: NatsClient,
         kill_senders: Arc<Mutex<HashMap<ExecutionId, oneshot::Sender<()>>>>,
@@ -41,6 +43,7 @@ impl AppState {
             metadata,
             cyclone_pool,
             decryption_key,
+            external_secrets,
             cyclone_client_execution_timeout,
             nats,
             kill_senders,
@@ -22,6 +22,8 @@ use si_pool_noodle::{
 use telemetry::prelude::*;
 use thiserror::Error;
 
+use crate::external_secret::ExternalSecretsConfig;
+
 pub use si_settings::{StandardConfig, StandardConfigFile};
 
 const DEFAULT_CONCURRENCY_LIMIT: usize = 1000;
@@ -65,6 +67,9 @@ pub struct Config {
     #[builder(default = "VeritechCryptoConfig::default()")]
     crypto: VeritechCryptoConfig,
 
+    #[builder(default)]
+    external_secrets: ExternalSecretsConfig,
+
     #[builder(default = "default_healthcheck_pool()")]
     healthcheck_pool: bool,
 
@@ -104,6 +109,11 @@ impl Config {
         &self.crypto
     }
 
+    /// Gets a reference to the config's external secret providers.
+    pub fn external_secrets(&self) -> &ExternalSecretsConfig {
+        &self.external_secrets
+    }
+
     /// Gets the config's healthcheck value.
     pub fn healthcheck_pool(&self) -> bool {
         self.healthcheck_pool
@@ -137,6 +147,8 @@ pub struct ConfigFile {
     pub cyclone: CycloneConfig,
     #[serde(default)]
     pub crypto: VeritechCryptoConfig,
+    #[serde(default)]
+    pub external_secrets: ExternalSecretsConfig,
     #[serde(default = "default_healthcheck_pool")]
     healthcheck_pool: bool,
     #[serde(default = "default_cyclone_client_execution_timeout_secs")]
@@ -159,6 +171,7 @@ impl ConfigFile {
             nats: Default::default(),
             cyclone: CycloneConfig::default_local_http(),
             crypto: Default::default(),
+            external_secrets: Default::default(),
             healthcheck_pool: default_healthcheck_pool(),
             cyclone_client_execution_timeout_secs: default_cyclone_client_execution_timeout_secs(),
             concurrency_limit: default_concurrency_limit(),
@@ -171,6 +184,7 @@ impl ConfigFile {
             nats: Default::default(),
             cyclone: CycloneConfig::default_local_uds(),
             crypto: Default::default(),
+            external_secrets: Default::default(),
             healthcheck_pool: default_healthcheck_pool(),
             cyclone_client_execution_timeout_secs: default_cyclone_client_execution_timeout_secs(),
             concurrency_limit: default_concurrency_limit(),
@@ -193,6 +207,7 @@ impl TryFrom<ConfigFile> for Config {
         config.nats(value.nats);
         config.cyclone_spec(value.cyclone.try_into()?);
         config.crypto(value.crypto);
+        config.external_secrets(value.external_secrets);
         config.cyclone_client_execution_timeout(Duration::from_secs(
             value.cyclone_client_execution_timeout_secs,
         ));
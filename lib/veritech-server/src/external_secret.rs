@@ -0,0 +1,415 @@
+use std::{collections::HashMap, env, io, path::PathBuf};
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use si_pool_noodle::{BeforeFunction, SensitiveStrings};
+use telemetry::prelude::*;
+use thiserror::Error;
+use tokio::fs;
+use veritech_core::{
+    find_external_secret_references, ExternalSecretReference, ExternalSecretReferenceError,
+};
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum ExternalSecretError {
+    #[error("environment variable not allowed by configuration: {0}")]
+    EnvVarNotAllowed(String),
+    #[error("environment variable not set: {0}")]
+    EnvVarNotSet(String),
+    #[error("file not allowed by configuration: {0}")]
+    FileNotAllowed(PathBuf),
+    #[error("http secret store error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("io error reading {0}: {1}")]
+    Io(PathBuf, #[source] io::Error),
+    #[error("invalid json pointer: {0}")]
+    InvalidJSONPointer(String),
+    #[error("invalid http secret store url {0}: {1}")]
+    InvalidStoreUrl(String, String),
+    #[error("local secret store error: {0}")]
+    LocalStore(#[source] serde_json::Error),
+    #[error("no external secret store configured")]
+    NoStoreConfigured,
+    #[error("external secret reference error: {0}")]
+    Reference(#[from] ExternalSecretReferenceError),
+    #[error("key not found in external secret store: {0}")]
+    StoreKeyNotFound(String),
+}
+
+type Result<T> = std::result::Result<T, ExternalSecretError>;
+
+/// Configures which external secret providers veritech may resolve references against. Every
+/// provider is disabled by default.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ExternalSecretsConfig {
+    /// Only environment variables starting with this prefix may be referenced.
+    #[serde(default)]
+    pub env_var_prefix: Option<String>,
+    /// Only files within these directories may be referenced.
+    #[serde(default)]
+    pub file_roots: Vec<PathBuf>,
+    /// The secret store that `store` references are resolved against.
+    #[serde(default)]
+    pub store: Option<ExternalSecretStoreConfig>,
+}
+
+#[remain::sorted]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ExternalSecretStoreConfig {
+    /// An HTTP secret store, where a key is fetched with `GET {base_url}/{key}` and the response
+    /// body is the value.
+    Http {
+        base_url: String,
+        /// The environment variable holding a bearer token for the store, if it needs one.
+        #[serde(default)]
+        token_env_var: Option<String>,
+    },
+    /// A local stand-in for the HTTP secret store: a JSON file mapping keys to values.
+    Local { path: PathBuf },
+}
+
+/// Resolves [`ExternalSecretReferences`](ExternalSecretReference) on the veritech host.
+#[derive(Clone, Debug)]
+pub struct ExternalSecretResolver {
+    env_var_prefix: Option<String>,
+    file_roots: Vec<PathBuf>,
+    store: Option<ExternalSecretStore>,
+}
+
+impl ExternalSecretResolver {
+    pub fn from_config(config: ExternalSecretsConfig) -> Result<Self> {
+        let store = match config.store {
+            Some(ExternalSecretStoreConfig::Http {
+                base_url,
+                token_env_var,
+            }) => {
+                let parsed = Url::parse(&base_url).map_err(|err| {
+                    ExternalSecretError::InvalidStoreUrl(base_url.to_owned(), err.to_string())
+                })?;
+                if parsed.cannot_be_a_base() {
+                    return Err(ExternalSecretError::InvalidStoreUrl(
+                        base_url,
+                        "url cannot be a base".to_owned(),
+                    ));
+                }
+                Some(ExternalSecretStore::Http {
+                    client: reqwest::Client::new(),
+                    base_url: parsed,
+                    token_env_var,
+                })
+            }
+            Some(ExternalSecretStoreConfig::Local { path }) => {
+                Some(ExternalSecretStore::Local { path })
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            env_var_prefix: config.env_var_prefix,
+            file_roots: config.file_roots,
+            store,
+        })
+    }
+
+    /// Replaces every external secret reference in the args of the before functions with its
+    /// resolved value, tracking the values as sensitive strings to be redacted.
+    pub async fn resolve_before_funcs(
+        &self,
+        before: &mut [BeforeFunction],
+        sensitive_strings: &mut SensitiveStrings,
+    ) -> Result<()> {
+        for func in before {
+            self.resolve_value_tree(&mut func.arg, sensitive_strings)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn resolve_value_tree(
+        &self,
+        value: &mut Value,
+        sensitive_strings: &mut SensitiveStrings,
+    ) -> Result<()> {
+        for (pointer, reference) in find_external_secret_references(value)? {
+            let resolved = self.resolve(&reference).await?;
+            sensitive_strings.insert(&resolved);
+
+            *value
+                .pointer_mut(&pointer)
+                .ok_or(ExternalSecretError::InvalidJSONPointer(pointer))? = Value::String(resolved);
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::disallowed_methods)] // Env references are resolved against the host environment
+    #[instrument(name = "veritech.external_secret.resolve", level = "debug", skip_all)]
+    async fn resolve(&self, reference: &ExternalSecretReference) -> Result<String> {
+        match reference {
+            ExternalSecretReference::Env(name) => {
+                match &self.env_var_prefix {
+                    Some(prefix) if name.starts_with(prefix.as_str()) => {}
+                    _ => return Err(ExternalSecretError::EnvVarNotAllowed(name.to_owned())),
+                }
+                env::var(name).map_err(|_| ExternalSecretError::EnvVarNotSet(name.to_owned()))
+            }
+            ExternalSecretReference::File(path) => {
+                // Canonicalize first so that neither ".." nor symlinks can escape the roots
+                let canonical = fs::canonicalize(path)
+                    .await
+                    .map_err(|err| ExternalSecretError::Io(path.to_owned(), err))?;
+                let mut allowed = false;
+                for root in &self.file_roots {
+                    if let Ok(root) = fs::canonicalize(root).await {
+                        if canonical.starts_with(root) {
+                            allowed = true;
+                            break;
+                        }
+                    }
+                }
+                if !allowed {
+                    return Err(ExternalSecretError::FileNotAllowed(path.to_owned()));
+                }
+
+                let contents = fs::read_to_string(&canonical)
+                    .await
+                    .map_err(|err| ExternalSecretError::Io(path.to_owned(), err))?;
+                Ok(trim_trailing_newline(contents))
+            }
+            ExternalSecretReference::Store(key) => match &self.store {
+                Some(store) => store.fetch(key).await,
+                None => Err(ExternalSecretError::NoStoreConfigured),
+            },
+        }
+    }
+}
+
+#[remain::sorted]
+#[derive(Clone, Debug)]
+enum ExternalSecretStore {
+    Http {
+        client: reqwest::Client,
+        base_url: Url,
+        token_env_var: Option<String>,
+    },
+    Local {
+        path: PathBuf,
+    },
+}
+
+impl ExternalSecretStore {
+    #[allow(clippy::disallowed_methods)] // The store token is configured as a host env var
+    async fn fetch(&self, key: &str) -> Result<String> {
+        match self {
+            Self::Http {
+                client,
+                base_url,
+                token_env_var,
+            } => {
+                // The key is pushed as a single path segment so that it gets percent-encoded and
+                // cannot walk to another path on the store
+                let mut url = base_url.clone();
+                url.path_segments_mut()
+                    .map_err(|_| {
+                        ExternalSecretError::InvalidStoreUrl(
+                            base_url.to_string(),
+                            "url cannot be a base".to_owned(),
+                        )
+                    })?
+                    .pop_if_empty()
+                    .push(key);
+
+                let mut request = client.get(url);
+                if let Some(token_env_var) = token_env_var {
+                    let token = env::var(token_env_var)
+                        .map_err(|_| ExternalSecretError::EnvVarNotSet(token_env_var.to_owned()))?;
+                    request = request.bearer_auth(token);
+                }
+
+                let response = request.send().await?;
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Err(ExternalSecretError::StoreKeyNotFound(key.to_owned()));
+                }
+                let body = response.error_for_status()?.text().await?;
+                Ok(trim_trailing_newline(body))
+            }
+            Self::Local { path } => {
+                let contents = fs::read(path)
+                    .await
+                    .map_err(|err| ExternalSecretError::Io(path.to_owned(), err))?;
+                let mut values: HashMap<String, String> =
+                    serde_json::from_slice(&contents).map_err(ExternalSecretError::LocalStore)?;
+                values
+                    .remove(key)
+                    .ok_or_else(|| ExternalSecretError::StoreKeyNotFound(key.to_owned()))
+            }
+        }
+    }
+}
+
+fn trim_trailing_newline(mut value: String) -> String {
+    while value.ends_with('\n') || value.ends_with('\r') {
+        value.pop();
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        sync::oneshot,
+    };
+    use ulid::Ulid;
+
+    use super::*;
+
+    fn resolver(config: ExternalSecretsConfig) -> ExternalSecretResolver {
+        ExternalSecretResolver::from_config(config).expect("could not build resolver")
+    }
+
+    #[tokio::test]
+    async fn resolves_env_vars_with_the_allowed_prefix() {
+        let name = format!("SI_TEST_EXTERNAL_SECRET_{}", Ulid::new());
+        env::set_var(&name, "hunter2");
+        let resolver = resolver(ExternalSecretsConfig {
+            env_var_prefix: Some("SI_TEST_EXTERNAL_SECRET_".to_owned()),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            "hunter2",
+            resolver
+                .resolve(&ExternalSecretReference::Env(name))
+                .await
+                .expect("could not resolve env var")
+        );
+        assert!(matches!(
+            resolver
+                .resolve(&ExternalSecretReference::Env("HOME".to_owned()))
+                .await,
+            Err(ExternalSecretError::EnvVarNotAllowed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn resolves_files_within_the_roots_only() {
+        let root = env::temp_dir().join(format!("si-external-secret-{}", Ulid::new()));
+        fs::create_dir_all(&root)
+            .await
+            .expect("could not create root");
+        let path = root.join("token");
+        fs::write(&path, "hunter2\n")
+            .await
+            .expect("could not write secret");
+        let resolver = resolver(ExternalSecretsConfig {
+            file_roots: vec![root.to_owned()],
+            ..Default::default()
+        });
+
+        assert_eq!(
+            "hunter2",
+            resolver
+                .resolve(&ExternalSecretReference::File(path))
+                .await
+                .expect("could not resolve file")
+        );
+        assert!(matches!(
+            resolver
+                .resolve(&ExternalSecretReference::File(root.join("..").join(".")))
+                .await,
+            Err(ExternalSecretError::FileNotAllowed(_))
+        ));
+
+        fs::remove_dir_all(&root)
+            .await
+            .expect("could not remove root");
+    }
+
+    #[tokio::test]
+    async fn resolves_keys_from_the_local_store() {
+        let path = env::temp_dir().join(format!("si-external-secret-{}.json", Ulid::new()));
+        fs::write(&path, json!({ "db/password": "hunter2" }).to_string())
+            .await
+            .expect("could not write store");
+        let resolver = resolver(ExternalSecretsConfig {
+            store: Some(ExternalSecretStoreConfig::Local { path: path.clone() }),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            "hunter2",
+            resolver
+                .resolve(&ExternalSecretReference::Store("db/password".to_owned()))
+                .await
+                .expect("could not resolve store key")
+        );
+        assert!(matches!(
+            resolver
+                .resolve(&ExternalSecretReference::Store("missing".to_owned()))
+                .await,
+            Err(ExternalSecretError::StoreKeyNotFound(_))
+        ));
+
+        fs::remove_file(&path)
+            .await
+            .expect("could not remove store");
+    }
+
+    #[tokio::test]
+    async fn http_store_escapes_the_key_into_a_single_path_segment() {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("could not bind listener");
+        let address = listener.local_addr().expect("could not get address");
+        let (request_line_tx, request_line_rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("could not accept");
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let read = stream.read(&mut buf).await.expect("could not read request");
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..read]);
+            }
+            let request = String::from_utf8_lossy(&request).into_owned();
+            let _ = request_line_tx.send(request.lines().next().unwrap_or_default().to_owned());
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\ncontent-length: 8\r\nconnection: close\r\n\r\nhunter2\n",
+                )
+                .await
+                .expect("could not write response");
+        });
+
+        let resolver = resolver(ExternalSecretsConfig {
+            store: Some(ExternalSecretStoreConfig::Http {
+                base_url: format!("http://{address}/secrets/"),
+                token_env_var: None,
+            }),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            "hunter2",
+            resolver
+                .resolve(&ExternalSecretReference::Store(
+                    "team/db password?x=1".to_owned()
+                ))
+                .await
+                .expect("could not resolve store key")
+        );
+        assert_eq!(
+            "GET /secrets/team%2Fdb%20password%3Fx=1 HTTP/1.1",
+            request_line_rx.await.expect("no request was made")
+        );
+    }
+}
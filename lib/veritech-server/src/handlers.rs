@@ -23,7 +23,12 @@ use veritech_core::{
     REPLY_INBOX_HEADER_NAME,
 };
 
-use crate::{app_state::AppState, request::DecryptRequest, Publisher, PublisherError};
+use crate::{
+    app_state::AppState,
+    external_secret::ExternalSecretError,
+    request::{BeforeFunctionsRequest, DecryptRequest},
+    Publisher, PublisherError,
+};
 
 pub use kill::process_kill_request;
 
@@ -38,6 +43,8 @@ pub enum HandlerError {
     CyclonePool(#[source] Box<dyn std::error::Error + Sync + Send + 'static>),
     #[error("cyclone timed out: {0:?}")]
     CycloneTimeout(Duration),
+    #[error("external secret error: {0}")]
+    ExternalSecret(#[from] ExternalSecretError),
     #[error("invalid incoming subject: {0}")]
     InvalidIncomingSubject(Subject),
     #[error("function execution killed: {0}")]
@@ -164,7 +171,13 @@ async fn dispatch_request<Request>(
     reply_mailbox: Subject,
 ) -> HandlerResult<()>
 where
-    Request: CycloneRequestable + DecryptRequest + Serialize + Clone + Send + Sync,
+    Request: CycloneRequestable
+        + DecryptRequest
+        + BeforeFunctionsRequest
+        + Serialize
+        + Clone
+        + Send
+        + Sync,
     Request::Response: Serialize + DeserializeOwned + std::fmt::Debug + std::marker::Unpin,
     HandlerError: From<ExecutionError<<Request as CycloneRequestable>::Response>>,
 {
@@ -181,6 +194,12 @@ where
     // Decrypt the relevant contents of the request and track any resulting sensitive strings
     // to be redacted
     request.decrypt(&mut sensitive_strings, &state.decryption_key)?;
+    // Resolve any references to external secrets now that the request is decrypted, tracking
+    // the resolved values to be redacted as well
+    state
+        .external_secrets
+        .resolve_before_funcs(request.before_functions_mut(), &mut sensitive_strings)
+        .await?;
 
     // NOTE(nick,fletcher): we need to create a owned client here because publisher has its own lifetime. Yeehaw.
     let nats_for_publisher = state.nats.clone();
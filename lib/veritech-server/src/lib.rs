@@ -1,5 +1,6 @@
 mod app_state;
 mod config;
+mod external_secret;
 mod handlers;
 mod publisher;
 mod request;
@@ -18,6 +19,7 @@ pub use crate::{
         detect_and_configure_development, Config, ConfigBuilder, ConfigError, ConfigFile,
         CycloneSpec, CycloneStream, StandardConfig, StandardConfigFile,
     },
+    external_secret::{ExternalSecretStoreConfig, ExternalSecretsConfig},
     server::Server,
 };
 
@@ -28,6 +30,8 @@ pub enum ServerError {
     CyclonePool(#[source] Box<dyn std::error::Error + Sync + Send + 'static>),
     #[error("cyclone spec setup error: {0}")]
     CycloneSetupError(#[source] Box<dyn std::error::Error + Sync + Send + 'static>),
+    #[error("external secrets configuration error: {0}")]
+    ExternalSecrets(#[from] external_secret::ExternalSecretError),
     #[error("jetstream consumer error: {0}")]
     JetStreamConsumer(#[from] async_nats::jetstream::stream::ConsumerError),
     #[error("jetstream consumer stream error: {0}")]
@@ -64,6 +64,42 @@ impl DecryptRequest for ManagementRequest {
     }
 }
 
+/// Requests whose before functions may carry external secret references to resolve.
+pub trait BeforeFunctionsRequest {
+    fn before_functions_mut(&mut self) -> &mut [BeforeFunction];
+}
+
+impl BeforeFunctionsRequest for ResolverFunctionRequest {
+    fn before_functions_mut(&mut self) -> &mut [BeforeFunction] {
+        &mut self.before
+    }
+}
+
+impl BeforeFunctionsRequest for ActionRunRequest {
+    fn before_functions_mut(&mut self) -> &mut [BeforeFunction] {
+        &mut self.before
+    }
+}
+
+impl BeforeFunctionsRequest for ValidationRequest {
+    fn before_functions_mut(&mut self) -> &mut [BeforeFunction] {
+        &mut self.before
+    }
+}
+
+impl BeforeFunctionsRequest for SchemaVariantDefinitionRequest {
+    fn before_functions_mut(&mut self) -> &mut [BeforeFunction] {
+        // No before funcs defined!
+        &mut []
+    }
+}
+
+impl BeforeFunctionsRequest for ManagementRequest {
+    fn before_functions_mut(&mut self) -> &mut [BeforeFunction] {
+        &mut self.before
+    }
+}
+
 fn decrypt_before_func_args(
     before: &mut Vec<BeforeFunction>,
     sensitive_strings: &mut SensitiveStrings,
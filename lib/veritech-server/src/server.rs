@@ -35,6 +35,7 @@ use veritech_core::{incoming_subject, veritech_work_queue, ExecutionId, GetNatsS
 use crate::{
     app_state::{AppState, KillAppState},
     config::CycloneSpec,
+    external_secret::ExternalSecretResolver,
     handlers, Config, ServerError, ServerResult,
 };
 
@@ -141,6 +142,9 @@ impl Server {
                     config.concurrency_limit(),
                     cyclone_pool,
                     Arc::new(decryption_key),
+                    Arc::new(ExternalSecretResolver::from_config(
+                        config.external_secrets().clone(),
+                    )?),
                     config.cyclone_client_execution_timeout(),
                     nats.clone(),
                     kill_senders.clone(),
@@ -186,6 +190,7 @@ impl Server {
         concurrency_limit: usize,
         cyclone_pool: PoolNoodle<LocalUdsInstance, LocalUdsInstanceSpec>,
         decryption_key: Arc<VeritechDecryptionKey>,
+        external_secrets: Arc<ExternalSecretResolver>,
         cyclone_client_execution_timeout: Duration,
         nats: NatsClient,
         kill_senders: Arc<Mutex<HashMap<ExecutionId, oneshot::Sender<()>>>>,
@@ -210,6 +215,7 @@ impl Server {
             metadata,
             cyclone_pool,
             decryption_key,
+            external_secrets,
             cyclone_client_execution_timeout,
             nats,
             kill_senders,